@@ -253,24 +253,20 @@ fn bench_key_folding_overhead(c: &mut Criterion) {
     let mut group = c.benchmark_group("key_folding");
 
     group.bench_function("without_folding", |b| {
-        let options = Some(EncodeOptions {
-            indent: None,
-            delimiter: None,
-            key_folding: Some(KeyFoldingMode::Off),
-            flatten_depth: None,
-            replacer: None,
-        });
+        let options = Some(
+            EncodeOptions::builder()
+                .key_folding(KeyFoldingMode::Off)
+                .build(),
+        );
         b.iter(|| encode(black_box(json.clone()), options.clone()));
     });
 
     group.bench_function("with_folding", |b| {
-        let options = Some(EncodeOptions {
-            indent: None,
-            delimiter: None,
-            key_folding: Some(KeyFoldingMode::Safe),
-            flatten_depth: None,
-            replacer: None,
-        });
+        let options = Some(
+            EncodeOptions::builder()
+                .key_folding(KeyFoldingMode::Safe)
+                .build(),
+        );
         b.iter(|| encode(black_box(json.clone()), options.clone()));
     });
 
@@ -313,13 +309,11 @@ fn bench_compression_ratio(c: &mut Criterion) {
     let nested = generate_foldable_structure(5, 10);
     let nested_json = serde_json::to_string(&nested).unwrap();
     let nested_toon_unfolded = encode(nested.clone(), None);
-    let options_folded = Some(EncodeOptions {
-        indent: None,
-        delimiter: None,
-        key_folding: Some(KeyFoldingMode::Safe),
-        flatten_depth: None,
-        replacer: None,
-    });
+    let options_folded = Some(
+        EncodeOptions::builder()
+            .key_folding(KeyFoldingMode::Safe)
+            .build(),
+    );
     let nested_toon_folded = encode(nested, options_folded);
 
     println!(
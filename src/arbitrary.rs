@@ -0,0 +1,167 @@
+//! `proptest` strategies for generating random documents (requires the
+//! `proptest` feature).
+//!
+//! ```ignore
+//! use proptest::prelude::*;
+//! use toon::JsonValue;
+//!
+//! proptest! {
+//!     #[test]
+//!     fn roundtrip(value in any::<JsonValue>()) {
+//!         let decoded = toon::decode(&toon::encode(value.clone(), None), None);
+//!         prop_assert!(value.semantic_eq(&decoded, Default::default()));
+//!     }
+//! }
+//! ```
+
+use std::collections::HashSet;
+
+use proptest::prelude::*;
+use proptest::strategy::BoxedStrategy;
+
+use crate::{JsonValue, StringOrNumberOrBoolOrNull};
+
+/// Strings that exercise quoting and type inference.
+const TRICKY_STRINGS: &[&str] = &[
+    "",
+    " ",
+    "true",
+    "false",
+    "null",
+    "007",
+    "1e5",
+    "-",
+    "- item",
+    "a,b",
+    "a|b",
+    "a;b",
+    "a: b",
+    "[1]",
+    "{x}",
+    "\"quoted\"",
+    "back\\slash",
+    "line\nbreak",
+    "#hash",
+];
+
+/// Shape of the documents produced by `any_with::<JsonValue>(params)`.
+///
+/// Scalar weights are relative; setting one to zero disables that kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JsonValueParams {
+    /// Maximum nesting depth of arrays and objects.
+    pub max_depth: u32,
+    /// Maximum number of items in each array or object.
+    pub max_width: usize,
+    pub string_weight: u32,
+    pub number_weight: u32,
+    pub bool_weight: u32,
+    pub null_weight: u32,
+    /// Relative weight of uniform arrays of objects, which encode as tables.
+    pub table_weight: u32,
+}
+
+impl Default for JsonValueParams {
+    fn default() -> Self {
+        Self {
+            max_depth: 4,
+            max_width: 6,
+            string_weight: 4,
+            number_weight: 3,
+            bool_weight: 1,
+            null_weight: 1,
+            table_weight: 1,
+        }
+    }
+}
+
+impl Arbitrary for JsonValue {
+    type Parameters = JsonValueParams;
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(params: Self::Parameters) -> Self::Strategy {
+        let scalar = scalar_strategy(params);
+        let width = params.max_width;
+        let table_leaf = scalar.clone();
+        let branch_size = u32::try_from(width.max(1)).unwrap_or(u32::MAX);
+        let desired_size = params.max_depth.saturating_mul(branch_size);
+
+        scalar
+            .prop_map(Self::Primitive)
+            .prop_recursive(params.max_depth, desired_size, branch_size, move |inner| {
+                prop_oneof![
+                    3 => prop::collection::vec(inner.clone(), 0..=width).prop_map(Self::Array),
+                    3 => prop::collection::vec((key_strategy(), inner), 0..=width)
+                        .prop_map(|entries| Self::Object(dedup_keys(entries))),
+                    params.table_weight => table_strategy(table_leaf.clone(), width),
+                ]
+            })
+            .boxed()
+    }
+}
+
+fn scalar_strategy(params: JsonValueParams) -> BoxedStrategy<StringOrNumberOrBoolOrNull> {
+    let number = prop_oneof![
+        any::<i32>().prop_map(f64::from),
+        any::<f64>().prop_filter("finite", |f| f.is_finite()),
+    ];
+    prop_oneof![
+        params.string_weight => string_strategy().prop_map(StringOrNumberOrBoolOrNull::String),
+        params.number_weight => number.prop_map(StringOrNumberOrBoolOrNull::from_f64),
+        params.bool_weight => any::<bool>().prop_map(StringOrNumberOrBoolOrNull::Bool),
+        params.null_weight => Just(StringOrNumberOrBoolOrNull::Null),
+    ]
+    .boxed()
+}
+
+fn string_strategy() -> BoxedStrategy<String> {
+    prop_oneof![
+        3 => "[a-zA-Z ]{0,12}",
+        1 => "\\PC{0,16}",
+        1 => prop::sample::select(TRICKY_STRINGS).prop_map(str::to_string),
+    ]
+    .boxed()
+}
+
+fn key_strategy() -> BoxedStrategy<String> {
+    prop_oneof![
+        3 => "[a-z_][a-z0-9_]{0,7}",
+        1 => "\\PC{0,8}",
+    ]
+    .boxed()
+}
+
+/// Arrays of objects sharing the same primitive-valued keys.
+fn table_strategy(
+    leaf: BoxedStrategy<StringOrNumberOrBoolOrNull>,
+    width: usize,
+) -> BoxedStrategy<JsonValue> {
+    prop::collection::hash_set(key_strategy(), 1..=width.max(1))
+        .prop_flat_map(move |keys| {
+            let keys: Vec<String> = keys.into_iter().collect();
+            let row = prop::collection::vec(leaf.clone(), keys.len());
+            prop::collection::vec(row, 1..=width.max(1)).prop_map(move |rows| {
+                JsonValue::Array(
+                    rows.into_iter()
+                        .map(|row| {
+                            JsonValue::Object(
+                                keys.iter()
+                                    .cloned()
+                                    .zip(row.into_iter().map(JsonValue::Primitive))
+                                    .collect(),
+                            )
+                        })
+                        .collect(),
+                )
+            })
+        })
+        .boxed()
+}
+
+fn dedup_keys(entries: Vec<(String, JsonValue)>) -> Vec<(String, JsonValue)> {
+    let mut seen = HashSet::new();
+    entries
+        .into_iter()
+        .filter(|(key, _)| seen.insert(key.clone()))
+        .collect()
+}
@@ -0,0 +1,78 @@
+//! Values that borrow keys and strings from the decoded input.
+//!
+//! [`decode_borrowed`](crate::decode_borrowed) copies only strings whose
+//! escapes must be rewritten; everything else points into the input.
+//!
+//! ```
+//! use std::borrow::Cow;
+//! use toon::{BorrowedPrimitive, BorrowedValue};
+//!
+//! let input = "name: Ada\nnote: \"tab\\there\"";
+//! let value = toon::decode_borrowed(input).unwrap();
+//! let BorrowedValue::Object(entries) = &value else { unreachable!() };
+//! assert!(matches!(entries[0].0, Cow::Borrowed("name")));
+//! assert!(matches!(
+//!     &entries[0].1,
+//!     BorrowedValue::Primitive(BorrowedPrimitive::String(Cow::Borrowed("Ada")))
+//! ));
+//! assert!(matches!(
+//!     &entries[1].1,
+//!     BorrowedValue::Primitive(BorrowedPrimitive::String(Cow::Owned(_)))
+//! ));
+//! assert_eq!(value.into_owned(), toon::decode(input, None));
+//! ```
+
+use std::borrow::Cow;
+
+use crate::{JsonValue, Number, StringOrNumberOrBoolOrNull};
+
+/// A primitive whose string, if any, may borrow from the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BorrowedPrimitive<'a> {
+    String(Cow<'a, str>),
+    Number(Number),
+    Bool(bool),
+    Null,
+}
+
+/// A [`JsonValue`] whose keys and strings may borrow from the input.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BorrowedValue<'a> {
+    Primitive(BorrowedPrimitive<'a>),
+    Array(Vec<Self>),
+    Object(Vec<(Cow<'a, str>, Self)>),
+}
+
+impl BorrowedValue<'_> {
+    /// Copy every borrowed key and string into an owned [`JsonValue`].
+    #[must_use]
+    pub fn into_owned(self) -> JsonValue {
+        JsonValue::from(self)
+    }
+}
+
+impl From<BorrowedPrimitive<'_>> for StringOrNumberOrBoolOrNull {
+    fn from(value: BorrowedPrimitive<'_>) -> Self {
+        match value {
+            BorrowedPrimitive::String(value) => Self::String(value.into_owned()),
+            BorrowedPrimitive::Number(value) => Self::Number(value),
+            BorrowedPrimitive::Bool(value) => Self::Bool(value),
+            BorrowedPrimitive::Null => Self::Null,
+        }
+    }
+}
+
+impl From<BorrowedValue<'_>> for JsonValue {
+    fn from(value: BorrowedValue<'_>) -> Self {
+        match value {
+            BorrowedValue::Primitive(value) => Self::Primitive(value.into()),
+            BorrowedValue::Array(items) => Self::Array(items.into_iter().map(Self::from).collect()),
+            BorrowedValue::Object(entries) => Self::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key.into_owned(), Self::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
@@ -0,0 +1,309 @@
+//! Incremental document construction.
+//!
+//! [`DocumentBuilder`] tracks a stack of open scopes (objects, arrays and
+//! tables) and rejects misuse as it happens — a row in an object, a key
+//! given twice, a row with the wrong number of cells — rather than leaving
+//! it to surface as a malformed event stream:
+//!
+//! ```
+//! use toon::DocumentBuilder;
+//!
+//! let mut doc = DocumentBuilder::new();
+//! doc.field("name", "nightly")?;
+//! doc.table("runs", ["id", "ok"])?;
+//! doc.row([1.into(), true.into()])?;
+//! doc.row([2.into(), false.into()])?;
+//! doc.end()?;
+//! assert_eq!(doc.build(None)?, "name: nightly\nruns[2]{id,ok}:\n  1,true\n  2,false");
+//! # Ok::<(), toon::error::ToonError>(())
+//! ```
+
+use std::io::Write;
+
+use crate::error::{Result, ToonError};
+use crate::{EncodeOptions, JsonValue, try_encode};
+
+#[derive(Debug)]
+enum Scope {
+    Object(Vec<(String, JsonValue)>),
+    Array(Vec<JsonValue>),
+    Table {
+        fields: Vec<String>,
+        rows: Vec<JsonValue>,
+    },
+}
+
+/// Builds a document from keys, nested scopes and table rows supplied over
+/// time. The root is an object; every scope opened must be closed with
+/// [`end`](Self::end) before [`finish`](Self::finish).
+#[derive(Debug)]
+pub struct DocumentBuilder {
+    /// Open scopes with the key (or `None` for array items) they close into.
+    stack: Vec<(Option<String>, Scope)>,
+}
+
+impl Default for DocumentBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DocumentBuilder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            stack: vec![(None, Scope::Object(Vec::new()))],
+        }
+    }
+
+    /// Number of scopes open below the root.
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.stack.len() - 1
+    }
+
+    /// Add `key: value` to the current object.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the current scope is not an object or already
+    /// has `key`.
+    pub fn field(&mut self, key: &str, value: impl Into<JsonValue>) -> Result<&mut Self> {
+        self.object_entries(key)?
+            .push((key.to_string(), value.into()));
+        Ok(self)
+    }
+
+    /// Open a nested object under `key` in the current object.
+    ///
+    /// # Errors
+    ///
+    /// As for [`field`](Self::field).
+    pub fn object(&mut self, key: &str) -> Result<&mut Self> {
+        self.object_entries(key)?;
+        self.stack
+            .push((Some(key.to_string()), Scope::Object(Vec::new())));
+        Ok(self)
+    }
+
+    /// Open an array under `key` in the current object.
+    ///
+    /// # Errors
+    ///
+    /// As for [`field`](Self::field).
+    pub fn array(&mut self, key: &str) -> Result<&mut Self> {
+        self.object_entries(key)?;
+        self.stack
+            .push((Some(key.to_string()), Scope::Array(Vec::new())));
+        Ok(self)
+    }
+
+    /// Open a table under `key` in the current object; fill it with
+    /// [`row`](Self::row).
+    ///
+    /// # Errors
+    ///
+    /// As for [`field`](Self::field), or if `fields` is empty or repeats a
+    /// column.
+    pub fn table<I, S>(&mut self, key: &str, fields: I) -> Result<&mut Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let fields: Vec<String> = fields.into_iter().map(Into::into).collect();
+        if fields.is_empty() {
+            return Err(ToonError::message(format!("table `{key}` has no columns")));
+        }
+        if let Some(repeated) = fields
+            .iter()
+            .enumerate()
+            .find_map(|(index, field)| fields[..index].contains(field).then_some(field))
+        {
+            return Err(ToonError::message(format!(
+                "table `{key}` repeats column `{repeated}`"
+            )));
+        }
+        self.object_entries(key)?;
+        self.stack.push((
+            Some(key.to_string()),
+            Scope::Table {
+                fields,
+                rows: Vec::new(),
+            },
+        ));
+        Ok(self)
+    }
+
+    /// Append a value to the current array.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the current scope is not an array.
+    pub fn item(&mut self, value: impl Into<JsonValue>) -> Result<&mut Self> {
+        self.array_items("item")?.push(value.into());
+        Ok(self)
+    }
+
+    /// Open an object as the next item of the current array.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the current scope is not an array.
+    pub fn object_item(&mut self) -> Result<&mut Self> {
+        self.array_items("object_item")?;
+        self.stack.push((None, Scope::Object(Vec::new())));
+        Ok(self)
+    }
+
+    /// Open an array as the next item of the current array.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the current scope is not an array.
+    pub fn array_item(&mut self) -> Result<&mut Self> {
+        self.array_items("array_item")?;
+        self.stack.push((None, Scope::Array(Vec::new())));
+        Ok(self)
+    }
+
+    /// Append a row of cells, in column order, to the current table.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the current scope is not a table, the cell count
+    /// differs from the column count, or a cell is an array or object.
+    pub fn row<I>(&mut self, cells: I) -> Result<&mut Self>
+    where
+        I: IntoIterator<Item = JsonValue>,
+    {
+        let key = self.current_key();
+        let Some((_, Scope::Table { fields, rows })) = self.stack.last_mut() else {
+            return Err(ToonError::message("row() called outside a table"));
+        };
+        let cells: Vec<JsonValue> = cells.into_iter().collect();
+        if cells.len() != fields.len() {
+            return Err(ToonError::message(format!(
+                "row {} of table `{key}` has {} cells, expected {}",
+                rows.len() + 1,
+                cells.len(),
+                fields.len()
+            )));
+        }
+        if let Some(index) = cells
+            .iter()
+            .position(|cell| !matches!(cell, JsonValue::Primitive(_)))
+        {
+            return Err(ToonError::message(format!(
+                "row {} of table `{key}`: cell `{}` must be a primitive",
+                rows.len() + 1,
+                fields[index]
+            )));
+        }
+        rows.push(JsonValue::Object(
+            fields.iter().cloned().zip(cells).collect(),
+        ));
+        Ok(self)
+    }
+
+    /// Close the innermost open scope.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if only the root is open.
+    pub fn end(&mut self) -> Result<&mut Self> {
+        if self.stack.len() == 1 {
+            return Err(ToonError::message("end() called with no open scope"));
+        }
+        let Some((key, scope)) = self.stack.pop() else {
+            unreachable!("the root scope is never popped");
+        };
+        let value = scope_into_value(scope);
+        match (key, self.stack.last_mut()) {
+            (Some(key), Some((_, Scope::Object(entries)))) => entries.push((key, value)),
+            (None, Some((_, Scope::Array(items)))) => items.push(value),
+            _ => unreachable!("scopes are only opened inside a matching parent"),
+        }
+        Ok(self)
+    }
+
+    /// The finished document.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the innermost scope still open.
+    pub fn finish(mut self) -> Result<JsonValue> {
+        if self.stack.len() > 1 {
+            return Err(ToonError::message(format!(
+                "scope `{}` was never closed",
+                self.current_key()
+            )));
+        }
+        let (_, root) = self.stack.remove(0);
+        Ok(scope_into_value(root))
+    }
+
+    /// Finish and encode the document with [`try_encode`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a scope is still open or encoding fails.
+    pub fn build(self, options: Option<EncodeOptions>) -> Result<String> {
+        try_encode(self.finish()?, options)
+    }
+
+    /// Finish and write the encoded document to `writer` line by line.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a scope is still open, encoding fails, or
+    /// writing fails.
+    pub fn write_to<W: Write>(self, writer: &mut W, options: Option<EncodeOptions>) -> Result<()> {
+        let encoded = self.build(options)?;
+        for (index, line) in encoded.split('\n').enumerate() {
+            if index > 0 {
+                writer.write_all(b"\n")?;
+            }
+            writer.write_all(line.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn object_entries(&mut self, key: &str) -> Result<&mut Vec<(String, JsonValue)>> {
+        let Some((_, Scope::Object(entries))) = self.stack.last_mut() else {
+            return Err(ToonError::message(format!(
+                "key `{key}` given outside an object"
+            )));
+        };
+        if entries.iter().any(|(existing, _)| existing == key) {
+            return Err(ToonError::message(format!("duplicate key `{key}`")));
+        }
+        Ok(entries)
+    }
+
+    fn array_items(&mut self, method: &str) -> Result<&mut Vec<JsonValue>> {
+        match self.stack.last_mut() {
+            Some((_, Scope::Array(items))) => Ok(items),
+            _ => Err(ToonError::message(format!(
+                "{method}() called outside an array"
+            ))),
+        }
+    }
+
+    /// Dotted path of the innermost scope, for error messages.
+    fn current_key(&self) -> String {
+        let keys: Vec<&str> = self
+            .stack
+            .iter()
+            .skip(1)
+            .map(|(key, _)| key.as_deref().unwrap_or("[]"))
+            .collect();
+        keys.join(".")
+    }
+}
+
+fn scope_into_value(scope: Scope) -> JsonValue {
+    match scope {
+        Scope::Object(entries) => JsonValue::Object(entries),
+        Scope::Array(items) | Scope::Table { rows: items, .. } => JsonValue::Array(items),
+    }
+}
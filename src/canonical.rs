@@ -0,0 +1,125 @@
+//! Normalization of decoded values ahead of canonical encoding or comparison.
+
+use std::collections::HashMap;
+
+use unicode_normalization::UnicodeNormalization;
+
+use crate::options::{EncodeOptions, Profile};
+use crate::{JsonValue, Number, StringOrNumberOrBoolOrNull};
+
+/// Steps applied by [`JsonValue::normalize`]. All are enabled by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct NormalizeOptions {
+    /// Sort object keys by byte order.
+    pub sort_keys: bool,
+    /// Keep one entry per key: the last value, at the first key's position,
+    /// as `JSON.parse` does.
+    pub dedup_keys: bool,
+    /// Convert keys to Unicode NFC, so `"é"` typed either way is one key.
+    /// Runs before deduplication.
+    pub nfc_keys: bool,
+    /// Replace `-0.0` with `0.0`.
+    pub fold_negative_zero: bool,
+}
+
+impl Default for NormalizeOptions {
+    fn default() -> Self {
+        Self {
+            sort_keys: true,
+            dedup_keys: true,
+            nfc_keys: true,
+            fold_negative_zero: true,
+        }
+    }
+}
+
+impl JsonValue {
+    /// Normalize this value in place, recursively.
+    pub fn normalize(&mut self, options: NormalizeOptions) {
+        match self {
+            Self::Primitive(StringOrNumberOrBoolOrNull::Number(Number::F64(number))) => {
+                if options.fold_negative_zero && *number == 0.0 {
+                    *number = 0.0;
+                }
+            }
+            Self::Primitive(_) => {}
+            Self::Array(items) => {
+                for item in items {
+                    item.normalize(options);
+                }
+            }
+            Self::Object(entries) => {
+                if options.nfc_keys {
+                    for (key, _) in entries.iter_mut() {
+                        if !unicode_normalization::is_nfc(key) {
+                            *key = key.nfc().collect();
+                        }
+                    }
+                }
+                if options.dedup_keys {
+                    dedup_keys_keep_last(entries);
+                }
+                if options.sort_keys {
+                    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                }
+                for (_, item) in entries {
+                    item.normalize(options);
+                }
+            }
+        }
+    }
+}
+
+/// Encode `input` so that semantically equal values give byte-identical
+/// output, for cache keys and signed payloads.
+///
+/// The value is [normalized](JsonValue::normalize) with every step on and
+/// numbers kept as source text are read as values, so `1.50` and `1.5`
+/// agree. It is then encoded with [`Profile::Canonical`]: sorted keys,
+/// two-space indent, comma delimiter, no key folding and every length
+/// written. Non-finite numbers are written as `null`.
+///
+/// ```
+/// let a = serde_json::json!({"b": [1.0, 2], "a": {"y": -0.0, "x": "s"}});
+/// let b = serde_json::json!({"a": {"x": "s", "y": 0}, "b": [1, 2.0]});
+/// assert_eq!(toon::encode_canonical(a), toon::encode_canonical(b));
+/// ```
+#[must_use]
+pub fn encode_canonical(input: impl Into<JsonValue>) -> String {
+    let mut value = input.into();
+    parse_raw_numbers(&mut value);
+    value.normalize(NormalizeOptions::default());
+    crate::encode(value, Some(EncodeOptions::profile(Profile::Canonical)))
+}
+
+fn parse_raw_numbers(value: &mut JsonValue) {
+    match value {
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(number)) => {
+            if let Number::Raw(text) = number {
+                *number = Number::parse_literal(text);
+            }
+        }
+        JsonValue::Primitive(_) => {}
+        JsonValue::Array(items) => items.iter_mut().for_each(parse_raw_numbers),
+        JsonValue::Object(entries) => {
+            for (_, item) in entries {
+                parse_raw_numbers(item);
+            }
+        }
+    }
+}
+
+fn dedup_keys_keep_last(entries: &mut Vec<(String, JsonValue)>) {
+    let mut positions: HashMap<String, usize> = HashMap::with_capacity(entries.len());
+    let mut deduped: Vec<(String, JsonValue)> = Vec::with_capacity(entries.len());
+    for (key, value) in entries.drain(..) {
+        if let Some(&index) = positions.get(&key) {
+            deduped[index].1 = value;
+        } else {
+            positions.insert(key.clone(), deduped.len());
+            deduped.push((key, value));
+        }
+    }
+    *entries = deduped;
+}
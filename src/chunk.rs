@@ -0,0 +1,203 @@
+//! Splitting documents into size-limited chunks and joining them back.
+//!
+//! [`split_document`] cuts along top-level keys, and along rows for arrays
+//! too large to fit a chunk on their own, so every chunk is a valid document
+//! that can be sent through a context-limited model separately.
+//! [`concat_documents`] merges the (possibly processed) chunks again:
+//!
+//! ```
+//! use toon::chunk::{ChunkLimit, concat_documents, split_document};
+//!
+//! let value = toon::decode("users[3]{id}:\n  1\n  2\n  3\nowner: ops", None);
+//! let chunks = split_document(&value, ChunkLimit::Bytes(24), None).unwrap();
+//! assert_eq!(chunks, ["users[2]{id}:\n  1\n  2", "users[1]{id}:\n  3", "owner: ops"]);
+//! assert_eq!(concat_documents(&chunks, None).unwrap(), value);
+//! ```
+
+use crate::error::{Result, ToonError};
+use crate::{DecodeOptions, EncodeOptions, JsonValue, try_decode, try_encode};
+
+/// Maximum size of one chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkLimit {
+    /// Encoded length in bytes.
+    Bytes(usize),
+    /// Estimated tokens, as computed by [`estimate_tokens`].
+    Tokens(usize),
+}
+
+impl ChunkLimit {
+    fn fits(self, text: &str) -> bool {
+        match self {
+            Self::Bytes(limit) => text.len() <= limit,
+            Self::Tokens(limit) => estimate_tokens(text) <= limit,
+        }
+    }
+}
+
+/// Simple token estimation heuristic (roughly 4 chars per token for English/code).
+/// This matches the behavior of tokenx used in the legacy CLI.
+#[must_use]
+pub fn estimate_tokens(text: &str) -> usize {
+    // Simple heuristic: count non-whitespace chars / 4, with minimum of word count
+    let char_estimate = text.chars().filter(|c| !c.is_whitespace()).count() / 4;
+    let word_estimate = text.split_whitespace().count();
+    char_estimate.max(word_estimate).max(1)
+}
+
+/// Encode `value` as a sequence of TOON documents, each within `limit`.
+///
+/// Top-level entries are packed greedily in order. An array that does not
+/// fit a chunk by itself is split between rows, repeating its key in each
+/// chunk; a root array is split between items. A single entry or row larger
+/// than the limit still gets a chunk of its own.
+///
+/// # Errors
+///
+/// Returns an error if encoding fails (see [`try_encode`]).
+pub fn split_document(
+    value: &JsonValue,
+    limit: ChunkLimit,
+    options: Option<EncodeOptions>,
+) -> Result<Vec<String>> {
+    let mut packer = Packer {
+        limit,
+        options,
+        root_array: matches!(value, JsonValue::Array(_)),
+        chunks: Vec::new(),
+        current: Vec::new(),
+    };
+    match value {
+        JsonValue::Array(items) => {
+            for item in items {
+                packer.push(String::new(), item.clone(), true)?;
+            }
+        }
+        JsonValue::Object(entries) => {
+            for (key, entry) in entries {
+                match entry {
+                    JsonValue::Array(items)
+                        if !items.is_empty()
+                            && !limit.fits(&packer.encode(vec![(key.clone(), entry.clone())])?) =>
+                    {
+                        for item in items {
+                            packer.push(key.clone(), item.clone(), true)?;
+                        }
+                    }
+                    _ => packer.push(key.clone(), entry.clone(), false)?,
+                }
+            }
+        }
+        JsonValue::Primitive(_) => return Ok(vec![try_encode(value.clone(), packer.options)?]),
+    }
+    packer.flush()?;
+    Ok(packer.chunks)
+}
+
+/// Decode `chunks` and merge them into one value, the inverse of
+/// [`split_document`].
+///
+/// Root arrays are concatenated. For objects, entries keep first-seen order
+/// and arrays under the same key are concatenated.
+///
+/// # Errors
+///
+/// Returns an error if a chunk fails to decode, chunks mix root kinds, or a
+/// non-array key appears in more than one chunk.
+pub fn concat_documents<S: AsRef<str>>(
+    chunks: &[S],
+    options: Option<DecodeOptions>,
+) -> Result<JsonValue> {
+    let decode = move |chunk: &str| try_decode(chunk, options.clone());
+    let mut merged: Option<JsonValue> = None;
+    for (index, chunk) in chunks.iter().enumerate() {
+        let value = decode(chunk.as_ref())?;
+        match (&mut merged, value) {
+            (None, value) => merged = Some(value),
+            (Some(JsonValue::Array(items)), JsonValue::Array(more)) => items.extend(more),
+            (Some(JsonValue::Object(entries)), JsonValue::Object(more)) => {
+                for (key, value) in more {
+                    match (entries.iter_mut().find(|(k, _)| *k == key), value) {
+                        (None, value) => entries.push((key, value)),
+                        (Some((_, JsonValue::Array(items))), JsonValue::Array(more)) => {
+                            items.extend(more);
+                        }
+                        (Some(_), _) => {
+                            return Err(ToonError::message(format!(
+                                "chunk {}: key `{key}` already appeared in an earlier chunk",
+                                index + 1
+                            )));
+                        }
+                    }
+                }
+            }
+            (Some(_), _) => {
+                return Err(ToonError::message(format!(
+                    "chunk {}: root kind differs from earlier chunks",
+                    index + 1
+                )));
+            }
+        }
+    }
+    Ok(merged.unwrap_or_else(|| JsonValue::Object(Vec::new())))
+}
+
+struct Packer {
+    limit: ChunkLimit,
+    options: Option<EncodeOptions>,
+    root_array: bool,
+    chunks: Vec<String>,
+    /// Entries of the chunk being filled; a root array uses one `""` entry.
+    current: Vec<(String, JsonValue)>,
+}
+
+impl Packer {
+    fn encode(&self, entries: Vec<(String, JsonValue)>) -> Result<String> {
+        let value = if self.root_array {
+            entries
+                .into_iter()
+                .next()
+                .map_or_else(|| JsonValue::Array(Vec::new()), |(_, value)| value)
+        } else {
+            JsonValue::Object(entries)
+        };
+        try_encode(value, self.options.clone())
+    }
+
+    /// Add an entry, or a row of the array under `key`, starting a new
+    /// chunk when the current one would overflow.
+    fn push(&mut self, key: String, value: JsonValue, row: bool) -> Result<()> {
+        let mut candidate = self.current.clone();
+        add(&mut candidate, key.clone(), value.clone(), row);
+        if self.current.is_empty() || self.limit.fits(&self.encode(candidate.clone())?) {
+            self.current = candidate;
+            return Ok(());
+        }
+        self.flush()?;
+        add(&mut self.current, key, value, row);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if !self.current.is_empty() {
+            let entries = std::mem::take(&mut self.current);
+            let chunk = self.encode(entries)?;
+            self.chunks.push(chunk);
+        }
+        Ok(())
+    }
+}
+
+fn add(entries: &mut Vec<(String, JsonValue)>, key: String, value: JsonValue, row: bool) {
+    if row {
+        if let Some((last_key, JsonValue::Array(items))) = entries.last_mut() {
+            if *last_key == key {
+                items.push(value);
+                return;
+            }
+        }
+        entries.push((key, JsonValue::Array(vec![value])));
+    } else {
+        entries.push((key, value));
+    }
+}
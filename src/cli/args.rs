@@ -1,6 +1,8 @@
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
+use crate::csv::CsvDialect;
+
 /// TOON CLI — Convert between JSON and TOON formats
 #[derive(Parser, Debug)]
 #[command(name = "toon", version, about, long_about = None)]
@@ -11,8 +13,19 @@ use std::path::PathBuf;
     toon input.json -o output.toon   # Encode to file
     cat data.json | toon --encode    # Encode from stdin
     cat data.toon | toon --decode    # Decode from stdin
-    toon input.json --stats          # Show token statistics")]
+    toon input.json --stats          # Show token statistics
+    toon input.toon --schema s.json  # Decode, failing if the data violates the schema
+    toon stats data.toon             # Per-section size report
+    toon explain data.json           # Annotate encoder decisions per line
+    toon fmt --sort-keys data.toon   # Reformat, keeping comments and blank lines
+    toon grep --key email data/      # Find values by key across .toon files
+    toon flatten data.json           # One `path: value` line per leaf
+    toon sample --schema s.json --rows 100 --seed 7  # Fake data for fixtures
+    toon repl --delimiter '|'        # Explore encodings interactively")]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Input file path (omit or use "-" to read from stdin)
     #[arg(value_name = "INPUT")]
     pub input: Option<PathBuf>,
@@ -29,58 +42,303 @@ pub struct Args {
     #[arg(short, long, conflicts_with = "encode")]
     pub decode: bool,
 
-    /// Delimiter for arrays: comma (,), tab (\t), or pipe (|)
-    #[arg(long, default_value = ",", value_parser = parse_delimiter)]
+    /// Delimiter for arrays: comma (,), tab (\t), pipe (|), or semicolon (;)
+    #[arg(long, global = true, default_value = ",", value_parser = parse_delimiter)]
     pub delimiter: char,
 
     /// Indentation size (spaces)
-    #[arg(long, default_value = "2", value_parser = clap::value_parser!(u8).range(0..=16))]
+    #[arg(long, global = true, default_value = "2", value_parser = clap::value_parser!(u8).range(0..=16))]
     pub indent: u8,
 
+    /// Indent encoded output with spaces or tabs (encode only); decode
+    /// tab-indented input with --no-strict
+    #[arg(long, global = true, value_enum, default_value = "spaces")]
+    pub indent_style: IndentStyleArg,
+
+    /// Line ending of encoded output (encode only)
+    #[arg(long, global = true, value_enum, default_value = "lf")]
+    pub line_ending: LineEndingArg,
+
     /// Disable strict mode for decoding (allows lenient parsing)
-    #[arg(long = "no-strict")]
+    #[arg(long = "no-strict", global = true)]
     pub no_strict: bool,
 
-    /// Key folding mode: off or safe
-    #[arg(long, value_enum, default_value = "off")]
+    /// Require indents to be exact multiples of --indent, even with --no-strict (decode only)
+    #[arg(long, global = true)]
+    pub exact_indent: bool,
+
+    /// Delimiter for the array at a dotted key path, e.g. `rows=|` (repeatable, encode only)
+    #[arg(long, global = true, value_name = "PATH=DELIM", value_parser = parse_delimiter_override)]
+    pub delimiter_at: Vec<(String, char)>,
+
+    /// Key folding mode: off, safe, or aggressive (quotes segments that need it)
+    #[arg(long, global = true, value_enum, default_value = "off")]
     pub key_folding: KeyFoldingArg,
 
     /// Maximum folded segment count when key folding is enabled
-    #[arg(long, value_name = "N")]
+    #[arg(long, global = true, value_name = "N")]
     pub flatten_depth: Option<usize>,
 
-    /// Path expansion mode: off or safe (decode only)
-    #[arg(long, value_enum, default_value = "off")]
+    /// Fold keys through arrays holding one single-key object, as `a[0].b` (encode only)
+    #[arg(long, global = true)]
+    pub fold_single_item_arrays: bool,
+
+    /// Which array headers carry their `[N]` length: all, tabular, or never (encode only)
+    #[arg(long, global = true, value_enum, default_value = "all")]
+    pub length_markers: LengthMarkersArg,
+
+    /// Strings quoted even when they could be bare: minimal, always-strings,
+    /// or always-keys-and-strings (encode only)
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value = "minimal",
+        value_name = "POLICY"
+    )]
+    pub quote_strings: QuoteStringsArg,
+
+    /// Fewest rows an array of objects needs to be written as a table (encode only)
+    #[arg(long, global = true, value_name = "N")]
+    pub tabular_min_rows: Option<usize>,
+
+    /// Most columns a table may have; wider arrays use the list form (encode only)
+    #[arg(long, global = true, value_name = "N")]
+    pub tabular_max_columns: Option<usize>,
+
+    /// Write arrays of objects as list items, never as tables (encode only)
+    #[arg(long = "no-tabular", global = true)]
+    pub no_tabular: bool,
+
+    /// Wrap inline arrays onto continuation lines past this many characters (encode only)
+    #[arg(long, global = true, value_name = "N")]
+    pub max_line_width: Option<usize>,
+
+    /// Path expansion mode: off, safe, indexed, or aggressive (decode only)
+    #[arg(long, global = true, value_enum, default_value = "off")]
     pub expand_paths: ExpandPathsArg,
 
+    /// Path expansion conflict policy: error, keep-first, overwrite, merge,
+    /// prefer-folded, or prefer-literal
+    /// (decode only; defaults to error in strict mode, merge otherwise)
+    #[arg(long, global = true, value_enum, value_name = "POLICY")]
+    pub on_conflict: Option<OnConflictArg>,
+
+    /// Object key order in the output: preserve or sorted
+    #[arg(long, global = true, value_enum, default_value = "preserve")]
+    pub key_order: KeyOrderArg,
+
+    /// Key repeated within one object: error, first-wins, last-wins, or
+    /// collect-into-array (decode only; defaults to keeping every entry)
+    #[arg(long, global = true, value_enum, value_name = "POLICY")]
+    pub duplicate_keys: Option<DuplicateKeysArg>,
+
+    /// Fail on array headers without a valid `[N]` length, e.g. `items[]:` (decode only)
+    #[arg(long, global = true)]
+    pub require_length_markers: bool,
+
+    /// Reject input lines longer than this many bytes (decode only)
+    #[arg(long, global = true, value_name = "BYTES")]
+    pub max_line_length: Option<usize>,
+
+    /// Warn about unquoted values whose type may be unintended, e.g. `1e5` or `007` (decode only)
+    #[arg(long)]
+    pub warn_ambiguous: bool,
+
+    /// Indentation of the emitted JSON, 0 for compact (decode only; defaults to --indent)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(0..=16))]
+    pub json_indent: Option<u8>,
+
+    /// Escape non-ASCII characters in the emitted JSON as \uXXXX (decode only)
+    #[arg(long)]
+    pub ascii: bool,
+
+    /// Output the array of values matching a `JSONPath` expression such as
+    /// `$.users[*].name` (decode only)
+    #[arg(long, value_name = "EXPR")]
+    pub query: Option<String>,
+
     /// Show token statistics (encode only)
     #[arg(long)]
     pub stats: bool,
+
+    /// Validate the data against a JSON Schema file and fail on violations
+    /// (requires the `schema` feature)
+    #[arg(long, value_name = "FILE")]
+    pub schema: Option<PathBuf>,
+
+    /// Field delimiter for `.csv` input and output
+    #[arg(long, default_value = ",", value_name = "DELIM", value_parser = parse_delimiter)]
+    pub csv_delimiter: char,
+
+    /// Quote character for `.csv` input and output
+    #[arg(long, default_value = "\"", value_name = "CHAR")]
+    pub csv_quote: char,
+
+    /// Treat the first CSV record as data; columns are named col1, col2, ...
+    #[arg(long)]
+    pub csv_no_header: bool,
+
+    /// Unquoted CSV field text that stands for null
+    #[arg(long, default_value = "", value_name = "TEXT")]
+    pub csv_null: String,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Show lines, bytes, table rows and estimated tokens per top-level key
+    Stats {
+        /// TOON file to analyze; `.json` input is encoded first (omit or use "-" for stdin)
+        #[arg(value_name = "INPUT")]
+        input: Option<PathBuf>,
+    },
+    /// Annotate each encoded line with its depth, array form, folded keys and quoting reasons
+    Explain {
+        /// JSON file to encode and explain; `.toon` input is decoded first (omit or use "-" for stdin)
+        #[arg(value_name = "INPUT")]
+        input: Option<PathBuf>,
+    },
+    /// Reformat a TOON document with --indent and --delimiter, keeping comments and blank lines
+    Fmt {
+        /// TOON file to format (omit or use "-" for stdin)
+        #[arg(value_name = "INPUT")]
+        input: Option<PathBuf>,
+        /// Sort the keys of each object
+        #[arg(long)]
+        sort_keys: bool,
+    },
+    /// Print the path and line of every value whose key or content matches
+    Grep {
+        /// Match values under keys containing this text
+        #[arg(long, required_unless_present = "value")]
+        key: Option<String>,
+        /// Match primitive values containing this text (strings without quotes)
+        #[arg(long)]
+        value: Option<String>,
+        /// Match case-insensitively
+        #[arg(short, long)]
+        ignore_case: bool,
+        /// TOON files, or directories searched recursively for `.toon` files (stdin if omitted)
+        #[arg(value_name = "PATH")]
+        paths: Vec<PathBuf>,
+    },
+    /// List every leaf as a `path: value` line, with paths like `users.0.email`
+    Flatten {
+        /// JSON or TOON document (omit or use "-" for stdin)
+        #[arg(value_name = "INPUT")]
+        input: Option<PathBuf>,
+    },
+    /// Rebuild a document from a `toon flatten` listing
+    Unflatten {
+        /// Flattened listing as TOON or JSON (omit or use "-" for stdin)
+        #[arg(value_name = "INPUT")]
+        input: Option<PathBuf>,
+    },
+    /// Generate fake data conforming to a schema, as TOON
+    Sample {
+        /// JSON Schema file, or a TOON schema when the extension is `.toon`
+        #[arg(long, value_name = "FILE")]
+        schema: PathBuf,
+        /// Number of records to generate
+        #[arg(long, default_value = "10", value_name = "N")]
+        rows: usize,
+        /// Seed for reproducible output (random if omitted)
+        #[arg(long, value_name = "N")]
+        seed: Option<u64>,
+    },
+    /// Convert JSON or TOON snippets interactively, keeping options set with `:set`
+    Repl,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
 pub enum KeyFoldingArg {
     Off,
     Safe,
+    Aggressive,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LengthMarkersArg {
+    All,
+    Tabular,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum IndentStyleArg {
+    Spaces,
+    Tabs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LineEndingArg {
+    Lf,
+    Crlf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum QuoteStringsArg {
+    Minimal,
+    AlwaysStrings,
+    AlwaysKeysAndStrings,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
 pub enum ExpandPathsArg {
     Off,
     Safe,
+    Indexed,
+    Aggressive,
 }
 
-fn parse_delimiter(s: &str) -> Result<char, String> {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OnConflictArg {
+    Error,
+    KeepFirst,
+    Overwrite,
+    Merge,
+    PreferFolded,
+    PreferLiteral,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DuplicateKeysArg {
+    Error,
+    FirstWins,
+    LastWins,
+    CollectIntoArray,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum KeyOrderArg {
+    Preserve,
+    Sorted,
+}
+
+pub(crate) fn parse_delimiter(s: &str) -> Result<char, String> {
     match s {
         "," | "comma" => Ok(','),
         "|" | "pipe" => Ok('|'),
         "\\t" | "\t" | "tab" => Ok('\t'),
+        ";" | "semicolon" => Ok(';'),
         _ => Err(format!(
-            "Invalid delimiter \"{s}\". Valid delimiters are: comma (,), tab (\\t), pipe (|)"
+            "Invalid delimiter \"{s}\". Valid delimiters are: comma (,), tab (\\t), pipe (|), semicolon (;)"
         )),
     }
 }
 
+fn parse_delimiter_override(s: &str) -> Result<(String, char), String> {
+    let (path, delimiter) = s
+        .rsplit_once('=')
+        .ok_or_else(|| format!("Invalid delimiter override \"{s}\". Expected PATH=DELIM"))?;
+    if path.is_empty() {
+        return Err(format!(
+            "Invalid delimiter override \"{s}\". Path must not be empty"
+        ));
+    }
+    Ok((path.to_string(), parse_delimiter(delimiter)?))
+}
+
 impl Args {
     /// Detect the operation mode based on flags and file extension.
     #[must_use]
@@ -97,7 +355,7 @@ impl Args {
         if let Some(ref path) = self.input {
             if let Some(ext) = path.extension() {
                 let ext = ext.to_string_lossy().to_lowercase();
-                if ext == "json" {
+                if ext == "json" || ext == "csv" {
                     return Mode::Encode;
                 }
                 if ext == "toon" {
@@ -110,6 +368,17 @@ impl Args {
         Mode::Encode
     }
 
+    /// CSV layout from the `--csv-*` flags.
+    #[must_use]
+    pub fn csv_dialect(&self) -> CsvDialect {
+        CsvDialect {
+            delimiter: self.csv_delimiter,
+            quote: self.csv_quote,
+            has_header: !self.csv_no_header,
+            null: self.csv_null.clone(),
+        }
+    }
+
     /// Returns true if reading from stdin.
     #[must_use]
     pub fn is_stdin(&self) -> bool {
@@ -133,23 +402,65 @@ mod tests {
         assert_eq!(parse_delimiter("|"), Ok('|'));
         assert_eq!(parse_delimiter("\\t"), Ok('\t'));
         assert_eq!(parse_delimiter("tab"), Ok('\t'));
+        assert_eq!(parse_delimiter(";"), Ok(';'));
+        assert_eq!(parse_delimiter("semicolon"), Ok(';'));
         assert!(parse_delimiter("invalid").is_err());
     }
 
+    #[test]
+    fn test_parse_delimiter_override() {
+        assert_eq!(
+            parse_delimiter_override("data.rows=|"),
+            Ok(("data.rows".to_string(), '|'))
+        );
+        assert_eq!(
+            parse_delimiter_override("a=b=semicolon"),
+            Ok(("a=b".to_string(), ';'))
+        );
+        assert!(parse_delimiter_override("rows").is_err());
+        assert!(parse_delimiter_override("=|").is_err());
+    }
+
     #[test]
     fn test_detect_mode_explicit_flags() {
         let args = Args {
+            command: None,
             input: None,
             output: None,
             encode: true,
             decode: false,
             delimiter: ',',
+            delimiter_at: Vec::new(),
             indent: 2,
             no_strict: false,
+            exact_indent: false,
             key_folding: KeyFoldingArg::Off,
             flatten_depth: None,
+            fold_single_item_arrays: false,
+            length_markers: LengthMarkersArg::All,
+            quote_strings: QuoteStringsArg::Minimal,
+            tabular_min_rows: None,
+            tabular_max_columns: None,
+            no_tabular: false,
+            max_line_width: None,
+            indent_style: IndentStyleArg::Spaces,
+            line_ending: LineEndingArg::Lf,
             expand_paths: ExpandPathsArg::Off,
+            on_conflict: None,
+            key_order: KeyOrderArg::Preserve,
+            duplicate_keys: None,
+            require_length_markers: false,
+            max_line_length: None,
+            warn_ambiguous: false,
+            json_indent: None,
+            ascii: false,
+            query: None,
             stats: false,
+            schema: None,
+            csv_delimiter: ',',
+            csv_quote: '"',
+            csv_no_header: false,
+            csv_null: String::new(),
         };
         assert_eq!(args.detect_mode(), Mode::Encode);
     }
@@ -157,17 +468,43 @@ mod tests {
     #[test]
     fn test_detect_mode_by_extension() {
         let args = Args {
+            command: None,
             input: Some(PathBuf::from("data.toon")),
             output: None,
             encode: false,
             decode: false,
             delimiter: ',',
+            delimiter_at: Vec::new(),
             indent: 2,
             no_strict: false,
+            exact_indent: false,
             key_folding: KeyFoldingArg::Off,
             flatten_depth: None,
+            fold_single_item_arrays: false,
+            length_markers: LengthMarkersArg::All,
+            quote_strings: QuoteStringsArg::Minimal,
+            tabular_min_rows: None,
+            tabular_max_columns: None,
+            no_tabular: false,
+            max_line_width: None,
+            indent_style: IndentStyleArg::Spaces,
+            line_ending: LineEndingArg::Lf,
             expand_paths: ExpandPathsArg::Off,
+            on_conflict: None,
+            key_order: KeyOrderArg::Preserve,
+            duplicate_keys: None,
+            require_length_markers: false,
+            max_line_length: None,
+            warn_ambiguous: false,
+            json_indent: None,
+            ascii: false,
+            query: None,
             stats: false,
+            schema: None,
+            csv_delimiter: ',',
+            csv_quote: '"',
+            csv_no_header: false,
+            csv_null: String::new(),
         };
         assert_eq!(args.detect_mode(), Mode::Decode);
     }
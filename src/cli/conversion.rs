@@ -1,11 +1,17 @@
 use crate::cli::json_stream::json_stream_from_events;
-use crate::cli::json_stringify::json_stringify_lines;
+use crate::cli::json_stringify::{
+    JsonFormat, escape_chunks_to_ascii, json_stringify_formatted, json_stringify_lines,
+};
 use crate::decode::decoders as decoder_impl;
-use crate::decode::event_builder::{build_node_from_events, node_to_json};
-use crate::decode::expand::expand_paths_safe;
-use crate::error::{Result, ToonError};
+use crate::decode::event_builder::{
+    build_node_from_events, node_to_json, resolve_duplicate_keys, sort_node_keys,
+};
+use crate::decode::expand::expand_paths;
+use crate::decode::warnings::DecodeWarning;
+use crate::error::Result;
 use crate::options::{
-    DecodeOptions, DecodeStreamOptions, EncodeOptions, ExpandPathsMode, resolve_decode_options,
+    DecodeOptions, DuplicateKeyPolicy, EncodeOptions, ExpandPathsMode, KeyOrder,
+    ResolvedDecodeOptions, resolve_decode_options,
 };
 use crate::{JsonValue, StringOrNumberOrBoolOrNull};
 
@@ -18,10 +24,10 @@ pub fn encode_to_toon_lines(
     input_json: &str,
     options: Option<EncodeOptions>,
 ) -> Result<Vec<String>> {
-    let value: serde_json::Value =
-        serde_json::from_str(input_json).map_err(|err| ToonError::json_parse(&err))?;
-    let converted = JsonValue::from(value);
-    Ok(crate::encode::encode_lines(converted, options))
+    Ok(crate::encode::encode_lines(
+        JsonValue::from_json(input_json)?,
+        options,
+    ))
 }
 
 /// Decode TOON input into JSON output chunks.
@@ -30,41 +36,103 @@ pub fn encode_to_toon_lines(
 ///
 /// Returns an error if decoding fails or strict validation errors occur.
 pub fn decode_to_json_chunks(input: &str, options: Option<DecodeOptions>) -> Result<Vec<String>> {
+    decode_to_json_chunks_inner(input, options, None, false).map(|(chunks, _)| chunks)
+}
+
+/// Decode TOON input into JSON output chunks laid out according to `format`.
+///
+/// # Errors
+///
+/// Returns an error if decoding fails or strict validation errors occur.
+pub fn decode_to_json_chunks_with_format(
+    input: &str,
+    options: Option<DecodeOptions>,
+    format: &JsonFormat,
+) -> Result<Vec<String>> {
+    decode_to_json_chunks_inner(input, options, Some(format), false).map(|(chunks, _)| chunks)
+}
+
+/// Decode TOON input into JSON output chunks laid out according to `format`,
+/// also collecting ambiguity warnings.
+///
+/// # Errors
+///
+/// Returns an error if decoding fails or strict validation errors occur.
+pub fn decode_to_json_chunks_with_warnings(
+    input: &str,
+    options: Option<DecodeOptions>,
+    format: &JsonFormat,
+) -> Result<(Vec<String>, Vec<DecodeWarning>)> {
+    decode_to_json_chunks_inner(input, options, Some(format), true)
+}
+
+/// Without an explicit `format`, JSON is indented like the TOON input.
+fn decode_to_json_chunks_inner(
+    input: &str,
+    options: Option<DecodeOptions>,
+    format: Option<&JsonFormat>,
+    collect_warnings: bool,
+) -> Result<(Vec<String>, Vec<DecodeWarning>)> {
     let resolved = resolve_decode_options(options);
+    let format = format.copied().unwrap_or_else(|| JsonFormat {
+        indent: resolved.indent,
+        ..JsonFormat::default()
+    });
+    let (events, warnings) = decode_events(input, &resolved, collect_warnings)?;
 
-    if resolved.expand_paths == ExpandPathsMode::Safe {
-        let value = decode_to_value(input, &resolved)?;
-        return Ok(json_stringify_lines(&value, resolved.indent));
+    if resolved.expand_paths != ExpandPathsMode::Off
+        || resolved.key_order == KeyOrder::Sorted
+        || resolved
+            .duplicate_keys
+            .is_some_and(|policy| policy != DuplicateKeyPolicy::Error)
+    {
+        let value = events_to_value(events, &resolved)?;
+        return Ok((json_stringify_formatted(&value, &format), warnings));
     }
 
-    let events = decode_events(input, resolved.indent, resolved.strict)?;
-    json_stream_from_events(events, resolved.indent)
+    let mut chunks = json_stream_from_events(events, format.indent)?;
+    if format.ascii {
+        escape_chunks_to_ascii(&mut chunks);
+    }
+    Ok((chunks, warnings))
 }
 
-fn decode_events(input: &str, indent: usize, strict: bool) -> Result<Vec<crate::JsonStreamEvent>> {
+fn decode_events(
+    input: &str,
+    options: &ResolvedDecodeOptions,
+    collect_warnings: bool,
+) -> Result<(Vec<crate::JsonStreamEvent>, Vec<DecodeWarning>)> {
     let lines = input
         .split('\n')
         .map(std::string::ToString::to_string)
         .collect::<Vec<_>>();
+    let stream_options = Some(options.stream_options());
 
-    decoder_impl::decode_stream_sync(
-        lines,
-        Some(DecodeStreamOptions {
-            indent: Some(indent),
-            strict: Some(strict),
-        }),
-    )
+    if collect_warnings {
+        decoder_impl::decode_stream_sync_with_warnings(lines, stream_options)
+    } else {
+        decoder_impl::decode_stream_sync(lines, stream_options).map(|events| (events, Vec::new()))
+    }
 }
 
-fn decode_to_value(
-    input: &str,
-    options: &crate::options::ResolvedDecodeOptions,
+fn events_to_value(
+    events: Vec<crate::JsonStreamEvent>,
+    options: &ResolvedDecodeOptions,
 ) -> Result<JsonValue> {
-    let events = decode_events(input, options.indent, options.strict)?;
     let mut node = build_node_from_events(events)?;
+    if let Some(policy) = options.duplicate_keys {
+        resolve_duplicate_keys(&mut node, policy);
+    }
+
+    node = expand_paths(
+        node,
+        options.expand_paths,
+        options.on_conflict,
+        options.limits.and_then(|limits| limits.max_array_length),
+    )?;
 
-    if options.expand_paths == ExpandPathsMode::Safe {
-        node = expand_paths_safe(node, options.strict)?;
+    if options.key_order == KeyOrder::Sorted {
+        sort_node_keys(&mut node);
     }
 
     Ok(node_to_json(node))
@@ -0,0 +1,209 @@
+//! Line-by-line annotation of encoder output behind `toon explain`.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::decode::parser::{parse_array_header_line, parse_key_token};
+use crate::error::Result;
+use crate::options::{EncodeOptions, LengthMarkers, resolve_encode_options};
+use crate::shared::constants::DOUBLE_QUOTE;
+use crate::shared::string_utils::{find_closing_quote, unescape_string};
+use crate::shared::validation::unquoted_rejection;
+use crate::{JsonValue, try_encode};
+
+/// Widest TOON column before annotations stop being aligned.
+const MAX_TEXT_WIDTH: usize = 48;
+
+/// One encoded line with notes on why it looks the way it does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExplainedLine {
+    pub line: usize,
+    pub depth: usize,
+    pub text: String,
+    pub notes: Vec<String>,
+}
+
+/// Encode `value` and annotate every output line with its depth and the
+/// encoder decisions visible on it: tables and other array forms, folded
+/// key chains and why each quoted string needed quotes.
+///
+/// # Errors
+///
+/// Returns an error if encoding fails (see [`try_encode`]).
+pub fn explain(value: &JsonValue, options: Option<EncodeOptions>) -> Result<Vec<ExplainedLine>> {
+    let resolved = resolve_encode_options(options.clone());
+    let encoded = try_encode(value.clone(), options.clone())?;
+    // Notes are read off a copy with every length written, so tables can be
+    // counted whatever `length_markers` says.
+    let marked = try_encode(
+        value.clone(),
+        Some(EncodeOptions {
+            length_markers: Some(LengthMarkers::AllArrays),
+            ..options.unwrap_or_default()
+        }),
+    )?;
+    let mut literal_keys = HashSet::new();
+    collect_keys(value, &mut literal_keys);
+
+    // Delimiter and rows still expected from the most recent table header.
+    let mut table: Option<(char, usize, usize)> = None;
+    let mut explained = Vec::new();
+    for (index, (text, marked_text)) in encoded.split('\n').zip(marked.split('\n')).enumerate() {
+        let indent = text.len() - text.trim_start_matches(' ').len();
+        let depth = indent.checked_div(resolved.indent).unwrap_or(0);
+        let content = &marked_text[indent..];
+        let mut notes = Vec::new();
+
+        if let Some((delimiter, row, rows)) = table.as_mut() {
+            *row += 1;
+            notes.push(format!("row {row} of {rows}"));
+            note_quoting(content, *delimiter, &mut notes);
+            if *row == *rows {
+                table = None;
+            }
+        } else {
+            let body = content.strip_prefix("- ").map_or(content, |item| {
+                notes.push("list item".to_string());
+                item
+            });
+            table = describe(body, resolved.delimiter, &literal_keys, &mut notes);
+        }
+
+        explained.push(ExplainedLine {
+            line: index + 1,
+            depth,
+            text: text.to_string(),
+            notes,
+        });
+    }
+    Ok(explained)
+}
+
+/// Note what a non-row line is; returns the table state if it opens one.
+fn describe(
+    body: &str,
+    delimiter: char,
+    literal_keys: &HashSet<&str>,
+    notes: &mut Vec<String>,
+) -> Option<(char, usize, usize)> {
+    if let Ok(Some(parsed)) = parse_array_header_line(body, delimiter) {
+        let header = parsed.header;
+        if let Some(key) = header.key.as_deref() {
+            note_folding(key, header.key_was_quoted, literal_keys, notes);
+        }
+        if header.delimiter != delimiter {
+            notes.push(format!("delimiter {:?}", header.delimiter));
+        }
+        let table = match (&header.fields, &parsed.inline_values) {
+            (Some(fields), _) => {
+                notes.push(format!(
+                    "table: {} rows x {} columns",
+                    header.length,
+                    fields.len()
+                ));
+                (header.length > 0).then_some((header.delimiter, 0, header.length))
+            }
+            (None, Some(_)) => {
+                notes.push(format!("inline array of {} primitives", header.length));
+                None
+            }
+            (None, None) if header.length == 0 => {
+                notes.push("empty array".to_string());
+                None
+            }
+            (None, None) => {
+                notes.push(format!(
+                    "list array of {} items (mixed or nested items rule out a table)",
+                    header.length
+                ));
+                None
+            }
+        };
+        note_quoting(body, header.delimiter, notes);
+        return table;
+    }
+
+    if let Ok((key, end, was_quoted)) = parse_key_token(body, 0) {
+        note_folding(&key, was_quoted, literal_keys, notes);
+        notes.push(if body[end..].trim().is_empty() {
+            "object".to_string()
+        } else {
+            "key-value".to_string()
+        });
+    } else {
+        notes.push("primitive".to_string());
+    }
+    note_quoting(body, delimiter, notes);
+    None
+}
+
+fn note_folding(
+    key: &str,
+    was_quoted: bool,
+    literal_keys: &HashSet<&str>,
+    notes: &mut Vec<String>,
+) {
+    if !was_quoted && key.contains('.') && !literal_keys.contains(key) {
+        notes.push(format!("folded key chain `{key}`"));
+    }
+}
+
+fn note_quoting(content: &str, delimiter: char, notes: &mut Vec<String>) {
+    let mut start = 0;
+    while let Some(offset) = content[start..].find(DOUBLE_QUOTE) {
+        let open = start + offset;
+        let Some(close) = find_closing_quote(content, open) else {
+            return;
+        };
+        let raw = &content[open + 1..close];
+        let value = unescape_string(raw).unwrap_or_else(|_| raw.to_string());
+        let reason = unquoted_rejection(&value, delimiter).unwrap_or("not a valid bare key");
+        notes.push(format!("quoted \"{raw}\": {reason}"));
+        start = close + 1;
+    }
+}
+
+fn collect_keys<'a>(value: &'a JsonValue, keys: &mut HashSet<&'a str>) {
+    match value {
+        JsonValue::Primitive(_) => {}
+        JsonValue::Array(items) => {
+            for item in items {
+                collect_keys(item, keys);
+            }
+        }
+        JsonValue::Object(entries) => {
+            for (key, item) in entries {
+                keys.insert(key);
+                collect_keys(item, keys);
+            }
+        }
+    }
+}
+
+/// Render lines as `line depth text  # notes`, aligning the notes.
+#[must_use]
+pub fn format_explain(lines: &[ExplainedLine]) -> String {
+    let width = lines
+        .iter()
+        .map(|line| line.text.chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(MAX_TEXT_WIDTH);
+    let mut out = String::new();
+    for line in lines {
+        let start = out.len();
+        let _ = write!(
+            out,
+            "{:>4} d{:<2} {:<width$}",
+            line.line, line.depth, line.text
+        );
+        if line.notes.is_empty() {
+            out.truncate(start + out[start..].trim_end().len());
+        } else {
+            let _ = write!(out, "  # {}", line.notes.join("; "));
+        }
+        out.push('\n');
+    }
+    out.pop();
+    out
+}
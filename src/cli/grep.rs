@@ -0,0 +1,253 @@
+//! Key/value search behind `toon grep`.
+//!
+//! Documents are searched event by event as a [`ToonParser`] produces them,
+//! without building a value tree, so memory stays proportional to the
+//! largest top-level entry or root-array item.
+
+use std::io::{self, Read};
+
+use crate::decode::ToonParser;
+use crate::encode::primitives::encode_primitive;
+use crate::error::{Result, ToonError};
+use crate::options::{DecodeStreamOptions, PathSegment, QuotePolicy};
+use crate::shared::constants::DEFAULT_DELIMITER;
+use crate::shared::string_utils::escape_string;
+use crate::shared::validation::is_identifier_segment;
+use crate::{JsonPrimitive, JsonStreamEvent, StringOrNumberOrBoolOrNull};
+
+/// What to look for; both patterns must match when both are given.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GrepQuery {
+    /// Substring of the key a value sits under.
+    pub key: Option<String>,
+    /// Substring of a primitive value (strings without quotes).
+    pub value: Option<String>,
+    pub ignore_case: bool,
+}
+
+/// One hit: where it is and what it holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrepMatch {
+    pub line: usize,
+    /// Dotted path such as `users[0].email`, or `(root)`.
+    pub path: String,
+    /// The value as TOON writes it, or `{...}` / `[...]` for containers.
+    pub value: String,
+}
+
+impl GrepQuery {
+    fn contains(&self, haystack: &str, needle: &str) -> bool {
+        if self.ignore_case {
+            haystack.to_lowercase().contains(&needle.to_lowercase())
+        } else {
+            haystack.contains(needle)
+        }
+    }
+
+    fn key_matches(&self, path: &[PathSegment]) -> bool {
+        self.key.as_deref().is_none_or(|pattern| {
+            matches!(path.last(), Some(PathSegment::Key(key)) if self.contains(key, pattern))
+        })
+    }
+}
+
+/// Open container and the next child index for arrays.
+enum Frame {
+    Object,
+    Array(usize),
+}
+
+/// Search one TOON document for values matching `query`, in document order.
+///
+/// A key-only query also reports objects and arrays under matching keys,
+/// at the line of their key.
+///
+/// # Errors
+///
+/// Returns an error if the document does not decode.
+pub fn grep(
+    input: &str,
+    query: &GrepQuery,
+    options: Option<DecodeStreamOptions>,
+) -> Result<Vec<GrepMatch>> {
+    let mut matches = Vec::new();
+    grep_reader(input.as_bytes(), query, options, |found| {
+        matches.push(found);
+        Ok(())
+    })?;
+    Ok(matches)
+}
+
+/// Search a document read from `reader` like [`grep`], streaming matches.
+///
+/// Each match goes to `on_match` as soon as the top-level entry or root-array
+/// item holding it has been decoded (see [`ToonParser`]). Neither the input
+/// nor the matches are held, so large files search in bounded memory.
+///
+/// # Errors
+///
+/// Returns an error if reading fails, the document does not decode, or
+/// `on_match` fails. Matches before the error have already been handed on.
+pub fn grep_reader(
+    mut reader: impl Read,
+    query: &GrepQuery,
+    options: Option<DecodeStreamOptions>,
+    mut on_match: impl FnMut(GrepMatch) -> Result<()>,
+) -> Result<()> {
+    let mut parser = ToonParser::new(options);
+    let mut search = Search {
+        query,
+        frames: Vec::new(),
+        path: Vec::new(),
+        pending_key: None,
+    };
+    let mut buffer = vec![0; CHUNK_SIZE];
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(ToonError::io("Failed to read input", None, err)),
+        };
+        parser.feed(&buffer[..read])?;
+        search.drain(&mut parser, &mut on_match)?;
+    }
+    parser.finish()?;
+    search.drain(&mut parser, &mut on_match)
+}
+
+/// Bytes read per [`ToonParser::feed`].
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Where the search is in the document.
+struct Search<'a> {
+    query: &'a GrepQuery,
+    frames: Vec<Frame>,
+    path: Vec<PathSegment>,
+    pending_key: Option<String>,
+}
+
+impl Search<'_> {
+    fn drain(
+        &mut self,
+        parser: &mut ToonParser,
+        on_match: &mut impl FnMut(GrepMatch) -> Result<()>,
+    ) -> Result<()> {
+        while let Some((line, event)) = parser.next_event_with_line() {
+            if let Some(found) = self.event(line, event) {
+                on_match(found)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn event(&mut self, line: usize, event: JsonStreamEvent) -> Option<GrepMatch> {
+        let Self {
+            query,
+            frames,
+            path,
+            pending_key,
+        } = self;
+        match event {
+            JsonStreamEvent::Key { key, .. } => *pending_key = Some(key),
+            JsonStreamEvent::StartObject | JsonStreamEvent::StartArray { .. } => {
+                let object = matches!(event, JsonStreamEvent::StartObject);
+                enter(frames, path, pending_key);
+                frames.push(if object {
+                    Frame::Object
+                } else {
+                    Frame::Array(0)
+                });
+                if query.value.is_none() && query.key.is_some() && query.key_matches(path) {
+                    return Some(GrepMatch {
+                        line,
+                        path: format_path(path),
+                        value: if object { "{...}" } else { "[...]" }.to_string(),
+                    });
+                }
+            }
+            JsonStreamEvent::EndObject | JsonStreamEvent::EndArray => {
+                frames.pop();
+                // The root container has no segment of its own.
+                if !frames.is_empty() {
+                    path.pop();
+                }
+            }
+            JsonStreamEvent::Primitive { value } => {
+                let entered = enter(frames, path, pending_key);
+                let found = (query.key_matches(path)
+                    && query
+                        .value
+                        .as_deref()
+                        .is_none_or(|pattern| query.contains(&primitive_text(&value), pattern)))
+                .then(|| GrepMatch {
+                    line,
+                    path: format_path(path),
+                    value: encode_primitive(&value, DEFAULT_DELIMITER, QuotePolicy::Minimal),
+                });
+                if entered {
+                    path.pop();
+                }
+                return found;
+            }
+        }
+        None
+    }
+}
+
+/// Push the path segment for a value starting in the current container;
+/// returns `false` at the root.
+fn enter(
+    frames: &mut [Frame],
+    path: &mut Vec<PathSegment>,
+    pending_key: &mut Option<String>,
+) -> bool {
+    match frames.last_mut() {
+        Some(Frame::Object) => {
+            path.push(PathSegment::Key(pending_key.take().unwrap_or_default()));
+            true
+        }
+        Some(Frame::Array(next)) => {
+            path.push(PathSegment::Index(*next));
+            *next += 1;
+            true
+        }
+        None => false,
+    }
+}
+
+fn primitive_text(value: &JsonPrimitive) -> String {
+    match value {
+        StringOrNumberOrBoolOrNull::String(text) => text.clone(),
+        other => encode_primitive(other, DEFAULT_DELIMITER, QuotePolicy::Minimal),
+    }
+}
+
+fn format_path(path: &[PathSegment]) -> String {
+    let mut out = String::new();
+    for segment in path {
+        match segment {
+            PathSegment::Index(index) => {
+                out.push('[');
+                out.push_str(&index.to_string());
+                out.push(']');
+            }
+            PathSegment::Key(key) => {
+                if !out.is_empty() {
+                    out.push('.');
+                }
+                if is_identifier_segment(key) {
+                    out.push_str(key);
+                } else {
+                    out.push('"');
+                    out.push_str(&escape_string(key));
+                    out.push('"');
+                }
+            }
+        }
+    }
+    if out.is_empty() {
+        out.push_str("(root)");
+    }
+    out
+}
@@ -13,6 +13,56 @@ pub fn json_stringify_lines(value: &JsonValue, indent: usize) -> Vec<String> {
     vec![buf]
 }
 
+/// Layout of JSON produced from decoded TOON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JsonFormat {
+    /// Spaces per nesting level; `0` emits compact JSON. Defaults to 2.
+    pub indent: usize,
+    /// Escape every non-ASCII character as `\uXXXX` (surrogate pairs above
+    /// U+FFFF). Defaults to `false`.
+    pub ascii: bool,
+}
+
+impl Default for JsonFormat {
+    fn default() -> Self {
+        Self {
+            indent: 2,
+            ascii: false,
+        }
+    }
+}
+
+/// Stringify `value` laid out according to `format`.
+#[must_use]
+pub fn json_stringify_formatted(value: &JsonValue, format: &JsonFormat) -> Vec<String> {
+    let mut chunks = json_stringify_lines(value, format.indent);
+    if format.ascii {
+        escape_chunks_to_ascii(&mut chunks);
+    }
+    chunks
+}
+
+/// Replace non-ASCII characters in JSON text chunks with `\uXXXX` escapes.
+///
+/// Outside strings JSON is pure ASCII, so escaping the whole text only
+/// touches string contents.
+pub fn escape_chunks_to_ascii(chunks: &mut [String]) {
+    for chunk in chunks.iter_mut().filter(|chunk| !chunk.is_ascii()) {
+        let mut escaped = String::with_capacity(chunk.len() + 16);
+        for c in chunk.chars() {
+            if c.is_ascii() {
+                escaped.push(c);
+            } else {
+                let mut units = [0u16; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    let _ = write!(escaped, "\\u{unit:04x}");
+                }
+            }
+        }
+        *chunk = escaped;
+    }
+}
+
 /// Estimate the JSON output size for pre-allocation
 fn estimate_json_size(value: &JsonValue, indent: usize) -> usize {
     match value {
@@ -125,8 +175,8 @@ fn stringify_primitive_to_buf(value: &crate::JsonPrimitive, buf: &mut String) {
         crate::StringOrNumberOrBoolOrNull::Bool(true) => buf.push_str("true"),
         crate::StringOrNumberOrBoolOrNull::Bool(false) => buf.push_str("false"),
         crate::StringOrNumberOrBoolOrNull::Number(n) => {
-            if let Some(num) = serde_json::Number::from_f64(*n) {
-                buf.push_str(&num.to_string());
+            if let Some(text) = n.to_json_text() {
+                buf.push_str(&text);
             } else {
                 buf.push_str("null");
             }
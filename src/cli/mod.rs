@@ -1,15 +1,29 @@
 pub mod args;
 pub mod conversion;
+pub mod explain;
+pub mod grep;
 pub mod json_stream;
 pub mod json_stringify;
+pub mod repl;
+pub mod stats;
 
+use crate::JsonValue;
+use crate::chunk::estimate_tokens;
 use crate::error::{Result, ToonError};
-use crate::options::{DecodeOptions, EncodeOptions, ExpandPathsMode, KeyFoldingMode};
-use args::{Args, ExpandPathsArg, KeyFoldingArg, Mode};
+use crate::options::{
+    DecodeOptions, DuplicateKeyPolicy, EncodeOptions, ExpandConflictPolicy, ExpandPathsMode,
+    IndentStyle, KeyFoldingMode, KeyOrder, LengthMarkers, LineEnding, QuotePolicy, Strictness,
+    resolve_decode_options,
+};
+use args::{
+    Args, Command, DuplicateKeysArg, ExpandPathsArg, IndentStyleArg, KeyFoldingArg, KeyOrderArg,
+    LengthMarkersArg, LineEndingArg, Mode, OnConflictArg, QuoteStringsArg,
+};
 use clap::Parser;
+use json_stringify::JsonFormat;
 use std::fs::File;
 use std::io::{self, BufWriter, Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Runs the CLI entrypoint.
 ///
@@ -18,6 +32,9 @@ use std::path::Path;
 /// Returns an error if parsing, encoding, decoding, or I/O fails.
 pub fn run() -> Result<()> {
     let args = Args::parse();
+    if let Some(command) = &args.command {
+        return run_command(&args, command);
+    }
     let mode = args.detect_mode();
 
     match mode {
@@ -26,28 +43,277 @@ pub fn run() -> Result<()> {
     }
 }
 
-fn run_encode(args: &Args) -> Result<()> {
-    // Read input (JSON)
-    let input = read_input(args)?;
+fn run_command(args: &Args, command: &Command) -> Result<()> {
+    match command {
+        Command::Stats { input } => run_stats(input.as_deref()),
+        Command::Explain { input } => run_explain(args, input.as_deref()),
+        Command::Fmt { input, sort_keys } => {
+            let options = crate::FormatOptions {
+                indent: usize::from(args.indent),
+                delimiter: Some(args.delimiter),
+                sort_keys: *sort_keys,
+            };
+            let formatted = crate::format(&read_path_or_stdin(input.as_deref())?, options)?;
+            write_to_stdout(
+                formatted
+                    .strip_suffix('\n')
+                    .unwrap_or(&formatted)
+                    .as_bytes(),
+            )
+        }
+        Command::Grep {
+            key,
+            value,
+            ignore_case,
+            paths,
+        } => {
+            let query = grep::GrepQuery {
+                key: key.clone(),
+                value: value.clone(),
+                ignore_case: *ignore_case,
+            };
+            run_grep(args, &query, paths)
+        }
+        Command::Flatten { input } => {
+            let value = read_document(args, input.as_deref())?;
+            write_to_stdout(
+                crate::try_encode(value.flatten(), Some(encode_options(args)))?.as_bytes(),
+            )
+        }
+        Command::Unflatten { input } => {
+            let value = read_document(args, input.as_deref())?.unflatten()?;
+            write_to_stdout(crate::try_encode(value, Some(encode_options(args)))?.as_bytes())
+        }
+        Command::Sample { schema, rows, seed } => run_sample(args, schema, *rows, *seed),
+        Command::Repl => run_repl(args),
+    }
+}
+
+fn run_stats(input: Option<&Path>) -> Result<()> {
+    let mut text = read_path_or_stdin(input)?;
+    if input.is_some_and(|path| path.extension().is_some_and(|ext| ext == "json")) {
+        text = conversion::encode_to_toon_lines(&text, None)?.join("\n");
+    }
+    let sections = stats::section_stats(&text)?;
+    let report = stats::format_stats(&sections);
+    write_to_stdout(report.as_bytes())
+}
+
+fn run_explain(args: &Args, input: Option<&Path>) -> Result<()> {
+    let text = read_path_or_stdin(input)?;
+    let value = if input.is_some_and(|path| path.extension().is_some_and(|ext| ext == "toon")) {
+        crate::try_decode(&text, None)?
+    } else {
+        JsonValue::from_json(&text)?
+    };
+    let lines = explain::explain(&value, Some(encode_options(args)))?;
+    write_to_stdout(explain::format_explain(&lines).as_bytes())
+}
+
+fn run_grep(args: &Args, query: &grep::GrepQuery, paths: &[PathBuf]) -> Result<()> {
+    let options = resolve_decode_options(Some(decode_options(args))).stream_options();
+    // Matches are written as they are found, one per line.
+    let mut out = BufWriter::new(io::stdout().lock());
+    if paths.is_empty() || paths.iter().any(|path| path.as_os_str() == "-") {
+        grep::grep_reader(io::stdin().lock(), query, Some(options.clone()), |found| {
+            writeln!(out, "{}: {} = {}", found.line, found.path, found.value)
+                .map_err(ToonError::stdout_write)
+        })?;
+    }
+
+    let mut files = Vec::new();
+    for path in paths.iter().filter(|path| path.as_os_str() != "-") {
+        if path.is_dir() {
+            collect_toon_files(path, &mut files)?;
+        } else {
+            files.push(path.clone());
+        }
+    }
+    for file in &files {
+        let reader = File::open(file).map_err(|e| ToonError::file_read(file.clone(), e))?;
+        grep::grep_reader(reader, query, Some(options.clone()), |found| {
+            writeln!(
+                out,
+                "{}:{}: {} = {}",
+                file.display(),
+                found.line,
+                found.path,
+                found.value
+            )
+            .map_err(ToonError::stdout_write)
+        })
+        .map_err(|err| ToonError::message(format!("{}: {err}", file.display())))?;
+    }
+    out.flush().map_err(ToonError::stdout_write)
+}
 
-    // Build encode options
-    let options = EncodeOptions {
+/// Add every `.toon` file under `dir`, recursively and in name order.
+fn collect_toon_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let read_error = |err| ToonError::file_read(dir.to_path_buf(), err);
+    let mut entries = std::fs::read_dir(dir)
+        .map_err(read_error)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()
+        .map_err(read_error)?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            collect_toon_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "toon") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn run_sample(args: &Args, schema_path: &Path, rows: usize, seed: Option<u64>) -> Result<()> {
+    let text = read_file(schema_path)?;
+    let schema = if schema_path.extension().is_some_and(|ext| ext == "toon") {
+        text.parse::<crate::schema::ToonSchema>()?.to_json_schema()
+    } else {
+        let json: serde_json::Value =
+            serde_json::from_str(&text).map_err(|err| ToonError::json_parse(&err))?;
+        JsonValue::from(json)
+    };
+    let seed = seed.unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| {
+                elapsed.as_secs() ^ u64::from(elapsed.subsec_nanos())
+            })
+    });
+    let value = crate::schema::sample(&schema, rows, seed)?;
+    write_to_stdout(crate::try_encode(value, Some(encode_options(args)))?.as_bytes())
+}
+
+/// Read JSON, or TOON when the input is not valid JSON.
+fn read_document(args: &Args, input: Option<&Path>) -> Result<JsonValue> {
+    let text = read_path_or_stdin(input)?;
+    JsonValue::from_json(&text).or_else(|_| crate::try_decode(&text, Some(decode_options(args))))
+}
+
+fn run_repl(args: &Args) -> Result<()> {
+    let mut session = repl::Session::new(encode_options(args), decode_options(args));
+    repl::run(&mut session, io::stdin().lock(), &mut io::stdout().lock())
+        .map_err(ToonError::stdout_write)
+}
+
+fn encode_options(args: &Args) -> EncodeOptions {
+    EncodeOptions {
         indent: Some(usize::from(args.indent)),
         delimiter: Some(args.delimiter),
         key_folding: Some(match args.key_folding {
             KeyFoldingArg::Off => KeyFoldingMode::Off,
             KeyFoldingArg::Safe => KeyFoldingMode::Safe,
+            KeyFoldingArg::Aggressive => KeyFoldingMode::Aggressive,
         }),
         flatten_depth: args.flatten_depth,
         replacer: None,
+        delimiter_overrides: Some(args.delimiter_at.clone()),
+        max_depth: None,
+        on_non_finite: None,
+        length_markers: Some(match args.length_markers {
+            LengthMarkersArg::All => LengthMarkers::AllArrays,
+            LengthMarkersArg::Tabular => LengthMarkers::TabularOnly,
+            LengthMarkersArg::Never => LengthMarkers::Never,
+        }),
+        metrics: None,
+        sort_keys: Some(args.key_order == KeyOrderArg::Sorted),
+        key_comparator: None,
+        quote_strings: Some(match args.quote_strings {
+            QuoteStringsArg::Minimal => QuotePolicy::Minimal,
+            QuoteStringsArg::AlwaysStrings => QuotePolicy::AlwaysStrings,
+            QuoteStringsArg::AlwaysKeysAndStrings => QuotePolicy::AlwaysKeysAndStrings,
+        }),
+        tabular_min_rows: args.tabular_min_rows,
+        tabular_max_columns: args.tabular_max_columns,
+        tabular: Some(!args.no_tabular),
+        max_line_width: args.max_line_width,
+        indent_style: Some(match args.indent_style {
+            IndentStyleArg::Spaces => IndentStyle::Spaces,
+            IndentStyleArg::Tabs => IndentStyle::Tabs,
+        }),
+        line_ending: Some(line_ending(args)),
+        fold_single_item_arrays: Some(args.fold_single_item_arrays),
+    }
+}
+
+const fn line_ending(args: &Args) -> LineEnding {
+    match args.line_ending {
+        LineEndingArg::Lf => LineEnding::Lf,
+        LineEndingArg::Crlf => LineEnding::CrLf,
+    }
+}
+
+fn decode_options(args: &Args) -> DecodeOptions {
+    let strictness = if args.no_strict {
+        Strictness::lenient()
+    } else {
+        Strictness::default()
     };
+    DecodeOptions {
+        indent: Some(usize::from(args.indent)),
+        strictness: Some(Strictness {
+            indentation: strictness.indentation || args.exact_indent,
+            ..strictness
+        }),
+        expand_paths: Some(match args.expand_paths {
+            ExpandPathsArg::Off => ExpandPathsMode::Off,
+            ExpandPathsArg::Safe => ExpandPathsMode::Safe,
+            ExpandPathsArg::Indexed => ExpandPathsMode::Indexed,
+            ExpandPathsArg::Aggressive => ExpandPathsMode::Aggressive,
+        }),
+        on_conflict: args
+            .on_conflict
+            .map(|policy| match policy {
+                OnConflictArg::Error => ExpandConflictPolicy::Error,
+                OnConflictArg::KeepFirst => ExpandConflictPolicy::KeepFirst,
+                OnConflictArg::Overwrite => ExpandConflictPolicy::Overwrite,
+                OnConflictArg::Merge => ExpandConflictPolicy::Merge,
+                OnConflictArg::PreferFolded => ExpandConflictPolicy::PreferFolded,
+                OnConflictArg::PreferLiteral => ExpandConflictPolicy::PreferLiteral,
+            })
+            .or_else(|| args.no_strict.then_some(ExpandConflictPolicy::Merge)),
+        key_order: Some(match args.key_order {
+            KeyOrderArg::Preserve => KeyOrder::Preserve,
+            KeyOrderArg::Sorted => KeyOrder::Sorted,
+        }),
+        duplicate_keys: args.duplicate_keys.map(|policy| match policy {
+            DuplicateKeysArg::Error => DuplicateKeyPolicy::Error,
+            DuplicateKeysArg::FirstWins => DuplicateKeyPolicy::FirstWins,
+            DuplicateKeysArg::LastWins => DuplicateKeyPolicy::LastWins,
+            DuplicateKeysArg::CollectIntoArray => DuplicateKeyPolicy::CollectIntoArray,
+        }),
+        require_length_markers: Some(args.require_length_markers),
+        max_line_length: args.max_line_length,
+        limits: None,
+        preserve_number_literals: None,
+        metrics: None,
+        collect_warnings: None,
+    }
+}
+
+fn run_encode(args: &Args) -> Result<()> {
+    // Read input (JSON)
+    let input = read_input(args)?;
 
     // Encode
-    let toon_lines = conversion::encode_to_toon_lines(&input, Some(options))?;
+    let toon_lines = if has_extension(args.input.as_deref(), "csv") {
+        let rows = crate::csv::read_table(&input, &args.csv_dialect())?;
+        if let Some(ref schema_path) = args.schema {
+            check_schema(schema_path, &JsonValue::Array(rows.clone()))?;
+        }
+        crate::encode::encode_lines(JsonValue::Array(rows), Some(encode_options(args)))
+    } else {
+        if let Some(ref schema_path) = args.schema {
+            check_schema(schema_path, &JsonValue::from_json(&input)?)?;
+        }
+        conversion::encode_to_toon_lines(&input, Some(encode_options(args)))?
+    };
 
     // Output
     if args.stats {
-        let toon_output = toon_lines.join("\n");
+        let toon_output = toon_lines.join(line_ending(args).as_str());
         write_output(args, toon_output.as_bytes())?;
 
         // Calculate token estimates (simple heuristic: ~4 chars per token)
@@ -86,18 +352,57 @@ fn run_decode(args: &Args) -> Result<()> {
     // Read input (TOON)
     let input = read_input(args)?;
 
-    // Build decode options
-    let options = DecodeOptions {
-        indent: Some(usize::from(args.indent)),
-        strict: Some(!args.no_strict),
-        expand_paths: Some(match args.expand_paths {
-            ExpandPathsArg::Off => ExpandPathsMode::Off,
-            ExpandPathsArg::Safe => ExpandPathsMode::Safe,
-        }),
+    let options = decode_options(args);
+
+    if let Some(ref schema_path) = args.schema {
+        check_schema(
+            schema_path,
+            &crate::try_decode(&input, Some(options.clone()))?,
+        )?;
+    }
+
+    let queried = match args.query {
+        Some(ref expression) => {
+            let value = crate::try_decode(&input, Some(options.clone()))?;
+            let matches = crate::query::query(&value, expression)?;
+            Some(JsonValue::Array(
+                matches.into_iter().map(|m| m.value.clone()).collect(),
+            ))
+        }
+        None => None,
     };
 
+    if has_extension(args.output.as_deref(), "csv") {
+        let value = match queried {
+            Some(value) => value,
+            None => crate::try_decode(&input, Some(options))?,
+        };
+        let csv = crate::csv::write_table(csv_rows(&value)?, &args.csv_dialect())?;
+        write_output(args, csv.trim_end_matches('\n').as_bytes())?;
+        if let Some(ref output_path) = args.output {
+            let input_label = format_input_label(args);
+            eprintln!("Decoded `{input_label}` → `{}`", output_path.display());
+        }
+        return Ok(());
+    }
+
     // Decode to JSON chunks
-    let json_chunks = conversion::decode_to_json_chunks(&input, Some(options))?;
+    let format = JsonFormat {
+        indent: args.json_indent.unwrap_or(args.indent).into(),
+        ascii: args.ascii,
+    };
+    let json_chunks = if let Some(ref value) = queried {
+        json_stringify::json_stringify_formatted(value, &format)
+    } else if args.warn_ambiguous {
+        let (chunks, warnings) =
+            conversion::decode_to_json_chunks_with_warnings(&input, Some(options), &format)?;
+        for warning in &warnings {
+            eprintln!("warning: {warning}");
+        }
+        chunks
+    } else {
+        conversion::decode_to_json_chunks_with_format(&input, Some(options), &format)?
+    };
 
     // Write output
     write_chunks(args, &json_chunks)?;
@@ -112,6 +417,58 @@ fn run_decode(args: &Args) -> Result<()> {
     Ok(())
 }
 
+fn has_extension(path: Option<&Path>, extension: &str) -> bool {
+    path.and_then(Path::extension)
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+}
+
+/// The rows to write as CSV: a root array, or the array under a document's
+/// only key.
+fn csv_rows(value: &JsonValue) -> Result<&crate::JsonArray> {
+    match value {
+        JsonValue::Array(rows) => return Ok(rows),
+        JsonValue::Object(entries) => {
+            if let [(_, JsonValue::Array(rows))] = entries.as_slice() {
+                return Ok(rows);
+            }
+        }
+        JsonValue::Primitive(_) => {}
+    }
+    Err(ToonError::message(
+        "CSV output needs a root array or a document with a single array key",
+    ))
+}
+
+/// Fail with every violation, one per line, if `value` does not conform to
+/// the JSON Schema at `path`.
+#[cfg(feature = "schema")]
+fn check_schema(path: &Path, value: &JsonValue) -> Result<()> {
+    use std::fmt::Write as _;
+
+    let schema: serde_json::Value =
+        serde_json::from_str(&read_file(path)?).map_err(|err| ToonError::json_parse(&err))?;
+    let violations = crate::schema::validate(value, &JsonValue::from(schema));
+    if violations.is_empty() {
+        return Ok(());
+    }
+    let mut message = format!(
+        "Data does not conform to schema `{}` ({} violations):",
+        path.display(),
+        violations.len()
+    );
+    for violation in &violations {
+        let _ = write!(message, "\n  {violation}");
+    }
+    Err(ToonError::message(message))
+}
+
+#[cfg(not(feature = "schema"))]
+fn check_schema(_path: &Path, _value: &JsonValue) -> Result<()> {
+    Err(ToonError::message(
+        "--schema requires toon to be built with the `schema` feature",
+    ))
+}
+
 fn read_input(args: &Args) -> Result<String> {
     if args.is_stdin() {
         read_stdin()
@@ -124,6 +481,13 @@ fn read_input(args: &Args) -> Result<String> {
     }
 }
 
+fn read_path_or_stdin(path: Option<&Path>) -> Result<String> {
+    match path {
+        Some(path) if path.as_os_str() != "-" => read_file(path),
+        _ => read_stdin(),
+    }
+}
+
 fn read_stdin() -> Result<String> {
     let mut buffer = String::new();
     io::stdin()
@@ -145,15 +509,21 @@ fn write_output(args: &Args, data: &[u8]) -> Result<()> {
         file.write_all(b"\n")
             .map_err(|e| ToonError::file_write(path.clone(), e))?;
     } else {
-        let stdout = io::stdout();
-        let mut handle = stdout.lock();
-        handle.write_all(data).map_err(ToonError::stdout_write)?;
-        handle.write_all(b"\n").map_err(ToonError::stdout_write)?;
+        write_to_stdout(data)?;
     }
     Ok(())
 }
 
+fn write_to_stdout(data: &[u8]) -> Result<()> {
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    handle.write_all(data).map_err(ToonError::stdout_write)?;
+    handle.write_all(b"\n").map_err(ToonError::stdout_write)?;
+    Ok(())
+}
+
 fn write_lines(args: &Args, lines: &[String]) -> Result<()> {
+    let newline = line_ending(args).as_str().as_bytes();
     if let Some(ref path) = args.output {
         let file = File::create(path).map_err(|e| ToonError::file_create(path.clone(), e))?;
         let mut writer = BufWriter::new(file);
@@ -161,7 +531,7 @@ fn write_lines(args: &Args, lines: &[String]) -> Result<()> {
         for (i, line) in lines.iter().enumerate() {
             if i > 0 {
                 writer
-                    .write_all(b"\n")
+                    .write_all(newline)
                     .map_err(|e| ToonError::file_write(path.clone(), e))?;
             }
             writer
@@ -170,7 +540,7 @@ fn write_lines(args: &Args, lines: &[String]) -> Result<()> {
         }
         // Trailing newline
         writer
-            .write_all(b"\n")
+            .write_all(newline)
             .map_err(|e| ToonError::file_write(path.clone(), e))?;
     } else {
         let stdout = io::stdout();
@@ -178,14 +548,14 @@ fn write_lines(args: &Args, lines: &[String]) -> Result<()> {
 
         for (i, line) in lines.iter().enumerate() {
             if i > 0 {
-                handle.write_all(b"\n").map_err(ToonError::stdout_write)?;
+                handle.write_all(newline).map_err(ToonError::stdout_write)?;
             }
             handle
                 .write_all(line.as_bytes())
                 .map_err(ToonError::stdout_write)?;
         }
         // Trailing newline
-        handle.write_all(b"\n").map_err(ToonError::stdout_write)?;
+        handle.write_all(newline).map_err(ToonError::stdout_write)?;
     }
     Ok(())
 }
@@ -228,12 +598,3 @@ fn format_input_label(args: &Args) -> String {
         "stdin".to_string()
     }
 }
-
-/// Simple token estimation heuristic (roughly 4 chars per token for English/code).
-/// This matches the behavior of tokenx used in the legacy CLI.
-fn estimate_tokens(text: &str) -> usize {
-    // Simple heuristic: count non-whitespace chars / 4, with minimum of word count
-    let char_estimate = text.chars().filter(|c| !c.is_whitespace()).count() / 4;
-    let word_estimate = text.split_whitespace().count();
-    char_estimate.max(word_estimate).max(1)
-}
@@ -0,0 +1,257 @@
+//! Interactive conversion loop behind `toon repl`.
+//!
+//! Each snippet is converted as soon as it is complete: JSON once it parses,
+//! TOON when a blank line ends it. Lines starting with `:` are commands that
+//! change the session options applied to every later snippet.
+
+use std::fmt::Write as _;
+use std::io::{self, BufRead, Write};
+
+use crate::chunk::estimate_tokens;
+use crate::error::Result;
+use crate::options::{
+    DecodeOptions, EncodeOptions, ExpandPathsMode, KeyFoldingMode, KeyOrder, Strictness,
+    resolve_decode_options, resolve_encode_options,
+};
+use crate::{JsonValue, try_decode, try_encode};
+
+use super::args::parse_delimiter;
+
+const PROMPT: &str = "toon> ";
+const CONTINUATION_PROMPT: &str = "...> ";
+
+const HELP: &str = "\
+Enter JSON (converted once it parses) or TOON (converted at the next blank line).
+Commands:
+  :set delimiter <,|tab|pipe|;>     :set indent <n>
+  :set key-folding <off|safe>       :set flatten-depth <n>
+  :set strict <on|off>              :set expand-paths <off|safe|indexed>
+  :set key-order <preserve|sorted>
+  :show    print the session options
+  :help    print this help
+  :quit    leave the REPL";
+
+/// Options remembered between snippets.
+#[derive(Clone, Default)]
+pub struct Session {
+    pub encode: EncodeOptions,
+    pub decode: DecodeOptions,
+}
+
+/// What the loop should do after a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reply {
+    Print(String),
+    Quit,
+}
+
+impl Session {
+    #[must_use]
+    pub const fn new(encode: EncodeOptions, decode: DecodeOptions) -> Self {
+        Self { encode, decode }
+    }
+
+    /// Run a `:` command (without the colon).
+    #[must_use]
+    pub fn command(&mut self, command: &str) -> Reply {
+        let mut words = command.split_whitespace();
+        match (words.next(), words.next(), words.next()) {
+            (Some("q" | "quit" | "exit"), None, None) => Reply::Quit,
+            (Some("help" | "h"), None, None) => Reply::Print(HELP.to_string()),
+            (Some("show"), None, None) => Reply::Print(self.describe()),
+            (Some("set"), Some(option), Some(value)) if words.next().is_none() => {
+                Reply::Print(match self.set(option, value) {
+                    Ok(()) => format!("{option} = {value}"),
+                    Err(message) => format!("error: {message}"),
+                })
+            }
+            _ => Reply::Print(format!(
+                "error: unknown command `:{}` (try :help)",
+                command.trim()
+            )),
+        }
+    }
+
+    fn set(&mut self, option: &str, value: &str) -> std::result::Result<(), String> {
+        match option {
+            "delimiter" => self.encode.delimiter = Some(parse_delimiter(value)?),
+            "indent" => {
+                let indent = value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|indent| *indent <= 16)
+                    .ok_or_else(|| format!("invalid indent `{value}`, expected 0..=16"))?;
+                self.encode.indent = Some(indent);
+                self.decode.indent = Some(indent);
+            }
+            "key-folding" => {
+                self.encode.key_folding = Some(match value {
+                    "off" => KeyFoldingMode::Off,
+                    "safe" => KeyFoldingMode::Safe,
+                    "aggressive" => KeyFoldingMode::Aggressive,
+                    _ => {
+                        return Err(format!(
+                            "invalid key folding `{value}`, expected off, safe or aggressive"
+                        ));
+                    }
+                });
+            }
+            "flatten-depth" => {
+                self.encode.flatten_depth = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid flatten depth `{value}`"))?,
+                );
+            }
+            "strict" => {
+                self.decode.strictness = Some(match value {
+                    "on" | "true" => Strictness::default(),
+                    "off" | "false" => Strictness::lenient(),
+                    _ => return Err(format!("invalid strict `{value}`, expected on or off")),
+                });
+            }
+            "expand-paths" => {
+                self.decode.expand_paths = Some(match value {
+                    "off" => ExpandPathsMode::Off,
+                    "safe" => ExpandPathsMode::Safe,
+                    "indexed" => ExpandPathsMode::Indexed,
+                    "aggressive" => ExpandPathsMode::Aggressive,
+                    _ => {
+                        return Err(format!(
+                            "invalid path expansion `{value}`, expected off, safe, indexed or aggressive"
+                        ));
+                    }
+                });
+            }
+            "key-order" => {
+                self.decode.key_order = Some(match value {
+                    "preserve" => KeyOrder::Preserve,
+                    "sorted" => KeyOrder::Sorted,
+                    _ => {
+                        return Err(format!(
+                            "invalid key order `{value}`, expected preserve or sorted"
+                        ));
+                    }
+                });
+            }
+            _ => return Err(format!("unknown option `{option}` (try :help)")),
+        }
+        Ok(())
+    }
+
+    fn describe(&self) -> String {
+        let encode = resolve_encode_options(Some(self.encode.clone()));
+        let decode = resolve_decode_options(Some(self.decode.clone()));
+        let mut out = String::new();
+        let _ = writeln!(out, "delimiter     = {:?}", encode.delimiter);
+        let _ = writeln!(out, "indent        = {}", encode.indent);
+        let _ = writeln!(out, "key-folding   = {:?}", encode.key_folding);
+        if encode.flatten_depth == usize::MAX {
+            let _ = writeln!(out, "flatten-depth = unlimited");
+        } else {
+            let _ = writeln!(out, "flatten-depth = {}", encode.flatten_depth);
+        }
+        let strict = decode.strictness != Strictness::lenient();
+        let _ = writeln!(out, "strict        = {strict}");
+        let _ = writeln!(out, "expand-paths  = {:?}", decode.expand_paths);
+        let _ = write!(out, "key-order     = {:?}", decode.key_order);
+        out
+    }
+
+    /// Convert a complete snippet: JSON is encoded, anything else is decoded
+    /// as TOON. The converted form is followed by a token comparison.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the snippet is neither valid JSON nor valid TOON.
+    pub fn convert(&self, snippet: &str) -> Result<String> {
+        let (converted, json, toon) =
+            if let Ok(json) = serde_json::from_str::<serde_json::Value>(snippet) {
+                let toon = try_encode(JsonValue::from(json), Some(self.encode.clone()))?;
+                (toon.clone(), snippet.to_string(), toon)
+            } else {
+                let value = try_decode(snippet, Some(self.decode.clone()))?;
+                let json = serde_json::Value::from(value);
+                let pretty = serde_json::to_string_pretty(&json).unwrap_or_default();
+                (pretty, json.to_string(), snippet.to_string())
+            };
+        Ok(format!("{converted}\n{}", token_summary(&json, &toon)))
+    }
+}
+
+fn token_summary(json: &str, toon: &str) -> String {
+    let json_tokens = estimate_tokens(json);
+    let toon_tokens = estimate_tokens(toon);
+    #[allow(clippy::cast_precision_loss)]
+    let percent = (toon_tokens as f64 - json_tokens as f64) / json_tokens as f64 * 100.0;
+    format!("-- ~{json_tokens} tokens (JSON) vs ~{toon_tokens} tokens (TOON), {percent:+.1}%")
+}
+
+/// Read snippets and commands from `input` until `:quit` or end of input,
+/// writing prompts, conversions and errors to `output`.
+///
+/// # Errors
+///
+/// Returns an error if reading `input` or writing `output` fails.
+pub fn run<R: BufRead, W: Write>(
+    session: &mut Session,
+    input: R,
+    output: &mut W,
+) -> io::Result<()> {
+    let mut buffer = String::new();
+    let mut lines = input.lines();
+    loop {
+        write!(
+            output,
+            "{}",
+            if buffer.is_empty() {
+                PROMPT
+            } else {
+                CONTINUATION_PROMPT
+            }
+        )?;
+        output.flush()?;
+        let Some(line) = lines.next().transpose()? else {
+            if !buffer.trim().is_empty() {
+                writeln!(output)?;
+                print_conversion(session, &buffer, output)?;
+            }
+            writeln!(output)?;
+            return Ok(());
+        };
+
+        if buffer.is_empty() {
+            if let Some(command) = line.trim_start().strip_prefix(':') {
+                match session.command(command) {
+                    Reply::Print(text) => writeln!(output, "{text}")?,
+                    Reply::Quit => return Ok(()),
+                }
+                continue;
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+        }
+
+        if line.trim().is_empty() {
+            print_conversion(session, &buffer, output)?;
+            buffer.clear();
+            continue;
+        }
+        if !buffer.is_empty() {
+            buffer.push('\n');
+        }
+        buffer.push_str(&line);
+        if serde_json::from_str::<serde_json::Value>(&buffer).is_ok() {
+            print_conversion(session, &buffer, output)?;
+            buffer.clear();
+        }
+    }
+}
+
+fn print_conversion<W: Write>(session: &Session, snippet: &str, output: &mut W) -> io::Result<()> {
+    match session.convert(snippet) {
+        Ok(text) => writeln!(output, "{text}"),
+        Err(err) => writeln!(output, "error: {err}"),
+    }
+}
@@ -0,0 +1,141 @@
+//! Per-section size report behind `toon stats`.
+
+use std::fmt::Write;
+
+use crate::chunk::estimate_tokens;
+use crate::decode::parser::{parse_array_header_line, parse_key_token};
+use crate::error::Result;
+use crate::shared::constants::DEFAULT_DELIMITER;
+use crate::try_decode;
+
+/// Size of one top-level section of a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionStats {
+    /// Top-level key, or `(root)` for a root array or primitive.
+    pub key: String,
+    pub lines: usize,
+    /// Bytes including each line's newline.
+    pub bytes: usize,
+    /// Row count when the section is a table.
+    pub rows: Option<usize>,
+    pub tokens: usize,
+}
+
+/// Break a TOON document down by top-level key, in document order.
+///
+/// # Errors
+///
+/// Returns an error if the document does not decode.
+pub fn section_stats(input: &str) -> Result<Vec<SectionStats>> {
+    try_decode(input, None)?;
+
+    let mut sections: Vec<(SectionStats, String)> = Vec::new();
+    for line in input.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if !line.starts_with(' ') || sections.is_empty() {
+            let (key, rows) = section_key(line);
+            sections.push((
+                SectionStats {
+                    key,
+                    lines: 0,
+                    bytes: 0,
+                    rows,
+                    tokens: 0,
+                },
+                String::new(),
+            ));
+        }
+        if let Some((section, text)) = sections.last_mut() {
+            section.lines += 1;
+            section.bytes += line.len() + 1;
+            text.push_str(line);
+            text.push('\n');
+        }
+    }
+
+    Ok(sections
+        .into_iter()
+        .map(|(mut section, text)| {
+            section.tokens = estimate_tokens(&text);
+            section
+        })
+        .collect())
+}
+
+fn section_key(line: &str) -> (String, Option<usize>) {
+    if let Ok(Some(parsed)) = parse_array_header_line(line, DEFAULT_DELIMITER) {
+        let rows = parsed.header.fields.map(|_| parsed.header.length);
+        let key = parsed
+            .header
+            .key
+            .map_or_else(|| "(root)".to_string(), String::from);
+        return (key, rows);
+    }
+    parse_key_token(line, 0).map_or_else(
+        |_| ("(root)".to_string(), None),
+        |(key, ..)| (key.into_owned(), None),
+    )
+}
+
+/// Render sections as an aligned table with a share-of-tokens column and
+/// a total row.
+#[must_use]
+pub fn format_stats(sections: &[SectionStats]) -> String {
+    let total_tokens: usize = sections.iter().map(|section| section.tokens).sum();
+    let mut rows: Vec<[String; 6]> = sections
+        .iter()
+        .map(|section| {
+            [
+                section.key.clone(),
+                section.lines.to_string(),
+                section.bytes.to_string(),
+                section
+                    .rows
+                    .map_or_else(|| "-".to_string(), |rows| rows.to_string()),
+                section.tokens.to_string(),
+                share(section.tokens, total_tokens),
+            ]
+        })
+        .collect();
+    rows.push([
+        "TOTAL".to_string(),
+        sections.iter().map(|s| s.lines).sum::<usize>().to_string(),
+        sections.iter().map(|s| s.bytes).sum::<usize>().to_string(),
+        "-".to_string(),
+        total_tokens.to_string(),
+        share(total_tokens, total_tokens),
+    ]);
+    rows.insert(
+        0,
+        ["SECTION", "LINES", "BYTES", "ROWS", "TOKENS", "SHARE"].map(ToString::to_string),
+    );
+
+    let mut widths = [0usize; 6];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut out = String::new();
+    for row in &rows {
+        let _ = write!(out, "{:<width$}", row[0], width = widths[0]);
+        for (cell, width) in row.iter().zip(widths).skip(1) {
+            let _ = write!(out, "  {cell:>width$}");
+        }
+        out.push('\n');
+    }
+    out.pop();
+    out
+}
+
+fn share(tokens: usize, total: usize) -> String {
+    if total == 0 {
+        return "-".to_string();
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let percent = tokens as f64 / total as f64 * 100.0;
+    format!("{percent:.1}%")
+}
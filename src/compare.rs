@@ -0,0 +1,219 @@
+//! Approximate structural comparison of decoded values.
+
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::{JsonValue, StringOrNumberOrBoolOrNull};
+
+/// How closely two numbers must agree for [`JsonValue::semantic_eq`].
+///
+/// Two finite numbers are equal when their difference is within `absolute`
+/// or within `relative` times the larger magnitude.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    /// Maximum difference relative to the larger magnitude.
+    pub relative: f64,
+    /// Maximum absolute difference, useful for values near zero.
+    pub absolute: f64,
+    /// Treat `-0.0` and `0.0` as equal.
+    pub signed_zero_equal: bool,
+    /// Treat `NaN` as equal to `NaN`.
+    pub nan_equal: bool,
+    /// Compare an integer and a float by value, so `1 == 1.0`; otherwise
+    /// they always differ.
+    pub int_float_equal: bool,
+}
+
+impl Tolerance {
+    /// Bitwise-strict comparison: no tolerance, signed zeros and `NaN`
+    /// differ, and an integer never equals a float.
+    pub const EXACT: Self = Self {
+        relative: 0.0,
+        absolute: 0.0,
+        signed_zero_equal: false,
+        nan_equal: false,
+        int_float_equal: false,
+    };
+
+    /// Default flags with the given relative tolerance.
+    #[must_use]
+    pub const fn relative(relative: f64) -> Self {
+        Self {
+            relative,
+            absolute: 0.0,
+            signed_zero_equal: true,
+            nan_equal: false,
+            int_float_equal: true,
+        }
+    }
+
+    /// Default flags with the given absolute tolerance.
+    #[must_use]
+    pub const fn absolute(absolute: f64) -> Self {
+        Self {
+            relative: 0.0,
+            absolute,
+            signed_zero_equal: true,
+            nan_equal: false,
+            int_float_equal: true,
+        }
+    }
+
+    #[allow(clippy::float_cmp)]
+    fn numbers_eq(&self, a: f64, b: f64) -> bool {
+        if a.is_nan() || b.is_nan() {
+            return self.nan_equal && a.is_nan() && b.is_nan();
+        }
+        if a == b {
+            return self.signed_zero_equal || a.is_sign_negative() == b.is_sign_negative();
+        }
+        if !a.is_finite() || !b.is_finite() {
+            return false;
+        }
+        let diff = (a - b).abs();
+        diff <= self.absolute || diff <= self.relative * a.abs().max(b.abs())
+    }
+}
+
+impl Default for Tolerance {
+    /// Relative tolerance of `1e-10`; `-0.0 == 0.0`; `NaN != NaN`;
+    /// `1 == 1.0`.
+    fn default() -> Self {
+        Self::relative(1e-10)
+    }
+}
+
+impl JsonValue {
+    /// Compare two values structurally, allowing numbers to differ within
+    /// `tolerance`.
+    ///
+    /// Two integers must be equal exactly, and an integer equals a float
+    /// only with [`Tolerance::int_float_equal`]. Object key order is
+    /// ignored and entries compare as a multiset, so a repeated key must be
+    /// repeated as often on both sides; arrays must match element by
+    /// element.
+    #[must_use]
+    pub fn semantic_eq(&self, other: &Self, tolerance: Tolerance) -> bool {
+        match (self, other) {
+            (
+                Self::Primitive(StringOrNumberOrBoolOrNull::Number(a)),
+                Self::Primitive(StringOrNumberOrBoolOrNull::Number(b)),
+            ) if a.is_integer() && b.is_integer() => a == b,
+            (
+                Self::Primitive(StringOrNumberOrBoolOrNull::Number(a)),
+                Self::Primitive(StringOrNumberOrBoolOrNull::Number(b)),
+            ) if a.is_integer() != b.is_integer() && !tolerance.int_float_equal => false,
+            (
+                Self::Primitive(StringOrNumberOrBoolOrNull::Number(a)),
+                Self::Primitive(StringOrNumberOrBoolOrNull::Number(b)),
+            ) => tolerance.numbers_eq(a.as_f64(), b.as_f64()),
+            (Self::Primitive(a), Self::Primitive(b)) => a == b,
+            (Self::Array(a), Self::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.semantic_eq(b, tolerance))
+            }
+            (Self::Object(a), Self::Object(b)) => {
+                if a.len() != b.len() {
+                    return false;
+                }
+                // Pair each entry with an unused one of `b`, so repeated keys
+                // cannot all match the same entry.
+                let mut used = vec![false; b.len()];
+                a.iter().all(|(key, value)| {
+                    let found = b
+                        .iter()
+                        .enumerate()
+                        .position(|(index, (other_key, other))| {
+                            !used[index] && other_key == key && value.semantic_eq(other, tolerance)
+                        });
+                    found.is_some_and(|index| {
+                        used[index] = true;
+                        true
+                    })
+                })
+            }
+            _ => false,
+        }
+    }
+
+    /// A hash that ignores object key order but not array order, for
+    /// deduplicating values or ruling out equality without encoding them.
+    ///
+    /// Values equal up to key order hash alike, with numbers compared by
+    /// value: `1`, `1.0` and `-0.0 == 0` agree. Hashes are stable within
+    /// one build but may change across Rust releases, so do not persist
+    /// them.
+    ///
+    /// ```
+    /// let a = toon::decode("id: 1\ntags[2]: x,y", None);
+    /// let b = toon::decode("tags[2]: x,y\nid: 1.0", None);
+    /// let c = toon::decode("id: 1\ntags[2]: y,x", None);
+    /// assert_eq!(a.structural_hash(), b.structural_hash());
+    /// assert_ne!(a.structural_hash(), c.structural_hash());
+    /// ```
+    #[must_use]
+    pub fn structural_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash_structure(&mut hasher);
+        hasher.finish()
+    }
+
+    fn hash_structure(&self, hasher: &mut DefaultHasher) {
+        match self {
+            Self::Primitive(StringOrNumberOrBoolOrNull::Null) => 0u8.hash(hasher),
+            Self::Primitive(StringOrNumberOrBoolOrNull::Bool(value)) => (1u8, value).hash(hasher),
+            Self::Primitive(StringOrNumberOrBoolOrNull::Number(number)) => {
+                (2u8, number).hash(hasher);
+            }
+            Self::Primitive(StringOrNumberOrBoolOrNull::String(value)) => {
+                (3u8, value).hash(hasher);
+            }
+            Self::Array(items) => {
+                (4u8, items.len()).hash(hasher);
+                for item in items {
+                    item.hash_structure(hasher);
+                }
+            }
+            Self::Object(entries) => {
+                // Entries hash on their own and add up, so order drops out.
+                let sum = entries.iter().fold(0u64, |sum, (key, value)| {
+                    let mut entry = DefaultHasher::new();
+                    key.hash(&mut entry);
+                    value.hash_structure(&mut entry);
+                    sum.wrapping_add(entry.finish())
+                });
+                (5u8, entries.len(), sum).hash(hasher);
+            }
+        }
+    }
+}
+
+/// Remove every value equal to an earlier one, keeping the first of each in
+/// its place.
+///
+/// Equality is [`JsonValue`]'s own, so objects with the same entries in a
+/// different order are kept apart; [`normalize`](JsonValue::normalize) them
+/// first to merge those. Values are not cloned. For set operations beyond
+/// this, collect into a `HashSet<JsonValue>`.
+///
+/// ```
+/// let mut values = vec![1.into(), "a".into(), 1.0.into(), "a".into(), 2.into()];
+/// toon::dedup_values(&mut values);
+/// assert_eq!(values, [1.into(), "a".into(), 2.into()]);
+/// ```
+pub fn dedup_values(values: &mut Vec<JsonValue>) {
+    let mut first_by_hash: HashMap<u64, Vec<usize>> = HashMap::new();
+    let mut keep = Vec::with_capacity(values.len());
+    for (index, value) in values.iter().enumerate() {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let firsts = first_by_hash.entry(hasher.finish()).or_default();
+        let unique = firsts.iter().all(|&first| values[first] != *value);
+        if unique {
+            firsts.push(index);
+        }
+        keep.push(unique);
+    }
+    let mut keep = keep.into_iter();
+    values.retain(|_| keep.next().unwrap_or(true));
+}
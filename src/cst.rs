@@ -0,0 +1,723 @@
+//! Lossless concrete syntax tree, for tools that edit TOON text in place.
+//!
+//! [`parse`] splits a document into [`Line`]s nested by indentation and
+//! keeps the exact text of each: indentation, quoting, delimiters, trailing
+//! whitespace, blank lines and line endings. Printing an unedited
+//! [`Document`] reproduces its input byte for byte. An edited line is
+//! rendered again from its parts under its old indentation, while every
+//! other line stays as written:
+//!
+//! ```
+//! use toon::JsonPrimitive;
+//!
+//! let text = "# service\nname:   api\nports[2|]: 80|443\n";
+//! let mut doc = toon::cst::parse(text).unwrap();
+//! assert_eq!(doc.to_string(), text);
+//!
+//! let name = doc.get_mut(&["name"]).unwrap();
+//! name.set_value(&JsonPrimitive::String("web".into())).unwrap();
+//! assert_eq!(doc.to_string(), "# service\nname: web\nports[2|]: 80|443\n");
+//! ```
+//!
+//! TOON has no comment syntax of its own. The tree keeps a line whose
+//! content starts with `#` as a [`LineKind::Comment`], except among the rows
+//! of a tabular array, and [`Document::to_value`] leaves comments out.
+
+use std::fmt;
+
+use crate::decode::parser::{
+    UNMARKED_LENGTH, is_key_value_line, parse_array_header_line, parse_delimited_values,
+    parse_key_token, parse_primitive_token, parse_unmarked_array_header,
+};
+use crate::decode::validation::is_data_row;
+use crate::encode::primitives::encode_primitive;
+use crate::error::{Result, ToonError};
+use crate::options::QuotePolicy;
+use crate::shared::constants::{
+    COLON, DEFAULT_DELIMITER, DOUBLE_QUOTE, LIST_ITEM_MARKER, LIST_ITEM_PREFIX, OPEN_BRACKET,
+};
+use crate::shared::string_utils::escape_string;
+use crate::shared::validation::is_valid_unquoted_key;
+use crate::{DecodeOptions, JsonPrimitive, JsonValue, StringOrNumberOrBoolOrNull};
+
+const COMMENT_MARKER: char = '#';
+
+/// A parsed document: its top-level lines, each holding the lines indented
+/// under it.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Document {
+    nodes: Vec<Node>,
+}
+
+/// A line and the lines nested under it.
+///
+/// Blank and comment lines belong to the node of the content line that
+/// follows them; those at the end of the document are top-level nodes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    line: Line,
+    children: Vec<Self>,
+}
+
+/// One source line, kept as written along with what it holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    number: usize,
+    text: String,
+    ending: String,
+    indent: usize,
+    kind: LineKind,
+}
+
+/// What a line holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineKind {
+    /// An empty or whitespace-only line.
+    Blank,
+    /// A `#` line, holding the text after the `#`.
+    Comment(String),
+    /// A key, array header, list item or bare value.
+    Entry(Entry),
+    /// A row of a tabular array.
+    Row(Row),
+}
+
+/// A line that is not blank, a comment or a tabular row.
+///
+/// `key: value` has a key and one value, `key:` opens a nested object, and
+/// `key[N]: a,b` has a header and its inline values. A list item may hold
+/// any of these, or just one value; a root primitive is a single value.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Entry {
+    /// The line starts with the list item marker `-`.
+    pub list_item: bool,
+    pub key: Option<Key>,
+    pub header: Option<Header>,
+    /// Values after the colon, or the value of a list item or root primitive.
+    pub values: Vec<Scalar>,
+}
+
+/// A key or tabular field name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Key {
+    /// The key with escapes resolved.
+    pub name: String,
+    /// Written inside double quotes.
+    pub quoted: bool,
+}
+
+/// The `[N]{fields}` part of an array header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// Declared length, or `None` for a header such as `[]` without one.
+    pub length: Option<usize>,
+    pub delimiter: char,
+    /// Field names of a tabular array.
+    pub fields: Option<Vec<Key>>,
+}
+
+/// The values of one tabular row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Row {
+    /// Delimiter declared by the row's header.
+    pub delimiter: char,
+    pub values: Vec<Scalar>,
+}
+
+/// A primitive value along with its text as written, such as `1.50` or a
+/// quoted `"80"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scalar {
+    raw: String,
+    value: JsonPrimitive,
+}
+
+/// Parse `input` into a [`Document`] that prints back as `input`.
+///
+/// Parsing only checks each line on its own; whether the lines form a valid
+/// document is up to [`Document::to_value`].
+///
+/// # Errors
+///
+/// Returns an error for a malformed quoted key or string, or a key without
+/// its colon.
+pub fn parse(input: &str) -> Result<Document> {
+    let mut raw_lines = Vec::new();
+    let mut rest = input;
+    while !rest.is_empty() {
+        let (text, ending) = match rest.find('\n') {
+            Some(end) if rest[..end].ends_with('\r') => (&rest[..end - 1], &rest[end - 1..=end]),
+            Some(end) => (&rest[..end], &rest[end..=end]),
+            None => (rest, ""),
+        };
+        rest = &rest[text.len() + ending.len()..];
+        raw_lines.push(RawLine {
+            number: raw_lines.len() + 1,
+            text,
+            ending,
+            indent: text.len() - text.trim_start_matches([' ', '\t']).len(),
+        });
+    }
+
+    let mut builder = Builder {
+        lines: raw_lines,
+        index: 0,
+    };
+    let nodes = builder.nodes(None)?;
+    Ok(Document { nodes })
+}
+
+impl Document {
+    #[must_use]
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    pub const fn nodes_mut(&mut self) -> &mut Vec<Node> {
+        &mut self.nodes
+    }
+
+    /// Every line in document order.
+    pub fn lines(&self) -> impl Iterator<Item = &Line> {
+        let mut lines = Vec::new();
+        collect_lines(&self.nodes, &mut lines);
+        lines.into_iter()
+    }
+
+    /// The node of the entry at `path`, one key per level of nested objects.
+    #[must_use]
+    pub fn get(&self, path: &[&str]) -> Option<&Node> {
+        let (first, rest) = path.split_first()?;
+        let mut node = find_key(&self.nodes, first)?;
+        for key in rest {
+            node = node.child(key)?;
+        }
+        Some(node)
+    }
+
+    /// Mutable counterpart of [`get`](Self::get).
+    pub fn get_mut(&mut self, path: &[&str]) -> Option<&mut Node> {
+        let (first, rest) = path.split_first()?;
+        let mut node = find_key_mut(&mut self.nodes, first)?;
+        for key in rest {
+            node = node.child_mut(key)?;
+        }
+        Some(node)
+    }
+
+    /// Decode the document, leaving out comment lines.
+    ///
+    /// Errors report line numbers of the document as printed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document does not decode (see
+    /// [`try_decode`](crate::try_decode)).
+    pub fn to_value(&self, options: Option<DecodeOptions>) -> Result<JsonValue> {
+        let mut numbers = Vec::new();
+        let mut text = Vec::new();
+        for (index, line) in self.lines().enumerate() {
+            if !matches!(line.kind, LineKind::Comment(_)) {
+                numbers.push(index + 1);
+                text.push(line.text.clone());
+            }
+        }
+        crate::try_decode_from_lines(text, options).map_err(|mut err| {
+            if let ToonError::Decode(details) = &mut err {
+                if let Some(number) = details.line.checked_sub(1).and_then(|i| numbers.get(i)) {
+                    details.line = *number;
+                }
+            }
+            err
+        })
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in self.lines() {
+            f.write_str(&line.text)?;
+            f.write_str(&line.ending)?;
+        }
+        Ok(())
+    }
+}
+
+impl Node {
+    #[must_use]
+    pub const fn new(line: Line) -> Self {
+        Self {
+            line,
+            children: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub const fn line(&self) -> &Line {
+        &self.line
+    }
+
+    pub const fn line_mut(&mut self) -> &mut Line {
+        &mut self.line
+    }
+
+    #[must_use]
+    pub fn children(&self) -> &[Self] {
+        &self.children
+    }
+
+    pub const fn children_mut(&mut self) -> &mut Vec<Self> {
+        &mut self.children
+    }
+
+    /// The key of this node's line, if it has one.
+    #[must_use]
+    pub fn key(&self) -> Option<&str> {
+        self.line.entry()?.key.as_ref().map(|key| key.name.as_str())
+    }
+
+    /// The nested entry with `key`.
+    #[must_use]
+    pub fn child(&self, key: &str) -> Option<&Self> {
+        find_key(&self.children, key)
+    }
+
+    /// Mutable counterpart of [`child`](Self::child).
+    pub fn child_mut(&mut self, key: &str) -> Option<&mut Self> {
+        find_key_mut(&mut self.children, key)
+    }
+
+    /// Make this entry hold the single value `value`, dropping any lines
+    /// nested under it. A string written in quotes stays quoted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the line is not an entry, or is an array header.
+    pub fn set_value(&mut self, value: &JsonPrimitive) -> Result<()> {
+        let Some(mut entry) = self.line.entry().cloned() else {
+            return Err(ToonError::message(format!(
+                "Line {} holds no entry to set a value on",
+                self.line.number
+            )));
+        };
+        if entry.header.is_some() {
+            return Err(ToonError::message(format!(
+                "Line {} is an array header; edit its items instead",
+                self.line.number
+            )));
+        }
+        let mut scalar = Scalar::new(value, DEFAULT_DELIMITER);
+        if let StringOrNumberOrBoolOrNull::String(text) = value {
+            if entry.values.first().is_some_and(Scalar::is_quoted) && !scalar.is_quoted() {
+                scalar.raw = quote(text);
+            }
+        }
+        entry.values = vec![scalar];
+        self.line.set_kind(LineKind::Entry(entry));
+        self.children.clear();
+        Ok(())
+    }
+}
+
+impl Line {
+    /// A line to insert, ending in `\n`.
+    #[must_use]
+    pub fn new(indent: &str, kind: LineKind) -> Self {
+        let mut line = Self {
+            number: 0,
+            text: indent.to_string(),
+            ending: "\n".to_string(),
+            indent: indent.len(),
+            kind: LineKind::Blank,
+        };
+        line.set_kind(kind);
+        line
+    }
+
+    /// 1-based line number in the parsed input, or 0 for an inserted line.
+    #[must_use]
+    pub const fn number(&self) -> usize {
+        self.number
+    }
+
+    /// The line as written, without its line ending.
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// `\n`, `\r\n`, or empty for a last line without one.
+    #[must_use]
+    pub fn ending(&self) -> &str {
+        &self.ending
+    }
+
+    /// Leading spaces and tabs.
+    #[must_use]
+    pub fn indent(&self) -> &str {
+        &self.text[..self.indent]
+    }
+
+    /// The text between the indentation and any trailing whitespace.
+    #[must_use]
+    pub fn content(&self) -> &str {
+        self.text[self.indent..].trim_end()
+    }
+
+    #[must_use]
+    pub const fn kind(&self) -> &LineKind {
+        &self.kind
+    }
+
+    #[must_use]
+    pub const fn entry(&self) -> Option<&Entry> {
+        match &self.kind {
+            LineKind::Entry(entry) => Some(entry),
+            _ => None,
+        }
+    }
+
+    /// Replace what the line holds, rendering its text again under the same
+    /// indentation. A blank line is left empty.
+    pub fn set_kind(&mut self, kind: LineKind) {
+        let content = render(&kind);
+        self.text = if content.is_empty() {
+            String::new()
+        } else {
+            format!("{}{content}", self.indent())
+        };
+        self.indent = self.text.len() - content.len();
+        self.kind = kind;
+    }
+
+    /// Replace the indentation, keeping the rest of the line.
+    pub fn set_indent(&mut self, indent: &str) {
+        self.text.replace_range(..self.indent, indent);
+        self.indent = indent.len();
+    }
+}
+
+impl Key {
+    /// A key quoted only if it needs to be.
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+        Self {
+            quoted: !is_valid_unquoted_key(&name),
+            name,
+        }
+    }
+
+    fn parse(token: &str, quoted: bool) -> Self {
+        Self {
+            name: token.to_string(),
+            quoted,
+        }
+    }
+
+    fn render(&self, out: &mut String) {
+        if self.quoted {
+            out.push_str(&quote(&self.name));
+        } else {
+            out.push_str(&self.name);
+        }
+    }
+}
+
+impl Header {
+    fn render(&self, out: &mut String) {
+        out.push(OPEN_BRACKET);
+        if let Some(length) = self.length {
+            out.push_str(&length.to_string());
+        }
+        if self.delimiter != DEFAULT_DELIMITER {
+            out.push(self.delimiter);
+        }
+        out.push(']');
+        if let Some(fields) = &self.fields {
+            out.push('{');
+            for (index, field) in fields.iter().enumerate() {
+                if index > 0 {
+                    out.push(self.delimiter);
+                }
+                field.render(out);
+            }
+            out.push('}');
+        }
+    }
+
+    /// Rows follow this header on their own lines.
+    fn is_tabular(&self) -> bool {
+        self.fields
+            .as_ref()
+            .is_some_and(|fields| !fields.is_empty())
+    }
+}
+
+impl Scalar {
+    /// `value` written as the encoder would, for a line using `delimiter`.
+    #[must_use]
+    pub fn new(value: &JsonPrimitive, delimiter: char) -> Self {
+        Self {
+            raw: encode_primitive(value, delimiter, QuotePolicy::Minimal),
+            value: value.clone(),
+        }
+    }
+
+    fn parse(token: &str) -> Result<Self> {
+        Ok(Self {
+            raw: token.to_string(),
+            value: parse_primitive_token(token)?,
+        })
+    }
+
+    /// The value as written.
+    #[must_use]
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    #[must_use]
+    pub const fn value(&self) -> &JsonPrimitive {
+        &self.value
+    }
+
+    /// Written inside double quotes.
+    #[must_use]
+    pub fn is_quoted(&self) -> bool {
+        self.raw.starts_with(DOUBLE_QUOTE)
+    }
+}
+
+fn quote(text: &str) -> String {
+    format!("{DOUBLE_QUOTE}{}{DOUBLE_QUOTE}", escape_string(text))
+}
+
+fn render(kind: &LineKind) -> String {
+    let mut out = String::new();
+    match kind {
+        LineKind::Blank => {}
+        LineKind::Comment(text) => {
+            out.push(COMMENT_MARKER);
+            out.push_str(text);
+        }
+        LineKind::Row(row) => join_values(&row.values, row.delimiter, &mut out),
+        LineKind::Entry(entry) => {
+            if entry.list_item {
+                out.push_str(LIST_ITEM_MARKER);
+                if entry.key.is_some() || entry.header.is_some() || !entry.values.is_empty() {
+                    out.push(' ');
+                }
+            }
+            if let Some(key) = &entry.key {
+                key.render(&mut out);
+            }
+            if let Some(header) = &entry.header {
+                header.render(&mut out);
+            }
+            let keyed = entry.key.is_some() || entry.header.is_some();
+            if keyed {
+                out.push(COLON);
+            }
+            if !entry.values.is_empty() {
+                if keyed {
+                    out.push(' ');
+                }
+                let delimiter = entry
+                    .header
+                    .as_ref()
+                    .map_or(DEFAULT_DELIMITER, |header| header.delimiter);
+                join_values(&entry.values, delimiter, &mut out);
+            }
+        }
+    }
+    out
+}
+
+fn join_values(values: &[Scalar], delimiter: char, out: &mut String) {
+    for (index, value) in values.iter().enumerate() {
+        if index > 0 {
+            out.push(delimiter);
+        }
+        out.push_str(&value.raw);
+    }
+}
+
+fn collect_lines<'a>(nodes: &'a [Node], lines: &mut Vec<&'a Line>) {
+    for node in nodes {
+        lines.push(&node.line);
+        collect_lines(&node.children, lines);
+    }
+}
+
+fn find_key<'a>(nodes: &'a [Node], key: &str) -> Option<&'a Node> {
+    nodes.iter().find(|node| node.key() == Some(key))
+}
+
+fn find_key_mut<'a>(nodes: &'a mut [Node], key: &str) -> Option<&'a mut Node> {
+    nodes.iter_mut().find(|node| node.key() == Some(key))
+}
+
+#[derive(Clone, Copy)]
+struct RawLine<'a> {
+    number: usize,
+    text: &'a str,
+    ending: &'a str,
+    indent: usize,
+}
+
+impl RawLine<'_> {
+    fn content(&self) -> &str {
+        self.text[self.indent..].trim_end()
+    }
+
+    fn into_line(self, kind: LineKind) -> Line {
+        Line {
+            number: self.number,
+            text: self.text.to_string(),
+            ending: self.ending.to_string(),
+            indent: self.indent,
+            kind,
+        }
+    }
+}
+
+struct Builder<'a> {
+    lines: Vec<RawLine<'a>>,
+    index: usize,
+}
+
+/// Where lines are being nested: under a line with this indentation, whose
+/// rows use `table`'s delimiter if it is a tabular header.
+#[derive(Clone, Copy)]
+struct Parent {
+    indent: usize,
+    table: Option<char>,
+}
+
+impl<'a> Builder<'a> {
+    /// Nodes until the first content line that does not belong under
+    /// `parent`, or the end of input for the top level.
+    fn nodes(&mut self, parent: Option<Parent>) -> Result<Vec<Node>> {
+        let table = parent.and_then(|parent| parent.table);
+        let mut nodes = Vec::new();
+        loop {
+            let next = (self.index..self.lines.len()).find(|&index| {
+                let content = self.lines[index].content();
+                !content.is_empty() && (table.is_some() || !content.starts_with(COMMENT_MARKER))
+            });
+            let belongs = next.is_some_and(|index| {
+                parent.is_none_or(|parent| self.lines[index].indent > parent.indent)
+            });
+            let end = match next {
+                Some(index) if belongs => index,
+                None if parent.is_none() => self.lines.len(),
+                _ => return Ok(nodes),
+            };
+            while self.index < end {
+                let raw = self.take();
+                let content = raw.content();
+                let kind = content
+                    .strip_prefix(COMMENT_MARKER)
+                    .map_or(LineKind::Blank, |text| LineKind::Comment(text.to_string()));
+                nodes.push(Node::new(raw.into_line(kind)));
+            }
+            if !belongs {
+                return Ok(nodes);
+            }
+
+            let raw = self.take();
+            let number = raw.number;
+            let kind = parse_content(raw.content(), table).map_err(|err| {
+                err.at_line(number)
+                    .with_source(|_| Some(self.lines[number - 1].text))
+            })?;
+            let line = raw.into_line(kind);
+            let table = line
+                .entry()
+                .and_then(|entry| entry.header.as_ref().filter(|_| entry.values.is_empty()))
+                .filter(|header| header.is_tabular())
+                .map(|header| header.delimiter);
+            let children = self.nodes(Some(Parent {
+                indent: line.indent,
+                table,
+            }))?;
+            nodes.push(Node { line, children });
+        }
+    }
+
+    fn take(&mut self) -> RawLine<'a> {
+        let raw = self.lines[self.index];
+        self.index += 1;
+        raw
+    }
+}
+
+/// Parse the content of a line, which is a row if it sits under a tabular
+/// header and reads as one.
+fn parse_content(content: &str, table: Option<char>) -> Result<LineKind> {
+    if let Some(delimiter) = table.filter(|&delimiter| is_row(content, delimiter)) {
+        let values = parse_delimited_values(content, delimiter)
+            .into_iter()
+            .map(Scalar::parse)
+            .collect::<Result<_>>()?;
+        return Ok(LineKind::Row(Row { delimiter, values }));
+    }
+
+    let mut entry = Entry::default();
+    let mut rest = content;
+    if content == LIST_ITEM_MARKER {
+        entry.list_item = true;
+        return Ok(LineKind::Entry(entry));
+    }
+    if let Some(after_marker) = content.strip_prefix(LIST_ITEM_PREFIX) {
+        entry.list_item = true;
+        rest = after_marker.trim_start();
+    }
+
+    let header = match parse_array_header_line(rest, DEFAULT_DELIMITER)? {
+        Some(header) => Some(header),
+        None => parse_unmarked_array_header(rest, DEFAULT_DELIMITER)?,
+    };
+    if let Some(parsed) = header {
+        let header = parsed.header;
+        entry.key = header
+            .key
+            .map(|key| Key::parse(&key, header.key_was_quoted));
+        entry.header = Some(Header {
+            length: (header.length != UNMARKED_LENGTH).then_some(header.length),
+            delimiter: header.delimiter,
+            fields: header.fields.map(|fields| {
+                fields
+                    .iter()
+                    .map(|field| Key::parse(&field.name, field.was_quoted))
+                    .collect()
+            }),
+        });
+        entry.values = parsed
+            .inline_values
+            .map(|values| parse_delimited_values(values, header.delimiter))
+            .unwrap_or_default()
+            .into_iter()
+            .map(Scalar::parse)
+            .collect::<Result<_>>()?;
+    } else if is_key_value_line(rest) {
+        let (key, end, quoted) = parse_key_token(rest, 0)?;
+        entry.key = Some(Key::parse(&key, quoted));
+        let value = rest[end..].trim();
+        if !value.is_empty() {
+            entry.values.push(Scalar::parse(value)?);
+        }
+    } else {
+        entry.values.push(Scalar::parse(rest)?);
+    }
+    Ok(LineKind::Entry(entry))
+}
+
+/// A line under a tabular header is a row unless it is a list item or a
+/// key, which has a colon before any delimiter.
+pub(crate) fn is_row(content: &str, delimiter: char) -> bool {
+    content != LIST_ITEM_MARKER
+        && !content.starts_with(LIST_ITEM_PREFIX)
+        && is_data_row(content, delimiter)
+}
@@ -0,0 +1,316 @@
+//! CSV import and export for tabular TOON sections.
+//!
+//! Rows are objects holding only primitive values, as in a `key[N]{a,b}:`
+//! table. [`CsvDialect`] describes how the CSV side is laid out:
+//!
+//! ```
+//! use toon::csv::{CsvDialect, read_table, write_table};
+//!
+//! let dialect = CsvDialect { delimiter: ';', null: "NULL".to_string(), ..CsvDialect::default() };
+//! let rows = read_table("id;name\n1;Ada\n2;NULL\n", &dialect).unwrap();
+//! assert_eq!(toon::encode(toon::JsonValue::Array(rows.clone()), None), "[2]{id,name}:\n  1,Ada\n  2,null");
+//! assert_eq!(write_table(&rows, &dialect).unwrap(), "id;name\n1;Ada\n2;NULL\n");
+//! ```
+
+use crate::encode::primitives::encode_primitive;
+use crate::error::{Result, ToonError};
+use crate::options::QuotePolicy;
+use crate::shared::constants::{DEFAULT_DELIMITER, FALSE_LITERAL, TRUE_LITERAL};
+use crate::shared::literal_utils::{is_numeric_like, is_numeric_literal};
+use crate::{JsonArray, JsonValue, Number, StringOrNumberOrBoolOrNull};
+
+/// Layout of a CSV file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvDialect {
+    /// Field separator. Defaults to `,`.
+    pub delimiter: char,
+    /// Character wrapping fields that contain special characters; doubled
+    /// inside a quoted field. Defaults to `"`.
+    pub quote: char,
+    /// Whether the first record names the columns. Without a header,
+    /// columns are named `col1`, `col2`, ... Defaults to `true`.
+    pub has_header: bool,
+    /// Unquoted field text that stands for `null`. Defaults to the empty
+    /// string.
+    pub null: String,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        Self {
+            delimiter: DEFAULT_DELIMITER,
+            quote: '"',
+            has_header: true,
+            null: String::new(),
+        }
+    }
+}
+
+/// Parse CSV text into rows.
+///
+/// Unquoted fields are typed like TOON tokens: the null text becomes `null`,
+/// `true`/`false` become booleans and numeric text becomes a number. Quoted
+/// fields are always strings.
+///
+/// # Errors
+///
+/// Returns an error if a quoted field is unterminated, the delimiter and
+/// quote are the same character, the header has an empty or repeated
+/// name, or a record has more fields than the header.
+pub fn read_table(input: &str, dialect: &CsvDialect) -> Result<JsonArray> {
+    check_dialect(dialect)?;
+    let mut records = parse_records(input, dialect)?.into_iter();
+    let mut header: Vec<String> = Vec::new();
+    if dialect.has_header {
+        if let Some((line, fields)) = records.next() {
+            for field in fields {
+                if field.text.is_empty() {
+                    return Err(ToonError::parse(
+                        line,
+                        format!("CSV header column {} has no name", header.len() + 1),
+                    ));
+                }
+                if header.contains(&field.text) {
+                    return Err(ToonError::parse(
+                        line,
+                        format!("CSV header repeats column `{}`", field.text),
+                    ));
+                }
+                header.push(field.text);
+            }
+        }
+    }
+
+    let mut rows = Vec::new();
+    for (line, fields) in records {
+        if dialect.has_header && fields.len() > header.len() {
+            return Err(ToonError::parse(
+                line,
+                format!(
+                    "CSV record has {} fields, header has {}",
+                    fields.len(),
+                    header.len()
+                ),
+            ));
+        }
+        let mut entries = Vec::with_capacity(fields.len());
+        for (index, field) in fields.into_iter().enumerate() {
+            let name = header
+                .get(index)
+                .cloned()
+                .unwrap_or_else(|| format!("col{}", index + 1));
+            entries.push((name, field.into_value(dialect)));
+        }
+        for name in header.iter().skip(entries.len()) {
+            entries.push((
+                name.clone(),
+                JsonValue::Primitive(StringOrNumberOrBoolOrNull::Null),
+            ));
+        }
+        rows.push(JsonValue::Object(entries));
+    }
+    Ok(rows)
+}
+
+/// Render rows as CSV, one record per line with a trailing newline.
+///
+/// Columns are the union of row keys in first-seen order; a row missing a
+/// column gets the null text. Strings are quoted when they contain the
+/// delimiter, quote or a line break, have surrounding spaces, or would read
+/// back as another type.
+///
+/// # Errors
+///
+/// Returns an error if a row is not an object, a cell is not a primitive, or
+/// the delimiter and quote are the same character.
+pub fn write_table(rows: &JsonArray, dialect: &CsvDialect) -> Result<String> {
+    check_dialect(dialect)?;
+    let mut header: Vec<&str> = Vec::new();
+    for (index, row) in rows.iter().enumerate() {
+        let JsonValue::Object(entries) = row else {
+            return Err(ToonError::invalid_value(
+                format!("[{index}]"),
+                "CSV rows must be objects",
+            ));
+        };
+        for (key, value) in entries {
+            if !matches!(value, JsonValue::Primitive(_)) {
+                return Err(ToonError::invalid_value(
+                    format!("[{index}].{key}"),
+                    "CSV cells must be primitives",
+                ));
+            }
+            if !header.contains(&key.as_str()) {
+                header.push(key);
+            }
+        }
+    }
+
+    let mut out = String::new();
+    if dialect.has_header {
+        let names: Vec<String> = header
+            .iter()
+            .map(|name| quote_field(name, true, dialect))
+            .collect();
+        push_record(&mut out, &names, dialect);
+    }
+    for row in rows {
+        let JsonValue::Object(entries) = row else {
+            continue;
+        };
+        let cells: Vec<String> = header
+            .iter()
+            .map(|name| {
+                match entries
+                    .iter()
+                    .find(|(key, _)| key == name)
+                    .map(|(_, value)| value)
+                {
+                    Some(JsonValue::Primitive(StringOrNumberOrBoolOrNull::String(text))) => {
+                        quote_field(text, false, dialect)
+                    }
+                    Some(JsonValue::Primitive(StringOrNumberOrBoolOrNull::Null)) | None => {
+                        dialect.null.clone()
+                    }
+                    Some(JsonValue::Primitive(primitive)) => {
+                        encode_primitive(primitive, dialect.delimiter, QuotePolicy::Minimal)
+                    }
+                    Some(_) => String::new(),
+                }
+            })
+            .collect();
+        push_record(&mut out, &cells, dialect);
+    }
+    Ok(out)
+}
+
+fn check_dialect(dialect: &CsvDialect) -> Result<()> {
+    if dialect.delimiter == dialect.quote {
+        return Err(ToonError::message(format!(
+            "CSV delimiter and quote must differ (both are {:?})",
+            dialect.delimiter
+        )));
+    }
+    Ok(())
+}
+
+fn push_record(out: &mut String, cells: &[String], dialect: &CsvDialect) {
+    for (index, cell) in cells.iter().enumerate() {
+        if index > 0 {
+            out.push(dialect.delimiter);
+        }
+        out.push_str(cell);
+    }
+    out.push('\n');
+}
+
+/// Quote `text` if it would not read back as the same string. Header names
+/// are never typed, so only structural characters force quotes there.
+fn quote_field(text: &str, header: bool, dialect: &CsvDialect) -> String {
+    let structural = text.is_empty() && !header && dialect.null.is_empty()
+        || text.contains(dialect.delimiter)
+        || text.contains(dialect.quote)
+        || text.contains(['\n', '\r'])
+        || text.trim() != text;
+    let ambiguous = !header && (text == dialect.null || is_typed_literal(text));
+    if !structural && !ambiguous {
+        return text.to_string();
+    }
+    let quote = dialect.quote.to_string();
+    let doubled = format!("{quote}{quote}");
+    format!("{quote}{}{quote}", text.replace(&quote, &doubled))
+}
+
+fn is_typed_literal(text: &str) -> bool {
+    text == TRUE_LITERAL || text == FALSE_LITERAL || is_numeric_like(text)
+}
+
+struct Field {
+    text: String,
+    quoted: bool,
+}
+
+impl Field {
+    fn into_value(self, dialect: &CsvDialect) -> JsonValue {
+        if self.quoted {
+            return JsonValue::from(self.text);
+        }
+        if self.text == dialect.null {
+            return JsonValue::Primitive(StringOrNumberOrBoolOrNull::Null);
+        }
+        match self.text.as_str() {
+            TRUE_LITERAL => JsonValue::from(true),
+            FALSE_LITERAL => JsonValue::from(false),
+            text if is_numeric_literal(text) && text.trim() == text => {
+                JsonValue::from(Number::parse_literal(text))
+            }
+            _ => JsonValue::from(self.text),
+        }
+    }
+}
+
+/// Split `input` into records of fields, each with its 1-based start line.
+/// Blank lines are skipped.
+fn parse_records(input: &str, dialect: &CsvDialect) -> Result<Vec<(usize, Vec<Field>)>> {
+    let mut records = Vec::new();
+    let mut chars = input.chars().peekable();
+    let mut line = 1;
+    while chars.peek().is_some() {
+        let start_line = line;
+        let mut fields = Vec::new();
+        let mut field = Field {
+            text: String::new(),
+            quoted: false,
+        };
+        let mut in_quotes = false;
+        loop {
+            let Some(ch) = chars.next() else {
+                if in_quotes {
+                    return Err(ToonError::parse(
+                        start_line,
+                        "unterminated quoted CSV field",
+                    ));
+                }
+                break;
+            };
+            if ch == '\n' {
+                line += 1;
+            }
+            if in_quotes {
+                if ch == dialect.quote {
+                    if chars.peek() == Some(&dialect.quote) {
+                        chars.next();
+                        field.text.push(ch);
+                    } else {
+                        in_quotes = false;
+                    }
+                } else {
+                    field.text.push(ch);
+                }
+            } else if ch == dialect.quote && field.text.is_empty() && !field.quoted {
+                in_quotes = true;
+                field.quoted = true;
+            } else if ch == dialect.delimiter {
+                fields.push(std::mem::replace(
+                    &mut field,
+                    Field {
+                        text: String::new(),
+                        quoted: false,
+                    },
+                ));
+            } else if ch == '\n' {
+                break;
+            } else if ch == '\r' && chars.peek() == Some(&'\n') {
+                // Line ending handled by the following `\n`.
+            } else {
+                field.text.push(ch);
+            }
+        }
+        if fields.is_empty() && field.text.is_empty() && !field.quoted {
+            continue;
+        }
+        fields.push(field);
+        records.push((start_line, fields));
+    }
+    Ok(records)
+}
@@ -0,0 +1,592 @@
+//! A `serde::Deserializer` over decoded [`JsonValue`]s, so TOON decodes
+//! into any `T: Deserialize` without a `serde_json::Value` round trip.
+//!
+//! ```
+//! #[derive(serde::Deserialize, Debug, PartialEq)]
+//! struct User {
+//!     id: u32,
+//!     name: String,
+//! }
+//!
+//! let users: Vec<User> = toon::from_str("[2]{id,name}:\n  1,Ada\n  2,Bob").unwrap();
+//! assert_eq!(users[1], User { id: 2, name: "Bob".to_string() });
+//! ```
+
+use serde::de::value::StringDeserializer;
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+use crate::error::{Result, ToonError};
+use crate::exact::MAX_SAFE_INTEGER;
+use crate::{JsonValue, Number, StringOrNumberOrBoolOrNull, try_decode};
+
+/// Decode TOON text into `T` with the default decode options.
+///
+/// Integers, and whole floats within the `f64`-safe integer range,
+/// deserialize into integer fields; use [`from_value`] with [`try_decode`]
+/// for other options.
+///
+/// # Errors
+///
+/// Returns an error if decoding fails or the value does not match `T`.
+pub fn from_str<T: DeserializeOwned>(input: &str) -> Result<T> {
+    from_value(try_decode(input, None)?)
+}
+
+/// Deserialize `T` from a decoded value.
+///
+/// # Errors
+///
+/// Returns an error if the value does not match `T`.
+pub fn from_value<T: DeserializeOwned>(value: JsonValue) -> Result<T> {
+    T::deserialize(value)
+}
+
+/// Builds a value from any self-describing format, keeping map entries in
+/// the order the format yields them, repeated keys included.
+///
+/// ```
+/// let value: toon::JsonValue = serde_json::from_str(r#"{"b": [1, null], "a": 2.5}"#).unwrap();
+/// assert_eq!(toon::encode(value, None), "b[2]: 1,null\na: 2.5");
+/// ```
+impl<'de> de::Deserialize<'de> for JsonValue {
+    fn deserialize<D: de::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = JsonValue;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("any JSON value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> std::result::Result<JsonValue, E> {
+        Ok(JsonValue::from(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> std::result::Result<JsonValue, E> {
+        Ok(JsonValue::from(value))
+    }
+
+    fn visit_i128<E>(self, value: i128) -> std::result::Result<JsonValue, E> {
+        Ok(JsonValue::from(Number::from(value)))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> std::result::Result<JsonValue, E> {
+        Ok(JsonValue::from(value))
+    }
+
+    fn visit_u128<E>(self, value: u128) -> std::result::Result<JsonValue, E> {
+        Ok(JsonValue::from(Number::from(value)))
+    }
+
+    /// Non-finite numbers are kept, as [`to_value`](crate::to_value) keeps
+    /// them.
+    fn visit_f64<E>(self, value: f64) -> std::result::Result<JsonValue, E> {
+        Ok(JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(
+            Number::F64(value),
+        )))
+    }
+
+    fn visit_str<E>(self, value: &str) -> std::result::Result<JsonValue, E> {
+        Ok(JsonValue::from(value))
+    }
+
+    fn visit_string<E>(self, value: String) -> std::result::Result<JsonValue, E> {
+        Ok(JsonValue::from(value))
+    }
+
+    fn visit_none<E>(self) -> std::result::Result<JsonValue, E> {
+        Ok(JsonValue::from(()))
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<JsonValue, D::Error> {
+        de::Deserialize::deserialize(deserializer)
+    }
+
+    fn visit_unit<E>(self) -> std::result::Result<JsonValue, E> {
+        Ok(JsonValue::from(()))
+    }
+
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<JsonValue, D::Error> {
+        de::Deserialize::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(
+        self,
+        mut seq: A,
+    ) -> std::result::Result<JsonValue, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(JsonValue::Array(items))
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(
+        self,
+        mut map: A,
+    ) -> std::result::Result<JsonValue, A::Error> {
+        let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(JsonValue::Object(entries))
+    }
+}
+
+impl JsonValue {
+    /// Parse JSON text.
+    ///
+    /// A repeated key keeps its last value at the first key's position, as
+    /// with `serde_json::Value`. With `bignum`, numbers are read from their
+    /// source text as TOON decodes them, so integers beyond 64 bits and
+    /// decimals that `f64` would round stay exact.
+    ///
+    /// ```
+    /// let value = toon::JsonValue::from_json(r#"{"a": 1, "b": [true], "a": 2}"#).unwrap();
+    /// assert_eq!(toon::encode(value, None), "a: 2\nb[1]: true");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `text` is not valid JSON.
+    pub fn from_json(text: &str) -> Result<Self> {
+        let mut value: Self =
+            serde_json::from_str(text).map_err(|err| ToonError::json_parse(&err))?;
+        #[cfg(feature = "bignum")]
+        restore_number_literals(&mut value, &mut number_literals(text));
+        value.normalize(crate::canonical::NormalizeOptions {
+            sort_keys: false,
+            dedup_keys: true,
+            nfc_keys: false,
+            fold_negative_zero: false,
+        });
+        Ok(value)
+    }
+}
+
+/// The number literals of valid JSON `text`, in document order. Outside
+/// strings, every run starting with `-` or a digit is one.
+#[cfg(feature = "bignum")]
+fn number_literals(text: &str) -> impl Iterator<Item = &str> {
+    let bytes = text.as_bytes();
+    let mut index = 0;
+    std::iter::from_fn(move || {
+        while index < bytes.len() {
+            match bytes[index] {
+                b'"' => {
+                    index += 1;
+                    while bytes[index] != b'"' {
+                        index += if bytes[index] == b'\\' { 2 } else { 1 };
+                    }
+                    index += 1;
+                }
+                b'-' | b'0'..=b'9' => {
+                    let start = index;
+                    while index < bytes.len()
+                        && matches!(bytes[index], b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+                    {
+                        index += 1;
+                    }
+                    return Some(&text[start..index]);
+                }
+                _ => index += 1,
+            }
+        }
+        None
+    })
+}
+
+/// Replace each number in `value`, in document order, with the next literal.
+#[cfg(feature = "bignum")]
+fn restore_number_literals<'a>(
+    value: &mut JsonValue,
+    literals: &mut impl Iterator<Item = &'a str>,
+) {
+    match value {
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(number)) => {
+            if let Some(literal) = literals.next() {
+                *number = Number::parse_literal(literal);
+            }
+        }
+        JsonValue::Primitive(_) => {}
+        JsonValue::Array(items) => {
+            for item in items {
+                restore_number_literals(item, literals);
+            }
+        }
+        JsonValue::Object(entries) => {
+            for (_, item) in entries {
+                restore_number_literals(item, literals);
+            }
+        }
+    }
+}
+
+impl de::Error for ToonError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Self::message(msg.to_string())
+    }
+}
+
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::float_cmp
+)]
+fn visit_number<'de, V: Visitor<'de>>(number: &Number, visitor: V) -> Result<V::Value> {
+    let number = match number {
+        Number::I64(value) => return visitor.visit_i64(*value),
+        Number::U64(value) => return visitor.visit_u64(*value),
+        Number::F64(value) => *value,
+        Number::Raw(text) => return visit_number(&Number::parse_literal(text), visitor),
+        #[cfg(feature = "bignum")]
+        Number::BigInt(value) => {
+            use bigdecimal::ToPrimitive;
+            if let Some(value) = value.to_i128() {
+                return visitor.visit_i128(value);
+            }
+            if let Some(value) = value.to_u128() {
+                return visitor.visit_u128(value);
+            }
+            return visitor.visit_f64(number.as_f64());
+        }
+        #[cfg(feature = "bignum")]
+        Number::BigDecimal(_) => return visitor.visit_f64(number.as_f64()),
+    };
+    let is_safe_integer = number.fract() == 0.0
+        && number.abs() <= MAX_SAFE_INTEGER
+        && !(number == 0.0 && number.is_sign_negative());
+    if !is_safe_integer {
+        visitor.visit_f64(number)
+    } else if number < 0.0 {
+        visitor.visit_i64(number as i64)
+    } else {
+        visitor.visit_u64(number as u64)
+    }
+}
+
+fn unexpected(value: &JsonValue) -> de::Unexpected<'_> {
+    match value {
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Null) => de::Unexpected::Unit,
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Bool(value)) => {
+            de::Unexpected::Bool(*value)
+        }
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(value)) => match value {
+            Number::I64(value) => de::Unexpected::Signed(*value),
+            Number::U64(value) => de::Unexpected::Unsigned(*value),
+            Number::F64(value) => de::Unexpected::Float(*value),
+            Number::Raw(_) => de::Unexpected::Float(value.as_f64()),
+            #[cfg(feature = "bignum")]
+            big @ (Number::BigInt(_) | Number::BigDecimal(_)) => {
+                de::Unexpected::Float(big.as_f64())
+            }
+        },
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::String(value)) => {
+            de::Unexpected::Str(value)
+        }
+        JsonValue::Array(_) => de::Unexpected::Seq,
+        JsonValue::Object(_) => de::Unexpected::Map,
+    }
+}
+
+impl<'de> de::Deserializer<'de> for JsonValue {
+    type Error = ToonError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Self::Primitive(StringOrNumberOrBoolOrNull::Null) => visitor.visit_unit(),
+            Self::Primitive(StringOrNumberOrBoolOrNull::Bool(value)) => visitor.visit_bool(value),
+            Self::Primitive(StringOrNumberOrBoolOrNull::Number(value)) => {
+                visit_number(&value, visitor)
+            }
+            Self::Primitive(StringOrNumberOrBoolOrNull::String(value)) => {
+                visitor.visit_string(value)
+            }
+            Self::Array(items) => {
+                let len = items.len();
+                let mut seq = SeqDeserializer {
+                    items: items.into_iter(),
+                };
+                let value = visitor.visit_seq(&mut seq)?;
+                if seq.items.len() == 0 {
+                    Ok(value)
+                } else {
+                    Err(de::Error::invalid_length(len, &"fewer elements in array"))
+                }
+            }
+            Self::Object(entries) => visitor.visit_map(MapDeserializer {
+                entries: entries.into_iter(),
+                value: None,
+            }),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Self::Primitive(StringOrNumberOrBoolOrNull::Null) => visitor.visit_none(),
+            other => visitor.visit_some(other),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Unit variants are strings; other variants are single-key objects
+    /// (`Flag:\n  urgent: true`), as in `serde_json`.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self {
+            Self::Primitive(StringOrNumberOrBoolOrNull::String(variant)) => {
+                visitor.visit_enum(EnumDeserializer {
+                    variant,
+                    value: None,
+                })
+            }
+            Self::Object(mut entries) if entries.len() == 1 => {
+                let (variant, value) = entries.remove(0);
+                visitor.visit_enum(EnumDeserializer {
+                    variant,
+                    value: Some(value),
+                })
+            }
+            other => Err(de::Error::invalid_type(
+                unexpected(&other),
+                &"a string or an object with one key",
+            )),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+impl IntoDeserializer<'_, ToonError> for JsonValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+struct SeqDeserializer {
+    items: std::vec::IntoIter<JsonValue>,
+}
+
+impl<'de> de::SeqAccess<'de> for SeqDeserializer {
+    type Error = ToonError;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>> {
+        self.items
+            .next()
+            .map(|item| seed.deserialize(item))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+struct MapDeserializer {
+    entries: std::vec::IntoIter<(String, JsonValue)>,
+    value: Option<JsonValue>,
+}
+
+impl<'de> de::MapAccess<'de> for MapDeserializer {
+    type Error = ToonError;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        let Some((key, value)) = self.entries.next() else {
+            return Ok(None);
+        };
+        self.value = Some(value);
+        seed.deserialize(MapKeyDeserializer { key }).map(Some)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| ToonError::message("map value requested before its key"))?;
+        seed.deserialize(value)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+/// Object keys, which parse into integer and boolean map keys as in
+/// `serde_json`.
+struct MapKeyDeserializer {
+    key: String,
+}
+
+macro_rules! deserialize_parsed_key {
+    ($($method:ident => $visit:ident),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+                let parsed = self.key.parse().map_err(|_| {
+                    <ToonError as de::Error>::invalid_value(de::Unexpected::Str(&self.key), &visitor)
+                })?;
+                visitor.$visit(parsed)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for MapKeyDeserializer {
+    type Error = ToonError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_string(self.key)
+    }
+
+    deserialize_parsed_key! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_enum(StringDeserializer::new(self.key))
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple tuple_struct
+        map struct identifier ignored_any
+    }
+}
+
+struct EnumDeserializer {
+    variant: String,
+    value: Option<JsonValue>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer {
+    type Error = ToonError;
+    type Variant = VariantDeserializer;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, VariantDeserializer)> {
+        let variant = seed.deserialize(StringDeserializer::<ToonError>::new(self.variant))?;
+        Ok((variant, VariantDeserializer { value: self.value }))
+    }
+}
+
+struct VariantDeserializer {
+    value: Option<JsonValue>,
+}
+
+impl<'de> de::VariantAccess<'de> for VariantDeserializer {
+    type Error = ToonError;
+
+    fn unit_variant(self) -> Result<()> {
+        match self.value {
+            None | Some(JsonValue::Primitive(StringOrNumberOrBoolOrNull::Null)) => Ok(()),
+            Some(other) => Err(de::Error::invalid_type(unexpected(&other), &"unit variant")),
+        }
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        self.value.map_or_else(
+            || {
+                Err(de::Error::invalid_type(
+                    de::Unexpected::UnitVariant,
+                    &"newtype variant",
+                ))
+            },
+            |value| seed.deserialize(value),
+        )
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        match self.value {
+            Some(value @ JsonValue::Array(_)) => de::Deserializer::deserialize_any(value, visitor),
+            Some(other) => Err(de::Error::invalid_type(
+                unexpected(&other),
+                &"tuple variant",
+            )),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"tuple variant",
+            )),
+        }
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self.value {
+            Some(value @ JsonValue::Object(_)) => de::Deserializer::deserialize_any(value, visitor),
+            Some(other) => Err(de::Error::invalid_type(
+                unexpected(&other),
+                &"struct variant",
+            )),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"struct variant",
+            )),
+        }
+    }
+}
@@ -23,16 +23,17 @@
 //! ```
 
 use crate::JsonStreamEvent;
-use crate::decode::decoders as decoder_impl;
+use crate::decode::decoders::{self as decoder_impl, check_length_marker};
+use crate::decode::limits::LimitCounter;
 use crate::decode::parser::{
     is_array_header_content, is_key_value_content, parse_array_header_line, parse_key_token,
-    parse_primitive_token,
+    parse_primitive_token_with,
 };
 use crate::decode::scanner::{
     Depth, ParsedLine, StreamingScanState, create_scan_state, parse_line_incremental,
 };
 use crate::error::{Result, ToonError};
-use crate::options::DecodeStreamOptions;
+use crate::options::{DecodeStreamOptions, Strictness};
 use crate::shared::constants::{COLON, DEFAULT_DELIMITER, LIST_ITEM_PREFIX};
 use crate::shared::string_utils::find_closing_quote;
 use asupersync::stream::{Stream, StreamExt, iter};
@@ -83,13 +84,15 @@ pub struct AsyncDecodeStream<I: Iterator<Item = String>> {
     /// Stack of object depths for tracking nested structures
     context_stack: Vec<ObjectDepth>,
     /// Buffer for lines that need batch processing (arrays)
-    line_buffer: Vec<ParsedLine>,
+    line_buffer: Vec<ParsedLine<'static>>,
     /// Whether we've finished reading all lines
     lines_exhausted: bool,
     /// Error encountered during processing
     error: Option<ToonError>,
     /// Last emitted depth for tracking structure boundaries
     last_depth: Option<Depth>,
+    /// Depth and values of the events yielded so far, for the limits
+    limit_counter: LimitCounter,
 }
 
 impl<I: Iterator<Item = String>> AsyncDecodeStream<I> {
@@ -107,6 +110,7 @@ impl<I: Iterator<Item = String>> AsyncDecodeStream<I> {
             lines_exhausted: false,
             error: None,
             last_depth: None,
+            limit_counter: LimitCounter::default(),
         }
     }
 
@@ -115,9 +119,22 @@ impl<I: Iterator<Item = String>> AsyncDecodeStream<I> {
         self.options.indent.unwrap_or(2)
     }
 
-    /// Get the strict mode setting
-    fn strict(&self) -> bool {
-        self.options.strict.unwrap_or(true)
+    /// Get the strictness setting
+    fn strictness(&self) -> Strictness {
+        self.options.strictness.unwrap_or_default()
+    }
+
+    /// Process the next available event or line, checking it against the limits
+    fn next_checked(&mut self) -> Result<Option<JsonStreamEvent>> {
+        let event = self.process_next()?;
+        if let (Some(limits), Some(event)) = (&self.options.limits, &event) {
+            if let Err(err) = self.limit_counter.observe(limits, event) {
+                self.state = DecoderState::Finished;
+                self.event_queue.clear();
+                return Err(err);
+            }
+        }
+        Ok(event)
     }
 
     /// Process the next available event or line
@@ -150,8 +167,15 @@ impl<I: Iterator<Item = String>> AsyncDecodeStream<I> {
 
         // Parse the line (capture values before borrowing scan_state)
         let indent_size = self.indent_size();
-        let strict = self.strict();
-        let parsed = parse_line_incremental(&raw_line, &mut self.scan_state, indent_size, strict)?;
+        let strictness = self.strictness();
+        let parsed = parse_line_incremental(
+            &raw_line,
+            &mut self.scan_state,
+            indent_size,
+            strictness.tabs,
+            strictness.indentation,
+            self.options.max_line_length,
+        )?;
 
         // Skip blank lines
         let Some(line) = parsed else {
@@ -174,7 +198,10 @@ impl<I: Iterator<Item = String>> AsyncDecodeStream<I> {
     }
 
     /// Process the first line to determine document structure
-    fn process_initial_line(&mut self, line: ParsedLine) -> Result<Option<JsonStreamEvent>> {
+    fn process_initial_line(
+        &mut self,
+        line: ParsedLine<'static>,
+    ) -> Result<Option<JsonStreamEvent>> {
         // Check if it's an array header at root level
         if is_array_header_content(&line.content) {
             if let Some(_header_info) = parse_array_header_line(&line.content, DEFAULT_DELIMITER)? {
@@ -199,14 +226,17 @@ impl<I: Iterator<Item = String>> AsyncDecodeStream<I> {
         // Single primitive value
         self.state = DecoderState::Finished;
         Ok(Some(JsonStreamEvent::Primitive {
-            value: parse_primitive_token(line.content.trim())?,
+            value: parse_primitive_token_with(
+                line.content.trim(),
+                self.options.preserve_number_literals.unwrap_or(false),
+            )?,
         }))
     }
 
     /// Process a line in simple object mode
     fn process_simple_object_line(
         &mut self,
-        line: ParsedLine,
+        line: ParsedLine<'static>,
         base_depth: Depth,
     ) -> Result<Option<JsonStreamEvent>> {
         let current_depth = line.depth;
@@ -246,7 +276,7 @@ impl<I: Iterator<Item = String>> AsyncDecodeStream<I> {
     }
 
     /// Process a key-value line and queue events
-    fn process_key_value_line(&mut self, line: &ParsedLine) -> Result<()> {
+    fn process_key_value_line(&mut self, line: &ParsedLine<'static>) -> Result<()> {
         let content = &line.content;
 
         // Handle list items specially
@@ -257,6 +287,10 @@ impl<I: Iterator<Item = String>> AsyncDecodeStream<I> {
             return Ok(());
         }
 
+        if self.options.require_length_markers.unwrap_or(false) {
+            check_length_marker(content, line.line_number)?;
+        }
+
         // Parse key-value
         let (key, end, is_quoted) = parse_key_token(content, 0)?;
         let rest = content[end..].trim();
@@ -272,19 +306,22 @@ impl<I: Iterator<Item = String>> AsyncDecodeStream<I> {
 
         // Key with inline value - can emit incrementally
         self.event_queue.push_back(JsonStreamEvent::Key {
-            key,
+            key: key.into_owned(),
             was_quoted: is_quoted,
         });
         self.event_queue.push_back(JsonStreamEvent::Primitive {
-            value: parse_primitive_token(rest)?,
+            value: parse_primitive_token_with(
+                rest,
+                self.options.preserve_number_literals.unwrap_or(false),
+            )?,
         });
 
         Ok(())
     }
 
     /// Check if a line is a key-value line
-    fn is_key_value_line(line: &ParsedLine) -> bool {
-        let content = line.content.as_str();
+    fn is_key_value_line(line: &ParsedLine<'_>) -> bool {
+        let content = &*line.content;
 
         // Handle list items
         if content.starts_with(LIST_ITEM_PREFIX) {
@@ -331,25 +368,24 @@ impl<I: Iterator<Item = String>> AsyncDecodeStream<I> {
     fn batch_decode_remaining(&mut self) -> Result<Option<JsonStreamEvent>> {
         // Collect all remaining lines (capture values before borrowing scan_state)
         let indent_size = self.indent_size();
-        let strict = self.strict();
+        let strictness = self.strictness();
         for raw_line in self.lines.by_ref() {
-            if let Some(line) =
-                parse_line_incremental(&raw_line, &mut self.scan_state, indent_size, strict)?
-            {
+            if let Some(line) = parse_line_incremental(
+                &raw_line,
+                &mut self.scan_state,
+                indent_size,
+                strictness.tabs,
+                strictness.indentation,
+                self.options.max_line_length,
+            )? {
                 self.line_buffer.push(line);
             }
         }
 
         // Use sync decoder on buffered lines
-        let raw_lines: Vec<String> = self.line_buffer.iter().map(|p| p.raw.clone()).collect();
-
-        let events = decoder_impl::decode_stream_sync(
-            raw_lines,
-            Some(DecodeStreamOptions {
-                indent: self.options.indent,
-                strict: self.options.strict,
-            }),
-        )?;
+        let raw_lines = self.line_buffer.iter().map(|p| &*p.raw);
+
+        let events = decoder_impl::decode_stream_sync(raw_lines, Some(self.options.clone()))?;
 
         // Queue all events
         self.event_queue.extend(events);
@@ -367,7 +403,7 @@ impl<I: Iterator<Item = String> + Unpin> Stream for AsyncDecodeStream<I> {
     fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         // Loop until we either get an event or are truly finished
         loop {
-            match self.process_next() {
+            match self.next_checked() {
                 Ok(Some(event)) => return Poll::Ready(Some(Ok(event))),
                 Ok(None) => {
                     // Check if we're truly done
@@ -431,26 +467,31 @@ pub async fn try_decode_async(
     input: &str,
     options: Option<crate::options::DecodeOptions>,
 ) -> Result<crate::JsonValue> {
-    use crate::decode::event_builder::{build_node_from_events, node_to_json};
-    use crate::decode::expand::expand_paths_safe;
-    use crate::options::{ExpandPathsMode, resolve_decode_options};
+    use crate::decode::event_builder::{
+        build_node_from_events, node_to_json, resolve_duplicate_keys, sort_node_keys,
+    };
+    use crate::decode::expand::expand_paths;
+    use crate::options::{KeyOrder, resolve_decode_options};
 
     let resolved = resolve_decode_options(options);
     let lines: Vec<String> = input.split('\n').map(String::from).collect();
 
-    let events = try_decode_stream_async(
-        lines,
-        Some(DecodeStreamOptions {
-            indent: Some(resolved.indent),
-            strict: Some(resolved.strict),
-        }),
-    )
-    .await?;
+    let events = try_decode_stream_async(lines, Some(resolved.stream_options())).await?;
 
     let mut node = build_node_from_events(events)?;
+    if let Some(policy) = resolved.duplicate_keys {
+        resolve_duplicate_keys(&mut node, policy);
+    }
+
+    node = expand_paths(
+        node,
+        resolved.expand_paths,
+        resolved.on_conflict,
+        resolved.limits.and_then(|limits| limits.max_array_length),
+    )?;
 
-    if resolved.expand_paths == ExpandPathsMode::Safe {
-        node = expand_paths_safe(node, resolved.strict)?;
+    if resolved.key_order == KeyOrder::Sorted {
+        sort_node_keys(&mut node);
     }
 
     Ok(node_to_json(node))
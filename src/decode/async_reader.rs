@@ -0,0 +1,273 @@
+//! Async decoding from an [`AsyncBufRead`], emitting events as lines arrive.
+//!
+//! Lines are handed to a [`ToonParser`], so events match
+//! [`decode_stream_sync`](crate::decode_stream_sync) and come out as soon as
+//! each top-level entry or root array item is complete.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use asupersync::io::{AsyncBufRead, Lines};
+use asupersync::stream::{Stream, StreamExt};
+
+use crate::JsonStreamEvent;
+use crate::decode::push::ToonParser;
+use crate::error::{Result, ToonError};
+use crate::options::DecodeStreamOptions;
+
+/// Async stream of `JsonStreamEvent`s decoded from an [`AsyncBufRead`].
+///
+/// Lines may end in `\n` or `\r\n`. Events of an object document follow each
+/// top-level entry, and events of a root array follow each item; a read
+/// error or the first decode error ends the stream.
+pub struct AsyncReaderDecodeStream<R> {
+    lines: Lines<R>,
+    parser: ToonParser,
+    finished: bool,
+}
+
+impl<R: AsyncBufRead + Unpin> AsyncReaderDecodeStream<R> {
+    /// Create a stream decoding the lines of `reader`.
+    pub fn new(reader: R, options: Option<DecodeStreamOptions>) -> Self {
+        Self {
+            lines: Lines::new(reader),
+            parser: ToonParser::new(options),
+            finished: false,
+        }
+    }
+}
+
+impl<R: AsyncBufRead + Unpin> Stream for AsyncReaderDecodeStream<R> {
+    type Item = Result<JsonStreamEvent>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if let Some(event) = this.parser.next_event() {
+                return Poll::Ready(Some(Ok(event)));
+            }
+            if this.finished {
+                return Poll::Ready(None);
+            }
+            let result = match Pin::new(&mut this.lines).poll_next(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Some(Ok(line))) => this.parser.push_line(line),
+                Poll::Ready(Some(Err(err))) => {
+                    Err(ToonError::io("Failed to read input", None, err))
+                }
+                Poll::Ready(None) => {
+                    this.finished = true;
+                    this.parser.finish()
+                }
+            };
+            if let Err(err) = result {
+                this.finished = true;
+                return Poll::Ready(Some(Err(err)));
+            }
+        }
+    }
+}
+/// Decode TOON read from an [`AsyncBufRead`] into events, collecting the
+/// [`AsyncReaderDecodeStream`].
+///
+/// # Errors
+///
+/// Returns an error if reading fails, a line is not valid UTF-8, or decoding
+/// fails due to malformed input or strict-mode validation errors.
+pub async fn try_decode_stream_from_reader_async<R: AsyncBufRead + Unpin>(
+    reader: R,
+    options: Option<DecodeStreamOptions>,
+) -> Result<Vec<JsonStreamEvent>> {
+    let mut stream = AsyncReaderDecodeStream::new(reader, options);
+    let mut events = Vec::new();
+    while let Some(event) = stream.next().await {
+        events.push(event?);
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use asupersync::io::{AsyncRead, ReadBuf};
+    use std::collections::VecDeque;
+    use std::task::Waker;
+
+    /// Serves one chunk per fill, returning `Pending` between chunks.
+    struct Chunked {
+        chunks: VecDeque<&'static [u8]>,
+        ready: bool,
+    }
+
+    impl Chunked {
+        fn new(chunks: &[&'static str]) -> Self {
+            Self {
+                chunks: chunks.iter().map(|chunk| chunk.as_bytes()).collect(),
+                ready: false,
+            }
+        }
+    }
+
+    impl AsyncRead for Chunked {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            _buf: &mut ReadBuf<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            unreachable!("read through AsyncBufRead")
+        }
+    }
+
+    impl AsyncBufRead for Chunked {
+        fn poll_fill_buf(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<std::io::Result<&[u8]>> {
+            let this = self.get_mut();
+            if !this.ready && !this.chunks.is_empty() {
+                this.ready = true;
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            Poll::Ready(Ok(this.chunks.front().copied().unwrap_or_default()))
+        }
+
+        fn consume(self: Pin<&mut Self>, amt: usize) {
+            let this = self.get_mut();
+            if let Some(chunk) = this.chunks.front_mut() {
+                *chunk = &chunk[amt..];
+                if chunk.is_empty() {
+                    this.chunks.pop_front();
+                    this.ready = false;
+                }
+            }
+        }
+    }
+
+    /// Poll until the stream is pending or done.
+    fn drain<R: AsyncBufRead + Unpin>(
+        stream: &mut AsyncReaderDecodeStream<R>,
+    ) -> (Vec<Result<JsonStreamEvent>>, bool) {
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut items = Vec::new();
+        loop {
+            match Pin::new(&mut *stream).poll_next(&mut cx) {
+                Poll::Ready(Some(item)) => items.push(item),
+                Poll::Ready(None) => return (items, true),
+                Poll::Pending => return (items, false),
+            }
+        }
+    }
+
+    fn decode_all(
+        input: &str,
+        options: Option<DecodeStreamOptions>,
+    ) -> Result<Vec<JsonStreamEvent>> {
+        let mut stream = AsyncReaderDecodeStream::new(input.as_bytes(), options);
+        let (items, done) = drain(&mut stream);
+        assert!(done);
+        items.into_iter().collect()
+    }
+
+    fn decode_sync(
+        input: &str,
+        options: Option<DecodeStreamOptions>,
+    ) -> Result<Vec<JsonStreamEvent>> {
+        crate::try_decode_stream_sync(input.split('\n').map(String::from), options)
+    }
+
+    #[test]
+    fn test_reader_events_match_sync_decoder() {
+        let inputs = [
+            "",
+            "\n\n",
+            "42",
+            "\"just text\"",
+            "a: 1\nb[2]: x,y",
+            "a: 1\nb:\n  c: 2\nd: 3",
+            "a:\n  b: 1\n  c[1]: x\nd: 2\n\ne:",
+            "users[2]{id,name}:\n  1,Ada\n  2,Bob\nnext: true",
+            "[3]{id,tag}:\n  1,a\n  2,b\n  3,c",
+            "[3]:\n  - 1\n  - a: 1\n    b[2]:\n      - x\n      - y: 2\n    c: 3\n  -",
+            "[2]: a,b",
+            "[0]:",
+            "\"quoted key\": 1\nlist[1]:\n  - rows[1]{k}:\n      v\n    after: 1",
+        ];
+        for input in inputs {
+            for text in [input.to_string(), input.replace('\n', "\r\n")] {
+                assert_eq!(
+                    decode_all(&text, None).unwrap(),
+                    decode_sync(input, None).unwrap(),
+                    "input: {text:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_reader_errors_match_sync_decoder() {
+        let duplicates = DecodeStreamOptions {
+            strictness: Some(crate::options::Strictness {
+                duplicate_keys: true,
+                ..crate::options::Strictness::default()
+            }),
+            ..DecodeStreamOptions::default()
+        };
+        let limits = DecodeStreamOptions {
+            limits: Some(crate::options::DecodeLimits {
+                max_depth: Some(2),
+                max_nodes: Some(3),
+                ..crate::options::DecodeLimits::default()
+            }),
+            ..DecodeStreamOptions::default()
+        };
+        let cases = [
+            ("hello\nworld", None),
+            ("a: 1\n\tb: 2", None),
+            ("a:\n   b: 1", None),
+            ("[3]{id}:\n  1\n  2", None),
+            ("[1]{id}:\n  1\n  2", None),
+            ("[2]{id}:\n  1\n\n  2", None),
+            ("[2]:\n  - a\n\n  - b", None),
+            ("[1]:\n  - a\n  - b", None),
+            ("[2]:\n  - a\nafter: 1", None),
+            ("a: 1\nb:\n  c: 1\na: 2", Some(duplicates.clone())),
+            ("a:\n  c: 1\n  c: 2", Some(duplicates)),
+            ("a: 1\nb: 2\nc: 3", Some(limits.clone())),
+            ("a:\n  b:\n    c: 1", Some(limits)),
+        ];
+        for (input, options) in cases {
+            let expected = decode_sync(input, options.clone()).unwrap_err().to_string();
+            let actual = decode_all(input, options).unwrap_err().to_string();
+            assert_eq!(actual, expected, "input: {input:?}");
+        }
+    }
+
+    #[test]
+    fn test_reader_emits_entries_as_lines_arrive() {
+        let reader = Chunked::new(&["[3]{id}:\n  1\n  2\n", "  3\n"]);
+        let mut stream = AsyncReaderDecodeStream::new(reader, None);
+        assert_eq!(drain(&mut stream).0.len(), 0);
+
+        let (items, done) = drain(&mut stream);
+        assert!(!done);
+        let events = items.into_iter().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(events.len(), 9);
+        assert_eq!(events[0], JsonStreamEvent::StartArray { length: 3 });
+
+        let (items, done) = drain(&mut stream);
+        assert!(done);
+        assert_eq!(items.len(), 5);
+
+        // An entry is complete once the next top-level line arrives.
+        let reader = Chunked::new(&["a: 1\nb:\n  c: 2\n", "d: 3\n"]);
+        let mut stream = AsyncReaderDecodeStream::new(reader, None);
+        drain(&mut stream);
+        let (items, done) = drain(&mut stream);
+        assert!(!done);
+        assert_eq!(items.len(), 3);
+        let (items, done) = drain(&mut stream);
+        assert!(done);
+        assert_eq!(items.len(), 8);
+    }
+}
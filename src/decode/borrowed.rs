@@ -0,0 +1,404 @@
+//! Decoding into [`BorrowedValue`], which points into the input instead of
+//! copying keys and strings.
+//!
+//! This mirrors the event decoder in [`decoders`](super::decoders) with the
+//! default options, but builds the tree directly from line slices.
+
+use std::borrow::Cow;
+
+use crate::borrowed::BorrowedValue;
+use crate::decode::parser::{
+    ArrayHeaderInfo, ArrayHeaderParseResult, UNMARKED_LENGTH, is_array_header_content,
+    is_key_value_content, is_key_value_line, parse_array_header_line, parse_borrowed_primitive,
+    parse_delimited_values, parse_key_token,
+};
+use crate::decode::scanner::{BlankLineInfo, Depth, create_scan_state, scan_line};
+use crate::decode::validation::{
+    assert_expected_count, validate_no_blank_lines_in_range, validate_no_extra_list_items,
+    validate_no_extra_tabular_rows,
+};
+use crate::error::{DecodeErrorKind, Result, ToonError};
+use crate::shared::constants::{DEFAULT_DELIMITER, LIST_ITEM_MARKER, LIST_ITEM_PREFIX};
+
+const INDENT: usize = 2;
+
+type Entries<'a> = Vec<(Cow<'a, str>, BorrowedValue<'a>)>;
+
+/// Decode a TOON string into a [`BorrowedValue`] that borrows from `input`.
+///
+/// Keys and strings are copied only when they contain escape sequences.
+/// Decoding follows the default [`DecodeOptions`](crate::DecodeOptions):
+/// strict mode, two-space indentation and no path expansion, so
+/// `decode_borrowed(input)?.into_owned()` equals `try_decode(input, None)?`.
+///
+/// # Errors
+///
+/// Returns an error if decoding fails due to malformed input or strict-mode validation errors.
+pub fn decode_borrowed(input: &str) -> Result<BorrowedValue<'_>> {
+    let mut state = create_scan_state();
+    let mut lines = Vec::new();
+    for raw in input.split('\n') {
+        if let Some((indent, depth)) = scan_line(raw, &mut state, INDENT, true, true, None)? {
+            lines.push(SourceLine {
+                raw,
+                content: &raw[indent..],
+                depth,
+                line_number: state.line_number,
+            });
+        }
+    }
+    let mut cursor = Cursor {
+        lines,
+        index: 0,
+        blank_lines: state.blank_lines,
+    };
+    decode_document(&mut cursor).map_err(|err| {
+        let line = cursor.current().map_or(0, |line| line.line_number);
+        err.at_line(line).with_source(|number| {
+            let index = cursor
+                .lines
+                .binary_search_by_key(&number, |line| line.line_number)
+                .ok()?;
+            Some(cursor.lines[index].raw)
+        })
+    })
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SourceLine<'a> {
+    raw: &'a str,
+    content: &'a str,
+    depth: Depth,
+    line_number: usize,
+}
+
+struct Cursor<'a> {
+    lines: Vec<SourceLine<'a>>,
+    index: usize,
+    blank_lines: Vec<BlankLineInfo>,
+}
+
+impl<'a> Cursor<'a> {
+    fn peek(&self) -> Option<SourceLine<'a>> {
+        self.lines.get(self.index).copied()
+    }
+
+    fn next(&mut self) -> Option<SourceLine<'a>> {
+        let line = self.peek()?;
+        self.index += 1;
+        Some(line)
+    }
+
+    fn advance(&mut self) {
+        self.index = (self.index + 1).min(self.lines.len());
+    }
+
+    /// Line most recently consumed.
+    fn current(&self) -> Option<SourceLine<'a>> {
+        self.index.checked_sub(1).map(|index| self.lines[index])
+    }
+
+    fn next_line(&self) -> Option<(Depth, &'a str)> {
+        self.peek().map(|line| (line.depth, line.content))
+    }
+
+    fn current_line_number(&self) -> usize {
+        self.current().map_or(0, |line| line.line_number)
+    }
+
+    fn next_line_number(&self) -> usize {
+        self.peek().map_or(0, |line| line.line_number)
+    }
+}
+
+fn decode_document<'a>(cursor: &mut Cursor<'a>) -> Result<BorrowedValue<'a>> {
+    let Some(first) = cursor.peek() else {
+        return Ok(BorrowedValue::Object(Vec::new()));
+    };
+
+    if is_array_header_content(first.content) {
+        if let Some(header_info) = parse_array_header_line(first.content, DEFAULT_DELIMITER)
+            .map_err(|err| err.at_line(first.line_number))?
+        {
+            cursor.advance();
+            return decode_array(header_info, cursor, 0);
+        }
+    }
+
+    cursor.advance();
+    if cursor.peek().is_none() && !is_key_value_line(first.content) {
+        let primitive = parse_borrowed_primitive(first.content)
+            .map_err(|err| err.at_line(first.line_number))?;
+        return Ok(BorrowedValue::Primitive(primitive));
+    }
+
+    let mut entries = Vec::new();
+    decode_key_value(&mut entries, first.content, cursor, 0)?;
+    while let Some(line) = cursor.peek() {
+        if line.depth != 0 {
+            break;
+        }
+        cursor.advance();
+        decode_key_value(&mut entries, line.content, cursor, 0)?;
+    }
+    Ok(BorrowedValue::Object(entries))
+}
+
+fn decode_key_value<'a>(
+    entries: &mut Entries<'a>,
+    content: &'a str,
+    cursor: &mut Cursor<'a>,
+    base_depth: Depth,
+) -> Result<()> {
+    let line_number = cursor.current_line_number();
+    let at_line = |err: ToonError| err.at_line(line_number);
+    if let Some(mut header_info) =
+        parse_array_header_line(content, DEFAULT_DELIMITER).map_err(at_line)?
+    {
+        if let Some(key) = header_info.header.key.take() {
+            let value = decode_array(header_info, cursor, base_depth)?;
+            entries.push((key, value));
+            return Ok(());
+        }
+    }
+
+    let (key, end, _) = parse_key_token(content, 0).map_err(at_line)?;
+    let rest = content[end..].trim();
+    if !rest.is_empty() {
+        let value = BorrowedValue::Primitive(parse_borrowed_primitive(rest).map_err(at_line)?);
+        entries.push((key, value));
+        return Ok(());
+    }
+
+    let fields = match cursor.peek() {
+        Some(next) if next.depth > base_depth => decode_object_fields(cursor, base_depth + 1)?,
+        _ => Vec::new(),
+    };
+    entries.push((key, BorrowedValue::Object(fields)));
+    Ok(())
+}
+
+fn decode_object_fields<'a>(cursor: &mut Cursor<'a>, base_depth: Depth) -> Result<Entries<'a>> {
+    let mut entries = Vec::new();
+    let mut computed_depth: Option<Depth> = None;
+    while let Some(line) = cursor.peek() {
+        if line.depth < base_depth || line.depth != *computed_depth.get_or_insert(line.depth) {
+            break;
+        }
+        cursor.advance();
+        decode_key_value(&mut entries, line.content, cursor, line.depth)?;
+    }
+    Ok(entries)
+}
+
+fn decode_array<'a>(
+    header_info: ArrayHeaderParseResult<'a>,
+    cursor: &mut Cursor<'a>,
+    base_depth: Depth,
+) -> Result<BorrowedValue<'a>> {
+    let ArrayHeaderParseResult {
+        header,
+        inline_values,
+    } = header_info;
+    let header_line = cursor.current_line_number();
+
+    // Errors not tied to an item or row belong to the header.
+    let items = if let Some(inline_values) = inline_values {
+        decode_inline_array(&header, inline_values, cursor, base_depth)
+    } else if header
+        .fields
+        .as_ref()
+        .is_some_and(|fields| !fields.is_empty())
+    {
+        decode_tabular_array(&header, cursor, base_depth)
+    } else {
+        decode_list_array(&header, cursor, base_depth)
+    }
+    .map_err(|err| err.at_line(header_line))?;
+    Ok(BorrowedValue::Array(items))
+}
+
+fn decode_inline_array<'a>(
+    header: &ArrayHeaderInfo<'a>,
+    inline_values: &'a str,
+    cursor: &mut Cursor<'a>,
+    base_depth: Depth,
+) -> Result<Vec<BorrowedValue<'a>>> {
+    if inline_values.trim().is_empty() {
+        assert_expected_count(0, header.length, "inline array items", true)?;
+        return Ok(Vec::new());
+    }
+
+    let parse = |values: &'a str| {
+        parse_delimited_values(values, header.delimiter)
+            .into_iter()
+            .map(|value| parse_borrowed_primitive(value).map(BorrowedValue::Primitive))
+            .collect::<Result<Vec<_>>>()
+    };
+    let mut items = parse(inline_values)?;
+    // Continuation lines of a wrapped array.
+    while header.length != UNMARKED_LENGTH && items.len() < header.length {
+        match cursor.peek() {
+            Some(line) if line.depth == base_depth + 1 => {
+                cursor.advance();
+                items.extend(parse(line.content).map_err(|err| err.at_line(line.line_number))?);
+            }
+            _ => break,
+        }
+    }
+    assert_expected_count(items.len(), header.length, "inline array items", true)?;
+    Ok(items)
+}
+
+fn decode_tabular_array<'a>(
+    header: &ArrayHeaderInfo<'a>,
+    cursor: &mut Cursor<'a>,
+    base_depth: Depth,
+) -> Result<Vec<BorrowedValue<'a>>> {
+    let fields = header.fields.as_deref().ok_or_else(|| {
+        ToonError::decode(
+            DecodeErrorKind::Structure,
+            "Tabular array is missing header fields",
+        )
+    })?;
+    let row_depth = base_depth + 1;
+    let mut rows = Vec::new();
+    let mut span: Option<(usize, usize)> = None;
+
+    while rows.len() < header.length {
+        let Some(line) = cursor.peek() else {
+            break;
+        };
+        if line.depth != row_depth {
+            break;
+        }
+        span = Some((
+            span.map_or(line.line_number, |(start, _)| start),
+            line.line_number,
+        ));
+        cursor.advance();
+
+        let values = parse_delimited_values(line.content, header.delimiter);
+        let row = assert_expected_count(values.len(), fields.len(), "tabular row values", true)
+            .and_then(|()| {
+                fields
+                    .iter()
+                    .zip(values)
+                    .map(|(field, value)| {
+                        let value = parse_borrowed_primitive(value)?;
+                        Ok((field.name.clone(), BorrowedValue::Primitive(value)))
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .map_err(|err| err.at_line(line.line_number))?;
+        rows.push(BorrowedValue::Object(row));
+    }
+
+    assert_expected_count(rows.len(), header.length, "tabular rows", true)?;
+    if let Some((start, end)) = span {
+        validate_no_blank_lines_in_range(start, end, &cursor.blank_lines, true, "tabular array")?;
+    }
+    validate_no_extra_tabular_rows(cursor.next_line(), row_depth, header, true)
+        .map_err(|err| err.at_line(cursor.next_line_number()))?;
+    Ok(rows)
+}
+
+fn decode_list_array<'a>(
+    header: &ArrayHeaderInfo<'a>,
+    cursor: &mut Cursor<'a>,
+    base_depth: Depth,
+) -> Result<Vec<BorrowedValue<'a>>> {
+    let item_depth = base_depth + 1;
+    let mut items = Vec::new();
+    let mut span: Option<(usize, usize)> = None;
+
+    while items.len() < header.length {
+        let Some(line) = cursor.peek() else {
+            break;
+        };
+        let is_list_item =
+            line.content.starts_with(LIST_ITEM_PREFIX) || line.content == LIST_ITEM_MARKER;
+        if line.depth != item_depth || !is_list_item {
+            break;
+        }
+        let start = span.map_or(line.line_number, |(start, _)| start);
+
+        items.push(decode_list_item(cursor, item_depth)?);
+
+        let end = cursor
+            .current()
+            .map_or(line.line_number, |line| line.line_number);
+        span = Some((start, end));
+    }
+
+    assert_expected_count(items.len(), header.length, "list array items", true)?;
+    if let Some((start, end)) = span {
+        validate_no_blank_lines_in_range(start, end, &cursor.blank_lines, true, "list array")?;
+    }
+    validate_no_extra_list_items(cursor.next_line(), item_depth, header.length, true)
+        .map_err(|err| err.at_line(cursor.next_line_number()))?;
+    Ok(items)
+}
+
+fn decode_list_item<'a>(cursor: &mut Cursor<'a>, base_depth: Depth) -> Result<BorrowedValue<'a>> {
+    let line = cursor
+        .next()
+        .ok_or_else(|| ToonError::decode(DecodeErrorKind::Structure, "Expected list item"))?;
+
+    if line.content == LIST_ITEM_MARKER {
+        return Ok(BorrowedValue::Object(Vec::new()));
+    }
+    let Some(after_hyphen) = line.content.strip_prefix(LIST_ITEM_PREFIX) else {
+        return Err(ToonError::decode(
+            DecodeErrorKind::Structure,
+            format!("Expected list item to start with \"{LIST_ITEM_PREFIX}\""),
+        )
+        .at_line(line.line_number));
+    };
+    if after_hyphen.trim().is_empty() {
+        return Ok(BorrowedValue::Object(Vec::new()));
+    }
+
+    let at_line = |err: ToonError| err.at_line(line.line_number);
+    if let Some(mut header_info) =
+        parse_array_header_line(after_hyphen, DEFAULT_DELIMITER).map_err(at_line)?
+    {
+        if is_array_header_content(after_hyphen) {
+            return decode_array(header_info, cursor, base_depth);
+        }
+        if header_info.header.fields.is_some() {
+            if let Some(key) = header_info.header.key.take() {
+                let mut entries = vec![(key, decode_array(header_info, cursor, base_depth + 1)?)];
+                decode_follow_fields(&mut entries, cursor, base_depth + 1)?;
+                return Ok(BorrowedValue::Object(entries));
+            }
+        }
+    }
+
+    if is_key_value_content(after_hyphen) {
+        let mut entries = Vec::new();
+        decode_key_value(&mut entries, after_hyphen, cursor, base_depth + 1)?;
+        decode_follow_fields(&mut entries, cursor, base_depth + 1)?;
+        return Ok(BorrowedValue::Object(entries));
+    }
+
+    Ok(BorrowedValue::Primitive(
+        parse_borrowed_primitive(after_hyphen).map_err(at_line)?,
+    ))
+}
+
+/// Decode the fields that continue an object opened on a list item line.
+fn decode_follow_fields<'a>(
+    entries: &mut Entries<'a>,
+    cursor: &mut Cursor<'a>,
+    follow_depth: Depth,
+) -> Result<()> {
+    while let Some(line) = cursor.peek() {
+        if line.depth != follow_depth || line.content.starts_with(LIST_ITEM_PREFIX) {
+            break;
+        }
+        cursor.advance();
+        decode_key_value(entries, line.content, cursor, follow_depth)?;
+    }
+    Ok(())
+}
@@ -1,24 +1,42 @@
-use crate::JsonStreamEvent;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::decode::event_builder::SerdeBuilder;
+use crate::decode::limits::LimitCounter;
 use crate::decode::parser::{
-    FieldName, is_array_header_content, is_key_value_content, map_row_values_to_primitives,
-    parse_array_header_line, parse_delimited_values, parse_key_token, parse_primitive_token,
+    ArrayHeaderInfo, ArrayHeaderParseResult, FieldName, UNMARKED_LENGTH, is_array_header_content,
+    is_key_value_content, is_key_value_line, parse_array_header_line, parse_borrowed_primitive,
+    parse_delimited_values, parse_key_token, parse_primitive_token_with,
+    parse_unmarked_array_header,
 };
 use crate::decode::scanner::{
-    Depth, ParsedLine, StreamingLineCursor, create_scan_state, parse_lines_sync,
+    Depth, ParsedLine, StreamingLineCursor, create_scan_state, parse_lines_recovering,
+    parse_lines_sync,
 };
 use crate::decode::validation::{
     assert_expected_count, validate_no_blank_lines_in_range, validate_no_extra_list_items,
     validate_no_extra_tabular_rows,
 };
-use crate::error::{Result, ToonError};
-use crate::options::DecodeStreamOptions;
-use crate::shared::constants::{COLON, DEFAULT_DELIMITER, LIST_ITEM_MARKER, LIST_ITEM_PREFIX};
-use crate::shared::string_utils::find_closing_quote;
+use crate::decode::warnings::{DecodeWarning, DecodeWarningKind, ambiguity_message};
+use crate::error::{DecodeErrorKind, Result, ToonError};
+use crate::metrics::Metrics;
+use crate::options::{DecodeLimits, DecodeStreamOptions, Strictness};
+use crate::shared::constants::{DEFAULT_DELIMITER, LIST_ITEM_MARKER, LIST_ITEM_PREFIX};
+use crate::{JsonPrimitive, JsonStreamEvent};
 
 #[derive(Debug, Clone, Copy)]
-pub struct DecoderContext {
+pub struct DecoderContext<'a> {
     pub indent: usize,
-    pub strict: bool,
+    pub strictness: Strictness,
+    /// Reject header-shaped keys without a valid length marker.
+    pub require_length_markers: bool,
+    /// Keep numbers as their source text.
+    pub preserve_number_literals: bool,
+    /// Sink for warnings; `None` skips the checks.
+    pub warnings: Option<&'a RefCell<Vec<DecodeWarning>>>,
+    /// Counters for tabular rows.
+    pub metrics: Option<&'a dyn Metrics>,
 }
 
 /// Decode TOON input into a stream of JSON events.
@@ -27,51 +45,419 @@ pub struct DecoderContext {
 ///
 /// Returns an error if scanning or decoding fails (invalid indentation, malformed arrays,
 /// or strict-mode validation failures).
-pub fn decode_stream_sync(
-    source: impl IntoIterator<Item = String>,
+pub fn decode_stream_sync<'a>(
+    source: impl IntoIterator<Item = impl Into<Cow<'a, str>>>,
+    options: Option<DecodeStreamOptions>,
+) -> Result<Vec<JsonStreamEvent>> {
+    decode_stream_with_sink(source, options, None)
+}
+
+/// Decode TOON input into a stream of JSON events, also reporting unquoted
+/// values whose inferred type may be unintended.
+///
+/// # Errors
+///
+/// Returns an error if scanning or decoding fails (invalid indentation, malformed arrays,
+/// or strict-mode validation failures).
+pub fn decode_stream_sync_with_warnings<'a>(
+    source: impl IntoIterator<Item = impl Into<Cow<'a, str>>>,
+    options: Option<DecodeStreamOptions>,
+) -> Result<(Vec<JsonStreamEvent>, Vec<DecodeWarning>)> {
+    let warnings = RefCell::new(Vec::new());
+    let events = decode_stream_with_sink(source, options, Some(&warnings))?;
+    Ok((events, warnings.into_inner()))
+}
+
+/// Decode TOON input into a stream of JSON events, each paired with the
+/// 1-based line it was read from (`0` for the empty document).
+///
+/// Closing events belong to the last line of the value they close; every
+/// field of a tabular row shares the row's line.
+///
+/// # Errors
+///
+/// Returns an error if scanning or decoding fails (invalid indentation, malformed arrays,
+/// or strict-mode validation failures).
+pub fn decode_stream_sync_with_lines<'a>(
+    source: impl IntoIterator<Item = impl Into<Cow<'a, str>>>,
+    options: Option<DecodeStreamOptions>,
+) -> Result<Vec<(usize, JsonStreamEvent)>> {
+    let mut events = EventSink {
+        lines: Some(Vec::new()),
+        ..EventSink::default()
+    };
+    decode_into_sink(source, options, None, &mut events)?;
+    Ok(events
+        .lines
+        .unwrap_or_default()
+        .into_iter()
+        .zip(events.events)
+        .collect())
+}
+
+/// Decode TOON input straight into a `serde_json::Value`.
+///
+/// Each event goes into the tree as it is decoded instead of being collected
+/// first. Keys repeat as in
+/// [`build_serde_from_events`](crate::decode::event_builder::build_serde_from_events).
+///
+/// # Errors
+///
+/// Returns an error if scanning or decoding fails (invalid indentation, malformed arrays,
+/// or strict-mode validation failures).
+pub fn decode_to_serde_sync<'a>(
+    source: impl IntoIterator<Item = impl Into<Cow<'a, str>>>,
+    options: Option<DecodeStreamOptions>,
+) -> Result<serde_json::Value> {
+    let mut events = EventSink {
+        serde: Some(SerdeBuilder::default()),
+        ..EventSink::default()
+    };
+    decode_into_sink(source, options, None, &mut events)?;
+    events.serde.unwrap_or_default().finish()
+}
+
+/// Decode TOON input into a stream of JSON events, carrying on past errors
+/// instead of stopping at the first.
+///
+/// Returns the events of a best-effort document and every error found, in
+/// line order. A value that does not parse is kept as its source text, a line
+/// that cannot be decoded is skipped along with the lines nested under it, and
+/// count, blank-line and duplicate-key checks only report.
+#[must_use]
+pub fn decode_stream_sync_recovering<'a>(
+    source: impl IntoIterator<Item = impl Into<Cow<'a, str>>>,
+    options: Option<DecodeStreamOptions>,
+) -> (Vec<JsonStreamEvent>, Vec<ToonError>) {
+    decode_recovering(source, options, false)
+}
+
+/// Check TOON input as [`decode_stream_sync_recovering`] does, returning
+/// every error found without keeping any events. Lines passed as `&str` are
+/// not copied.
+#[must_use]
+pub fn validate_stream_sync<'a>(
+    source: impl IntoIterator<Item = impl Into<Cow<'a, str>>>,
+    options: Option<DecodeStreamOptions>,
+) -> Vec<ToonError> {
+    decode_recovering(source, options, true).1
+}
+
+fn decode_recovering<'a>(
+    source: impl IntoIterator<Item = impl Into<Cow<'a, str>>>,
+    options: Option<DecodeStreamOptions>,
+    discard: bool,
+) -> (Vec<JsonStreamEvent>, Vec<ToonError>) {
+    let mut events = EventSink {
+        errors: Some(Vec::new()),
+        discard,
+        ..EventSink::default()
+    };
+    let result = decode_into_sink(source, options, None, &mut events);
+    let mut errors = events.errors.unwrap_or_default();
+    if let Err(err) = result {
+        // Only a limit or an internal inconsistency gets here; the events
+        // are unusable.
+        errors.push(err);
+        events.events.clear();
+    }
+    errors.sort_by_key(|err| err.decode_error().map_or(0, |err| err.line));
+    (events.events, errors)
+}
+
+/// Events collected by the decoder, optionally tagged with source lines.
+#[derive(Debug, Default)]
+struct EventSink {
+    events: Vec<JsonStreamEvent>,
+    /// Source line of each event, recorded only when present.
+    lines: Option<Vec<usize>>,
+    /// Line most recently consumed from the cursor.
+    line: usize,
+    /// First line of each key in the open objects, tracked only when
+    /// duplicate keys are rejected.
+    key_lines: Option<Vec<HashMap<String, usize>>>,
+    /// Errors carried past in recovery mode; `None` stops at the first.
+    errors: Option<Vec<ToonError>>,
+    /// Check events without keeping them, when only errors matter.
+    discard: bool,
+    /// Build the events into a `serde_json::Value` instead of keeping them.
+    serde: Option<SerdeBuilder>,
+    /// Bounds checked on every event, fatal even in recovery mode.
+    limits: Option<DecodeLimits>,
+    limit_counter: LimitCounter,
+}
+
+impl EventSink {
+    fn push(&mut self, event: JsonStreamEvent) -> Result<()> {
+        if let Some(limits) = &self.limits {
+            self.limit_counter
+                .observe(limits, &event)
+                .map_err(|err| err.at_line(self.line))?;
+        }
+        if let Some(objects) = &mut self.key_lines {
+            match &event {
+                JsonStreamEvent::StartObject => objects.push(HashMap::new()),
+                JsonStreamEvent::EndObject => {
+                    objects.pop();
+                }
+                _ => {}
+            }
+        }
+        if self.discard {
+            return Ok(());
+        }
+        if let Some(builder) = &mut self.serde {
+            return builder.push(event);
+        }
+        if let Some(lines) = &mut self.lines {
+            lines.push(self.line);
+        }
+        self.events.push(event);
+        Ok(())
+    }
+
+    /// Push a key after checking it for duplicates, copying it only when the
+    /// event is kept or counted against limits.
+    fn push_key(&mut self, key: Cow<'_, str>, was_quoted: bool) -> Result<()> {
+        let line = self.line;
+        let first = self
+            .key_lines
+            .as_mut()
+            .and_then(|objects| objects.last_mut())
+            .and_then(|keys| {
+                if let Some(first) = keys.get_mut(&*key) {
+                    return Some(std::mem::replace(first, line));
+                }
+                keys.insert(key.to_string(), line);
+                None
+            });
+        if let Some(first) = first {
+            self.record(
+                ToonError::decode(
+                    DecodeErrorKind::DuplicateKey,
+                    format!("duplicate key `{key}` (first defined at line {first})"),
+                )
+                .at_line(line),
+            )?;
+        }
+        if !self.builds_events() {
+            return Ok(());
+        }
+        self.push(JsonStreamEvent::Key {
+            key: key.into_owned(),
+            was_quoted,
+        })
+    }
+
+    /// Whether events are needed at all: kept, or counted against limits.
+    const fn builds_events(&self) -> bool {
+        !self.discard || self.limits.is_some()
+    }
+
+    /// Record `err` and carry on in recovery mode, or fail with it.
+    fn record(&mut self, err: ToonError) -> Result<()> {
+        match &mut self.errors {
+            Some(errors) => {
+                errors.push(err);
+                Ok(())
+            }
+            None => Err(err),
+        }
+    }
+
+    /// Record a failed check and carry on in recovery mode.
+    fn check(&mut self, result: Result<()>) -> Result<()> {
+        result.or_else(|err| self.record(err))
+    }
+
+    fn advance(&mut self, cursor: &mut StreamingLineCursor) {
+        cursor.advance_sync();
+        self.line = current_line_number(cursor);
+    }
+
+    fn next_line<'a>(&mut self, cursor: &mut StreamingLineCursor<'a>) -> Option<ParsedLine<'a>> {
+        let line = cursor.next_sync()?;
+        self.line = line.line_number;
+        Some(line)
+    }
+}
+
+fn decode_stream_with_sink<'a>(
+    source: impl IntoIterator<Item = impl Into<Cow<'a, str>>>,
     options: Option<DecodeStreamOptions>,
+    warnings: Option<&RefCell<Vec<DecodeWarning>>>,
 ) -> Result<Vec<JsonStreamEvent>> {
-    let options = options.unwrap_or(DecodeStreamOptions {
-        indent: None,
-        strict: None,
+    let mut events = EventSink::default();
+    decode_into_sink(source, options, warnings, &mut events)?;
+    Ok(events.events)
+}
+
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(name = "decode", level = "debug", skip_all)
+)]
+fn decode_into_sink<'a>(
+    source: impl IntoIterator<Item = impl Into<Cow<'a, str>>>,
+    options: Option<DecodeStreamOptions>,
+    warnings: Option<&RefCell<Vec<DecodeWarning>>>,
+    events: &mut EventSink,
+) -> Result<()> {
+    let options = options.unwrap_or_default();
+    let warning_count = decode_lines_into(source, &options, 0, warnings, events)?;
+
+    if let Some(metrics) = options.metrics.as_deref() {
+        metrics.document_decoded();
+        if warning_count > 0 {
+            metrics.warnings_emitted(warning_count);
+        }
+    }
+    Ok(())
+}
+
+/// Decode a self-contained piece of a larger document whose first line is
+/// line `line_offset + 1`, pairing each event with its line. Unlike a whole
+/// document, this does not count as a decoded document in the metrics.
+pub(crate) fn decode_fragment(
+    source: Vec<String>,
+    options: &DecodeStreamOptions,
+    line_offset: usize,
+) -> Result<Vec<(usize, JsonStreamEvent)>> {
+    let mut events = EventSink {
+        lines: Some(Vec::new()),
+        ..EventSink::default()
+    };
+    decode_lines_into(source, options, line_offset, None, &mut events)?;
+    Ok(events
+        .lines
+        .unwrap_or_default()
+        .into_iter()
+        .zip(events.events)
+        .collect())
+}
+
+/// Decode `source` into `events`, returning how many warnings were raised.
+/// They go to `warnings` and to `options.collect_warnings`.
+fn decode_lines_into<'a>(
+    source: impl IntoIterator<Item = impl Into<Cow<'a, str>>>,
+    options: &DecodeStreamOptions,
+    line_offset: usize,
+    warnings: Option<&RefCell<Vec<DecodeWarning>>>,
+    events: &mut EventSink,
+) -> Result<usize> {
+    let collected = RefCell::new(Vec::new());
+    let warnings = warnings.or_else(|| options.collect_warnings.as_ref().map(|_| &collected));
+    let result = decode_lines_with_warnings(source, options, line_offset, warnings, events);
+    let count = warnings.map_or(0, |warnings| {
+        let warnings = warnings.borrow();
+        if let Some(sink) = &options.collect_warnings {
+            sink.extend(warnings.iter().cloned());
+        }
+        warnings.len()
     });
+    result.map(|()| count)
+}
+
+fn decode_lines_with_warnings<'a>(
+    source: impl IntoIterator<Item = impl Into<Cow<'a, str>>>,
+    options: &DecodeStreamOptions,
+    line_offset: usize,
+    warnings: Option<&RefCell<Vec<DecodeWarning>>>,
+    events: &mut EventSink,
+) -> Result<()> {
+    let strictness = options.strictness.unwrap_or_default();
+    if strictness.duplicate_keys {
+        events.key_lines = Some(Vec::new());
+    }
+    events.limits = options.limits;
     let context = DecoderContext {
         indent: options.indent.unwrap_or(2),
-        strict: options.strict.unwrap_or(true),
+        strictness,
+        require_length_markers: options.require_length_markers.unwrap_or(false),
+        preserve_number_literals: options.preserve_number_literals.unwrap_or(false),
+        warnings,
+        metrics: options.metrics.as_deref(),
     };
 
     let mut scan_state = create_scan_state();
-    let lines = parse_lines_sync(source, context.indent, context.strict, &mut scan_state)?;
+    scan_state.line_number = line_offset;
+    let lines = if let Some(errors) = &mut events.errors {
+        parse_lines_recovering(
+            source,
+            context.indent,
+            strictness.tabs,
+            strictness.indentation,
+            options.max_line_length,
+            &mut scan_state,
+            errors,
+        )
+    } else {
+        parse_lines_sync(
+            source,
+            context.indent,
+            strictness.tabs,
+            strictness.indentation,
+            options.max_line_length,
+            &mut scan_state,
+        )?
+    };
+    #[cfg(feature = "tracing")]
+    tracing::debug!(lines = lines.len(), "scanned");
+    if let Some(warnings) = warnings {
+        let mut warnings = warnings.borrow_mut();
+        for line in &lines {
+            push_whitespace_warnings(&mut warnings, line, strictness.tabs);
+        }
+    }
     let mut cursor = StreamingLineCursor::new(lines, scan_state.blank_lines);
+    let result = decode_document_sync(events, &mut cursor, context).map_err(|err| {
+        err.at_line(events.line)
+            .with_source(|line| cursor.source_line(line))
+    });
+    events.errors = events.errors.take().map(|errors| {
+        errors
+            .into_iter()
+            .map(|err| err.with_source(|line| cursor.source_line(line)))
+            .collect()
+    });
+    result
+}
 
-    let mut events = Vec::new();
-
+fn decode_document_sync(
+    events: &mut EventSink,
+    cursor: &mut StreamingLineCursor<'_>,
+    context: DecoderContext<'_>,
+) -> Result<()> {
     let first = cursor.peek_sync().cloned();
     let Some(first) = first else {
-        events.push(JsonStreamEvent::StartObject);
-        events.push(JsonStreamEvent::EndObject);
-        return Ok(events);
+        events.push(JsonStreamEvent::StartObject)?;
+        events.push(JsonStreamEvent::EndObject)?;
+        return Ok(());
     };
 
     if is_array_header_content(&first.content) {
-        if let Some(header_info) = parse_array_header_line(&first.content, DEFAULT_DELIMITER)? {
-            cursor.advance_sync();
-            decode_array_from_header_sync(&mut events, header_info, &mut cursor, 0, context)?;
-            return Ok(events);
+        match parse_header_sync(&first.content, context) {
+            Ok(Some(header_info)) => {
+                events.advance(cursor);
+                decode_array_from_header_sync(events, header_info, cursor, 0, context)?;
+                return Ok(());
+            }
+            Ok(None) => {}
+            // Decoded as a key-value line, which reports the error.
+            Err(_) if events.errors.is_some() => {}
+            Err(err) => return Err(err.at_line(first.line_number)),
         }
     }
 
-    cursor.advance_sync();
+    events.advance(cursor);
     let has_more = !cursor.at_end_sync();
-    if !has_more && !is_key_value_line_sync(&first) {
-        events.push(JsonStreamEvent::Primitive {
-            value: parse_primitive_token(first.content.trim())?,
-        });
-        return Ok(events);
+    if !has_more && !is_key_value_line(&first.content) {
+        let value = parse_value_token(events, first.content.trim(), first.line_number, context)?;
+        events.push(JsonStreamEvent::Primitive { value })?;
+        return Ok(());
     }
 
-    events.push(JsonStreamEvent::StartObject);
-    decode_key_value_sync(&mut events, &first.content, &mut cursor, 0, context)?;
+    events.push(JsonStreamEvent::StartObject)?;
+    decode_key_value_sync(events, &first.content, cursor, 0, context)?;
 
     while !cursor.at_end_sync() {
         let line = cursor.peek_sync().cloned();
@@ -81,67 +467,78 @@ pub fn decode_stream_sync(
         if line.depth != 0 {
             break;
         }
-        cursor.advance_sync();
-        decode_key_value_sync(&mut events, &line.content, &mut cursor, 0, context)?;
+        events.advance(cursor);
+        decode_key_value_sync(events, &line.content, cursor, 0, context)?;
     }
 
-    events.push(JsonStreamEvent::EndObject);
-    Ok(events)
+    events.push(JsonStreamEvent::EndObject)?;
+    Ok(())
 }
 
 fn decode_key_value_sync(
-    events: &mut Vec<JsonStreamEvent>,
+    events: &mut EventSink,
     content: &str,
-    cursor: &mut StreamingLineCursor,
+    cursor: &mut StreamingLineCursor<'_>,
     base_depth: Depth,
-    options: DecoderContext,
+    options: DecoderContext<'_>,
 ) -> Result<()> {
-    if let Some(header_info) = parse_array_header_line(content, DEFAULT_DELIMITER)? {
+    let line_number = current_line_number(cursor);
+    let header_info = match parse_header_sync(content, options) {
+        Ok(header_info) => header_info,
+        Err(err) => {
+            events.record(err.at_line(line_number))?;
+            skip_nested(events, cursor, base_depth);
+            return Ok(());
+        }
+    };
+    if let Some(header_info) = header_info {
         if let Some(key) = header_info.header.key.clone() {
-            events.push(JsonStreamEvent::Key {
-                key,
-                was_quoted: header_info.header.key_was_quoted,
-            });
+            events.push_key(key, header_info.header.key_was_quoted)?;
             decode_array_from_header_sync(events, header_info, cursor, base_depth, options)?;
             return Ok(());
         }
     }
+    if options.require_length_markers {
+        events.check(check_length_marker(content, line_number))?;
+    }
 
-    let (key, end, is_quoted) = parse_key_token(content, 0)?;
+    let (key, end, is_quoted) = match parse_key_token(content, 0) {
+        Ok(token) => token,
+        Err(err) => {
+            events.record(err.at_line(line_number))?;
+            skip_nested(events, cursor, base_depth);
+            return Ok(());
+        }
+    };
     let rest = content[end..].trim();
 
-    events.push(JsonStreamEvent::Key {
-        key,
-        was_quoted: is_quoted,
-    });
+    events.push_key(key, is_quoted)?;
 
     if rest.is_empty() {
         let next_line = cursor.peek_sync();
         if let Some(next) = next_line {
             if next.depth > base_depth {
-                events.push(JsonStreamEvent::StartObject);
+                events.push(JsonStreamEvent::StartObject)?;
                 decode_object_fields_sync(events, cursor, base_depth + 1, options)?;
-                events.push(JsonStreamEvent::EndObject);
+                events.push(JsonStreamEvent::EndObject)?;
                 return Ok(());
             }
         }
 
-        events.push(JsonStreamEvent::StartObject);
-        events.push(JsonStreamEvent::EndObject);
+        events.push(JsonStreamEvent::StartObject)?;
+        events.push(JsonStreamEvent::EndObject)?;
         return Ok(());
     }
 
-    events.push(JsonStreamEvent::Primitive {
-        value: parse_primitive_token(rest)?,
-    });
-    Ok(())
+    let value = parse_value_token(events, rest, line_number, options)?;
+    events.push(JsonStreamEvent::Primitive { value })
 }
 
 fn decode_object_fields_sync(
-    events: &mut Vec<JsonStreamEvent>,
-    cursor: &mut StreamingLineCursor,
+    events: &mut EventSink,
+    cursor: &mut StreamingLineCursor<'_>,
     base_depth: Depth,
-    options: DecoderContext,
+    options: DecoderContext<'_>,
 ) -> Result<()> {
     let mut computed_depth: Option<Depth> = None;
 
@@ -159,7 +556,7 @@ fn decode_object_fields_sync(
         }
 
         if Some(line.depth) == computed_depth {
-            cursor.advance_sync();
+            events.advance(cursor);
             decode_key_value_sync(events, &line.content, cursor, line.depth, options)?;
         } else {
             break;
@@ -170,73 +567,150 @@ fn decode_object_fields_sync(
 }
 
 fn decode_array_from_header_sync(
-    events: &mut Vec<JsonStreamEvent>,
-    header_info: crate::decode::parser::ArrayHeaderParseResult,
-    cursor: &mut StreamingLineCursor,
+    events: &mut EventSink,
+    header_info: ArrayHeaderParseResult<'_>,
+    cursor: &mut StreamingLineCursor<'_>,
     base_depth: Depth,
-    options: DecoderContext,
+    options: DecoderContext<'_>,
 ) -> Result<()> {
     let header = header_info.header;
     let inline_values = header_info.inline_values;
+    let header_line = current_line_number(cursor);
 
+    let start = events.events.len();
     events.push(JsonStreamEvent::StartArray {
         length: header.length,
-    });
+    })?;
 
-    if let Some(inline_values) = inline_values {
-        decode_inline_primitive_array_sync(events, &header, &inline_values, options)?;
-        events.push(JsonStreamEvent::EndArray);
-        return Ok(());
+    // Errors not tied to an item or row belong to the header.
+    let count = if let Some(inline_values) = inline_values {
+        decode_inline_primitive_array_sync(
+            events,
+            &header,
+            inline_values,
+            cursor,
+            base_depth,
+            header_line,
+            options,
+        )
+    } else if header
+        .fields
+        .as_ref()
+        .is_some_and(|fields| !fields.is_empty())
+    {
+        decode_tabular_array_sync(events, &header, cursor, base_depth, header_line, options)
+            .inspect(|&rows| {
+                if let Some(metrics) = options.metrics {
+                    metrics.rows_parsed(rows);
+                }
+            })
+    } else {
+        decode_list_array_sync(events, &header, cursor, base_depth, header_line, options)
     }
+    .map_err(|err| err.at_line(header_line))?;
 
-    if let Some(fields) = &header.fields {
-        if !fields.is_empty() {
-            decode_tabular_array_sync(events, &header, cursor, base_depth, options)?;
-            events.push(JsonStreamEvent::EndArray);
-            return Ok(());
+    if header.length == UNMARKED_LENGTH {
+        // Events that were not kept need no fix-up.
+        if let Some(event) = events.events.get_mut(start) {
+            *event = JsonStreamEvent::StartArray { length: count };
+        }
+        if let Some(warnings) = options.warnings {
+            let target = header
+                .key
+                .as_ref()
+                .map_or_else(|| "root array".to_string(), |key| format!("array `{key}`"));
+            warnings.borrow_mut().push(DecodeWarning {
+                line: header_line,
+                kind: DecodeWarningKind::MissingLengthMarker,
+                message: format!("{target} has no valid length marker; inferred length {count}"),
+            });
         }
     }
+    events.push(JsonStreamEvent::EndArray)
+}
 
-    decode_list_array_sync(events, &header, cursor, base_depth, options)?;
-    events.push(JsonStreamEvent::EndArray);
-    Ok(())
+/// Parse an array header. With the length-marker check off, unless length
+/// markers are required, a header without a valid length is accepted too.
+fn parse_header_sync<'a>(
+    content: &'a str,
+    options: DecoderContext<'_>,
+) -> Result<Option<ArrayHeaderParseResult<'a>>> {
+    if let Some(header_info) = parse_array_header_line(content, DEFAULT_DELIMITER)? {
+        return Ok(Some(header_info));
+    }
+    if options.strictness.length_markers || options.require_length_markers {
+        return Ok(None);
+    }
+    parse_unmarked_array_header(content, DEFAULT_DELIMITER)
 }
 
+/// Decode the values after an array header. Values wrapped by
+/// `max_line_width` continue on the following lines one level deeper, read
+/// while fewer values than the `[N]` length have been seen.
 fn decode_inline_primitive_array_sync(
-    events: &mut Vec<JsonStreamEvent>,
-    header: &crate::decode::parser::ArrayHeaderInfo,
+    events: &mut EventSink,
+    header: &ArrayHeaderInfo<'_>,
     inline_values: &str,
-    options: DecoderContext,
-) -> Result<()> {
+    cursor: &mut StreamingLineCursor<'_>,
+    base_depth: Depth,
+    line_number: usize,
+    options: DecoderContext<'_>,
+) -> Result<usize> {
     if inline_values.trim().is_empty() {
-        assert_expected_count(0, header.length, "inline array items", options.strict)?;
-        return Ok(());
+        check_count(
+            events,
+            0,
+            header.length,
+            "inline array items",
+            line_number,
+            options,
+        )?;
+        return Ok(0);
     }
 
     let values = parse_delimited_values(inline_values, header.delimiter);
-    let primitives = map_row_values_to_primitives(&values)?;
+    let mut primitives = parse_value_tokens(events, &values, line_number, options)?;
+    while header.length != UNMARKED_LENGTH && primitives.len() < header.length {
+        if !matches!(next_line(cursor), Some((depth, _)) if depth == base_depth + 1) {
+            break;
+        }
+        let Some(line) = events.next_line(cursor) else {
+            break;
+        };
+        let values = parse_delimited_values(&line.content, header.delimiter);
+        primitives.extend(parse_value_tokens(
+            events,
+            &values,
+            line.line_number,
+            options,
+        )?);
+    }
 
-    assert_expected_count(
+    check_count(
+        events,
         primitives.len(),
         header.length,
         "inline array items",
-        options.strict,
+        line_number,
+        options,
     )?;
 
+    let count = primitives.len();
     for primitive in primitives {
-        events.push(JsonStreamEvent::Primitive { value: primitive });
+        events.push(JsonStreamEvent::Primitive { value: primitive })?;
     }
 
-    Ok(())
+    Ok(count)
 }
 
 fn decode_tabular_array_sync(
-    events: &mut Vec<JsonStreamEvent>,
-    header: &crate::decode::parser::ArrayHeaderInfo,
-    cursor: &mut StreamingLineCursor,
+    events: &mut EventSink,
+    header: &ArrayHeaderInfo<'_>,
+    cursor: &mut StreamingLineCursor<'_>,
     base_depth: Depth,
-    options: DecoderContext,
-) -> Result<()> {
+    header_line: usize,
+    options: DecoderContext<'_>,
+) -> Result<usize> {
     let row_depth = base_depth + 1;
     let mut row_count = 0usize;
     let mut start_line: Option<usize> = None;
@@ -257,21 +731,25 @@ fn decode_tabular_array_sync(
             }
             end_line = Some(line.line_number);
 
-            cursor.advance_sync();
+            events.advance(cursor);
             let values = parse_delimited_values(&line.content, header.delimiter);
-            let fields = header
-                .fields
-                .as_ref()
-                .ok_or_else(|| ToonError::message("Tabular array is missing header fields"))?;
-            assert_expected_count(
+            let fields = header.fields.as_ref().ok_or_else(|| {
+                ToonError::decode(
+                    DecodeErrorKind::Structure,
+                    "Tabular array is missing header fields",
+                )
+            })?;
+            check_count(
+                events,
                 values.len(),
                 fields.len(),
                 "tabular row values",
-                options.strict,
+                line.line_number,
+                options,
             )?;
 
-            let primitives = map_row_values_to_primitives(&values)?;
-            yield_object_from_fields(events, fields, &primitives);
+            let primitives = parse_value_tokens(events, &values, line.line_number, options)?;
+            yield_object_from_fields(events, fields, &primitives)?;
 
             row_count += 1;
         } else {
@@ -279,31 +757,47 @@ fn decode_tabular_array_sync(
         }
     }
 
-    assert_expected_count(row_count, header.length, "tabular rows", options.strict)?;
+    check_count(
+        events,
+        row_count,
+        header.length,
+        "tabular rows",
+        header_line,
+        options,
+    )?;
 
-    if options.strict {
+    if options.strictness.blank_lines {
         if let (Some(start), Some(end)) = (start_line, end_line) {
-            validate_no_blank_lines_in_range(
+            events.check(validate_no_blank_lines_in_range(
                 start,
                 end,
                 cursor.get_blank_lines(),
-                options.strict,
+                true,
                 "tabular array",
-            )?;
+            ))?;
         }
     }
 
-    validate_no_extra_tabular_rows(cursor.peek_sync(), row_depth, header, options.strict)?;
-    Ok(())
+    if let Err(err) = validate_no_extra_tabular_rows(
+        next_line(cursor),
+        row_depth,
+        header,
+        options.strictness.trailing_content,
+    ) {
+        events.record(err.at_line(next_line_number(cursor)))?;
+        skip_nested(events, cursor, base_depth);
+    }
+    Ok(row_count)
 }
 
 fn decode_list_array_sync(
-    events: &mut Vec<JsonStreamEvent>,
-    header: &crate::decode::parser::ArrayHeaderInfo,
-    cursor: &mut StreamingLineCursor,
+    events: &mut EventSink,
+    header: &ArrayHeaderInfo<'_>,
+    cursor: &mut StreamingLineCursor<'_>,
     base_depth: Depth,
-    options: DecoderContext,
-) -> Result<()> {
+    header_line: usize,
+    options: DecoderContext<'_>,
+) -> Result<usize> {
     let item_depth = base_depth + 1;
     let mut item_count = 0usize;
     let mut start_line: Option<usize> = None;
@@ -338,82 +832,95 @@ fn decode_list_array_sync(
         }
     }
 
-    assert_expected_count(
+    check_count(
+        events,
         item_count,
         header.length,
         "list array items",
-        options.strict,
+        header_line,
+        options,
     )?;
 
-    if options.strict {
+    if options.strictness.blank_lines {
         if let (Some(start), Some(end)) = (start_line, end_line) {
-            validate_no_blank_lines_in_range(
+            events.check(validate_no_blank_lines_in_range(
                 start,
                 end,
                 cursor.get_blank_lines(),
-                options.strict,
+                true,
                 "list array",
-            )?;
+            ))?;
         }
     }
 
-    validate_no_extra_list_items(
-        cursor.peek_sync(),
+    if let Err(err) = validate_no_extra_list_items(
+        next_line(cursor),
         item_depth,
         header.length,
-        options.strict,
-    )?;
-    Ok(())
+        options.strictness.trailing_content,
+    ) {
+        events.record(err.at_line(next_line_number(cursor)))?;
+        skip_nested(events, cursor, base_depth);
+    }
+    Ok(item_count)
 }
 
 fn decode_list_item_sync(
-    events: &mut Vec<JsonStreamEvent>,
-    cursor: &mut StreamingLineCursor,
+    events: &mut EventSink,
+    cursor: &mut StreamingLineCursor<'_>,
     base_depth: Depth,
-    options: DecoderContext,
+    options: DecoderContext<'_>,
 ) -> Result<()> {
-    let line = cursor
-        .next_sync()
-        .ok_or_else(|| ToonError::message("Expected list item"))?;
+    let line = events
+        .next_line(cursor)
+        .ok_or_else(|| ToonError::decode(DecodeErrorKind::Structure, "Expected list item"))?;
 
     if line.content == LIST_ITEM_MARKER {
-        events.push(JsonStreamEvent::StartObject);
-        events.push(JsonStreamEvent::EndObject);
+        events.push(JsonStreamEvent::StartObject)?;
+        events.push(JsonStreamEvent::EndObject)?;
         return Ok(());
     }
 
-    let after_hyphen = if line.content.starts_with(LIST_ITEM_PREFIX) {
-        line.content[LIST_ITEM_PREFIX.len()..].to_string()
-    } else {
-        return Err(ToonError::message(format!(
-            "Expected list item to start with \"{LIST_ITEM_PREFIX}\""
-        )));
+    let Some(after_hyphen) = line.content.strip_prefix(LIST_ITEM_PREFIX) else {
+        return Err(ToonError::decode(
+            DecodeErrorKind::Structure,
+            format!("Expected list item to start with \"{LIST_ITEM_PREFIX}\""),
+        )
+        .at_line(line.line_number));
     };
 
     if after_hyphen.trim().is_empty() {
-        events.push(JsonStreamEvent::StartObject);
-        events.push(JsonStreamEvent::EndObject);
+        events.push(JsonStreamEvent::StartObject)?;
+        events.push(JsonStreamEvent::EndObject)?;
         return Ok(());
     }
 
-    if is_array_header_content(&after_hyphen) {
-        if let Some(header_info) = parse_array_header_line(&after_hyphen, DEFAULT_DELIMITER)? {
+    let header_info = match parse_header_sync(after_hyphen, options) {
+        Ok(header_info) => header_info,
+        Err(err) => {
+            events.record(err.at_line(line.line_number))?;
+            skip_nested(events, cursor, base_depth);
+            return events.push(JsonStreamEvent::Primitive {
+                value: JsonPrimitive::Null,
+            });
+        }
+    };
+    if is_array_header_content(after_hyphen) {
+        if let Some(header_info) = header_info {
             decode_array_from_header_sync(events, header_info, cursor, base_depth, options)?;
             return Ok(());
         }
-    }
-
-    if let Some(header_info) = parse_array_header_line(&after_hyphen, DEFAULT_DELIMITER)? {
+    } else if let Some(header_info) = header_info {
         if header_info.header.key.is_some() && header_info.header.fields.is_some() {
             let header = header_info.header;
-            events.push(JsonStreamEvent::StartObject);
-            events.push(JsonStreamEvent::Key {
-                key: header.key.clone().unwrap_or_default(),
-                was_quoted: header.key_was_quoted,
-            });
+            events.push(JsonStreamEvent::StartObject)?;
+            events.push_key(
+                header.key.clone().unwrap_or_default(),
+                header.key_was_quoted,
+            )?;
             decode_array_from_header_sync(
                 events,
-                crate::decode::parser::ArrayHeaderParseResult {
+                ArrayHeaderParseResult {
                     header,
                     inline_values: header_info.inline_values,
                 },
@@ -422,98 +929,210 @@ fn decode_list_item_sync(
                 options,
             )?;
 
-            let follow_depth = base_depth + 1;
-            while !cursor.at_end_sync() {
-                let next_line = cursor.peek_sync().cloned();
-                let Some(next_line) = next_line else {
-                    break;
-                };
-                if next_line.depth < follow_depth {
-                    break;
-                }
-                if next_line.depth == follow_depth
-                    && !next_line.content.starts_with(LIST_ITEM_PREFIX)
-                {
-                    cursor.advance_sync();
-                    decode_key_value_sync(
-                        events,
-                        &next_line.content,
-                        cursor,
-                        follow_depth,
-                        options,
-                    )?;
-                } else {
-                    break;
-                }
-            }
-
-            events.push(JsonStreamEvent::EndObject);
+            decode_list_item_fields_sync(events, cursor, base_depth + 1, options)?;
             return Ok(());
         }
     }
 
-    if is_key_value_content(&after_hyphen) {
-        events.push(JsonStreamEvent::StartObject);
-        decode_key_value_sync(events, &after_hyphen, cursor, base_depth + 1, options)?;
+    if is_key_value_content(after_hyphen) {
+        events.push(JsonStreamEvent::StartObject)?;
+        decode_key_value_sync(events, after_hyphen, cursor, base_depth + 1, options)?;
 
-        let follow_depth = base_depth + 1;
-        while !cursor.at_end_sync() {
-            let next_line = cursor.peek_sync().cloned();
-            let Some(next_line) = next_line else {
-                break;
-            };
-            if next_line.depth < follow_depth {
-                break;
-            }
-            if next_line.depth == follow_depth && !next_line.content.starts_with(LIST_ITEM_PREFIX) {
-                cursor.advance_sync();
-                decode_key_value_sync(events, &next_line.content, cursor, follow_depth, options)?;
-            } else {
-                break;
-            }
+        decode_list_item_fields_sync(events, cursor, base_depth + 1, options)?;
+        return Ok(());
+    }
+
+    let value = parse_value_token(events, after_hyphen, line.line_number, options)?;
+    events.push(JsonStreamEvent::Primitive { value })
+}
+
+/// Decode the fields following the first one of a list item object, then
+/// close the object.
+fn decode_list_item_fields_sync(
+    events: &mut EventSink,
+    cursor: &mut StreamingLineCursor<'_>,
+    follow_depth: Depth,
+    options: DecoderContext<'_>,
+) -> Result<()> {
+    while !cursor.at_end_sync() {
+        let next_line = cursor.peek_sync().cloned();
+        let Some(next_line) = next_line else {
+            break;
+        };
+        if next_line.depth < follow_depth {
+            break;
         }
+        if next_line.depth == follow_depth && !next_line.content.starts_with(LIST_ITEM_PREFIX) {
+            events.advance(cursor);
+            decode_key_value_sync(events, &next_line.content, cursor, follow_depth, options)?;
+        } else {
+            break;
+        }
+    }
 
-        events.push(JsonStreamEvent::EndObject);
-        return Ok(());
+    events.push(JsonStreamEvent::EndObject)
+}
+
+/// Parse a value token, recording an ambiguity warning when a sink is attached.
+/// In recovery mode a token that does not parse is kept as written. When no
+/// events are built, the token is only checked and `Null` stands in for it.
+fn parse_value_token(
+    events: &mut EventSink,
+    token: &str,
+    line_number: usize,
+    options: DecoderContext<'_>,
+) -> Result<JsonPrimitive> {
+    if !events.builds_events() && options.warnings.is_none() {
+        // Only errors matter: check the token without copying it.
+        if let Err(err) = parse_borrowed_primitive(token) {
+            events.record(err.at_line(line_number))?;
+        }
+        return Ok(JsonPrimitive::Null);
     }
+    let value = match parse_primitive_token_with(token, options.preserve_number_literals) {
+        Ok(value) => value,
+        Err(err) => {
+            events.record(err.at_line(line_number))?;
+            return Ok(JsonPrimitive::String(token.trim().to_string()));
+        }
+    };
+    if let Some(warnings) = options.warnings {
+        if let Some(message) = ambiguity_message(token, &value) {
+            warnings.borrow_mut().push(DecodeWarning {
+                line: line_number,
+                kind: DecodeWarningKind::AmbiguousValue,
+                message,
+            });
+        }
+    }
+    Ok(value)
+}
 
-    events.push(JsonStreamEvent::Primitive {
-        value: parse_primitive_token(&after_hyphen)?,
-    });
-    Ok(())
+fn parse_value_tokens(
+    events: &mut EventSink,
+    values: &[&str],
+    line_number: usize,
+    options: DecoderContext<'_>,
+) -> Result<Vec<JsonPrimitive>> {
+    values
+        .iter()
+        .map(|value| parse_value_token(events, value, line_number, options))
+        .collect()
 }
 
-fn yield_object_from_fields(
-    events: &mut Vec<JsonStreamEvent>,
-    fields: &[FieldName],
-    primitives: &[crate::JsonPrimitive],
+/// Check a count of items, rows or row values against the declared one:
+/// an error with the length-marker check on, otherwise a warning when a sink
+/// is attached.
+fn check_count(
+    events: &mut EventSink,
+    actual: usize,
+    expected: usize,
+    item_type: &str,
+    line_number: usize,
+    options: DecoderContext<'_>,
+) -> Result<()> {
+    if let Some(warnings) = options.warnings {
+        let checked = options.strictness.length_markers;
+        if !checked && actual != expected && expected != UNMARKED_LENGTH {
+            warnings.borrow_mut().push(DecodeWarning {
+                line: line_number,
+                kind: DecodeWarningKind::LengthMismatch,
+                message: format!("Expected {expected} {item_type}, but got {actual}"),
+            });
+        }
+    }
+    events.check(
+        assert_expected_count(
+            actual,
+            expected,
+            item_type,
+            options.strictness.length_markers,
+        )
+        .map_err(|err| err.at_line(line_number)),
+    )
+}
+
+/// Flag a line ending in whitespace, and tabs in indentation let through
+/// with the tab check off.
+fn push_whitespace_warnings(
+    warnings: &mut Vec<DecodeWarning>,
+    line: &ParsedLine<'_>,
+    reject_tabs: bool,
 ) {
-    events.push(JsonStreamEvent::StartObject);
-    for (idx, field) in fields.iter().enumerate() {
-        events.push(JsonStreamEvent::Key {
-            key: field.name.clone(),
-            was_quoted: field.was_quoted,
+    let indentation = &line.raw[..line.raw.len() - line.raw.trim_start_matches([' ', '\t']).len()];
+    if !reject_tabs && indentation.contains('\t') {
+        warnings.push(DecodeWarning {
+            line: line.line_number,
+            kind: DecodeWarningKind::TabIndentation,
+            message: "indentation contains tabs, accepted because tabs are not checked".to_string(),
+        });
+    }
+    if line.raw.ends_with([' ', '\t']) {
+        warnings.push(DecodeWarning {
+            line: line.line_number,
+            kind: DecodeWarningKind::TrailingWhitespace,
+            message: "line ends with whitespace".to_string(),
         });
+    }
+}
+
+/// Skip the lines nested deeper than `depth` under a line that could not be
+/// decoded.
+fn skip_nested(events: &mut EventSink, cursor: &mut StreamingLineCursor<'_>, depth: Depth) {
+    while cursor.peek_sync().is_some_and(|line| line.depth > depth) {
+        events.advance(cursor);
+    }
+}
+
+fn current_line_number(cursor: &StreamingLineCursor<'_>) -> usize {
+    cursor.current().map_or(0, |line| line.line_number)
+}
+
+fn next_line_number(cursor: &StreamingLineCursor<'_>) -> usize {
+    cursor.peek_sync().map_or(0, |line| line.line_number)
+}
+
+fn next_line<'c>(cursor: &'c StreamingLineCursor<'_>) -> Option<(Depth, &'c str)> {
+    cursor.peek_sync().map(|line| (line.depth, &*line.content))
+}
+
+fn yield_object_from_fields(
+    events: &mut EventSink,
+    fields: &[FieldName<'_>],
+    primitives: &[JsonPrimitive],
+) -> Result<()> {
+    events.push(JsonStreamEvent::StartObject)?;
+    for (idx, field) in fields.iter().enumerate() {
+        events.push_key(field.name.clone(), field.was_quoted)?;
         if let Some(value) = primitives.get(idx) {
             events.push(JsonStreamEvent::Primitive {
                 value: value.clone(),
-            });
+            })?;
         } else {
             events.push(JsonStreamEvent::Primitive {
                 value: crate::StringOrNumberOrBoolOrNull::Null,
-            });
+            })?;
         }
     }
-    events.push(JsonStreamEvent::EndObject);
+    events.push(JsonStreamEvent::EndObject)
 }
 
-fn is_key_value_line_sync(line: &ParsedLine) -> bool {
-    let content = line.content.as_str();
-    if content.starts_with('"') {
-        if let Some(closing) = find_closing_quote(content, 0) {
-            return content[closing + 1..].contains(COLON);
-        }
-        return false;
-    }
-    content.contains(COLON)
+/// Reject a header-shaped key whose brackets hold no valid length.
+///
+/// # Errors
+///
+/// Returns a validation error naming the malformed header.
+pub fn check_length_marker(content: &str, line_number: usize) -> Result<()> {
+    let Some(parsed) = parse_unmarked_array_header(content, DEFAULT_DELIMITER)? else {
+        return Ok(());
+    };
+    let target = parsed
+        .header
+        .key
+        .map_or_else(|| "root array".to_string(), |key| format!("array `{key}`"));
+    Err(ToonError::decode(
+        DecodeErrorKind::InvalidLength,
+        format!("{target} has no valid length marker"),
+    )
+    .at_line(line_number))
 }
@@ -1,6 +1,7 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use crate::error::{Result, ToonError};
+use crate::options::DuplicateKeyPolicy;
 use crate::{JsonPrimitive, JsonStreamEvent, JsonValue};
 
 #[derive(Debug, Clone, PartialEq)]
@@ -10,10 +11,42 @@ pub enum NodeValue {
     Object(ObjectNode),
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct ObjectNode {
     pub entries: Vec<(String, NodeValue)>,
-    pub quoted_keys: HashSet<String>,
+    /// Indices into `entries` whose key was quoted in the source, so a
+    /// quoted `"a.b"` stays apart from a dotted `a.b` with the same text.
+    pub quoted_entries: HashSet<usize>,
+}
+
+impl ObjectNode {
+    /// The entries with their quoted flags, for reordering them together.
+    fn into_flagged(self) -> Vec<(String, NodeValue, bool)> {
+        let quoted = self.quoted_entries;
+        self.entries
+            .into_iter()
+            .enumerate()
+            .map(|(index, (key, value))| (key, value, quoted.contains(&index)))
+            .collect()
+    }
+
+    fn from_flagged(flagged: Vec<(String, NodeValue, bool)>) -> Self {
+        let mut quoted_entries = HashSet::new();
+        let entries = flagged
+            .into_iter()
+            .enumerate()
+            .map(|(index, (key, value, quoted))| {
+                if quoted {
+                    quoted_entries.insert(index);
+                }
+                (key, value)
+            })
+            .collect();
+        Self {
+            entries,
+            quoted_entries,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -21,7 +54,7 @@ enum BuildContext {
     Object {
         entries: Vec<(String, NodeValue)>,
         current_key: Option<String>,
-        quoted_keys: HashSet<String>,
+        quoted_entries: HashSet<usize>,
     },
     Array {
         items: Vec<NodeValue>,
@@ -68,6 +101,189 @@ pub fn node_to_json(value: NodeValue) -> JsonValue {
     }
 }
 
+/// Convert a node tree to `serde_json::Value`. A repeated key keeps its
+/// first position and last value, as converting from [`JsonValue`] does.
+pub fn node_to_serde(value: NodeValue) -> serde_json::Value {
+    match value {
+        NodeValue::Primitive(value) => serde_json::Value::from(value),
+        NodeValue::Array(items) => {
+            serde_json::Value::Array(items.into_iter().map(node_to_serde).collect())
+        }
+        NodeValue::Object(obj) => serde_json::Value::Object(
+            obj.entries
+                .into_iter()
+                .map(|(key, value)| (key, node_to_serde(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Build a `serde_json::Value` straight from a stream of events, skipping
+/// the [`NodeValue`] tree. Keys repeat as in [`node_to_serde`].
+///
+/// # Errors
+///
+/// Returns an error if the event stream is malformed (mismatched start/end
+/// events, missing keys, or incomplete stacks).
+pub fn build_serde_from_events(
+    events: impl IntoIterator<Item = JsonStreamEvent>,
+) -> Result<serde_json::Value> {
+    let mut builder = SerdeBuilder::default();
+    for event in events {
+        builder.push(event)?;
+    }
+    builder.finish()
+}
+
+/// [`build_serde_from_events`] one event at a time, for a decoder that
+/// hands events over as it reads them.
+#[derive(Debug, Default)]
+pub(crate) struct SerdeBuilder {
+    stack: Vec<SerdeFrame>,
+    root: Option<serde_json::Value>,
+}
+
+#[derive(Debug)]
+enum SerdeFrame {
+    Object(serde_json::Map<String, serde_json::Value>, Option<String>),
+    Array(Vec<serde_json::Value>),
+}
+
+impl SerdeBuilder {
+    pub(crate) fn push(&mut self, event: JsonStreamEvent) -> Result<()> {
+        let value = match event {
+            JsonStreamEvent::StartObject => {
+                self.stack
+                    .push(SerdeFrame::Object(serde_json::Map::new(), None));
+                return Ok(());
+            }
+            JsonStreamEvent::StartArray { .. } => {
+                self.stack.push(SerdeFrame::Array(Vec::new()));
+                return Ok(());
+            }
+            JsonStreamEvent::Key { key, .. } => {
+                let Some(SerdeFrame::Object(_, current_key)) = self.stack.last_mut() else {
+                    return Err(ToonError::unexpected_event(
+                        "Key",
+                        "outside of object context",
+                    ));
+                };
+                *current_key = Some(key);
+                return Ok(());
+            }
+            JsonStreamEvent::EndObject => match self.stack.pop() {
+                Some(SerdeFrame::Object(map, _)) => serde_json::Value::Object(map),
+                Some(SerdeFrame::Array(_)) => {
+                    return Err(ToonError::mismatched_end("Object", "Array"));
+                }
+                None => {
+                    return Err(ToonError::unexpected_event("endObject", "with empty stack"));
+                }
+            },
+            JsonStreamEvent::EndArray => match self.stack.pop() {
+                Some(SerdeFrame::Array(items)) => serde_json::Value::Array(items),
+                Some(SerdeFrame::Object(..)) => {
+                    return Err(ToonError::mismatched_end("Array", "Object"));
+                }
+                None => return Err(ToonError::unexpected_event("endArray", "with empty stack")),
+            },
+            JsonStreamEvent::Primitive { value } => serde_json::Value::from(value),
+        };
+        match self.stack.last_mut() {
+            Some(SerdeFrame::Object(map, current_key)) => {
+                let Some(key) = current_key.take() else {
+                    return Err(ToonError::message(
+                        "Value event without preceding key in object",
+                    ));
+                };
+                map.insert(key, value);
+            }
+            Some(SerdeFrame::Array(items)) => items.push(value),
+            None => self.root = Some(value),
+        }
+        Ok(())
+    }
+
+    pub(crate) fn finish(self) -> Result<serde_json::Value> {
+        if !self.stack.is_empty() {
+            return Err(ToonError::event_stream(
+                "Incomplete event stream: stack not empty at end",
+            ));
+        }
+        self.root
+            .ok_or_else(|| ToonError::event_stream("No root value built from events"))
+    }
+}
+
+/// Sort object entries by key at every level of the tree.
+///
+/// The sort is stable, so duplicate keys keep their relative order.
+pub fn sort_node_keys(value: &mut NodeValue) {
+    match value {
+        NodeValue::Primitive(_) => {}
+        NodeValue::Array(items) => items.iter_mut().for_each(sort_node_keys),
+        NodeValue::Object(obj) => {
+            let mut flagged = std::mem::take(obj).into_flagged();
+            flagged.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+            *obj = ObjectNode::from_flagged(flagged);
+            for (_, child) in &mut obj.entries {
+                sort_node_keys(child);
+            }
+        }
+    }
+}
+
+/// Resolve keys repeated within an object by `policy`, at every level of the
+/// tree. `Error` leaves the tree as is, since the decoder rejects duplicates.
+pub fn resolve_duplicate_keys(value: &mut NodeValue, policy: DuplicateKeyPolicy) {
+    match value {
+        NodeValue::Primitive(_) => {}
+        NodeValue::Array(items) => items
+            .iter_mut()
+            .for_each(|item| resolve_duplicate_keys(item, policy)),
+        NodeValue::Object(obj) => {
+            if policy != DuplicateKeyPolicy::Error {
+                let flagged = std::mem::take(obj).into_flagged();
+                *obj = ObjectNode::from_flagged(merge_duplicate_entries(flagged, policy));
+            }
+            for (_, child) in &mut obj.entries {
+                resolve_duplicate_keys(child, policy);
+            }
+        }
+    }
+}
+
+/// The merged entry keeps the position, and quoted flag, of the first key.
+fn merge_duplicate_entries(
+    entries: Vec<(String, NodeValue, bool)>,
+    policy: DuplicateKeyPolicy,
+) -> Vec<(String, NodeValue, bool)> {
+    let mut positions: HashMap<String, usize> = HashMap::with_capacity(entries.len());
+    let mut groups: Vec<(String, bool, Vec<NodeValue>)> = Vec::with_capacity(entries.len());
+    for (key, value, quoted) in entries {
+        if let Some(&index) = positions.get(&key) {
+            groups[index].2.push(value);
+        } else {
+            positions.insert(key.clone(), groups.len());
+            groups.push((key, quoted, vec![value]));
+        }
+    }
+    groups
+        .into_iter()
+        .filter_map(|(key, quoted, mut values)| {
+            let value = match policy {
+                _ if values.len() == 1 => values.pop(),
+                DuplicateKeyPolicy::Error | DuplicateKeyPolicy::FirstWins => {
+                    values.into_iter().next()
+                }
+                DuplicateKeyPolicy::LastWins => values.pop(),
+                DuplicateKeyPolicy::CollectIntoArray => Some(NodeValue::Array(values)),
+            };
+            value.map(|value| (key, value, quoted))
+        })
+        .collect()
+}
+
 #[allow(clippy::too_many_lines)]
 fn apply_event(state: &mut BuildState, event: JsonStreamEvent) -> Result<()> {
     match event {
@@ -75,7 +291,7 @@ fn apply_event(state: &mut BuildState, event: JsonStreamEvent) -> Result<()> {
             state.stack.push(BuildContext::Object {
                 entries: Vec::new(),
                 current_key: None,
-                quoted_keys: HashSet::new(),
+                quoted_entries: HashSet::new(),
             });
         }
         JsonStreamEvent::EndObject => {
@@ -84,7 +300,7 @@ fn apply_event(state: &mut BuildState, event: JsonStreamEvent) -> Result<()> {
             };
             let BuildContext::Object {
                 entries,
-                quoted_keys,
+                quoted_entries,
                 ..
             } = context
             else {
@@ -92,7 +308,7 @@ fn apply_event(state: &mut BuildState, event: JsonStreamEvent) -> Result<()> {
             };
             let node = NodeValue::Object(ObjectNode {
                 entries,
-                quoted_keys,
+                quoted_entries,
             });
             if let Some(parent) = state.stack.last_mut() {
                 match parent {
@@ -151,9 +367,9 @@ fn apply_event(state: &mut BuildState, event: JsonStreamEvent) -> Result<()> {
         }
         JsonStreamEvent::Key { key, was_quoted } => {
             let Some(BuildContext::Object {
+                entries,
                 current_key,
-                quoted_keys,
-                ..
+                quoted_entries,
             }) = state.stack.last_mut()
             else {
                 return Err(ToonError::unexpected_event(
@@ -161,10 +377,11 @@ fn apply_event(state: &mut BuildState, event: JsonStreamEvent) -> Result<()> {
                     "outside of object context",
                 ));
             };
-            *current_key = Some(key.clone());
+            // The key's value is the next entry pushed.
             if was_quoted {
-                quoted_keys.insert(key);
+                quoted_entries.insert(entries.len());
             }
+            *current_key = Some(key);
         }
         JsonStreamEvent::Primitive { value } => {
             if state.stack.is_empty() {
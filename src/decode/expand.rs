@@ -1,158 +1,411 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use crate::decode::event_builder::{NodeValue, ObjectNode};
 use crate::error::{Result, ToonError};
-use crate::shared::constants::DOT;
-use crate::shared::validation::is_identifier_segment;
+use crate::options::{ExpandConflictPolicy, ExpandPathsMode, PathSegment};
+use crate::shared::constants::{CLOSE_BRACKET, DOT, OPEN_BRACKET};
+use crate::shared::validation::{is_identifier_segment, split_folded_key};
 
-/// Expand dotted keys into nested objects (safe mode).
+/// Expand dotted keys into nested structures according to `mode`.
+///
+/// `Safe` expands identifier-only paths into nested objects. `Indexed`
+/// additionally treats numeric segments (`items.0.name`) and bracketed
+/// indices (`items[0].name`) as array positions. `Aggressive` reads what key
+/// folding writes: quoted segments (`"a.b".c`) and bracketed indices
+/// (`a[0].b`).
+///
+/// Collisions are resolved by `on_conflict`; see [`ExpandConflictPolicy`].
+/// Array indices must fill positions in order, and stay below
+/// `max_array_length` when given.
 ///
 /// # Errors
 ///
-/// Returns an error in strict mode when path expansion encounters a conflict.
-pub fn expand_paths_safe(value: NodeValue, strict: bool) -> Result<NodeValue> {
+/// Returns an error under [`ExpandConflictPolicy::Error`] when path expansion
+/// encounters a conflict, and under any policy for an array index that
+/// skips positions or exceeds `max_array_length`.
+pub fn expand_paths(
+    value: NodeValue,
+    mode: ExpandPathsMode,
+    on_conflict: ExpandConflictPolicy,
+    max_array_length: Option<usize>,
+) -> Result<NodeValue> {
+    if mode == ExpandPathsMode::Off {
+        return Ok(value);
+    }
+    let context = ExpandContext {
+        mode,
+        on_conflict,
+        max_array_length,
+    };
+    expand_value(value, context)
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ExpandContext {
+    mode: ExpandPathsMode,
+    on_conflict: ExpandConflictPolicy,
+    max_array_length: Option<usize>,
+}
+
+fn expand_value(value: NodeValue, context: ExpandContext) -> Result<NodeValue> {
     match value {
         NodeValue::Array(items) => {
             let mut expanded = Vec::with_capacity(items.len());
             for item in items {
-                expanded.push(expand_paths_safe(item, strict)?);
+                expanded.push(expand_value(item, context)?);
             }
             Ok(NodeValue::Array(expanded))
         }
-        NodeValue::Object(obj) => Ok(NodeValue::Object(expand_object(obj, strict)?)),
+        NodeValue::Object(obj) => Ok(NodeValue::Object(expand_object(obj, context)?)),
         NodeValue::Primitive(value) => Ok(NodeValue::Primitive(value)),
     }
 }
 
-fn expand_object(obj: ObjectNode, strict: bool) -> Result<ObjectNode> {
-    let quoted_keys = obj.quoted_keys;
-    let mut expanded = ObjectNode {
-        entries: Vec::new(),
-        quoted_keys: HashSet::new(),
-    };
+fn expand_object(obj: ObjectNode, context: ExpandContext) -> Result<ObjectNode> {
+    let quoted_entries = obj.quoted_entries;
+    let mut expanded = empty_object();
+    let mut state = ExpandState::new(context);
 
-    for (key, value) in obj.entries {
-        let value = expand_paths_safe(value, strict)?;
-        let is_quoted = quoted_keys.contains(&key);
-
-        if key.contains(DOT) && !is_quoted {
-            let segments: Vec<&str> = key.split(DOT).collect();
-            if segments
-                .iter()
-                .all(|segment| is_identifier_segment(segment))
-            {
-                insert_path_entries(&mut expanded.entries, &segments, value, strict)?;
+    for (index, (key, value)) in obj.entries.into_iter().enumerate() {
+        let value = expand_value(value, context)?;
+        let is_quoted = quoted_entries.contains(&index);
+
+        if !is_quoted {
+            if let Some(segments) = split_path(&key, context.mode) {
+                state.incoming = Origin::Folded;
+                insert_path_entries(&mut expanded.entries, &segments, value, &mut state)?;
                 continue;
             }
         }
 
-        insert_literal_entry(&mut expanded.entries, key, value, strict)?;
+        state.incoming = Origin::Literal;
+        insert_literal_entry(&mut expanded.entries, key, value, &mut state)?;
     }
 
     Ok(expanded)
 }
 
+/// Whether a value came from a literal key or from expanding a dotted one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Origin {
+    Literal,
+    Folded,
+}
+
+/// Insertion state for one object being expanded.
+struct ExpandState {
+    context: ExpandContext,
+    /// Origin of the entry being inserted.
+    incoming: Origin,
+    /// Position being written, relative to the object.
+    at: Vec<PathSegment>,
+    /// Origin of each position written so far; only kept for the
+    /// `Prefer*` policies, which need it.
+    origins: HashMap<Vec<PathSegment>, Origin>,
+}
+
+impl ExpandState {
+    fn new(context: ExpandContext) -> Self {
+        Self {
+            context,
+            incoming: Origin::Literal,
+            at: Vec::new(),
+            origins: HashMap::new(),
+        }
+    }
+
+    /// Run `insert` with `segment` appended to the current position.
+    fn enter<T>(&mut self, segment: PathSegment, insert: impl FnOnce(&mut Self) -> T) -> T {
+        self.at.push(segment);
+        let out = insert(self);
+        self.at.pop();
+        out
+    }
+
+    /// Note that the incoming entry now holds the current position.
+    fn record(&mut self) {
+        if !matches!(
+            self.context.on_conflict,
+            ExpandConflictPolicy::PreferFolded | ExpandConflictPolicy::PreferLiteral
+        ) {
+            return;
+        }
+        let Self {
+            at,
+            origins,
+            incoming,
+            ..
+        } = self;
+        origins.retain(|path, _| !path.starts_with(at));
+        origins.insert(at.clone(), *incoming);
+    }
+
+    /// Whether the incoming value replaces an existing one it cannot merge
+    /// with; `None` under [`ExpandConflictPolicy::Error`].
+    fn incoming_wins(&self) -> Option<bool> {
+        let prefer = match self.context.on_conflict {
+            ExpandConflictPolicy::Error => return None,
+            ExpandConflictPolicy::KeepFirst => return Some(false),
+            ExpandConflictPolicy::Overwrite | ExpandConflictPolicy::Merge => return Some(true),
+            ExpandConflictPolicy::PreferFolded => Origin::Folded,
+            ExpandConflictPolicy::PreferLiteral => Origin::Literal,
+        };
+        // The origin recorded closest above the position is the existing one.
+        let existing = (1..=self.at.len())
+            .rev()
+            .find_map(|len| self.origins.get(&self.at[..len]))
+            .copied()
+            .unwrap_or(Origin::Literal);
+        // Between values of the same origin the later one wins, as in `Merge`.
+        Some(existing == self.incoming || self.incoming == prefer)
+    }
+}
+
+/// Split an unquoted key into expansion segments, or `None` when the key
+/// should stay literal.
+fn split_path(key: &str, mode: ExpandPathsMode) -> Option<Vec<PathSegment>> {
+    match mode {
+        ExpandPathsMode::Off => None,
+        ExpandPathsMode::Aggressive => split_folded_key(key),
+        ExpandPathsMode::Safe => {
+            if !key.contains(DOT) {
+                return None;
+            }
+            key.split(DOT)
+                .map(|segment| {
+                    is_identifier_segment(segment).then(|| PathSegment::Key(segment.to_string()))
+                })
+                .collect()
+        }
+        ExpandPathsMode::Indexed => {
+            if !key.contains(DOT) && !key.contains(OPEN_BRACKET) {
+                return None;
+            }
+            let mut segments = Vec::new();
+            for token in key.split(DOT) {
+                if let Some(index) = parse_index(token) {
+                    if segments.is_empty() {
+                        return None;
+                    }
+                    segments.push(PathSegment::Index(index));
+                    continue;
+                }
+
+                let (name, mut rest) = token
+                    .find(OPEN_BRACKET)
+                    .map_or((token, ""), |pos| (&token[..pos], &token[pos..]));
+                if !is_identifier_segment(name) {
+                    return None;
+                }
+                segments.push(PathSegment::Key(name.to_string()));
+
+                while !rest.is_empty() {
+                    let close = rest.find(CLOSE_BRACKET)?;
+                    if !rest.starts_with(OPEN_BRACKET) {
+                        return None;
+                    }
+                    segments.push(PathSegment::Index(parse_index(&rest[1..close])?));
+                    rest = &rest[close + 1..];
+                }
+            }
+            (segments.len() > 1).then_some(segments)
+        }
+    }
+}
+
+fn parse_index(token: &str) -> Option<usize> {
+    if token.is_empty() || !token.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    if token.len() > 1 && token.starts_with('0') {
+        return None;
+    }
+    token.parse().ok()
+}
+
 fn insert_path_entries(
     entries: &mut Vec<(String, NodeValue)>,
-    segments: &[&str],
+    segments: &[PathSegment],
     value: NodeValue,
-    strict: bool,
+    state: &mut ExpandState,
 ) -> Result<()> {
-    if segments.is_empty() {
+    let Some((PathSegment::Key(key), rest)) = segments.split_first() else {
         return Ok(());
+    };
+
+    if rest.is_empty() {
+        return insert_literal_entry(entries, key.clone(), value, state);
     }
 
-    if segments.len() == 1 {
-        return insert_literal_entry(entries, segments[0].to_string(), value, strict);
+    state.enter(PathSegment::Key(key.clone()), |state| {
+        let index = if let Some(index) = find_entry_index(entries, key) {
+            if !ensure_container(&mut entries[index].1, &rest[0], key, state)? {
+                return Ok(());
+            }
+            index
+        } else {
+            entries.push((key.clone(), empty_container(&rest[0])));
+            state.record();
+            entries.len() - 1
+        };
+
+        descend(&mut entries[index].1, rest, value, state)
+    })
+}
+
+fn insert_index_entries(
+    items: &mut Vec<NodeValue>,
+    segments: &[PathSegment],
+    value: NodeValue,
+    state: &mut ExpandState,
+) -> Result<()> {
+    let Some((PathSegment::Index(position), rest)) = segments.split_first() else {
+        return Ok(());
+    };
+    let position = *position;
+
+    if let Some(max) = state
+        .context
+        .max_array_length
+        .filter(|max| position >= *max)
+    {
+        return Err(ToonError::limit_exceeded(format!(
+            "Path expansion index {position} exceeds the array length limit of {max}"
+        )));
+    }
+    // Only ever append: padding up to an index taken from the document
+    // would let a short key allocate without bound.
+    if position > items.len() {
+        return Err(ToonError::message(format!(
+            "Path expansion conflict at index {position}: array has only {} items",
+            items.len()
+        )));
     }
 
-    let key = segments[0].to_string();
-    if let Some(index) = find_entry_index(entries, &key) {
-        let needs_object = !matches!(entries[index].1, NodeValue::Object(_));
-        if needs_object {
-            if strict {
-                return Err(ToonError::message(format!(
-                    "Path expansion conflict at segment \"{key}\": expected object but found {existing}",
-                    existing = node_type_name(&entries[index].1)
-                )));
+    state.enter(PathSegment::Index(position), |state| {
+        let label = position.to_string();
+        if rest.is_empty() {
+            if position == items.len() {
+                items.push(value);
+                state.record();
+                return Ok(());
             }
-            entries[index].1 = NodeValue::Object(ObjectNode {
-                entries: Vec::new(),
-                quoted_keys: HashSet::new(),
-            });
+            return replace_or_merge(&mut items[position], value, &label, state);
         }
 
-        if let NodeValue::Object(obj) = &mut entries[index].1 {
-            return insert_path_entries(&mut obj.entries, &segments[1..], value, strict);
-        }
-    } else {
-        entries.push((
-            key,
-            NodeValue::Object(ObjectNode {
-                entries: Vec::new(),
-                quoted_keys: HashSet::new(),
-            }),
-        ));
-        let index = entries.len() - 1;
-        if let NodeValue::Object(obj) = &mut entries[index].1 {
-            return insert_path_entries(&mut obj.entries, &segments[1..], value, strict);
+        if position == items.len() {
+            items.push(empty_container(&rest[0]));
+            state.record();
+        } else if !ensure_container(&mut items[position], &rest[0], &label, state)? {
+            return Ok(());
         }
+
+        descend(&mut items[position], rest, value, state)
+    })
+}
+
+fn descend(
+    target: &mut NodeValue,
+    segments: &[PathSegment],
+    value: NodeValue,
+    state: &mut ExpandState,
+) -> Result<()> {
+    match target {
+        NodeValue::Object(obj) => insert_path_entries(&mut obj.entries, segments, value, state),
+        NodeValue::Array(items) => insert_index_entries(items, segments, value, state),
+        NodeValue::Primitive(_) => Ok(()),
     }
+}
 
-    Ok(())
+/// Make sure `existing` can hold the next path segment.
+///
+/// Returns `false` when the policy keeps the existing value and the rest of
+/// the path should be dropped.
+fn ensure_container(
+    existing: &mut NodeValue,
+    next: &PathSegment,
+    label: &str,
+    state: &mut ExpandState,
+) -> Result<bool> {
+    let fits = matches!(
+        (&*existing, next),
+        (NodeValue::Object(_), PathSegment::Key(_)) | (NodeValue::Array(_), PathSegment::Index(_))
+    );
+    if fits {
+        return Ok(true);
+    }
+
+    let Some(replace) = state.incoming_wins() else {
+        let expected = match next {
+            PathSegment::Key(_) => "object",
+            PathSegment::Index(_) => "array",
+        };
+        return Err(ToonError::message(format!(
+            "Path expansion conflict at segment \"{label}\": expected {expected} but found {existing}",
+            existing = node_type_name(existing)
+        )));
+    };
+    if replace {
+        *existing = empty_container(next);
+        state.record();
+    }
+    Ok(replace)
 }
 
 fn insert_literal_entry(
     entries: &mut Vec<(String, NodeValue)>,
     key: String,
     value: NodeValue,
-    strict: bool,
+    state: &mut ExpandState,
 ) -> Result<()> {
-    if let Some(index) = find_entry_index(entries, &key) {
-        let existing = entries[index].1.clone();
-        if can_merge(&existing, &value) {
-            let mut existing_obj = extract_object(existing)?;
-            let source_obj = extract_object(value)?;
-            merge_objects(&mut existing_obj, source_obj, strict)?;
-            entries[index].1 = NodeValue::Object(existing_obj);
-        } else if strict {
-            return Err(ToonError::message(format!(
-                "Path expansion conflict at key \"{key}\": cannot merge {left} with {right}",
-                left = node_type_name(&existing),
-                right = node_type_name(&value)
-            )));
+    state.enter(PathSegment::Key(key.clone()), |state| {
+        if let Some(index) = find_entry_index(entries, &key) {
+            replace_or_merge(&mut entries[index].1, value, &key, state)
         } else {
-            entries[index].1 = value;
+            entries.push((key, value));
+            state.record();
+            Ok(())
         }
-    } else {
-        entries.push((key, value));
-    }
-
-    Ok(())
+    })
 }
 
-fn merge_objects(target: &mut ObjectNode, source: ObjectNode, strict: bool) -> Result<()> {
-    for (key, value) in source.entries {
-        if let Some(index) = find_entry_index(&target.entries, &key) {
-            let existing = target.entries[index].1.clone();
-            if can_merge(&existing, &value) {
-                let mut existing_obj = extract_object(existing)?;
-                let source_obj = extract_object(value)?;
-                merge_objects(&mut existing_obj, source_obj, strict)?;
-                target.entries[index].1 = NodeValue::Object(existing_obj);
-            } else if strict {
+fn replace_or_merge(
+    existing: &mut NodeValue,
+    value: NodeValue,
+    label: &str,
+    state: &mut ExpandState,
+) -> Result<()> {
+    match (existing, value) {
+        (NodeValue::Object(existing_obj), NodeValue::Object(source_obj))
+            if state.context.on_conflict != ExpandConflictPolicy::Overwrite =>
+        {
+            merge_objects(existing_obj, source_obj, state)
+        }
+        (existing, value) => {
+            let Some(replace) = state.incoming_wins() else {
                 return Err(ToonError::message(format!(
-                    "Path expansion conflict at key \"{key}\": cannot merge {left} with {right}",
-                    left = node_type_name(&existing),
+                    "Path expansion conflict at key \"{label}\": cannot merge {left} with {right}",
+                    left = node_type_name(existing),
                     right = node_type_name(&value)
                 )));
-            } else {
-                target.entries[index].1 = value;
+            };
+            if replace {
+                *existing = value;
+                state.record();
             }
-        } else {
-            target.entries.push((key, value));
+            Ok(())
         }
     }
+}
+
+fn merge_objects(
+    target: &mut ObjectNode,
+    source: ObjectNode,
+    state: &mut ExpandState,
+) -> Result<()> {
+    for (key, value) in source.entries {
+        insert_literal_entry(&mut target.entries, key, value, state)?;
+    }
     Ok(())
 }
 
@@ -160,17 +413,17 @@ fn find_entry_index(entries: &[(String, NodeValue)], key: &str) -> Option<usize>
     entries.iter().position(|(k, _)| k == key)
 }
 
-const fn can_merge(left: &NodeValue, right: &NodeValue) -> bool {
-    matches!((left, right), (NodeValue::Object(_), NodeValue::Object(_)))
+fn empty_object() -> ObjectNode {
+    ObjectNode {
+        entries: Vec::new(),
+        quoted_entries: HashSet::new(),
+    }
 }
 
-fn extract_object(value: NodeValue) -> Result<ObjectNode> {
-    match value {
-        NodeValue::Object(obj) => Ok(obj),
-        other => Err(ToonError::message(format!(
-            "Expected object but found {}",
-            node_type_name(&other)
-        ))),
+fn empty_container(next: &PathSegment) -> NodeValue {
+    match next {
+        PathSegment::Key(_) => NodeValue::Object(empty_object()),
+        PathSegment::Index(_) => NodeValue::Array(Vec::new()),
     }
 }
 
@@ -0,0 +1,67 @@
+//! Running checks of decoded events against [`DecodeLimits`].
+
+use serde::{Deserialize, Serialize};
+
+use crate::decode::parser::UNMARKED_LENGTH;
+use crate::error::{Result, ToonError};
+use crate::options::DecodeLimits;
+use crate::{JsonPrimitive, JsonStreamEvent};
+
+/// Nesting depth and values seen so far in one document.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LimitCounter {
+    depth: usize,
+    nodes: usize,
+}
+
+impl LimitCounter {
+    /// Count `event`, failing if it takes the document past `limits`.
+    pub fn observe(&mut self, limits: &DecodeLimits, event: &JsonStreamEvent) -> Result<()> {
+        match event {
+            JsonStreamEvent::EndObject | JsonStreamEvent::EndArray => {
+                self.depth = self.depth.saturating_sub(1);
+                return Ok(());
+            }
+            JsonStreamEvent::Key { key, .. } => return check_string(limits, "Key", key),
+            JsonStreamEvent::StartObject | JsonStreamEvent::StartArray { .. } => {
+                self.depth += 1;
+                if let Some(max) = limits.max_depth.filter(|max| self.depth > *max) {
+                    return Err(ToonError::limit_exceeded(format!(
+                        "Nesting depth exceeds the limit of {max}"
+                    )));
+                }
+            }
+            JsonStreamEvent::Primitive { .. } => {}
+        }
+        match event {
+            JsonStreamEvent::StartArray { length } if *length != UNMARKED_LENGTH => {
+                if let Some(max) = limits.max_array_length.filter(|max| length > max) {
+                    return Err(ToonError::limit_exceeded(format!(
+                        "Declared array length {length} exceeds the limit of {max}"
+                    )));
+                }
+            }
+            JsonStreamEvent::Primitive {
+                value: JsonPrimitive::String(text),
+            } => check_string(limits, "String", text)?,
+            _ => {}
+        }
+        self.nodes += 1;
+        if let Some(max) = limits.max_nodes.filter(|max| self.nodes > *max) {
+            return Err(ToonError::limit_exceeded(format!(
+                "Document exceeds the limit of {max} values"
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn check_string(limits: &DecodeLimits, what: &str, text: &str) -> Result<()> {
+    match limits.max_string_bytes {
+        Some(max) if text.len() > max => Err(ToonError::limit_exceeded(format!(
+            "{what} of {} bytes exceeds the limit of {max}",
+            text.len()
+        ))),
+        _ => Ok(()),
+    }
+}
@@ -1,24 +1,43 @@
+mod borrowed;
 pub mod decoders;
 pub mod event_builder;
 pub mod expand;
+mod limits;
 pub mod parser;
+mod push;
+mod reader;
 pub mod scanner;
 pub mod validation;
+pub mod warnings;
 
 #[cfg(feature = "async-stream")]
 mod async_decode;
+#[cfg(feature = "async-stream")]
+mod async_reader;
+
+pub use borrowed::decode_borrowed;
+pub use push::{ParserCheckpoint, ToonParser};
 
 use crate::decode::decoders as decoder_impl;
-use crate::decode::event_builder::{build_node_from_events, node_to_json};
-use crate::decode::expand::expand_paths_safe;
-use crate::error::Result;
-use crate::options::{DecodeOptions, DecodeStreamOptions, ExpandPathsMode, resolve_decode_options};
-use crate::{JsonStreamEvent, JsonValue};
+use crate::decode::event_builder::{
+    build_node_from_events, node_to_json, node_to_serde, resolve_duplicate_keys, sort_node_keys,
+};
+use crate::decode::expand::expand_paths;
+use crate::decode::reader::ReaderLines;
+use crate::decode::warnings::{DecodeWarning, WarningSink};
+use crate::error::{DecodeError, DecodeErrorKind, Result, ToonError};
+use crate::options::{
+    DecodeOptions, DecodeStreamOptions, DuplicateKeyPolicy, ExpandPathsMode, KeyOrder,
+    ResolvedDecodeOptions, resolve_decode_options,
+};
+use crate::{JsonPrimitive, JsonStreamEvent, JsonValue};
 
 #[cfg(feature = "async-stream")]
 pub use async_decode::{
     AsyncDecodeStream, decode_stream_async, try_decode_async, try_decode_stream_async,
 };
+#[cfg(feature = "async-stream")]
+pub use async_reader::{AsyncReaderDecodeStream, try_decode_stream_from_reader_async};
 
 /// Try to decode a TOON string into a JSON value, returning a Result.
 ///
@@ -47,6 +66,270 @@ pub fn decode(input: &str, options: Option<DecodeOptions>) -> JsonValue {
     try_decode(input, options).unwrap_or_else(|err| panic!("{err}"))
 }
 
+/// Decode a TOON string, also returning its non-fatal warnings.
+///
+/// Shorthand for [`try_decode`] with a fresh
+/// [`collect_warnings`](DecodeOptions::collect_warnings) sink, so it reports
+/// every [`DecodeWarningKind`](crate::DecodeWarningKind). A sink already set
+/// in `options` receives the warnings too.
+///
+/// # Errors
+///
+/// Returns an error if decoding fails due to malformed input or strict-mode validation errors.
+pub fn try_decode_with_warnings(
+    input: &str,
+    options: Option<DecodeOptions>,
+) -> Result<(JsonValue, Vec<DecodeWarning>)> {
+    let mut options = options.unwrap_or_default();
+    let sink = WarningSink::new();
+    let caller_sink = options.collect_warnings.replace(sink.clone());
+    let value = try_decode(input, Some(options));
+    let warnings = sink.take();
+    if let Some(caller_sink) = caller_sink {
+        caller_sink.extend(warnings.iter().cloned());
+    }
+    Ok((value?, warnings))
+}
+
+/// Decode a TOON string without stopping at the first error, returning a
+/// best-effort value and every error found, in line order.
+///
+/// Useful for linting hand-written documents. A value that does not parse is
+/// kept as its source text, a line that cannot be decoded is dropped along
+/// with the lines nested under it, and count, blank-line and duplicate-key
+/// checks only report. With no errors, the value is what [`try_decode`]
+/// returns. A document past one of its [`limits`](DecodeOptions::limits)
+/// stops there, with a null value.
+///
+/// ```
+/// let (value, errors) = toon::decode_all_errors("a: 1\nb\nc[3]: x,y\nd: 2", None);
+/// assert_eq!(value, toon::decode("a: 1\nc[2]: x,y\nd: 2", None));
+/// let lines: Vec<usize> = errors
+///     .iter()
+///     .filter_map(|err| err.decode_error().map(|err| err.line))
+///     .collect();
+/// assert_eq!(lines, [2, 3]);
+/// ```
+#[must_use]
+pub fn decode_all_errors(
+    input: &str,
+    options: Option<DecodeOptions>,
+) -> (JsonValue, Vec<ToonError>) {
+    let resolved = resolve_decode_options(options);
+    let lines = input.split('\n').map(std::string::ToString::to_string);
+    let (events, mut errors) =
+        decoder_impl::decode_stream_sync_recovering(lines, Some(resolved.stream_options()));
+    if events.is_empty() {
+        // Decoding stopped at a limit; there is no value to recover.
+        return (JsonValue::Primitive(JsonPrimitive::Null), errors);
+    }
+    let mut node = match build_node_from_events(events) {
+        Ok(node) => node,
+        Err(err) => {
+            errors.push(err);
+            return (JsonValue::Primitive(JsonPrimitive::Null), errors);
+        }
+    };
+    if let Some(policy) = resolved.duplicate_keys {
+        resolve_duplicate_keys(&mut node, policy);
+    }
+    if resolved.expand_paths != ExpandPathsMode::Off {
+        match expand_paths(
+            node.clone(),
+            resolved.expand_paths,
+            resolved.on_conflict,
+            resolved.limits.and_then(|limits| limits.max_array_length),
+        ) {
+            Ok(expanded) => node = expanded,
+            Err(err) => errors.push(err),
+        }
+    }
+    if resolved.key_order == KeyOrder::Sorted {
+        sort_node_keys(&mut node);
+    }
+    (node_to_json(node), errors)
+}
+
+/// Check a TOON string for every error [`decode_all_errors`] would report,
+/// without building a value.
+///
+/// Syntax, indentation, declared lengths, blank lines, duplicate keys and
+/// [`limits`](DecodeOptions::limits) are checked as the decoder reads each
+/// line. Lines are borrowed from `input` and no events are built, so no line,
+/// key or value is copied; memory goes to an index of the content lines and,
+/// with duplicate keys rejected, the keys of the open objects. Setting
+/// `limits` builds each event to measure it, and warnings each value to
+/// inspect it. Path expansion is not attempted, so its conflicts are not
+/// reported.
+///
+/// ```
+/// assert!(toon::validate("a: 1\ntags[2]: x,y", None).is_ok());
+///
+/// let errors = toon::validate("a: 1\nb\nc[3]: x,y", None).unwrap_err();
+/// let lines: Vec<usize> = errors.iter().map(|err| err.line).collect();
+/// assert_eq!(lines, [2, 3]);
+/// ```
+///
+/// # Errors
+///
+/// Returns every error found, in line order.
+pub fn validate(
+    input: &str,
+    options: Option<DecodeOptions>,
+) -> std::result::Result<(), Vec<DecodeError>> {
+    let resolved = resolve_decode_options(options);
+    let errors =
+        decoder_impl::validate_stream_sync(input.split('\n'), Some(resolved.stream_options()));
+    if errors.is_empty() {
+        return Ok(());
+    }
+    Err(errors
+        .into_iter()
+        .map(|err| match err {
+            ToonError::Decode(err) => *err,
+            err => DecodeError {
+                kind: DecodeErrorKind::Structure,
+                line: 0,
+                columns: 0..0,
+                source_line: String::new(),
+                message: err.to_string(),
+            },
+        })
+        .collect())
+}
+
+/// Decode a TOON string straight into a `serde_json::Value`.
+///
+/// Equivalent to `serde_json::Value::from(try_decode(input, options)?)`,
+/// but without the intermediate [`JsonValue`]. Lines are borrowed from
+/// `input`, and unless paths are expanded, keys sorted or duplicates
+/// resolved, each event goes into the tree as soon as it is decoded.
+///
+/// ```
+/// let value = toon::decode_to_serde("user:\n  name: Ada\n  tags[2]: a,b", None).unwrap();
+/// assert_eq!(value, serde_json::json!({"user": {"name": "Ada", "tags": ["a", "b"]}}));
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if decoding fails due to malformed input or strict-mode validation errors.
+pub fn decode_to_serde(input: &str, options: Option<DecodeOptions>) -> Result<serde_json::Value> {
+    let resolved = resolve_decode_options(options);
+    let lines = input.split('\n');
+    if resolved.expand_paths == ExpandPathsMode::Off
+        && resolved.key_order == KeyOrder::Preserve
+        && resolved
+            .duplicate_keys
+            .is_none_or(|policy| policy == DuplicateKeyPolicy::Error)
+    {
+        return decoder_impl::decode_to_serde_sync(lines, Some(resolved.stream_options()));
+    }
+
+    let events = decoder_impl::decode_stream_sync(lines, Some(resolved.stream_options()))?;
+    let mut node = build_node_from_events(events)?;
+    if let Some(policy) = resolved.duplicate_keys {
+        resolve_duplicate_keys(&mut node, policy);
+    }
+    node = expand_paths(
+        node,
+        resolved.expand_paths,
+        resolved.on_conflict,
+        resolved.limits.and_then(|limits| limits.max_array_length),
+    )?;
+    if resolved.key_order == KeyOrder::Sorted {
+        sort_node_keys(&mut node);
+    }
+    Ok(node_to_serde(node))
+}
+
+/// Decode a document whose top level is an object with homogeneous values
+/// into any map type, e.g. `BTreeMap<String, T>` or `HashMap<String, T>`.
+///
+/// Keys are parsed with [`FromStr`](std::str::FromStr) and values are
+/// deserialized with [`from_value`](crate::from_value): integers, and whole
+/// floats within the `f64`-safe integer range, deserialize into integer
+/// fields.
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// let ports: BTreeMap<String, u16> = toon::decode_map("http: 80\nhttps: 443", None).unwrap();
+/// assert_eq!(ports["https"], 443);
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if decoding fails, the top level is not an object, or a
+/// key or value does not convert, naming the key at fault.
+pub fn decode_map<M, K, V>(input: &str, options: Option<DecodeOptions>) -> Result<M>
+where
+    M: FromIterator<(K, V)>,
+    K: std::str::FromStr,
+    K::Err: std::fmt::Display,
+    V: serde::de::DeserializeOwned,
+{
+    let JsonValue::Object(entries) = try_decode(input, options)? else {
+        return Err(ToonError::message(
+            "decode_map: expected an object at the top level",
+        ));
+    };
+    entries
+        .into_iter()
+        .map(|(key, value)| {
+            let parsed_key = key
+                .parse::<K>()
+                .map_err(|err| ToonError::json(format!("Key '{key}': {err}")))?;
+            let parsed_value = crate::de::from_value(value)
+                .map_err(|err| ToonError::message(format!("Value for key '{key}': {err}")))?;
+            Ok((parsed_key, parsed_value))
+        })
+        .collect()
+}
+
+/// Decode TOON read from any [`io::Read`](std::io::Read), such as a `File`
+/// or a socket.
+///
+/// Input is buffered internally and split on `\n` or `\r\n`. With
+/// [`DecodeOptions::max_line_length`] set, an over-long line fails without
+/// being read into memory.
+///
+/// ```
+/// let value = toon::decode_from_reader("name: Ada\r\ntags[2]: a,b\r\n".as_bytes(), None).unwrap();
+/// assert_eq!(value, toon::decode("name: Ada\ntags[2]: a,b", None));
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if reading fails, a line is not valid UTF-8, or decoding
+/// fails due to malformed input or strict-mode validation errors.
+pub fn decode_from_reader(
+    reader: impl std::io::Read,
+    options: Option<DecodeOptions>,
+) -> Result<JsonValue> {
+    let resolved = resolve_decode_options(options);
+    let mut lines = ReaderLines::new(reader, resolved.max_line_length);
+    let events = decoder_impl::decode_stream_sync(&mut lines, Some(resolved.stream_options()));
+    let events = lines.finish(events)?;
+    events_to_value(events, &resolved)
+}
+
+/// Decode TOON read from any [`io::Read`](std::io::Read) into a stream of
+/// events, splitting lines as [`decode_from_reader`] does.
+///
+/// # Errors
+///
+/// Returns an error if reading fails, a line is not valid UTF-8, or decoding
+/// fails due to malformed input or strict-mode validation errors.
+pub fn decode_stream_from_reader(
+    reader: impl std::io::Read,
+    options: Option<DecodeStreamOptions>,
+) -> Result<Vec<JsonStreamEvent>> {
+    let max_line_length = options.as_ref().and_then(|options| options.max_line_length);
+    let mut lines = ReaderLines::new(reader, max_line_length);
+    let events = decoder_impl::decode_stream_sync(&mut lines, options);
+    lines.finish(events)
+}
+
 /// Try to decode TOON lines into a JSON value, returning a Result.
 ///
 /// This is the fallible version of [`decode_from_lines`]. Use this when you want to handle
@@ -60,18 +343,32 @@ pub fn try_decode_from_lines(
     options: Option<DecodeOptions>,
 ) -> Result<JsonValue> {
     let resolved = resolve_decode_options(options);
-    let events = decoder_impl::decode_stream_sync(
-        lines,
-        Some(DecodeStreamOptions {
-            indent: Some(resolved.indent),
-            strict: Some(resolved.strict),
-        }),
-    )?;
+    let events = decoder_impl::decode_stream_sync(lines, Some(resolved.stream_options()))?;
+    events_to_value(events, &resolved)
+}
 
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(events = events.len()))
+)]
+fn events_to_value(
+    events: Vec<JsonStreamEvent>,
+    resolved: &ResolvedDecodeOptions,
+) -> Result<JsonValue> {
     let mut node = build_node_from_events(events)?;
+    if let Some(policy) = resolved.duplicate_keys {
+        resolve_duplicate_keys(&mut node, policy);
+    }
+
+    node = expand_paths(
+        node,
+        resolved.expand_paths,
+        resolved.on_conflict,
+        resolved.limits.and_then(|limits| limits.max_array_length),
+    )?;
 
-    if resolved.expand_paths == ExpandPathsMode::Safe {
-        node = expand_paths_safe(node, resolved.strict)?;
+    if resolved.key_order == KeyOrder::Sorted {
+        sort_node_keys(&mut node);
     }
 
     Ok(node_to_json(node))
@@ -1,29 +1,36 @@
-use crate::error::{Result, ToonError};
+use std::borrow::Cow;
+
+use crate::borrowed::BorrowedPrimitive;
+use crate::error::{DecodeErrorKind, Result, ToonError};
 use crate::shared::constants::{
-    BACKSLASH, CLOSE_BRACE, CLOSE_BRACKET, COLON, DOUBLE_QUOTE, OPEN_BRACE, OPEN_BRACKET, PIPE, TAB,
+    BACKSLASH, CLOSE_BRACE, CLOSE_BRACKET, COLON, DOT, DOUBLE_QUOTE, OPEN_BRACE, OPEN_BRACKET,
+    PIPE, SEMICOLON, TAB,
 };
 use crate::shared::literal_utils::{is_boolean_or_null_literal, is_numeric_literal};
-use crate::shared::string_utils::{find_closing_quote, find_unquoted_char, unescape_string};
+use crate::shared::string_utils::{find_closing_quote, find_unquoted_char, unescape_str};
+use crate::shared::validation::split_folded_key;
 
+/// A parsed array header. Keys and field names borrow from the header line
+/// unless they hold escapes.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct ArrayHeaderInfo {
-    pub key: Option<String>,
+pub struct ArrayHeaderInfo<'a> {
+    pub key: Option<Cow<'a, str>>,
     pub key_was_quoted: bool,
     pub length: usize,
     pub delimiter: char,
-    pub fields: Option<Vec<FieldName>>,
+    pub fields: Option<Vec<FieldName<'a>>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct FieldName {
-    pub name: String,
+pub struct FieldName<'a> {
+    pub name: Cow<'a, str>,
     pub was_quoted: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct ArrayHeaderParseResult {
-    pub header: ArrayHeaderInfo,
-    pub inline_values: Option<String>,
+pub struct ArrayHeaderParseResult<'a> {
+    pub header: ArrayHeaderInfo<'a>,
+    pub inline_values: Option<&'a str>,
 }
 
 /// Parse a TOON array header line, returning header metadata and inline values.
@@ -34,23 +41,54 @@ pub struct ArrayHeaderParseResult {
 pub fn parse_array_header_line(
     content: &str,
     default_delimiter: char,
-) -> Result<Option<ArrayHeaderParseResult>> {
-    let trimmed = content.trim_start();
+) -> Result<Option<ArrayHeaderParseResult<'_>>> {
+    Ok(scan_array_header_line(content, default_delimiter)?
+        .and_then(|(header, marked)| marked.then_some(header)))
+}
+
+/// Length given to headers parsed without a valid marker: readers take every
+/// item at the array's depth.
+pub const UNMARKED_LENGTH: usize = usize::MAX;
+
+/// Parse a line shaped like an array header whose brackets hold no valid
+/// length, such as `items[]:` or `rows[n]{id,name}:`. The returned header
+/// has a length of [`UNMARKED_LENGTH`].
+///
+/// # Errors
+///
+/// Returns an error for malformed quoted keys or string literals.
+pub fn parse_unmarked_array_header(
+    content: &str,
+    default_delimiter: char,
+) -> Result<Option<ArrayHeaderParseResult<'_>>> {
+    Ok(scan_array_header_line(content, default_delimiter)?
+        .and_then(|(header, marked)| (!marked).then_some(header)))
+}
 
-    let bracket_start = if trimmed.starts_with(DOUBLE_QUOTE) {
-        let closing = find_closing_quote(trimmed, 0)
-            .ok_or_else(|| ToonError::message("Unterminated string: missing closing quote"))?;
+/// Parse a header-shaped line, flagging whether its length marker is valid.
+fn scan_array_header_line(
+    content: &str,
+    default_delimiter: char,
+) -> Result<Option<(ArrayHeaderParseResult<'_>, bool)>> {
+    let trimmed = content.trim_start();
+    let leading_ws = content.len() - trimmed.len();
+    let path_end =
+        quoted_path_end(trimmed, 0).filter(|&end| trimmed[end..].starts_with(OPEN_BRACKET));
+
+    let bracket_start = if let Some(path_end) = path_end {
+        Some(leading_ws + path_end)
+    } else if trimmed.starts_with(DOUBLE_QUOTE) {
+        let closing = find_closing_quote(trimmed, 0).ok_or_else(ToonError::unterminated_string)?;
         let after_quote = &trimmed[closing + 1..];
         if !after_quote.starts_with(OPEN_BRACKET) {
             return Ok(None);
         }
-        let leading_ws = content.len() - trimmed.len();
         let key_end = leading_ws + closing + 1;
         content[key_end..]
             .find(OPEN_BRACKET)
             .map(|idx| key_end + idx)
     } else {
-        content.find(OPEN_BRACKET)
+        header_bracket(content)
     };
 
     let Some(bracket_start) = bracket_start else {
@@ -62,60 +100,72 @@ pub fn parse_array_header_line(
     };
     let bracket_end = bracket_start + bracket_end;
 
+    // Field names may be quoted, so braces and colons inside quotes are skipped.
     let mut brace_end = bracket_end + 1;
-    let brace_start = content[bracket_end + 1..]
-        .find(OPEN_BRACE)
-        .map(|idx| bracket_end + 1 + idx);
-    let colon_after_bracket = content[bracket_end + 1..]
-        .find(COLON)
-        .map(|idx| bracket_end + 1 + idx);
+    let brace_start = find_unquoted_char(content, OPEN_BRACE, bracket_end + 1);
+    let colon_after_bracket = find_unquoted_char(content, COLON, bracket_end + 1);
 
     if let (Some(brace_start), Some(colon_after_bracket)) = (brace_start, colon_after_bracket) {
         if brace_start < colon_after_bracket {
-            if let Some(found_end) = content[brace_start..].find(CLOSE_BRACE) {
-                let found_end = brace_start + found_end;
+            if let Some(found_end) = find_unquoted_char(content, CLOSE_BRACE, brace_start) {
                 brace_end = found_end + 1;
             }
         }
     }
 
-    let colon_index = content[brace_end..].find(COLON).map(|idx| brace_end + idx);
+    let colon_index = find_unquoted_char(content, COLON, brace_end);
     let Some(colon_index) = colon_index else {
         return Ok(None);
     };
 
-    let mut key: Option<String> = None;
+    // Anything between the header and its colon (e.g. `items[0].name:`) means
+    // this is a plain key that happens to contain brackets.
+    if !content[brace_end..colon_index].trim().is_empty() {
+        return Ok(None);
+    }
+
+    let mut key: Option<Cow<'_, str>> = None;
     let mut key_was_quoted = false;
     if bracket_start > 0 {
         let raw_key = content[..bracket_start].trim();
-        if raw_key.starts_with(DOUBLE_QUOTE) {
+        if path_end.is_some() {
+            key = Some(Cow::Borrowed(raw_key));
+        } else if raw_key.starts_with(DOUBLE_QUOTE) {
             key = Some(parse_string_literal(raw_key)?);
             key_was_quoted = true;
         } else if !raw_key.is_empty() {
-            key = Some(raw_key.to_string());
+            key = Some(Cow::Borrowed(raw_key));
         }
     }
 
     let after_colon = content[colon_index + 1..].trim();
     let bracket_content = &content[bracket_start + 1..bracket_end];
 
-    let Ok((length, delimiter)) = parse_bracket_segment(bracket_content, default_delimiter) else {
-        return Ok(None);
-    };
+    let (length, delimiter, marked) =
+        match parse_bracket_segment(bracket_content, default_delimiter) {
+            Ok((length, delimiter)) => (length, delimiter, true),
+            // A key holding a colon before the brackets is a plain key-value line.
+            Err(_) if find_unquoted_char(content, COLON, 0) < Some(bracket_start) => {
+                return Ok(None);
+            }
+            Err(_) => (
+                UNMARKED_LENGTH,
+                split_bracket_delimiter(bracket_content, default_delimiter).1,
+                false,
+            ),
+        };
 
-    let mut fields: Option<Vec<FieldName>> = None;
+    let mut fields: Option<Vec<FieldName<'_>>> = None;
     if let Some(brace_start) = brace_start {
         if brace_start < colon_index {
-            if let Some(found_end) = content[brace_start..].find(CLOSE_BRACE) {
-                let found_end = brace_start + found_end;
+            if let Some(found_end) = find_unquoted_char(content, CLOSE_BRACE, brace_start) {
                 if found_end < colon_index {
                     let fields_content = &content[brace_start + 1..found_end];
                     let parsed_fields = parse_delimited_values(fields_content, delimiter)
                         .into_iter()
                         .map(|field| {
-                            let trimmed = field.trim();
-                            let was_quoted = trimmed.starts_with(DOUBLE_QUOTE);
-                            let name = parse_string_literal(trimmed)?;
+                            let was_quoted = field.starts_with(DOUBLE_QUOTE);
+                            let name = parse_string_literal(field)?;
                             Ok(FieldName { name, was_quoted })
                         })
                         .collect::<Result<Vec<_>>>()?;
@@ -125,7 +175,7 @@ pub fn parse_array_header_line(
         }
     }
 
-    Ok(Some(ArrayHeaderParseResult {
+    let header = ArrayHeaderParseResult {
         header: ArrayHeaderInfo {
             key,
             key_was_quoted,
@@ -133,12 +183,9 @@ pub fn parse_array_header_line(
             delimiter,
             fields,
         },
-        inline_values: if after_colon.is_empty() {
-            None
-        } else {
-            Some(after_colon.to_string())
-        },
-    }))
+        inline_values: (!after_colon.is_empty()).then_some(after_colon),
+    };
+    Ok(Some((header, marked)))
 }
 
 /// Parse the bracket length segment, extracting length and delimiter.
@@ -147,59 +194,51 @@ pub fn parse_array_header_line(
 ///
 /// Returns an error if the length is invalid.
 pub fn parse_bracket_segment(seg: &str, default_delimiter: char) -> Result<(usize, char)> {
-    let mut content = seg.to_string();
-    let mut delimiter = default_delimiter;
-
-    if content.ends_with(TAB) {
-        delimiter = TAB;
-        content.pop();
-    } else if content.ends_with(PIPE) {
-        delimiter = PIPE;
-        content.pop();
-    }
-
+    let (content, delimiter) = split_bracket_delimiter(seg, default_delimiter);
     let length = content
         .parse::<usize>()
-        .map_err(|_| ToonError::message(format!("Invalid array length: {seg}")))?;
+        .map_err(|_| ToonError::invalid_array_length(seg))?;
 
     Ok((length, delimiter))
 }
 
+/// Split a trailing delimiter marker off a bracket segment.
+fn split_bracket_delimiter(seg: &str, default_delimiter: char) -> (&str, char) {
+    [TAB, PIPE, SEMICOLON]
+        .into_iter()
+        .find_map(|delimiter| Some((seg.strip_suffix(delimiter)?, delimiter)))
+        .unwrap_or((seg, default_delimiter))
+}
+
+/// Split delimited values outside quotes, trimming each one.
 #[must_use]
-pub fn parse_delimited_values(input: &str, delimiter: char) -> Vec<String> {
+pub fn parse_delimited_values(input: &str, delimiter: char) -> Vec<&str> {
     // Pre-estimate capacity based on delimiter count
     let estimated_count = input.chars().filter(|&c| c == delimiter).count() + 1;
     let mut values = Vec::with_capacity(estimated_count);
-    let mut buffer = String::with_capacity(64); // Reasonable default for field values
+    let mut start = 0usize;
     let mut in_quotes = false;
-    let mut iter = input.chars();
+    let mut iter = input.char_indices();
 
-    while let Some(ch) = iter.next() {
+    while let Some((idx, ch)) = iter.next() {
         if ch == BACKSLASH && in_quotes {
-            buffer.push(ch);
-            if let Some(next) = iter.next() {
-                buffer.push(next);
-            }
+            iter.next();
             continue;
         }
 
         if ch == DOUBLE_QUOTE {
             in_quotes = !in_quotes;
-            buffer.push(ch);
             continue;
         }
 
         if ch == delimiter && !in_quotes {
-            values.push(buffer.trim().to_string());
-            buffer.clear();
-            continue;
+            values.push(input[start..idx].trim());
+            start = idx + ch.len_utf8();
         }
-
-        buffer.push(ch);
     }
 
-    if !buffer.is_empty() || !values.is_empty() {
-        values.push(buffer.trim().to_string());
+    if start < input.len() || !values.is_empty() {
+        values.push(input[start..].trim());
     }
 
     values
@@ -210,7 +249,7 @@ pub fn parse_delimited_values(input: &str, delimiter: char) -> Vec<String> {
 /// # Errors
 ///
 /// Returns an error if any token is a malformed quoted string.
-pub fn map_row_values_to_primitives(values: &[String]) -> Result<Vec<crate::JsonPrimitive>> {
+pub fn map_row_values_to_primitives(values: &[&str]) -> Result<Vec<crate::JsonPrimitive>> {
     values
         .iter()
         .map(|value| parse_primitive_token(value))
@@ -223,39 +262,60 @@ pub fn map_row_values_to_primitives(values: &[String]) -> Result<Vec<crate::Json
 ///
 /// Returns an error if a quoted string token is unterminated or malformed.
 pub fn parse_primitive_token(token: &str) -> Result<crate::JsonPrimitive> {
+    parse_borrowed_primitive(token).map(crate::JsonPrimitive::from)
+}
+
+/// Parse a primitive token, borrowing string values from `token` unless they
+/// hold escapes.
+///
+/// # Errors
+///
+/// Returns an error if a quoted string token is unterminated or malformed.
+pub fn parse_borrowed_primitive(token: &str) -> Result<BorrowedPrimitive<'_>> {
     let trimmed = token.trim();
 
     if trimmed.is_empty() {
-        return Ok(crate::StringOrNumberOrBoolOrNull::String(String::new()));
+        return Ok(BorrowedPrimitive::String(Cow::Borrowed("")));
     }
 
     if trimmed.starts_with(DOUBLE_QUOTE) {
-        return Ok(crate::StringOrNumberOrBoolOrNull::String(
-            parse_string_literal(trimmed)?,
-        ));
+        return Ok(BorrowedPrimitive::String(parse_string_literal(trimmed)?));
     }
 
     if is_boolean_or_null_literal(trimmed) {
         return Ok(match trimmed {
-            "true" => crate::StringOrNumberOrBoolOrNull::Bool(true),
-            "false" => crate::StringOrNumberOrBoolOrNull::Bool(false),
-            _ => crate::StringOrNumberOrBoolOrNull::Null,
+            "true" => BorrowedPrimitive::Bool(true),
+            "false" => BorrowedPrimitive::Bool(false),
+            _ => BorrowedPrimitive::Null,
         });
     }
 
     if is_numeric_literal(trimmed) {
-        let parsed = trimmed.parse::<f64>().unwrap_or(f64::NAN);
-        let normalized = if parsed == 0.0 && parsed.is_sign_negative() {
-            0.0
-        } else {
-            parsed
-        };
-        return Ok(crate::StringOrNumberOrBoolOrNull::Number(normalized));
+        return Ok(BorrowedPrimitive::Number(crate::Number::parse_literal(
+            trimmed,
+        )));
     }
 
-    Ok(crate::StringOrNumberOrBoolOrNull::String(
-        trimmed.to_string(),
-    ))
+    Ok(BorrowedPrimitive::String(Cow::Borrowed(trimmed)))
+}
+
+/// Parse a primitive token, keeping numbers as their source text in a
+/// [`Number::Raw`](crate::Number::Raw) when `preserve_number_literals` is set.
+///
+/// # Errors
+///
+/// Returns an error if a quoted string token is unterminated or malformed.
+pub fn parse_primitive_token_with(
+    token: &str,
+    preserve_number_literals: bool,
+) -> Result<crate::JsonPrimitive> {
+    let mut value = parse_primitive_token(token)?;
+    if preserve_number_literals {
+        if let crate::StringOrNumberOrBoolOrNull::Number(number) = &mut value {
+            *number = crate::Number::Raw(token.trim().to_string());
+        }
+    }
+    Ok(value)
 }
 
 /// Parse a quoted string literal, unescaping escape sequences.
@@ -263,22 +323,22 @@ pub fn parse_primitive_token(token: &str) -> Result<crate::JsonPrimitive> {
 /// # Errors
 ///
 /// Returns an error for unterminated quotes or invalid escape sequences.
-pub fn parse_string_literal(token: &str) -> Result<String> {
+pub fn parse_string_literal(token: &str) -> Result<Cow<'_, str>> {
     let trimmed = token.trim();
 
     if trimmed.starts_with(DOUBLE_QUOTE) {
-        let closing = find_closing_quote(trimmed, 0)
-            .ok_or_else(|| ToonError::message("Unterminated string: missing closing quote"))?;
+        let closing = find_closing_quote(trimmed, 0).ok_or_else(ToonError::unterminated_string)?;
         if closing != trimmed.len() - 1 {
-            return Err(ToonError::message(
+            return Err(ToonError::decode(
+                DecodeErrorKind::TrailingCharacters,
                 "Unexpected characters after closing quote",
             ));
         }
         let content = &trimmed[1..closing];
-        return unescape_string(content).map_err(ToonError::message);
+        return unescape_str(content).map_err(invalid_escape);
     }
 
-    Ok(trimmed.to_string())
+    Ok(Cow::Borrowed(trimmed))
 }
 
 /// Parse an unquoted key up to the colon delimiter.
@@ -286,34 +346,42 @@ pub fn parse_string_literal(token: &str) -> Result<String> {
 /// # Errors
 ///
 /// Returns an error if no colon is found after the key.
-pub fn parse_unquoted_key(content: &str, start: usize) -> Result<(String, usize)> {
+pub fn parse_unquoted_key(content: &str, start: usize) -> Result<(Cow<'_, str>, usize)> {
     let mut pos = start;
     while pos < content.len() && content.as_bytes()[pos] as char != COLON {
         pos += 1;
     }
 
     if pos >= content.len() || content.as_bytes()[pos] as char != COLON {
-        return Err(ToonError::message("Missing colon after key"));
+        return Err(ToonError::missing_colon());
     }
 
-    let key = content[start..pos].trim().to_string();
+    let key = Cow::Borrowed(content[start..pos].trim());
     pos += 1;
     Ok((key, pos))
 }
 
+fn invalid_escape(message: String) -> ToonError {
+    ToonError::decode(DecodeErrorKind::InvalidEscape, message)
+}
+
 /// Parse a quoted key and validate the following colon.
 ///
 /// # Errors
 ///
 /// Returns an error for unterminated quotes or missing colon.
-pub fn parse_quoted_key(content: &str, start: usize) -> Result<(String, usize)> {
-    let closing = find_closing_quote(content, start)
-        .ok_or_else(|| ToonError::message("Unterminated quoted key"))?;
+pub fn parse_quoted_key(content: &str, start: usize) -> Result<(Cow<'_, str>, usize)> {
+    let closing = find_closing_quote(content, start).ok_or_else(|| {
+        ToonError::decode(
+            DecodeErrorKind::UnterminatedString,
+            "Unterminated quoted key",
+        )
+    })?;
     let key_content = &content[start + 1..closing];
-    let key = unescape_string(key_content).map_err(ToonError::message)?;
+    let key = unescape_str(key_content).map_err(invalid_escape)?;
     let mut pos = closing + 1;
     if pos >= content.len() || content.as_bytes()[pos] as char != COLON {
-        return Err(ToonError::message("Missing colon after key"));
+        return Err(ToonError::missing_colon());
     }
     pos += 1;
     Ok((key, pos))
@@ -324,7 +392,14 @@ pub fn parse_quoted_key(content: &str, start: usize) -> Result<(String, usize)>
 /// # Errors
 ///
 /// Returns an error if the key is malformed or missing a trailing colon.
-pub fn parse_key_token(content: &str, start: usize) -> Result<(String, usize, bool)> {
+pub fn parse_key_token(content: &str, start: usize) -> Result<(Cow<'_, str>, usize, bool)> {
+    // A key folded with quoted segments is kept as written, unquoted, for
+    // path expansion to split.
+    if let Some(end) = quoted_path_end(content, start)
+        .filter(|&end| content.as_bytes().get(end).map(|b| *b as char) == Some(COLON))
+    {
+        return Ok((Cow::Borrowed(&content[start..end]), end + 1, false));
+    }
     let is_quoted = content.as_bytes().get(start).map(|b| *b as char) == Some(DOUBLE_QUOTE);
     let (key, end) = if is_quoted {
         parse_quoted_key(content, start)?
@@ -334,6 +409,51 @@ pub fn parse_key_token(content: &str, start: usize) -> Result<(String, usize, bo
     Ok((key, end, is_quoted))
 }
 
+/// The `[` that opens an array header, skipping the indexes of a folded key
+/// such as the `[0]` in `a[0].b[2]:`.
+fn header_bracket(content: &str) -> Option<usize> {
+    let mut from = 0;
+    loop {
+        let open = from + content[from..].find(OPEN_BRACKET)?;
+        let close = open + content[open..].find(CLOSE_BRACKET)?;
+        if !content[close + 1..].starts_with(DOT) {
+            return Some(open);
+        }
+        from = close + 1;
+    }
+}
+
+/// End of a key folded with quoted segments, such as `"a.b".c` or
+/// `"a.b"[0].c`, that starts at `start`; see [`split_folded_key`].
+fn quoted_path_end(content: &str, start: usize) -> Option<usize> {
+    let bytes = content.as_bytes();
+    let mut pos = start;
+    loop {
+        if bytes.get(pos).map(|b| *b as char) == Some(DOUBLE_QUOTE) {
+            pos = find_closing_quote(content, pos)? + 1;
+        } else {
+            while bytes
+                .get(pos)
+                .is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'_')
+            {
+                pos += 1;
+            }
+        }
+        // Indexes belong to the key only when another key follows; otherwise
+        // they open an array header.
+        let mut end = pos;
+        while bytes.get(end).map(|b| *b as char) == Some(OPEN_BRACKET) {
+            end += content[end..].find(CLOSE_BRACKET)? + 1;
+        }
+        if bytes.get(end).map(|b| *b as char) != Some(DOT) {
+            break;
+        }
+        pos = end + 1;
+    }
+    let key = &content[start..pos];
+    (key.contains(DOUBLE_QUOTE) && split_folded_key(key).is_some()).then_some(pos)
+}
+
 #[must_use]
 pub fn is_array_header_content(content: &str) -> bool {
     content.trim_start().starts_with(OPEN_BRACKET)
@@ -344,3 +464,16 @@ pub fn is_array_header_content(content: &str) -> bool {
 pub fn is_key_value_content(content: &str) -> bool {
     find_unquoted_char(content, COLON, 0).is_some()
 }
+
+/// Whether a line is a `key: value` line; a colon inside a leading quoted
+/// key does not count.
+#[must_use]
+pub fn is_key_value_line(content: &str) -> bool {
+    if content.starts_with(DOUBLE_QUOTE) {
+        if let Some(closing) = find_closing_quote(content, 0) {
+            return content[closing + 1..].contains(COLON);
+        }
+        return false;
+    }
+    content.contains(COLON)
+}
@@ -0,0 +1,635 @@
+//! Push decoding: input arrives in chunks and events come out as soon as
+//! the lines they depend on are complete.
+//!
+//! The input is cut into pieces that decode on their own: each top-level
+//! entry of an object document, and each item of a root array with a `[N]`
+//! header. A piece is decoded with the synchronous decoder as soon as the
+//! line after it arrives, so the events are exactly those of
+//! [`decode_stream_sync`](crate::decode_stream_sync) while memory stays
+//! bounded by the largest piece. Other documents are decoded at the end.
+
+use std::collections::{HashMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::JsonStreamEvent;
+use crate::decode::decoders::decode_fragment;
+use crate::decode::limits::LimitCounter;
+use crate::decode::parser::{is_array_header_content, is_key_value_line, parse_array_header_line};
+use crate::decode::scanner::{StreamingScanState, create_scan_state, scan_line};
+use crate::decode::validation::{
+    assert_expected_count, validate_no_blank_lines_in_range, validate_no_extra_list_items,
+    validate_no_extra_tabular_rows,
+};
+use crate::error::{DecodeErrorKind, Result, ToonError};
+use crate::options::{DecodeStreamOptions, Strictness};
+use crate::shared::constants::{DEFAULT_DELIMITER, LIST_ITEM_MARKER, LIST_ITEM_PREFIX};
+
+/// Incremental decoder fed raw bytes, such as frames off a socket, that
+/// need not line up with line breaks.
+///
+/// Call [`feed`](Self::feed) with each chunk, take completed events with
+/// [`next_event`](Self::next_event), and call [`finish`](Self::finish) at
+/// the end of the input. Events of an object document follow each
+/// top-level entry, and events of a root array follow each item. After an
+/// error the parser stops and drops events not yet taken.
+///
+/// ```
+/// use toon::{JsonStreamEvent, ToonParser};
+///
+/// let mut parser = ToonParser::new(None);
+/// parser.feed(b"[2]{id}:\n  1\n  ").unwrap();
+/// assert_eq!(parser.next_event(), Some(JsonStreamEvent::StartArray { length: 2 }));
+/// assert_eq!(parser.next_event(), Some(JsonStreamEvent::StartObject));
+/// parser.feed(b"2\n").unwrap();
+/// parser.finish().unwrap();
+/// assert_eq!(std::iter::from_fn(|| parser.next_event()).count(), 8);
+/// ```
+pub struct ToonParser {
+    options: DecodeStreamOptions,
+    /// Bytes fed so far.
+    offset: u64,
+    scan_state: StreamingScanState,
+    /// Bytes of the line not yet ended by `\n`.
+    partial: Vec<u8>,
+    layout: Layout,
+    /// Lines of the entry or item still being read, starting at `unit_start`.
+    unit: Vec<String>,
+    unit_start: usize,
+    /// Completed events, each with the line it was read from.
+    events: VecDeque<(usize, JsonStreamEvent)>,
+    /// Line of each top-level key, tracked only when duplicate keys are rejected.
+    top_level_keys: Option<HashMap<String, usize>>,
+    /// Depth and values of the events queued so far, for the limits.
+    limit_counter: LimitCounter,
+    /// Set once the input has ended or an error occurred.
+    stopped: bool,
+}
+
+/// The state of a [`ToonParser`] between two chunks, to resume decoding
+/// after a restart.
+///
+/// It holds the scanner position, the unfinished line, and the lines of the
+/// entry or item being read, so it stays small for documents made of many
+/// small entries. Serialize it with any serde format, then pass it to
+/// [`ToonParser::resume`] and feed the input from
+/// [`offset`](Self::offset) on.
+///
+/// ```
+/// use toon::{ParserCheckpoint, ToonParser};
+///
+/// let input = b"a: 1\nb: 2\nc: 3";
+/// let mut parser = ToonParser::new(None);
+/// parser.feed(&input[..7]).unwrap();
+/// let taken: Vec<_> = std::iter::from_fn(|| parser.next_event()).collect();
+/// let saved = serde_json::to_string(&parser.checkpoint().unwrap()).unwrap();
+///
+/// let checkpoint: ParserCheckpoint = serde_json::from_str(&saved).unwrap();
+/// let offset = usize::try_from(checkpoint.offset()).unwrap();
+/// let mut parser = ToonParser::resume(checkpoint, None);
+/// parser.feed(&input[offset..]).unwrap();
+/// parser.finish().unwrap();
+/// let rest: Vec<_> = std::iter::from_fn(|| parser.next_event()).collect();
+/// assert_eq!(taken.len() + rest.len(), 8);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParserCheckpoint {
+    offset: u64,
+    scan_state: StreamingScanState,
+    partial: Vec<u8>,
+    layout: Layout,
+    unit: Vec<String>,
+    unit_start: usize,
+    top_level_keys: Option<HashMap<String, usize>>,
+    limit_counter: LimitCounter,
+}
+
+impl ParserCheckpoint {
+    /// Bytes of input consumed; feed the resumed parser from here.
+    #[must_use]
+    pub const fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Lines of input completed.
+    #[must_use]
+    pub const fn line_number(&self) -> usize {
+        self.scan_state.line_number
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+enum Layout {
+    /// No content line yet.
+    Empty,
+    /// Top-level entries; `started` once `StartObject` is out.
+    Object { started: bool },
+    /// Items of a root array with a `[N]` header and no inline values.
+    Array(RootArray),
+    /// Anything else, decoded as a whole at the end.
+    Whole,
+    /// After a root array ends; later lines are only scanned.
+    Done,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct RootArray {
+    header: String,
+    length: usize,
+    tabular: bool,
+    count: usize,
+    first_item_line: usize,
+    header_line: usize,
+}
+
+impl RootArray {
+    const fn item_type(&self) -> &'static str {
+        if self.tabular {
+            "tabular rows"
+        } else {
+            "list array items"
+        }
+    }
+
+    /// The header with its length replaced by 1, to decode one item alone.
+    fn single_item_header(&self) -> String {
+        let header = self.header.as_str();
+        let start = header.find(|c: char| c.is_ascii_digit()).unwrap_or(1);
+        let end = header[start..]
+            .find(|c: char| !c.is_ascii_digit())
+            .map_or(header.len(), |end| start + end);
+        format!("{}1{}", &header[..start], &header[end..])
+    }
+}
+
+impl ToonParser {
+    /// Create a parser for one document.
+    #[must_use]
+    pub fn new(options: Option<DecodeStreamOptions>) -> Self {
+        let options = options.unwrap_or_default();
+        let top_level_keys = options
+            .strictness
+            .unwrap_or_default()
+            .duplicate_keys
+            .then(HashMap::new);
+        Self {
+            options,
+            offset: 0,
+            scan_state: create_scan_state(),
+            partial: Vec::new(),
+            layout: Layout::Empty,
+            unit: Vec::new(),
+            unit_start: 0,
+            events: VecDeque::new(),
+            top_level_keys,
+            limit_counter: LimitCounter::default(),
+            stopped: false,
+        }
+    }
+
+    /// Feed the next chunk of input. Chunks may split lines, `\r\n` endings
+    /// and multi-byte characters anywhere.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a completed line is not valid UTF-8 or fails to
+    /// decode, if the unfinished line grows past `max_line_length`, or if
+    /// the parser already finished or failed.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<()> {
+        self.run(|parser| parser.feed_bytes(chunk))?;
+        self.offset += chunk.len() as u64;
+        Ok(())
+    }
+
+    /// Mark the end of the input, decoding whatever is still buffered.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the remaining input fails to decode, or if the
+    /// parser already finished or failed.
+    pub fn finish(&mut self) -> Result<()> {
+        self.run(|parser| {
+            if !parser.partial.is_empty() {
+                let bytes = std::mem::take(&mut parser.partial);
+                let line = parser.line_from_bytes(bytes)?;
+                parser.route_line(line)?;
+            }
+            parser.end_input()
+        })?;
+        self.stopped = true;
+        Ok(())
+    }
+
+    /// The next completed event, if any.
+    pub fn next_event(&mut self) -> Option<JsonStreamEvent> {
+        self.next_event_with_line().map(|(_, event)| event)
+    }
+
+    /// The next completed event with the 1-based line it was read from, as
+    /// [`decode_stream_sync_with_lines`](crate::decode::decoders::decode_stream_sync_with_lines)
+    /// reports it.
+    pub fn next_event_with_line(&mut self) -> Option<(usize, JsonStreamEvent)> {
+        self.events.pop_front()
+    }
+
+    /// Save the decoder state after the input fed so far.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if events are still waiting to be taken with
+    /// [`next_event`](Self::next_event), since the checkpoint does not hold
+    /// them, or if the parser already finished or failed.
+    pub fn checkpoint(&self) -> Result<ParserCheckpoint> {
+        if self.stopped {
+            return Err(ToonError::message("Parser has already finished"));
+        }
+        if !self.events.is_empty() {
+            return Err(ToonError::message(
+                "Take pending events before saving a checkpoint",
+            ));
+        }
+        Ok(ParserCheckpoint {
+            offset: self.offset,
+            scan_state: self.scan_state.clone(),
+            partial: self.partial.clone(),
+            layout: self.layout.clone(),
+            unit: self.unit.clone(),
+            unit_start: self.unit_start,
+            top_level_keys: self.top_level_keys.clone(),
+            limit_counter: self.limit_counter,
+        })
+    }
+
+    /// Continue decoding from `checkpoint`. `options` should match those of
+    /// the parser that saved it.
+    #[must_use]
+    pub fn resume(checkpoint: ParserCheckpoint, options: Option<DecodeStreamOptions>) -> Self {
+        let ParserCheckpoint {
+            offset,
+            scan_state,
+            partial,
+            layout,
+            unit,
+            unit_start,
+            top_level_keys,
+            limit_counter,
+        } = checkpoint;
+        Self {
+            options: options.unwrap_or_default(),
+            offset,
+            scan_state,
+            partial,
+            layout,
+            unit,
+            unit_start,
+            events: VecDeque::new(),
+            top_level_keys,
+            limit_counter,
+            stopped: false,
+        }
+    }
+
+    /// Feed one line without its line ending.
+    #[cfg(feature = "async-stream")]
+    pub(crate) fn push_line(&mut self, line: String) -> Result<()> {
+        self.run(|parser| parser.route_line(line))
+    }
+
+    /// Run `step`, stopping the parser and dropping pending events on error.
+    fn run(&mut self, step: impl FnOnce(&mut Self) -> Result<()>) -> Result<()> {
+        if self.stopped {
+            return Err(ToonError::message("Parser has already finished"));
+        }
+        let result = step(self);
+        if result.is_err() {
+            self.stopped = true;
+            self.events.clear();
+        }
+        result
+    }
+
+    fn feed_bytes(&mut self, mut chunk: &[u8]) -> Result<()> {
+        while let Some(newline) = chunk.iter().position(|&byte| byte == b'\n') {
+            self.partial.extend_from_slice(&chunk[..newline]);
+            chunk = &chunk[newline + 1..];
+            let mut bytes = std::mem::take(&mut self.partial);
+            if bytes.last() == Some(&b'\r') {
+                bytes.pop();
+            }
+            let line = self.line_from_bytes(bytes)?;
+            self.route_line(line)?;
+        }
+        self.partial.extend_from_slice(chunk);
+
+        // Keep one spare byte for the `\r` of a `\r\n` ending.
+        if let Some(limit) = self
+            .options
+            .max_line_length
+            .filter(|limit| self.partial.len() > limit.saturating_add(1))
+        {
+            return Err(ToonError::line_too_long(
+                self.scan_state.line_number + 1,
+                limit,
+                self.partial.len(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn line_from_bytes(&self, bytes: Vec<u8>) -> Result<String> {
+        String::from_utf8(bytes)
+            .map_err(|err| ToonError::invalid_utf8(self.scan_state.line_number + 1, &err))
+    }
+
+    fn strictness(&self) -> Strictness {
+        self.options.strictness.unwrap_or_default()
+    }
+
+    fn route_line(&mut self, raw: String) -> Result<()> {
+        let strictness = self.strictness();
+        let scanned = scan_line(
+            &raw,
+            &mut self.scan_state,
+            self.options.indent.unwrap_or(2),
+            strictness.tabs,
+            strictness.indentation,
+            self.options.max_line_length,
+        )?;
+        let line_number = self.scan_state.line_number;
+        if !matches!(self.layout, Layout::Array(_)) {
+            self.scan_state.blank_lines.clear();
+        }
+
+        let Some((indent, depth)) = scanned else {
+            if !self.unit.is_empty() {
+                self.unit.push(raw);
+            }
+            return Ok(());
+        };
+
+        match &self.layout {
+            Layout::Empty => self.start(raw, indent, line_number),
+            Layout::Object { .. } if depth == 0 => {
+                self.close_entry(Some(&raw))?;
+                self.open_unit(raw, line_number);
+                Ok(())
+            }
+            Layout::Object { .. } | Layout::Whole => {
+                self.unit.push(raw);
+                Ok(())
+            }
+            Layout::Array(_) => self.push_item_line(raw, indent, depth, line_number),
+            Layout::Done => Ok(()),
+        }
+    }
+
+    fn start(&mut self, raw: String, indent: usize, line_number: usize) -> Result<()> {
+        let content = &raw[indent..];
+        if is_array_header_content(content) {
+            let parsed = parse_array_header_line(content, DEFAULT_DELIMITER)
+                .map_err(|err| err.at_line(line_number).with_source(|_| Some(&raw)))?;
+            if let Some(parsed) = parsed {
+                if parsed.inline_values.is_none() {
+                    let length = parsed.header.length;
+                    let tabular = parsed
+                        .header
+                        .fields
+                        .as_ref()
+                        .is_some_and(|fields| !fields.is_empty());
+                    self.queue(line_number, JsonStreamEvent::StartArray { length })?;
+                    self.layout = Layout::Array(RootArray {
+                        header: content.to_string(),
+                        length,
+                        tabular,
+                        count: 0,
+                        first_item_line: 0,
+                        header_line: line_number,
+                    });
+                    return Ok(());
+                }
+            }
+            self.layout = Layout::Whole;
+        } else {
+            self.layout = Layout::Object { started: false };
+        }
+        self.open_unit(raw, line_number);
+        Ok(())
+    }
+
+    fn open_unit(&mut self, raw: String, line_number: usize) {
+        self.unit.push(raw);
+        self.unit_start = line_number;
+    }
+
+    fn decode_unit(&mut self, header: Option<String>) -> Result<Vec<(usize, JsonStreamEvent)>> {
+        let mut offset = self.unit_start - 1;
+        let mut lines = std::mem::take(&mut self.unit);
+        if let Some(header) = header {
+            lines.insert(0, header);
+            offset -= 1;
+        }
+        decode_fragment(lines, &self.options, offset)
+    }
+
+    /// Queue `event`, read from `line`, checking it against the limits.
+    fn queue(&mut self, line: usize, event: JsonStreamEvent) -> Result<()> {
+        if let Some(limits) = &self.options.limits {
+            self.limit_counter
+                .observe(limits, &event)
+                .map_err(|err| err.at_line(line))?;
+        }
+        self.events.push_back((line, event));
+        Ok(())
+    }
+
+    fn queue_all(
+        &mut self,
+        events: impl IntoIterator<Item = (usize, JsonStreamEvent)>,
+    ) -> Result<()> {
+        events
+            .into_iter()
+            .try_for_each(|(line, event)| self.queue(line, event))
+    }
+
+    /// Queue the events of a fragment without its outer start and end.
+    fn queue_inner(&mut self, mut events: Vec<(usize, JsonStreamEvent)>) -> Result<()> {
+        events.pop();
+        self.queue_all(events.into_iter().skip(1))
+    }
+
+    /// Decode the top-level entry read so far; `next` is the line that
+    /// starts the following entry, or `None` at the end of the input.
+    fn close_entry(&mut self, next: Option<&str>) -> Result<()> {
+        let Layout::Object { started } = self.layout else {
+            return Ok(());
+        };
+        if !started {
+            let Some(next) = next else {
+                // The only entry is the whole document, possibly a primitive.
+                let events = self.decode_unit(None)?;
+                return self.queue_all(events);
+            };
+            if !is_key_value_line(self.unit[0].trim_start_matches(' ')) {
+                // Let the decoder report why this is neither an object nor a primitive.
+                let mut lines = self.unit.clone();
+                lines.push(next.to_string());
+                decode_fragment(lines, &self.options, self.unit_start - 1)?;
+            }
+            self.layout = Layout::Object { started: true };
+            self.queue(self.unit_start, JsonStreamEvent::StartObject)?;
+        }
+
+        let key_line = self.top_level_keys.as_ref().map(|_| self.unit[0].clone());
+        let events = self.decode_unit(None)?;
+        if let (Some(keys), Some((line, JsonStreamEvent::Key { key, .. }))) =
+            (&mut self.top_level_keys, events.get(1))
+        {
+            if let Some(first) = keys.insert(key.clone(), *line) {
+                return Err(ToonError::decode(
+                    DecodeErrorKind::DuplicateKey,
+                    format!("duplicate key `{key}` (first defined at line {first})"),
+                )
+                .at_line(*line)
+                .with_source(|_| key_line.as_deref()));
+            }
+        }
+        self.queue_inner(events)
+    }
+
+    fn push_item_line(
+        &mut self,
+        raw: String,
+        indent: usize,
+        depth: usize,
+        line_number: usize,
+    ) -> Result<()> {
+        let strictness = self.strictness();
+        let Layout::Array(array) = &self.layout else {
+            return Ok(());
+        };
+        let content = &raw[indent..];
+        let starts_item = depth == 1
+            && (array.tabular
+                || content.starts_with(LIST_ITEM_PREFIX)
+                || content == LIST_ITEM_MARKER);
+
+        if !starts_item {
+            if depth > 1 && !array.tabular && !self.unit.is_empty() {
+                self.unit.push(raw);
+                return Ok(());
+            }
+            return self.end_array();
+        }
+
+        self.close_item()?;
+        let Layout::Array(array) = &mut self.layout else {
+            return Ok(());
+        };
+        if array.count == array.length {
+            let next_line = Some((depth, content));
+            let extra = if array.tabular {
+                parse_array_header_line(&array.header, DEFAULT_DELIMITER).and_then(|parsed| {
+                    parsed.map_or(Ok(()), |parsed| {
+                        validate_no_extra_tabular_rows(
+                            next_line,
+                            1,
+                            &parsed.header,
+                            strictness.trailing_content,
+                        )
+                    })
+                })
+            } else {
+                validate_no_extra_list_items(
+                    next_line,
+                    1,
+                    array.length,
+                    strictness.trailing_content,
+                )
+            };
+            extra.map_err(|err| err.at_line(line_number).with_source(|_| Some(&raw)))?;
+            return self.end_array();
+        }
+
+        if array.count == 0 {
+            array.first_item_line = line_number;
+        }
+        let array_kind = if array.tabular {
+            "tabular array"
+        } else {
+            "list array"
+        };
+        validate_no_blank_lines_in_range(
+            array.first_item_line,
+            line_number,
+            &self.scan_state.blank_lines,
+            strictness.blank_lines,
+            array_kind,
+        )?;
+        array.count += 1;
+        let tabular = array.tabular;
+        self.scan_state.blank_lines.clear();
+        self.open_unit(raw, line_number);
+        if tabular {
+            self.close_item()?;
+        }
+        Ok(())
+    }
+
+    fn close_item(&mut self) -> Result<()> {
+        let Layout::Array(array) = &self.layout else {
+            return Ok(());
+        };
+        if self.unit.is_empty() {
+            return Ok(());
+        }
+        let header = array.single_item_header();
+        let events = self.decode_unit(Some(header))?;
+        self.queue_inner(events)
+    }
+
+    fn end_array(&mut self) -> Result<()> {
+        self.close_item()?;
+        let Layout::Array(array) = &self.layout else {
+            return Ok(());
+        };
+        assert_expected_count(
+            array.count,
+            array.length,
+            array.item_type(),
+            self.strictness().length_markers,
+        )
+        .map_err(|err| {
+            err.at_line(array.header_line)
+                .with_source(|_| Some(&array.header))
+        })?;
+        self.queue(0, JsonStreamEvent::EndArray)?;
+        self.layout = Layout::Done;
+        self.scan_state.blank_lines.clear();
+        Ok(())
+    }
+
+    fn end_input(&mut self) -> Result<()> {
+        match self.layout {
+            Layout::Empty => {
+                self.queue(0, JsonStreamEvent::StartObject)?;
+                self.queue(0, JsonStreamEvent::EndObject)?;
+            }
+            Layout::Object { started } => {
+                self.close_entry(None)?;
+                if started {
+                    self.queue(0, JsonStreamEvent::EndObject)?;
+                }
+            }
+            Layout::Array(_) => self.end_array()?,
+            Layout::Whole => {
+                let events = self.decode_unit(None)?;
+                self.queue_all(events)?;
+            }
+            Layout::Done => {}
+        }
+        if let Some(metrics) = self.options.metrics.as_deref() {
+            metrics.document_decoded();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+use std::io::{BufRead, BufReader, Read};
+
+use crate::error::{Result, ToonError};
+
+/// Lines read from an [`io::Read`](std::io::Read), split on `\n` with a
+/// trailing `\r` dropped.
+///
+/// Ends at the first error, which [`finish`](Self::finish) reports in place
+/// of whatever the decoder made of the truncated input. A line over
+/// `max_line_length` bytes is rejected after buffering at most one byte
+/// more than the limit.
+pub struct ReaderLines<R> {
+    reader: BufReader<R>,
+    max_line_length: Option<usize>,
+    line_number: usize,
+    error: Option<ToonError>,
+}
+
+impl<R: Read> ReaderLines<R> {
+    pub fn new(reader: R, max_line_length: Option<usize>) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            max_line_length,
+            line_number: 0,
+            error: None,
+        }
+    }
+
+    /// Prefer a read error over `result`.
+    pub fn finish<T>(self, result: Result<T>) -> Result<T> {
+        self.error.map_or(result, Err)
+    }
+
+    fn read_line(&mut self) -> Result<Option<String>> {
+        let mut buffer = Vec::new();
+        let mut found = 0usize;
+        let mut terminated = false;
+        let mut ends_with_cr = false;
+        loop {
+            let available = self
+                .reader
+                .fill_buf()
+                .map_err(|err| ToonError::io("Failed to read input", None, err))?;
+            if available.is_empty() {
+                break;
+            }
+            let newline = available.iter().position(|&byte| byte == b'\n');
+            let chunk = &available[..newline.unwrap_or(available.len())];
+            found += chunk.len();
+            if let Some(&last) = chunk.last() {
+                ends_with_cr = last == b'\r';
+            }
+            // Keep one spare byte for the `\r` of a `\r\n` ending.
+            if self
+                .max_line_length
+                .is_none_or(|limit| found <= limit.saturating_add(1))
+            {
+                buffer.extend_from_slice(chunk);
+            }
+            let used = chunk.len() + usize::from(newline.is_some());
+            self.reader.consume(used);
+            if newline.is_some() {
+                terminated = true;
+                break;
+            }
+        }
+
+        if !terminated && found == 0 {
+            return Ok(None);
+        }
+        self.line_number += 1;
+        if terminated && ends_with_cr {
+            found -= 1;
+            buffer.truncate(found);
+        }
+        if let Some(limit) = self.max_line_length.filter(|limit| found > *limit) {
+            return Err(ToonError::line_too_long(self.line_number, limit, found));
+        }
+        String::from_utf8(buffer)
+            .map(Some)
+            .map_err(|err| ToonError::invalid_utf8(self.line_number, &err))
+    }
+}
+
+impl<R: Read> Iterator for ReaderLines<R> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        if self.error.is_some() {
+            return None;
+        }
+        match self.read_line() {
+            Ok(line) => line,
+            Err(err) => {
+                self.error = Some(err);
+                None
+            }
+        }
+    }
+}
@@ -1,25 +1,47 @@
-use crate::error::{Result, ToonError};
+use std::borrow::Cow;
+
+use crate::error::{DecodeErrorKind, Result, ToonError};
 use crate::shared::constants::{SPACE, TAB};
 
 pub type Depth = usize;
 
+/// A scanned content line, borrowing from the input when it was given as
+/// `&str` and owning its text otherwise.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct ParsedLine {
-    pub raw: String,
+pub struct ParsedLine<'a> {
+    pub raw: Cow<'a, str>,
     pub indent: usize,
-    pub content: String,
+    pub content: Cow<'a, str>,
     pub depth: Depth,
     pub line_number: usize,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+impl<'a> ParsedLine<'a> {
+    /// Split `raw` at `indent`, moving an owned line instead of copying it
+    /// twice.
+    fn new(raw: Cow<'a, str>, indent: usize, depth: Depth, line_number: usize) -> Self {
+        let content = match &raw {
+            Cow::Borrowed(text) => Cow::Borrowed(&text[indent..]),
+            Cow::Owned(text) => Cow::Owned(text[indent..].to_string()),
+        };
+        Self {
+            raw,
+            indent,
+            content,
+            depth,
+            line_number,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct BlankLineInfo {
     pub line_number: usize,
     pub indent: usize,
     pub depth: Depth,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct StreamingScanState {
     pub line_number: usize,
     pub blank_lines: Vec<BlankLineInfo>,
@@ -35,29 +57,83 @@ pub const fn create_scan_state() -> StreamingScanState {
 
 /// Parse a line with indentation and strict-mode validation.
 ///
+/// `strict` rejects tabs in indentation, which otherwise count as one level
+/// each; `exact_indent` rejects indentation that is not a multiple of the
+/// indent size. Lines longer than
+/// `max_line_length` bytes are rejected before anything is copied.
+///
 /// # Errors
 ///
-/// Returns an error if strict mode rules are violated (tabs in indentation or
-/// indentation not a multiple of the indent size).
+/// Returns an error if any of these rules is violated.
 pub fn parse_line_incremental(
     raw: &str,
     state: &mut StreamingScanState,
     indent_size: usize,
     strict: bool,
-) -> Result<Option<ParsedLine>> {
+    exact_indent: bool,
+    max_line_length: Option<usize>,
+) -> Result<Option<ParsedLine<'static>>> {
+    let Some((indent, depth)) = scan_line(
+        raw,
+        state,
+        indent_size,
+        strict,
+        exact_indent,
+        max_line_length,
+    )?
+    else {
+        return Ok(None);
+    };
+
+    Ok(Some(ParsedLine::new(
+        Cow::Owned(raw.to_string()),
+        indent,
+        depth,
+        state.line_number,
+    )))
+}
+
+/// Validate a line as [`parse_line_incremental`] does without copying it,
+/// returning its indent and depth, or `None` for a blank line.
+///
+/// # Errors
+///
+/// Returns an error if the line violates the same rules.
+pub fn scan_line(
+    raw: &str,
+    state: &mut StreamingScanState,
+    indent_size: usize,
+    strict: bool,
+    exact_indent: bool,
+    max_line_length: Option<usize>,
+) -> Result<Option<(usize, Depth)>> {
     state.line_number += 1;
     let line_number = state.line_number;
 
+    if let Some(limit) = max_line_length.filter(|limit| raw.len() > *limit) {
+        return Err(
+            ToonError::line_too_long(line_number, limit, raw.len()).with_source(|_| Some(raw))
+        );
+    }
+
+    // `indent` is the length of the leading whitespace; `columns` its width,
+    // where a tab stands for a whole level.
     let mut indent = 0usize;
+    let mut columns = 0usize;
     let raw_bytes = raw.as_bytes();
-    while indent < raw_bytes.len() && raw_bytes[indent] == SPACE as u8 {
+    while indent < raw_bytes.len() {
+        match raw_bytes[indent] {
+            byte if byte == SPACE as u8 => columns += 1,
+            byte if byte == TAB as u8 && !strict => {
+                columns = columns.saturating_add(indent_size.max(1));
+            }
+            _ => break,
+        }
         indent += 1;
     }
 
-    // Check if line is blank before allocating content string
-    let content_slice = &raw[indent..];
-    if content_slice.trim().is_empty() {
-        let depth = compute_depth_from_indent(indent, indent_size);
+    if raw[indent..].trim().is_empty() {
+        let depth = compute_depth_from_indent(columns, indent_size);
         state.blank_lines.push(BlankLineInfo {
             line_number,
             indent,
@@ -66,9 +142,7 @@ pub fn parse_line_incremental(
         return Ok(None);
     }
 
-    // Only allocate content string for non-blank lines
-    let content = content_slice.to_string();
-    let depth = compute_depth_from_indent(indent, indent_size);
+    let depth = compute_depth_from_indent(columns, indent_size);
 
     if strict {
         let mut whitespace_end = 0usize;
@@ -79,56 +153,104 @@ pub fn parse_line_incremental(
         }
 
         if raw[..whitespace_end].contains(TAB) {
-            return Err(ToonError::tabs_not_allowed(line_number));
+            return Err(ToonError::tabs_not_allowed(line_number).with_source(|_| Some(raw)));
         }
+    }
 
+    if exact_indent {
         if indent_size == 0 {
-            if indent > 0 {
-                return Err(ToonError::validation(
-                    line_number,
+            if columns > 0 {
+                return Err(ToonError::decode(
+                    DecodeErrorKind::Indentation,
                     format!(
-                        "Indentation not allowed when indent size is 0, but found {indent} spaces"
+                        "Indentation not allowed when indent size is 0, but found {columns} spaces"
                     ),
-                ));
+                )
+                .at_line(line_number)
+                .with_source(|_| Some(raw)));
             }
-        } else if indent > 0 && indent % indent_size != 0 {
-            return Err(ToonError::invalid_indentation(
-                line_number,
-                indent_size,
-                indent,
-            ));
+        } else if columns > 0 && columns % indent_size != 0 {
+            return Err(
+                ToonError::invalid_indentation(line_number, indent_size, columns)
+                    .with_source(|_| Some(raw)),
+            );
         }
     }
 
-    Ok(Some(ParsedLine {
-        raw: raw.to_string(),
-        indent,
-        content,
-        depth,
-        line_number,
-    }))
+    Ok(Some((indent, depth)))
 }
 
 /// Parse all lines from the source, skipping blank lines but recording them for validation.
 ///
+/// Lines given as `&str` are borrowed, not copied; owned lines are moved.
+///
 /// # Errors
 ///
 /// Returns an error if any line violates strict indentation rules.
-pub fn parse_lines_sync(
-    source: impl IntoIterator<Item = String>,
+pub fn parse_lines_sync<'a>(
+    source: impl IntoIterator<Item = impl Into<Cow<'a, str>>>,
     indent_size: usize,
     strict: bool,
+    exact_indent: bool,
+    max_line_length: Option<usize>,
     state: &mut StreamingScanState,
-) -> Result<Vec<ParsedLine>> {
+) -> Result<Vec<ParsedLine<'a>>> {
     let mut lines = Vec::new();
     for raw in source {
-        if let Some(parsed) = parse_line_incremental(&raw, state, indent_size, strict)? {
-            lines.push(parsed);
+        let raw = raw.into();
+        if let Some((indent, depth)) = scan_line(
+            &raw,
+            state,
+            indent_size,
+            strict,
+            exact_indent,
+            max_line_length,
+        )? {
+            lines.push(ParsedLine::new(raw, indent, depth, state.line_number));
         }
     }
     Ok(lines)
 }
 
+/// Parse all lines as [`parse_lines_sync`] does, but record each line that
+/// breaks a rule in `errors` and keep it, scanned without the rules, instead
+/// of failing.
+pub fn parse_lines_recovering<'a>(
+    source: impl IntoIterator<Item = impl Into<Cow<'a, str>>>,
+    indent_size: usize,
+    strict: bool,
+    exact_indent: bool,
+    max_line_length: Option<usize>,
+    state: &mut StreamingScanState,
+    errors: &mut Vec<ToonError>,
+) -> Vec<ParsedLine<'a>> {
+    let mut lines = Vec::new();
+    for raw in source {
+        let raw = raw.into();
+        let scanned = match scan_line(
+            &raw,
+            state,
+            indent_size,
+            strict,
+            exact_indent,
+            max_line_length,
+        ) {
+            Ok(scanned) => scanned,
+            Err(err) => {
+                errors.push(err);
+                state.line_number -= 1;
+                scan_line(&raw, state, indent_size, false, false, None)
+                    .ok()
+                    .flatten()
+            }
+        };
+        if let Some((indent, depth)) = scanned {
+            lines.push(ParsedLine::new(raw, indent, depth, state.line_number));
+        }
+    }
+    lines
+}
+
 #[must_use]
 pub const fn compute_depth_from_indent(indent_spaces: usize, indent_size: usize) -> Depth {
     if indent_size == 0 {
@@ -138,56 +260,57 @@ pub const fn compute_depth_from_indent(indent_spaces: usize, indent_size: usize)
 }
 
 #[derive(Debug, Clone)]
-pub struct StreamingLineCursor {
-    lines: Vec<ParsedLine>,
+pub struct StreamingLineCursor<'a> {
+    lines: Vec<ParsedLine<'a>>,
     index: usize,
-    last_line: Option<ParsedLine>,
     blank_lines: Vec<BlankLineInfo>,
 }
 
-impl StreamingLineCursor {
+impl<'a> StreamingLineCursor<'a> {
     #[must_use]
-    pub const fn new(lines: Vec<ParsedLine>, blank_lines: Vec<BlankLineInfo>) -> Self {
+    pub const fn new(lines: Vec<ParsedLine<'a>>, blank_lines: Vec<BlankLineInfo>) -> Self {
         Self {
             lines,
             index: 0,
-            last_line: None,
             blank_lines,
         }
     }
 
+    /// The raw text of content line `line_number`.
+    #[must_use]
+    pub fn source_line(&self, line_number: usize) -> Option<&str> {
+        self.lines
+            .binary_search_by_key(&line_number, |line| line.line_number)
+            .ok()
+            .map(|index| &*self.lines[index].raw)
+    }
+
     #[must_use]
     pub fn get_blank_lines(&self) -> &[BlankLineInfo] {
         &self.blank_lines
     }
 
     #[must_use]
-    pub fn peek_sync(&self) -> Option<&ParsedLine> {
+    pub fn peek_sync(&self) -> Option<&ParsedLine<'a>> {
         self.lines.get(self.index)
     }
 
     pub fn advance_sync(&mut self) {
         if self.index < self.lines.len() {
-            // Store index instead of cloning
-            self.last_line = Some(self.lines[self.index].clone());
             self.index += 1;
         }
     }
 
-    pub fn next_sync(&mut self) -> Option<ParsedLine> {
-        if self.index < self.lines.len() {
-            let line = self.lines[self.index].clone();
-            self.last_line = Some(line.clone());
-            self.index += 1;
-            Some(line)
-        } else {
-            None
-        }
+    pub fn next_sync(&mut self) -> Option<ParsedLine<'a>> {
+        let line = self.lines.get(self.index)?.clone();
+        self.index += 1;
+        Some(line)
     }
 
+    /// The line most recently consumed.
     #[must_use]
-    pub const fn current(&self) -> Option<&ParsedLine> {
-        self.last_line.as_ref()
+    pub fn current(&self) -> Option<&ParsedLine<'a>> {
+        self.index.checked_sub(1).map(|index| &self.lines[index])
     }
 
     #[must_use]
@@ -1,6 +1,6 @@
 use crate::decode::parser::ArrayHeaderInfo;
-use crate::decode::scanner::{BlankLineInfo, Depth, ParsedLine};
-use crate::error::{Result, ToonError};
+use crate::decode::scanner::{BlankLineInfo, Depth};
+use crate::error::{DecodeErrorKind, Result, ToonError};
 use crate::shared::constants::{COLON, LIST_ITEM_PREFIX};
 use crate::shared::string_utils::find_unquoted_char;
 
@@ -16,30 +16,33 @@ pub fn assert_expected_count(
     strict: bool,
 ) -> Result<()> {
     if strict && actual != expected {
-        return Err(ToonError::message(format!(
-            "Expected {expected} {item_type}, but got {actual}"
-        )));
+        return Err(ToonError::decode(
+            DecodeErrorKind::CountMismatch,
+            format!("Expected {expected} {item_type}, but got {actual}"),
+        ));
     }
     Ok(())
 }
 
 /// Validate that there are no extra list items beyond the expected count.
+/// `next_line` is the depth and content of the line after the items.
 ///
 /// # Errors
 ///
 /// Returns an error in strict mode when extra list items are found.
 pub fn validate_no_extra_list_items(
-    next_line: Option<&ParsedLine>,
+    next_line: Option<(Depth, &str)>,
     item_depth: Depth,
     expected_count: usize,
     strict: bool,
 ) -> Result<()> {
     if strict {
-        if let Some(line) = next_line {
-            if line.depth == item_depth && line.content.starts_with(LIST_ITEM_PREFIX) {
-                return Err(ToonError::message(format!(
-                    "Expected {expected_count} list array items, but found more"
-                )));
+        if let Some((depth, content)) = next_line {
+            if depth == item_depth && content.starts_with(LIST_ITEM_PREFIX) {
+                return Err(ToonError::decode(
+                    DecodeErrorKind::CountMismatch,
+                    format!("Expected {expected_count} list array items, but found more"),
+                ));
             }
         }
     }
@@ -47,26 +50,27 @@ pub fn validate_no_extra_list_items(
 }
 
 /// Validate that there are no extra tabular rows beyond the expected count.
+/// `next_line` is the depth and content of the line after the rows.
 ///
 /// # Errors
 ///
 /// Returns an error in strict mode when extra tabular rows are found.
 pub fn validate_no_extra_tabular_rows(
-    next_line: Option<&ParsedLine>,
+    next_line: Option<(Depth, &str)>,
     row_depth: Depth,
-    header: &ArrayHeaderInfo,
+    header: &ArrayHeaderInfo<'_>,
     strict: bool,
 ) -> Result<()> {
     if strict {
-        if let Some(line) = next_line {
-            if line.depth == row_depth
-                && !line.content.starts_with(LIST_ITEM_PREFIX)
-                && is_data_row(&line.content, header.delimiter)
+        if let Some((depth, content)) = next_line {
+            if depth == row_depth
+                && !content.starts_with(LIST_ITEM_PREFIX)
+                && is_data_row(content, header.delimiter)
             {
-                return Err(ToonError::message(format!(
-                    "Expected {} tabular rows, but found more",
-                    header.length
-                )));
+                return Err(ToonError::decode(
+                    DecodeErrorKind::CountMismatch,
+                    format!("Expected {} tabular rows, but found more", header.length),
+                ));
             }
         }
     }
@@ -93,16 +97,20 @@ pub fn validate_no_blank_lines_in_range(
         .iter()
         .find(|blank| blank.line_number > start_line && blank.line_number < end_line)
     {
-        return Err(ToonError::message(format!(
-            "Line {}: Blank lines inside {context} are not allowed in strict mode",
-            first_blank.line_number
-        )));
+        return Err(ToonError::decode(
+            DecodeErrorKind::BlankLine,
+            format!("Blank lines inside {context} are not allowed in strict mode"),
+        )
+        .at_line(first_blank.line_number));
     }
 
     Ok(())
 }
 
-fn is_data_row(content: &str, delimiter: char) -> bool {
+/// Whether a line under a tabular header reads as a row rather than a key:
+/// it has no unquoted colon, or a delimiter comes first.
+#[must_use]
+pub fn is_data_row(content: &str, delimiter: char) -> bool {
     // Find first unquoted colon and delimiter to properly handle quoted strings
     let colon_pos = find_unquoted_char(content, COLON, 0);
     let delimiter_pos = find_unquoted_char(content, delimiter, 0);
@@ -0,0 +1,115 @@
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::StringOrNumberOrBoolOrNull;
+use crate::shared::constants::DOUBLE_QUOTE;
+use crate::shared::literal_utils::is_numeric_like;
+
+/// Non-fatal diagnostic reported while decoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeWarning {
+    /// 1-based source line the warning refers to.
+    pub line: usize,
+    pub kind: DecodeWarningKind,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeWarningKind {
+    /// An unquoted value whose inferred type may not be what the author meant.
+    AmbiguousValue,
+    /// An array header without a valid `[N]` length, accepted with
+    /// `strictness.length_markers` off and the length counted from its items.
+    MissingLengthMarker,
+    /// An array whose items, rows or row values differ from its declared
+    /// length, accepted with `strictness.length_markers` off.
+    LengthMismatch,
+    /// A line ending in spaces or tabs.
+    TrailingWhitespace,
+    /// Tabs in indentation, accepted with `strictness.tabs` off.
+    TabIndentation,
+}
+
+impl fmt::Display for DecodeWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Shared list that decoders append warnings to, see
+/// [`DecodeOptions::collect_warnings`](crate::options::DecodeOptions::collect_warnings).
+///
+/// Clones share one list, so a sink kept by the caller sees the warnings of
+/// every decode its clones were passed to.
+#[derive(Debug, Clone, Default)]
+pub struct WarningSink(Arc<Mutex<Vec<DecodeWarning>>>);
+
+impl WarningSink {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remove and return the warnings gathered so far.
+    #[must_use]
+    pub fn take(&self) -> Vec<DecodeWarning> {
+        std::mem::take(&mut *self.0.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    pub(crate) fn extend(&self, warnings: impl IntoIterator<Item = DecodeWarning>) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .extend(warnings);
+    }
+}
+
+/// Words that read as booleans or null in other formats but decode as strings.
+const LOOKALIKE_WORDS: &[&str] = &[
+    "yes", "no", "on", "off", "none", "nil", "nan", "inf", "infinity",
+];
+
+/// Describe why an unquoted `token` that decoded to `value` is ambiguous, or
+/// `None` when the inferred type is unsurprising.
+///
+/// Flags numbers written in exponent form, `f64` decimals with more digits
+/// than the float holds, strings that look numeric (leading zeros), and
+/// strings that look like booleans or null (`yes`, `True`, `NULL`).
+#[must_use]
+pub fn ambiguity_message(token: &str, value: &StringOrNumberOrBoolOrNull) -> Option<String> {
+    let token = token.trim();
+    if token.is_empty() || token.starts_with(DOUBLE_QUOTE) {
+        return None;
+    }
+
+    match value {
+        StringOrNumberOrBoolOrNull::Number(number) => {
+            if token.contains(['e', 'E']) {
+                return Some(format!(
+                    "`{token}` was parsed as the number {number}; quote it to keep it as a string"
+                ));
+            }
+            let digits = token.bytes().filter(u8::is_ascii_digit).count();
+            if matches!(number, crate::Number::F64(_)) && digits > 15 {
+                return Some(format!(
+                    "`{token}` was parsed as a number but has more digits than a float preserves; quote it to keep it as a string"
+                ));
+            }
+            None
+        }
+        StringOrNumberOrBoolOrNull::String(_) => {
+            if is_numeric_like(token) {
+                return Some(format!(
+                    "`{token}` looks like a number but was parsed as a string"
+                ));
+            }
+            let lower = token.to_ascii_lowercase();
+            let lookalike = matches!(lower.as_str(), "true" | "false" | "null")
+                || LOOKALIKE_WORDS.contains(&lower.as_str());
+            lookalike.then(|| {
+                format!("`{token}` looks like a boolean or null but was parsed as a string")
+            })
+        }
+        StringOrNumberOrBoolOrNull::Bool(_) | StringOrNumberOrBoolOrNull::Null => None,
+    }
+}
@@ -0,0 +1,147 @@
+//! [`miette::Diagnostic`] for decode errors, so a CLI can render them as an
+//! annotated snippet of the offending line with a hint on how to fix it.
+//!
+//! ```
+//! let err = toon::try_decode("a:\n  b: 1\n  c 2", None).unwrap_err();
+//! let report = miette::Report::new(err);
+//! assert_eq!(report.code().unwrap().to_string(), "toon::decode::missing_colon");
+//! ```
+
+use std::fmt;
+
+use miette::{
+    Diagnostic, LabeledSpan, MietteError, MietteSpanContents, SourceCode, SourceSpan, SpanContents,
+};
+
+use crate::error::{DecodeError, DecodeErrorKind, ToonError};
+
+impl DecodeErrorKind {
+    const fn code(self) -> &'static str {
+        match self {
+            Self::UnterminatedString => "toon::decode::unterminated_string",
+            Self::InvalidEscape => "toon::decode::invalid_escape",
+            Self::TrailingCharacters => "toon::decode::trailing_characters",
+            Self::MissingColon => "toon::decode::missing_colon",
+            Self::InvalidLength => "toon::decode::invalid_length",
+            Self::CountMismatch => "toon::decode::count_mismatch",
+            Self::BlankLine => "toon::decode::blank_line",
+            Self::Indentation => "toon::decode::indentation",
+            Self::LineTooLong => "toon::decode::line_too_long",
+            Self::InvalidUtf8 => "toon::decode::invalid_utf8",
+            Self::DuplicateKey => "toon::decode::duplicate_key",
+            Self::LimitExceeded => "toon::decode::limit_exceeded",
+            Self::Structure => "toon::decode::structure",
+        }
+    }
+
+    const fn label(self) -> &'static str {
+        match self {
+            Self::UnterminatedString => "string opened here",
+            Self::InvalidEscape => "unknown escape",
+            Self::TrailingCharacters => "value with text after its closing quote",
+            Self::MissingColon => "expected `key: value`",
+            Self::InvalidLength => "invalid length",
+            Self::CountMismatch => "count differs from the declared length",
+            Self::BlankLine => "blank line",
+            Self::Indentation => "indentation",
+            Self::LineTooLong => "over the limit",
+            Self::InvalidUtf8 => "invalid bytes",
+            Self::DuplicateKey => "duplicate key",
+            Self::LimitExceeded => "past the limit",
+            Self::Structure => "here",
+        }
+    }
+
+    const fn help(self) -> Option<&'static str> {
+        match self {
+            Self::UnterminatedString => Some("close the string with `\"`"),
+            Self::InvalidEscape => Some("valid escapes are `\\\\`, `\\\"`, `\\n`, `\\r` and `\\t`"),
+            Self::TrailingCharacters => Some("quote the whole value"),
+            Self::MissingColon => Some("separate the key from its value with `: `"),
+            Self::InvalidLength => Some("write the length as a whole number, as in `items[3]:`"),
+            Self::CountMismatch => Some(
+                "make the `[N]` length match the items, or turn off `strictness.length_markers` to accept it",
+            ),
+            Self::BlankLine => {
+                Some("remove the blank line, or turn off `strictness.blank_lines` to accept it")
+            }
+            Self::Indentation => Some("indent with spaces, one indent size per level"),
+            Self::LineTooLong => Some("split the value, or raise `max_line_length`"),
+            Self::InvalidUtf8 => Some("save the input as UTF-8"),
+            Self::DuplicateKey => Some("rename or remove one of the keys"),
+            Self::LimitExceeded => Some("shrink the document, or raise `DecodeOptions::limits`"),
+            Self::Structure => None,
+        }
+    }
+}
+
+impl Diagnostic for DecodeError {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(self.kind.code()))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.kind
+            .help()
+            .map(|help| Box::new(help) as Box<dyn fmt::Display>)
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        if self.source_line.is_empty() {
+            return None;
+        }
+        Some(self)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        if self.source_line.is_empty() {
+            return None;
+        }
+        let end = self.columns.end.min(self.source_line.len());
+        let start = self.columns.start.min(end);
+        Some(Box::new(std::iter::once(LabeledSpan::new_with_span(
+            Some(self.kind.label().to_string()),
+            start..end,
+        ))))
+    }
+}
+
+/// The source is the offending line alone, numbered as in the document.
+impl SourceCode for DecodeError {
+    fn read_span<'a>(
+        &'a self,
+        span: &SourceSpan,
+        context_lines_before: usize,
+        context_lines_after: usize,
+    ) -> Result<Box<dyn SpanContents<'a> + 'a>, MietteError> {
+        let contents =
+            self.source_line
+                .read_span(span, context_lines_before, context_lines_after)?;
+        Ok(Box::new(MietteSpanContents::new(
+            contents.data(),
+            *contents.span(),
+            contents.line() + self.line.saturating_sub(1),
+            contents.column(),
+            contents.line_count(),
+        )))
+    }
+}
+
+/// Decode errors report their location; other errors are plain messages.
+impl Diagnostic for ToonError {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.decode_error()?.code()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.decode_error()?.help()
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.decode_error()?.source_code()
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.decode_error()?.labels()
+    }
+}
@@ -0,0 +1,193 @@
+//! Structural differences between two decoded values.
+
+use std::collections::HashSet;
+
+use crate::error::{Result, ToonError};
+use crate::patch::{Patch, PatchOperation};
+use crate::pointer::escape;
+use crate::{EncodeOptions, JsonObject, JsonValue};
+
+/// One difference between two values, addressed by JSON Pointer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffEntry {
+    /// `path` exists only in the new value.
+    Added { path: String, value: JsonValue },
+    /// `path` exists only in the old value.
+    Removed { path: String, value: JsonValue },
+    /// `path` holds different values on each side.
+    Changed {
+        path: String,
+        old: JsonValue,
+        new: JsonValue,
+    },
+}
+
+impl DiffEntry {
+    /// The JSON Pointer of the differing value.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Added { path, .. } | Self::Removed { path, .. } | Self::Changed { path, .. } => {
+                path
+            }
+        }
+    }
+}
+
+/// How [`render`] writes a diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffFormat {
+    /// The equivalent patch document encoded as TOON.
+    Toon,
+    /// The equivalent RFC 6902 JSON Patch document, pretty-printed.
+    JsonPatch,
+}
+
+/// Compare `old` against `new`, descending into objects and arrays.
+///
+/// Object members are matched by key regardless of order (a repeated key
+/// compares its last entry); array items are matched by index, and
+/// numbers by value, so `1` and `1.0` agree. Entries come out in an order
+/// that [`to_patch`] can replay: within an array, trailing removals run
+/// from the highest index down.
+///
+/// ```
+/// use toon::diff::DiffEntry;
+///
+/// let old = toon::decode("port: 80\ntags[2]: a,b", None);
+/// let new = toon::decode("port: 8080\ntags[1]: a\ndebug: true", None);
+/// assert_eq!(
+///     toon::diff(&old, &new),
+///     [
+///         DiffEntry::Changed { path: "/port".into(), old: 80.into(), new: 8080.into() },
+///         DiffEntry::Removed { path: "/tags/1".into(), value: "b".into() },
+///         DiffEntry::Added { path: "/debug".into(), value: true.into() },
+///     ]
+/// );
+/// ```
+#[must_use]
+pub fn diff(old: &JsonValue, new: &JsonValue) -> Vec<DiffEntry> {
+    let mut entries = Vec::new();
+    diff_into(old, new, &mut String::new(), &mut entries);
+    entries
+}
+
+/// The patch turning the old value of a [`diff`] into the new one.
+#[must_use]
+pub fn to_patch(entries: &[DiffEntry]) -> Patch {
+    Patch(
+        entries
+            .iter()
+            .map(|entry| match entry {
+                DiffEntry::Added { path, value } => PatchOperation::Add {
+                    path: path.clone(),
+                    value: value.clone(),
+                },
+                DiffEntry::Removed { path, .. } => PatchOperation::Remove { path: path.clone() },
+                DiffEntry::Changed { path, new, .. } => PatchOperation::Replace {
+                    path: path.clone(),
+                    value: new.clone(),
+                },
+            })
+            .collect(),
+    )
+}
+
+/// Render a diff as a patch document in the given format.
+///
+/// # Errors
+///
+/// Returns an error if JSON serialization fails.
+pub fn render(
+    entries: &[DiffEntry],
+    format: DiffFormat,
+    options: Option<EncodeOptions>,
+) -> Result<String> {
+    let document = to_patch(entries).to_value();
+    Ok(match format {
+        DiffFormat::Toon => crate::encode(document, options),
+        DiffFormat::JsonPatch => serde_json::to_string_pretty(&serde_json::Value::from(document))
+            .map_err(|err| ToonError::json_stringify(&err))?,
+    })
+}
+
+fn diff_into(old: &JsonValue, new: &JsonValue, path: &mut String, out: &mut Vec<DiffEntry>) {
+    match (old, new) {
+        (JsonValue::Object(before), JsonValue::Object(after)) => {
+            let mut seen = HashSet::new();
+            for (key, _) in before {
+                if !seen.insert(key.as_str()) {
+                    continue;
+                }
+                let before_value = last(before, key).expect("key is present");
+                let len = push_segment(path, key);
+                match last(after, key) {
+                    Some(after_value) => diff_into(before_value, after_value, path, out),
+                    None => out.push(DiffEntry::Removed {
+                        path: path.clone(),
+                        value: before_value.clone(),
+                    }),
+                }
+                path.truncate(len);
+            }
+            for (key, _) in after {
+                if last(before, key).is_some() || !seen.insert(key.as_str()) {
+                    continue;
+                }
+                let len = push_segment(path, key);
+                out.push(DiffEntry::Added {
+                    path: path.clone(),
+                    value: last(after, key).expect("key is present").clone(),
+                });
+                path.truncate(len);
+            }
+        }
+        (JsonValue::Array(before), JsonValue::Array(after)) => {
+            let shared = before.len().min(after.len());
+            for (index, (before_item, after_item)) in before.iter().zip(after).enumerate() {
+                let len = push_segment(path, &index.to_string());
+                diff_into(before_item, after_item, path, out);
+                path.truncate(len);
+            }
+            for (index, item) in before.iter().enumerate().skip(shared).rev() {
+                let len = push_segment(path, &index.to_string());
+                out.push(DiffEntry::Removed {
+                    path: path.clone(),
+                    value: item.clone(),
+                });
+                path.truncate(len);
+            }
+            for (index, item) in after.iter().enumerate().skip(shared) {
+                let len = push_segment(path, &index.to_string());
+                out.push(DiffEntry::Added {
+                    path: path.clone(),
+                    value: item.clone(),
+                });
+                path.truncate(len);
+            }
+        }
+        _ if old == new => {}
+        _ => out.push(DiffEntry::Changed {
+            path: path.clone(),
+            old: old.clone(),
+            new: new.clone(),
+        }),
+    }
+}
+
+/// The last entry for `key`, as JSON parsers keep it.
+fn last<'a>(entries: &'a JsonObject, key: &str) -> Option<&'a JsonValue> {
+    entries
+        .iter()
+        .rev()
+        .find(|(k, _)| k == key)
+        .map(|(_, value)| value)
+}
+
+/// Append one escaped segment, returning the length to truncate back to.
+fn push_segment(path: &mut String, segment: &str) -> usize {
+    let len = path.len();
+    path.push('/');
+    path.push_str(&escape(segment));
+    len
+}
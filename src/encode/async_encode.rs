@@ -19,12 +19,12 @@
 //! }
 //! ```
 
-use crate::encode::encoders;
-use crate::encode::normalize::normalize_json_value;
-use crate::encode::replacer::apply_replacer;
+use crate::encode::{encoders, prepare_value};
+use crate::error::{Result, ToonError};
 use crate::options::{EncodeOptions, ResolvedEncodeOptions, resolve_encode_options};
 use crate::shared::validation::is_valid_unquoted_key;
 use crate::{JsonStreamEvent, JsonValue};
+use asupersync::io::{AsyncWrite, AsyncWriteExt};
 use asupersync::stream::{Stream, StreamExt, iter};
 use std::pin::Pin;
 use std::task::{Context, Poll};
@@ -43,15 +43,15 @@ pub struct AsyncEncodeStream {
 
 impl AsyncEncodeStream {
     /// Create a new async encode stream from a JSON value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`EncodeOptions::on_non_finite`] is `Error` and the value
+    /// holds a non-finite number.
     #[must_use]
     pub fn new(input: impl Into<JsonValue>, options: Option<EncodeOptions>) -> Self {
         let resolved = resolve_encode_options(options);
-        let normalized = normalize_json_value(input.into());
-        let replaced = if let Some(replacer) = &resolved.replacer {
-            apply_replacer(&normalized, replacer)
-        } else {
-            normalized
-        };
+        let replaced = prepare_value(input.into(), &resolved).unwrap_or_else(|err| panic!("{err}"));
         let lines = encoders::encode_json_value(&replaced, &resolved);
 
         Self { lines, index: 0 }
@@ -114,15 +114,15 @@ enum EncodeStackFrame {
 
 impl AsyncEncodeEventStream {
     /// Create a new async event stream from a JSON value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`EncodeOptions::on_non_finite`] is `Error` and the value
+    /// holds a non-finite number.
     #[must_use]
     pub fn new(input: impl Into<JsonValue>, options: Option<EncodeOptions>) -> Self {
         let resolved = resolve_encode_options(options);
-        let normalized = normalize_json_value(input.into());
-        let replaced = if let Some(replacer) = &resolved.replacer {
-            apply_replacer(&normalized, replacer)
-        } else {
-            normalized
-        };
+        let replaced = prepare_value(input.into(), &resolved).unwrap_or_else(|err| panic!("{err}"));
 
         Self {
             stack: Vec::new(),
@@ -224,6 +224,11 @@ impl Stream for AsyncEncodeEventStream {
 ///
 /// This function creates an async stream and collects all lines. The async
 /// wrapper provides yield points for cooperative scheduling.
+///
+/// # Panics
+///
+/// Panics if [`EncodeOptions::on_non_finite`] is `Error` and the value holds
+/// a non-finite number.
 pub async fn encode_lines_async(
     input: impl Into<JsonValue>,
     options: Option<EncodeOptions>,
@@ -232,12 +237,7 @@ pub async fn encode_lines_async(
 
     // Use asupersync's iter() to create a yielding stream from the lines
     let resolved = resolve_encode_options(options);
-    let normalized = normalize_json_value(input);
-    let replaced = if let Some(replacer) = &resolved.replacer {
-        apply_replacer(&normalized, replacer)
-    } else {
-        normalized
-    };
+    let replaced = prepare_value(input, &resolved).unwrap_or_else(|err| panic!("{err}"));
     let lines = encoders::encode_json_value(&replaced, &resolved);
 
     // Wrap lines in an async stream for yield points
@@ -251,8 +251,12 @@ pub async fn encode_lines_async(
 
 /// Encode a JSON value to a TOON string asynchronously.
 pub async fn encode_async(input: impl Into<JsonValue>, options: Option<EncodeOptions>) -> String {
+    let line_ending = options
+        .as_ref()
+        .and_then(|options| options.line_ending)
+        .unwrap_or_default();
     let lines = encode_lines_async(input, options).await;
-    lines.join("\n")
+    lines.join(line_ending.as_str())
 }
 
 /// Encode a JSON value to events asynchronously.
@@ -274,10 +278,176 @@ pub async fn encode_events_async(
     events
 }
 
+/// Encode a JSON value into an [`AsyncWrite`], one top-level entry or root
+/// array item at a time.
+///
+/// Each piece is written out before the next one is encoded, so a slow
+/// writer holds the encoder back and memory stays at the size of the
+/// largest piece rather than the whole document. The writer is flushed once
+/// everything is written. The bytes match [`encode`](crate::encode).
+///
+/// # Errors
+///
+/// Returns an error if the writer fails, or if
+/// [`EncodeOptions::on_non_finite`] is `Error` and the value holds a
+/// non-finite number.
+pub async fn encode_stream_async<W: AsyncWrite + Unpin>(
+    input: impl Into<JsonValue>,
+    writer: &mut W,
+    options: Option<EncodeOptions>,
+) -> Result<()> {
+    let resolved = resolve_encode_options(options);
+    let value = prepare_value(input.into(), &resolved)?;
+    let write_error = |err| ToonError::io("Failed to write encoded output", None, err);
+
+    let mut chunk = String::new();
+    let mut started = false;
+    for lines in encoders::LinePieces::new(&value, &resolved) {
+        chunk.clear();
+        for line in lines {
+            if started {
+                chunk.push_str(resolved.line_ending.as_str());
+            }
+            started = true;
+            chunk.push_str(&line);
+        }
+        writer
+            .write_all(chunk.as_bytes())
+            .await
+            .map_err(write_error)?;
+    }
+    writer.flush().await.map_err(write_error)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::StringOrNumberOrBoolOrNull;
+    use crate::{Number, StringOrNumberOrBoolOrNull};
+    use std::task::Waker;
+
+    /// Accepts at most `limit` bytes per write, returning `Pending` before
+    /// each one, and records the largest buffer it was offered.
+    #[derive(Default)]
+    struct Throttled {
+        written: Vec<u8>,
+        limit: usize,
+        ready: bool,
+        largest: usize,
+        flushed: bool,
+        fail: bool,
+    }
+
+    impl AsyncWrite for Throttled {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            let this = self.get_mut();
+            if this.fail {
+                return Poll::Ready(Err(std::io::Error::other("disk full")));
+            }
+            if !this.ready {
+                this.ready = true;
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            this.ready = false;
+            this.largest = this.largest.max(buf.len());
+            let amount = buf.len().min(this.limit);
+            this.written.extend_from_slice(&buf[..amount]);
+            Poll::Ready(Ok(amount))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            self.get_mut().flushed = true;
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    fn encode_throttled(value: serde_json::Value, options: Option<EncodeOptions>) -> Throttled {
+        let mut writer = Throttled {
+            limit: 7,
+            ..Throttled::default()
+        };
+        block_on(encode_stream_async(value, &mut writer, options)).unwrap();
+        writer
+    }
+
+    #[test]
+    fn test_encode_stream_async_matches_encode() {
+        let values = [
+            serde_json::json!({"a.b": 1, "a": {"b": {"c": 2}}, "tags": ["x", "y"]}),
+            serde_json::json!([{"id": 1, "name": "Ada"}, {"id": 2, "name": "Lin"}]),
+            serde_json::json!([1, {"k": [1, 2]}, [3, 4], "s"]),
+            serde_json::json!([[1, 2], [3]]),
+            serde_json::json!([]),
+            serde_json::json!({}),
+            serde_json::json!(""),
+            serde_json::json!("hello, world"),
+        ];
+        for value in values {
+            let writer = encode_throttled(value.clone(), None);
+            assert_eq!(
+                String::from_utf8(writer.written).unwrap(),
+                crate::encode(value, None)
+            );
+            assert!(writer.flushed);
+        }
+    }
+
+    #[test]
+    fn test_encode_stream_async_with_options() {
+        let options = EncodeOptions {
+            key_folding: Some(crate::options::KeyFoldingMode::Safe),
+            delimiter: Some('|'),
+            ..EncodeOptions::default()
+        };
+        let value = serde_json::json!({"a": {"b": {"c": [1, 2]}}, "rows": [{"x": 1}, {"x": 2}]});
+        let writer = encode_throttled(value.clone(), Some(options.clone()));
+        assert_eq!(
+            String::from_utf8(writer.written).unwrap(),
+            crate::encode(value, Some(options))
+        );
+    }
+
+    #[test]
+    fn test_encode_stream_async_waits_on_writer() {
+        let rows: Vec<_> = (0..50).map(|id| serde_json::json!({"id": id})).collect();
+        let writer = encode_throttled(serde_json::Value::Array(rows), None);
+        // Rows are handed over one at a time, never the whole table.
+        assert_eq!(writer.written.len(), "[50]{id}:".len() + 10 * 4 + 40 * 5);
+        assert_eq!(writer.largest, "[50]{id}:".len());
+    }
+
+    #[test]
+    fn test_encode_stream_async_write_error() {
+        let mut writer = Throttled {
+            fail: true,
+            ..Throttled::default()
+        };
+        let err = block_on(encode_stream_async(
+            serde_json::json!({"a": 1}),
+            &mut writer,
+            None,
+        ))
+        .unwrap_err();
+        assert!(err.to_string().contains("Failed to write encoded output"));
+    }
 
     #[test]
     fn test_async_encode_stream_creation() {
@@ -288,7 +458,7 @@ mod tests {
             ),
             (
                 "age".to_string(),
-                JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(30.0)),
+                JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(Number::I64(30))),
             ),
         ]);
         let stream = AsyncEncodeStream::new(value, None);
@@ -322,8 +492,8 @@ mod tests {
             (
                 "items".to_string(),
                 JsonValue::Array(vec![
-                    JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(1.0)),
-                    JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(2.0)),
+                    JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(Number::I64(1))),
+                    JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(Number::I64(2))),
                 ]),
             ),
         ]);
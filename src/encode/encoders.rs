@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::collections::HashSet;
 
 use crate::encode::folding::try_fold_key_chain;
@@ -8,31 +9,216 @@ use crate::encode::normalize::{
 use crate::encode::primitives::{
     encode_and_join_primitives, encode_key, encode_primitive, format_header,
 };
-use crate::options::ResolvedEncodeOptions;
+use crate::options::{IndentStyle, KeyFoldingMode, QuotePolicy, ResolvedEncodeOptions};
 use crate::shared::constants::{DOT, LIST_ITEM_MARKER, LIST_ITEM_PREFIX};
-use crate::{JsonArray, JsonObject, JsonPrimitive, JsonValue};
+use crate::shared::validation::{is_identifier_segment, is_safe_unquoted};
+use crate::{JsonArray, JsonObject, JsonPrimitive, JsonValue, StringOrNumberOrBoolOrNull};
 
 #[must_use]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(name = "encode", level = "debug", skip_all)
+)]
 pub fn encode_json_value(value: &JsonValue, options: &ResolvedEncodeOptions) -> Vec<String> {
     let estimated_lines = estimate_line_count(value);
     let mut out = Vec::with_capacity(estimated_lines);
+    encode_json_value_into(value, options, &mut out);
+    #[cfg(feature = "tracing")]
+    tracing::debug!(lines = out.len(), "encoded");
+    out
+}
+
+/// Destination for encoded lines, in order and without separators.
+pub trait LineSink {
+    fn push(&mut self, line: String);
+}
+
+impl LineSink for Vec<String> {
+    fn push(&mut self, line: String) {
+        Self::push(self, line);
+    }
+}
+
+/// Encode `value` line by line into `out`, so a sink that writes each line
+/// out never holds the whole document.
+pub fn encode_json_value_into(
+    value: &JsonValue,
+    options: &ResolvedEncodeOptions,
+    out: &mut impl LineSink,
+) {
     match value {
         JsonValue::Primitive(primitive) => {
-            let encoded = encode_primitive(primitive, options.delimiter);
+            let encoded = encode_value(primitive, options);
             if !encoded.is_empty() {
                 out.push(encoded);
             }
         }
         JsonValue::Array(items) => {
-            encode_array_lines(None, items, 0, options, &mut out);
+            encode_array_lines(None, None, items, 0, options, out);
         }
         JsonValue::Object(entries) => {
-            encode_object_lines(entries, 0, options, None, None, None, &mut out);
+            encode_object_lines(entries, 0, options, None, None, None, out);
         }
     }
+}
+
+/// Lines for `value` as one item of a root list array (`- ...`), for
+/// writers that emit the `[N]:` header themselves.
+#[must_use]
+pub fn encode_root_list_item(value: &JsonValue, options: &ResolvedEncodeOptions) -> Vec<String> {
+    let mut out = Vec::new();
+    encode_list_item_value_lines(value, None, 1, options, &mut out);
     out
 }
 
+/// The line for `row` in a root table with columns `header`, or `None` if
+/// `row` does not hold exactly those columns with primitive values.
+#[must_use]
+pub fn encode_root_table_row(
+    row: &JsonValue,
+    header: &[String],
+    options: &ResolvedEncodeOptions,
+) -> Option<String> {
+    let rows = std::slice::from_ref(row);
+    if tabular_mismatch(rows, header).is_some() {
+        return None;
+    }
+    let mut out = Vec::with_capacity(1);
+    write_tabular_rows_lines(rows, header, 1, options, &mut out);
+    out.pop()
+}
+
+/// The lines of [`encode_json_value`] in pieces: one per top-level entry
+/// of an object, or a header followed by one piece per item of a root list
+/// or table. Anything else is a single piece.
+///
+/// Encoding a piece at a time keeps memory to the largest piece, which is
+/// what lets a writer apply backpressure between them.
+pub struct LinePieces<'a> {
+    options: &'a ResolvedEncodeOptions,
+    layout: PieceLayout<'a>,
+    index: usize,
+}
+
+enum PieceLayout<'a> {
+    Whole(&'a JsonValue),
+    Object {
+        entries: &'a JsonObject,
+        keys: Vec<&'a str>,
+        root_literal_keys: HashSet<String>,
+    },
+    Table {
+        rows: &'a JsonArray,
+        header: Vec<String>,
+    },
+    List(&'a JsonArray),
+}
+
+impl<'a> LinePieces<'a> {
+    #[must_use]
+    pub fn new(value: &'a JsonValue, options: &'a ResolvedEncodeOptions) -> Self {
+        let layout = match value {
+            JsonValue::Object(entries) => {
+                let keys: Vec<&str> = entries.iter().map(|(key, _)| key.as_str()).collect();
+                let root_literal_keys = dotted_keys(&keys);
+                PieceLayout::Object {
+                    entries,
+                    keys,
+                    root_literal_keys,
+                }
+            }
+            JsonValue::Array(items) if items.is_empty() || is_array_of_primitives(items) => {
+                PieceLayout::Whole(value)
+            }
+            // Arrays of primitive arrays encode like any other list.
+            JsonValue::Array(items) => is_array_of_objects(items)
+                .then(|| extract_tabular_header(items, options))
+                .flatten()
+                .map_or(PieceLayout::List(items), |header| PieceLayout::Table {
+                    rows: items,
+                    header,
+                }),
+            JsonValue::Primitive(_) => PieceLayout::Whole(value),
+        };
+        Self {
+            options,
+            layout,
+            index: 0,
+        }
+    }
+}
+
+impl Iterator for LinePieces<'_> {
+    type Item = Vec<String>;
+
+    fn next(&mut self) -> Option<Vec<String>> {
+        let options = self.options;
+        let index = self.index;
+        let mut out = Vec::new();
+        match &self.layout {
+            PieceLayout::Whole(value) => {
+                if index > 0 {
+                    return None;
+                }
+                encode_json_value_into(value, options, &mut out);
+            }
+            PieceLayout::Object {
+                entries,
+                keys,
+                root_literal_keys,
+            } => {
+                let (key, value) = entries.get(index)?;
+                encode_key_value_pair_lines(
+                    key,
+                    value,
+                    0,
+                    options,
+                    keys,
+                    Some(root_literal_keys),
+                    None,
+                    options.flatten_depth,
+                    &mut out,
+                );
+            }
+            PieceLayout::Table { rows, header } => {
+                if index == 0 {
+                    out.push(format_header(
+                        options.length_marker(rows.len(), true),
+                        None,
+                        Some(header),
+                        options.delimiter,
+                        options.quote_strings,
+                    ));
+                } else {
+                    let row = rows.get(index - 1)?;
+                    write_tabular_rows_lines(
+                        std::slice::from_ref(row),
+                        header,
+                        1,
+                        options,
+                        &mut out,
+                    );
+                }
+            }
+            PieceLayout::List(items) => {
+                if index == 0 {
+                    out.push(format_header(
+                        options.length_marker(items.len(), false),
+                        None,
+                        None,
+                        options.delimiter,
+                        options.quote_strings,
+                    ));
+                } else {
+                    encode_list_item_value_lines(items.get(index - 1)?, None, 1, options, &mut out);
+                }
+            }
+        }
+        self.index += 1;
+        Some(out)
+    }
+}
+
 fn encode_object_lines(
     value: &JsonObject,
     depth: usize,
@@ -40,18 +226,14 @@ fn encode_object_lines(
     root_literal_keys: Option<&HashSet<String>>,
     path_prefix: Option<&str>,
     remaining_depth: Option<usize>,
-    out: &mut Vec<String>,
+    out: &mut impl LineSink,
 ) {
     // Collect keys as references to avoid cloning
     let keys: Vec<&str> = value.iter().map(|(key, _)| key.as_str()).collect();
 
-    let mut root_literal_set = HashSet::new();
+    let root_literal_set;
     let root_literal_keys = if depth == 0 && root_literal_keys.is_none() {
-        for key in &keys {
-            if key.contains(DOT) {
-                root_literal_set.insert((*key).to_string());
-            }
-        }
+        root_literal_set = dotted_keys(&keys);
         Some(&root_literal_set)
     } else {
         root_literal_keys
@@ -74,6 +256,14 @@ fn encode_object_lines(
     }
 }
 
+/// Keys that already contain a dot, which folded keys must not collide with.
+fn dotted_keys(keys: &[&str]) -> HashSet<String> {
+    keys.iter()
+        .filter(|key| key.contains(DOT))
+        .map(|key| (*key).to_string())
+        .collect()
+}
+
 #[allow(clippy::too_many_arguments)]
 fn encode_key_value_pair_lines(
     key: &str,
@@ -84,8 +274,9 @@ fn encode_key_value_pair_lines(
     root_literal_keys: Option<&HashSet<String>>,
     path_prefix: Option<&str>,
     flatten_depth: usize,
-    out: &mut Vec<String>,
+    out: &mut impl LineSink,
 ) {
+    let indent = Indent::of(options);
     let current_path =
         path_prefix.map_or_else(|| key.to_string(), |prefix| format!("{prefix}{DOT}{key}"));
 
@@ -98,27 +289,38 @@ fn encode_key_value_pair_lines(
         path_prefix,
         flatten_depth,
     ) {
-        let encoded_key = encode_key(&folded.folded_key);
+        let encoded_key = folded.folded_key.clone();
 
         if folded.remainder.is_none() {
             match folded.leaf_value {
                 JsonValue::Primitive(primitive) => {
-                    let encoded = encode_primitive(&primitive, options.delimiter);
+                    let encoded = encode_value(&primitive, options);
                     out.push(indented_key_value_line(
                         depth,
                         &encoded_key,
                         &encoded,
-                        options.indent,
+                        indent,
                     ));
                     return;
                 }
                 JsonValue::Array(items) => {
-                    encode_array_lines(Some(&folded.folded_key), &items, depth, options, out);
+                    let folded_path = path_prefix.map_or_else(
+                        || folded.folded_key.clone(),
+                        |prefix| format!("{prefix}{DOT}{}", folded.folded_key),
+                    );
+                    encode_keyed_array_lines(
+                        &folded.folded_key,
+                        &folded_path,
+                        &items,
+                        depth,
+                        options,
+                        out,
+                    );
                     return;
                 }
                 JsonValue::Object(entries) => {
                     if is_empty_object(&entries) {
-                        out.push(indented_key_colon_line(depth, &encoded_key, options.indent));
+                        out.push(indented_key_colon_line(depth, &encoded_key, indent));
                         return;
                     }
                 }
@@ -126,7 +328,7 @@ fn encode_key_value_pair_lines(
         }
 
         if let Some(JsonValue::Object(entries)) = folded.remainder {
-            out.push(indented_key_colon_line(depth, &encoded_key, options.indent));
+            out.push(indented_key_colon_line(depth, &encoded_key, indent));
             let remaining_depth = flatten_depth.saturating_sub(folded.segment_count);
             let folded_path = if let Some(prefix) = path_prefix {
                 format!("{prefix}{DOT}{}", folded.folded_key)
@@ -146,23 +348,23 @@ fn encode_key_value_pair_lines(
         }
     }
 
-    let encoded_key = encode_key(key);
+    let encoded_key = encode_object_key(key, options);
 
     match value {
         JsonValue::Primitive(primitive) => {
-            let encoded = encode_primitive(primitive, options.delimiter);
+            let encoded = encode_value(primitive, options);
             out.push(indented_key_value_line(
                 depth,
                 &encoded_key,
                 &encoded,
-                options.indent,
+                indent,
             ));
         }
         JsonValue::Array(items) => {
-            encode_array_lines(Some(key), items, depth, options, out);
+            encode_keyed_array_lines(&encoded_key, &current_path, items, depth, options, out);
         }
         JsonValue::Object(entries) => {
-            out.push(indented_key_colon_line(depth, &encoded_key, options.indent));
+            out.push(indented_key_colon_line(depth, &encoded_key, indent));
             if !is_empty_object(entries) {
                 encode_object_lines(
                     entries,
@@ -178,22 +380,93 @@ fn encode_key_value_pair_lines(
     }
 }
 
+/// Encode a literal object key. Under [`KeyFoldingMode::Aggressive`] a key
+/// that is not a single identifier segment, such as `a.b`, is quoted so
+/// that aggressive path expansion keeps it as one key.
+fn encode_object_key(key: &str, options: &ResolvedEncodeOptions) -> String {
+    if options.key_folding == KeyFoldingMode::Aggressive && !is_identifier_segment(key) {
+        return encode_key(key, QuotePolicy::AlwaysKeysAndStrings);
+    }
+    encode_key(key, options.quote_strings)
+}
+
+/// Encode a primitive value, counting it if it needs quotes.
+fn encode_value(primitive: &JsonPrimitive, options: &ResolvedEncodeOptions) -> String {
+    record_quoted(std::slice::from_ref(primitive), options);
+    encode_primitive(primitive, options.delimiter, options.quote_strings)
+}
+
+/// Report the strings among `values` that will be written quoted.
+fn record_quoted(values: &[JsonPrimitive], options: &ResolvedEncodeOptions) {
+    let Some(metrics) = &options.metrics else {
+        return;
+    };
+    let quoted = values
+        .iter()
+        .filter(|value| {
+            matches!(value, StringOrNumberOrBoolOrNull::String(text)
+                if options.quote_strings.quotes_strings()
+                    || !is_safe_unquoted(text, options.delimiter))
+        })
+        .count();
+    if quoted > 0 {
+        metrics.strings_quoted(quoted);
+    }
+}
+
+/// The options for the array at `path` alone: `options` with the delimiter
+/// override configured for that path, if any. Arrays and objects nested
+/// inside the array are encoded with `options` again.
+pub(crate) fn array_options<'a>(
+    path: Option<&str>,
+    options: &'a ResolvedEncodeOptions,
+) -> Cow<'a, ResolvedEncodeOptions> {
+    match path.and_then(|path| options.delimiter_override(path)) {
+        Some(delimiter) if delimiter != options.delimiter => Cow::Owned(ResolvedEncodeOptions {
+            delimiter,
+            ..options.clone()
+        }),
+        _ => Cow::Borrowed(options),
+    }
+}
+
+/// Encode an array under the already encoded `key`, reached through `path`.
+fn encode_keyed_array_lines(
+    key: &str,
+    path: &str,
+    value: &JsonArray,
+    depth: usize,
+    options: &ResolvedEncodeOptions,
+    out: &mut impl LineSink,
+) {
+    encode_array_lines(Some(key), Some(path), value, depth, options, out);
+}
+
 fn encode_array_lines(
     key: Option<&str>,
+    path: Option<&str>,
     value: &JsonArray,
     depth: usize,
     options: &ResolvedEncodeOptions,
-    out: &mut Vec<String>,
+    out: &mut impl LineSink,
 ) {
+    let own = array_options(path, options);
     if value.is_empty() {
-        let header = format_header(0, key, None, options.delimiter);
-        out.push(indented_line(depth, &header, options.indent));
+        let header = keyed_header(key, own.length_marker(0, false), None, &own);
+        out.push(indented_line(depth, &header, Indent::of(options)));
         return;
     }
 
     if is_array_of_primitives(value) {
-        let array_line = encode_inline_array_line(value, options.delimiter, key);
-        out.push(indented_line(depth, &array_line, options.indent));
+        push_inline_array_lines(
+            value,
+            &own,
+            key,
+            line_lead(depth, 0, options),
+            depth + 1,
+            |line| indented_line(depth, line, Indent::of(options)),
+            out,
+        );
         return;
     }
 
@@ -203,42 +476,66 @@ fn encode_array_lines(
             _ => false,
         });
         if all_primitive_arrays {
-            encode_array_of_arrays_as_list_items_lines(key, value, depth, options, out);
+            encode_array_of_arrays_as_list_items_lines(key, value, depth, &own, options, out);
             return;
         }
     }
 
     if is_array_of_objects(value) {
-        if let Some(header) = extract_tabular_header(value) {
-            encode_array_of_objects_as_tabular_lines(key, value, &header, depth, options, out);
+        if let Some(header) = extract_tabular_header(value, options) {
+            encode_array_of_objects_as_tabular_lines(key, value, &header, depth, &own, out);
         } else {
-            encode_mixed_array_as_list_items_lines(key, value, depth, options, out);
+            encode_mixed_array_as_list_items_lines(key, path, value, depth, &own, options, out);
         }
         return;
     }
 
-    encode_mixed_array_as_list_items_lines(key, value, depth, options, out);
+    encode_mixed_array_as_list_items_lines(key, path, value, depth, &own, options, out);
 }
 
+/// Encode an array of primitive arrays: its header with `own`, the options
+/// for the array itself, and each inner array with `options`.
 fn encode_array_of_arrays_as_list_items_lines(
     key: Option<&str>,
     values: &JsonArray,
     depth: usize,
+    own: &ResolvedEncodeOptions,
     options: &ResolvedEncodeOptions,
-    out: &mut Vec<String>,
+    out: &mut impl LineSink,
 ) {
-    let header = format_header(values.len(), key, None, options.delimiter);
-    out.push(indented_line(depth, &header, options.indent));
+    let header = keyed_header(key, own.length_marker(values.len(), false), None, own);
+    out.push(indented_line(depth, &header, Indent::of(options)));
 
     for item in values {
         if let JsonValue::Array(items) = item {
-            let line = encode_inline_array_line(items, options.delimiter, None);
-            out.push(indented_list_item(depth + 1, &line, options.indent));
+            push_inline_array_lines(
+                items,
+                options,
+                None,
+                line_lead(depth + 1, LIST_ITEM_PREFIX.len(), options),
+                depth + 2,
+                |line| indented_list_item(depth + 1, line, Indent::of(options)),
+                out,
+            );
         }
     }
 }
 
-fn encode_inline_array_line(values: &JsonArray, delimiter: char, key: Option<&str>) -> String {
+/// Push an inline array: `first_line` places the header and values, then
+/// any continuation lines follow. With `max_line_width` set, an array whose
+/// first line, `lead` columns in, would be wider wraps its values onto lines
+/// at `continuation_depth`; a wrapped array always keeps its `[N]`, which
+/// tells the decoder how many values to read.
+fn push_inline_array_lines(
+    values: &JsonArray,
+    options: &ResolvedEncodeOptions,
+    key: Option<&str>,
+    lead: usize,
+    continuation_depth: usize,
+    first_line: impl FnOnce(&str) -> String,
+    out: &mut impl LineSink,
+) {
+    let delimiter = options.delimiter;
     let primitives: Vec<JsonPrimitive> = values
         .iter()
         .filter_map(|item| match item {
@@ -246,17 +543,83 @@ fn encode_inline_array_line(values: &JsonArray, delimiter: char, key: Option<&st
             _ => None,
         })
         .collect();
-    let header = format_header(values.len(), key, None, delimiter);
+    let header = keyed_header(
+        key,
+        options.length_marker(values.len(), false),
+        None,
+        options,
+    );
     if primitives.is_empty() {
-        return header;
-    }
-    let joined = encode_and_join_primitives(&primitives, delimiter);
-    // Build "header joined" without format!
-    let mut out = String::with_capacity(header.len() + 1 + joined.len());
-    out.push_str(&header);
-    out.push(' ');
-    out.push_str(&joined);
-    out
+        out.push(first_line(&header));
+        return;
+    }
+    record_quoted(&primitives, options);
+    let joined = encode_and_join_primitives(&primitives, delimiter, options.quote_strings);
+    let line_width = |text: &str| text.chars().count();
+    let Some(width) = options.max_line_width.filter(|&width| {
+        lead.saturating_add(line_width(&header) + 1 + line_width(&joined)) > width
+    }) else {
+        // Build "header joined" without format!
+        let mut line = String::with_capacity(header.len() + 1 + joined.len());
+        line.push_str(&header);
+        line.push(' ');
+        line.push_str(&joined);
+        out.push(first_line(&line));
+        return;
+    };
+
+    let mut line = keyed_header(key, Some(values.len()), None, options);
+    line.push(' ');
+    let mut used = lead.saturating_add(line_width(&line));
+    let mut first_line = Some(first_line);
+    let mut line_has_values = false;
+    let mut flush = |line: &str| match first_line.take() {
+        Some(first_line) => out.push(first_line(line)),
+        None => out.push(indented_line(continuation_depth, line, Indent::of(options))),
+    };
+    for primitive in &primitives {
+        let token = encode_primitive(primitive, delimiter, options.quote_strings);
+        let token_width = line_width(&token);
+        if line_has_values && used + 1 + token_width > width {
+            flush(&std::mem::take(&mut line));
+            used = line_lead(continuation_depth, 0, options);
+            line_has_values = false;
+        }
+        if line_has_values {
+            line.push(delimiter);
+            used += 1;
+        }
+        line.push_str(&token);
+        used += token_width;
+        line_has_values = true;
+    }
+    flush(&line);
+}
+
+/// An array header after `key`, which is already encoded; folded keys are
+/// written as they come.
+fn keyed_header(
+    key: Option<&str>,
+    length: Option<usize>,
+    fields: Option<&[String]>,
+    options: &ResolvedEncodeOptions,
+) -> String {
+    let header = format_header(
+        length,
+        None,
+        fields,
+        options.delimiter,
+        options.quote_strings,
+    );
+    match key {
+        Some(key) => format!("{key}{header}"),
+        None => header,
+    }
+}
+
+/// Columns before the content of a line at `depth`, plus `prefix`.
+const fn line_lead(depth: usize, prefix: usize, options: &ResolvedEncodeOptions) -> usize {
+    options.indent.saturating_mul(depth).saturating_add(prefix)
 }
 
 fn encode_array_of_objects_as_tabular_lines(
@@ -265,19 +628,24 @@ fn encode_array_of_objects_as_tabular_lines(
     header: &[String],
     depth: usize,
     options: &ResolvedEncodeOptions,
-    out: &mut Vec<String>,
+    out: &mut impl LineSink,
 ) {
-    let formatted_header = format_header(rows.len(), key, Some(header), options.delimiter);
-    out.push(indented_line(depth, &formatted_header, options.indent));
+    let formatted_header = keyed_header(
+        key,
+        options.length_marker(rows.len(), true),
+        Some(header),
+        options,
+    );
+    out.push(indented_line(depth, &formatted_header, Indent::of(options)));
     write_tabular_rows_lines(rows, header, depth + 1, options, out);
 }
 
 fn write_tabular_rows_lines(
-    rows: &JsonArray,
+    rows: &[JsonValue],
     header: &[String],
     depth: usize,
     options: &ResolvedEncodeOptions,
-    out: &mut Vec<String>,
+    out: &mut impl LineSink,
 ) {
     for row in rows {
         if let JsonValue::Object(entries) = row {
@@ -290,78 +658,139 @@ fn write_tabular_rows_lines(
                     panic!("tabular row contains non-primitive value");
                 }
             }
-            let joined = encode_and_join_primitives(&values, options.delimiter);
-            out.push(indented_line(depth, &joined, options.indent));
+            record_quoted(&values, options);
+            let joined =
+                encode_and_join_primitives(&values, options.delimiter, options.quote_strings);
+            out.push(indented_line(depth, &joined, Indent::of(options)));
         }
     }
 }
 
-fn extract_tabular_header(rows: &JsonArray) -> Option<Vec<String>> {
-    if rows.is_empty() {
-        return None;
-    }
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(name = "detect_table", level = "trace", skip_all, fields(rows = rows.len()), ret)
+)]
+fn extract_tabular_header(
+    rows: &JsonArray,
+    options: &ResolvedEncodeOptions,
+) -> Option<Vec<String>> {
+    tabular_layout(rows, options).ok()
+}
 
-    let JsonValue::Object(first) = &rows[0] else {
-        return None;
+/// Why an array of objects is not written as a table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableRejection {
+    /// The first row has no fields.
+    EmptyFirstRow,
+    /// The item at this index is not an object.
+    NotAnObject(usize),
+    /// The row at this index has other keys than the first row.
+    DifferentKeys(usize),
+    /// The row at this index holds an array or object.
+    NestedValue(usize),
+    /// The array has this many rows, fewer than `tabular_min_rows`.
+    TooFewRows(usize),
+    /// The rows have this many columns, more than `tabular_max_columns`.
+    TooManyColumns(usize),
+    /// Tables are turned off with `tabular: false`.
+    Disabled,
+}
+
+/// The columns of `rows` as a table, taken from the first row.
+///
+/// # Errors
+///
+/// Returns the first [`TableRejection`] that rules out a table.
+pub fn tabular_header(rows: &[JsonValue]) -> Result<Vec<String>, TableRejection> {
+    let Some(JsonValue::Object(first)) = rows.first() else {
+        return Err(TableRejection::NotAnObject(0));
     };
 
     if first.is_empty() {
-        return None;
+        return Err(TableRejection::EmptyFirstRow);
     }
 
     let header: Vec<String> = first.iter().map(|(key, _)| key.clone()).collect();
-    if is_tabular_array(rows, &header) {
-        Some(header)
+    tabular_mismatch(rows, &header).map_or(Ok(header), Err)
+}
+
+/// The columns of `rows` as a table, as [`tabular_header`], if tables are
+/// enabled and this one meets the `tabular_min_rows` and
+/// `tabular_max_columns` thresholds.
+///
+/// # Errors
+///
+/// Returns the first [`TableRejection`] that rules out a table.
+pub fn tabular_layout(
+    rows: &[JsonValue],
+    options: &ResolvedEncodeOptions,
+) -> Result<Vec<String>, TableRejection> {
+    let header = tabular_header(rows)?;
+    if !options.tabular {
+        Err(TableRejection::Disabled)
+    } else if rows.len() < options.tabular_min_rows {
+        Err(TableRejection::TooFewRows(rows.len()))
+    } else if header.len() > options.tabular_max_columns {
+        Err(TableRejection::TooManyColumns(header.len()))
     } else {
-        None
+        Ok(header)
     }
 }
 
-fn is_tabular_array(rows: &JsonArray, header: &[String]) -> bool {
-    for row in rows {
+fn tabular_mismatch(rows: &[JsonValue], header: &[String]) -> Option<TableRejection> {
+    for (index, row) in rows.iter().enumerate() {
         let JsonValue::Object(entries) = row else {
-            return false;
+            return Some(TableRejection::NotAnObject(index));
         };
 
         if entries.len() != header.len() {
-            return false;
+            return Some(TableRejection::DifferentKeys(index));
         }
 
         for key in header {
             let Some(value) = object_get(entries, key) else {
-                return false;
+                return Some(TableRejection::DifferentKeys(index));
             };
             if !is_json_primitive(value) {
-                return false;
+                return Some(TableRejection::NestedValue(index));
             }
         }
     }
-    true
+    None
 }
 
+/// Encode an array as list items: its header with `own`, the options for
+/// the array itself, and each item with `options`. Objects in the items
+/// continue the array's `path`.
+#[allow(clippy::too_many_arguments)]
 fn encode_mixed_array_as_list_items_lines(
     key: Option<&str>,
+    path: Option<&str>,
     items: &JsonArray,
     depth: usize,
+    own: &ResolvedEncodeOptions,
     options: &ResolvedEncodeOptions,
-    out: &mut Vec<String>,
+    out: &mut impl LineSink,
 ) {
-    let header = format_header(items.len(), key, None, options.delimiter);
-    out.push(indented_line(depth, &header, options.indent));
+    let header = keyed_header(key, own.length_marker(items.len(), false), None, own);
+    out.push(indented_line(depth, &header, Indent::of(options)));
 
     for item in items {
-        encode_list_item_value_lines(item, depth + 1, options, out);
+        encode_list_item_value_lines(item, path, depth + 1, options, out);
     }
 }
 
+/// Encode an object as a list item of the array at `path`, which its keys
+/// continue.
 fn encode_object_as_list_item_lines(
     obj: &JsonObject,
+    path: Option<&str>,
     depth: usize,
     options: &ResolvedEncodeOptions,
-    out: &mut Vec<String>,
+    out: &mut impl LineSink,
 ) {
     if obj.is_empty() {
-        out.push(indented_line(depth, LIST_ITEM_MARKER, options.indent));
+        out.push(indented_line(depth, LIST_ITEM_MARKER, Indent::of(options)));
         return;
     }
 
@@ -372,110 +801,164 @@ fn encode_object_as_list_item_lines(
         Vec::new()
     };
     let (first_key, first_value) = first;
+    let first_path = path.map_or_else(
+        || first_key.clone(),
+        |prefix| format!("{prefix}{DOT}{first_key}"),
+    );
 
     if let JsonValue::Array(items) = &first_value {
         if is_array_of_objects(items) {
-            if let Some(header) = extract_tabular_header(items) {
+            if let Some(header) = extract_tabular_header(items, options) {
+                let own = array_options(Some(&first_path), options);
                 let formatted = format_header(
-                    items.len(),
-                    Some(&first_key),
+                    own.length_marker(items.len(), true),
+                    None,
                     Some(&header),
-                    options.delimiter,
+                    own.delimiter,
+                    own.quote_strings,
                 );
-                out.push(indented_list_item(depth, &formatted, options.indent));
-                write_tabular_rows_lines(items, &header, depth + 2, options, out);
+                let formatted = format!("{}{formatted}", encode_object_key(&first_key, options));
+                out.push(indented_list_item(depth, &formatted, Indent::of(options)));
+                write_tabular_rows_lines(items, &header, depth + 2, &own, out);
                 if !rest.is_empty() {
-                    encode_object_lines(&rest, depth + 1, options, None, None, None, out);
+                    encode_object_lines(&rest, depth + 1, options, None, path, None, out);
                 }
                 return;
             }
         }
     }
 
-    let encoded_key = encode_key(&first_key);
+    let encoded_key = encode_object_key(&first_key, options);
 
     match first_value {
         JsonValue::Primitive(primitive) => {
-            let encoded = encode_primitive(&primitive, options.delimiter);
+            let encoded = encode_value(&primitive, options);
             out.push(indented_list_item_key_value(
                 depth,
                 &encoded_key,
                 &encoded,
-                options.indent,
+                Indent::of(options),
             ));
         }
         JsonValue::Array(items) => {
-            if items.is_empty() {
-                let header = format_header(0, None, None, options.delimiter);
-                out.push(indented_list_item_key_header(
-                    depth,
-                    &encoded_key,
-                    &header,
-                    options.indent,
-                ));
-            } else if is_array_of_primitives(&items) {
-                let line = encode_inline_array_line(&items, options.delimiter, None);
-                out.push(indented_list_item_key_header(
-                    depth,
-                    &encoded_key,
-                    &line,
-                    options.indent,
-                ));
-            } else {
-                let header = format_header(items.len(), None, None, options.delimiter);
-                out.push(indented_list_item_key_header(
-                    depth,
-                    &encoded_key,
-                    &header,
-                    options.indent,
-                ));
-                for item in &items {
-                    encode_list_item_value_lines(item, depth + 2, options, out);
-                }
-            }
+            encode_list_item_keyed_array_lines(
+                &encoded_key,
+                &first_path,
+                &items,
+                depth,
+                options,
+                out,
+            );
         }
         JsonValue::Object(entries) => {
             out.push(indented_list_item_key_colon(
                 depth,
                 &encoded_key,
-                options.indent,
+                Indent::of(options),
             ));
             if !is_empty_object(&entries) {
-                encode_object_lines(&entries, depth + 2, options, None, None, None, out);
+                encode_object_lines(
+                    &entries,
+                    depth + 2,
+                    options,
+                    None,
+                    Some(&first_path),
+                    None,
+                    out,
+                );
             }
         }
     }
 
     if !rest.is_empty() {
-        encode_object_lines(&rest, depth + 1, options, None, None, None, out);
+        encode_object_lines(&rest, depth + 1, options, None, path, None, out);
+    }
+}
+
+/// Encode the array under `key`, the first key of a list item, reached
+/// through `path`.
+fn encode_list_item_keyed_array_lines(
+    key: &str,
+    path: &str,
+    items: &JsonArray,
+    depth: usize,
+    options: &ResolvedEncodeOptions,
+    out: &mut impl LineSink,
+) {
+    let own = array_options(Some(path), options);
+    // An empty array is just its header.
+    if items.is_empty() || is_array_of_primitives(items) {
+        push_inline_array_lines(
+            items,
+            &own,
+            None,
+            line_lead(depth, LIST_ITEM_PREFIX.len() + key.chars().count(), options),
+            depth + 2,
+            |line| indented_list_item_key_header(depth, key, line, Indent::of(options)),
+            out,
+        );
+    } else {
+        let header = format_header(
+            own.length_marker(items.len(), false),
+            None,
+            None,
+            own.delimiter,
+            own.quote_strings,
+        );
+        out.push(indented_list_item_key_header(
+            depth,
+            key,
+            &header,
+            Indent::of(options),
+        ));
+        for item in items {
+            encode_list_item_value_lines(item, Some(path), depth + 2, options, out);
+        }
     }
 }
 
+/// Encode `value` as a list item of the array at `path`. An array nested
+/// directly in a list item has no key of its own, so no delimiter override
+/// applies to it.
 fn encode_list_item_value_lines(
     value: &JsonValue,
+    path: Option<&str>,
     depth: usize,
     options: &ResolvedEncodeOptions,
-    out: &mut Vec<String>,
+    out: &mut impl LineSink,
 ) {
     match value {
         JsonValue::Primitive(primitive) => {
-            let encoded = encode_primitive(primitive, options.delimiter);
-            out.push(indented_list_item(depth, &encoded, options.indent));
+            let encoded = encode_value(primitive, options);
+            out.push(indented_list_item(depth, &encoded, Indent::of(options)));
         }
         JsonValue::Array(items) => {
             if is_array_of_primitives(items) {
-                let line = encode_inline_array_line(items, options.delimiter, None);
-                out.push(indented_list_item(depth, &line, options.indent));
+                push_inline_array_lines(
+                    items,
+                    options,
+                    None,
+                    line_lead(depth, LIST_ITEM_PREFIX.len(), options),
+                    depth + 1,
+                    |line| indented_list_item(depth, line, Indent::of(options)),
+                    out,
+                );
             } else {
-                let header = format_header(items.len(), None, None, options.delimiter);
-                out.push(indented_list_item(depth, &header, options.indent));
+                let header = format_header(
+                    options.length_marker(items.len(), false),
+                    None,
+                    None,
+                    options.delimiter,
+                    options.quote_strings,
+                );
+                out.push(indented_list_item(depth, &header, Indent::of(options)));
                 for item in items {
-                    encode_list_item_value_lines(item, depth + 1, options, out);
+                    encode_list_item_value_lines(item, path, depth + 1, options, out);
                 }
             }
         }
         JsonValue::Object(entries) => {
-            encode_object_as_list_item_lines(entries, depth, options, out);
+            encode_object_as_list_item_lines(entries, path, depth, options, out);
         }
     }
 }
@@ -484,22 +967,49 @@ fn object_get<'a>(entries: &'a JsonObject, key: &str) -> Option<&'a JsonValue> {
     entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
 }
 
-fn indented_line(depth: usize, content: &str, indent_size: usize) -> String {
+/// One level of indentation: `options.indent` spaces, or a tab under
+/// [`IndentStyle::Tabs`].
+#[derive(Clone, Copy)]
+struct Indent {
+    fill: char,
+    width: usize,
+}
+
+impl Indent {
+    const fn of(options: &ResolvedEncodeOptions) -> Self {
+        match options.indent_style {
+            IndentStyle::Spaces => Self {
+                fill: ' ',
+                width: options.indent,
+            },
+            IndentStyle::Tabs => Self {
+                fill: '\t',
+                width: 1,
+            },
+        }
+    }
+
+    const fn chars(self, depth: usize) -> usize {
+        self.width.saturating_mul(depth)
+    }
+}
+
+fn indented_line(depth: usize, content: &str, indent: Indent) -> String {
     // Use saturating arithmetic to prevent overflow with deeply nested structures
-    let indent_chars = indent_size.saturating_mul(depth);
+    let indent_chars = indent.chars(depth);
     let capacity = indent_chars.saturating_add(content.len());
     let mut out = String::with_capacity(capacity);
     for _ in 0..indent_chars {
-        out.push(' ');
+        out.push(indent.fill);
     }
     out.push_str(content);
     out
 }
 
 /// Build "key: value" line directly without intermediate format! allocation
-fn indented_key_value_line(depth: usize, key: &str, value: &str, indent_size: usize) -> String {
+fn indented_key_value_line(depth: usize, key: &str, value: &str, indent: Indent) -> String {
     // Use saturating arithmetic to prevent overflow with deeply nested structures
-    let indent_chars = indent_size.saturating_mul(depth);
+    let indent_chars = indent.chars(depth);
     // key + ": " + value
     let capacity = indent_chars
         .saturating_add(key.len())
@@ -507,7 +1017,7 @@ fn indented_key_value_line(depth: usize, key: &str, value: &str, indent_size: us
         .saturating_add(value.len());
     let mut out = String::with_capacity(capacity);
     for _ in 0..indent_chars {
-        out.push(' ');
+        out.push(indent.fill);
     }
     out.push_str(key);
     out.push_str(": ");
@@ -516,30 +1026,30 @@ fn indented_key_value_line(depth: usize, key: &str, value: &str, indent_size: us
 }
 
 /// Build "key:" line directly without intermediate format! allocation
-fn indented_key_colon_line(depth: usize, key: &str, indent_size: usize) -> String {
+fn indented_key_colon_line(depth: usize, key: &str, indent: Indent) -> String {
     // Use saturating arithmetic to prevent overflow with deeply nested structures
-    let indent_chars = indent_size.saturating_mul(depth);
+    let indent_chars = indent.chars(depth);
     // key + ":"
     let capacity = indent_chars.saturating_add(key.len()).saturating_add(1);
     let mut out = String::with_capacity(capacity);
     for _ in 0..indent_chars {
-        out.push(' ');
+        out.push(indent.fill);
     }
     out.push_str(key);
     out.push(':');
     out
 }
 
-fn indented_list_item(depth: usize, content: &str, indent_size: usize) -> String {
+fn indented_list_item(depth: usize, content: &str, indent: Indent) -> String {
     // Use saturating arithmetic to prevent overflow with deeply nested structures
-    let indent_chars = indent_size.saturating_mul(depth);
+    let indent_chars = indent.chars(depth);
     let prefix_len = LIST_ITEM_PREFIX.len();
     let capacity = indent_chars
         .saturating_add(prefix_len)
         .saturating_add(content.len());
     let mut out = String::with_capacity(capacity);
     for _ in 0..indent_chars {
-        out.push(' ');
+        out.push(indent.fill);
     }
     out.push_str(LIST_ITEM_PREFIX);
     out.push_str(content);
@@ -547,14 +1057,9 @@ fn indented_list_item(depth: usize, content: &str, indent_size: usize) -> String
 }
 
 /// Build "- key: value" list item directly without intermediate format! allocation
-fn indented_list_item_key_value(
-    depth: usize,
-    key: &str,
-    value: &str,
-    indent_size: usize,
-) -> String {
+fn indented_list_item_key_value(depth: usize, key: &str, value: &str, indent: Indent) -> String {
     // Use saturating arithmetic to prevent overflow with deeply nested structures
-    let indent_chars = indent_size.saturating_mul(depth);
+    let indent_chars = indent.chars(depth);
     let prefix_len = LIST_ITEM_PREFIX.len();
     // "- " + key + ": " + value
     let capacity = indent_chars
@@ -564,7 +1069,7 @@ fn indented_list_item_key_value(
         .saturating_add(value.len());
     let mut out = String::with_capacity(capacity);
     for _ in 0..indent_chars {
-        out.push(' ');
+        out.push(indent.fill);
     }
     out.push_str(LIST_ITEM_PREFIX);
     out.push_str(key);
@@ -574,9 +1079,9 @@ fn indented_list_item_key_value(
 }
 
 /// Build "- key:" list item directly without intermediate format! allocation
-fn indented_list_item_key_colon(depth: usize, key: &str, indent_size: usize) -> String {
+fn indented_list_item_key_colon(depth: usize, key: &str, indent: Indent) -> String {
     // Use saturating arithmetic to prevent overflow with deeply nested structures
-    let indent_chars = indent_size.saturating_mul(depth);
+    let indent_chars = indent.chars(depth);
     let prefix_len = LIST_ITEM_PREFIX.len();
     // "- " + key + ":"
     let capacity = indent_chars
@@ -585,7 +1090,7 @@ fn indented_list_item_key_colon(depth: usize, key: &str, indent_size: usize) ->
         .saturating_add(1);
     let mut out = String::with_capacity(capacity);
     for _ in 0..indent_chars {
-        out.push(' ');
+        out.push(indent.fill);
     }
     out.push_str(LIST_ITEM_PREFIX);
     out.push_str(key);
@@ -594,14 +1099,9 @@ fn indented_list_item_key_colon(depth: usize, key: &str, indent_size: usize) ->
 }
 
 /// Build "- key[N]:" list item directly without intermediate format! allocation
-fn indented_list_item_key_header(
-    depth: usize,
-    key: &str,
-    header: &str,
-    indent_size: usize,
-) -> String {
+fn indented_list_item_key_header(depth: usize, key: &str, header: &str, indent: Indent) -> String {
     // Use saturating arithmetic to prevent overflow with deeply nested structures
-    let indent_chars = indent_size.saturating_mul(depth);
+    let indent_chars = indent.chars(depth);
     let prefix_len = LIST_ITEM_PREFIX.len();
     // "- " + key + header
     let capacity = indent_chars
@@ -610,7 +1110,7 @@ fn indented_list_item_key_header(
         .saturating_add(header.len());
     let mut out = String::with_capacity(capacity);
     for _ in 0..indent_chars {
-        out.push(' ');
+        out.push(indent.fill);
     }
     out.push_str(LIST_ITEM_PREFIX);
     out.push_str(key);
@@ -1,19 +1,42 @@
 use std::collections::HashSet;
+use std::fmt::Write;
 
 use crate::JsonValue;
-use crate::encode::normalize::{is_empty_object, is_json_object};
-use crate::options::{KeyFoldingMode, ResolvedEncodeOptions};
-use crate::shared::constants::DOT;
+use crate::encode::normalize::is_empty_object;
+use crate::options::{KeyFoldingMode, PathSegment, ResolvedEncodeOptions};
+use crate::shared::constants::{DOT, DOUBLE_QUOTE};
+use crate::shared::string_utils::escape_string;
 use crate::shared::validation::is_identifier_segment;
 
 #[derive(Debug, Clone)]
 pub struct FoldResult {
+    /// The key as written: keys joined by dots, quoted where needed under
+    /// [`KeyFoldingMode::Aggressive`], with `[0]` after a key folded through
+    /// a single-item array.
     pub folded_key: String,
+    /// The unescaped keys and array indexes folded together.
+    pub segments: Vec<PathSegment>,
     pub remainder: Option<JsonValue>,
     pub leaf_value: JsonValue,
+    /// How many keys were folded together.
     pub segment_count: usize,
 }
 
+/// Why a key was not folded into a dotted chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FoldSkip {
+    /// Folding is off, or the value does not start a chain of single-key objects.
+    NoChain,
+    /// `flatten_depth` is below two.
+    DepthLimit,
+    /// A segment, such as `a-b`, cannot be written bare (`Safe` only).
+    InvalidSegment(String),
+    /// A sibling key is already spelled like the folded key.
+    SiblingCollision(String),
+    /// A literal dotted key at the root is already spelled like the folded path.
+    RootCollision(String),
+}
+
 #[must_use]
 #[allow(clippy::implicit_hasher)]
 pub fn try_fold_key_chain(
@@ -25,40 +48,111 @@ pub fn try_fold_key_chain(
     path_prefix: Option<&str>,
     flatten_depth: usize,
 ) -> Option<FoldResult> {
-    if options.key_folding != KeyFoldingMode::Safe {
-        return None;
-    }
+    fold_key_chain(
+        key,
+        value,
+        siblings,
+        options,
+        root_literal_keys,
+        path_prefix,
+        flatten_depth,
+    )
+    .ok()
+}
 
-    if !is_json_object(value) {
-        return None;
+/// Fold `key` and its chain of single-key objects, or say why not.
+///
+/// # Errors
+///
+/// Returns the [`FoldSkip`] that prevented folding.
+#[allow(clippy::implicit_hasher)]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        name = "fold_key_chain",
+        level = "trace",
+        skip_all,
+        fields(key = key, folded = tracing::field::Empty)
+    )
+)]
+pub fn fold_key_chain(
+    key: &str,
+    value: &JsonValue,
+    siblings: &[&str],
+    options: &ResolvedEncodeOptions,
+    root_literal_keys: Option<&HashSet<String>>,
+    path_prefix: Option<&str>,
+    flatten_depth: usize,
+) -> Result<FoldResult, FoldSkip> {
+    if options.key_folding == KeyFoldingMode::Off {
+        return Err(FoldSkip::NoChain);
     }
 
+    let entries = match value {
+        JsonValue::Object(entries) => entries,
+        JsonValue::Array(items) if options.fold_single_item_arrays && items.len() == 1 => {
+            match &items[0] {
+                JsonValue::Object(entries) => entries,
+                _ => return Err(FoldSkip::NoChain),
+            }
+        }
+        _ => return Err(FoldSkip::NoChain),
+    };
+
     let effective_depth = flatten_depth;
     if effective_depth < 2 {
-        return None;
+        return Err(if entries.len() == 1 {
+            FoldSkip::DepthLimit
+        } else {
+            FoldSkip::NoChain
+        });
     }
 
-    let (segments, tail, leaf_value) = collect_single_key_chain(key, value, effective_depth);
+    let (segments, tail, leaf_value) =
+        collect_single_key_chain(key, value, effective_depth, options.fold_single_item_arrays);
+    let segment_count = segments
+        .iter()
+        .filter(|segment| matches!(segment, PathSegment::Key(_)))
+        .count();
 
-    if segments.len() < 2 {
-        return None;
+    if segment_count < 2 {
+        return Err(FoldSkip::NoChain);
     }
 
-    if !segments.iter().all(|seg| is_identifier_segment(seg)) {
-        return None;
+    let aggressive = options.key_folding == KeyFoldingMode::Aggressive;
+    if !aggressive {
+        if let Some(PathSegment::Key(segment)) = segments
+            .iter()
+            .find(|seg| matches!(seg, PathSegment::Key(key) if !is_identifier_segment(key)))
+        {
+            return Err(FoldSkip::InvalidSegment(segment.clone()));
+        }
     }
-
-    let mut folded_key =
-        String::with_capacity(segments.iter().map(String::len).sum::<usize>() + segments.len());
-    for (i, seg) in segments.iter().enumerate() {
-        if i > 0 {
-            folded_key.push(DOT);
+    let quote_all = aggressive && options.quote_strings.quotes_keys();
+
+    let mut folded_key = String::new();
+    for seg in &segments {
+        match seg {
+            PathSegment::Key(seg) => {
+                if !folded_key.is_empty() {
+                    folded_key.push(DOT);
+                }
+                if quote_all || !is_identifier_segment(seg) {
+                    folded_key.push(DOUBLE_QUOTE);
+                    folded_key.push_str(&escape_string(seg));
+                    folded_key.push(DOUBLE_QUOTE);
+                } else {
+                    folded_key.push_str(seg);
+                }
+            }
+            PathSegment::Index(index) => {
+                let _ = write!(folded_key, "[{index}]");
+            }
         }
-        folded_key.push_str(seg);
     }
 
     if siblings.iter().any(|sibling| *sibling == folded_key) {
-        return None;
+        return Err(FoldSkip::SiblingCollision(folded_key));
     }
 
     let absolute_path = path_prefix.map_or_else(
@@ -68,29 +162,43 @@ pub fn try_fold_key_chain(
 
     if let Some(root_keys) = root_literal_keys {
         if root_keys.contains(&absolute_path) {
-            return None;
+            return Err(FoldSkip::RootCollision(absolute_path));
         }
     }
 
-    Some(FoldResult {
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record("folded", folded_key.as_str());
+
+    Ok(FoldResult {
         folded_key,
+        segment_count,
+        segments,
         remainder: tail,
         leaf_value,
-        segment_count: segments.len(),
     })
 }
 
+/// Follow single-key objects from `start_key` for up to `max_depth` keys,
+/// and with `through_arrays` also arrays whose only item is a single-key
+/// object, which add an index before that item's key.
 fn collect_single_key_chain(
     start_key: &str,
     start_value: &JsonValue,
     max_depth: usize,
-) -> (Vec<String>, Option<JsonValue>, JsonValue) {
-    let mut segments = vec![start_key.to_string()];
+    through_arrays: bool,
+) -> (Vec<PathSegment>, Option<JsonValue>, JsonValue) {
+    let mut segments = vec![PathSegment::Key(start_key.to_string())];
+    let mut keys = 1;
     let mut current_value = start_value.clone();
 
-    while segments.len() < max_depth {
-        let JsonValue::Object(ref obj) = current_value else {
-            break;
+    while keys < max_depth {
+        let (index, obj) = match &current_value {
+            JsonValue::Object(obj) => (None, obj),
+            JsonValue::Array(items) if through_arrays && items.len() == 1 => match &items[0] {
+                JsonValue::Object(obj) => (Some(0), obj),
+                _ => break,
+            },
+            _ => break,
         };
 
         if obj.len() != 1 {
@@ -98,7 +206,11 @@ fn collect_single_key_chain(
         }
 
         let (next_key, next_value) = obj[0].clone();
-        segments.push(next_key);
+        if let Some(index) = index {
+            segments.push(PathSegment::Index(index));
+        }
+        segments.push(PathSegment::Key(next_key));
+        keys += 1;
         current_value = next_value;
     }
 
@@ -3,6 +3,7 @@ pub mod folding;
 pub mod normalize;
 pub mod primitives;
 pub mod replacer;
+pub mod report;
 
 #[cfg(feature = "async-stream")]
 pub mod async_encode;
@@ -10,50 +11,302 @@ pub mod async_encode;
 #[cfg(feature = "async-stream")]
 pub use async_encode::{
     AsyncEncodeEventStream, AsyncEncodeStream, encode_async, encode_events_async,
-    encode_lines_async,
+    encode_lines_async, encode_stream_async,
 };
 
-use crate::encode::normalize::normalize_json_value;
-use crate::encode::replacer::apply_replacer;
-use crate::options::{EncodeOptions, resolve_encode_options};
-use crate::shared::validation::is_valid_unquoted_key;
+use crate::encode::encoders::LineSink;
+use crate::encode::normalize::{
+    apply_non_finite_policy, normalize_json_value, sort_object_keys, validate_json_value,
+};
+use crate::encode::replacer::{apply_replacer, apply_replacer_at};
+use crate::encode::report::EncodeDecision;
+use crate::error::{Result, ToonError};
+use crate::options::{
+    EncodeOptions, NonFinitePolicy, PathSegment, ResolvedEncodeOptions, resolve_encode_options,
+};
+use crate::shared::validation::{is_supported_delimiter, is_valid_unquoted_key};
 use crate::{JsonStreamEvent, JsonValue};
 
+/// Encode a value as a TOON document.
+///
+/// Unsupported delimiters fall back to `,`; use [`try_encode`] to have
+/// them, and non-finite numbers, rejected instead.
+///
+/// # Panics
+///
+/// Panics if [`EncodeOptions::on_non_finite`] is `Error` and the value, or a
+/// [`replacer`](EncodeOptions::replacer) result, holds a non-finite number.
 pub fn encode(input: impl Into<JsonValue>, options: Option<EncodeOptions>) -> String {
-    let lines = encode_lines(input, options);
-    lines.join("\n")
+    let line_ending = options
+        .as_ref()
+        .and_then(|options| options.line_ending)
+        .unwrap_or_default();
+    encode_lines(input, options).join(line_ending.as_str())
 }
 
+/// Encode a value into TOON lines, without the final join (so without
+/// [`EncodeOptions::line_ending`]).
+///
+/// # Panics
+///
+/// Panics if [`EncodeOptions::on_non_finite`] is `Error` and the value holds
+/// a non-finite number.
 pub fn encode_lines(input: impl Into<JsonValue>, options: Option<EncodeOptions>) -> Vec<String> {
     let resolved = resolve_encode_options(options);
-    let normalized = normalize_json_value(input.into());
-    let replaced = if let Some(replacer) = &resolved.replacer {
-        apply_replacer(&normalized, replacer)
-    } else {
-        normalized
+    let value = prepare_value(input.into(), &resolved).unwrap_or_else(|err| panic!("{err}"));
+    encoders::encode_json_value(&value, &resolved)
+}
+
+/// Encode a value straight into an [`io::Write`](std::io::Write), one line
+/// at a time, instead of building the whole document as a `String`.
+///
+/// The bytes written are exactly what [`encode`] returns, with no trailing
+/// newline. Each line is a separate write, so wrap unbuffered writers such
+/// as files in a [`BufWriter`](std::io::BufWriter).
+///
+/// ```
+/// let mut out = Vec::new();
+/// toon::encode_to_writer(serde_json::json!({"ids": [1, 2]}), &mut out, None).unwrap();
+/// assert_eq!(out, b"ids[2]: 1,2");
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the writer fails, or if
+/// [`EncodeOptions::on_non_finite`] is `Error` and the value holds a
+/// non-finite number.
+pub fn encode_to_writer<W: std::io::Write>(
+    input: impl Into<JsonValue>,
+    mut writer: W,
+    options: Option<EncodeOptions>,
+) -> Result<()> {
+    let resolved = resolve_encode_options(options);
+    let value = prepare_value(input.into(), &resolved)?;
+    let mut sink = JoinedLines::new(
+        |text: &str| writer.write_all(text.as_bytes()),
+        resolved.line_ending.as_str(),
+    );
+    encoders::encode_json_value_into(&value, &resolved, &mut sink);
+    sink.finish()
+        .map_err(|err| ToonError::io("Failed to write encoded output", None, err))
+}
+
+/// Encode a value into a [`fmt::Write`](std::fmt::Write) such as a `String`
+/// or a `Formatter`, like [`encode_to_writer`].
+///
+/// ```
+/// let mut out = String::from("payload:\n");
+/// toon::encode_to_fmt(serde_json::json!({"a": 1}), &mut out, None).unwrap();
+/// assert_eq!(out, "payload:\na: 1");
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the writer fails, or if
+/// [`EncodeOptions::on_non_finite`] is `Error` and the value holds a
+/// non-finite number.
+pub fn encode_to_fmt<W: std::fmt::Write>(
+    input: impl Into<JsonValue>,
+    mut writer: W,
+    options: Option<EncodeOptions>,
+) -> Result<()> {
+    let resolved = resolve_encode_options(options);
+    let value = prepare_value(input.into(), &resolved)?;
+    let mut sink = JoinedLines::new(
+        |text: &str| writer.write_str(text),
+        resolved.line_ending.as_str(),
+    );
+    encoders::encode_json_value_into(&value, &resolved, &mut sink);
+    sink.finish()
+        .map_err(|std::fmt::Error| ToonError::message("Failed to write encoded output"))
+}
+
+/// Writes lines joined by `separator`, keeping the first error and
+/// skipping everything after it.
+struct JoinedLines<F, E> {
+    write: F,
+    separator: &'static str,
+    started: bool,
+    error: Option<E>,
+}
+
+impl<F, E> JoinedLines<F, E>
+where
+    F: FnMut(&str) -> std::result::Result<(), E>,
+{
+    const fn new(write: F, separator: &'static str) -> Self {
+        Self {
+            write,
+            separator,
+            started: false,
+            error: None,
+        }
+    }
+
+    fn finish(self) -> std::result::Result<(), E> {
+        self.error.map_or(Ok(()), Err)
+    }
+}
+
+impl<F, E> LineSink for JoinedLines<F, E>
+where
+    F: FnMut(&str) -> std::result::Result<(), E>,
+{
+    fn push(&mut self, line: String) {
+        if self.error.is_some() {
+            return;
+        }
+        let result = if self.started {
+            (self.write)(self.separator).and_then(|()| (self.write)(&line))
+        } else {
+            (self.write)(&line)
+        };
+        self.started = true;
+        if let Err(err) = result {
+            self.error = Some(err);
+        }
+    }
+}
+
+/// Encode a value, failing instead of silently altering it.
+///
+/// Unless [`EncodeOptions::on_non_finite`] says otherwise, non-finite
+/// numbers are an error naming the offending path rather than `null`.
+/// Also enforces [`EncodeOptions::max_depth`], and rejects the unsupported
+/// delimiters [`encode`] replaces with `,`. Rust strings are always valid
+/// UTF-8, so unpaired surrogates cannot reach the encoder.
+///
+/// # Errors
+///
+/// Returns an error naming an unsupported [`EncodeOptions::delimiter`] or
+/// [`EncodeOptions::delimiter_overrides`] entry, and
+/// [`ToonError::InvalidValue`](crate::error::ToonError::InvalidValue) for
+/// the first value that fails validation.
+pub fn try_encode(input: impl Into<JsonValue>, options: Option<EncodeOptions>) -> Result<String> {
+    let (value, resolved) = prepare_checked(input.into(), options)?;
+    Ok(encoders::encode_json_value(&value, &resolved).join(resolved.line_ending.as_str()))
+}
+
+/// Encode a value like [`try_encode`], also reporting per path why each
+/// representation was chosen: tables and what ruled one out, folded and
+/// unfolded key chains, and why strings needed quotes.
+///
+/// ```
+/// use toon::encode::report::EncodeDecisionKind;
+///
+/// let value = serde_json::json!({"rows": [{"id": 1}, {"id": 2, "tag": "x"}]});
+/// let (_, decisions) = toon::encode_with_report(value, None).unwrap();
+/// assert_eq!(decisions[0].kind, EncodeDecisionKind::ListArray);
+/// assert_eq!(decisions[0].to_string(), "rows: not a table: row [1] has field `tag`, unlike row [0]");
+/// ```
+///
+/// # Errors
+///
+/// Returns the same errors as [`try_encode`].
+pub fn encode_with_report(
+    input: impl Into<JsonValue>,
+    options: Option<EncodeOptions>,
+) -> Result<(String, Vec<EncodeDecision>)> {
+    let (value, resolved) = prepare_checked(input.into(), options)?;
+    let decisions = report::collect_decisions(&value, &resolved);
+    Ok((
+        encoders::encode_json_value(&value, &resolved).join(resolved.line_ending.as_str()),
+        decisions,
+    ))
+}
+
+/// Prepare a value the way [`try_encode`] does, validating it.
+fn prepare_checked(
+    input: JsonValue,
+    options: Option<EncodeOptions>,
+) -> Result<(JsonValue, ResolvedEncodeOptions)> {
+    let mut options = options.unwrap_or_default();
+    check_delimiters(&options)?;
+    options.on_non_finite.get_or_insert(NonFinitePolicy::Error);
+    let resolved = resolve_encode_options(Some(options));
+    let value = prepare_value(input, &resolved)?;
+    validate_json_value(&value, resolved.max_depth)?;
+    Ok((value, resolved))
+}
+
+fn check_delimiters(options: &EncodeOptions) -> Result<()> {
+    if let Some(delimiter) = options.delimiter.filter(|d| !is_supported_delimiter(*d)) {
+        return Err(ToonError::message(format!(
+            "Unsupported delimiter {delimiter:?}: expected ',', '\\t', '|' or ';'"
+        )));
+    }
+    let overrides = options.delimiter_overrides.iter().flatten();
+    if let Some((path, delimiter)) = overrides
+        .into_iter()
+        .find(|(_, d)| !is_supported_delimiter(*d))
+    {
+        return Err(ToonError::message(format!(
+            "Unsupported delimiter {delimiter:?} for `{path}`: expected ',', '\\t', '|' or ';'"
+        )));
+    }
+    Ok(())
+}
+
+/// Apply normalization, the replacer, the non-finite policy and key sorting,
+/// in that order, so the policy also covers values the replacer returns.
+pub(crate) fn prepare_value(
+    input: JsonValue,
+    options: &ResolvedEncodeOptions,
+) -> Result<JsonValue> {
+    let normalized = normalize_json_value(input);
+    let replaced = match &options.replacer {
+        Some(replacer) => apply_replacer(&normalized, replacer),
+        None => normalized,
     };
-    encoders::encode_json_value(&replaced, &resolved)
+    finish_prepared_value(replaced, options)
+}
+
+/// Like [`prepare_value`] for a value at `path` of a document encoded a
+/// piece at a time, so the replacer sees its full path. `None` means the
+/// replacer removed the value.
+pub(crate) fn prepare_value_at(
+    input: JsonValue,
+    path: &[PathSegment],
+    options: &ResolvedEncodeOptions,
+) -> Result<Option<JsonValue>> {
+    let normalized = normalize_json_value(input);
+    let replaced = match &options.replacer {
+        Some(replacer) => match apply_replacer_at(&normalized, replacer, path) {
+            Some(value) => value,
+            None => return Ok(None),
+        },
+        None => normalized,
+    };
+    finish_prepared_value(replaced, options).map(Some)
+}
+
+fn finish_prepared_value(value: JsonValue, options: &ResolvedEncodeOptions) -> Result<JsonValue> {
+    let mut value = apply_non_finite_policy(value, options.on_non_finite)?;
+    if options.sort_keys {
+        sort_object_keys(&mut value, options.key_comparator.as_ref());
+    }
+    Ok(value)
 }
 
 /// Encode a JSON value into a stream of events.
 ///
 /// This produces the same event sequence that `decode_stream_sync` would emit
 /// when decoding the TOON representation of this JSON value.
+///
+/// # Panics
+///
+/// Panics if [`EncodeOptions::on_non_finite`] is `Error` and the value holds
+/// a non-finite number.
 #[must_use]
 pub fn encode_stream_events(
     input: impl Into<JsonValue>,
     options: Option<EncodeOptions>,
 ) -> Vec<JsonStreamEvent> {
     let resolved = resolve_encode_options(options);
-    let normalized = normalize_json_value(input.into());
-    let replaced = if let Some(replacer) = &resolved.replacer {
-        apply_replacer(&normalized, replacer)
-    } else {
-        normalized
-    };
+    let value = prepare_value(input.into(), &resolved).unwrap_or_else(|err| panic!("{err}"));
 
     let mut events = Vec::new();
-    emit_events(&replaced, &mut events);
+    emit_events(&value, &mut events);
     events
 }
 
@@ -1,4 +1,8 @@
-use crate::{JsonArray, JsonObject, JsonPrimitive, JsonValue, StringOrNumberOrBoolOrNull};
+use std::fmt::Write;
+
+use crate::error::{Result, ToonError};
+use crate::options::{KeyComparator, NonFinitePolicy};
+use crate::{JsonArray, JsonObject, JsonPrimitive, JsonValue, Number, StringOrNumberOrBoolOrNull};
 
 pub fn normalize_json_value(value: JsonValue) -> JsonValue {
     match value {
@@ -15,17 +19,153 @@ pub fn normalize_json_value(value: JsonValue) -> JsonValue {
     }
 }
 
+/// Sort the keys of every object by `comparator`, or by byte order without
+/// one. The sort is stable, so repeated keys keep their relative order.
+pub fn sort_object_keys(value: &mut JsonValue, comparator: Option<&KeyComparator>) {
+    match value {
+        JsonValue::Primitive(_) => {}
+        JsonValue::Array(items) => {
+            for item in items {
+                sort_object_keys(item, comparator);
+            }
+        }
+        JsonValue::Object(entries) => {
+            match comparator {
+                Some(comparator) => entries.sort_by(|(a, _), (b, _)| comparator(a, b)),
+                None => entries.sort_by(|(a, _), (b, _)| a.cmp(b)),
+            }
+            for (_, item) in entries {
+                sort_object_keys(item, comparator);
+            }
+        }
+    }
+}
+
+/// Replace `NaN` and `±Infinity` according to `policy`, so the result holds
+/// only finite numbers.
+///
+/// # Errors
+///
+/// With [`NonFinitePolicy::Error`], returns [`ToonError::InvalidValue`] for
+/// the first non-finite number.
+pub fn apply_non_finite_policy(value: JsonValue, policy: NonFinitePolicy) -> Result<JsonValue> {
+    if policy == NonFinitePolicy::Error {
+        validate_json_value(&value, None)?;
+        return Ok(value);
+    }
+    Ok(replace_non_finite(value, policy))
+}
+
+fn replace_non_finite(value: JsonValue, policy: NonFinitePolicy) -> JsonValue {
+    match value {
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(Number::F64(number)))
+            if !number.is_finite() =>
+        {
+            JsonValue::Primitive(match policy {
+                NonFinitePolicy::StringLiteral if number.is_nan() => {
+                    StringOrNumberOrBoolOrNull::String("NaN".to_string())
+                }
+                NonFinitePolicy::StringLiteral if number > 0.0 => {
+                    StringOrNumberOrBoolOrNull::String("Infinity".to_string())
+                }
+                NonFinitePolicy::StringLiteral => {
+                    StringOrNumberOrBoolOrNull::String("-Infinity".to_string())
+                }
+                NonFinitePolicy::Null | NonFinitePolicy::Error => StringOrNumberOrBoolOrNull::Null,
+            })
+        }
+        JsonValue::Primitive(_) => value,
+        JsonValue::Array(items) => JsonValue::Array(
+            items
+                .into_iter()
+                .map(|item| replace_non_finite(item, policy))
+                .collect(),
+        ),
+        JsonValue::Object(entries) => JsonValue::Object(
+            entries
+                .into_iter()
+                .map(|(key, item)| (key, replace_non_finite(item, policy)))
+                .collect(),
+        ),
+    }
+}
+
+/// Check that `value` encodes without being altered: every number is finite
+/// and nesting stays within `max_depth`.
+///
+/// Errors name the offending value by path (`items[2].score`).
+///
+/// # Errors
+///
+/// Returns [`ToonError::InvalidValue`] for the first value that fails.
+pub fn validate_json_value(value: &JsonValue, max_depth: Option<usize>) -> Result<()> {
+    validate_at(value, 0, max_depth, &mut String::new())
+}
+
+fn validate_at(
+    value: &JsonValue,
+    depth: usize,
+    max_depth: Option<usize>,
+    path: &mut String,
+) -> Result<()> {
+    let display_path = |path: &str| {
+        if path.is_empty() {
+            "(root)".to_string()
+        } else {
+            path.to_string()
+        }
+    };
+
+    match value {
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(number)) if !number.is_finite() => {
+            Err(ToonError::invalid_value(
+                display_path(path),
+                format!("{number} is not a finite number"),
+            ))
+        }
+        JsonValue::Primitive(_) => Ok(()),
+        JsonValue::Array(_) | JsonValue::Object(_)
+            if max_depth.is_some_and(|max_depth| depth >= max_depth) =>
+        {
+            Err(ToonError::invalid_value(
+                display_path(path),
+                format!(
+                    "nesting exceeds the maximum depth of {}",
+                    max_depth.unwrap_or(0)
+                ),
+            ))
+        }
+        JsonValue::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                let len = path.len();
+                let _ = write!(path, "[{index}]");
+                validate_at(item, depth + 1, max_depth, path)?;
+                path.truncate(len);
+            }
+            Ok(())
+        }
+        JsonValue::Object(entries) => {
+            for (key, item) in entries {
+                let len = path.len();
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+                validate_at(item, depth + 1, max_depth, path)?;
+                path.truncate(len);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Fold `-0.0` into `0.0`. Non-finite numbers are left for the
+/// [`NonFinitePolicy`](crate::options::NonFinitePolicy) to replace.
 #[must_use]
 pub fn normalize_primitive(value: JsonPrimitive) -> JsonPrimitive {
     match value {
-        StringOrNumberOrBoolOrNull::Number(value) => {
-            if !value.is_finite() {
-                StringOrNumberOrBoolOrNull::Null
-            } else if value == 0.0 {
-                StringOrNumberOrBoolOrNull::Number(0.0)
-            } else {
-                StringOrNumberOrBoolOrNull::Number(value)
-            }
+        StringOrNumberOrBoolOrNull::Number(Number::F64(value)) if value.is_finite() => {
+            StringOrNumberOrBoolOrNull::from_f64(value)
         }
         _ => value,
     }
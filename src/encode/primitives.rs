@@ -1,17 +1,20 @@
 use std::fmt::Write;
 
 use crate::JsonPrimitive;
+use crate::Number;
 use crate::StringOrNumberOrBoolOrNull;
+use crate::options::QuotePolicy;
 use crate::shared::constants::{DEFAULT_DELIMITER, DOUBLE_QUOTE};
 use crate::shared::string_utils::escape_string;
 use crate::shared::validation::{is_safe_unquoted, is_valid_unquoted_key};
 
 #[must_use]
-pub fn encode_primitive(value: &JsonPrimitive, delimiter: char) -> String {
+pub fn encode_primitive(value: &JsonPrimitive, delimiter: char, quote: QuotePolicy) -> String {
     match value {
         StringOrNumberOrBoolOrNull::Null => "null".to_string(),
         StringOrNumberOrBoolOrNull::Bool(value) => value.to_string(),
-        StringOrNumberOrBoolOrNull::Number(value) => format_number(*value),
+        StringOrNumberOrBoolOrNull::Number(value) => format_number(value),
+        StringOrNumberOrBoolOrNull::String(value) if quote.quotes_strings() => quoted(value),
         StringOrNumberOrBoolOrNull::String(value) => encode_string_literal(value, delimiter),
     }
 }
@@ -21,19 +24,27 @@ pub fn encode_string_literal(value: &str, delimiter: char) -> String {
     if is_safe_unquoted(value, delimiter) {
         return value.to_string();
     }
-    format!("{DOUBLE_QUOTE}{}{DOUBLE_QUOTE}", escape_string(value))
+    quoted(value)
 }
 
 #[must_use]
-pub fn encode_key(key: &str) -> String {
-    if is_valid_unquoted_key(key) {
+pub fn encode_key(key: &str, quote: QuotePolicy) -> String {
+    if is_valid_unquoted_key(key) && !quote.quotes_keys() {
         return key.to_string();
     }
-    format!("{DOUBLE_QUOTE}{}{DOUBLE_QUOTE}", escape_string(key))
+    quoted(key)
+}
+
+fn quoted(text: &str) -> String {
+    format!("{DOUBLE_QUOTE}{}{DOUBLE_QUOTE}", escape_string(text))
 }
 
 #[must_use]
-pub fn encode_and_join_primitives(values: &[JsonPrimitive], delimiter: char) -> String {
+pub fn encode_and_join_primitives(
+    values: &[JsonPrimitive],
+    delimiter: char,
+    quote: QuotePolicy,
+) -> String {
     if values.is_empty() {
         return String::new();
     }
@@ -43,29 +54,33 @@ pub fn encode_and_join_primitives(values: &[JsonPrimitive], delimiter: char) ->
         if idx > 0 {
             out.push(delimiter);
         }
-        out.push_str(&encode_primitive(value, delimiter));
+        out.push_str(&encode_primitive(value, delimiter, quote));
     }
     out
 }
 
 #[must_use]
 pub fn format_header(
-    length: usize,
+    length: Option<usize>,
     key: Option<&str>,
     fields: Option<&[String]>,
     delimiter: char,
+    quote: QuotePolicy,
 ) -> String {
     let mut header = String::new();
 
     if let Some(key) = key {
-        header.push_str(&encode_key(key));
+        header.push_str(&encode_key(key, quote));
     }
 
-    if delimiter == DEFAULT_DELIMITER {
-        let _ = write!(header, "[{length}]");
-    } else {
-        let _ = write!(header, "[{length}{delimiter}]");
+    header.push('[');
+    if let Some(length) = length {
+        let _ = write!(header, "{length}");
+    }
+    if delimiter != DEFAULT_DELIMITER {
+        header.push(delimiter);
     }
+    header.push(']');
 
     if let Some(fields) = fields {
         header.push('{');
@@ -73,7 +88,7 @@ pub fn format_header(
             if idx > 0 {
                 header.push(delimiter);
             }
-            header.push_str(&encode_key(field));
+            header.push_str(&encode_key(field, quote));
         }
         header.push('}');
     }
@@ -82,11 +97,8 @@ pub fn format_header(
     header
 }
 
-fn format_number(value: f64) -> String {
-    if value == 0.0 {
-        return "0".to_string();
-    }
-    if value.is_nan() || !value.is_finite() {
+fn format_number(value: &Number) -> String {
+    if !value.is_finite() {
         return "null".to_string();
     }
     value.to_string()
@@ -12,6 +12,27 @@ pub fn apply_replacer(root: &JsonValue, replacer: &EncodeReplacer) -> JsonValue
     transform_children(root.clone(), replacer, &[])
 }
 
+/// Apply the replacer to `value` found at the non-empty `path` of a larger
+/// document, as if it were reached while walking that document. `None`
+/// means the replacer removed it.
+pub fn apply_replacer_at(
+    value: &JsonValue,
+    replacer: &EncodeReplacer,
+    path: &[PathSegment],
+) -> Option<JsonValue> {
+    let key = match path.last() {
+        Some(PathSegment::Key(key)) => key.clone(),
+        Some(PathSegment::Index(index)) => index.to_string(),
+        None => String::new(),
+    };
+    let replacement = replacer(&key, value, path)?;
+    Some(transform_children(
+        normalize_json_value(replacement),
+        replacer,
+        path,
+    ))
+}
+
 fn transform_children(
     value: JsonValue,
     replacer: &EncodeReplacer,
@@ -0,0 +1,603 @@
+//! Why the encoder chose each representation, for
+//! [`encode_with_report`](crate::encode::encode_with_report).
+//!
+//! The walk mirrors the encoder's own decisions (tables, folding, quoting)
+//! so the report names the data paths a user would restructure.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::encode::encoders::{TableRejection, array_options, tabular_layout};
+use crate::encode::folding::{FoldSkip, fold_key_chain};
+use crate::encode::normalize::{
+    is_array_of_arrays, is_array_of_objects, is_array_of_primitives, is_empty_object,
+};
+use crate::options::{KeyFoldingMode, PathSegment, ResolvedEncodeOptions};
+use crate::shared::constants::DOT;
+use crate::shared::literal_utils::is_numeric_like;
+use crate::shared::validation::{is_identifier_segment, is_valid_unquoted_key, unquoted_rejection};
+use crate::{JsonArray, JsonPrimitive, JsonValue, StringOrNumberOrBoolOrNull};
+
+/// One encoder decision and the reason behind it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodeDecision {
+    /// Path of the value, such as `users[2].email`; empty for the root.
+    pub path: String,
+    pub kind: EncodeDecisionKind,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodeDecisionKind {
+    /// An array of primitives written on its header line.
+    InlineArray,
+    /// An array of objects written as a table.
+    Table,
+    /// An array written as `- ` items; the message says what ruled out a table.
+    ListArray,
+    /// A chain of single-key objects written as one dotted key.
+    Folded,
+    /// A chain of single-key objects left unfolded.
+    FoldSkipped,
+    /// A string value or key written in quotes.
+    Quoted,
+}
+
+impl fmt::Display for EncodeDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_empty() {
+            "(root)"
+        } else {
+            &self.path
+        };
+        write!(f, "{path}: {}", self.message)
+    }
+}
+
+/// Decisions the encoder makes for `value`, in document order.
+#[must_use]
+pub fn collect_decisions(
+    value: &JsonValue,
+    options: &ResolvedEncodeOptions,
+) -> Vec<EncodeDecision> {
+    let mut report = Report::default();
+    match value {
+        JsonValue::Primitive(primitive) => report.primitive("", primitive, options),
+        JsonValue::Array(items) => report.array("", None, items, options),
+        JsonValue::Object(entries) => {
+            let root_literal_keys: HashSet<String> = entries
+                .iter()
+                .filter(|(key, _)| key.contains(DOT))
+                .map(|(key, _)| key.clone())
+                .collect();
+            report.object(
+                "",
+                entries,
+                options,
+                Some(&root_literal_keys),
+                None,
+                options.flatten_depth,
+            );
+        }
+    }
+    report.decisions
+}
+
+#[derive(Default)]
+struct Report {
+    decisions: Vec<EncodeDecision>,
+}
+
+impl Report {
+    fn push(&mut self, path: &str, kind: EncodeDecisionKind, message: String) {
+        self.decisions.push(EncodeDecision {
+            path: path.to_string(),
+            kind,
+            message,
+        });
+    }
+
+    fn object(
+        &mut self,
+        path: &str,
+        entries: &[(String, JsonValue)],
+        options: &ResolvedEncodeOptions,
+        root_literal_keys: Option<&HashSet<String>>,
+        fold_prefix: Option<&str>,
+        flatten_depth: usize,
+    ) {
+        let siblings: Vec<&str> = entries.iter().map(|(key, _)| key.as_str()).collect();
+        for (key, value) in entries {
+            self.field(
+                path,
+                key,
+                value,
+                options,
+                &siblings,
+                root_literal_keys,
+                fold_prefix,
+                flatten_depth,
+            );
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn field(
+        &mut self,
+        path: &str,
+        key: &str,
+        value: &JsonValue,
+        options: &ResolvedEncodeOptions,
+        siblings: &[&str],
+        root_literal_keys: Option<&HashSet<String>>,
+        fold_prefix: Option<&str>,
+        flatten_depth: usize,
+    ) {
+        let field_path = key_path(path, key);
+        let fold_path = fold_prefix.map_or_else(|| key.to_string(), |prefix| key_path(prefix, key));
+
+        match fold_key_chain(
+            key,
+            value,
+            siblings,
+            options,
+            root_literal_keys,
+            fold_prefix,
+            flatten_depth,
+        ) {
+            Ok(folded) => {
+                self.push(
+                    &field_path,
+                    EncodeDecisionKind::Folded,
+                    format!(
+                        "{} single-key objects folded into `{}`",
+                        folded.segment_count, folded.folded_key
+                    ),
+                );
+                let leaf_path =
+                    folded
+                        .segments
+                        .iter()
+                        .skip(1)
+                        .fold(field_path.clone(), |path, segment| match segment {
+                            PathSegment::Key(key) => key_path(&path, key),
+                            PathSegment::Index(index) => index_path(&path, *index),
+                        });
+                let folded_path = fold_prefix.map_or_else(
+                    || folded.folded_key.clone(),
+                    |prefix| key_path(prefix, &folded.folded_key),
+                );
+                match (&folded.remainder, &folded.leaf_value) {
+                    (None, JsonValue::Primitive(primitive)) => {
+                        self.primitive(&leaf_path, primitive, options);
+                        return;
+                    }
+                    (None, JsonValue::Array(items)) => {
+                        self.array(&leaf_path, Some(&folded_path), items, options);
+                        return;
+                    }
+                    (None, JsonValue::Object(entries)) if is_empty_object(entries) => return,
+                    (Some(JsonValue::Object(entries)), _) => {
+                        self.object(
+                            &leaf_path,
+                            entries,
+                            options,
+                            root_literal_keys,
+                            Some(&folded_path),
+                            flatten_depth.saturating_sub(folded.segment_count),
+                        );
+                        return;
+                    }
+                    _ => {}
+                }
+            }
+            Err(FoldSkip::NoChain) => {}
+            Err(skip) => self.push(
+                &field_path,
+                EncodeDecisionKind::FoldSkipped,
+                fold_skip_message(&skip),
+            ),
+        }
+
+        self.key(&field_path, key, true, options);
+        match value {
+            JsonValue::Primitive(primitive) => self.primitive(&field_path, primitive, options),
+            JsonValue::Array(items) => self.array(&field_path, Some(&fold_path), items, options),
+            JsonValue::Object(entries) => {
+                if !is_empty_object(entries) {
+                    self.object(
+                        &field_path,
+                        entries,
+                        options,
+                        root_literal_keys,
+                        Some(&fold_path),
+                        flatten_depth,
+                    );
+                }
+            }
+        }
+    }
+
+    /// The array at `path`, whose delimiter override is looked up by
+    /// `fold_path`, the path as written with folded keys.
+    fn array(
+        &mut self,
+        path: &str,
+        fold_path: Option<&str>,
+        items: &JsonArray,
+        options: &ResolvedEncodeOptions,
+    ) {
+        if items.is_empty() {
+            return;
+        }
+
+        let own = array_options(fold_path, options);
+        if is_array_of_primitives(items) {
+            self.inline(path, items, &own);
+            return;
+        }
+
+        let all_primitive_arrays = items.iter().all(|item| match item {
+            JsonValue::Array(inner) => is_array_of_primitives(inner),
+            _ => false,
+        });
+        if is_array_of_arrays(items) && all_primitive_arrays {
+            self.push(
+                path,
+                EncodeDecisionKind::ListArray,
+                "arrays of primitives, each written inline as a list item".to_string(),
+            );
+            for (index, item) in items.iter().enumerate() {
+                if let JsonValue::Array(inner) = item {
+                    self.inline(&index_path(path, index), inner, options);
+                }
+            }
+            return;
+        }
+
+        if is_array_of_objects(items) {
+            match tabular_layout(items, options) {
+                Ok(header) => self.table(path, items, &header, &own),
+                Err(rejection) => {
+                    self.push(
+                        path,
+                        EncodeDecisionKind::ListArray,
+                        format!(
+                            "not a table: {}",
+                            rejection_message(items, rejection, options)
+                        ),
+                    );
+                    self.list_items(path, fold_path, items, options);
+                }
+            }
+            return;
+        }
+
+        self.push(
+            path,
+            EncodeDecisionKind::ListArray,
+            format!("not a table or inline array: {}", mix_message(items)),
+        );
+        self.list_items(path, fold_path, items, options);
+    }
+
+    fn inline(&mut self, path: &str, items: &JsonArray, options: &ResolvedEncodeOptions) {
+        if items.is_empty() {
+            return;
+        }
+        self.push(
+            path,
+            EncodeDecisionKind::InlineArray,
+            format!("inline array of {} primitives", items.len()),
+        );
+        for (index, item) in items.iter().enumerate() {
+            if let JsonValue::Primitive(primitive) = item {
+                self.primitive(&index_path(path, index), primitive, options);
+            }
+        }
+    }
+
+    fn table(
+        &mut self,
+        path: &str,
+        rows: &[JsonValue],
+        header: &[String],
+        options: &ResolvedEncodeOptions,
+    ) {
+        self.push(
+            path,
+            EncodeDecisionKind::Table,
+            format!(
+                "table of {} rows x {} columns: every row has the same primitive fields",
+                rows.len(),
+                header.len()
+            ),
+        );
+        for field in header {
+            self.key(
+                &key_path(&index_path(path, 0), field),
+                field,
+                false,
+                options,
+            );
+        }
+        for (index, row) in rows.iter().enumerate() {
+            let JsonValue::Object(entries) = row else {
+                continue;
+            };
+            let row_path = index_path(path, index);
+            for (key, value) in entries {
+                if let JsonValue::Primitive(primitive) = value {
+                    self.primitive(&key_path(&row_path, key), primitive, options);
+                }
+            }
+        }
+    }
+
+    fn list_items(
+        &mut self,
+        path: &str,
+        fold_path: Option<&str>,
+        items: &JsonArray,
+        options: &ResolvedEncodeOptions,
+    ) {
+        for (index, item) in items.iter().enumerate() {
+            self.list_item(&index_path(path, index), fold_path, item, options);
+        }
+    }
+
+    fn list_item(
+        &mut self,
+        path: &str,
+        fold_path: Option<&str>,
+        item: &JsonValue,
+        options: &ResolvedEncodeOptions,
+    ) {
+        match item {
+            JsonValue::Primitive(primitive) => self.primitive(path, primitive, options),
+            JsonValue::Array(items) => self.nested_array(path, fold_path, items, options, options),
+            JsonValue::Object(entries) => self.object_item(path, fold_path, entries, options),
+        }
+    }
+
+    /// An array inside a list item, which is never written as a table;
+    /// `own` holds the options for the array itself.
+    fn nested_array(
+        &mut self,
+        path: &str,
+        fold_path: Option<&str>,
+        items: &JsonArray,
+        own: &ResolvedEncodeOptions,
+        options: &ResolvedEncodeOptions,
+    ) {
+        if is_array_of_primitives(items) {
+            self.inline(path, items, own);
+            return;
+        }
+        self.push(
+            path,
+            EncodeDecisionKind::ListArray,
+            "nested directly in a list item, so written as a list".to_string(),
+        );
+        self.list_items(path, fold_path, items, options);
+    }
+
+    fn object_item(
+        &mut self,
+        path: &str,
+        fold_path: Option<&str>,
+        entries: &[(String, JsonValue)],
+        options: &ResolvedEncodeOptions,
+    ) {
+        let Some(((first_key, first_value), rest)) = entries.split_first() else {
+            return;
+        };
+        let first_path = key_path(path, first_key);
+        let first_fold_path =
+            fold_path.map_or_else(|| first_key.clone(), |prefix| key_path(prefix, first_key));
+        let own = array_options(Some(&first_fold_path), options);
+
+        self.key(&first_path, first_key, true, options);
+        match first_value {
+            JsonValue::Primitive(primitive) => self.primitive(&first_path, primitive, options),
+            JsonValue::Array(items) if !items.is_empty() && is_array_of_objects(items) => {
+                match tabular_layout(items, options) {
+                    Ok(header) => self.table(&first_path, items, &header, &own),
+                    Err(rejection) => {
+                        self.push(
+                            &first_path,
+                            EncodeDecisionKind::ListArray,
+                            format!(
+                                "not a table: {}",
+                                rejection_message(items, rejection, options)
+                            ),
+                        );
+                        self.list_items(&first_path, Some(&first_fold_path), items, options);
+                    }
+                }
+            }
+            JsonValue::Array(items) => {
+                self.nested_array(&first_path, Some(&first_fold_path), items, &own, options);
+            }
+            JsonValue::Object(entries) => {
+                if !is_empty_object(entries) {
+                    self.object(
+                        &first_path,
+                        entries,
+                        options,
+                        None,
+                        Some(&first_fold_path),
+                        options.flatten_depth,
+                    );
+                }
+            }
+        }
+
+        if !rest.is_empty() {
+            self.object(path, rest, options, None, fold_path, options.flatten_depth);
+        }
+    }
+
+    /// Report `key` if it is written quoted; table field names (`object_key`
+    /// false) are never expanded as paths.
+    fn key(&mut self, path: &str, key: &str, object_key: bool, options: &ResolvedEncodeOptions) {
+        let reason = if !is_valid_unquoted_key(key) {
+            "not a valid bare key"
+        } else if object_key
+            && options.key_folding == KeyFoldingMode::Aggressive
+            && !is_identifier_segment(key)
+        {
+            "aggressive folding quotes keys holding more than one segment"
+        } else if options.quote_strings.quotes_keys() {
+            "the quote policy quotes every key"
+        } else {
+            return;
+        };
+        self.push(
+            path,
+            EncodeDecisionKind::Quoted,
+            format!("key quoted: {reason}"),
+        );
+    }
+
+    fn primitive(
+        &mut self,
+        path: &str,
+        primitive: &JsonPrimitive,
+        options: &ResolvedEncodeOptions,
+    ) {
+        let StringOrNumberOrBoolOrNull::String(text) = primitive else {
+            return;
+        };
+        let reason = match unquoted_rejection(text, options.delimiter) {
+            Some(_) if has_leading_zero(text) => "has a leading zero, so it looks like a number",
+            Some(reason) => reason,
+            None if options.quote_strings.quotes_strings() => {
+                "the quote policy quotes every string"
+            }
+            None => return,
+        };
+        self.push(
+            path,
+            EncodeDecisionKind::Quoted,
+            format!("string quoted: {reason}"),
+        );
+    }
+}
+
+fn key_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}{DOT}{key}")
+    }
+}
+
+fn index_path(path: &str, index: usize) -> String {
+    format!("{path}[{index}]")
+}
+
+fn has_leading_zero(text: &str) -> bool {
+    let digits = text.strip_prefix('-').unwrap_or(text).as_bytes();
+    digits.len() > 1 && digits[0] == b'0' && digits[1].is_ascii_digit() && is_numeric_like(text)
+}
+
+fn fold_skip_message(skip: &FoldSkip) -> String {
+    match skip {
+        FoldSkip::NoChain => "no chain of single-key objects".to_string(),
+        FoldSkip::DepthLimit => "not folded: flatten_depth is below 2".to_string(),
+        FoldSkip::InvalidSegment(segment) => {
+            format!("not folded: segment `{segment}` is not a bare identifier")
+        }
+        FoldSkip::SiblingCollision(folded) => {
+            format!("not folded: sibling key `{folded}` already exists")
+        }
+        FoldSkip::RootCollision(path) => {
+            format!("not folded: literal root key `{path}` already exists")
+        }
+    }
+}
+
+fn rejection_message(
+    rows: &[JsonValue],
+    rejection: TableRejection,
+    options: &ResolvedEncodeOptions,
+) -> String {
+    let first = match rows.first() {
+        Some(JsonValue::Object(entries)) => entries.as_slice(),
+        _ => &[],
+    };
+    let row = |index: usize| match rows.get(index) {
+        Some(JsonValue::Object(entries)) => entries.as_slice(),
+        _ => &[],
+    };
+    let has = |entries: &[(String, JsonValue)], key: &str| entries.iter().any(|(k, _)| k == key);
+
+    match rejection {
+        TableRejection::EmptyFirstRow => "the first row has no fields".to_string(),
+        TableRejection::NotAnObject(index) => format!("item [{index}] is not an object"),
+        TableRejection::DifferentKeys(index) => {
+            let entries = row(index);
+            if let Some((missing, _)) = first.iter().find(|(key, _)| !has(entries, key)) {
+                format!("row [{index}] has no field `{missing}`, unlike row [0]")
+            } else if let Some((extra, _)) = entries.iter().find(|(key, _)| !has(first, key)) {
+                format!("row [{index}] has field `{extra}`, unlike row [0]")
+            } else {
+                format!("row [{index}] has different fields than row [0]")
+            }
+        }
+        TableRejection::NestedValue(index) => row(index)
+            .iter()
+            .find(|(_, value)| !matches!(value, JsonValue::Primitive(_)))
+            .map_or_else(
+                || format!("row [{index}] holds a nested value"),
+                |(key, value)| {
+                    let kind = if matches!(value, JsonValue::Array(_)) {
+                        "an array"
+                    } else {
+                        "an object"
+                    };
+                    format!("row [{index}] field `{key}` holds {kind}")
+                },
+            ),
+        TableRejection::TooFewRows(count) => format!(
+            "{count} rows, fewer than tabular_min_rows ({})",
+            options.tabular_min_rows
+        ),
+        TableRejection::TooManyColumns(count) => format!(
+            "{count} columns, more than tabular_max_columns ({})",
+            options.tabular_max_columns
+        ),
+        TableRejection::Disabled => "tables are disabled".to_string(),
+    }
+}
+
+fn mix_message(items: &JsonArray) -> String {
+    let mut kinds = Vec::new();
+    for (name, present) in [
+        (
+            "primitives",
+            items
+                .iter()
+                .any(|item| matches!(item, JsonValue::Primitive(_))),
+        ),
+        (
+            "arrays",
+            items.iter().any(|item| matches!(item, JsonValue::Array(_))),
+        ),
+        (
+            "objects",
+            items
+                .iter()
+                .any(|item| matches!(item, JsonValue::Object(_))),
+        ),
+    ] {
+        if present {
+            kinds.push(name);
+        }
+    }
+    match kinds.as_slice() {
+        [only] => format!("items are {only} holding nested values"),
+        _ => format!("items mix {}", kinds.join(" and ")),
+    }
+}
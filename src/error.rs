@@ -1,3 +1,5 @@
+use std::fmt;
+use std::ops::Range;
 use std::path::PathBuf;
 use thiserror::Error;
 
@@ -12,6 +14,10 @@ pub enum ToonError {
     #[error("Validation error at line {line}: {message}")]
     Validation { line: usize, message: String },
 
+    /// Malformed TOON input, with its position in the source
+    #[error("{0}")]
+    Decode(Box<DecodeError>),
+
     /// Event stream processing error
     #[error("Event stream error: {message}")]
     EventStream { message: String },
@@ -20,6 +26,22 @@ pub enum ToonError {
     #[error("Path expansion error for '{path}': {message}")]
     PathExpansion { path: String, message: String },
 
+    /// Value that cannot be encoded faithfully
+    #[error("Invalid value at '{path}': {message}")]
+    InvalidValue { path: String, message: String },
+
+    /// JSON Patch operation that could not be parsed or applied
+    #[error("Patch operation {index} failed at '{path}': {message}")]
+    Patch {
+        index: usize,
+        path: String,
+        message: String,
+    },
+
+    /// `JSONPath` expression that could not be parsed
+    #[error("Invalid query at offset {offset}: {message}")]
+    Query { offset: usize, message: String },
+
     /// I/O error with operation context
     #[error("{operation}{}: {source}", path.as_ref().map(|p| format!(" '{}'", p.display())).unwrap_or_default())]
     Io {
@@ -40,6 +62,129 @@ pub enum ToonError {
 
 pub type Result<T> = std::result::Result<T, ToonError>;
 
+/// The cause of a [`DecodeError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DecodeErrorKind {
+    /// A quoted string or key has no closing quote.
+    UnterminatedString,
+    /// A backslash escape other than `\\`, `\"`, `\n`, `\r` or `\t`.
+    InvalidEscape,
+    /// Text follows the closing quote of a string.
+    TrailingCharacters,
+    /// A key is not followed by a colon.
+    MissingColon,
+    /// An array header without a valid length.
+    InvalidLength,
+    /// An array holds more or fewer items, rows or row values than declared.
+    CountMismatch,
+    /// A blank line inside an array in strict mode.
+    BlankLine,
+    /// Indentation with tabs, or not a multiple of the indent size.
+    Indentation,
+    /// A line over `max_line_length` bytes.
+    LineTooLong,
+    /// A line that is not valid UTF-8.
+    InvalidUtf8,
+    /// A key repeated in one object while duplicates are rejected.
+    DuplicateKey,
+    /// The document goes past one of its [`DecodeLimits`](crate::DecodeLimits).
+    LimitExceeded,
+    /// Lines that do not fit the document structure.
+    Structure,
+}
+
+/// A decode failure located in the source, for editors and tools that
+/// point at the offending text.
+///
+/// Displays as `Line 3, column 7: Missing colon after key`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    pub kind: DecodeErrorKind,
+    /// 1-based line number, or 0 when the error has no single line.
+    pub line: usize,
+    /// Byte range of the offending text within `source_line`.
+    pub columns: Range<usize>,
+    /// The offending line as read, or empty when it is not available.
+    pub source_line: String,
+    pub message: String,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            return f.write_str(&self.message);
+        }
+        write!(
+            f,
+            "Line {}, column {}: {}",
+            self.line,
+            self.columns.start + 1,
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl DecodeError {
+    /// Point `columns` at the offending text of `source_line`, if not set.
+    fn locate(&mut self) {
+        if !self.columns.is_empty() {
+            return;
+        }
+        let line = self.source_line.as_str();
+        let start = line.len() - line.trim_start().len();
+        let end = line.trim_end().len().max(start);
+        self.columns = match self.kind {
+            DecodeErrorKind::Indentation => 0..start,
+            DecodeErrorKind::UnterminatedString => {
+                unclosed_quote(line).map_or(start..end, |quote| quote..end)
+            }
+            DecodeErrorKind::InvalidEscape => invalid_escape(line).unwrap_or(start..end),
+            DecodeErrorKind::InvalidLength => line
+                .find('[')
+                .and_then(|open| Some(open..open + line[open..].find(']')? + 1))
+                .unwrap_or(start..end),
+            _ => start..end,
+        };
+    }
+}
+
+/// Position of the opening quote of a string left open at the end of `line`.
+fn unclosed_quote(line: &str) -> Option<usize> {
+    let mut open = None;
+    let mut escaped = false;
+    for (index, byte) in line.bytes().enumerate() {
+        match (open, byte) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), b'\\') => escaped = true,
+            (Some(_), b'"') => open = None,
+            (None, b'"') => open = Some(index),
+            _ => {}
+        }
+    }
+    open
+}
+
+/// Range of the first escape in a quoted string that is not one of
+/// `\\`, `\"`, `\n`, `\r` or `\t`.
+fn invalid_escape(line: &str) -> Option<Range<usize>> {
+    let mut in_string = false;
+    let mut chars = line.char_indices();
+    while let Some((index, ch)) = chars.next() {
+        match ch {
+            '"' => in_string = !in_string,
+            '\\' if in_string => match chars.next() {
+                Some((_, '\\' | '"' | 'n' | 'r' | 't')) => {}
+                Some((next, escaped)) => return Some(index..next + escaped.len_utf8()),
+                None => return Some(index..index + 1),
+            },
+            _ => {}
+        }
+    }
+    None
+}
+
 impl ToonError {
     // =========================================================================
     // Backward-compatible constructor (preserves existing API)
@@ -66,22 +211,83 @@ impl ToonError {
         }
     }
 
-    /// Create a parse error for unterminated string.
+    // =========================================================================
+    // Decode error constructors
+    // =========================================================================
+
+    /// Create a decode error not yet tied to a line; the decoder fills in
+    /// the line and source text.
+    #[must_use]
+    pub fn decode(kind: DecodeErrorKind, message: impl Into<String>) -> Self {
+        Self::Decode(Box::new(DecodeError {
+            kind,
+            line: 0,
+            columns: 0..0,
+            source_line: String::new(),
+            message: message.into(),
+        }))
+    }
+
+    /// Create a decode error for unterminated string.
+    #[must_use]
+    pub fn unterminated_string() -> Self {
+        Self::decode(
+            DecodeErrorKind::UnterminatedString,
+            "Unterminated string: missing closing quote",
+        )
+    }
+
+    /// Create a decode error for missing colon after key.
+    #[must_use]
+    pub fn missing_colon() -> Self {
+        Self::decode(DecodeErrorKind::MissingColon, "Missing colon after key")
+    }
+
+    /// Create a decode error for invalid array length.
+    #[must_use]
+    pub fn invalid_array_length(value: &str) -> Self {
+        Self::decode(
+            DecodeErrorKind::InvalidLength,
+            format!("Invalid array length: {value}"),
+        )
+    }
+
+    /// The location details of a decode error.
     #[must_use]
-    pub fn unterminated_string(line: usize) -> Self {
-        Self::parse(line, "Unterminated string: missing closing quote")
+    pub fn decode_error(&self) -> Option<&DecodeError> {
+        match self {
+            Self::Decode(err) => Some(err),
+            _ => None,
+        }
     }
 
-    /// Create a parse error for missing colon after key.
+    /// Tie a decode error raised without a line to `line`.
     #[must_use]
-    pub fn missing_colon(line: usize) -> Self {
-        Self::parse(line, "Missing colon after key")
+    pub(crate) fn at_line(mut self, line: usize) -> Self {
+        if let Self::Decode(err) = &mut self {
+            if err.line == 0 {
+                err.line = line;
+            }
+        }
+        self
     }
 
-    /// Create a parse error for invalid array length.
+    /// Fill in the source text of a decode error from `source_line`, which
+    /// maps a line number to its text, and locate its columns.
     #[must_use]
-    pub fn invalid_array_length(line: usize, value: &str) -> Self {
-        Self::parse(line, format!("Invalid array length: {value}"))
+    pub(crate) fn with_source<'a>(
+        mut self,
+        source_line: impl FnOnce(usize) -> Option<&'a str>,
+    ) -> Self {
+        if let Self::Decode(err) = &mut self {
+            if err.line > 0 && err.source_line.is_empty() {
+                if let Some(text) = source_line(err.line) {
+                    err.source_line = text.to_string();
+                    err.locate();
+                }
+            }
+        }
+        self
     }
 
     // =========================================================================
@@ -97,19 +303,59 @@ impl ToonError {
         }
     }
 
-    /// Create a validation error for tabs in indentation.
+    /// Create a decode error for a line that is not valid UTF-8, spanning
+    /// the first invalid sequence of its lossy text.
+    #[must_use]
+    pub fn invalid_utf8(line: usize, err: &std::string::FromUtf8Error) -> Self {
+        let start = err.utf8_error().valid_up_to();
+        Self::Decode(Box::new(DecodeError {
+            kind: DecodeErrorKind::InvalidUtf8,
+            line,
+            columns: start..start + char::REPLACEMENT_CHARACTER.len_utf8(),
+            source_line: String::from_utf8_lossy(err.as_bytes()).into_owned(),
+            message: "Line is not valid UTF-8".to_string(),
+        }))
+    }
+
+    /// Create a decode error for tabs in indentation.
     #[must_use]
     pub fn tabs_not_allowed(line: usize) -> Self {
-        Self::validation(line, "Tabs are not allowed in indentation in strict mode")
+        Self::decode(
+            DecodeErrorKind::Indentation,
+            "Tabs are not allowed in indentation in strict mode",
+        )
+        .at_line(line)
     }
 
-    /// Create a validation error for incorrect indentation.
+    /// Create a decode error for incorrect indentation.
     #[must_use]
     pub fn invalid_indentation(line: usize, expected: usize, found: usize) -> Self {
-        Self::validation(
-            line,
+        Self::decode(
+            DecodeErrorKind::Indentation,
             format!("Indentation must be exact multiple of {expected}, but found {found} spaces"),
         )
+        .at_line(line)
+    }
+
+    /// Create a decode error for a line over the configured length limit,
+    /// spanning the bytes past the limit.
+    #[must_use]
+    pub fn line_too_long(line: usize, limit: usize, found: usize) -> Self {
+        let mut err = Self::decode(
+            DecodeErrorKind::LineTooLong,
+            format!("Line is {found} bytes long, over the limit of {limit}"),
+        )
+        .at_line(line);
+        if let Self::Decode(details) = &mut err {
+            details.columns = limit..found;
+        }
+        err
+    }
+
+    /// Create a decode error for a document past one of its limits.
+    #[must_use]
+    pub fn limit_exceeded(message: impl Into<String>) -> Self {
+        Self::decode(DecodeErrorKind::LimitExceeded, message)
     }
 
     // =========================================================================
@@ -157,6 +403,46 @@ impl ToonError {
         Self::path_expansion(path, format!("conflicts with existing key '{existing}'"))
     }
 
+    // =========================================================================
+    // Encode validation error constructors
+    // =========================================================================
+
+    /// Create an error for a value that cannot be encoded faithfully.
+    #[must_use]
+    pub fn invalid_value(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::InvalidValue {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+
+    // =========================================================================
+    // Patch error constructors
+    // =========================================================================
+
+    /// Create an error for the patch operation at `index`, acting on `path`.
+    #[must_use]
+    pub fn patch(index: usize, path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::Patch {
+            index,
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+
+    // =========================================================================
+    // Query error constructors
+    // =========================================================================
+
+    /// Create an error for a query that fails to parse at byte `offset`.
+    #[must_use]
+    pub fn query(offset: usize, message: impl Into<String>) -> Self {
+        Self::Query {
+            offset,
+            message: message.into(),
+        }
+    }
+
     // =========================================================================
     // I/O error constructors
     // =========================================================================
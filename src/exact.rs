@@ -0,0 +1,245 @@
+//! Exact round-trip mode for `serde_json::Value`.
+//!
+//! Plain TOON keeps every value's structure and every 64-bit integer, but
+//! `1.0` comes back as `1`, `-0.0` loses its sign, and integers beyond 64 bits
+//! lose precision. Tabular rows also come back in header key order.
+//!
+//! [`encode_exact`] records what a plain decode would get wrong as
+//! annotations, and [`decode_exact`] applies them, so
+//! `decode_exact(&encode_exact(&x, None)?, None)? == x` holds for any value,
+//! key order included. Documents that need no annotations are plain TOON.
+//! Otherwise the output is an ordinary TOON object readable by any decoder:
+//!
+//! ```text
+//! "$exact"[1]{path,value}:
+//!   /ratio,"1.0"
+//! "$value":
+//!   ratio: 1
+//! ```
+//!
+//! Each annotation pairs a JSON Pointer with JSON text: a number literal to
+//! restore, or an array of keys giving the original order of an object.
+
+use serde_json::{Map, Number, Value};
+
+use crate::error::{Result, ToonError};
+use crate::options::{
+    DecodeOptions, EncodeOptions, IndentStyle, KeyFoldingMode, LengthMarkers,
+    resolve_encode_options,
+};
+use crate::{JsonValue, StringOrNumberOrBoolOrNull, try_decode, try_encode};
+
+const ANNOTATIONS_KEY: &str = "$exact";
+const VALUE_KEY: &str = "$value";
+
+/// Largest magnitude below which every integer is exactly representable as `f64`.
+pub(crate) const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// Encode `value` so that [`decode_exact`] reproduces it exactly.
+///
+/// The layout is always one the default decoder reads: key folding is off,
+/// indentation uses spaces and every array header carries its `[N]`,
+/// whatever `options` says.
+///
+/// # Errors
+///
+/// Returns an error if `options` are rejected by [`try_encode`], or the
+/// plain encoding does not decode.
+pub fn encode_exact(value: &Value, options: Option<EncodeOptions>) -> Result<String> {
+    let options = EncodeOptions {
+        key_folding: Some(KeyFoldingMode::Off),
+        indent_style: Some(IndentStyle::Spaces),
+        length_markers: Some(LengthMarkers::AllArrays),
+        ..options.unwrap_or_default()
+    };
+    let indent = resolve_encode_options(Some(options.clone())).indent;
+
+    let plain = try_encode(value.clone(), Some(options.clone()))?;
+    let trial_options = DecodeOptions::builder().indent(indent).build();
+    let decoded = try_decode(&plain, Some(trial_options))?;
+    let mut annotations = Vec::new();
+    collect_annotations(value, &decoded, &mut String::new(), &mut annotations);
+
+    if annotations.is_empty() && !is_envelope(value) {
+        return Ok(plain);
+    }
+
+    let rows = annotations
+        .into_iter()
+        .map(|(path, text)| {
+            let mut row = Map::new();
+            row.insert("path".to_string(), Value::String(path));
+            row.insert("value".to_string(), Value::String(text));
+            Value::Object(row)
+        })
+        .collect();
+    let mut envelope = Map::new();
+    envelope.insert(ANNOTATIONS_KEY.to_string(), Value::Array(rows));
+    envelope.insert(VALUE_KEY.to_string(), value.clone());
+    try_encode(Value::Object(envelope), Some(options))
+}
+
+/// Decode a document produced by [`encode_exact`].
+///
+/// Plain TOON input is accepted too; whole floats within the `f64`-safe
+/// integer range decode as integers.
+///
+/// # Errors
+///
+/// Returns an error if decoding fails or an annotation is malformed.
+pub fn decode_exact(input: &str, options: Option<DecodeOptions>) -> Result<Value> {
+    let decoded = to_exact_json(try_decode(input, options)?);
+
+    let Value::Object(mut root) = decoded else {
+        return Ok(decoded);
+    };
+    if !is_envelope_map(&root) {
+        return Ok(Value::Object(root));
+    }
+
+    let annotations = root.shift_remove(ANNOTATIONS_KEY).unwrap_or_default();
+    let mut value = root.shift_remove(VALUE_KEY).unwrap_or_default();
+    let Value::Array(annotations) = annotations else {
+        return Err(ToonError::message(
+            "Exact decode: annotations must be an array",
+        ));
+    };
+    for annotation in annotations {
+        apply_annotation(&mut value, &annotation)?;
+    }
+    Ok(value)
+}
+
+fn collect_annotations(
+    original: &Value,
+    decoded: &JsonValue,
+    pointer: &mut String,
+    out: &mut Vec<(String, String)>,
+) {
+    match (original, decoded) {
+        (Value::Number(number), JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(n))) => {
+            if exact_number(n).as_ref() != Some(number) {
+                out.push((pointer.clone(), number.to_string()));
+            }
+        }
+        (Value::Array(items), JsonValue::Array(decoded_items)) => {
+            for (index, (item, decoded_item)) in items.iter().zip(decoded_items).enumerate() {
+                let len = pointer.len();
+                push_pointer_token(pointer, &index.to_string());
+                collect_annotations(item, decoded_item, pointer, out);
+                pointer.truncate(len);
+            }
+        }
+        (Value::Object(map), JsonValue::Object(entries)) => {
+            let in_order = map
+                .keys()
+                .zip(entries)
+                .all(|(key, (decoded_key, _))| key == decoded_key);
+            if !in_order {
+                let keys: Vec<&String> = map.keys().collect();
+                out.push((
+                    pointer.clone(),
+                    serde_json::to_string(&keys).unwrap_or_default(),
+                ));
+            }
+            for (key, item) in map {
+                let Some((_, decoded_item)) = entries.iter().find(|(k, _)| k == key) else {
+                    continue;
+                };
+                let len = pointer.len();
+                push_pointer_token(pointer, key);
+                collect_annotations(item, decoded_item, pointer, out);
+                pointer.truncate(len);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn apply_annotation(value: &mut Value, annotation: &Value) -> Result<()> {
+    let malformed =
+        || ToonError::message(format!("Exact decode: malformed annotation {annotation}"));
+
+    let path = annotation
+        .get("path")
+        .and_then(Value::as_str)
+        .ok_or_else(malformed)?;
+    let text = annotation
+        .get("value")
+        .and_then(Value::as_str)
+        .ok_or_else(malformed)?;
+    let target = value.pointer_mut(path).ok_or_else(|| {
+        ToonError::message(format!("Exact decode: annotation path `{path}` not found"))
+    })?;
+
+    match (
+        serde_json::from_str::<Value>(text).map_err(|_| malformed())?,
+        target,
+    ) {
+        (Value::Number(number), target @ Value::Number(_)) => {
+            *target = Value::Number(number);
+        }
+        (Value::Array(keys), Value::Object(map)) => {
+            let mut reordered = Map::with_capacity(map.len());
+            for key in &keys {
+                let key = key.as_str().ok_or_else(malformed)?;
+                if let Some(item) = map.shift_remove(key) {
+                    reordered.insert(key.to_string(), item);
+                }
+            }
+            reordered.append(map);
+            *map = reordered;
+        }
+        _ => return Err(malformed()),
+    }
+    Ok(())
+}
+
+/// Convert a decoded value to JSON, mapping safe whole numbers to integers.
+pub(crate) fn to_exact_json(value: JsonValue) -> Value {
+    match value {
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(n)) => {
+            exact_number(&n).map_or(Value::Null, Value::Number)
+        }
+        JsonValue::Primitive(primitive) => Value::from(JsonValue::Primitive(primitive)),
+        JsonValue::Array(items) => Value::Array(items.into_iter().map(to_exact_json).collect()),
+        JsonValue::Object(entries) => Value::Object(
+            entries
+                .into_iter()
+                .map(|(key, item)| (key, to_exact_json(item)))
+                .collect(),
+        ),
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::float_cmp)]
+fn exact_number(value: &crate::Number) -> Option<Number> {
+    let &crate::Number::F64(value) = value else {
+        return value.to_json_number();
+    };
+    let is_safe_integer = value.fract() == 0.0
+        && value.abs() <= MAX_SAFE_INTEGER
+        && !(value == 0.0 && value.is_sign_negative());
+    if is_safe_integer {
+        Some(Number::from(value as i64))
+    } else {
+        Number::from_f64(value)
+    }
+}
+
+fn is_envelope(value: &Value) -> bool {
+    value.as_object().is_some_and(is_envelope_map)
+}
+
+fn is_envelope_map(map: &Map<String, Value>) -> bool {
+    map.len() == 2
+        && map
+            .keys()
+            .zip([ANNOTATIONS_KEY, VALUE_KEY])
+            .all(|(key, expected)| key == expected)
+}
+
+fn push_pointer_token(pointer: &mut String, token: &str) {
+    pointer.push('/');
+    pointer.push_str(&token.replace('~', "~0").replace('/', "~1"));
+}
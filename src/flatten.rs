@@ -0,0 +1,205 @@
+//! Flattening documents into `path: value` listings and back.
+//!
+//! A flattened document is one object whose keys are the paths of every leaf
+//! in the original: object keys and array indices joined with `.`. Keys that
+//! are not plain identifiers are quoted, so any document round-trips:
+//!
+//! ```
+//! let value = toon::decode("users[2]{id,name}:\n  1,Ada\n  2,Bob\ntags[0]:", None);
+//! let flat = value.flatten();
+//! assert_eq!(
+//!     toon::encode(flat.clone(), None),
+//!     "users.0.id: 1\nusers.0.name: Ada\nusers.1.id: 2\nusers.1.name: Bob\ntags[0]:"
+//! );
+//! assert_eq!(flat.unflatten().unwrap(), value);
+//! ```
+//!
+//! When every key is an identifier the listing also decodes back to the
+//! original with [`ExpandPathsMode::Indexed`](crate::options::ExpandPathsMode).
+
+use crate::JsonValue;
+use crate::error::{Result, ToonError};
+use crate::options::PathSegment;
+use crate::shared::constants::{DOT, DOUBLE_QUOTE};
+use crate::shared::string_utils::{escape_string, find_closing_quote, unescape_string};
+use crate::shared::validation::is_identifier_segment;
+
+impl JsonValue {
+    /// Flatten into an object mapping each leaf's path to the leaf.
+    ///
+    /// Leaves are primitives and empty arrays or objects. Primitives and
+    /// empty containers at the root are returned unchanged.
+    #[must_use]
+    pub fn flatten(&self) -> Self {
+        if is_leaf(self) {
+            return self.clone();
+        }
+        let mut entries = Vec::new();
+        flatten_into(self, &mut String::new(), &mut entries);
+        Self::Object(entries)
+    }
+
+    /// Rebuild a document from a listing produced by [`JsonValue::flatten`].
+    ///
+    /// Numeric path segments are array indices and must appear in order.
+    /// Values other than objects are returned unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a path is malformed, skips an array position, or
+    /// collides with an earlier path.
+    pub fn unflatten(&self) -> Result<Self> {
+        let Self::Object(entries) = self else {
+            return Ok(self.clone());
+        };
+        let mut root: Option<Self> = None;
+        for (path, leaf) in entries {
+            let segments = parse_path(path)?;
+            let node = root.get_or_insert_with(|| container_for(&segments[0]));
+            insert(node, &segments, leaf.clone(), path)?;
+        }
+        Ok(root.unwrap_or_else(|| Self::Object(Vec::new())))
+    }
+}
+
+fn is_leaf(value: &JsonValue) -> bool {
+    match value {
+        JsonValue::Primitive(_) => true,
+        JsonValue::Array(items) => items.is_empty(),
+        JsonValue::Object(entries) => entries.is_empty(),
+    }
+}
+
+fn flatten_into(value: &JsonValue, prefix: &mut String, entries: &mut Vec<(String, JsonValue)>) {
+    if is_leaf(value) {
+        entries.push((prefix.clone(), value.clone()));
+        return;
+    }
+    let start = prefix.len();
+    let mut descend = |segment: &str, child: &JsonValue, prefix: &mut String| {
+        if start > 0 {
+            prefix.push(DOT);
+        }
+        prefix.push_str(segment);
+        flatten_into(child, prefix, entries);
+        prefix.truncate(start);
+    };
+    match value {
+        JsonValue::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                descend(&index.to_string(), item, prefix);
+            }
+        }
+        JsonValue::Object(fields) => {
+            for (key, child) in fields {
+                if is_identifier_segment(key) {
+                    descend(key, child, prefix);
+                } else {
+                    descend(&format!("\"{}\"", escape_string(key)), child, prefix);
+                }
+            }
+        }
+        JsonValue::Primitive(_) => {}
+    }
+}
+
+fn parse_path(path: &str) -> Result<Vec<PathSegment>> {
+    let malformed =
+        |reason: &str| ToonError::invalid_value(path, format!("malformed path: {reason}"));
+    let mut segments = Vec::new();
+    let mut start = 0;
+    loop {
+        let end = if path[start..].starts_with(DOUBLE_QUOTE) {
+            let close =
+                find_closing_quote(path, start).ok_or_else(|| malformed("unterminated quote"))?;
+            let key = unescape_string(&path[start + 1..close]).map_err(|err| malformed(&err))?;
+            segments.push(PathSegment::Key(key));
+            close + 1
+        } else {
+            let end = path[start..]
+                .find(DOT)
+                .map_or(path.len(), |offset| start + offset);
+            let token = &path[start..end];
+            if token.is_empty() {
+                return Err(malformed("empty segment"));
+            }
+            segments.push(
+                parse_index(token)
+                    .map_or_else(|| PathSegment::Key(token.to_string()), PathSegment::Index),
+            );
+            end
+        };
+        match path[end..].chars().next() {
+            None => return Ok(segments),
+            Some(DOT) => start = end + 1,
+            Some(_) => return Err(malformed("expected `.` after quoted segment")),
+        }
+    }
+}
+
+fn parse_index(token: &str) -> Option<usize> {
+    if !token.bytes().all(|b| b.is_ascii_digit()) || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+const fn container_for(segment: &PathSegment) -> JsonValue {
+    match segment {
+        PathSegment::Key(_) => JsonValue::Object(Vec::new()),
+        PathSegment::Index(_) => JsonValue::Array(Vec::new()),
+    }
+}
+
+fn insert(
+    node: &mut JsonValue,
+    segments: &[PathSegment],
+    leaf: JsonValue,
+    path: &str,
+) -> Result<()> {
+    let Some((first, rest)) = segments.split_first() else {
+        return Ok(());
+    };
+    let fresh = || rest.first().map_or_else(|| leaf.clone(), container_for);
+    let (child, created) = match (node, first) {
+        (JsonValue::Object(entries), PathSegment::Key(key)) => {
+            if let Some(position) = entries.iter().position(|(existing, _)| existing == key) {
+                (&mut entries[position].1, false)
+            } else {
+                entries.push((key.clone(), fresh()));
+                let position = entries.len() - 1;
+                (&mut entries[position].1, true)
+            }
+        }
+        (JsonValue::Array(items), PathSegment::Index(index)) if *index <= items.len() => {
+            let created = *index == items.len();
+            if created {
+                items.push(fresh());
+            }
+            (&mut items[*index], created)
+        }
+        (JsonValue::Array(items), PathSegment::Index(index)) => {
+            return Err(ToonError::invalid_value(
+                path,
+                format!(
+                    "index {index} skips positions (array has {} items)",
+                    items.len()
+                ),
+            ));
+        }
+        _ => {
+            return Err(ToonError::invalid_value(
+                path,
+                "path conflicts with an earlier entry",
+            ));
+        }
+    };
+    match (rest.is_empty(), created) {
+        (true, true) => Ok(()),
+        (true, false) => Err(ToonError::invalid_value(
+            path,
+            "path conflicts with an earlier entry",
+        )),
+        (false, _) => insert(child, rest, leaf, path),
+    }
+}
@@ -0,0 +1,206 @@
+//! Reformatting TOON text without decoding it.
+//!
+//! [`format`] rewrites the layout of a document through its
+//! [`cst`](crate::cst), so comments, blank lines, quoting and number
+//! spellings survive that a decode and re-encode would lose:
+//!
+//! ```
+//! use toon::FormatOptions;
+//!
+//! let text = "# ports\nports[2|]:   80|443   \nname:    api\n\n\n";
+//! let options = FormatOptions {
+//!     delimiter: Some(','),
+//!     sort_keys: true,
+//!     ..FormatOptions::default()
+//! };
+//! assert_eq!(toon::format(text, options).unwrap(), "name: api\n# ports\nports[2]: 80,443\n");
+//! ```
+
+use crate::cst::{self, LineKind, Node, Scalar};
+use crate::error::Result;
+
+/// Layout applied by [`format`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Spaces per nesting level. Defaults to 2.
+    pub indent: usize,
+    /// Write every array with this delimiter, quoting values that contain
+    /// it. Defaults to `None`, keeping each array's own.
+    pub delimiter: Option<char>,
+    /// Sort the keys of each object by byte order, moving blank and comment
+    /// lines along with the key below them. The first key of an object that
+    /// starts on a list item line stays first. Defaults to `false`.
+    pub sort_keys: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent: 2,
+            delimiter: None,
+            sort_keys: false,
+        }
+    }
+}
+
+/// Rewrite `input` in a canonical layout.
+///
+/// That is one indent per nesting level, `key: value` spacing, no trailing
+/// whitespace and `\n` line endings. Runs of blank lines shrink to one, and none are left at the start of a block
+/// or the end of the document.
+///
+/// Every line is formatted on its own, so a document that does not decode
+/// is still formatted as far as its lines allow. Formatting is idempotent,
+/// and the result ends with a line break if `input` does.
+///
+/// # Errors
+///
+/// Returns an error if a line does not parse (see [`cst::parse`]).
+pub fn format(input: &str, options: FormatOptions) -> Result<String> {
+    let mut doc = cst::parse(input)?;
+    let unit = doc
+        .lines()
+        .map(|line| line.indent().len())
+        .filter(|&indent| indent > 0)
+        .min()
+        .unwrap_or(options.indent);
+    let formatter = Formatter { options, unit };
+
+    let nodes = doc.nodes_mut();
+    while nodes.last().is_some_and(is_blank) {
+        nodes.pop();
+    }
+    formatter.nodes(nodes, 0);
+
+    let mut out = String::with_capacity(input.len());
+    for line in doc.lines() {
+        out.push_str(line.text());
+        out.push('\n');
+    }
+    if !input.ends_with('\n') {
+        out.pop();
+    }
+    Ok(out)
+}
+
+struct Formatter {
+    options: FormatOptions,
+    /// Smallest indentation in the input, taken as its indent size.
+    unit: usize,
+}
+
+impl Formatter {
+    fn nodes(&self, nodes: &mut Vec<Node>, depth: usize) {
+        nodes.dedup_by(|next, previous| is_blank(next) && is_blank(previous));
+        for node in nodes.iter_mut() {
+            self.node(node, depth);
+        }
+        if self.options.sort_keys {
+            sort_keys(nodes);
+        }
+        if nodes.first().is_some_and(is_blank) {
+            nodes.remove(0);
+        }
+    }
+
+    fn node(&self, node: &mut Node, depth: usize) {
+        let item_indent = node.line().indent().len();
+        let mut kind = node.line().kind().clone();
+        let mut follow_fields = false;
+        match &mut kind {
+            LineKind::Blank | LineKind::Comment(_) => {}
+            LineKind::Row(row) => {
+                if let Some(delimiter) = self.options.delimiter {
+                    row.delimiter = delimiter;
+                    requote(&mut row.values, delimiter);
+                }
+            }
+            LineKind::Entry(entry) => {
+                if let (Some(delimiter), Some(header)) = (self.options.delimiter, &mut entry.header)
+                {
+                    header.delimiter = delimiter;
+                    for field in header.fields.iter_mut().flatten() {
+                        field.quoted |= field.name.contains(delimiter);
+                    }
+                    requote(&mut entry.values, delimiter);
+                }
+                follow_fields = entry.list_item && (entry.key.is_some() || entry.header.is_some());
+            }
+        }
+
+        let line = node.line_mut();
+        line.set_kind(kind);
+        if !matches!(line.kind(), LineKind::Blank) {
+            line.set_indent(&" ".repeat(depth * self.options.indent));
+        }
+
+        // Fields after the first of an object opened on a list item line sit
+        // one level in; what the first field opens sits two levels in.
+        let children = node.children_mut();
+        if follow_fields {
+            let first_field = children
+                .iter()
+                .position(|child| {
+                    !is_trivia(child) && child.line().indent().len() <= item_indent + self.unit
+                })
+                .unwrap_or(children.len());
+            let start = children[..first_field]
+                .iter()
+                .rposition(|child| !is_trivia(child))
+                .map_or(0, |index| index + 1);
+            let mut fields = children.split_off(start);
+            self.nodes(children, depth + 2);
+            self.nodes(&mut fields, depth + 1);
+            children.append(&mut fields);
+        } else {
+            self.nodes(children, depth + 1);
+        }
+    }
+}
+
+/// Quote bare values that would split on `delimiter`.
+fn requote(values: &mut [Scalar], delimiter: char) {
+    for value in values {
+        if !value.is_quoted() && value.raw().contains(delimiter) {
+            *value = Scalar::new(value.value(), delimiter);
+        }
+    }
+}
+
+/// Sort the entries of an object, each with the blank and comment lines
+/// above it. Lists of anything other than keyed entries keep their order.
+fn sort_keys(nodes: &mut Vec<Node>) {
+    let is_field = |node: &Node| {
+        node.line()
+            .entry()
+            .is_some_and(|entry| entry.key.is_some() && !entry.list_item)
+    };
+    if !nodes.iter().all(|node| is_trivia(node) || is_field(node)) {
+        return;
+    }
+
+    let mut groups: Vec<Vec<Node>> = Vec::new();
+    let mut pending = Vec::new();
+    for node in nodes.drain(..) {
+        let field = is_field(&node);
+        pending.push(node);
+        if field {
+            groups.push(std::mem::take(&mut pending));
+        }
+    }
+    groups.sort_by(|a, b| {
+        a.last()
+            .and_then(Node::key)
+            .cmp(&b.last().and_then(Node::key))
+    });
+    nodes.extend(groups.into_iter().flatten());
+    nodes.append(&mut pending);
+}
+
+const fn is_blank(node: &Node) -> bool {
+    matches!(node.line().kind(), LineKind::Blank)
+}
+
+const fn is_trivia(node: &Node) -> bool {
+    matches!(node.line().kind(), LineKind::Blank | LineKind::Comment(_))
+}
@@ -0,0 +1,192 @@
+//! `value["key"][0]` navigation of decoded values, as in `serde_json`.
+
+use std::ops;
+
+use crate::{JsonValue, StringOrNumberOrBoolOrNull};
+
+static NULL: JsonValue = JsonValue::Primitive(StringOrNumberOrBoolOrNull::Null);
+
+/// Types that can index into a [`JsonValue`]: `usize` for arrays and
+/// strings for objects. Sealed; see [`JsonValue::get`].
+pub trait ValueIndex: private::Sealed {
+    #[doc(hidden)]
+    fn index_into<'v>(&self, value: &'v JsonValue) -> Option<&'v JsonValue>;
+
+    #[doc(hidden)]
+    fn index_into_mut<'v>(&self, value: &'v mut JsonValue) -> Option<&'v mut JsonValue>;
+
+    /// Panics where `IndexMut` cannot produce a slot.
+    #[doc(hidden)]
+    fn index_or_insert<'v>(&self, value: &'v mut JsonValue) -> &'v mut JsonValue;
+}
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for usize {}
+    impl Sealed for str {}
+    impl Sealed for String {}
+    impl<T: ?Sized + Sealed> Sealed for &T {}
+}
+
+impl ValueIndex for usize {
+    fn index_into<'v>(&self, value: &'v JsonValue) -> Option<&'v JsonValue> {
+        match value {
+            JsonValue::Array(items) => items.get(*self),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut JsonValue) -> Option<&'v mut JsonValue> {
+        match value {
+            JsonValue::Array(items) => items.get_mut(*self),
+            _ => None,
+        }
+    }
+
+    fn index_or_insert<'v>(&self, value: &'v mut JsonValue) -> &'v mut JsonValue {
+        match value {
+            JsonValue::Array(items) => {
+                let len = items.len();
+                items.get_mut(*self).unwrap_or_else(|| {
+                    panic!("cannot access index {self} of JSON array of length {len}")
+                })
+            }
+            other => panic!("cannot access index {self} of JSON {}", type_name(other)),
+        }
+    }
+}
+
+impl ValueIndex for str {
+    fn index_into<'v>(&self, value: &'v JsonValue) -> Option<&'v JsonValue> {
+        match value {
+            JsonValue::Object(entries) => entries
+                .iter()
+                .rev()
+                .find(|(key, _)| key == self)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut JsonValue) -> Option<&'v mut JsonValue> {
+        match value {
+            JsonValue::Object(entries) => entries
+                .iter_mut()
+                .rev()
+                .find(|(key, _)| key == self)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    fn index_or_insert<'v>(&self, value: &'v mut JsonValue) -> &'v mut JsonValue {
+        if matches!(
+            value,
+            JsonValue::Primitive(StringOrNumberOrBoolOrNull::Null)
+        ) {
+            *value = JsonValue::Object(Vec::new());
+        }
+        match value {
+            JsonValue::Object(entries) => {
+                let position = entries.iter().rposition(|(key, _)| key == self);
+                let position = position.unwrap_or_else(|| {
+                    entries.push((self.to_string(), NULL.clone()));
+                    entries.len() - 1
+                });
+                &mut entries[position].1
+            }
+            other => panic!("cannot access key {self:?} in JSON {}", type_name(other)),
+        }
+    }
+}
+
+impl ValueIndex for String {
+    fn index_into<'v>(&self, value: &'v JsonValue) -> Option<&'v JsonValue> {
+        self.as_str().index_into(value)
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut JsonValue) -> Option<&'v mut JsonValue> {
+        self.as_str().index_into_mut(value)
+    }
+
+    fn index_or_insert<'v>(&self, value: &'v mut JsonValue) -> &'v mut JsonValue {
+        self.as_str().index_or_insert(value)
+    }
+}
+
+impl<T: ?Sized + ValueIndex> ValueIndex for &T {
+    fn index_into<'v>(&self, value: &'v JsonValue) -> Option<&'v JsonValue> {
+        (**self).index_into(value)
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut JsonValue) -> Option<&'v mut JsonValue> {
+        (**self).index_into_mut(value)
+    }
+
+    fn index_or_insert<'v>(&self, value: &'v mut JsonValue) -> &'v mut JsonValue {
+        (**self).index_or_insert(value)
+    }
+}
+
+impl JsonValue {
+    /// The array item or object member at `index`, or `None` if it is
+    /// missing or `self` is the wrong kind of value. A repeated key
+    /// resolves to its last entry.
+    ///
+    /// ```
+    /// let value = toon::decode("users[1]{name}:\n  Ada", None);
+    /// assert_eq!(value.get("users").and_then(|users| users.get(0)), Some(&value["users"][0]));
+    /// assert_eq!(value.get("missing"), None);
+    /// ```
+    #[must_use]
+    pub fn get<I: ValueIndex>(&self, index: I) -> Option<&Self> {
+        index.index_into(self)
+    }
+
+    /// Mutable counterpart of [`get`](Self::get).
+    #[must_use]
+    pub fn get_mut<I: ValueIndex>(&mut self, index: I) -> Option<&mut Self> {
+        index.index_into_mut(self)
+    }
+}
+
+/// Missing members and out-of-range indices yield `null` rather than
+/// panicking:
+///
+/// ```
+/// let value = toon::decode("users[1]{name}:\n  Ada", None);
+/// assert_eq!(value["users"][0]["name"], "Ada".into());
+/// assert_eq!(value["users"][5]["name"], toon::toon_value!(null));
+/// ```
+impl<I: ValueIndex> ops::Index<I> for JsonValue {
+    type Output = Self;
+
+    fn index(&self, index: I) -> &Self {
+        index.index_into(self).unwrap_or(&NULL)
+    }
+}
+
+/// Assigning to a missing key inserts it, turning a `null` into an object
+/// first; indexing an array out of range, or a primitive, panics:
+///
+/// ```
+/// let mut value = toon::toon_value!(null);
+/// value["server"]["port"] = 8080.into();
+/// assert_eq!(value, toon::toon_value!({"server": {"port": 8080}}));
+/// ```
+impl<I: ValueIndex> ops::IndexMut<I> for JsonValue {
+    fn index_mut(&mut self, index: I) -> &mut Self {
+        index.index_or_insert(self)
+    }
+}
+
+const fn type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Object(_) => "object",
+        JsonValue::Array(_) => "array",
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::String(_)) => "string",
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(_)) => "number",
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Bool(_)) => "boolean",
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Null) => "null",
+    }
+}
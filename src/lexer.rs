@@ -0,0 +1,406 @@
+//! Tokens of TOON text with their byte spans, for editor tooling.
+//!
+//! [`tokenize`] splits a document into [`Token`]s that cover every byte of
+//! the input in order, whitespace and line endings included, so a
+//! highlighter can color each span without lexing TOON itself:
+//!
+//! ```
+//! use toon::lexer::{TokenKind, tokenize};
+//!
+//! let text = "tags[2|]: a|1\n";
+//! let kinds: Vec<_> = tokenize(text).map(|token| token.kind).collect();
+//! assert_eq!(
+//!     kinds,
+//!     [
+//!         TokenKind::Key,
+//!         TokenKind::OpenBracket,
+//!         TokenKind::Length,
+//!         TokenKind::Delimiter,
+//!         TokenKind::CloseBracket,
+//!         TokenKind::Colon,
+//!         TokenKind::Whitespace,
+//!         TokenKind::Text,
+//!         TokenKind::Delimiter,
+//!         TokenKind::Number,
+//!         TokenKind::Newline,
+//!     ]
+//! );
+//! ```
+//!
+//! The lexer never fails. Text it cannot place, such as an unterminated
+//! string, comes back as [`TokenKind::Error`]. Like the [`cst`](crate::cst),
+//! it treats a line starting with `#` as a comment unless it is a row of a
+//! tabular array.
+
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use crate::cst::is_row;
+use crate::decode::parser::{
+    is_key_value_line, parse_array_header_line, parse_unmarked_array_header,
+};
+use crate::shared::constants::{
+    CARRIAGE_RETURN, CLOSE_BRACE, CLOSE_BRACKET, COLON, DEFAULT_DELIMITER, DOUBLE_QUOTE,
+    FALSE_LITERAL, LIST_ITEM_MARKER, NEWLINE, NULL_LITERAL, OPEN_BRACE, OPEN_BRACKET, PIPE,
+    SEMICOLON, TAB, TRUE_LITERAL,
+};
+use crate::shared::literal_utils::is_numeric_literal;
+use crate::shared::string_utils::{find_closing_quote, find_unquoted_char};
+
+const COMMENT_MARKER: char = '#';
+
+/// What a [`Token`] holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// Leading spaces or tabs of a line.
+    Indent,
+    /// Spaces or tabs between tokens, or at the end of a line.
+    Whitespace,
+    /// `\n` or `\r\n`.
+    Newline,
+    /// A whole `#` line.
+    Comment,
+    /// The list item marker `-`.
+    ListMarker,
+    /// A key or tabular field name, quoted or not.
+    Key,
+    /// The colon after a key or array header.
+    Colon,
+    /// `[` of an array header.
+    OpenBracket,
+    /// The length inside `[...]`, as written.
+    Length,
+    /// `]` of an array header.
+    CloseBracket,
+    /// `{` before the field names of a tabular header.
+    OpenBrace,
+    /// `}` after the field names of a tabular header.
+    CloseBrace,
+    /// A delimiter between values or field names, or declared in `[N|]`.
+    Delimiter,
+    /// A quoted string value.
+    String,
+    /// A number value.
+    Number,
+    /// `true` or `false`.
+    Bool,
+    /// `null`.
+    Null,
+    /// An unquoted string value.
+    Text,
+    /// Text that does not lex, such as an unterminated string.
+    Error,
+}
+
+/// A token and the byte range of the input it covers.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Range<usize>,
+}
+
+impl Token {
+    /// The text of this token within `source`, the input it was lexed from.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `source` is not the tokenized input and the span falls
+    /// outside it or off a character boundary.
+    #[must_use]
+    pub fn text<'a>(&self, source: &'a str) -> &'a str {
+        &source[self.span.clone()]
+    }
+}
+
+/// Split `input` into tokens, lexing one line at a time as the iterator is
+/// consumed.
+///
+/// The spans are contiguous: they start at 0, each begins where the one
+/// before it ended, and the last ends at `input.len()`.
+#[must_use]
+pub const fn tokenize(input: &str) -> Tokens<'_> {
+    Tokens {
+        input,
+        pos: 0,
+        pending: VecDeque::new(),
+        tables: Vec::new(),
+    }
+}
+
+/// Iterator returned by [`tokenize`].
+#[derive(Debug, Clone)]
+pub struct Tokens<'a> {
+    input: &'a str,
+    pos: usize,
+    pending: VecDeque<Token>,
+    /// Indent and delimiter of each open tabular header.
+    tables: Vec<(usize, char)>,
+}
+
+impl Iterator for Tokens<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        if self.pending.is_empty() && self.pos < self.input.len() {
+            self.lex_line();
+        }
+        self.pending.pop_front()
+    }
+}
+
+impl Tokens<'_> {
+    fn lex_line(&mut self) {
+        let start = self.pos;
+        let rest = &self.input[start..];
+        let (line_len, ending_len) = match rest.find(NEWLINE) {
+            Some(at) if rest[..at].ends_with(CARRIAGE_RETURN) => (at - 1, 2),
+            Some(at) => (at, 1),
+            None => (rest.len(), 0),
+        };
+        let line = &rest[..line_len];
+        let mut lexer = LineLexer {
+            line,
+            offset: start,
+            at: 0,
+            tokens: &mut self.pending,
+        };
+
+        let content = line.trim_start_matches([' ', TAB]);
+        let indent = line.len() - content.len();
+        lexer.push(TokenKind::Indent, indent);
+        let content = content.trim_end_matches([' ', TAB]);
+        if !content.is_empty() {
+            while self.tables.last().is_some_and(|&(open, _)| open >= indent) {
+                self.tables.pop();
+            }
+            match self.tables.last() {
+                Some(&(_, delimiter)) if is_row(content, delimiter) => {
+                    lexer.values(content.len() + indent, delimiter);
+                }
+                _ if content.starts_with(COMMENT_MARKER) => {
+                    lexer.push(TokenKind::Comment, indent + content.len());
+                }
+                _ => {
+                    if let Some(delimiter) = lexer.entry(indent, content) {
+                        self.tables.push((indent, delimiter));
+                    }
+                }
+            }
+        }
+        lexer.fill(line_len);
+        lexer.push(TokenKind::Newline, line_len + ending_len);
+        self.pos = start + line_len + ending_len;
+    }
+}
+
+/// Lexes one line, pushing tokens with absolute spans.
+struct LineLexer<'a, 'b> {
+    line: &'a str,
+    offset: usize,
+    /// End of the last token, relative to the line.
+    at: usize,
+    tokens: &'b mut VecDeque<Token>,
+}
+
+impl LineLexer<'_, '_> {
+    /// Push a token running from the last token to `end`, relative to the
+    /// line. An empty token is skipped.
+    fn push(&mut self, kind: TokenKind, end: usize) {
+        if end > self.at {
+            self.tokens.push_back(Token {
+                kind,
+                span: self.offset + self.at..self.offset + end,
+            });
+            self.at = end;
+        }
+    }
+
+    /// Push whitespace up to `start`, then a token from `start` to `end`.
+    fn token(&mut self, kind: TokenKind, start: usize, end: usize) {
+        self.fill(start);
+        self.push(kind, end);
+    }
+
+    /// Cover the text up to `end` with whitespace, or an error token if it
+    /// holds anything other than spaces and tabs.
+    fn fill(&mut self, end: usize) {
+        let gap = &self.line[self.at..end];
+        let kind = if gap.trim_matches([' ', TAB]).is_empty() {
+            TokenKind::Whitespace
+        } else {
+            TokenKind::Error
+        };
+        self.push(kind, end);
+    }
+
+    /// Lex a key, header or value after the indent, returning the delimiter
+    /// of a tabular header.
+    fn entry(&mut self, indent: usize, content: &str) -> Option<char> {
+        let mut start = indent;
+        let mut rest = content;
+        if rest == LIST_ITEM_MARKER || rest.starts_with("- ") {
+            self.token(TokenKind::ListMarker, start, start + 1);
+            let item = rest[1..].trim_start();
+            start += rest.len() - item.len();
+            rest = item;
+            if rest.is_empty() {
+                return None;
+            }
+        }
+
+        let is_header = matches!(
+            parse_array_header_line(rest, DEFAULT_DELIMITER),
+            Ok(Some(_))
+        ) || matches!(
+            parse_unmarked_array_header(rest, DEFAULT_DELIMITER),
+            Ok(Some(_))
+        );
+        if is_header {
+            return self.header(start, rest);
+        }
+        if is_key_value_line(rest) {
+            let colon = if rest.starts_with(DOUBLE_QUOTE) {
+                find_closing_quote(rest, 0).and_then(|closing| {
+                    let after = closing + 1;
+                    rest[after..].starts_with(COLON).then_some(after)
+                })
+            } else {
+                rest.find(COLON)
+            };
+            if let Some(colon) = colon {
+                let key = rest[..colon].trim_end();
+                self.token(TokenKind::Key, start, start + key.len());
+                self.token(TokenKind::Colon, start + colon, start + colon + 1);
+                let value = rest[colon + 1..].trim_start();
+                let value_start = start + rest.len() - value.len();
+                self.scalar(value_start, value);
+                return None;
+            }
+        }
+        self.scalar(start, rest);
+        None
+    }
+
+    /// Lex an array header and its inline values, which
+    /// [`parse_array_header_line`] has accepted.
+    fn header(&mut self, start: usize, content: &str) -> Option<char> {
+        let bracket = if content.starts_with(DOUBLE_QUOTE) {
+            find_closing_quote(content, 0)? + 1
+        } else {
+            content.find(OPEN_BRACKET)?
+        };
+        let close = bracket + content[bracket..].find(CLOSE_BRACKET)?;
+        if bracket > 0 {
+            self.token(
+                TokenKind::Key,
+                start,
+                start + content[..bracket].trim_end().len(),
+            );
+        }
+        self.token(TokenKind::OpenBracket, start + bracket, start + bracket + 1);
+        let segment = &content[bracket + 1..close];
+        let delimiter = [TAB, PIPE, SEMICOLON]
+            .into_iter()
+            .find(|&delimiter| segment.ends_with(delimiter));
+        let length_end = bracket + 1 + segment.len() - delimiter.map_or(0, char::len_utf8);
+        self.token(TokenKind::Length, start + bracket + 1, start + length_end);
+        self.token(TokenKind::Delimiter, start + length_end, start + close);
+        self.token(TokenKind::CloseBracket, start + close, start + close + 1);
+        let delimiter = delimiter.unwrap_or(DEFAULT_DELIMITER);
+
+        let mut tabular = false;
+        let mut after = close + 1;
+        let brace = find_unquoted_char(content, OPEN_BRACE, after);
+        let colon = find_unquoted_char(content, COLON, after)?;
+        if let Some(brace) = brace.filter(|&brace| brace < colon) {
+            if let Some(end) = find_unquoted_char(content, CLOSE_BRACE, brace) {
+                tabular = true;
+                self.token(TokenKind::OpenBrace, start + brace, start + brace + 1);
+                self.fields(start + brace + 1, &content[brace + 1..end], delimiter);
+                self.token(TokenKind::CloseBrace, start + end, start + end + 1);
+                after = end + 1;
+            }
+        }
+        let colon = find_unquoted_char(content, COLON, after)?;
+        self.token(TokenKind::Colon, start + colon, start + colon + 1);
+        self.values(start + content.len(), delimiter);
+        tabular.then_some(delimiter)
+    }
+
+    /// Lex field names between `start` and the end of `fields`.
+    fn fields(&mut self, start: usize, fields: &str, delimiter: char) {
+        for (at, len, is_delimiter) in split(fields, delimiter) {
+            let kind = if is_delimiter {
+                TokenKind::Delimiter
+            } else {
+                TokenKind::Key
+            };
+            self.token(kind, start + at, start + at + len);
+        }
+    }
+
+    /// Lex delimited values from the last token up to `end`.
+    fn values(&mut self, end: usize, delimiter: char) {
+        let start = self.at;
+        let text = &self.line[start..end];
+        for (at, len, is_delimiter) in split(text, delimiter) {
+            if is_delimiter {
+                self.token(TokenKind::Delimiter, start + at, start + at + len);
+            } else {
+                self.scalar(start + at, &text[at..at + len]);
+            }
+        }
+    }
+
+    /// Lex one trimmed value starting at `start`.
+    fn scalar(&mut self, start: usize, value: &str) {
+        let kind = if value.starts_with(DOUBLE_QUOTE) {
+            if value.len() > 1 && find_closing_quote(value, 0) == Some(value.len() - 1) {
+                TokenKind::String
+            } else {
+                TokenKind::Error
+            }
+        } else if value == TRUE_LITERAL || value == FALSE_LITERAL {
+            TokenKind::Bool
+        } else if value == NULL_LITERAL {
+            TokenKind::Null
+        } else if is_numeric_literal(value) {
+            TokenKind::Number
+        } else {
+            TokenKind::Text
+        };
+        self.token(kind, start, start + value.len());
+    }
+}
+
+/// Split `text` at unquoted delimiters into `(start, len, is_delimiter)`
+/// pieces, trimming spaces and tabs off each value.
+fn split(text: &str, delimiter: char) -> Vec<(usize, usize, bool)> {
+    let mut pieces = Vec::new();
+    let mut value_start = 0;
+    let mut in_quotes = false;
+    let mut chars = text.char_indices();
+    while let Some((idx, ch)) = chars.next() {
+        if in_quotes && ch == '\\' {
+            chars.next();
+        } else if ch == DOUBLE_QUOTE {
+            in_quotes = !in_quotes;
+        } else if ch == delimiter && !in_quotes {
+            push_value(&mut pieces, text, value_start..idx);
+            pieces.push((idx, ch.len_utf8(), true));
+            value_start = idx + ch.len_utf8();
+        }
+    }
+    push_value(&mut pieces, text, value_start..text.len());
+    pieces
+}
+
+fn push_value(pieces: &mut Vec<(usize, usize, bool)>, text: &str, range: Range<usize>) {
+    let value = &text[range.clone()];
+    let trimmed = value.trim_start_matches([' ', TAB]);
+    let at = range.start + value.len() - trimmed.len();
+    let trimmed = trimmed.trim_end_matches([' ', TAB]);
+    if !trimmed.is_empty() {
+        pieces.push((at, trimmed.len(), false));
+    }
+}
@@ -1,24 +1,90 @@
 #![forbid(unsafe_code)]
 
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+
+mod macros;
+
+pub mod borrowed;
+pub mod builder;
+pub mod canonical;
+pub mod chunk;
 pub mod cli;
+pub mod compare;
+pub mod cst;
+pub mod csv;
+pub mod de;
 pub mod decode;
+pub mod diff;
 pub mod encode;
 pub mod error;
+pub mod exact;
+pub mod flatten;
+pub mod format;
+pub mod index;
+pub mod lexer;
+pub mod lint;
+pub mod merge;
+pub mod metrics;
+pub mod number;
+pub mod object;
 pub mod options;
+pub mod patch;
+pub mod path;
+pub mod pointer;
+pub mod query;
+pub mod schema;
+pub mod ser;
 pub mod shared;
+pub mod table;
+pub mod transform;
+pub mod walk;
+
+#[cfg(feature = "parquet")]
+pub mod parquet;
+
+#[cfg(feature = "proptest")]
+pub mod arbitrary;
+
+#[cfg(feature = "diagnostics")]
+mod diagnostics;
 
 #[cfg(feature = "wasm")]
 pub mod wasm;
 
+pub use borrowed::{BorrowedPrimitive, BorrowedValue};
+pub use builder::DocumentBuilder;
+pub use canonical::{NormalizeOptions, encode_canonical};
+pub use compare::{Tolerance, dedup_values};
+pub use de::{from_str, from_value};
+pub use decode::warnings::{DecodeWarning, DecodeWarningKind, WarningSink};
 pub use decode::{
-    decode, decode_from_lines, decode_stream, decode_stream_sync, try_decode,
-    try_decode_from_lines, try_decode_stream, try_decode_stream_sync,
+    ParserCheckpoint, ToonParser, decode, decode_all_errors, decode_borrowed, decode_from_lines,
+    decode_from_reader, decode_map, decode_stream, decode_stream_from_reader, decode_stream_sync,
+    decode_to_serde, try_decode, try_decode_from_lines, try_decode_stream, try_decode_stream_sync,
+    try_decode_with_warnings, validate,
 };
-pub use encode::{encode, encode_lines, encode_stream_events};
+pub use diff::diff;
+pub use encode::report::{EncodeDecision, EncodeDecisionKind};
+pub use encode::{
+    encode, encode_lines, encode_stream_events, encode_to_fmt, encode_to_writer,
+    encode_with_report, try_encode,
+};
+pub use exact::{decode_exact, encode_exact};
+pub use format::{FormatOptions, format};
+pub use metrics::{Metrics, MetricsHook};
+pub use number::Number;
 pub use options::{
-    DecodeOptions, DecodeStreamOptions, EncodeOptions, EncodeReplacer, ResolvedDecodeOptions,
-    ResolvedEncodeOptions,
+    DecodeLimits, DecodeOptions, DecodeOptionsBuilder, DecodeStreamOptions, DuplicateKeyPolicy,
+    EncodeOptions, EncodeOptionsBuilder, EncodeReplacer, ExpandConflictPolicy, IndentStyle,
+    KeyComparator, KeyOrder, LengthMarkers, LineEnding, NonFinitePolicy, Profile, QuotePolicy,
+    ResolvedDecodeOptions, ResolvedEncodeOptions, Strictness,
 };
+pub use ser::{to_string, to_value};
+pub use table::{TableAppender, TableSpec, TabularWriter, append_rows, decode_table};
+pub use walk::WalkAction;
+#[cfg(feature = "bignum")]
+pub use {bigdecimal::BigDecimal, num_bigint::BigInt};
 
 /// Convenience wrapper: parse JSON text and encode to TOON.
 ///
@@ -27,9 +93,7 @@ pub use options::{
 /// # Errors
 /// Returns an error if the JSON input is invalid.
 pub fn json_to_toon(json: &str) -> crate::error::Result<String> {
-    let value: serde_json::Value =
-        serde_json::from_str(json).map_err(|e| crate::error::ToonError::json_parse(&e))?;
-    Ok(encode(value, None))
+    Ok(encode(JsonValue::from_json(json)?, None))
 }
 
 /// Convenience wrapper: decode TOON and return compact JSON text.
@@ -40,22 +104,28 @@ pub fn json_to_toon(json: &str) -> crate::error::Result<String> {
 /// Returns an error if the TOON input is invalid.
 pub fn toon_to_json(toon: &str) -> crate::error::Result<String> {
     let value = try_decode(toon, None)?;
-    let value = serde_json::Value::from(value);
-    serde_json::to_string(&value).map_err(|e| crate::error::ToonError::json_stringify(&e))
+    Ok(cli::json_stringify::json_stringify_lines(&value, 0).concat())
 }
 
 pub type JsonPrimitive = StringOrNumberOrBoolOrNull;
 pub type JsonObject = Vec<(String, JsonValue)>;
 pub type JsonArray = Vec<JsonValue>;
 
-#[derive(Debug, Clone, PartialEq)]
+/// A JSON value as TOON decodes and encodes it.
+///
+/// Equality and [`Hash`] are structural and follow the representation:
+/// objects compare entry by entry, so key order matters, and numbers compare
+/// by value as [`Number`] does, with `NaN == NaN` and `-0.0 == 0.0`. Use
+/// [`semantic_eq`](Self::semantic_eq) or
+/// [`structural_hash`](Self::structural_hash) to ignore key order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum JsonValue {
     Primitive(JsonPrimitive),
     Array(JsonArray),
     Object(JsonObject),
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum JsonStreamEvent {
     StartObject,
     EndObject,
@@ -65,24 +135,24 @@ pub enum JsonStreamEvent {
     Primitive { value: JsonPrimitive },
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum StringOrNumberOrBoolOrNull {
     String(String),
-    Number(f64),
+    Number(Number),
     Bool(bool),
     Null,
 }
 
 impl StringOrNumberOrBoolOrNull {
+    /// A number, with `-0.0` folded into `0.0`. `NaN` and `±Infinity` are
+    /// kept, so the encoder's [`NonFinitePolicy`] decides how they are
+    /// written.
     #[must_use]
     pub fn from_f64(value: f64) -> Self {
-        if !value.is_finite() {
-            return Self::Null;
-        }
         if value == 0.0 {
-            return Self::Number(0.0);
+            return Self::Number(Number::F64(0.0));
         }
-        Self::Number(value)
+        Self::Number(Number::F64(value))
     }
 }
 
@@ -116,17 +186,130 @@ impl From<f64> for JsonValue {
     }
 }
 
-#[allow(clippy::cast_precision_loss)]
 impl From<i64> for JsonValue {
     fn from(value: i64) -> Self {
-        Self::Primitive(StringOrNumberOrBoolOrNull::Number(value as f64))
+        Self::Primitive(StringOrNumberOrBoolOrNull::Number(Number::I64(value)))
+    }
+}
+
+impl From<Number> for JsonValue {
+    fn from(value: Number) -> Self {
+        match value {
+            Number::F64(value) => Self::from(value),
+            number => Self::Primitive(StringOrNumberOrBoolOrNull::Number(number)),
+        }
+    }
+}
+
+macro_rules! impl_from_integer {
+    ($($ty:ty => $via:ty),* $(,)?) => {
+        $(
+            impl From<$ty> for JsonValue {
+                fn from(value: $ty) -> Self {
+                    Self::from(Number::from(<$via>::from(value)))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_integer!(i8 => i64, i16 => i64, i32 => i64, u8 => i64, u16 => i64, u32 => i64, u64 => u64);
+
+impl From<isize> for JsonValue {
+    fn from(value: isize) -> Self {
+        Self::from(value as i64)
+    }
+}
+
+impl From<usize> for JsonValue {
+    fn from(value: usize) -> Self {
+        Self::from(value as u64)
+    }
+}
+
+impl From<f32> for JsonValue {
+    fn from(value: f32) -> Self {
+        Self::from(f64::from(value))
+    }
+}
+
+impl From<char> for JsonValue {
+    fn from(value: char) -> Self {
+        Self::Primitive(StringOrNumberOrBoolOrNull::String(value.to_string()))
+    }
+}
+
+impl From<Cow<'_, str>> for JsonValue {
+    fn from(value: Cow<'_, str>) -> Self {
+        Self::Primitive(StringOrNumberOrBoolOrNull::String(value.into_owned()))
+    }
+}
+
+/// `()` is `null`.
+impl From<()> for JsonValue {
+    fn from((): ()) -> Self {
+        Self::Primitive(StringOrNumberOrBoolOrNull::Null)
+    }
+}
+
+/// `None` is `null`.
+impl<T: Into<Self>> From<Option<T>> for JsonValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(
+            Self::Primitive(StringOrNumberOrBoolOrNull::Null),
+            Into::into,
+        )
+    }
+}
+
+impl<T: Into<Self>> From<Vec<T>> for JsonValue {
+    fn from(value: Vec<T>) -> Self {
+        Self::Array(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Clone + Into<Self>> From<&[T]> for JsonValue {
+    fn from(value: &[T]) -> Self {
+        Self::Array(value.iter().cloned().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Self>, const N: usize> From<[T; N]> for JsonValue {
+    fn from(value: [T; N]) -> Self {
+        Self::Array(value.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Members in key order.
+impl<K: Into<String>, V: Into<Self>> From<BTreeMap<K, V>> for JsonValue {
+    fn from(value: BTreeMap<K, V>) -> Self {
+        value.into_iter().collect()
+    }
+}
+
+/// Members in the map's iteration order, which is unspecified; use a
+/// `BTreeMap` for stable output.
+impl<K: Into<String>, V: Into<Self>, S> From<HashMap<K, V, S>> for JsonValue {
+    fn from(value: HashMap<K, V, S>) -> Self {
+        value.into_iter().collect()
+    }
+}
+
+/// Collects into an array.
+impl<T: Into<Self>> FromIterator<T> for JsonValue {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::Array(iter.into_iter().map(Into::into).collect())
     }
 }
 
-#[allow(clippy::use_self)]
-impl From<Vec<JsonValue>> for JsonValue {
-    fn from(value: Vec<JsonValue>) -> Self {
-        Self::Array(value)
+/// Collects key-value pairs into an object, in iteration order.
+impl<K: Into<String>, V: Into<Self>> FromIterator<(K, V)> for JsonValue {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self::Object(
+            iter.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
     }
 }
 
@@ -143,12 +326,7 @@ impl From<serde_json::Value> for JsonValue {
             serde_json::Value::Bool(value) => {
                 Self::Primitive(StringOrNumberOrBoolOrNull::Bool(value))
             }
-            serde_json::Value::Number(value) => {
-                let number = value
-                    .as_f64()
-                    .unwrap_or_else(|| value.to_string().parse::<f64>().unwrap_or(f64::NAN));
-                Self::Primitive(StringOrNumberOrBoolOrNull::from_f64(number))
-            }
+            serde_json::Value::Number(value) => Self::from(Number::from(&value)),
             serde_json::Value::String(value) => {
                 Self::Primitive(StringOrNumberOrBoolOrNull::String(value))
             }
@@ -166,17 +344,23 @@ impl From<serde_json::Value> for JsonValue {
     }
 }
 
+impl From<StringOrNumberOrBoolOrNull> for serde_json::Value {
+    fn from(value: StringOrNumberOrBoolOrNull) -> Self {
+        match value {
+            StringOrNumberOrBoolOrNull::String(value) => Self::String(value),
+            StringOrNumberOrBoolOrNull::Number(value) => {
+                value.to_json_number().map_or(Self::Null, Self::Number)
+            }
+            StringOrNumberOrBoolOrNull::Bool(value) => Self::Bool(value),
+            StringOrNumberOrBoolOrNull::Null => Self::Null,
+        }
+    }
+}
+
 impl From<JsonValue> for serde_json::Value {
     fn from(value: JsonValue) -> Self {
         match value {
-            JsonValue::Primitive(p) => match p {
-                StringOrNumberOrBoolOrNull::String(value) => Self::String(value),
-                StringOrNumberOrBoolOrNull::Number(value) => {
-                    serde_json::Number::from_f64(value).map_or(Self::Null, Self::Number)
-                }
-                StringOrNumberOrBoolOrNull::Bool(value) => Self::Bool(value),
-                StringOrNumberOrBoolOrNull::Null => Self::Null,
-            },
+            JsonValue::Primitive(p) => Self::from(p),
             JsonValue::Array(arr) => Self::Array(arr.into_iter().map(Self::from).collect()),
             JsonValue::Object(obj) => {
                 let mut map = serde_json::Map::new();
@@ -188,3 +372,29 @@ impl From<JsonValue> for serde_json::Value {
         }
     }
 }
+
+/// Decodes TOON with the default options, as [`try_decode`] does.
+///
+/// ```
+/// let value: toon::JsonValue = "name: Alice\nage: 30".parse().unwrap();
+/// assert_eq!(value["age"], 30.into());
+/// ```
+impl std::str::FromStr for JsonValue {
+    type Err = crate::error::ToonError;
+
+    fn from_str(input: &str) -> crate::error::Result<Self> {
+        try_decode(input, None)
+    }
+}
+
+/// Encodes as TOON with the default options, as [`encode()`] does.
+///
+/// ```
+/// let value = toon::toon_value!({"name": "Alice", "tags": ["a", "b"]});
+/// assert_eq!(value.to_string(), "name: Alice\ntags[2]: a,b");
+/// ```
+impl std::fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        encode_to_fmt(self.clone(), f, None).map_err(|_| std::fmt::Error)
+    }
+}
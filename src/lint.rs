@@ -0,0 +1,541 @@
+//! Style and correctness checks over TOON text.
+//!
+//! [`lint`] reads a document through its [`cst`](crate::cst) and reports a
+//! [`Finding`] per problem, each tagged with the [`Rule`] that raised it and
+//! a [`Severity`]. [`LintOptions::rules`] changes a rule's severity or turns
+//! it off:
+//!
+//! ```
+//! use toon::lint::{LintOptions, Rule, Severity, lint};
+//!
+//! let text = "name: \"api\"\nport: \"8080\"\n";
+//! let findings = lint(text, &LintOptions::default());
+//! let rules: Vec<_> = findings.iter().map(|finding| finding.rule.id()).collect();
+//! assert_eq!(rules, ["redundant-quotes", "quoted-literal"]);
+//!
+//! let options = LintOptions::default().with(Rule::RedundantQuotes, None);
+//! assert_eq!(lint(text, &options)[0].severity, Severity::Warning);
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::cst::{self, Document, Entry, Key, LineKind, Node, Scalar};
+use crate::encode::primitives::encode_key;
+use crate::error::{Result, ToonError};
+use crate::options::QuotePolicy;
+use crate::shared::constants::{COLON, DEFAULT_DELIMITER};
+use crate::shared::literal_utils::{is_boolean_or_null_literal, is_numeric_like};
+use crate::shared::string_utils::find_unquoted_char;
+use crate::shared::validation::{is_identifier_segment, is_safe_unquoted, is_valid_unquoted_key};
+use crate::{DecodeOptions, StringOrNumberOrBoolOrNull};
+
+/// How much a finding matters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// A suggestion; the document reads the same either way.
+    Info,
+    /// Likely a mistake, or inconsistent with the rest of the document.
+    Warning,
+    /// The document does not decode.
+    Error,
+}
+
+/// A check run by [`lint`], named by its [`id`](Self::id).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    /// `invalid`: the document fails to decode with the default options and
+    /// its own indent size.
+    Invalid,
+    /// `inconsistent-indent`: indentation with tabs, not a multiple of the
+    /// indent size, or more than one level past the parent line.
+    InconsistentIndent,
+    /// `mixed-delimiters`: arrays using a different delimiter than the first
+    /// array of the document.
+    MixedDelimiters,
+    /// `foldable-key`: a chain of objects with a single key each, which key
+    /// folding writes as one dotted key such as `a.b.c: 1`.
+    FoldableKey,
+    /// `could-be-tabular`: a list of objects with the same primitive fields,
+    /// which reads shorter as a table.
+    CouldBeTabular,
+    /// `quoted-literal`: a quoted string that would read as a number,
+    /// boolean or null without its quotes, which may not be what was meant.
+    QuotedLiteral,
+    /// `redundant-quotes`: a quoted key or string that reads the same bare.
+    RedundantQuotes,
+}
+
+impl Rule {
+    pub const ALL: [Self; 7] = [
+        Self::Invalid,
+        Self::InconsistentIndent,
+        Self::MixedDelimiters,
+        Self::FoldableKey,
+        Self::CouldBeTabular,
+        Self::QuotedLiteral,
+        Self::RedundantQuotes,
+    ];
+
+    /// Stable kebab-case name, as accepted by [`FromStr`].
+    #[must_use]
+    pub const fn id(self) -> &'static str {
+        match self {
+            Self::Invalid => "invalid",
+            Self::InconsistentIndent => "inconsistent-indent",
+            Self::MixedDelimiters => "mixed-delimiters",
+            Self::FoldableKey => "foldable-key",
+            Self::CouldBeTabular => "could-be-tabular",
+            Self::QuotedLiteral => "quoted-literal",
+            Self::RedundantQuotes => "redundant-quotes",
+        }
+    }
+
+    #[must_use]
+    pub const fn default_severity(self) -> Severity {
+        match self {
+            Self::Invalid => Severity::Error,
+            Self::InconsistentIndent | Self::MixedDelimiters | Self::QuotedLiteral => {
+                Severity::Warning
+            }
+            Self::FoldableKey | Self::CouldBeTabular | Self::RedundantQuotes => Severity::Info,
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+impl FromStr for Rule {
+    type Err = ToonError;
+
+    fn from_str(id: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|rule| rule.id() == id)
+            .ok_or_else(|| ToonError::message(format!("Unknown lint rule: {id}")))
+    }
+}
+
+/// Which rules [`lint`] runs, and how.
+#[derive(Debug, Clone, Default)]
+pub struct LintOptions {
+    /// Indent size the document should use. Defaults to its smallest
+    /// indentation.
+    pub indent: Option<usize>,
+    /// Severity of each listed rule in place of its
+    /// [`default_severity`](Rule::default_severity), or `None` to turn the
+    /// rule off.
+    pub rules: HashMap<Rule, Option<Severity>>,
+}
+
+impl LintOptions {
+    /// Set the severity of `rule`, or turn it off with `None`.
+    #[must_use]
+    pub fn with(mut self, rule: Rule, severity: Option<Severity>) -> Self {
+        self.rules.insert(rule, severity);
+        self
+    }
+
+    fn severity(&self, rule: Rule) -> Option<Severity> {
+        self.rules
+            .get(&rule)
+            .copied()
+            .unwrap_or_else(|| Some(rule.default_severity()))
+    }
+}
+
+/// One problem found by [`lint`].
+///
+/// Displays as `3:5: warning[mixed-delimiters]: Array uses `|` ...`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub rule: Rule,
+    pub severity: Severity,
+    /// 1-based line number, or 0 when the finding has no single line.
+    pub line: usize,
+    /// Byte range of the offending text within the line.
+    pub columns: Range<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(
+            f,
+            "{}:{}: {severity}[{}]: {}",
+            self.line,
+            self.columns.start + 1,
+            self.rule,
+            self.message
+        )
+    }
+}
+
+/// Check `input` against every enabled rule, returning findings in line
+/// order.
+///
+/// A line that does not parse at all is reported under [`Rule::Invalid`]
+/// and stops the other checks.
+#[must_use]
+pub fn lint(input: &str, options: &LintOptions) -> Vec<Finding> {
+    let mut linter = Linter {
+        options,
+        unit: 0,
+        delimiter: None,
+        findings: Vec::new(),
+    };
+    match cst::parse(input) {
+        Ok(doc) => linter.document(&doc),
+        Err(err) => linter.invalid(&err),
+    }
+    let mut findings = linter.findings;
+    findings.sort_by_key(|finding| finding.line);
+    findings
+}
+
+struct Linter<'a> {
+    options: &'a LintOptions,
+    unit: usize,
+    /// Delimiter of the first array that uses one, and its line.
+    delimiter: Option<(char, usize)>,
+    findings: Vec<Finding>,
+}
+
+impl Linter<'_> {
+    fn document(&mut self, doc: &Document) {
+        self.unit = self.options.indent.unwrap_or_else(|| {
+            doc.lines()
+                .map(|line| line.indent().len())
+                .filter(|&indent| indent > 0)
+                .min()
+                .unwrap_or(2)
+        });
+        self.unit = self.unit.max(1);
+        self.nodes(doc.nodes(), None, false);
+
+        let options = DecodeOptions {
+            indent: Some(self.unit),
+            ..DecodeOptions::default()
+        };
+        if let Err(err) = doc.to_value(Some(options)) {
+            self.invalid(&err);
+        }
+    }
+
+    fn invalid(&mut self, err: &ToonError) {
+        let (line, columns, message) = err.decode_error().map_or_else(
+            || (0, 0..0, err.to_string()),
+            |details| {
+                (
+                    details.line,
+                    details.columns.clone(),
+                    details.message.clone(),
+                )
+            },
+        );
+        self.report(Rule::Invalid, line, columns, message);
+    }
+
+    fn report(&mut self, rule: Rule, line: usize, columns: Range<usize>, message: String) {
+        if let Some(severity) = self.options.severity(rule) {
+            self.findings.push(Finding {
+                rule,
+                severity,
+                line,
+                columns,
+                message,
+            });
+        }
+    }
+
+    /// `in_chain` is set under a reported foldable key, so a chain is
+    /// reported once, at its top.
+    fn nodes(&mut self, nodes: &[Node], parent: Option<&Node>, in_chain: bool) {
+        for node in nodes {
+            let line = node.line();
+            let mut foldable = false;
+            match line.kind() {
+                LineKind::Blank | LineKind::Comment(_) => continue,
+                LineKind::Row(row) => {
+                    self.indent(node, parent);
+                    self.scalars(node, &row.values, row.delimiter);
+                }
+                LineKind::Entry(entry) => {
+                    self.indent(node, parent);
+                    if let Some(key) = &entry.key {
+                        self.key(node, key);
+                    }
+                    let delimiter = entry.header.as_ref().map_or(DEFAULT_DELIMITER, |header| {
+                        self.delimiter(node, entry, header.delimiter);
+                        header.delimiter
+                    });
+                    self.scalars(node, &entry.values, delimiter);
+                    self.tabular(node, entry);
+                    foldable = fold_child(node).is_some();
+                    if foldable && !in_chain {
+                        self.fold(node);
+                    }
+                }
+            }
+            self.nodes(node.children(), Some(node), foldable);
+        }
+    }
+
+    fn indent(&mut self, node: &Node, parent: Option<&Node>) {
+        let line = node.line();
+        let indent = line.indent();
+        let width = indent.len();
+        let message = if indent.contains('\t') {
+            "Indentation uses a tab".to_string()
+        } else if width % self.unit != 0 {
+            format!("Indented {width} spaces, not a multiple of {}", self.unit)
+        } else if let Some(parent) = parent {
+            let levels = (width - parent.line().indent().len()) / self.unit;
+            let allowed = if opens_on_list_item(parent) { 2 } else { 1 };
+            if levels <= allowed {
+                return;
+            }
+            format!("Indented {levels} levels past the line it belongs to")
+        } else if width > 0 {
+            "Top-level line is indented".to_string()
+        } else {
+            return;
+        };
+        self.report(Rule::InconsistentIndent, line.number(), 0..width, message);
+    }
+
+    fn delimiter(&mut self, node: &Node, entry: &Entry, delimiter: char) {
+        let header = entry.header.as_ref();
+        let uses_delimiter = !entry.values.is_empty() || header.is_some_and(|h| h.fields.is_some());
+        if !uses_delimiter {
+            return;
+        }
+        let line = node.line();
+        match self.delimiter {
+            None => self.delimiter = Some((delimiter, line.number())),
+            Some((first, first_line)) if first != delimiter => self.report(
+                Rule::MixedDelimiters,
+                line.number(),
+                content_columns(line),
+                format!(
+                    "Array uses `{}` while the array on line {first_line} uses `{}`",
+                    delimiter.escape_default(),
+                    first.escape_default()
+                ),
+            ),
+            Some(_) => {}
+        }
+    }
+
+    fn fold(&mut self, node: &Node) {
+        let mut path = node.key().unwrap_or_default().to_string();
+        let mut current = node;
+        while let Some(child) = fold_child(current) {
+            path.push('.');
+            path.push_str(child.key().unwrap_or_default());
+            current = child;
+        }
+        if let Some(child) = single_child(current).and_then(Node::key) {
+            path.push('.');
+            path.push_str(child);
+        }
+        let line = node.line();
+        self.report(
+            Rule::FoldableKey,
+            line.number(),
+            content_columns(line),
+            format!(
+                "`{}` holds a single key; with key folding it reads `{path}`",
+                node.key().unwrap_or_default()
+            ),
+        );
+    }
+
+    fn tabular(&mut self, node: &Node, entry: &Entry) {
+        let Some(header) = &entry.header else {
+            return;
+        };
+        if header.fields.is_some() || !entry.values.is_empty() {
+            return;
+        }
+        let mut items = node
+            .children()
+            .iter()
+            .filter(|child| !matches!(child.line().kind(), LineKind::Blank | LineKind::Comment(_)))
+            .peekable();
+        if items.peek().is_none() {
+            return;
+        }
+        let mut fields: Option<Vec<&str>> = None;
+        let mut count = 0usize;
+        for item in items {
+            let Some(keys) = primitive_fields(item) else {
+                return;
+            };
+            match &fields {
+                None => fields = Some(keys),
+                Some(first) => {
+                    if first.len() != keys.len() || !first.iter().all(|key| keys.contains(key)) {
+                        return;
+                    }
+                }
+            }
+            count += 1;
+        }
+        let fields = fields
+            .unwrap_or_default()
+            .iter()
+            .map(|field| encode_key(field, QuotePolicy::Minimal))
+            .collect::<Vec<_>>()
+            .join(&header.delimiter.to_string());
+        let key = entry
+            .key
+            .as_ref()
+            .map(|key| encode_key(&key.name, QuotePolicy::Minimal))
+            .unwrap_or_default();
+        let line = node.line();
+        self.report(
+            Rule::CouldBeTabular,
+            line.number(),
+            content_columns(line),
+            format!("{count} objects with the same primitive fields fit a table: `{key}[{count}]{{{fields}}}:`"),
+        );
+    }
+
+    fn key(&mut self, node: &Node, key: &Key) {
+        if key.quoted && is_valid_unquoted_key(&key.name) {
+            let line = node.line();
+            let quoted = format!("\"{}\"", key.name);
+            let columns = locate(line.text(), &quoted, line.indent().len())
+                .unwrap_or_else(|| content_columns(line));
+            self.report(
+                Rule::RedundantQuotes,
+                line.number(),
+                columns,
+                format!("Key `{}` reads the same without quotes", key.name),
+            );
+        }
+    }
+
+    fn scalars(&mut self, node: &Node, values: &[Scalar], delimiter: char) {
+        let line = node.line();
+        let text = line.text();
+        // Values follow the colon of a key or header.
+        let keyed = line
+            .entry()
+            .is_some_and(|entry| entry.key.is_some() || entry.header.is_some());
+        let mut from = match find_unquoted_char(text, COLON, 0) {
+            Some(colon) if keyed => colon + 1,
+            _ => line.indent().len(),
+        };
+        for value in values {
+            let columns = locate(text, value.raw(), from).unwrap_or_else(|| content_columns(line));
+            from = columns.end;
+            let StringOrNumberOrBoolOrNull::String(string) = value.value() else {
+                continue;
+            };
+            if !value.is_quoted() {
+                continue;
+            }
+            if is_boolean_or_null_literal(string) || is_numeric_like(string) {
+                self.report(
+                    Rule::QuotedLiteral,
+                    line.number(),
+                    columns,
+                    format!(
+                        "`{}` is a string; drop the quotes if the literal {string} was meant",
+                        value.raw()
+                    ),
+                );
+            } else if is_safe_unquoted(string, delimiter) {
+                self.report(
+                    Rule::RedundantQuotes,
+                    line.number(),
+                    columns,
+                    format!("`{string}` reads the same without quotes"),
+                );
+            }
+        }
+    }
+}
+
+/// The line opens an object on a list item, so what its first field holds
+/// sits two levels in.
+fn opens_on_list_item(node: &Node) -> bool {
+    node.line()
+        .entry()
+        .is_some_and(|entry| entry.list_item && (entry.key.is_some() || entry.header.is_some()))
+}
+
+/// The only content line nested under `node`.
+fn single_child(node: &Node) -> Option<&Node> {
+    let mut content = node
+        .children()
+        .iter()
+        .filter(|child| !matches!(child.line().kind(), LineKind::Blank | LineKind::Comment(_)));
+    let child = content.next()?;
+    content.next().is_none().then_some(child)
+}
+
+/// The single field of a `key:` object that folding would pull up into a
+/// dotted key.
+fn fold_child(node: &Node) -> Option<&Node> {
+    let entry = node.line().entry()?;
+    let key = entry.key.as_ref()?;
+    if entry.list_item || entry.header.is_some() || !entry.values.is_empty() {
+        return None;
+    }
+    if key.quoted || !is_identifier_segment(&key.name) {
+        return None;
+    }
+    let child = single_child(node)?;
+    let child_entry = child.line().entry()?;
+    let child_key = child_entry.key.as_ref()?;
+    (!child_entry.list_item && !child_key.quoted && is_identifier_segment(&child_key.name))
+        .then_some(child)
+}
+
+/// Keys of a list item holding an object of primitive fields only.
+fn primitive_fields(item: &Node) -> Option<Vec<&str>> {
+    let entry = item.line().entry()?;
+    if !entry.list_item {
+        return None;
+    }
+    let mut keys = vec![primitive_field(entry)?];
+    for child in item.children() {
+        match child.line().kind() {
+            LineKind::Blank | LineKind::Comment(_) => {}
+            LineKind::Entry(field) if !field.list_item && child.children().is_empty() => {
+                keys.push(primitive_field(field)?);
+            }
+            _ => return None,
+        }
+    }
+    Some(keys)
+}
+
+fn primitive_field(entry: &Entry) -> Option<&str> {
+    let key = entry.key.as_ref()?;
+    (entry.header.is_none() && entry.values.len() == 1).then_some(key.name.as_str())
+}
+
+fn content_columns(line: &cst::Line) -> Range<usize> {
+    let start = line.indent().len();
+    start..start + line.content().len()
+}
+
+fn locate(text: &str, needle: &str, from: usize) -> Option<Range<usize>> {
+    let start = from + text.get(from..)?.find(needle)?;
+    Some(start..start + needle.len())
+}
@@ -0,0 +1,98 @@
+//! `toon_value!` for writing [`JsonValue`](crate::JsonValue) literals.
+
+/// Build a [`JsonValue`](crate::JsonValue) from JSON-like syntax, as
+/// `serde_json::json!` does, without going through `serde_json`.
+///
+/// Object keys are string literals or parenthesized expressions and keep
+/// their written order; `null` is the null primitive, nested `[...]` and
+/// `{...}` build arrays and objects, and any other expression is converted
+/// with `JsonValue::from`.
+///
+/// ```
+/// use toon::toon_value;
+///
+/// let name = "Ada";
+/// let value = toon_value!({
+///     "user": {"name": name, "tags": ["admin", "dev"]},
+///     ("id".to_string() + "s"): [1, 2.5, -3],
+///     "active": true,
+///     "manager": null,
+/// });
+/// assert_eq!(
+///     toon::encode(value, None),
+///     "user:\n  name: Ada\n  tags[2]: admin,dev\nids[3]: 1,2.5,-3\nactive: true\nmanager: null"
+/// );
+/// ```
+#[macro_export]
+macro_rules! toon_value {
+    // Arrays: elements accumulate in [...] as expressions followed by commas.
+    (@array [$($elems:expr,)*]) => {
+        ::std::vec![$($elems,)*]
+    };
+    (@array [$($elems:expr,)*] , $($rest:tt)*) => {
+        $crate::toon_value!(@array [$($elems,)*] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] null $($rest:tt)*) => {
+        $crate::toon_value!(@array [$($elems,)* $crate::toon_value!(null),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] [$($inner:tt)*] $($rest:tt)*) => {
+        $crate::toon_value!(@array [$($elems,)* $crate::toon_value!([$($inner)*]),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] {$($inner:tt)*} $($rest:tt)*) => {
+        $crate::toon_value!(@array [$($elems,)* $crate::toon_value!({$($inner)*}),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $next:expr, $($rest:tt)*) => {
+        $crate::toon_value!(@array [$($elems,)* $crate::toon_value!($next),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $last:expr) => {
+        $crate::toon_value!(@array [$($elems,)* $crate::toon_value!($last),])
+    };
+
+    // Objects: entries accumulate as (key, value) expressions; @value
+    // parses the value after a key.
+    (@object [$($entries:expr,)*]) => {
+        ::std::vec![$($entries,)*]
+    };
+    (@object [$($entries:expr,)*] , $($rest:tt)*) => {
+        $crate::toon_value!(@object [$($entries,)*] $($rest)*)
+    };
+    (@object [$($entries:expr,)*] $key:literal : $($rest:tt)*) => {
+        $crate::toon_value!(@value [$($entries,)*] ($key) $($rest)*)
+    };
+    (@object [$($entries:expr,)*] ($key:expr) : $($rest:tt)*) => {
+        $crate::toon_value!(@value [$($entries,)*] ($key) $($rest)*)
+    };
+    (@value [$($entries:expr,)*] ($key:expr) null $($rest:tt)*) => {
+        $crate::toon_value!(@entry [$($entries,)*] ($key) ($crate::toon_value!(null)) $($rest)*)
+    };
+    (@value [$($entries:expr,)*] ($key:expr) [$($inner:tt)*] $($rest:tt)*) => {
+        $crate::toon_value!(@entry [$($entries,)*] ($key) ($crate::toon_value!([$($inner)*])) $($rest)*)
+    };
+    (@value [$($entries:expr,)*] ($key:expr) {$($inner:tt)*} $($rest:tt)*) => {
+        $crate::toon_value!(@entry [$($entries,)*] ($key) ($crate::toon_value!({$($inner)*})) $($rest)*)
+    };
+    (@value [$($entries:expr,)*] ($key:expr) $value:expr, $($rest:tt)*) => {
+        $crate::toon_value!(@entry [$($entries,)*] ($key) ($crate::toon_value!($value)) , $($rest)*)
+    };
+    (@value [$($entries:expr,)*] ($key:expr) $value:expr) => {
+        $crate::toon_value!(@entry [$($entries,)*] ($key) ($crate::toon_value!($value)))
+    };
+    (@entry [$($entries:expr,)*] ($key:expr) ($value:expr) $($rest:tt)*) => {
+        $crate::toon_value!(
+            @object [$($entries,)* (::std::string::ToString::to_string(&$key), $value),] $($rest)*
+        )
+    };
+
+    (null) => {
+        $crate::JsonValue::Primitive($crate::StringOrNumberOrBoolOrNull::Null)
+    };
+    ([$($tt:tt)*]) => {
+        $crate::JsonValue::Array($crate::toon_value!(@array [] $($tt)*))
+    };
+    ({$($tt:tt)*}) => {
+        $crate::JsonValue::Object($crate::toon_value!(@object [] $($tt)*))
+    };
+    ($other:expr) => {
+        $crate::JsonValue::from($other)
+    };
+}
@@ -0,0 +1,117 @@
+//! Layered deep merging of decoded values, e.g. defaults + environment +
+//! overrides.
+
+use std::sync::Arc;
+
+use crate::JsonValue;
+use crate::pointer::escape;
+
+/// Picks the merged value where both sides hold differing non-object
+/// values. Receives the JSON Pointer of the conflict, the base value and
+/// the overlay value.
+pub type ConflictResolver = Arc<dyn Fn(&str, &JsonValue, &JsonValue) -> JsonValue + Send + Sync>;
+
+/// How [`JsonValue::deep_merge`] combines two arrays at the same path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ArrayMerge {
+    /// The overlay array replaces the base array.
+    #[default]
+    Replace,
+    /// Overlay items are appended after base items.
+    Concat,
+    /// Object items whose `key` member matches a base item are deep-merged
+    /// into it; other items are appended unless an equal item is already
+    /// present.
+    UnionByKey(String),
+}
+
+/// Options for [`JsonValue::deep_merge`]. Objects are always unioned
+/// recursively; the default replaces arrays and lets the overlay win
+/// conflicts.
+#[derive(Clone, Default)]
+pub struct MergeStrategy {
+    pub arrays: ArrayMerge,
+    /// Resolve conflicting values. Defaults to taking the overlay value.
+    pub on_conflict: Option<ConflictResolver>,
+}
+
+impl JsonValue {
+    /// Merge `other` over `self` in place.
+    ///
+    /// ```
+    /// use toon::merge::{ArrayMerge, MergeStrategy};
+    ///
+    /// let mut config = toon::decode("server:\n  host: a\n  port: 80\ntags[1]: x", None);
+    /// let overlay = toon::decode("server:\n  port: 8080\ntags[1]: y", None);
+    /// let strategy = MergeStrategy { arrays: ArrayMerge::Concat, ..MergeStrategy::default() };
+    /// config.deep_merge(overlay, &strategy);
+    /// assert_eq!(config, toon::decode("server:\n  host: a\n  port: 8080\ntags[2]: x,y", None));
+    /// ```
+    pub fn deep_merge(&mut self, other: Self, strategy: &MergeStrategy) {
+        merge_at(self, other, strategy, &mut String::new());
+    }
+}
+
+fn merge_at(base: &mut JsonValue, overlay: JsonValue, strategy: &MergeStrategy, path: &mut String) {
+    match (base, overlay) {
+        (JsonValue::Object(entries), JsonValue::Object(members)) => {
+            for (key, value) in members {
+                match entries.iter_mut().rev().find(|(k, _)| *k == key) {
+                    Some((_, slot)) => {
+                        let len = path.len();
+                        path.push('/');
+                        path.push_str(&escape(&key));
+                        merge_at(slot, value, strategy, path);
+                        path.truncate(len);
+                    }
+                    None => entries.push((key, value)),
+                }
+            }
+        }
+        (JsonValue::Array(items), JsonValue::Array(overlay_items)) => match &strategy.arrays {
+            ArrayMerge::Replace => *items = overlay_items,
+            ArrayMerge::Concat => items.extend(overlay_items),
+            ArrayMerge::UnionByKey(key) => {
+                for item in overlay_items {
+                    let position = member(&item, key).and_then(|id| {
+                        items
+                            .iter()
+                            .position(|existing| member(existing, key) == Some(id))
+                    });
+                    match position {
+                        Some(index) => {
+                            let len = path.len();
+                            path.push('/');
+                            path.push_str(&index.to_string());
+                            merge_at(&mut items[index], item, strategy, path);
+                            path.truncate(len);
+                        }
+                        None if items.contains(&item) => {}
+                        None => items.push(item),
+                    }
+                }
+            }
+        },
+        (base, overlay) => {
+            if *base == overlay {
+                return;
+            }
+            *base = match &strategy.on_conflict {
+                Some(resolve) => resolve(path, base, &overlay),
+                None => overlay,
+            };
+        }
+    }
+}
+
+/// The last `key` member of an object item.
+fn member<'a>(item: &'a JsonValue, key: &str) -> Option<&'a JsonValue> {
+    match item {
+        JsonValue::Object(entries) => entries
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value),
+        _ => None,
+    }
+}
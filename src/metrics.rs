@@ -0,0 +1,56 @@
+//! Counters reported by the encoder and decoder, for wiring into Prometheus,
+//! statsd or any other metrics backend.
+//!
+//! ```
+//! use std::sync::Arc;
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//!
+//! use toon::metrics::Metrics;
+//!
+//! #[derive(Default)]
+//! struct RowCounter(AtomicUsize);
+//!
+//! impl Metrics for RowCounter {
+//!     fn rows_parsed(&self, count: usize) {
+//!         self.0.fetch_add(count, Ordering::Relaxed);
+//!     }
+//! }
+//!
+//! let counter = Arc::new(RowCounter::default());
+//! let options = toon::DecodeOptions::builder().metrics(counter.clone()).build();
+//! toon::try_decode("rows[2]{id}:\n  1\n  2", Some(options)).unwrap();
+//! assert_eq!(counter.0.load(Ordering::Relaxed), 2);
+//! ```
+
+use std::fmt;
+use std::panic::RefUnwindSafe;
+use std::sync::Arc;
+
+/// Receives counts as documents are encoded and decoded.
+///
+/// Every method defaults to doing nothing, so implementations override only
+/// the counters they export. Calls happen inline on the encoding or decoding
+/// thread; keep them cheap (an atomic add, a channel send). The
+/// `RefUnwindSafe` bound keeps options usable across `catch_unwind`.
+pub trait Metrics: Send + Sync + RefUnwindSafe {
+    /// A document decoded successfully.
+    fn document_decoded(&self) {}
+
+    /// `count` rows of one tabular array were decoded.
+    fn rows_parsed(&self, _count: usize) {}
+
+    /// `count` string values were written quoted while encoding.
+    fn strings_quoted(&self, _count: usize) {}
+
+    /// A decode reported `count` warnings.
+    fn warnings_emitted(&self, _count: usize) {}
+}
+
+/// Shared handle to a [`Metrics`] implementation, as stored in options.
+pub type MetricsHook = Arc<dyn Metrics>;
+
+impl fmt::Debug for dyn Metrics + '_ {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Metrics")
+    }
+}
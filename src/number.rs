@@ -0,0 +1,408 @@
+//! The number model: integers stay exact, everything else is `f64`.
+//!
+//! With the `bignum` feature, integers beyond 64 bits decode as
+//! [`Number::BigInt`] and decimals that `f64` cannot hold exactly as
+//! [`Number::BigDecimal`], so every numeric literal round-trips.
+//!
+//! ```
+//! use toon::{JsonValue, Number, StringOrNumberOrBoolOrNull};
+//!
+//! let value = toon::try_decode("id: 18446744073709551615\nratio: 0.5", None).unwrap();
+//! let JsonValue::Object(entries) = value else { unreachable!() };
+//! assert_eq!(
+//!     entries[0].1,
+//!     JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(Number::U64(u64::MAX)))
+//! );
+//! assert_eq!(
+//!     toon::encode(JsonValue::Object(entries), None),
+//!     "id: 18446744073709551615\nratio: 0.5"
+//! );
+//! ```
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+#[cfg(feature = "bignum")]
+use bigdecimal::{BigDecimal, ToPrimitive};
+#[cfg(feature = "bignum")]
+use num_bigint::BigInt;
+
+/// Largest decimal exponent kept as a [`Number::BigDecimal`]; beyond it the
+/// plain-notation output would be unreasonably long, so the literal falls
+/// back to `f64`.
+#[cfg(feature = "bignum")]
+const MAX_BIG_SCALE: i64 = 4096;
+
+/// A TOON number.
+///
+/// Integer literals decode as [`Number::I64`], or [`Number::U64`] above
+/// `i64::MAX`; literals with a fraction or exponent decode as
+/// [`Number::F64`].
+///
+/// Equality compares numeric values, so `I64(1) == U64(1) == F64(1.0)` and
+/// `F64(-0.0) == F64(0.0)`. Unlike `f64`, `NaN` equals `NaN`, which keeps
+/// equality reflexive so numbers are [`Eq`] and [`Hash`] and can key a map.
+#[derive(Debug, Clone)]
+pub enum Number {
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    /// A number kept as its source text, as decoded with
+    /// `preserve_number_literals`. It encodes verbatim and otherwise behaves
+    /// like the value [`Number::parse_literal`] reads from it.
+    Raw(String),
+    /// An integer outside the `i64` and `u64` ranges.
+    #[cfg(feature = "bignum")]
+    BigInt(Box<BigInt>),
+    /// A decimal that no `f64` represents exactly.
+    #[cfg(feature = "bignum")]
+    BigDecimal(Box<BigDecimal>),
+}
+
+impl Number {
+    /// Parse a numeric literal, keeping integers exact.
+    ///
+    /// With `bignum`, integers beyond 64 bits and decimals that `f64`
+    /// rounds stay exact, up to a decimal exponent of ±4096.
+    ///
+    /// The literal is not validated; text that is not a number yields `NaN`.
+    #[must_use]
+    pub fn parse_literal(literal: &str) -> Self {
+        if !literal.contains(['.', 'e', 'E']) {
+            if let Ok(value) = literal.parse::<i64>() {
+                return Self::I64(value);
+            }
+            if let Ok(value) = literal.parse::<u64>() {
+                return Self::U64(value);
+            }
+            #[cfg(feature = "bignum")]
+            if let Ok(value) = literal.parse::<BigInt>() {
+                return Self::BigInt(Box::new(value));
+            }
+        }
+        let value = literal.parse::<f64>().unwrap_or(f64::NAN);
+        #[cfg(feature = "bignum")]
+        if let Some(exact) = literal
+            .parse::<BigDecimal>()
+            .ok()
+            .filter(|exact| exact.as_bigint_and_exponent().1.abs() <= MAX_BIG_SCALE)
+        {
+            // Rust prints the shortest decimal that reads back as `value`, so
+            // this matches exactly when `value` loses nothing.
+            let fits = value.is_finite()
+                && value
+                    .to_string()
+                    .parse::<BigDecimal>()
+                    .is_ok_and(|shortest| shortest == exact);
+            if !fits {
+                return Self::BigDecimal(Box::new(exact));
+            }
+        }
+        Self::F64(if value == 0.0 { 0.0 } else { value })
+    }
+
+    /// The value as `f64`, rounding integers beyond 2^53.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss, clippy::missing_const_for_fn)]
+    pub fn as_f64(&self) -> f64 {
+        match self {
+            Self::I64(value) => *value as f64,
+            Self::U64(value) => *value as f64,
+            Self::F64(value) => *value,
+            Self::Raw(text) => Self::parse_literal(text).as_f64(),
+            #[cfg(feature = "bignum")]
+            Self::BigInt(value) => value.to_f64().unwrap_or(f64::NAN),
+            #[cfg(feature = "bignum")]
+            Self::BigDecimal(value) => value.to_f64().unwrap_or(f64::NAN),
+        }
+    }
+
+    /// The value as `i64`, if it is an integer in range.
+    #[must_use]
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::I64(value) => Some(*value),
+            Self::U64(value) => i64::try_from(*value).ok(),
+            Self::Raw(text) => Self::parse_literal(text).as_i64(),
+            #[cfg(feature = "bignum")]
+            Self::BigInt(value) => value.to_i64(),
+            #[cfg(feature = "bignum")]
+            Self::BigDecimal(_) => None,
+            Self::F64(_) => None,
+        }
+    }
+
+    /// The value as `u64`, if it is a non-negative integer in range.
+    #[must_use]
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Self::I64(value) => u64::try_from(*value).ok(),
+            Self::U64(value) => Some(*value),
+            Self::Raw(text) => Self::parse_literal(text).as_u64(),
+            #[cfg(feature = "bignum")]
+            Self::BigInt(value) => value.to_u64(),
+            #[cfg(feature = "bignum")]
+            Self::BigDecimal(_) => None,
+            Self::F64(_) => None,
+        }
+    }
+
+    /// The exact value as a [`BigDecimal`]; `None` for non-finite values.
+    #[cfg(feature = "bignum")]
+    #[must_use]
+    pub fn to_big_decimal(&self) -> Option<BigDecimal> {
+        match self {
+            Self::I64(value) => Some(BigDecimal::from(*value)),
+            Self::U64(value) => Some(BigDecimal::from(*value)),
+            Self::F64(value) => BigDecimal::try_from(*value).ok(),
+            Self::Raw(text) => Self::parse_literal(text).to_big_decimal(),
+            Self::BigInt(value) => Some(BigDecimal::from(BigInt::clone(value))),
+            Self::BigDecimal(value) => Some(BigDecimal::clone(value)),
+        }
+    }
+
+    /// Whether this is an integer variant rather than a decimal one, or raw
+    /// text that reads as one.
+    #[must_use]
+    pub fn is_integer(&self) -> bool {
+        match self {
+            Self::I64(_) | Self::U64(_) => true,
+            Self::Raw(text) => Self::parse_literal(text).is_integer(),
+            #[cfg(feature = "bignum")]
+            Self::BigInt(_) => true,
+            #[cfg(feature = "bignum")]
+            Self::BigDecimal(_) => false,
+            Self::F64(_) => false,
+        }
+    }
+
+    /// Whether the value is neither infinite nor `NaN`.
+    #[must_use]
+    pub fn is_finite(&self) -> bool {
+        match self {
+            Self::F64(value) => value.is_finite(),
+            Self::Raw(text) => Self::parse_literal(text).is_finite(),
+            #[cfg(feature = "bignum")]
+            Self::BigInt(_) | Self::BigDecimal(_) => true,
+            Self::I64(_) | Self::U64(_) => true,
+        }
+    }
+
+    /// The value as a `serde_json` number; `None` for non-finite values.
+    ///
+    /// `serde_json` holds big values exactly only with its
+    /// `arbitrary_precision` feature, which this crate leaves off; otherwise
+    /// they round to the nearest `f64`. [`to_json_text`](Self::to_json_text)
+    /// is always exact.
+    #[must_use]
+    pub fn to_json_number(&self) -> Option<serde_json::Number> {
+        match self {
+            Self::I64(value) => Some((*value).into()),
+            Self::U64(value) => Some((*value).into()),
+            Self::F64(value) => serde_json::Number::from_f64(*value),
+            Self::Raw(text) => Self::parse_literal(text).to_json_number(),
+            #[cfg(feature = "bignum")]
+            Self::BigInt(_) | Self::BigDecimal(_) => {
+                // Without `arbitrary_precision`, `serde_json` reads long
+                // literals as `f64`, not always the nearest one.
+                let text = self.to_string();
+                match text.parse::<serde_json::Number>() {
+                    Ok(number) if number.to_string() == text => Some(number),
+                    _ => serde_json::Number::from_f64(self.as_f64()),
+                }
+            }
+        }
+    }
+
+    /// The value as JSON number text, digit for digit for big values; `None`
+    /// for non-finite values.
+    #[must_use]
+    pub fn to_json_text(&self) -> Option<String> {
+        match self {
+            Self::Raw(text) => Self::parse_literal(text).to_json_text(),
+            #[cfg(feature = "bignum")]
+            Self::BigInt(_) | Self::BigDecimal(_) => Some(self.to_string()),
+            _ => self.to_json_number().map(|number| number.to_string()),
+        }
+    }
+
+    fn as_i128(&self) -> Option<i128> {
+        match self {
+            Self::I64(value) => Some(i128::from(*value)),
+            Self::U64(value) => Some(i128::from(*value)),
+            Self::Raw(text) => Self::parse_literal(text).as_i128(),
+            #[cfg(feature = "bignum")]
+            Self::BigInt(value) => value.to_i128(),
+            #[cfg(feature = "bignum")]
+            Self::BigDecimal(_) => None,
+            Self::F64(_) => None,
+        }
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::float_cmp)]
+fn int_eq_f64(int: i128, float: f64) -> bool {
+    // Every whole f64 below 2^127 converts to i128 exactly.
+    float.fract() == 0.0 && float.abs() < 2f64.powi(127) && float as i128 == int
+}
+
+impl PartialEq for Number {
+    #[allow(clippy::float_cmp)]
+    fn eq(&self, other: &Self) -> bool {
+        if let Self::Raw(text) = self {
+            return Self::parse_literal(text) == *other;
+        }
+        if let Self::Raw(text) = other {
+            return *self == Self::parse_literal(text);
+        }
+        #[cfg(feature = "bignum")]
+        if matches!(self, Self::BigInt(_) | Self::BigDecimal(_))
+            || matches!(other, Self::BigInt(_) | Self::BigDecimal(_))
+        {
+            return self
+                .to_big_decimal()
+                .zip(other.to_big_decimal())
+                .is_some_and(|(a, b)| a == b);
+        }
+        match (self.as_i128(), other.as_i128()) {
+            (Some(a), Some(b)) => a == b,
+            (Some(int), None) => int_eq_f64(int, other.as_f64()),
+            (None, Some(int)) => int_eq_f64(int, self.as_f64()),
+            (None, None) => {
+                let (a, b) = (self.as_f64(), other.as_f64());
+                a == b || (a.is_nan() && b.is_nan())
+            }
+        }
+    }
+}
+
+impl Eq for Number {}
+
+/// Hashes by value, agreeing with equality: whole numbers within `i128` hash
+/// as integers, so `2` and `2.0` agree, and anything else by its `f64` bits.
+impl Hash for Number {
+    #[allow(clippy::cast_possible_truncation)]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let integer = self
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| self.as_u64().map(i128::from));
+        let value = self.as_f64();
+        match integer {
+            Some(integer) => (0u8, integer).hash(state),
+            None if value.fract() == 0.0 && value.abs() < 2f64.powi(127) => {
+                (0u8, value as i128).hash(state);
+            }
+            None if value.is_nan() => 1u8.hash(state),
+            None => (2u8, value.to_bits()).hash(state),
+        }
+    }
+}
+
+/// Formats as TOON writes the number: integers in full, `-0.0` as `0`,
+/// raw numbers verbatim, non-finite values as Rust prints them.
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::I64(value) => value.fmt(f),
+            Self::U64(value) => value.fmt(f),
+            Self::F64(value) if *value == 0.0 => f.write_str("0"),
+            Self::F64(value) => value.fmt(f),
+            Self::Raw(text) => f.write_str(text),
+            #[cfg(feature = "bignum")]
+            Self::BigInt(value) => value.fmt(f),
+            #[cfg(feature = "bignum")]
+            Self::BigDecimal(value) => f.write_str(&value.normalized().to_plain_string()),
+        }
+    }
+}
+
+impl From<i64> for Number {
+    fn from(value: i64) -> Self {
+        Self::I64(value)
+    }
+}
+
+impl From<i32> for Number {
+    fn from(value: i32) -> Self {
+        Self::I64(i64::from(value))
+    }
+}
+
+impl From<u64> for Number {
+    fn from(value: u64) -> Self {
+        i64::try_from(value).map_or(Self::U64(value), Self::I64)
+    }
+}
+
+impl From<u32> for Number {
+    fn from(value: u32) -> Self {
+        Self::I64(i64::from(value))
+    }
+}
+
+/// Integers beyond 64 bits become [`Number::BigInt`] with `bignum`, and
+/// the nearest `f64` without it.
+impl From<i128> for Number {
+    #[allow(clippy::cast_precision_loss)]
+    fn from(value: i128) -> Self {
+        if let Ok(int) = i64::try_from(value) {
+            return Self::I64(int);
+        }
+        if let Ok(int) = u64::try_from(value) {
+            return Self::U64(int);
+        }
+        #[cfg(feature = "bignum")]
+        return Self::BigInt(Box::new(BigInt::from(value)));
+        #[cfg(not(feature = "bignum"))]
+        Self::F64(value as f64)
+    }
+}
+
+/// See the `i128` conversion.
+impl From<u128> for Number {
+    #[allow(clippy::cast_precision_loss)]
+    fn from(value: u128) -> Self {
+        if let Ok(int) = u64::try_from(value) {
+            return Self::from(int);
+        }
+        #[cfg(feature = "bignum")]
+        return Self::BigInt(Box::new(BigInt::from(value)));
+        #[cfg(not(feature = "bignum"))]
+        Self::F64(value as f64)
+    }
+}
+
+impl From<f64> for Number {
+    fn from(value: f64) -> Self {
+        Self::F64(value)
+    }
+}
+
+#[cfg(feature = "bignum")]
+impl From<BigInt> for Number {
+    fn from(value: BigInt) -> Self {
+        match (value.to_i64(), value.to_u64()) {
+            (Some(int), _) => Self::I64(int),
+            (None, Some(int)) => Self::U64(int),
+            (None, None) => Self::BigInt(Box::new(value)),
+        }
+    }
+}
+
+#[cfg(feature = "bignum")]
+impl From<BigDecimal> for Number {
+    fn from(value: BigDecimal) -> Self {
+        Self::BigDecimal(Box::new(value))
+    }
+}
+
+impl From<&serde_json::Number> for Number {
+    fn from(value: &serde_json::Number) -> Self {
+        match (value.as_i64(), value.as_u64()) {
+            (Some(int), _) => Self::I64(int),
+            (None, Some(int)) => Self::U64(int),
+            (None, None) => Self::parse_literal(&value.to_string()),
+        }
+    }
+}
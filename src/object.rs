@@ -0,0 +1,225 @@
+//! In-place editing of object members, in insertion order.
+//!
+//! Objects are plain `Vec<(String, JsonValue)>` lists, so a key may repeat;
+//! lookups here act on the last entry for a key, as JSON parsers keep it,
+//! and [`JsonValue::remove`] drops every entry for the key.
+
+use crate::{JsonObject, JsonValue, StringOrNumberOrBoolOrNull};
+
+impl JsonValue {
+    /// Set `key` to `value`, returning the previous value. An existing key
+    /// keeps its position; a new one is appended. A `null` becomes an empty
+    /// object first.
+    ///
+    /// ```
+    /// let mut value = toon::decode("a: 1\nb: 2", None);
+    /// assert_eq!(value.insert("a", 3.into()), Some(1.into()));
+    /// value.insert("c", 4.into());
+    /// assert_eq!(toon::encode(value, None), "a: 3\nb: 2\nc: 4");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is neither an object nor `null`.
+    pub fn insert(&mut self, key: impl Into<String>, value: Self) -> Option<Self> {
+        match self.entry(key) {
+            Entry::Occupied(mut entry) => Some(entry.insert(value)),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                None
+            }
+        }
+    }
+
+    /// Remove every entry for `key`, returning the last one's value. Other
+    /// members keep their order. Values that are not objects are unchanged.
+    pub fn remove(&mut self, key: &str) -> Option<Self> {
+        let Self::Object(entries) = self else {
+            return None;
+        };
+        let mut removed = None;
+        entries.retain_mut(|(k, value)| {
+            if k == key {
+                removed = Some(std::mem::replace(value, Self::null()));
+                false
+            } else {
+                true
+            }
+        });
+        removed
+    }
+
+    /// Keep only the members for which `keep` returns `true`. Values that
+    /// are not objects are unchanged.
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&str, &mut Self) -> bool,
+    {
+        if let Self::Object(entries) = self {
+            entries.retain_mut(|(key, value)| keep(key, value));
+        }
+    }
+
+    /// Object keys in insertion order; empty for other values.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.members().iter().map(|(key, _)| key.as_str())
+    }
+
+    /// Object member values in insertion order; empty for other values.
+    pub fn values(&self) -> impl Iterator<Item = &Self> {
+        self.members().iter().map(|(_, value)| value)
+    }
+
+    /// Mutable object member values in insertion order; empty for other
+    /// values.
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut Self> {
+        let entries = match self {
+            Self::Object(entries) => entries.as_mut_slice(),
+            _ => &mut [],
+        };
+        entries.iter_mut().map(|(_, value)| value)
+    }
+
+    /// The entry for `key`, for in-place insert-or-update. A `null` becomes
+    /// an empty object first.
+    ///
+    /// ```
+    /// use toon::JsonValue;
+    ///
+    /// let mut teams = JsonValue::Object(Vec::new());
+    /// for (team, name) in [("red", "Ada"), ("blue", "Bob"), ("red", "Cy")] {
+    ///     if let JsonValue::Array(members) = teams.entry(team).or_insert(JsonValue::Array(Vec::new())) {
+    ///         members.push(name.into());
+    ///     }
+    /// }
+    /// assert_eq!(toon::encode(teams, None), "red[2]: Ada,Cy\nblue[1]: Bob");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is neither an object nor `null`.
+    pub fn entry(&mut self, key: impl Into<String>) -> Entry<'_> {
+        if matches!(self, Self::Primitive(StringOrNumberOrBoolOrNull::Null)) {
+            *self = Self::Object(Vec::new());
+        }
+        let Self::Object(entries) = self else {
+            panic!("cannot edit members of a JSON value that is not an object");
+        };
+        let key = key.into();
+        match entries.iter().rposition(|(k, _)| *k == key) {
+            Some(index) => Entry::Occupied(OccupiedEntry { entries, index }),
+            None => Entry::Vacant(VacantEntry { entries, key }),
+        }
+    }
+
+    fn members(&self) -> &[(String, Self)] {
+        match self {
+            Self::Object(entries) => entries,
+            _ => &[],
+        }
+    }
+
+    const fn null() -> Self {
+        Self::Primitive(StringOrNumberOrBoolOrNull::Null)
+    }
+}
+
+/// A member slot returned by [`JsonValue::entry`].
+pub enum Entry<'a> {
+    Occupied(OccupiedEntry<'a>),
+    Vacant(VacantEntry<'a>),
+}
+
+/// An existing member (the last one, where the key repeats).
+pub struct OccupiedEntry<'a> {
+    entries: &'a mut JsonObject,
+    index: usize,
+}
+
+/// A key not yet present.
+pub struct VacantEntry<'a> {
+    entries: &'a mut JsonObject,
+    key: String,
+}
+
+impl<'a> Entry<'a> {
+    #[must_use]
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Occupied(entry) => entry.key(),
+            Self::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// The existing value, or `default` appended under the key.
+    #[allow(clippy::must_use_candidate)]
+    pub fn or_insert(self, default: JsonValue) -> &'a mut JsonValue {
+        self.or_insert_with(|| default)
+    }
+
+    /// The existing value, or the result of `default` appended under the key.
+    pub fn or_insert_with<F: FnOnce() -> JsonValue>(self, default: F) -> &'a mut JsonValue {
+        match self {
+            Self::Occupied(entry) => entry.into_mut(),
+            Self::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Run `f` on an existing value; vacant entries pass through.
+    #[must_use]
+    pub fn and_modify<F: FnOnce(&mut JsonValue)>(mut self, f: F) -> Self {
+        if let Self::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a> OccupiedEntry<'a> {
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.entries[self.index].0
+    }
+
+    #[must_use]
+    pub fn get(&self) -> &JsonValue {
+        &self.entries[self.index].1
+    }
+
+    pub fn get_mut(&mut self) -> &mut JsonValue {
+        &mut self.entries[self.index].1
+    }
+
+    /// The value, borrowed for as long as the object.
+    #[must_use]
+    pub fn into_mut(self) -> &'a mut JsonValue {
+        &mut self.entries[self.index].1
+    }
+
+    /// Replace the value, returning the old one.
+    pub fn insert(&mut self, value: JsonValue) -> JsonValue {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    /// Remove this member (other entries for the key stay), returning its
+    /// value.
+    #[must_use]
+    pub fn remove(self) -> JsonValue {
+        self.entries.remove(self.index).1
+    }
+}
+
+impl<'a> VacantEntry<'a> {
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Append the member, returning its value.
+    #[allow(clippy::must_use_candidate)]
+    pub fn insert(self, value: JsonValue) -> &'a mut JsonValue {
+        let index = self.entries.len();
+        self.entries.push((self.key, value));
+        &mut self.entries[index].1
+    }
+}
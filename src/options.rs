@@ -1,43 +1,748 @@
+use std::cmp::Ordering;
 use std::sync::Arc;
 
 use crate::JsonValue;
+use crate::decode::warnings::WarningSink;
+use crate::metrics::MetricsHook;
 use crate::shared::constants::DEFAULT_DELIMITER;
+use crate::shared::validation::is_supported_delimiter;
 
+/// Rewrites values before encoding, see [`EncodeOptions::replacer`].
+///
+/// Called with the key (an array index as text, `""` for the root), the
+/// value, and the full path from the root, whose length is the depth. A
+/// parent is called before its children, and the children visited are those
+/// of the returned value; returning `None` drops the entry.
+///
+/// ```
+/// use std::sync::Arc;
+/// use toon::options::PathSegment;
+/// use toon::{EncodeOptions, JsonValue, encode};
+///
+/// // Redact everything under `credentials`, at any depth.
+/// let redact = Arc::new(|_key: &str, value: &JsonValue, path: &[PathSegment]| {
+///     match path {
+///         [PathSegment::Key(root), _] if root == "credentials" => Some(JsonValue::from("***")),
+///         _ => Some(value.clone()),
+///     }
+/// });
+/// let value = serde_json::json!({"user": "ada", "credentials": {"token": "t1", "keys": [1, 2]}});
+/// let options = EncodeOptions::builder().replacer(redact).build();
+/// assert_eq!(
+///     encode(value, Some(options)),
+///     "user: ada\ncredentials:\n  token: ***\n  keys: ***"
+/// );
+/// ```
 pub type EncodeReplacer =
     Arc<dyn Fn(&str, &JsonValue, &[PathSegment]) -> Option<JsonValue> + Send + Sync>;
 
-#[derive(Clone)]
+/// Order of object keys written with [`EncodeOptions::sort_keys`].
+pub type KeyComparator = Arc<dyn Fn(&str, &str) -> Ordering + Send + Sync>;
+
+/// Encoder settings; every field left `None` takes its default.
+///
+/// Build with [`EncodeOptions::builder`] or start from a preset such as
+/// [`EncodeOptions::compact`]; the struct is `#[non_exhaustive]` so new
+/// options can be added without breaking callers.
+///
+/// ```
+/// use toon::EncodeOptions;
+///
+/// let options = EncodeOptions::builder().indent(4).delimiter('|').build();
+/// assert_eq!(toon::encode(toon::toon_value!({"a": {"b": [1, 2]}}), Some(options)), "a:\n    b[2|]: 1|2");
+/// ```
+#[derive(Clone, Default)]
+#[non_exhaustive]
 pub struct EncodeOptions {
     pub indent: Option<usize>,
+    /// Array delimiter: `,` (default), `\t`, `|`, or `;`. [`encode`] falls
+    /// back to the default for any other character, as it does for
+    /// [`delimiter_overrides`](Self::delimiter_overrides);
+    /// [`try_encode`] rejects it.
+    ///
+    /// [`encode`]: crate::encode
+    /// [`try_encode`]: crate::try_encode
     pub delimiter: Option<char>,
     pub key_folding: Option<KeyFoldingMode>,
     pub flatten_depth: Option<usize>,
+    /// Rewrites or drops values by key, value and path before encoding.
     pub replacer: Option<EncodeReplacer>,
+    /// Delimiters for specific arrays, keyed by the dotted key path from the
+    /// root (e.g. `data.rows`). Array elements add nothing to the path, so
+    /// `items.tags` names the `tags` array of every object in `items`. An
+    /// override applies to that array alone: arrays nested inside it use
+    /// [`delimiter`](Self::delimiter) unless they have their own. The
+    /// delimiter is written into the array header, so decoders pick it up
+    /// without extra configuration.
+    pub delimiter_overrides: Option<Vec<(String, char)>>,
+    /// Maximum levels of array/object nesting accepted by
+    /// [`try_encode`](crate::encode::try_encode) (`{"a": []}` has two).
+    /// [`encode`](crate::encode::encode) does not check it.
+    pub max_depth: Option<usize>,
+    /// What to write for `NaN` and `±Infinity`. Defaults to `Null` for
+    /// [`encode`](crate::encode::encode) and `Error` for
+    /// [`try_encode`](crate::encode::try_encode).
+    pub on_non_finite: Option<NonFinitePolicy>,
+    /// Which array headers carry their `[N]` length. Defaults to `AllArrays`.
+    pub length_markers: Option<LengthMarkers>,
+    /// Counters for quoted strings, reported by the whole-document encoders.
+    pub metrics: Option<MetricsHook>,
+    /// Write the keys of every object in sorted order, so the output does not
+    /// depend on insertion order. Defaults to `false`.
+    pub sort_keys: Option<bool>,
+    /// Order of keys under `sort_keys`. Defaults to byte order.
+    pub key_comparator: Option<KeyComparator>,
+    /// Which strings are quoted even when they could be written bare.
+    /// Defaults to `Minimal`.
+    pub quote_strings: Option<QuotePolicy>,
+    /// Fewest rows an array of objects needs to be written as a table;
+    /// shorter arrays use the list form. Defaults to 1.
+    pub tabular_min_rows: Option<usize>,
+    /// Most columns a table may have; wider arrays use the list form.
+    /// Defaults to no limit.
+    pub tabular_max_columns: Option<usize>,
+    /// Write arrays of uniform objects as tables. With `false` every array
+    /// of objects is written as list items. Defaults to `true`.
+    pub tabular: Option<bool>,
+    /// Widest line, in characters, before the values of an inline array
+    /// wrap onto continuation lines one level deeper. Wrapped arrays keep
+    /// their `[N]` whatever `length_markers` says, since the decoder reads
+    /// continuation lines until it has `N` values. Defaults to no wrapping.
+    pub max_line_width: Option<usize>,
+    /// Indent with `indent` spaces per level, or one tab. Defaults to
+    /// `Spaces`.
+    pub indent_style: Option<IndentStyle>,
+    /// What separates lines in the encoded text. Defaults to `Lf`. Decoders
+    /// read either.
+    pub line_ending: Option<LineEnding>,
+    /// Let key folding continue through an array holding a single one-key
+    /// object, writing `{"a": [{"b": 1}]}` as `a[0].b: 1`. Defaults to
+    /// `false`; decode with [`ExpandPathsMode::Indexed`] or `Aggressive`.
+    pub fold_single_item_arrays: Option<bool>,
+}
+
+impl EncodeOptions {
+    /// A builder starting from the defaults.
+    pub fn builder() -> EncodeOptionsBuilder {
+        EncodeOptionsBuilder::default()
+    }
+
+    /// A builder starting from these options, to adjust a preset, e.g.
+    /// `EncodeOptions::compact().into_builder().indent(4).build()`.
+    pub const fn into_builder(self) -> EncodeOptionsBuilder {
+        EncodeOptionsBuilder { options: self }
+    }
+
+    /// [`Profile::Compact`]: folded key chains.
+    #[must_use]
+    pub fn compact() -> Self {
+        Self::profile(Profile::Compact)
+    }
+
+    /// [`Profile::Readable`]: the defaults, spelled out.
+    #[must_use]
+    pub fn readable() -> Self {
+        Self::profile(Profile::Readable)
+    }
+
+    /// [`Profile::LlmOptimized`]: tab delimiters and folded keys.
+    #[must_use]
+    pub fn llm_optimized() -> Self {
+        Self::profile(Profile::LlmOptimized)
+    }
+
+    /// Options bundled for a use case; adjust individual fields with
+    /// [`into_builder`](Self::into_builder).
+    #[must_use]
+    pub fn profile(profile: Profile) -> Self {
+        let (delimiter, key_folding, length_markers) = match profile {
+            Profile::Compact => (',', KeyFoldingMode::Safe, LengthMarkers::AllArrays),
+            Profile::Readable | Profile::Canonical => {
+                (',', KeyFoldingMode::Off, LengthMarkers::AllArrays)
+            }
+            Profile::LlmOptimized => ('\t', KeyFoldingMode::Safe, LengthMarkers::AllArrays),
+        };
+        Self {
+            indent: Some(2),
+            delimiter: Some(delimiter),
+            key_folding: Some(key_folding),
+            length_markers: Some(length_markers),
+            sort_keys: (profile == Profile::Canonical).then_some(true),
+            ..Self::default()
+        }
+    }
+}
+
+/// Chained setters for [`EncodeOptions`], from [`EncodeOptions::builder`].
+/// Each setter fills the field of the same name.
+#[derive(Clone, Default)]
+#[must_use]
+pub struct EncodeOptionsBuilder {
+    options: EncodeOptions,
+}
+
+impl EncodeOptionsBuilder {
+    pub const fn indent(mut self, indent: usize) -> Self {
+        self.options.indent = Some(indent);
+        self
+    }
+
+    pub const fn delimiter(mut self, delimiter: char) -> Self {
+        self.options.delimiter = Some(delimiter);
+        self
+    }
+
+    pub const fn key_folding(mut self, key_folding: KeyFoldingMode) -> Self {
+        self.options.key_folding = Some(key_folding);
+        self
+    }
+
+    pub const fn flatten_depth(mut self, flatten_depth: usize) -> Self {
+        self.options.flatten_depth = Some(flatten_depth);
+        self
+    }
+
+    pub fn replacer(mut self, replacer: EncodeReplacer) -> Self {
+        self.options.replacer = Some(replacer);
+        self
+    }
+
+    /// Add one entry to [`EncodeOptions::delimiter_overrides`].
+    pub fn delimiter_override(mut self, path: impl Into<String>, delimiter: char) -> Self {
+        self.options
+            .delimiter_overrides
+            .get_or_insert_with(Vec::new)
+            .push((path.into(), delimiter));
+        self
+    }
+
+    pub const fn max_depth(mut self, max_depth: usize) -> Self {
+        self.options.max_depth = Some(max_depth);
+        self
+    }
+
+    pub const fn on_non_finite(mut self, policy: NonFinitePolicy) -> Self {
+        self.options.on_non_finite = Some(policy);
+        self
+    }
+
+    pub const fn length_markers(mut self, length_markers: LengthMarkers) -> Self {
+        self.options.length_markers = Some(length_markers);
+        self
+    }
+
+    pub fn metrics(mut self, metrics: MetricsHook) -> Self {
+        self.options.metrics = Some(metrics);
+        self
+    }
+
+    pub const fn sort_keys(mut self, sort_keys: bool) -> Self {
+        self.options.sort_keys = Some(sort_keys);
+        self
+    }
+
+    pub fn key_comparator(mut self, comparator: KeyComparator) -> Self {
+        self.options.key_comparator = Some(comparator);
+        self
+    }
+
+    pub const fn quote_strings(mut self, policy: QuotePolicy) -> Self {
+        self.options.quote_strings = Some(policy);
+        self
+    }
+
+    pub const fn tabular_min_rows(mut self, rows: usize) -> Self {
+        self.options.tabular_min_rows = Some(rows);
+        self
+    }
+
+    pub const fn tabular_max_columns(mut self, columns: usize) -> Self {
+        self.options.tabular_max_columns = Some(columns);
+        self
+    }
+
+    pub const fn tabular(mut self, tabular: bool) -> Self {
+        self.options.tabular = Some(tabular);
+        self
+    }
+
+    pub const fn max_line_width(mut self, width: usize) -> Self {
+        self.options.max_line_width = Some(width);
+        self
+    }
+
+    pub const fn indent_style(mut self, style: IndentStyle) -> Self {
+        self.options.indent_style = Some(style);
+        self
+    }
+
+    pub const fn line_ending(mut self, ending: LineEnding) -> Self {
+        self.options.line_ending = Some(ending);
+        self
+    }
+
+    pub const fn fold_single_item_arrays(mut self, enabled: bool) -> Self {
+        self.options.fold_single_item_arrays = Some(enabled);
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> EncodeOptions {
+        self.options
+    }
+}
+
+/// Coherent encoder settings for common uses, see [`EncodeOptions::profile`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    /// Fewest tokens: folded key chains, comma delimiters. Every length is
+    /// still written, since the default decoder reads `tags[]: a,b` as a
+    /// key `tags[]` holding a string.
+    Compact,
+    /// The defaults: nested keys, comma delimiters, every length written.
+    Readable,
+    /// Tab delimiters, which tokenizers keep apart from cell text, and folded
+    /// key chains; every length is written so a model can check counts.
+    LlmOptimized,
+    /// One spelling per document: keys sorted, no folding, comma delimiters,
+    /// every length written. Numbers and duplicate keys are left as given;
+    /// [`encode_canonical`](crate::encode_canonical) also normalizes those.
+    Canonical,
+}
+
+/// Which array headers carry their length, trading self-description for tokens.
+///
+/// Headers without a length are written `key[]:`; decoders read them back
+/// with [`Strictness::length_markers`] off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthMarkers {
+    /// Only tabular arrays (`key[N]{a,b}:`) carry a length.
+    TabularOnly,
+    /// Every array carries a length.
+    AllArrays,
+    /// No array carries a length.
+    Never,
+}
+
+/// Which strings the encoder quotes, see [`EncodeOptions::quote_strings`].
+///
+/// Quoting more than needed never changes the decoded value; it trades a
+/// few tokens for output that does not depend on the bare-string rules.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QuotePolicy {
+    /// Quote only strings that would otherwise read as another value or
+    /// break the line: `"true"`, `"42"`, `"a,b"`, `""`.
+    #[default]
+    Minimal,
+    /// Quote every string value, including array items and table cells.
+    AlwaysStrings,
+    /// Also quote every key and table field name. `Safe` key folding is off
+    /// under this policy, since a quoted `"a.b"` is a literal key;
+    /// `Aggressive` folding quotes each segment, as `"a"."b"`.
+    AlwaysKeysAndStrings,
+}
+
+impl QuotePolicy {
+    /// Whether every string value is quoted.
+    #[must_use]
+    pub const fn quotes_strings(self) -> bool {
+        !matches!(self, Self::Minimal)
+    }
+
+    /// Whether every key is quoted.
+    #[must_use]
+    pub const fn quotes_keys(self) -> bool {
+        matches!(self, Self::AlwaysKeysAndStrings)
+    }
+}
+
+/// What one level of encoded indentation is, see
+/// [`EncodeOptions::indent_style`].
+///
+/// Tab-indented documents decode with [`Strictness::tabs`] off, which reads
+/// each leading tab as one level.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IndentStyle {
+    /// `indent` spaces per level.
+    #[default]
+    Spaces,
+    /// One tab per level; `indent` is ignored.
+    Tabs,
+}
+
+/// Line separator of encoded text, see [`EncodeOptions::line_ending`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`.
+    #[default]
+    Lf,
+    /// `\r\n`, for Windows tools.
+    CrLf,
+}
+
+impl LineEnding {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+        }
+    }
+}
+
+/// Encoding of numbers that JSON and TOON cannot represent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonFinitePolicy {
+    /// Write `null`.
+    Null,
+    /// Fail the encode: `try_encode` returns an error and `encode` panics.
+    Error,
+    /// Write the strings `NaN`, `Infinity` and `-Infinity`.
+    StringLiteral,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum KeyFoldingMode {
     Off,
     Safe,
+    /// Like `Safe`, but segments that cannot be written bare are quoted
+    /// instead of stopping the fold: `{"a.b": {"c": 1}}` becomes
+    /// `"a.b".c: 1`. Each quoted segment is escaped like a quoted key, and
+    /// [`ExpandPathsMode::Aggressive`] reads such keys back. Unfolded keys
+    /// that are not one identifier segment are quoted too, so a literal
+    /// `{"a.b": 1}` is written `"a.b": 1`.
+    Aggressive,
 }
 
-#[derive(Debug, Clone)]
+/// Decoder settings; every field left `None` takes its default.
+///
+/// Build with [`DecodeOptions::builder`] or start from
+/// [`DecodeOptions::strict`] or [`DecodeOptions::lenient`]; the struct is
+/// `#[non_exhaustive]` so new options can be added without breaking callers.
+///
+/// ```
+/// use toon::{DecodeOptions, options::ExpandPathsMode};
+///
+/// let options = DecodeOptions::builder().expand_paths(ExpandPathsMode::Safe).build();
+/// assert_eq!(toon::decode("a.b: 1", Some(options)), toon::toon_value!({"a": {"b": 1}}));
+/// assert!(toon::try_decode("a: 1\na: 2", Some(DecodeOptions::strict())).is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
 pub struct DecodeOptions {
     pub indent: Option<usize>,
-    pub strict: Option<bool>,
+    /// Which checks reject input. Defaults to [`Strictness::default`].
+    pub strictness: Option<Strictness>,
     pub expand_paths: Option<ExpandPathsMode>,
+    /// How path expansion resolves collisions. Defaults to `Error`, or
+    /// `Merge` when every strictness check is off.
+    pub on_conflict: Option<ExpandConflictPolicy>,
+    pub key_order: Option<KeyOrder>,
+    /// How a key repeated within one object resolves. This is the only
+    /// duplicate-key setting: an explicit policy always applies, and
+    /// `strictness.duplicate_keys` merely selects the default, `Error` when
+    /// set and otherwise keeping every entry in document order. So
+    /// `LastWins` with [`DecodeOptions::strict`] accepts repeated keys.
+    pub duplicate_keys: Option<DuplicateKeyPolicy>,
+    /// Fail on array headers without a valid `[N]` length, such as
+    /// `items[]:`. Otherwise they are read as plain keys, or with
+    /// `strictness.length_markers` off as arrays whose items are counted.
+    /// Defaults to `false`.
+    pub require_length_markers: Option<bool>,
+    /// Reject any line longer than this many bytes before copying it, to
+    /// bound memory on untrusted input. Defaults to no limit.
+    pub max_line_length: Option<usize>,
+    /// Bounds on the decoded document, checked as it is decoded so that
+    /// attacker-controlled input fails early. Defaults to no limits.
+    pub limits: Option<DecodeLimits>,
+    /// Keep each number as its source text in a [`Number::Raw`], so `1.50`,
+    /// `1e3` and `0.1` re-encode byte for byte. Defaults to `false`.
+    ///
+    /// [`Number::Raw`]: crate::Number::Raw
+    pub preserve_number_literals: Option<bool>,
+    /// Counters for decoded documents, tabular rows and warnings, reported
+    /// by the synchronous decoders.
+    pub metrics: Option<MetricsHook>,
+    /// Append non-fatal [`DecodeWarning`](crate::DecodeWarning)s to this
+    /// sink: ambiguous values, missing or mismatched lengths and tab
+    /// indentation accepted by a relaxed [`Strictness`], and trailing
+    /// whitespace. Reported by the synchronous decoders;
+    /// [`try_decode_with_warnings`](crate::try_decode_with_warnings) sets a
+    /// fresh sink and returns its warnings.
+    pub collect_warnings: Option<WarningSink>,
+}
+
+impl DecodeOptions {
+    /// A builder starting from the defaults.
+    pub fn builder() -> DecodeOptionsBuilder {
+        DecodeOptionsBuilder::default()
+    }
+
+    /// A builder starting from these options, e.g.
+    /// `DecodeOptions::lenient().into_builder().indent(4).build()`.
+    pub const fn into_builder(self) -> DecodeOptionsBuilder {
+        DecodeOptionsBuilder { options: self }
+    }
+
+    /// Every [`Strictness`] check on, duplicate keys included.
+    #[must_use]
+    pub fn strict() -> Self {
+        Self::builder().strictness(Strictness::strict()).build()
+    }
+
+    /// Every [`Strictness`] check off, so path expansion also merges
+    /// collisions instead of failing.
+    #[must_use]
+    pub fn lenient() -> Self {
+        Self::builder().strictness(Strictness::lenient()).build()
+    }
+}
+
+/// Chained setters for [`DecodeOptions`], from [`DecodeOptions::builder`].
+/// Each setter fills the field of the same name.
+#[derive(Debug, Clone, Default)]
+#[must_use]
+pub struct DecodeOptionsBuilder {
+    options: DecodeOptions,
+}
+
+impl DecodeOptionsBuilder {
+    pub const fn indent(mut self, indent: usize) -> Self {
+        self.options.indent = Some(indent);
+        self
+    }
+
+    pub const fn strictness(mut self, strictness: Strictness) -> Self {
+        self.options.strictness = Some(strictness);
+        self
+    }
+
+    pub const fn expand_paths(mut self, expand_paths: ExpandPathsMode) -> Self {
+        self.options.expand_paths = Some(expand_paths);
+        self
+    }
+
+    pub const fn on_conflict(mut self, policy: ExpandConflictPolicy) -> Self {
+        self.options.on_conflict = Some(policy);
+        self
+    }
+
+    pub const fn key_order(mut self, key_order: KeyOrder) -> Self {
+        self.options.key_order = Some(key_order);
+        self
+    }
+
+    pub const fn duplicate_keys(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.options.duplicate_keys = Some(policy);
+        self
+    }
+
+    pub const fn require_length_markers(mut self, require: bool) -> Self {
+        self.options.require_length_markers = Some(require);
+        self
+    }
+
+    pub const fn max_line_length(mut self, max_line_length: usize) -> Self {
+        self.options.max_line_length = Some(max_line_length);
+        self
+    }
+
+    pub const fn limits(mut self, limits: DecodeLimits) -> Self {
+        self.options.limits = Some(limits);
+        self
+    }
+
+    pub const fn preserve_number_literals(mut self, preserve: bool) -> Self {
+        self.options.preserve_number_literals = Some(preserve);
+        self
+    }
+
+    pub fn metrics(mut self, metrics: MetricsHook) -> Self {
+        self.options.metrics = Some(metrics);
+        self
+    }
+
+    pub fn collect_warnings(mut self, sink: WarningSink) -> Self {
+        self.options.collect_warnings = Some(sink);
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> DecodeOptions {
+        self.options
+    }
+}
+
+/// Which decoder checks reject input, see [`DecodeOptions::strictness`].
+///
+/// New checks may be added, so build one from [`Strictness::strict`],
+/// [`Strictness::lenient`] or [`Strictness::default`] and adjust single
+/// checks on it. This keeps exact indentation but accepts arrays whose
+/// lengths are off:
+///
+/// ```
+/// use toon::options::{DecodeOptions, Strictness};
+///
+/// let mut strictness = Strictness::strict();
+/// strictness.length_markers = false;
+/// let options = DecodeOptions::builder().strictness(strictness).build();
+/// assert!(toon::try_decode("items[3]: a,b", Some(options)).is_ok());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+#[non_exhaustive]
+pub struct Strictness {
+    /// Indents must be exact multiples of the indent size, rather than
+    /// rounding down to the nearest depth.
+    pub indentation: bool,
+    /// Reject tabs in indentation. With this off, each leading tab counts as
+    /// one level, as [`IndentStyle::Tabs`] writes them.
+    pub tabs: bool,
+    /// Array items, rows and row values must match the declared `[N]`
+    /// length. Off, a header without a valid length is read as an array
+    /// whose length is counted from its items.
+    pub length_markers: bool,
+    /// Default [`DecodeOptions::duplicate_keys`] to `Error`, which rejects a
+    /// key repeated within one object, naming both lines.
+    pub duplicate_keys: bool,
+    /// Reject rows or items following the declared number of them.
+    pub trailing_content: bool,
+    /// Reject blank lines inside arrays.
+    pub blank_lines: bool,
+}
+
+impl Strictness {
+    /// Every check on.
+    #[must_use]
+    pub const fn strict() -> Self {
+        Self {
+            indentation: true,
+            tabs: true,
+            length_markers: true,
+            duplicate_keys: true,
+            trailing_content: true,
+            blank_lines: true,
+        }
+    }
+
+    /// Every check off.
+    #[must_use]
+    pub const fn lenient() -> Self {
+        Self {
+            indentation: false,
+            tabs: false,
+            length_markers: false,
+            duplicate_keys: false,
+            trailing_content: false,
+            blank_lines: false,
+        }
+    }
+}
+
+/// Every check but duplicate keys, which later keys are free to repeat.
+impl Default for Strictness {
+    fn default() -> Self {
+        Self {
+            duplicate_keys: false,
+            ..Self::strict()
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ExpandPathsMode {
     Off,
     Safe,
+    /// Like `Safe`, but numeric segments (`items.0.name`) and bracketed
+    /// indices (`items[0].name`) rebuild arrays instead of objects.
+    Indexed,
+    /// Like `Safe`, but also expands keys folded with quoted segments, such
+    /// as `"a.b".c`, taking each quoted segment as one key, and keys folded
+    /// through arrays, such as `a[0].b`. Other modes keep a key with quoted
+    /// segments as its literal text.
+    Aggressive,
+}
+
+/// Collision handling when an expanded path meets an existing value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpandConflictPolicy {
+    /// Objects merge; any other collision is an error.
+    Error,
+    /// Objects merge; otherwise the value seen first is kept.
+    KeepFirst,
+    /// The value seen last replaces the earlier one, objects included.
+    Overwrite,
+    /// Objects merge recursively; otherwise the value seen last wins.
+    Merge,
+    /// Objects merge recursively; otherwise a value written through a
+    /// dotted key beats one under a literal key, whichever came first.
+    /// Between two of the same kind the value seen last wins.
+    PreferFolded,
+    /// Like `PreferFolded`, but the value under the literal key wins.
+    PreferLiteral,
+}
+
+/// Bounds on a decoded document, see [`DecodeOptions::limits`]. Each is off
+/// when `None`.
+///
+/// The declared length of an array is checked before its items are read,
+/// so a header like `items[999999999]:` fails at once.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DecodeLimits {
+    /// Objects and arrays nested deeper than this fail; the root object or
+    /// array is at depth 1.
+    pub max_depth: Option<usize>,
+    /// Most objects, arrays and primitives in the document; keys do not
+    /// count.
+    pub max_nodes: Option<usize>,
+    /// Longest string value or key, in bytes after unescaping.
+    pub max_string_bytes: Option<usize>,
+    /// Largest declared `[N]` array length; also bounds the indices of
+    /// expanded paths such as `items.5`.
+    pub max_array_length: Option<usize>,
+}
+
+/// Resolution of a key repeated within one decoded object, see
+/// [`DecodeOptions::duplicate_keys`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKeyPolicy {
+    /// Fail the decode, naming the lines of both keys.
+    Error,
+    /// Keep the first value.
+    FirstWins,
+    /// Keep the last value, at the position of the first key.
+    LastWins,
+    /// Replace the values with an array of all of them in document order,
+    /// at the position of the first key.
+    CollectIntoArray,
+}
+
+/// Object key ordering in decoded values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyOrder {
+    /// Keys appear in source order.
+    Preserve,
+    /// Keys are sorted by byte order at every level, for deterministic output.
+    Sorted,
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct DecodeStreamOptions {
     pub indent: Option<usize>,
-    pub strict: Option<bool>,
+    /// Which checks reject input. Defaults to [`Strictness::default`].
+    pub strictness: Option<Strictness>,
+    /// Fail on array headers without a valid length. Defaults to `false`.
+    pub require_length_markers: Option<bool>,
+    /// Reject lines longer than this many bytes. Defaults to no limit.
+    pub max_line_length: Option<usize>,
+    /// Bounds on the decoded document. Defaults to no limits.
+    pub limits: Option<DecodeLimits>,
+    /// Keep numbers as their source text. Defaults to `false`.
+    pub preserve_number_literals: Option<bool>,
+    /// Counters for decoded documents, tabular rows and warnings.
+    pub metrics: Option<MetricsHook>,
+    /// Append non-fatal warnings to this sink.
+    pub collect_warnings: Option<WarningSink>,
 }
 
 #[derive(Clone)]
@@ -47,16 +752,79 @@ pub struct ResolvedEncodeOptions {
     pub key_folding: KeyFoldingMode,
     pub flatten_depth: usize,
     pub replacer: Option<EncodeReplacer>,
+    pub delimiter_overrides: Vec<(String, char)>,
+    pub max_depth: Option<usize>,
+    pub on_non_finite: NonFinitePolicy,
+    pub length_markers: LengthMarkers,
+    pub metrics: Option<MetricsHook>,
+    pub sort_keys: bool,
+    pub key_comparator: Option<KeyComparator>,
+    pub quote_strings: QuotePolicy,
+    pub tabular_min_rows: usize,
+    pub tabular_max_columns: usize,
+    pub tabular: bool,
+    pub max_line_width: Option<usize>,
+    pub indent_style: IndentStyle,
+    pub line_ending: LineEnding,
+    pub fold_single_item_arrays: bool,
+}
+
+impl ResolvedEncodeOptions {
+    /// Delimiter override for the array at `path`, if one was configured.
+    #[must_use]
+    pub fn delimiter_override(&self, path: &str) -> Option<char> {
+        self.delimiter_overrides
+            .iter()
+            .find(|(candidate, _)| candidate == path)
+            .map(|(_, delimiter)| *delimiter)
+    }
+
+    /// The length to write in an array header, if `length_markers` keeps one.
+    #[must_use]
+    pub const fn length_marker(&self, length: usize, tabular: bool) -> Option<usize> {
+        match self.length_markers {
+            LengthMarkers::AllArrays => Some(length),
+            LengthMarkers::TabularOnly if tabular => Some(length),
+            LengthMarkers::TabularOnly | LengthMarkers::Never => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct ResolvedDecodeOptions {
     pub indent: usize,
-    pub strict: bool,
+    pub strictness: Strictness,
     pub expand_paths: ExpandPathsMode,
+    pub on_conflict: ExpandConflictPolicy,
+    pub key_order: KeyOrder,
+    /// `None` keeps every entry of a repeated key.
+    pub duplicate_keys: Option<DuplicateKeyPolicy>,
+    pub require_length_markers: bool,
+    pub max_line_length: Option<usize>,
+    pub limits: Option<DecodeLimits>,
+    pub preserve_number_literals: bool,
+    pub metrics: Option<MetricsHook>,
+    pub collect_warnings: Option<WarningSink>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+impl ResolvedDecodeOptions {
+    /// The options the event decoder needs.
+    #[must_use]
+    pub fn stream_options(&self) -> DecodeStreamOptions {
+        DecodeStreamOptions {
+            indent: Some(self.indent),
+            strictness: Some(self.strictness),
+            require_length_markers: Some(self.require_length_markers),
+            max_line_length: self.max_line_length,
+            limits: self.limits,
+            preserve_number_literals: Some(self.preserve_number_literals),
+            metrics: self.metrics.clone(),
+            collect_warnings: self.collect_warnings.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum PathSegment {
     Key(String),
     Index(usize),
@@ -64,34 +832,72 @@ pub enum PathSegment {
 
 #[must_use]
 pub fn resolve_encode_options(options: Option<EncodeOptions>) -> ResolvedEncodeOptions {
-    let options = options.unwrap_or(EncodeOptions {
-        indent: None,
-        delimiter: None,
-        key_folding: None,
-        flatten_depth: None,
-        replacer: None,
-    });
+    let options = options.unwrap_or_default();
+    let quote_strings = options.quote_strings.unwrap_or_default();
 
     ResolvedEncodeOptions {
         indent: options.indent.unwrap_or(2),
-        delimiter: options.delimiter.unwrap_or(DEFAULT_DELIMITER),
-        key_folding: options.key_folding.unwrap_or(KeyFoldingMode::Off),
+        delimiter: options
+            .delimiter
+            .filter(|delimiter| is_supported_delimiter(*delimiter))
+            .unwrap_or(DEFAULT_DELIMITER),
+        key_folding: match options.key_folding.unwrap_or(KeyFoldingMode::Off) {
+            KeyFoldingMode::Safe if quote_strings.quotes_keys() => KeyFoldingMode::Off,
+            mode => mode,
+        },
         flatten_depth: options.flatten_depth.unwrap_or(usize::MAX),
         replacer: options.replacer,
+        delimiter_overrides: options
+            .delimiter_overrides
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(_, delimiter)| is_supported_delimiter(*delimiter))
+            .collect(),
+        max_depth: options.max_depth,
+        on_non_finite: options.on_non_finite.unwrap_or(NonFinitePolicy::Null),
+        length_markers: options.length_markers.unwrap_or(LengthMarkers::AllArrays),
+        metrics: options.metrics,
+        sort_keys: options.sort_keys.unwrap_or(false),
+        key_comparator: options.key_comparator,
+        quote_strings,
+        tabular_min_rows: options.tabular_min_rows.unwrap_or(1),
+        tabular_max_columns: options.tabular_max_columns.unwrap_or(usize::MAX),
+        tabular: options.tabular.unwrap_or(true),
+        max_line_width: options.max_line_width,
+        indent_style: options.indent_style.unwrap_or_default(),
+        line_ending: options.line_ending.unwrap_or_default(),
+        fold_single_item_arrays: options.fold_single_item_arrays.unwrap_or(false),
     }
 }
 
 #[must_use]
 pub fn resolve_decode_options(options: Option<DecodeOptions>) -> ResolvedDecodeOptions {
-    let options = options.unwrap_or(DecodeOptions {
-        indent: None,
-        strict: None,
-        expand_paths: None,
+    let options = options.unwrap_or_default();
+    let mut strictness = options.strictness.unwrap_or_default();
+    let default_conflict_policy = if strictness == Strictness::lenient() {
+        ExpandConflictPolicy::Merge
+    } else {
+        ExpandConflictPolicy::Error
+    };
+    let duplicate_keys = options.duplicate_keys.or_else(|| {
+        strictness
+            .duplicate_keys
+            .then_some(DuplicateKeyPolicy::Error)
     });
+    strictness.duplicate_keys = duplicate_keys == Some(DuplicateKeyPolicy::Error);
 
     ResolvedDecodeOptions {
         indent: options.indent.unwrap_or(2),
-        strict: options.strict.unwrap_or(true),
+        strictness,
         expand_paths: options.expand_paths.unwrap_or(ExpandPathsMode::Off),
+        on_conflict: options.on_conflict.unwrap_or(default_conflict_policy),
+        key_order: options.key_order.unwrap_or(KeyOrder::Preserve),
+        duplicate_keys,
+        require_length_markers: options.require_length_markers.unwrap_or(false),
+        max_line_length: options.max_line_length,
+        limits: options.limits,
+        preserve_number_literals: options.preserve_number_literals.unwrap_or(false),
+        metrics: options.metrics,
+        collect_warnings: options.collect_warnings,
     }
 }
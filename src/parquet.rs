@@ -0,0 +1,348 @@
+//! Parquet export and import for tabular TOON sections.
+//!
+//! A tabular section is an array of objects that share the same keys and hold
+//! only primitive values — exactly the shape the encoder renders as a
+//! `key[N]{a,b,c}:` table. These helpers write such arrays to Parquet and read
+//! them back, so TOON data can move in and out of data-lake tooling.
+//!
+//! Column types are inferred per field: booleans become `Boolean`, strings
+//! become `Utf8`, and numbers become `Int64` when every one is an integer in
+//! range, `UInt64` when the others need it, and `Float64` otherwise. Nulls
+//! are allowed in any column; a column that is entirely null is written as
+//! `Utf8`.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use ::parquet::arrow::ArrowWriter;
+use ::parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use ::parquet::file::reader::ChunkReader;
+use arrow_array::{
+    Array, ArrayRef, BooleanArray, Float32Array, Float64Array, Int32Array, Int64Array,
+    LargeStringArray, RecordBatch, StringArray, UInt32Array, UInt64Array,
+};
+use arrow_schema::{DataType, Field, Schema};
+
+use crate::encode::normalize::is_array_of_objects;
+use crate::error::{Result, ToonError};
+use crate::{JsonArray, JsonObject, JsonValue, Number, StringOrNumberOrBoolOrNull};
+
+/// Write a tabular section to `writer` as a single-row-group Parquet file.
+///
+/// # Errors
+///
+/// Returns an error if `rows` is not a uniform array of primitive-valued
+/// objects, if a column mixes value types, or if the Parquet writer fails.
+pub fn write_table<W: Write + Send>(rows: &JsonArray, writer: W) -> Result<()> {
+    let header = table_header(rows).ok_or_else(|| {
+        ToonError::message("Parquet export requires a uniform array of primitive-valued objects")
+    })?;
+
+    let mut fields = Vec::with_capacity(header.len());
+    let mut columns: Vec<ArrayRef> = Vec::with_capacity(header.len());
+    for name in &header {
+        let cells = column_cells(rows, name);
+        let (data_type, column) = build_column(name, &cells)?;
+        fields.push(Field::new(name.as_str(), data_type, true));
+        columns.push(column);
+    }
+
+    let schema = Arc::new(Schema::new(fields));
+    let batch = RecordBatch::try_new(schema.clone(), columns).map_err(parquet_error)?;
+    let mut writer = ArrowWriter::try_new(writer, schema, None).map_err(parquet_error)?;
+    writer.write(&batch).map_err(parquet_error)?;
+    writer.close().map_err(parquet_error)?;
+    Ok(())
+}
+
+/// Read a Parquet file back into a tabular section.
+///
+/// `Int64` and `UInt64` columns read back as exact integers; `Int32`,
+/// `UInt32` and `Float32` columns written by other tools are widened.
+/// Unsupported column types produce an error.
+///
+/// # Errors
+///
+/// Returns an error if the input is not valid Parquet or contains a column
+/// type that has no TOON primitive equivalent.
+pub fn read_table<R: ChunkReader + 'static>(reader: R) -> Result<JsonArray> {
+    let batches = ParquetRecordBatchReaderBuilder::try_new(reader)
+        .map_err(parquet_error)?
+        .build()
+        .map_err(parquet_error)?;
+
+    let mut rows = Vec::new();
+    for batch in batches {
+        let batch = batch.map_err(parquet_error)?;
+        let schema = batch.schema();
+        let mut batch_rows: Vec<JsonObject> = (0..batch.num_rows())
+            .map(|_| Vec::with_capacity(schema.fields().len()))
+            .collect();
+
+        for (field, column) in schema.fields().iter().zip(batch.columns()) {
+            for (row, entries) in batch_rows.iter_mut().enumerate() {
+                let value = read_cell(field.name(), column.as_ref(), row)?;
+                entries.push((field.name().clone(), JsonValue::Primitive(value)));
+            }
+        }
+
+        rows.extend(batch_rows.into_iter().map(JsonValue::Object));
+    }
+
+    Ok(rows)
+}
+
+/// Write a tabular section to a Parquet file at `path`.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be created or the export fails.
+pub fn write_table_to_path(rows: &JsonArray, path: &Path) -> Result<()> {
+    let file = File::create(path).map_err(|e| ToonError::file_create(path.to_path_buf(), e))?;
+    write_table(rows, file)
+}
+
+/// Read a tabular section from the Parquet file at `path`.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened or is not valid Parquet.
+pub fn read_table_from_path(path: &Path) -> Result<JsonArray> {
+    let file = File::open(path).map_err(|e| ToonError::file_read(path.to_path_buf(), e))?;
+    read_table(file)
+}
+
+/// Export every top-level tabular section of `value` into `dir`.
+///
+/// Each section is written to `<key>.parquet`. Returns the written paths in
+/// document order; non-tabular entries are skipped.
+///
+/// # Errors
+///
+/// Returns an error, before writing anything, if a section key is not a
+/// plain file name (empty, `.`, `..`, or holding a path separator or
+/// prefix), or if any section fails to export.
+pub fn export_tables(value: &JsonValue, dir: &Path) -> Result<Vec<PathBuf>> {
+    let sections = tabular_sections(value);
+    if let Some((key, _)) = sections.iter().find(|(key, _)| !is_plain_file_name(key)) {
+        return Err(ToonError::message(format!(
+            "Parquet export: section key `{key}` is not a plain file name"
+        )));
+    }
+
+    let mut written = Vec::new();
+    for (key, rows) in sections {
+        let path = dir.join(format!("{key}.parquet"));
+        write_table_to_path(rows, &path)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Whether `key` names a file inside a directory rather than a path that
+/// could leave it. Both separators are refused so a document behaves the
+/// same on every platform.
+fn is_plain_file_name(key: &str) -> bool {
+    let mut components = Path::new(key).components();
+    !key.contains(['/', '\\', '\0'])
+        && matches!(
+            (components.next(), components.next()),
+            (Some(Component::Normal(name)), None) if name == key
+        )
+}
+
+/// Collect the top-level entries of `value` that are tabular sections.
+#[must_use]
+pub fn tabular_sections(value: &JsonValue) -> Vec<(&str, &JsonArray)> {
+    let JsonValue::Object(entries) = value else {
+        return Vec::new();
+    };
+
+    entries
+        .iter()
+        .filter_map(|(key, value)| match value {
+            JsonValue::Array(rows) if table_header(rows).is_some() => Some((key.as_str(), rows)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn table_header(rows: &JsonArray) -> Option<Vec<String>> {
+    if rows.is_empty() || !is_array_of_objects(rows) {
+        return None;
+    }
+
+    let JsonValue::Object(first) = &rows[0] else {
+        return None;
+    };
+    if first.is_empty() {
+        return None;
+    }
+
+    let header: Vec<String> = first.iter().map(|(key, _)| key.clone()).collect();
+    let uniform = rows.iter().all(|row| {
+        let JsonValue::Object(entries) = row else {
+            return false;
+        };
+        entries.len() == header.len()
+            && header.iter().all(|key| {
+                entries
+                    .iter()
+                    .any(|(k, v)| k == key && matches!(v, JsonValue::Primitive(_)))
+            })
+    });
+
+    uniform.then_some(header)
+}
+
+fn column_cells<'a>(rows: &'a JsonArray, name: &str) -> Vec<&'a StringOrNumberOrBoolOrNull> {
+    rows.iter()
+        .filter_map(|row| match row {
+            JsonValue::Object(entries) => entries.iter().find(|(k, _)| k == name),
+            _ => None,
+        })
+        .filter_map(|(_, value)| match value {
+            JsonValue::Primitive(primitive) => Some(primitive),
+            _ => None,
+        })
+        .collect()
+}
+
+fn build_column(name: &str, cells: &[&StringOrNumberOrBoolOrNull]) -> Result<(DataType, ArrayRef)> {
+    let kind = cells
+        .iter()
+        .find(|cell| !matches!(cell, StringOrNumberOrBoolOrNull::Null))
+        .map_or(DataType::Utf8, |cell| match cell {
+            StringOrNumberOrBoolOrNull::Bool(_) => DataType::Boolean,
+            StringOrNumberOrBoolOrNull::Number(_) => number_type(cells),
+            _ => DataType::Utf8,
+        });
+
+    let mixed = || ToonError::message(format!("Parquet export: column `{name}` mixes value types"));
+
+    let column: ArrayRef = match kind {
+        DataType::Boolean => Arc::new(BooleanArray::from(
+            cells
+                .iter()
+                .map(|cell| match cell {
+                    StringOrNumberOrBoolOrNull::Bool(value) => Ok(Some(*value)),
+                    StringOrNumberOrBoolOrNull::Null => Ok(None),
+                    _ => Err(mixed()),
+                })
+                .collect::<Result<Vec<_>>>()?,
+        )),
+        DataType::Int64 => Arc::new(Int64Array::from(
+            cells
+                .iter()
+                .map(|cell| match cell {
+                    StringOrNumberOrBoolOrNull::Number(value) => {
+                        value.as_i64().map(Some).ok_or_else(mixed)
+                    }
+                    StringOrNumberOrBoolOrNull::Null => Ok(None),
+                    _ => Err(mixed()),
+                })
+                .collect::<Result<Vec<_>>>()?,
+        )),
+        DataType::UInt64 => Arc::new(UInt64Array::from(
+            cells
+                .iter()
+                .map(|cell| match cell {
+                    StringOrNumberOrBoolOrNull::Number(value) => {
+                        value.as_u64().map(Some).ok_or_else(mixed)
+                    }
+                    StringOrNumberOrBoolOrNull::Null => Ok(None),
+                    _ => Err(mixed()),
+                })
+                .collect::<Result<Vec<_>>>()?,
+        )),
+        DataType::Float64 => Arc::new(Float64Array::from(
+            cells
+                .iter()
+                .map(|cell| match cell {
+                    StringOrNumberOrBoolOrNull::Number(value) => Ok(Some(value.as_f64())),
+                    StringOrNumberOrBoolOrNull::Null => Ok(None),
+                    _ => Err(mixed()),
+                })
+                .collect::<Result<Vec<_>>>()?,
+        )),
+        _ => Arc::new(StringArray::from(
+            cells
+                .iter()
+                .map(|cell| match cell {
+                    StringOrNumberOrBoolOrNull::String(value) => Ok(Some(value.as_str())),
+                    StringOrNumberOrBoolOrNull::Null => Ok(None),
+                    _ => Err(mixed()),
+                })
+                .collect::<Result<Vec<_>>>()?,
+        )),
+    };
+
+    Ok((kind, column))
+}
+
+/// The narrowest column type holding every number in `cells` exactly.
+/// Cells of other types are left for [`build_column`] to reject.
+fn number_type(cells: &[&StringOrNumberOrBoolOrNull]) -> DataType {
+    let numbers = || {
+        cells.iter().filter_map(|cell| match cell {
+            StringOrNumberOrBoolOrNull::Number(value) => Some(value),
+            _ => None,
+        })
+    };
+    if numbers().all(|value| value.as_i64().is_some()) {
+        DataType::Int64
+    } else if numbers().all(|value| value.as_u64().is_some()) {
+        DataType::UInt64
+    } else {
+        DataType::Float64
+    }
+}
+
+fn read_cell(name: &str, column: &dyn Array, row: usize) -> Result<StringOrNumberOrBoolOrNull> {
+    if column.is_null(row) {
+        return Ok(StringOrNumberOrBoolOrNull::Null);
+    }
+
+    let any = column.as_any();
+    let value = match column.data_type() {
+        DataType::Boolean => downcast::<BooleanArray>(any, name)
+            .map(|array| StringOrNumberOrBoolOrNull::Bool(array.value(row)))?,
+        DataType::Float64 => downcast::<Float64Array>(any, name)
+            .map(|array| StringOrNumberOrBoolOrNull::from_f64(array.value(row)))?,
+        DataType::Float32 => downcast::<Float32Array>(any, name)
+            .map(|array| StringOrNumberOrBoolOrNull::from_f64(f64::from(array.value(row))))?,
+        DataType::Int64 => downcast::<Int64Array>(any, name)
+            .map(|array| StringOrNumberOrBoolOrNull::Number(Number::I64(array.value(row))))?,
+        DataType::Int32 => downcast::<Int32Array>(any, name)
+            .map(|array| StringOrNumberOrBoolOrNull::Number(Number::from(array.value(row))))?,
+        DataType::UInt64 => downcast::<UInt64Array>(any, name)
+            .map(|array| StringOrNumberOrBoolOrNull::Number(Number::U64(array.value(row))))?,
+        DataType::UInt32 => downcast::<UInt32Array>(any, name)
+            .map(|array| StringOrNumberOrBoolOrNull::Number(Number::from(array.value(row))))?,
+        DataType::Utf8 => downcast::<StringArray>(any, name)
+            .map(|array| StringOrNumberOrBoolOrNull::String(array.value(row).to_string()))?,
+        DataType::LargeUtf8 => downcast::<LargeStringArray>(any, name)
+            .map(|array| StringOrNumberOrBoolOrNull::String(array.value(row).to_string()))?,
+        other => {
+            return Err(ToonError::message(format!(
+                "Parquet import: column `{name}` has unsupported type {other}"
+            )));
+        }
+    };
+
+    Ok(value)
+}
+
+fn downcast<'a, T: 'static>(any: &'a dyn std::any::Any, name: &str) -> Result<&'a T> {
+    any.downcast_ref::<T>().ok_or_else(|| {
+        ToonError::message(format!(
+            "Parquet import: column `{name}` does not match its declared type"
+        ))
+    })
+}
+
+fn parquet_error(err: impl std::fmt::Display) -> ToonError {
+    ToonError::message(format!("Parquet error: {err}"))
+}
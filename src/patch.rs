@@ -0,0 +1,314 @@
+//! JSON Patch (RFC 6902) and JSON Merge Patch (RFC 7386) applied directly to
+//! decoded values.
+
+use crate::compare::Tolerance;
+use crate::error::{Result, ToonError};
+use crate::pointer::{parse_index, resolve, resolve_mut, tokens};
+use crate::{JsonValue, StringOrNumberOrBoolOrNull};
+
+/// One operation of a [`Patch`]; pointers are RFC 6901 strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchOperation {
+    Add { path: String, value: JsonValue },
+    Remove { path: String },
+    Replace { path: String, value: JsonValue },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: JsonValue },
+}
+
+impl PatchOperation {
+    /// The pointer this operation writes to or tests.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Add { path, .. }
+            | Self::Remove { path }
+            | Self::Replace { path, .. }
+            | Self::Move { path, .. }
+            | Self::Copy { path, .. }
+            | Self::Test { path, .. } => path,
+        }
+    }
+
+    fn to_value(&self) -> JsonValue {
+        let (op, from, path, value) = match self {
+            Self::Add { path, value } => ("add", None, path, Some(value)),
+            Self::Remove { path } => ("remove", None, path, None),
+            Self::Replace { path, value } => ("replace", None, path, Some(value)),
+            Self::Move { from, path } => ("move", Some(from), path, None),
+            Self::Copy { from, path } => ("copy", Some(from), path, None),
+            Self::Test { path, value } => ("test", None, path, Some(value)),
+        };
+        let mut entries = vec![("op".to_string(), JsonValue::from(op))];
+        if let Some(from) = from {
+            entries.push(("from".to_string(), from.as_str().into()));
+        }
+        entries.push(("path".to_string(), path.as_str().into()));
+        if let Some(value) = value {
+            entries.push(("value".to_string(), value.clone()));
+        }
+        JsonValue::Object(entries)
+    }
+}
+
+/// An ordered list of patch operations.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Patch(pub Vec<PatchOperation>);
+
+impl Patch {
+    /// Read a standard patch document: an array of objects with `op`,
+    /// `path`, and `value` or `from` members as the operation requires.
+    /// Unknown members are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ToonError::Patch`] naming the first malformed operation.
+    pub fn from_value(value: &JsonValue) -> Result<Self> {
+        let JsonValue::Array(items) = value else {
+            return Err(ToonError::patch(0, "", "patch must be an array"));
+        };
+        items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| parse_operation(index, item))
+            .collect::<Result<_>>()
+            .map(Self)
+    }
+
+    /// The standard patch document for these operations, the inverse of
+    /// [`from_value`](Self::from_value).
+    #[must_use]
+    pub fn to_value(&self) -> JsonValue {
+        JsonValue::Array(self.0.iter().map(PatchOperation::to_value).collect())
+    }
+}
+
+impl From<Vec<PatchOperation>> for Patch {
+    fn from(operations: Vec<PatchOperation>) -> Self {
+        Self(operations)
+    }
+}
+
+impl JsonValue {
+    /// Apply an RFC 7386 merge patch in place: object members of `patch`
+    /// merge recursively, `null` members delete the key, and any other
+    /// patch replaces the value outright (arrays included).
+    ///
+    /// ```
+    /// let mut config = toon::decode("server:\n  host: a\n  port: 80\ndebug: true", None);
+    /// config.merge_patch(&toon::decode("server:\n  port: 8080\ndebug: null", None));
+    /// assert_eq!(config, toon::decode("server:\n  host: a\n  port: 8080", None));
+    /// ```
+    pub fn merge_patch(&mut self, patch: &Self) {
+        let Self::Object(members) = patch else {
+            *self = patch.clone();
+            return;
+        };
+        if !matches!(self, Self::Object(_)) {
+            *self = Self::Object(Vec::new());
+        }
+        let Self::Object(entries) = self else {
+            unreachable!()
+        };
+        for (key, value) in members {
+            if matches!(value, Self::Primitive(StringOrNumberOrBoolOrNull::Null)) {
+                entries.retain(|(existing, _)| existing != key);
+            } else if let Some((_, slot)) = entries.iter_mut().rev().find(|(k, _)| k == key) {
+                slot.merge_patch(value);
+            } else {
+                let mut slot = Self::Primitive(StringOrNumberOrBoolOrNull::Null);
+                slot.merge_patch(value);
+                entries.push((key.clone(), slot));
+            }
+        }
+    }
+}
+
+/// Apply `patch` to `value`, all or nothing: if any operation fails
+/// (including a failed `test`), `value` is left unchanged.
+///
+/// ```
+/// use toon::patch::{Patch, apply};
+///
+/// let mut doc = toon::decode("name: api\ntags[1]: a", None);
+/// let patch = Patch::from_value(&toon::decode(
+///     "[2]:\n  - op: add\n    path: /tags/-\n    value: b\n  - op: remove\n    path: /name",
+///     None,
+/// ))
+/// .unwrap();
+/// apply(&mut doc, &patch).unwrap();
+/// assert_eq!(doc, toon::decode("tags[2]: a,b", None));
+/// ```
+///
+/// # Errors
+///
+/// Returns [`ToonError::Patch`] with the index of the failing operation.
+pub fn apply(value: &mut JsonValue, patch: &Patch) -> Result<()> {
+    let mut patched = value.clone();
+    for (index, operation) in patch.0.iter().enumerate() {
+        apply_operation(&mut patched, operation)
+            .map_err(|message| ToonError::patch(index, operation.path(), message))?;
+    }
+    *value = patched;
+    Ok(())
+}
+
+fn apply_operation(
+    target: &mut JsonValue,
+    operation: &PatchOperation,
+) -> std::result::Result<(), String> {
+    match operation {
+        PatchOperation::Add { path, value } => add(target, &split(path)?, value.clone()),
+        PatchOperation::Remove { path } => remove(target, &split(path)?).map(drop),
+        PatchOperation::Replace { path, value } => {
+            let slot = resolve_mut(target, &split(path)?).ok_or("path does not exist")?;
+            *slot = value.clone();
+            Ok(())
+        }
+        PatchOperation::Move { from, path } => {
+            let from = split(from)?;
+            let path = split(path)?;
+            if from == path {
+                return Ok(());
+            }
+            if path.starts_with(&from) {
+                return Err("cannot move a value into one of its children".to_string());
+            }
+            let value = remove(target, &from)?;
+            add(target, &path, value)
+        }
+        PatchOperation::Copy { from, path } => {
+            let value = resolve(target, &split(from)?)
+                .ok_or("'from' does not exist")?
+                .clone();
+            add(target, &split(path)?, value)
+        }
+        PatchOperation::Test { path, value } => {
+            let actual = resolve(target, &split(path)?).ok_or("path does not exist")?;
+            if actual.semantic_eq(value, Tolerance::relative(0.0)) {
+                Ok(())
+            } else {
+                Err("test failed".to_string())
+            }
+        }
+    }
+}
+
+fn split(pointer: &str) -> std::result::Result<Vec<String>, String> {
+    tokens(pointer).ok_or_else(|| format!("invalid pointer '{pointer}'"))
+}
+
+/// The container holding the last token of `path`, and that token.
+fn parent<'a, 'p>(
+    target: &'a mut JsonValue,
+    path: &'p [String],
+) -> std::result::Result<(&'a mut JsonValue, &'p str), String> {
+    let (last, rest) = path.split_last().ok_or("cannot address the root")?;
+    let parent = resolve_mut(target, rest).ok_or("parent does not exist")?;
+    Ok((parent, last))
+}
+
+fn add(
+    target: &mut JsonValue,
+    path: &[String],
+    value: JsonValue,
+) -> std::result::Result<(), String> {
+    if path.is_empty() {
+        *target = value;
+        return Ok(());
+    }
+    let (parent, token) = parent(target, path)?;
+    match parent {
+        JsonValue::Object(entries) => {
+            match entries.iter_mut().rev().find(|(key, _)| key == token) {
+                Some((_, slot)) => *slot = value,
+                None => entries.push((token.to_string(), value)),
+            }
+            Ok(())
+        }
+        JsonValue::Array(items) => {
+            let index = if token == "-" {
+                items.len()
+            } else {
+                parse_index(token)
+                    .filter(|&index| index <= items.len())
+                    .ok_or("array index out of bounds")?
+            };
+            items.insert(index, value);
+            Ok(())
+        }
+        JsonValue::Primitive(_) => Err("parent is not a container".to_string()),
+    }
+}
+
+fn remove(target: &mut JsonValue, path: &[String]) -> std::result::Result<JsonValue, String> {
+    let (parent, token) = parent(target, path)?;
+    match parent {
+        JsonValue::Object(entries) => {
+            let position = entries
+                .iter()
+                .rposition(|(key, _)| key == token)
+                .ok_or("path does not exist")?;
+            Ok(entries.remove(position).1)
+        }
+        JsonValue::Array(items) => {
+            let index = parse_index(token)
+                .filter(|&index| index < items.len())
+                .ok_or("array index out of bounds")?;
+            Ok(items.remove(index))
+        }
+        JsonValue::Primitive(_) => Err("path does not exist".to_string()),
+    }
+}
+
+fn parse_operation(index: usize, item: &JsonValue) -> Result<PatchOperation> {
+    let JsonValue::Object(entries) = item else {
+        return Err(ToonError::patch(index, "", "operation must be an object"));
+    };
+    let member = |name: &str| {
+        entries
+            .iter()
+            .rev()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value)
+    };
+    let string = |name: &str| match member(name) {
+        Some(JsonValue::Primitive(StringOrNumberOrBoolOrNull::String(text))) => Ok(text.clone()),
+        Some(_) => Err(format!("'{name}' must be a string")),
+        None => Err(format!("missing '{name}'")),
+    };
+    let path = string("path").map_err(|message| ToonError::patch(index, "", message))?;
+    let fail = |message: String| ToonError::patch(index, path.clone(), message);
+    let value = || {
+        member("value")
+            .cloned()
+            .ok_or_else(|| fail("missing 'value'".into()))
+    };
+    let from = || string("from").map_err(fail);
+    let op = string("op").map_err(fail)?;
+    Ok(match op.as_str() {
+        "add" => PatchOperation::Add {
+            value: value()?,
+            path,
+        },
+        "remove" => PatchOperation::Remove { path },
+        "replace" => PatchOperation::Replace {
+            value: value()?,
+            path,
+        },
+        "move" => PatchOperation::Move {
+            from: from()?,
+            path,
+        },
+        "copy" => PatchOperation::Copy {
+            from: from()?,
+            path,
+        },
+        "test" => PatchOperation::Test {
+            value: value()?,
+            path,
+        },
+        other => return Err(fail(format!("unknown op '{other}'"))),
+    })
+}
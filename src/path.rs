@@ -0,0 +1,186 @@
+//! Dotted-path access using the notation of folded keys.
+//!
+//! A path is a `.`-separated list of keys, each optionally followed by
+//! bracketed array indices: `users[0].name`, `a.b[2][1].c`. Keys that are not
+//! plain identifiers are quoted as in TOON (`"first name".x`), a numeric
+//! segment (`items.0`) addresses an array position, and a leading index
+//! (`[1].id`) addresses a root array. The empty path is the whole value.
+
+use std::borrow::Cow;
+
+use crate::error::{Result, ToonError};
+use crate::options::PathSegment;
+use crate::pointer::parse_index;
+use crate::shared::constants::{CLOSE_BRACKET, DOT, DOUBLE_QUOTE, OPEN_BRACKET};
+use crate::shared::string_utils::{find_closing_quote, unescape_string};
+use crate::{JsonValue, StringOrNumberOrBoolOrNull};
+
+impl JsonValue {
+    /// Look up a value by dotted path; `None` if the path is malformed or
+    /// does not exist. A repeated key resolves to its last entry.
+    ///
+    /// ```
+    /// let value = toon::decode("a:\n  b[3]: 1,2,3\nusers[1]{name}:\n  Ada", None);
+    /// assert_eq!(value.get_path("a.b[2]"), Some(&3.into()));
+    /// assert_eq!(value.get_path("users.0.name"), Some(&"Ada".into()));
+    /// assert_eq!(value.get_path("a.c"), None);
+    /// ```
+    #[must_use]
+    pub fn get_path(&self, path: &str) -> Option<&Self> {
+        parse_path(path)
+            .ok()?
+            .iter()
+            .try_fold(self, |node, segment| match (node, segment) {
+                (Self::Object(entries), _) => {
+                    let key = segment_key(segment);
+                    entries
+                        .iter()
+                        .rev()
+                        .find(|(k, _)| *k == key)
+                        .map(|(_, value)| value)
+                }
+                (Self::Array(items), PathSegment::Index(index)) => items.get(*index),
+                _ => None,
+            })
+    }
+
+    /// Look up a value by dotted path for editing in place; see
+    /// [`get_path`](Self::get_path).
+    #[must_use]
+    pub fn get_path_mut(&mut self, path: &str) -> Option<&mut Self> {
+        parse_path(path)
+            .ok()?
+            .iter()
+            .try_fold(self, |node, segment| match (node, segment) {
+                (Self::Object(entries), _) => {
+                    let key = segment_key(segment);
+                    entries
+                        .iter_mut()
+                        .rev()
+                        .find(|(k, _)| *k == key)
+                        .map(|(_, value)| value)
+                }
+                (Self::Array(items), PathSegment::Index(index)) => items.get_mut(*index),
+                _ => None,
+            })
+    }
+
+    /// Store `value` at a dotted path, creating missing objects (or arrays,
+    /// before numeric segments) along the way, as
+    /// [`ExpandPathsMode::Indexed`](crate::options::ExpandPathsMode) does.
+    /// An index equal to the array's length appends.
+    ///
+    /// ```
+    /// let mut value = toon::JsonValue::Object(Vec::new());
+    /// value.set_path("server.hosts[0]", "a".into()).unwrap();
+    /// value.set_path("server.port", 80.into()).unwrap();
+    /// assert_eq!(toon::encode(value, None), "server:\n  hosts[1]: a\n  port: 80");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path is malformed, an index skips array
+    /// positions, or the path runs through a primitive.
+    pub fn set_path(&mut self, path: &str, value: Self) -> Result<()> {
+        let segments = parse_path(path)?;
+        let mut node = self;
+        for (position, segment) in segments.iter().enumerate() {
+            let fresh = || match segments.get(position + 1) {
+                Some(PathSegment::Index(_)) => Self::Array(Vec::new()),
+                Some(_) => Self::Object(Vec::new()),
+                None => Self::Primitive(StringOrNumberOrBoolOrNull::Null),
+            };
+            node = match (node, segment) {
+                (Self::Object(entries), _) => {
+                    let key = segment_key(segment);
+                    let found = entries.iter().rposition(|(k, _)| *k == key);
+                    let found = found.unwrap_or_else(|| {
+                        entries.push((key.into_owned(), fresh()));
+                        entries.len() - 1
+                    });
+                    &mut entries[found].1
+                }
+                (Self::Array(items), PathSegment::Index(index)) => {
+                    if *index > items.len() {
+                        return Err(ToonError::invalid_value(
+                            path,
+                            format!(
+                                "index {index} skips positions (array has {} items)",
+                                items.len()
+                            ),
+                        ));
+                    }
+                    if *index == items.len() {
+                        items.push(fresh());
+                    }
+                    &mut items[*index]
+                }
+                _ => {
+                    return Err(ToonError::invalid_value(
+                        path,
+                        "path runs through a value that is not a container",
+                    ));
+                }
+            };
+        }
+        *node = value;
+        Ok(())
+    }
+}
+
+/// The object key a segment names: numeric segments match the key spelled
+/// with the same digits.
+fn segment_key(segment: &PathSegment) -> Cow<'_, str> {
+    match segment {
+        PathSegment::Key(key) => key.into(),
+        PathSegment::Index(index) => index.to_string().into(),
+    }
+}
+
+fn parse_path(path: &str) -> Result<Vec<PathSegment>> {
+    let malformed =
+        |reason: &str| ToonError::invalid_value(path, format!("malformed path: {reason}"));
+    let mut segments = Vec::new();
+    if path.is_empty() {
+        return Ok(segments);
+    }
+    let mut start = 0;
+    loop {
+        let mut end = if path[start..].starts_with(DOUBLE_QUOTE) {
+            let close =
+                find_closing_quote(path, start).ok_or_else(|| malformed("unterminated quote"))?;
+            let key = unescape_string(&path[start + 1..close]).map_err(|err| malformed(&err))?;
+            segments.push(PathSegment::Key(key));
+            close + 1
+        } else {
+            let end = path[start..]
+                .find([DOT, OPEN_BRACKET])
+                .map_or(path.len(), |offset| start + offset);
+            let token = &path[start..end];
+            if !token.is_empty() {
+                segments.push(
+                    parse_index(token)
+                        .map_or_else(|| PathSegment::Key(token.to_string()), PathSegment::Index),
+                );
+            } else if start > 0 || !path.starts_with(OPEN_BRACKET) {
+                return Err(malformed("empty segment"));
+            }
+            end
+        };
+        while path[end..].starts_with(OPEN_BRACKET) {
+            let close = path[end..]
+                .find(CLOSE_BRACKET)
+                .map(|offset| end + offset)
+                .ok_or_else(|| malformed("unterminated `[`"))?;
+            let index =
+                parse_index(&path[end + 1..close]).ok_or_else(|| malformed("invalid index"))?;
+            segments.push(PathSegment::Index(index));
+            end = close + 1;
+        }
+        match path[end..].chars().next() {
+            None => return Ok(segments),
+            Some(DOT) => start = end + 1,
+            Some(_) => return Err(malformed("expected `.` or `[`")),
+        }
+    }
+}
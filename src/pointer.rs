@@ -0,0 +1,102 @@
+//! JSON Pointer (RFC 6901) lookups on decoded values.
+
+use crate::JsonValue;
+
+impl JsonValue {
+    /// Look up a value by JSON Pointer, as `serde_json::Value::pointer` does.
+    ///
+    /// `""` is the whole value; every other pointer starts with `/` and
+    /// names one object key or array index per segment, with `~1` standing
+    /// for `/` and `~0` for `~`. Indices are decimal without leading zeros.
+    /// Where an object repeats a key, the last entry is found, as JSON
+    /// parsers keep it.
+    ///
+    /// ```
+    /// let value = toon::decode("users[2]{name,roles}:\n  Ada,admin\n  Bob,dev\n\"a/b\": 1", None);
+    /// assert_eq!(value.pointer("/users/1/name"), Some(&"Bob".into()));
+    /// assert_eq!(value.pointer("/a~1b"), Some(&1.into()));
+    /// assert_eq!(value.pointer("/users/2"), None);
+    /// ```
+    #[must_use]
+    pub fn pointer(&self, pointer: &str) -> Option<&Self> {
+        resolve(self, &tokens(pointer)?)
+    }
+
+    /// Look up a value by JSON Pointer for editing in place; see
+    /// [`pointer`](Self::pointer).
+    ///
+    /// ```
+    /// let mut value = toon::decode("server:\n  port: 80", None);
+    /// *value.pointer_mut("/server/port").unwrap() = 8080.into();
+    /// assert_eq!(value, toon::decode("server:\n  port: 8080", None));
+    /// ```
+    #[must_use]
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Self> {
+        resolve_mut(self, &tokens(pointer)?)
+    }
+}
+
+/// Follow unescaped reference tokens down from `value`.
+pub(crate) fn resolve<'a>(value: &'a JsonValue, tokens: &[String]) -> Option<&'a JsonValue> {
+    let mut target = value;
+    for token in tokens {
+        target = match target {
+            JsonValue::Object(entries) => entries
+                .iter()
+                .rev()
+                .find(|(key, _)| key == token)
+                .map(|(_, value)| value)?,
+            JsonValue::Array(items) => items.get(parse_index(token)?)?,
+            JsonValue::Primitive(_) => return None,
+        };
+    }
+    Some(target)
+}
+
+/// Follow unescaped reference tokens down from `value`, for editing.
+pub(crate) fn resolve_mut<'a>(
+    value: &'a mut JsonValue,
+    tokens: &[String],
+) -> Option<&'a mut JsonValue> {
+    let mut target = value;
+    for token in tokens {
+        target = match target {
+            JsonValue::Object(entries) => entries
+                .iter_mut()
+                .rev()
+                .find(|(key, _)| key == token)
+                .map(|(_, value)| value)?,
+            JsonValue::Array(items) => items.get_mut(parse_index(token)?)?,
+            JsonValue::Primitive(_) => return None,
+        };
+    }
+    Some(target)
+}
+
+/// Split a pointer into its unescaped reference tokens, or `None` if it is
+/// neither empty nor starts with `/`.
+pub(crate) fn tokens(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(Vec::new());
+    }
+    let rest = pointer.strip_prefix('/')?;
+    Some(
+        rest.split('/')
+            .map(|token| token.replace("~1", "/").replace("~0", "~"))
+            .collect(),
+    )
+}
+
+/// Escape an object key for use as one pointer segment.
+pub(crate) fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Parse an array index token: decimal digits without a sign or leading
+/// zeros.
+pub(crate) fn parse_index(token: &str) -> Option<usize> {
+    if token.starts_with('+') || (token.starts_with('0') && token.len() > 1) {
+        return None;
+    }
+    token.parse().ok()
+}
@@ -0,0 +1,624 @@
+//! `JSONPath` queries (a practical subset of RFC 9535) over decoded values.
+//!
+//! Supported syntax:
+//!
+//! - `$` the root, `.name` / `['name']` members, `[0]` / `[-1]` indices
+//! - `*` / `[*]` wildcards and `..` recursive descent (`$..name`)
+//! - `[0,2]` / `['a','b']` unions and `[start:end:step]` slices
+//! - `[?@.price < 10]` filters (parentheses as in `[?(...)]` are optional)
+//!   with `==`, `!=`, `<`, `<=`, `>`, `>=`, `&&`, `||`, `!`, existence tests
+//!   such as `[?@.isbn]`, and string, number, `true`, `false` and `null`
+//!   literals
+//!
+//! Each match carries the dotted path of the node in the notation
+//! [`JsonValue::get_path`] reads, e.g. `users[0].name`.
+//!
+//! ```
+//! let doc = toon::decode("users[3]{name,age}:\n  Ada,36\n  Bob,17\n  Cy,52", None);
+//! let adults = toon::query::query(&doc, "$.users[?@.age >= 18].name").unwrap();
+//! let names: Vec<_> = adults.iter().map(|m| (m.path.as_str(), m.value)).collect();
+//! assert_eq!(names, [("users[0].name", &"Ada".into()), ("users[2].name", &"Cy".into())]);
+//! ```
+
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use crate::compare::Tolerance;
+use crate::error::{Result, ToonError};
+use crate::number::Number;
+use crate::shared::string_utils::escape_string;
+use crate::shared::validation::is_identifier_segment;
+use crate::{JsonValue, StringOrNumberOrBoolOrNull};
+
+/// A parsed `JSONPath` expression, reusable across documents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    segments: Vec<Segment>,
+}
+
+/// One node selected by a [`Query`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryMatch<'a> {
+    /// Dotted path of the node; empty for the root.
+    pub path: String,
+    pub value: &'a JsonValue,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Segment {
+    descendant: bool,
+    selectors: Vec<Selector>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice {
+        start: Option<i64>,
+        end: Option<i64>,
+        step: i64,
+    },
+    Filter(Filter),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    Or(Box<Self>, Box<Self>),
+    And(Box<Self>, Box<Self>),
+    Not(Box<Self>),
+    Exists(Operand),
+    Compare(Operand, Comparison, Operand),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    /// `@...` (relative) or `$...` (absolute) path.
+    Path {
+        relative: bool,
+        segments: Vec<Segment>,
+    },
+    Literal(JsonValue),
+}
+
+/// Run `expression` against `value`, returning matches in document order.
+///
+/// # Errors
+///
+/// Returns [`ToonError::Query`] if the expression does not parse.
+pub fn query<'a>(value: &'a JsonValue, expression: &str) -> Result<Vec<QueryMatch<'a>>> {
+    Ok(expression.parse::<Query>()?.select(value))
+}
+
+impl Query {
+    /// Select the matching nodes of `value`, in document order.
+    #[must_use]
+    pub fn select<'a>(&self, value: &'a JsonValue) -> Vec<QueryMatch<'a>> {
+        select(&self.segments, value, vec![(String::new(), value)])
+            .into_iter()
+            .map(|(path, value)| QueryMatch { path, value })
+            .collect()
+    }
+}
+
+impl FromStr for Query {
+    type Err = ToonError;
+
+    fn from_str(expression: &str) -> Result<Self> {
+        let mut parser = Parser {
+            text: expression,
+            pos: 0,
+        };
+        parser.skip_whitespace();
+        parser.expect('$')?;
+        let segments = parser.segments(false)?;
+        parser.skip_whitespace();
+        if parser.pos < expression.len() {
+            return Err(parser.error("unexpected character"));
+        }
+        Ok(Self { segments })
+    }
+}
+
+// ============================================================================
+// Evaluation
+// ============================================================================
+
+type Node<'a> = (String, &'a JsonValue);
+
+fn select<'a>(segments: &[Segment], root: &'a JsonValue, start: Vec<Node<'a>>) -> Vec<Node<'a>> {
+    segments.iter().fold(start, |nodes, segment| {
+        let mut next = Vec::new();
+        for (path, node) in nodes {
+            if segment.descendant {
+                let mut visited = Vec::new();
+                descendants(path, node, &mut visited);
+                for (path, node) in visited {
+                    apply_selectors(&segment.selectors, root, &path, node, &mut next);
+                }
+            } else {
+                apply_selectors(&segment.selectors, root, &path, node, &mut next);
+            }
+        }
+        next
+    })
+}
+
+/// `node` and every node below it, parents first.
+fn descendants<'a>(path: String, node: &'a JsonValue, out: &mut Vec<Node<'a>>) {
+    let below = children(&path, node);
+    out.push((path, node));
+    for (child_path, child) in below {
+        descendants(child_path, child, out);
+    }
+}
+
+fn children<'a>(path: &str, node: &'a JsonValue) -> Vec<Node<'a>> {
+    match node {
+        JsonValue::Object(entries) => entries
+            .iter()
+            .map(|(key, value)| (key_path(path, key), value))
+            .collect(),
+        JsonValue::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(index, value)| (format!("{path}[{index}]"), value))
+            .collect(),
+        JsonValue::Primitive(_) => Vec::new(),
+    }
+}
+
+fn apply_selectors<'a>(
+    selectors: &[Selector],
+    root: &'a JsonValue,
+    path: &str,
+    node: &'a JsonValue,
+    out: &mut Vec<Node<'a>>,
+) {
+    for selector in selectors {
+        match (selector, node) {
+            (Selector::Name(name), JsonValue::Object(entries)) => {
+                if let Some((key, value)) = entries.iter().rev().find(|(key, _)| key == name) {
+                    out.push((key_path(path, key), value));
+                }
+            }
+            (Selector::Wildcard, _) => out.extend(children(path, node)),
+            (Selector::Index(index), JsonValue::Array(items)) => {
+                if let Some(position) = normalize_index(*index, items.len()) {
+                    out.push((format!("{path}[{position}]"), &items[position]));
+                }
+            }
+            (Selector::Slice { start, end, step }, JsonValue::Array(items)) => {
+                for position in slice_positions(*start, *end, *step, items.len()) {
+                    out.push((format!("{path}[{position}]"), &items[position]));
+                }
+            }
+            (Selector::Filter(filter), JsonValue::Object(_) | JsonValue::Array(_)) => {
+                out.extend(
+                    children(path, node)
+                        .into_iter()
+                        .filter(|(_, child)| test(filter, root, child)),
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+fn normalize_index(index: i64, len: usize) -> Option<usize> {
+    let len = i64::try_from(len).ok()?;
+    let position = if index < 0 { len + index } else { index };
+    (0..len)
+        .contains(&position)
+        .then(|| usize::try_from(position).ok())
+        .flatten()
+}
+
+/// Array positions of an RFC 9535 slice, in selection order.
+fn slice_positions(start: Option<i64>, end: Option<i64>, step: i64, len: usize) -> Vec<usize> {
+    let Ok(len) = i64::try_from(len) else {
+        return Vec::new();
+    };
+    let bound = |value: i64| {
+        if value < 0 {
+            (len + value).max(-1)
+        } else {
+            value.min(len)
+        }
+    };
+    let mut positions = Vec::new();
+    match step.cmp(&0) {
+        Ordering::Equal => {}
+        Ordering::Greater => {
+            let mut position = start.map_or(0, bound).max(0);
+            let end = end.map_or(len, bound);
+            while position < end {
+                positions.extend(usize::try_from(position));
+                position += step;
+            }
+        }
+        Ordering::Less => {
+            let mut position = start.map_or(len - 1, bound).min(len - 1);
+            let end = end.map_or(-1, bound);
+            while position > end {
+                positions.extend(usize::try_from(position));
+                position += step;
+            }
+        }
+    }
+    positions
+}
+
+fn test(filter: &Filter, root: &JsonValue, current: &JsonValue) -> bool {
+    match filter {
+        Filter::Or(left, right) => test(left, root, current) || test(right, root, current),
+        Filter::And(left, right) => test(left, root, current) && test(right, root, current),
+        Filter::Not(inner) => !test(inner, root, current),
+        Filter::Exists(operand) => match operand {
+            Operand::Path { .. } => !resolve(operand, root, current).is_empty(),
+            Operand::Literal(_) => true,
+        },
+        Filter::Compare(left, comparison, right) => {
+            let left = single(resolve(left, root, current));
+            let right = single(resolve(right, root, current));
+            compare(left, *comparison, right)
+        }
+    }
+}
+
+fn resolve<'a>(
+    operand: &'a Operand,
+    root: &'a JsonValue,
+    current: &'a JsonValue,
+) -> Vec<&'a JsonValue> {
+    match operand {
+        Operand::Literal(value) => vec![value],
+        Operand::Path { relative, segments } => {
+            let start = if *relative { current } else { root };
+            select(segments, root, vec![(String::new(), start)])
+                .into_iter()
+                .map(|(_, value)| value)
+                .collect()
+        }
+    }
+}
+
+/// A comparison operand is "nothing" unless it selects exactly one node.
+fn single(values: Vec<&JsonValue>) -> Option<&JsonValue> {
+    let mut values = values.into_iter();
+    match (values.next(), values.next()) {
+        (Some(value), None) => Some(value),
+        _ => None,
+    }
+}
+
+fn compare(left: Option<&JsonValue>, comparison: Comparison, right: Option<&JsonValue>) -> bool {
+    let equal = match (left, right) {
+        (None, None) => true,
+        (Some(a), Some(b)) => a.semantic_eq(b, Tolerance::relative(0.0)),
+        _ => false,
+    };
+    let less = || match (left, right) {
+        (
+            Some(JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(a))),
+            Some(JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(b))),
+        ) => a.as_f64() < b.as_f64(),
+        (
+            Some(JsonValue::Primitive(StringOrNumberOrBoolOrNull::String(a))),
+            Some(JsonValue::Primitive(StringOrNumberOrBoolOrNull::String(b))),
+        ) => a < b,
+        _ => false,
+    };
+    let greater = || compare(right, Comparison::Lt, left);
+    match comparison {
+        Comparison::Eq => equal,
+        Comparison::Ne => !equal,
+        Comparison::Lt => less(),
+        Comparison::Le => equal || less(),
+        Comparison::Gt => greater(),
+        Comparison::Ge => equal || greater(),
+    }
+}
+
+fn key_path(parent: &str, key: &str) -> String {
+    let key = if is_identifier_segment(key) {
+        key.to_string()
+    } else {
+        format!("\"{}\"", escape_string(key))
+    };
+    if parent.is_empty() {
+        key
+    } else {
+        format!("{parent}.{key}")
+    }
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> ToonError {
+        ToonError::query(self.pos, message)
+    }
+
+    fn rest(&self) -> &str {
+        &self.text[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: char) -> Result<()> {
+        if self.eat(token.encode_utf8(&mut [0; 4])) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{token}`")))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let trimmed = self.rest().trim_start_matches([' ', '\t', '\n', '\r']);
+        self.pos = self.text.len() - trimmed.len();
+    }
+
+    /// Segments after `$` or `@`. Inside filters whitespace may precede a
+    /// segment.
+    fn segments(&mut self, in_filter: bool) -> Result<Vec<Segment>> {
+        let mut segments = Vec::new();
+        loop {
+            let checkpoint = self.pos;
+            if in_filter {
+                self.skip_whitespace();
+            }
+            let descendant = self.eat("..");
+            let selectors = if self.peek() == Some('[') {
+                self.bracket()?
+            } else if descendant || self.eat(".") {
+                if self.eat("*") {
+                    vec![Selector::Wildcard]
+                } else {
+                    vec![Selector::Name(self.member_name()?)]
+                }
+            } else {
+                self.pos = checkpoint;
+                return Ok(segments);
+            };
+            segments.push(Segment {
+                descendant,
+                selectors,
+            });
+        }
+    }
+
+    fn member_name(&mut self) -> Result<String> {
+        let len = self
+            .rest()
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || !c.is_ascii()))
+            .unwrap_or_else(|| self.rest().len());
+        if len == 0 || self.rest().starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(self.error("expected a member name"));
+        }
+        let name = self.rest()[..len].to_string();
+        self.pos += len;
+        Ok(name)
+    }
+
+    fn bracket(&mut self) -> Result<Vec<Selector>> {
+        self.expect('[')?;
+        let mut selectors = Vec::new();
+        loop {
+            self.skip_whitespace();
+            selectors.push(self.selector()?);
+            self.skip_whitespace();
+            if self.eat("]") {
+                return Ok(selectors);
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn selector(&mut self) -> Result<Selector> {
+        match self.peek() {
+            Some('\'' | '"') => Ok(Selector::Name(self.string()?)),
+            Some('*') => {
+                self.pos += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some('?') => {
+                self.pos += 1;
+                self.skip_whitespace();
+                Ok(Selector::Filter(self.or()?))
+            }
+            _ => {
+                let start = self.optional_integer()?;
+                self.skip_whitespace();
+                if !self.eat(":") {
+                    return start
+                        .map(Selector::Index)
+                        .ok_or_else(|| self.error("expected a selector"));
+                }
+                self.skip_whitespace();
+                let end = self.optional_integer()?;
+                self.skip_whitespace();
+                let step = if self.eat(":") {
+                    self.skip_whitespace();
+                    self.optional_integer()?.unwrap_or(1)
+                } else {
+                    1
+                };
+                Ok(Selector::Slice { start, end, step })
+            }
+        }
+    }
+
+    fn optional_integer(&mut self) -> Result<Option<i64>> {
+        let negative = usize::from(self.rest().starts_with('-'));
+        let digits = self.rest()[negative..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or_else(|| self.rest().len() - negative);
+        if digits == 0 {
+            return if negative == 1 {
+                Err(self.error("expected digits after `-`"))
+            } else {
+                Ok(None)
+            };
+        }
+        let text = &self.rest()[..negative + digits];
+        let value = text
+            .parse()
+            .map_err(|_| self.error("integer out of range"))?;
+        self.pos += text.len();
+        Ok(Some(value))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let quote = self.peek().ok_or_else(|| self.error("expected a string"))?;
+        self.pos += 1;
+        let mut out = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                c if c == quote => {
+                    self.pos += offset + 1;
+                    return Ok(out);
+                }
+                '\\' => {
+                    let escaped = match chars.next().map(|(_, c)| c) {
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                            u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.error("invalid \\u escape"))?
+                        }
+                        Some(c @ ('\\' | '/' | '\'' | '"')) => c,
+                        _ => return Err(self.error("invalid escape in string")),
+                    };
+                    out.push(escaped);
+                }
+                c => out.push(c),
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    fn or(&mut self) -> Result<Filter> {
+        let mut filter = self.and()?;
+        while {
+            self.skip_whitespace();
+            self.eat("||")
+        } {
+            filter = Filter::Or(Box::new(filter), Box::new(self.and()?));
+        }
+        Ok(filter)
+    }
+
+    fn and(&mut self) -> Result<Filter> {
+        let mut filter = self.unary()?;
+        while {
+            self.skip_whitespace();
+            self.eat("&&")
+        } {
+            filter = Filter::And(Box::new(filter), Box::new(self.unary()?));
+        }
+        Ok(filter)
+    }
+
+    fn unary(&mut self) -> Result<Filter> {
+        self.skip_whitespace();
+        if self.eat("!") {
+            return Ok(Filter::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("(") {
+            let filter = self.or()?;
+            self.skip_whitespace();
+            self.expect(')')?;
+            return Ok(filter);
+        }
+        let left = self.operand()?;
+        self.skip_whitespace();
+        let comparison = [
+            ("==", Comparison::Eq),
+            ("!=", Comparison::Ne),
+            ("<=", Comparison::Le),
+            (">=", Comparison::Ge),
+            ("<", Comparison::Lt),
+            (">", Comparison::Gt),
+        ]
+        .into_iter()
+        .find(|(token, _)| self.eat(token));
+        let Some((_, comparison)) = comparison else {
+            return match left {
+                Operand::Path { .. } => Ok(Filter::Exists(left)),
+                Operand::Literal(_) => Err(self.error("expected a comparison")),
+            };
+        };
+        self.skip_whitespace();
+        Ok(Filter::Compare(left, comparison, self.operand()?))
+    }
+
+    fn operand(&mut self) -> Result<Operand> {
+        let literal = |value: StringOrNumberOrBoolOrNull| Ok(Operand::Literal(value.into()));
+        match self.peek() {
+            Some(c @ ('@' | '$')) => {
+                self.pos += 1;
+                Ok(Operand::Path {
+                    relative: c == '@',
+                    segments: self.segments(true)?,
+                })
+            }
+            Some('\'' | '"') => literal(StringOrNumberOrBoolOrNull::String(self.string()?)),
+            Some('-' | '0'..='9') => {
+                let len = self
+                    .rest()
+                    .find(|c: char| {
+                        !(c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+                    })
+                    .unwrap_or_else(|| self.rest().len());
+                let text = &self.rest()[..len];
+                if text.parse::<f64>().is_err() {
+                    return Err(self.error("invalid number"));
+                }
+                let number = Number::parse_literal(text);
+                self.pos += len;
+                literal(StringOrNumberOrBoolOrNull::Number(number))
+            }
+            _ if self.eat("true") => literal(StringOrNumberOrBoolOrNull::Bool(true)),
+            _ if self.eat("false") => literal(StringOrNumberOrBoolOrNull::Bool(false)),
+            _ if self.eat("null") => literal(StringOrNumberOrBoolOrNull::Null),
+            _ => Err(self.error("expected `@`, `$` or a literal")),
+        }
+    }
+}
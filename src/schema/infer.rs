@@ -0,0 +1,141 @@
+//! JSON Schema inference from sample documents.
+
+use crate::{JsonValue, StringOrNumberOrBoolOrNull};
+
+const DRAFT_2020_12: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Most distinct values a string field may have to be inferred as an `enum`.
+const MAX_ENUM_VALUES: usize = 8;
+
+/// Infer a JSON Schema (draft 2020-12) describing every sample in `values`.
+///
+/// Types are unioned across samples, whole numbers become `integer`, object
+/// keys present in every sample are `required`, array items share one schema,
+/// and string fields with few distinct values that each repeat become an
+/// `enum`. With no samples the result accepts anything.
+#[must_use]
+pub fn infer_schema(values: &[JsonValue]) -> JsonValue {
+    let samples: Vec<&JsonValue> = values.iter().collect();
+    let mut schema = vec![("$schema".to_string(), JsonValue::from(DRAFT_2020_12))];
+    schema.extend(infer(&samples));
+    JsonValue::Object(schema)
+}
+
+fn infer(samples: &[&JsonValue]) -> Vec<(String, JsonValue)> {
+    let mut objects = Vec::new();
+    let mut arrays = Vec::new();
+    let mut strings = Vec::new();
+    let mut numbers = Vec::new();
+    let mut has_bool = false;
+    let mut has_null = false;
+    for sample in samples {
+        match sample {
+            JsonValue::Object(entries) => objects.push(entries),
+            JsonValue::Array(items) => arrays.push(items),
+            JsonValue::Primitive(StringOrNumberOrBoolOrNull::String(text)) => strings.push(text),
+            JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(number)) => {
+                numbers.push(number.as_f64());
+            }
+            JsonValue::Primitive(StringOrNumberOrBoolOrNull::Bool(_)) => has_bool = true,
+            JsonValue::Primitive(StringOrNumberOrBoolOrNull::Null) => has_null = true,
+        }
+    }
+
+    let mut types = Vec::new();
+    if !objects.is_empty() {
+        types.push("object");
+    }
+    if !arrays.is_empty() {
+        types.push("array");
+    }
+    if !strings.is_empty() {
+        types.push("string");
+    }
+    if !numbers.is_empty() {
+        let all_whole = numbers.iter().all(|number| number.fract() == 0.0);
+        types.push(if all_whole { "integer" } else { "number" });
+    }
+    if has_bool {
+        types.push("boolean");
+    }
+    if has_null {
+        types.push("null");
+    }
+
+    let mut schema = Vec::new();
+    match types.as_slice() {
+        [] => return schema,
+        [single] => schema.push(("type".to_string(), JsonValue::from(*single))),
+        _ => schema.push((
+            "type".to_string(),
+            JsonValue::Array(types.iter().map(|name| JsonValue::from(*name)).collect()),
+        )),
+    }
+
+    let only_strings = objects.is_empty() && arrays.is_empty() && numbers.is_empty() && !has_bool;
+    if only_strings {
+        if let Some(values) = enum_values(&strings) {
+            let mut options: Vec<JsonValue> = values.into_iter().map(JsonValue::from).collect();
+            if has_null {
+                options.push(JsonValue::Primitive(StringOrNumberOrBoolOrNull::Null));
+            }
+            schema.push(("enum".to_string(), JsonValue::Array(options)));
+        }
+    }
+
+    if !objects.is_empty() {
+        schema.extend(infer_object(&objects));
+    }
+    if !arrays.is_empty() {
+        let items: Vec<&JsonValue> = arrays.iter().flat_map(|items| items.iter()).collect();
+        if !items.is_empty() {
+            schema.push(("items".to_string(), JsonValue::Object(infer(&items))));
+        }
+    }
+    schema
+}
+
+fn infer_object(objects: &[&Vec<(String, JsonValue)>]) -> Vec<(String, JsonValue)> {
+    let mut keys: Vec<&str> = Vec::new();
+    for entries in objects {
+        for (key, _) in *entries {
+            if !keys.contains(&key.as_str()) {
+                keys.push(key);
+            }
+        }
+    }
+
+    let mut properties = Vec::with_capacity(keys.len());
+    let mut required = Vec::new();
+    for key in keys {
+        let values: Vec<&JsonValue> = objects
+            .iter()
+            .filter_map(|entries| entries.iter().find(|(k, _)| k == key).map(|(_, v)| v))
+            .collect();
+        if values.len() == objects.len() {
+            required.push(JsonValue::from(key));
+        }
+        properties.push((key.to_string(), JsonValue::Object(infer(&values))));
+    }
+
+    let mut schema = vec![("properties".to_string(), JsonValue::Object(properties))];
+    if !required.is_empty() {
+        schema.push(("required".to_string(), JsonValue::Array(required)));
+    }
+    schema
+}
+
+/// Distinct strings in first-seen order, if there are few and each repeats
+/// on average.
+fn enum_values<'a>(strings: &[&'a String]) -> Option<Vec<&'a str>> {
+    let mut distinct: Vec<&str> = Vec::new();
+    for text in strings {
+        if !distinct.contains(&text.as_str()) {
+            if distinct.len() == MAX_ENUM_VALUES {
+                return None;
+            }
+            distinct.push(text);
+        }
+    }
+    (strings.len() >= 2 * distinct.len()).then_some(distinct)
+}
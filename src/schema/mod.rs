@@ -0,0 +1,42 @@
+//! Schemas for decoded values: JSON Schema inference and validation (the
+//! latter requires the `schema` feature), and a native TOON schema dialect.
+
+use std::fmt;
+
+mod infer;
+mod sample;
+mod toon_schema;
+#[cfg(feature = "schema")]
+mod validate;
+
+pub use infer::infer_schema;
+pub use sample::sample;
+pub use toon_schema::{SchemaType, ToonSchema};
+#[cfg(feature = "schema")]
+pub use validate::validate;
+
+/// A value that fails a schema keyword.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// JSON Pointer to the offending value; empty for the root.
+    pub path: String,
+    /// The schema keyword that failed (`type`, `required`, ...).
+    pub keyword: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_empty() {
+            "(root)"
+        } else {
+            &self.path
+        };
+        write!(f, "{path}: {}", self.message)
+    }
+}
+
+fn push_pointer_token(pointer: &mut String, token: &str) {
+    pointer.push('/');
+    pointer.push_str(&token.replace('~', "~0").replace('/', "~1"));
+}
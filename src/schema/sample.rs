@@ -0,0 +1,511 @@
+//! Synthetic data generation from JSON Schema.
+
+use chrono::{Duration, NaiveDate};
+
+use crate::error::{Result, ToonError};
+use crate::{JsonValue, StringOrNumberOrBoolOrNull};
+
+/// Deepest `$ref` / nesting chain followed before falling back to `null`.
+const MAX_DEPTH: usize = 16;
+
+/// Chance of choosing the `null` branch of an optional value.
+const NULL_PERCENT: u64 = 10;
+
+const FIRST_NAMES: &[&str] = &[
+    "Ada",
+    "Grace",
+    "Alan",
+    "Linus",
+    "Margaret",
+    "Dennis",
+    "Barbara",
+    "Ken",
+    "Frances",
+    "Edsger",
+    "Katherine",
+    "John",
+    "Radia",
+    "Tim",
+    "Hedy",
+    "Niklaus",
+];
+const LAST_NAMES: &[&str] = &[
+    "Lovelace",
+    "Hopper",
+    "Turing",
+    "Torvalds",
+    "Hamilton",
+    "Ritchie",
+    "Liskov",
+    "Thompson",
+    "Allen",
+    "Dijkstra",
+    "Johnson",
+    "Backus",
+    "Perlman",
+    "Berners-Lee",
+    "Lamarr",
+    "Wirth",
+];
+const CITIES: &[&str] = &[
+    "Lisbon",
+    "Osaka",
+    "Toronto",
+    "Nairobi",
+    "Oslo",
+    "Santiago",
+    "Melbourne",
+    "Austin",
+    "Krakow",
+    "Seoul",
+];
+const COUNTRIES: &[&str] = &[
+    "Portugal",
+    "Japan",
+    "Canada",
+    "Kenya",
+    "Norway",
+    "Chile",
+    "Australia",
+    "United States",
+    "Poland",
+    "South Korea",
+];
+const STATUSES: &[&str] = &["active", "pending", "inactive", "archived"];
+const WORDS: &[&str] = &[
+    "alpha", "bright", "cedar", "delta", "ember", "fable", "harbor", "island", "jade", "kernel",
+    "lumen", "meadow", "nova", "orbit", "prism", "quartz", "river", "summit", "tide", "vector",
+];
+
+/// Generate `rows` values conforming to `schema`, reproducibly for a given
+/// `seed`.
+///
+/// For a schema whose root is an array, the result is one such array with
+/// `rows` items; otherwise it is an array of `rows` instances, which encodes
+/// as a table when the instances are flat objects. Field names steer string
+/// and number content (`email`, `name`, `city`, `age`, `price`, ...), and
+/// integer `id` fields count up from 1.
+///
+/// Supported keywords: `type`, `enum`, `const`, `properties`, `items`,
+/// `prefixItems`, `anyOf`, `oneOf`, `allOf` (first branch), local `$ref`,
+/// `format` (`email`, `uri`, `uuid`, `date`, `date-time`), `minimum`,
+/// `maximum`, `minLength`, `maxLength`, `minItems` and `maxItems`. Every
+/// declared property is generated.
+///
+/// # Errors
+///
+/// Returns an error if a `$ref` does not resolve within the schema.
+pub fn sample(schema: &JsonValue, rows: usize, seed: u64) -> Result<JsonValue> {
+    let mut generator = Generator {
+        root: schema,
+        rng: Rng(seed),
+        row: 0,
+        person: (FIRST_NAMES[0], LAST_NAMES[0]),
+    };
+    let root = generator.resolve(schema)?;
+    if declared_type(root) == Some("array") {
+        let items = field(root, "items");
+        let mut values = Vec::with_capacity(rows);
+        for row in 0..rows {
+            generator.row = row;
+            values.push(match items {
+                Some(items) => generator.value(items, "", 1)?,
+                None => generator.any(),
+            });
+        }
+        return Ok(JsonValue::Array(values));
+    }
+
+    let mut values = Vec::with_capacity(rows);
+    for row in 0..rows {
+        generator.row = row;
+        values.push(generator.value(schema, "", 0)?);
+    }
+    Ok(JsonValue::Array(values))
+}
+
+struct Generator<'s> {
+    root: &'s JsonValue,
+    rng: Rng,
+    /// Index of the top-level instance being generated, for `id` fields.
+    row: usize,
+    /// First and last name shared by the name and email fields of the
+    /// object being generated.
+    person: (&'static str, &'static str),
+}
+
+impl<'s> Generator<'s> {
+    /// Follow `$ref` chains to the schema that describes the value.
+    fn resolve(&self, mut schema: &'s JsonValue) -> Result<&'s JsonValue> {
+        for _ in 0..MAX_DEPTH {
+            let Some(reference) = field(schema, "$ref").and_then(as_str) else {
+                return Ok(schema);
+            };
+            schema = resolve_pointer(self.root, reference).ok_or_else(|| {
+                ToonError::message(format!("cannot resolve schema reference `{reference}`"))
+            })?;
+        }
+        Ok(schema)
+    }
+
+    fn value(&mut self, schema: &'s JsonValue, key: &str, depth: usize) -> Result<JsonValue> {
+        if depth > MAX_DEPTH {
+            return Ok(JsonValue::Primitive(StringOrNumberOrBoolOrNull::Null));
+        }
+        let schema = self.resolve(schema)?;
+        let JsonValue::Object(_) = schema else {
+            // `true`, `{}` and other non-object schemas accept anything.
+            return Ok(self.any());
+        };
+
+        if let Some(value) = field(schema, "const") {
+            return Ok(value.clone());
+        }
+        if let Some(JsonValue::Array(options)) = field(schema, "enum") {
+            if !options.is_empty() {
+                return Ok(self.rng.pick(options).clone());
+            }
+        }
+        for keyword in ["anyOf", "oneOf"] {
+            if let Some(JsonValue::Array(branches)) = field(schema, keyword) {
+                if let Some(branch) = self.branch(branches)? {
+                    return self.value(branch, key, depth + 1);
+                }
+            }
+        }
+        if let Some(JsonValue::Array(branches)) = field(schema, "allOf") {
+            if let Some(first) = branches.first() {
+                return self.value(first, key, depth + 1);
+            }
+        }
+
+        let kind = match field(schema, "type") {
+            Some(JsonValue::Array(types)) => {
+                let names: Vec<&str> = types.iter().filter_map(as_str).collect();
+                let non_null: Vec<&str> = names
+                    .iter()
+                    .copied()
+                    .filter(|name| *name != "null")
+                    .collect();
+                if non_null.is_empty()
+                    || (non_null.len() < names.len() && self.rng.percent(NULL_PERCENT))
+                {
+                    "null"
+                } else {
+                    *self.rng.pick(&non_null)
+                }
+            }
+            _ => declared_type(schema).unwrap_or("string"),
+        };
+
+        Ok(match kind {
+            "object" => self.object(schema, depth)?,
+            "array" => self.array(schema, key, depth)?,
+            "integer" => self.integer(schema, key),
+            "number" => self.number(schema, key),
+            "boolean" => JsonValue::from(self.rng.percent(50)),
+            "null" => JsonValue::Primitive(StringOrNumberOrBoolOrNull::Null),
+            _ => JsonValue::from(self.string(schema, key)),
+        })
+    }
+
+    /// Pick an `anyOf`/`oneOf` branch, choosing a bare `null` branch rarely.
+    fn branch(&mut self, branches: &'s [JsonValue]) -> Result<Option<&'s JsonValue>> {
+        let mut null = None;
+        let mut others = Vec::new();
+        for branch in branches {
+            if declared_type(self.resolve(branch)?) == Some("null") {
+                null = Some(branch);
+            } else {
+                others.push(branch);
+            }
+        }
+        Ok(match null {
+            Some(null) if others.is_empty() || self.rng.percent(NULL_PERCENT) => Some(null),
+            _ if others.is_empty() => None,
+            _ => Some(*self.rng.pick(&others)),
+        })
+    }
+
+    fn object(&mut self, schema: &'s JsonValue, depth: usize) -> Result<JsonValue> {
+        let outer = self.person;
+        self.person = (*self.rng.pick(FIRST_NAMES), *self.rng.pick(LAST_NAMES));
+        let mut entries = Vec::new();
+        if let Some(JsonValue::Object(properties)) = field(schema, "properties") {
+            for (name, property) in properties {
+                let value = self.value(property, name, depth + 1)?;
+                entries.push((name.clone(), value));
+            }
+        }
+        self.person = outer;
+        Ok(JsonValue::Object(entries))
+    }
+
+    fn array(&mut self, schema: &'s JsonValue, key: &str, depth: usize) -> Result<JsonValue> {
+        let min = field(schema, "minItems").and_then(as_usize).unwrap_or(1);
+        let max = field(schema, "maxItems")
+            .and_then(as_usize)
+            .unwrap_or_else(|| min.max(3))
+            .max(min);
+        let length = min + self.rng.below(max - min + 1);
+        let prefix: &[JsonValue] = match field(schema, "prefixItems") {
+            Some(JsonValue::Array(prefix)) => prefix,
+            _ => &[],
+        };
+        let items = field(schema, "items");
+
+        let mut values = Vec::with_capacity(length);
+        for index in 0..length.max(prefix.len()) {
+            let value = match (prefix.get(index), items) {
+                (Some(item), _) => self.value(item, key, depth + 1)?,
+                (None, Some(item)) if index < length => self.value(item, key, depth + 1)?,
+                (None, None) if index < length => self.any(),
+                _ => break,
+            };
+            values.push(value);
+        }
+        Ok(JsonValue::Array(values))
+    }
+
+    fn integer(&mut self, schema: &JsonValue, key: &str) -> JsonValue {
+        let key = key.to_ascii_lowercase();
+        let (low, high) = if key == "id" {
+            #[allow(clippy::cast_possible_wrap)]
+            let id = self.row as i64 + 1;
+            (id, id)
+        } else if key.contains("age") {
+            (18, 90)
+        } else if key.contains("year") {
+            (1990, 2025)
+        } else if key.contains("count") || key.contains("qty") || key.contains("quantity") {
+            (0, 100)
+        } else {
+            (1, 1000)
+        };
+        #[allow(clippy::cast_possible_truncation)]
+        let low = field(schema, "minimum")
+            .and_then(as_f64)
+            .map_or(low, |min| min.ceil() as i64);
+        #[allow(clippy::cast_possible_truncation)]
+        let high = field(schema, "maximum")
+            .and_then(as_f64)
+            .map_or(high, |max| max.floor() as i64)
+            .max(low);
+        let span = usize::try_from(high - low).unwrap_or(usize::MAX - 1) + 1;
+        let value = low + i64::try_from(self.rng.below(span)).unwrap_or(0);
+        JsonValue::from(value)
+    }
+
+    fn number(&mut self, schema: &JsonValue, key: &str) -> JsonValue {
+        let key = key.to_ascii_lowercase();
+        let (low, high) = if key.contains("price") || key.contains("amount") || key.contains("cost")
+        {
+            (1.0, 500.0)
+        } else if key.contains("lat") {
+            (-90.0, 90.0)
+        } else if key.contains("lon") || key.contains("lng") {
+            (-180.0, 180.0)
+        } else if key.contains("rate") || key.contains("score") || key.contains("ratio") {
+            (0.0, 1.0)
+        } else {
+            (0.0, 1000.0)
+        };
+        let low = field(schema, "minimum").and_then(as_f64).unwrap_or(low);
+        let high = field(schema, "maximum")
+            .and_then(as_f64)
+            .unwrap_or(high)
+            .max(low);
+        let value = (self.rng.unit().mul_add(high - low, low) * 100.0).round() / 100.0;
+        JsonValue::from(value.clamp(low, high))
+    }
+
+    fn string(&mut self, schema: &JsonValue, key: &str) -> String {
+        let key = key.to_ascii_lowercase();
+        let format = field(schema, "format").and_then(as_str).unwrap_or("");
+        let (first, last) = self.person;
+        let text = if format == "email" || key.contains("email") {
+            format!(
+                "{}.{}{}@example.com",
+                first.to_ascii_lowercase(),
+                last.to_ascii_lowercase(),
+                self.rng.below(100)
+            )
+        } else if format == "uuid" || key.contains("uuid") || key.contains("guid") {
+            self.uuid()
+        } else if format == "uri"
+            || format == "url"
+            || key.contains("url")
+            || key.contains("website")
+        {
+            format!("https://example.com/{}", self.rng.pick(WORDS))
+        } else if format == "date-time" || key.ends_with("_at") || key.contains("timestamp") {
+            let seconds = self.rng.below(86_400);
+            let time = self.date().and_hms_opt(0, 0, 0).unwrap_or_default()
+                + Duration::seconds(i64::try_from(seconds).unwrap_or(0));
+            time.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+        } else if format == "date" || key.contains("date") || key.contains("birthday") {
+            self.date().format("%Y-%m-%d").to_string()
+        } else if key.contains("first") && key.contains("name") {
+            first.to_string()
+        } else if key.contains("last") && key.contains("name") || key.contains("surname") {
+            last.to_string()
+        } else if key.contains("name") || key.contains("author") || key.contains("owner") {
+            format!("{first} {last}")
+        } else if key.contains("city") {
+            (*self.rng.pick(CITIES)).to_string()
+        } else if key.contains("country") {
+            (*self.rng.pick(COUNTRIES)).to_string()
+        } else if key.contains("phone") {
+            format!("+1-555-{:04}", self.rng.below(10_000))
+        } else if key.contains("status") || key.contains("state") {
+            (*self.rng.pick(STATUSES)).to_string()
+        } else if key.contains("description") || key.contains("note") || key.contains("comment") {
+            let count = 4 + self.rng.below(5);
+            self.words(count)
+        } else if key.contains("title") || key.contains("label") {
+            let count = 2 + self.rng.below(2);
+            self.words(count)
+        } else {
+            (*self.rng.pick(WORDS)).to_string()
+        };
+        self.fit_length(schema, text)
+    }
+
+    fn fit_length(&mut self, schema: &JsonValue, mut text: String) -> String {
+        let min = field(schema, "minLength").and_then(as_usize).unwrap_or(0);
+        let max = field(schema, "maxLength")
+            .and_then(as_usize)
+            .unwrap_or(usize::MAX);
+        while text.chars().count() < min {
+            text.push_str(self.rng.pick::<&str>(WORDS));
+        }
+        if text.chars().count() > max {
+            text = text.chars().take(max).collect();
+        }
+        text
+    }
+
+    fn words(&mut self, count: usize) -> String {
+        (0..count)
+            .map(|_| *self.rng.pick(WORDS))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn date(&mut self) -> NaiveDate {
+        let start = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap_or_default();
+        start + Duration::days(i64::try_from(self.rng.below(6 * 365)).unwrap_or(0))
+    }
+
+    fn uuid(&mut self) -> String {
+        let high = self.rng.next_u64();
+        let low = self.rng.next_u64();
+        format!(
+            "{:08x}-{:04x}-4{:03x}-{:04x}-{:012x}",
+            high >> 32,
+            (high >> 16) & 0xffff,
+            high & 0x0fff,
+            0x8000 | (low >> 48) & 0x3fff,
+            low & 0xffff_ffff_ffff
+        )
+    }
+
+    /// A value for a schema that accepts anything.
+    fn any(&mut self) -> JsonValue {
+        JsonValue::from(*self.rng.pick(WORDS))
+    }
+}
+
+fn field<'s>(schema: &'s JsonValue, name: &str) -> Option<&'s JsonValue> {
+    match schema {
+        JsonValue::Object(entries) => entries
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value),
+        _ => None,
+    }
+}
+
+/// The single `type` a schema declares, or the one implied by its keywords.
+fn declared_type(schema: &JsonValue) -> Option<&str> {
+    if let Some(kind) = field(schema, "type") {
+        return as_str(kind);
+    }
+    if field(schema, "properties").is_some() {
+        Some("object")
+    } else if field(schema, "items").is_some() || field(schema, "prefixItems").is_some() {
+        Some("array")
+    } else {
+        None
+    }
+}
+
+/// Resolve a local `#/...` JSON Pointer reference.
+fn resolve_pointer<'s>(root: &'s JsonValue, reference: &str) -> Option<&'s JsonValue> {
+    let pointer = reference.strip_prefix('#')?;
+    let mut node = root;
+    for token in pointer.split('/').skip(1) {
+        let token = token.replace("~1", "/").replace("~0", "~");
+        node = match node {
+            JsonValue::Object(_) => field(node, &token)?,
+            JsonValue::Array(items) => items.get(token.parse::<usize>().ok()?)?,
+            JsonValue::Primitive(_) => return None,
+        };
+    }
+    Some(node)
+}
+
+fn as_str(value: &JsonValue) -> Option<&str> {
+    match value {
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::String(text)) => Some(text),
+        _ => None,
+    }
+}
+
+fn as_f64(value: &JsonValue) -> Option<f64> {
+    match value {
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(number)) => Some(number.as_f64()),
+        _ => None,
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn as_usize(value: &JsonValue) -> Option<usize> {
+    as_f64(value)
+        .filter(|number| *number >= 0.0)
+        .map(|number| number as usize)
+}
+
+/// `SplitMix64`: small, fast and identical on every platform, so a seed
+/// always produces the same data.
+struct Rng(u64);
+
+impl Rng {
+    const fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..bound`; `bound` must be non-zero.
+    #[allow(clippy::cast_possible_truncation)]
+    const fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    const fn percent(&mut self, chance: u64) -> bool {
+        self.next_u64() % 100 < chance
+    }
+
+    /// Uniform in `[0, 1)`.
+    #[allow(clippy::cast_precision_loss)]
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
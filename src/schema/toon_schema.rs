@@ -0,0 +1,347 @@
+//! A schema dialect written in TOON itself, so a schema can sit next to the
+//! data and prompt that use it:
+//!
+//! ```text
+//! id: integer
+//! nickname: string?
+//! role: enum(admin|user)
+//! tags: string[]
+//! address:
+//!   city: string
+//!   "zip?": string
+//! orders[2]{field,type}:
+//!   sku,string
+//!   qty,integer
+//! ```
+//!
+//! Each field maps to a type: `string`, `number`, `integer`, `boolean`,
+//! `null`, `any` or `enum(a|b|...)`, with `[]` suffixes for arrays and a
+//! final `?` for fields that may be missing or null (a `?` at the end of the
+//! key works too). A nested object describes an object shape, and a
+//! `{field,type}` table describes an array of rows with those columns.
+//! Objects are closed: fields the schema does not list are violations.
+
+use std::str::FromStr;
+
+use crate::error::{Result, ToonError};
+use crate::schema::{SchemaViolation, push_pointer_token};
+use crate::{JsonValue, StringOrNumberOrBoolOrNull, try_decode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaType {
+    String,
+    Number,
+    Integer,
+    Boolean,
+    Null,
+    Any,
+}
+
+/// A parsed TOON schema. The root of a schema document is always an
+/// [`Object`](Self::Object).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToonSchema {
+    Type(SchemaType),
+    Enum(Vec<String>),
+    Array(Box<Self>),
+    /// Fields as `(name, schema, optional)`, in declaration order.
+    Object(Vec<(String, Self, bool)>),
+}
+
+impl FromStr for ToonSchema {
+    type Err = ToonError;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match try_decode(input, None)? {
+            JsonValue::Object(entries) => Ok(Self::Object(parse_fields(&entries, "")?)),
+            _ => Err(ToonError::message(
+                "TOON schema must be an object of field declarations",
+            )),
+        }
+    }
+}
+
+impl ToonSchema {
+    /// Check `value` against this schema, returning every violation found.
+    #[must_use]
+    pub fn validate(&self, value: &JsonValue) -> Vec<SchemaViolation> {
+        let mut violations = Vec::new();
+        self.check(value, &mut String::new(), &mut violations);
+        violations
+    }
+
+    /// The equivalent JSON Schema (draft 2020-12), for tools that expect one.
+    #[must_use]
+    pub fn to_json_schema(&self) -> JsonValue {
+        let entries = |pairs: Vec<(&str, JsonValue)>| {
+            JsonValue::Object(
+                pairs
+                    .into_iter()
+                    .map(|(key, value)| (key.to_string(), value))
+                    .collect(),
+            )
+        };
+        match self {
+            Self::Type(SchemaType::Any) => JsonValue::Object(Vec::new()),
+            Self::Type(kind) => entries(vec![("type", JsonValue::from(type_name(*kind)))]),
+            Self::Enum(options) => entries(vec![(
+                "enum",
+                JsonValue::Array(
+                    options
+                        .iter()
+                        .map(|o| JsonValue::from(o.as_str()))
+                        .collect(),
+                ),
+            )]),
+            Self::Array(items) => entries(vec![
+                ("type", JsonValue::from("array")),
+                ("items", items.to_json_schema()),
+            ]),
+            Self::Object(fields) => {
+                let properties = fields
+                    .iter()
+                    .map(|(name, schema, optional)| {
+                        let property = if *optional {
+                            entries(vec![(
+                                "anyOf",
+                                JsonValue::Array(vec![
+                                    schema.to_json_schema(),
+                                    entries(vec![("type", JsonValue::from("null"))]),
+                                ]),
+                            )])
+                        } else {
+                            schema.to_json_schema()
+                        };
+                        (name.clone(), property)
+                    })
+                    .collect();
+                let required = fields
+                    .iter()
+                    .filter(|(_, _, optional)| !optional)
+                    .map(|(name, _, _)| JsonValue::from(name.as_str()))
+                    .collect();
+                entries(vec![
+                    ("type", JsonValue::from("object")),
+                    ("properties", JsonValue::Object(properties)),
+                    ("required", JsonValue::Array(required)),
+                    ("additionalProperties", JsonValue::from(false)),
+                ])
+            }
+        }
+    }
+
+    fn check(&self, value: &JsonValue, path: &mut String, out: &mut Vec<SchemaViolation>) {
+        match (self, value) {
+            (Self::Type(kind), _) => {
+                if !matches_type(*kind, value) {
+                    report(
+                        out,
+                        path,
+                        "type",
+                        format!("expected {}, found {}", type_name(*kind), value_kind(value)),
+                    );
+                }
+            }
+            (
+                Self::Enum(options),
+                JsonValue::Primitive(StringOrNumberOrBoolOrNull::String(text)),
+            ) if options.contains(text) => {}
+            (Self::Enum(options), _) => {
+                report(
+                    out,
+                    path,
+                    "enum",
+                    format!("expected one of {}", options.join(", ")),
+                );
+            }
+            (Self::Array(items), JsonValue::Array(values)) => {
+                for (index, item) in values.iter().enumerate() {
+                    let len = path.len();
+                    push_pointer_token(path, &index.to_string());
+                    items.check(item, path, out);
+                    path.truncate(len);
+                }
+            }
+            (Self::Object(fields), JsonValue::Object(entries)) => {
+                for (name, schema, optional) in fields {
+                    let found = entries.iter().find(|(key, _)| key == name).map(|(_, v)| v);
+                    match found {
+                        None | Some(JsonValue::Primitive(StringOrNumberOrBoolOrNull::Null))
+                            if *optional => {}
+                        None => report(out, path, "required", format!("missing field `{name}`")),
+                        Some(item) => {
+                            let len = path.len();
+                            push_pointer_token(path, name);
+                            schema.check(item, path, out);
+                            path.truncate(len);
+                        }
+                    }
+                }
+                for (key, _) in entries {
+                    if !fields.iter().any(|(name, _, _)| name == key) {
+                        let len = path.len();
+                        push_pointer_token(path, key);
+                        report(
+                            out,
+                            path,
+                            "additionalProperties",
+                            format!("unexpected field `{key}`"),
+                        );
+                        path.truncate(len);
+                    }
+                }
+            }
+            (Self::Array(_), _) => {
+                report(
+                    out,
+                    path,
+                    "type",
+                    format!("expected array, found {}", value_kind(value)),
+                );
+            }
+            (Self::Object(_), _) => {
+                report(
+                    out,
+                    path,
+                    "type",
+                    format!("expected object, found {}", value_kind(value)),
+                );
+            }
+        }
+    }
+}
+
+fn report(out: &mut Vec<SchemaViolation>, path: &str, keyword: &str, message: String) {
+    out.push(SchemaViolation {
+        path: path.to_string(),
+        keyword: keyword.to_string(),
+        message,
+    });
+}
+
+fn parse_fields(
+    entries: &[(String, JsonValue)],
+    path: &str,
+) -> Result<Vec<(String, ToonSchema, bool)>> {
+    entries
+        .iter()
+        .map(|(key, declaration)| {
+            let (name, key_optional) = key
+                .strip_suffix('?')
+                .map_or((key.as_str(), false), |name| (name, true));
+            let field_path = if path.is_empty() {
+                name.to_string()
+            } else {
+                format!("{path}.{name}")
+            };
+            let (schema, type_optional) = parse_declaration(declaration, &field_path)?;
+            Ok((name.to_string(), schema, key_optional || type_optional))
+        })
+        .collect()
+}
+
+fn parse_declaration(declaration: &JsonValue, path: &str) -> Result<(ToonSchema, bool)> {
+    match declaration {
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::String(spec)) => parse_type_spec(spec)
+            .ok_or_else(|| ToonError::message(format!("Field `{path}`: unknown type `{spec}`"))),
+        JsonValue::Object(entries) => Ok((ToonSchema::Object(parse_fields(entries, path)?), false)),
+        JsonValue::Array(rows) => {
+            let columns = rows
+                .iter()
+                .map(|row| {
+                    let field = row_cell(row, "field");
+                    let kind = row_cell(row, "type");
+                    match (field, kind) {
+                        (Some(field), Some(kind)) => Ok((field.to_string(), JsonValue::from(kind))),
+                        _ => Err(ToonError::message(format!(
+                            "Field `{path}`: table shapes need {{field,type}} rows"
+                        ))),
+                    }
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let row_schema = ToonSchema::Object(parse_fields(&columns, path)?);
+            Ok((ToonSchema::Array(Box::new(row_schema)), false))
+        }
+        JsonValue::Primitive(_) => Err(ToonError::message(format!(
+            "Field `{path}`: expected a type name, object shape or table shape"
+        ))),
+    }
+}
+
+fn row_cell<'a>(row: &'a JsonValue, column: &str) -> Option<&'a str> {
+    let JsonValue::Object(cells) = row else {
+        return None;
+    };
+    match cells.iter().find(|(key, _)| key == column) {
+        Some((_, JsonValue::Primitive(StringOrNumberOrBoolOrNull::String(text)))) => Some(text),
+        _ => None,
+    }
+}
+
+/// Parse `base[]...?` into a schema and whether the field is optional.
+fn parse_type_spec(spec: &str) -> Option<(ToonSchema, bool)> {
+    let spec = spec.trim();
+    let (spec, optional) = spec
+        .strip_suffix('?')
+        .map_or((spec, false), |spec| (spec, true));
+
+    let mut base = spec;
+    let mut depth = 0;
+    while let Some(inner) = base.strip_suffix("[]") {
+        base = inner;
+        depth += 1;
+    }
+
+    let mut schema = match base {
+        "string" => ToonSchema::Type(SchemaType::String),
+        "number" => ToonSchema::Type(SchemaType::Number),
+        "integer" => ToonSchema::Type(SchemaType::Integer),
+        "boolean" => ToonSchema::Type(SchemaType::Boolean),
+        "null" => ToonSchema::Type(SchemaType::Null),
+        "any" => ToonSchema::Type(SchemaType::Any),
+        _ => {
+            let options = base.strip_prefix("enum(")?.strip_suffix(')')?;
+            ToonSchema::Enum(options.split('|').map(|o| o.trim().to_string()).collect())
+        }
+    };
+    for _ in 0..depth {
+        schema = ToonSchema::Array(Box::new(schema));
+    }
+    Some((schema, optional))
+}
+
+fn matches_type(kind: SchemaType, value: &JsonValue) -> bool {
+    match (kind, value) {
+        (SchemaType::Any, _)
+        | (SchemaType::String, JsonValue::Primitive(StringOrNumberOrBoolOrNull::String(_)))
+        | (SchemaType::Number, JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(_)))
+        | (SchemaType::Boolean, JsonValue::Primitive(StringOrNumberOrBoolOrNull::Bool(_)))
+        | (SchemaType::Null, JsonValue::Primitive(StringOrNumberOrBoolOrNull::Null)) => true,
+        (SchemaType::Integer, JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(n))) => {
+            n.is_integer() || n.as_f64().fract() == 0.0
+        }
+        _ => false,
+    }
+}
+
+const fn type_name(kind: SchemaType) -> &'static str {
+    match kind {
+        SchemaType::String => "string",
+        SchemaType::Number => "number",
+        SchemaType::Integer => "integer",
+        SchemaType::Boolean => "boolean",
+        SchemaType::Null => "null",
+        SchemaType::Any => "any",
+    }
+}
+
+const fn value_kind(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::String(_)) => "string",
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(_)) => "number",
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Bool(_)) => "boolean",
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Null) => "null",
+        JsonValue::Array(_) => "array",
+        JsonValue::Object(_) => "object",
+    }
+}
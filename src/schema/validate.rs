@@ -0,0 +1,449 @@
+//! JSON Schema validation of decoded values.
+
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use crate::compare::Tolerance;
+use crate::schema::{SchemaViolation, push_pointer_token};
+use crate::{JsonValue, StringOrNumberOrBoolOrNull};
+
+/// Nested `$ref` resolutions allowed before a schema is treated as cyclic.
+const MAX_REF_DEPTH: usize = 64;
+
+/// Equality used by `enum`, `const` and `uniqueItems`: exact, except that
+/// `-0.0 == 0.0` and `1 == 1.0`, as JSON Schema compares numbers by value.
+const SCHEMA_EQUALITY: Tolerance = Tolerance {
+    signed_zero_equal: true,
+    int_float_equal: true,
+    ..Tolerance::EXACT
+};
+
+/// Validate `value` against `schema`, returning every violation found.
+///
+/// An empty result means the value conforms.
+///
+/// Supports a subset of draft 2020-12: boolean schemas, `type`, `enum`,
+/// `const`, `properties`, `required`, `additionalProperties`,
+/// `minProperties`/`maxProperties`, `items`, `prefixItems`,
+/// `minItems`/`maxItems`, `uniqueItems`, `minLength`/`maxLength`, `pattern`,
+/// `minimum`/`maximum`, `exclusiveMinimum`/`exclusiveMaximum`, `multipleOf`,
+/// `allOf`/`anyOf`/`oneOf`/`not`, and local `$ref`s (`#`, `#/$defs/...`).
+/// Other keywords are ignored.
+#[must_use]
+pub fn validate(value: &JsonValue, schema: &JsonValue) -> Vec<SchemaViolation> {
+    let mut validator = Validator {
+        root: schema,
+        patterns: HashMap::new(),
+        ref_depth: 0,
+    };
+    let mut violations = Vec::new();
+    validator.check(value, schema, &mut String::new(), &mut violations);
+    violations
+}
+
+struct Validator<'s> {
+    root: &'s JsonValue,
+    patterns: HashMap<&'s str, Option<Regex>>,
+    ref_depth: usize,
+}
+
+impl<'s> Validator<'s> {
+    fn check(
+        &mut self,
+        value: &JsonValue,
+        schema: &'s JsonValue,
+        path: &mut String,
+        out: &mut Vec<SchemaViolation>,
+    ) {
+        let keywords = match schema {
+            JsonValue::Object(entries) => Keywords(entries),
+            JsonValue::Primitive(StringOrNumberOrBoolOrNull::Bool(false)) => {
+                out.push(violation(path, "false", "no value is allowed here"));
+                return;
+            }
+            _ => return,
+        };
+        let mut found = Vec::new();
+
+        check_type_and_equality(value, &keywords, &mut found);
+        match value {
+            JsonValue::Primitive(StringOrNumberOrBoolOrNull::String(text)) => {
+                self.check_string(text, &keywords, &mut found);
+            }
+            JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(number)) => {
+                check_number(number.as_f64(), &keywords, &mut found);
+            }
+            JsonValue::Array(_) | JsonValue::Object(_) => check_size(value, &keywords, &mut found),
+            JsonValue::Primitive(_) => {}
+        }
+        out.extend(
+            found
+                .into_iter()
+                .map(|(keyword, message)| violation(path, keyword, &message)),
+        );
+
+        self.check_children(value, &keywords, path, out);
+        self.check_combinators(value, &keywords, path, out);
+    }
+
+    fn check_string(
+        &mut self,
+        text: &str,
+        keywords: &Keywords<'s>,
+        found: &mut Vec<(&'static str, String)>,
+    ) {
+        let length = text.chars().count();
+        if let Some(min) = keywords.get("minLength").and_then(as_usize) {
+            if length < min {
+                found.push(("minLength", format!("string shorter than {min} characters")));
+            }
+        }
+        if let Some(max) = keywords.get("maxLength").and_then(as_usize) {
+            if length > max {
+                found.push(("maxLength", format!("string longer than {max} characters")));
+            }
+        }
+        if let Some(pattern) = keywords.get("pattern").and_then(as_str) {
+            match self
+                .patterns
+                .entry(pattern)
+                .or_insert_with(|| Regex::new(pattern).ok())
+            {
+                Some(regex) if !regex.is_match(text) => {
+                    found.push(("pattern", format!("string does not match `{pattern}`")));
+                }
+                Some(_) => {}
+                None => found.push(("pattern", format!("invalid pattern `{pattern}`"))),
+            }
+        }
+    }
+
+    fn check_children(
+        &mut self,
+        value: &JsonValue,
+        keywords: &Keywords<'s>,
+        path: &mut String,
+        out: &mut Vec<SchemaViolation>,
+    ) {
+        match value {
+            JsonValue::Array(items) => {
+                let prefix: &[JsonValue] = match keywords.get("prefixItems") {
+                    Some(JsonValue::Array(prefix)) => prefix,
+                    _ => &[],
+                };
+                let rest = keywords.get("items");
+                for (index, item) in items.iter().enumerate() {
+                    let Some(item_schema) = prefix.get(index).or(rest) else {
+                        continue;
+                    };
+                    let len = path.len();
+                    push_pointer_token(path, &index.to_string());
+                    self.check(item, item_schema, path, out);
+                    path.truncate(len);
+                }
+            }
+            JsonValue::Object(fields) => {
+                let properties = match keywords.get("properties") {
+                    Some(JsonValue::Object(properties)) => properties.as_slice(),
+                    _ => &[],
+                };
+                let additional = keywords.get("additionalProperties");
+                for (key, item) in fields {
+                    let declared = properties
+                        .iter()
+                        .find(|(name, _)| name == key)
+                        .map(|(_, schema)| schema);
+                    let Some(field_schema) = declared.or(additional) else {
+                        continue;
+                    };
+                    let len = path.len();
+                    push_pointer_token(path, key);
+                    if declared.is_none() && field_schema == &JsonValue::from(false) {
+                        out.push(SchemaViolation {
+                            path: path.clone(),
+                            keyword: "additionalProperties".to_string(),
+                            message: format!("property `{key}` is not allowed"),
+                        });
+                    } else {
+                        self.check(item, field_schema, path, out);
+                    }
+                    path.truncate(len);
+                }
+            }
+            JsonValue::Primitive(_) => {}
+        }
+    }
+
+    fn check_combinators(
+        &mut self,
+        value: &JsonValue,
+        keywords: &Keywords<'s>,
+        path: &mut String,
+        out: &mut Vec<SchemaViolation>,
+    ) {
+        if let Some(JsonValue::Array(schemas)) = keywords.get("allOf") {
+            for schema in schemas {
+                self.check(value, schema, path, out);
+            }
+        }
+        if let Some(JsonValue::Array(schemas)) = keywords.get("anyOf") {
+            if !schemas
+                .iter()
+                .any(|schema| self.conforms(value, schema, path))
+            {
+                out.push(violation(
+                    path,
+                    "anyOf",
+                    "value matches none of the `anyOf` schemas",
+                ));
+            }
+        }
+        if let Some(JsonValue::Array(schemas)) = keywords.get("oneOf") {
+            let matched = schemas
+                .iter()
+                .filter(|schema| self.conforms(value, schema, path))
+                .count();
+            if matched != 1 {
+                out.push(violation(
+                    path,
+                    "oneOf",
+                    &format!("value matches {matched} of the `oneOf` schemas, expected exactly 1"),
+                ));
+            }
+        }
+        if let Some(schema) = keywords.get("not") {
+            if self.conforms(value, schema, path) {
+                out.push(violation(path, "not", "value matches the `not` schema"));
+            }
+        }
+        if let Some(reference) = keywords.get("$ref").and_then(as_str) {
+            let Some(target) = self.resolve_ref(reference) else {
+                out.push(violation(
+                    path,
+                    "$ref",
+                    &format!("cannot resolve reference `{reference}`"),
+                ));
+                return;
+            };
+            if self.ref_depth >= MAX_REF_DEPTH {
+                out.push(violation(path, "$ref", "reference nesting is too deep"));
+                return;
+            }
+            self.ref_depth += 1;
+            self.check(value, target, path, out);
+            self.ref_depth -= 1;
+        }
+    }
+
+    fn conforms(&mut self, value: &JsonValue, schema: &'s JsonValue, path: &mut String) -> bool {
+        let mut scratch = Vec::new();
+        self.check(value, schema, path, &mut scratch);
+        scratch.is_empty()
+    }
+
+    fn resolve_ref(&self, reference: &str) -> Option<&'s JsonValue> {
+        let pointer = reference.strip_prefix('#')?;
+        let mut target = self.root;
+        for token in pointer.split('/').skip(1) {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            target = match target {
+                JsonValue::Object(entries) => &entries.iter().find(|(key, _)| *key == token)?.1,
+                JsonValue::Array(items) => items.get(token.parse::<usize>().ok()?)?,
+                JsonValue::Primitive(_) => return None,
+            };
+        }
+        Some(target)
+    }
+}
+
+/// The keywords of an object schema.
+struct Keywords<'s>(&'s [(String, JsonValue)]);
+
+impl<'s> Keywords<'s> {
+    fn get(&self, name: &str) -> Option<&'s JsonValue> {
+        self.0
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value)
+    }
+}
+
+fn check_type_and_equality(
+    value: &JsonValue,
+    keywords: &Keywords<'_>,
+    found: &mut Vec<(&'static str, String)>,
+) {
+    if let Some(expected) = keywords.get("type") {
+        let names: Vec<&str> = match expected {
+            JsonValue::Primitive(StringOrNumberOrBoolOrNull::String(name)) => vec![name],
+            JsonValue::Array(names) => names.iter().filter_map(as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !names.iter().any(|name| has_type(value, name)) {
+            found.push((
+                "type",
+                format!(
+                    "expected {}, found {}",
+                    names.join(" or "),
+                    type_name(value)
+                ),
+            ));
+        }
+    }
+    if let Some(JsonValue::Array(options)) = keywords.get("enum") {
+        if !options
+            .iter()
+            .any(|option| option.semantic_eq(value, SCHEMA_EQUALITY))
+        {
+            found.push(("enum", "value is not one of the allowed values".to_string()));
+        }
+    }
+    if let Some(expected) = keywords.get("const") {
+        if !expected.semantic_eq(value, SCHEMA_EQUALITY) {
+            found.push((
+                "const",
+                "value does not equal the required constant".to_string(),
+            ));
+        }
+    }
+}
+
+fn check_number(number: f64, keywords: &Keywords<'_>, found: &mut Vec<(&'static str, String)>) {
+    if let Some(min) = keywords.get("minimum").and_then(as_f64) {
+        if number < min {
+            found.push(("minimum", format!("{number} is less than {min}")));
+        }
+    }
+    if let Some(max) = keywords.get("maximum").and_then(as_f64) {
+        if number > max {
+            found.push(("maximum", format!("{number} is greater than {max}")));
+        }
+    }
+    if let Some(min) = keywords.get("exclusiveMinimum").and_then(as_f64) {
+        if number <= min {
+            found.push((
+                "exclusiveMinimum",
+                format!("{number} is not greater than {min}"),
+            ));
+        }
+    }
+    if let Some(max) = keywords.get("exclusiveMaximum").and_then(as_f64) {
+        if number >= max {
+            found.push((
+                "exclusiveMaximum",
+                format!("{number} is not less than {max}"),
+            ));
+        }
+    }
+    if let Some(divisor) = keywords.get("multipleOf").and_then(as_f64) {
+        let quotient = number / divisor;
+        if divisor > 0.0 && (quotient - quotient.round()).abs() > 1e-9 {
+            found.push((
+                "multipleOf",
+                format!("{number} is not a multiple of {divisor}"),
+            ));
+        }
+    }
+}
+
+fn check_size(value: &JsonValue, keywords: &Keywords<'_>, found: &mut Vec<(&'static str, String)>) {
+    let (len, min_keyword, max_keyword, noun) = match value {
+        JsonValue::Array(items) => (items.len(), "minItems", "maxItems", "array items"),
+        JsonValue::Object(fields) => (
+            fields.len(),
+            "minProperties",
+            "maxProperties",
+            "object properties",
+        ),
+        JsonValue::Primitive(_) => return,
+    };
+    if let Some(min) = keywords.get(min_keyword).and_then(as_usize) {
+        if len < min {
+            found.push((min_keyword, format!("fewer than {min} {noun}")));
+        }
+    }
+    if let Some(max) = keywords.get(max_keyword).and_then(as_usize) {
+        if len > max {
+            found.push((max_keyword, format!("more than {max} {noun}")));
+        }
+    }
+
+    match value {
+        JsonValue::Array(items) if keywords.get("uniqueItems") == Some(&JsonValue::from(true)) => {
+            let duplicate = items.iter().enumerate().any(|(index, item)| {
+                items[..index]
+                    .iter()
+                    .any(|earlier| earlier.semantic_eq(item, SCHEMA_EQUALITY))
+            });
+            if duplicate {
+                found.push(("uniqueItems", "array items are not unique".to_string()));
+            }
+        }
+        JsonValue::Object(fields) => {
+            if let Some(JsonValue::Array(required)) = keywords.get("required") {
+                for name in required.iter().filter_map(as_str) {
+                    if !fields.iter().any(|(key, _)| key == name) {
+                        found.push(("required", format!("missing required property `{name}`")));
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn violation(path: &str, keyword: &str, message: &str) -> SchemaViolation {
+    SchemaViolation {
+        path: path.to_string(),
+        keyword: keyword.to_string(),
+        message: message.to_string(),
+    }
+}
+
+fn has_type(value: &JsonValue, name: &str) -> bool {
+    match (name, value) {
+        ("null", JsonValue::Primitive(StringOrNumberOrBoolOrNull::Null))
+        | ("boolean", JsonValue::Primitive(StringOrNumberOrBoolOrNull::Bool(_)))
+        | ("number", JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(_)))
+        | ("string", JsonValue::Primitive(StringOrNumberOrBoolOrNull::String(_)))
+        | ("array", JsonValue::Array(_))
+        | ("object", JsonValue::Object(_)) => true,
+        ("integer", JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(number))) => {
+            number.is_integer() || (number.is_finite() && number.as_f64().fract() == 0.0)
+        }
+        _ => false,
+    }
+}
+
+const fn type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Null) => "null",
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Bool(_)) => "boolean",
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(_)) => "number",
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::String(_)) => "string",
+        JsonValue::Array(_) => "array",
+        JsonValue::Object(_) => "object",
+    }
+}
+
+fn as_str(value: &JsonValue) -> Option<&str> {
+    match value {
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::String(text)) => Some(text),
+        _ => None,
+    }
+}
+
+fn as_f64(value: &JsonValue) -> Option<f64> {
+    match value {
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(number)) => Some(number.as_f64()),
+        _ => None,
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn as_usize(value: &JsonValue) -> Option<usize> {
+    as_f64(value)
+        .filter(|number| *number >= 0.0 && number.fract() == 0.0)
+        .map(|number| number as usize)
+}
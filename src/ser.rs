@@ -0,0 +1,623 @@
+//! A `serde::Serializer` that turns any `T: Serialize` into a [`JsonValue`],
+//! so Rust types encode to TOON without a `serde_json::Value` round trip.
+//!
+//! The value is collected as a tree before encoding: a table header names
+//! its columns and row count, so every row must be known before the first
+//! line is written.
+//!
+//! ```
+//! #[derive(serde::Serialize)]
+//! struct User {
+//!     id: u32,
+//!     name: &'static str,
+//! }
+//!
+//! let users = vec![User { id: 1, name: "Ada" }, User { id: 2, name: "Bob" }];
+//! assert_eq!(toon::to_string(&users, None).unwrap(), "[2]{id,name}:\n  1,Ada\n  2,Bob");
+//! ```
+
+use serde::ser::{self, Serialize};
+
+use crate::error::{Result, ToonError};
+use crate::options::EncodeOptions;
+use crate::{JsonObject, JsonValue, Number, StringOrNumberOrBoolOrNull, try_encode};
+
+/// Serialize `value` and encode it as TOON.
+///
+/// # Errors
+///
+/// Returns an error if `value` fails to serialize (for example a map with
+/// non-scalar keys) or fails [`try_encode`]'s checks.
+pub fn to_string<T: Serialize + ?Sized>(
+    value: &T,
+    options: Option<EncodeOptions>,
+) -> Result<String> {
+    try_encode(to_value(value)?, options)
+}
+
+/// Serialize `value` into a [`JsonValue`].
+///
+/// # Errors
+///
+/// Returns an error if `value` fails to serialize, for example a map with
+/// non-scalar keys.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<JsonValue> {
+    value.serialize(Serializer)
+}
+
+/// Objects serialize as maps in entry order, repeated keys included, so
+/// [`to_value`] returns an equal value. Numbers serialize as the nearest
+/// serde number: `bignum` integers as `i128`/`u128` where they fit and
+/// decimals as `f64`.
+///
+/// ```
+/// let value = toon::decode("id: 7\ntags[2]: a,b", None);
+/// assert_eq!(serde_json::to_string(&value).unwrap(), r#"{"id":7,"tags":["a","b"]}"#);
+/// ```
+impl Serialize for JsonValue {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Self::Primitive(value) => value.serialize(serializer),
+            Self::Array(items) => serializer.collect_seq(items),
+            Self::Object(entries) => {
+                serializer.collect_map(entries.iter().map(|(key, value)| (key, value)))
+            }
+        }
+    }
+}
+
+impl Serialize for StringOrNumberOrBoolOrNull {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Self::String(value) => serializer.serialize_str(value),
+            Self::Number(value) => value.serialize(serializer),
+            Self::Bool(value) => serializer.serialize_bool(*value),
+            Self::Null => serializer.serialize_unit(),
+        }
+    }
+}
+
+impl Serialize for Number {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Self::I64(value) => serializer.serialize_i64(*value),
+            Self::U64(value) => serializer.serialize_u64(*value),
+            Self::F64(value) => serializer.serialize_f64(*value),
+            Self::Raw(text) => Self::parse_literal(text).serialize(serializer),
+            #[cfg(feature = "bignum")]
+            Self::BigInt(value) => {
+                use bigdecimal::ToPrimitive;
+                if let Some(value) = value.to_i128() {
+                    serializer.serialize_i128(value)
+                } else if let Some(value) = value.to_u128() {
+                    serializer.serialize_u128(value)
+                } else {
+                    serializer.serialize_f64(self.as_f64())
+                }
+            }
+            #[cfg(feature = "bignum")]
+            Self::BigDecimal(_) => serializer.serialize_f64(self.as_f64()),
+        }
+    }
+}
+
+impl ser::Error for ToonError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Self::message(msg.to_string())
+    }
+}
+
+const fn primitive(value: StringOrNumberOrBoolOrNull) -> JsonValue {
+    JsonValue::Primitive(value)
+}
+
+const fn number_i64(value: i64) -> JsonValue {
+    primitive(StringOrNumberOrBoolOrNull::Number(Number::I64(value)))
+}
+
+fn number_u64(value: u64) -> JsonValue {
+    primitive(StringOrNumberOrBoolOrNull::Number(Number::from(value)))
+}
+
+fn string(value: impl Into<String>) -> JsonValue {
+    primitive(StringOrNumberOrBoolOrNull::String(value.into()))
+}
+
+/// Serializer whose output is a [`JsonValue`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Serializer;
+
+impl ser::Serializer for Serializer {
+    type Ok = JsonValue;
+    type Error = ToonError;
+
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeVariant<SerializeArray>;
+    type SerializeMap = SerializeObject;
+    type SerializeStruct = SerializeObject;
+    type SerializeStructVariant = SerializeVariant<SerializeObject>;
+
+    fn serialize_bool(self, value: bool) -> Result<JsonValue> {
+        Ok(primitive(StringOrNumberOrBoolOrNull::Bool(value)))
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<JsonValue> {
+        Ok(number_i64(i64::from(value)))
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<JsonValue> {
+        Ok(number_i64(i64::from(value)))
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<JsonValue> {
+        Ok(number_i64(i64::from(value)))
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<JsonValue> {
+        Ok(number_i64(value))
+    }
+
+    fn serialize_i128(self, value: i128) -> Result<JsonValue> {
+        Ok(primitive(StringOrNumberOrBoolOrNull::Number(Number::from(
+            value,
+        ))))
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<JsonValue> {
+        Ok(number_u64(u64::from(value)))
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<JsonValue> {
+        Ok(number_u64(u64::from(value)))
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<JsonValue> {
+        Ok(number_u64(u64::from(value)))
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<JsonValue> {
+        Ok(number_u64(value))
+    }
+
+    fn serialize_u128(self, value: u128) -> Result<JsonValue> {
+        Ok(primitive(StringOrNumberOrBoolOrNull::Number(Number::from(
+            value,
+        ))))
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<JsonValue> {
+        self.serialize_f64(f64::from(value))
+    }
+
+    /// Non-finite numbers are kept, so the encoder's
+    /// [`NonFinitePolicy`](crate::NonFinitePolicy) decides how they are written.
+    fn serialize_f64(self, value: f64) -> Result<JsonValue> {
+        Ok(primitive(StringOrNumberOrBoolOrNull::Number(Number::F64(
+            value,
+        ))))
+    }
+
+    fn serialize_char(self, value: char) -> Result<JsonValue> {
+        Ok(string(value))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<JsonValue> {
+        Ok(string(value))
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<JsonValue> {
+        Ok(JsonValue::Array(
+            value
+                .iter()
+                .map(|byte| number_u64(u64::from(*byte)))
+                .collect(),
+        ))
+    }
+
+    fn serialize_none(self) -> Result<JsonValue> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<JsonValue> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<JsonValue> {
+        Ok(primitive(StringOrNumberOrBoolOrNull::Null))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<JsonValue> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<JsonValue> {
+        Ok(string(variant))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<JsonValue> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<JsonValue> {
+        Ok(JsonValue::Object(vec![(
+            variant.to_string(),
+            value.serialize(self)?,
+        )]))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray> {
+        Ok(SerializeArray {
+            items: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeArray> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SerializeArray> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVariant<SerializeArray>> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeObject> {
+        Ok(SerializeObject {
+            entries: Vec::with_capacity(len.unwrap_or(0)),
+            next_key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeObject> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVariant<SerializeObject>> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_map(Some(len))?,
+        })
+    }
+}
+
+/// Collects sequence and tuple elements.
+#[derive(Debug)]
+pub struct SerializeArray {
+    items: Vec<JsonValue>,
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = JsonValue;
+    type Error = ToonError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.items.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<JsonValue> {
+        Ok(JsonValue::Array(self.items))
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = JsonValue;
+    type Error = ToonError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<JsonValue> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = JsonValue;
+    type Error = ToonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<JsonValue> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+/// Collects map entries and struct fields, in order.
+#[derive(Debug)]
+pub struct SerializeObject {
+    entries: JsonObject,
+    next_key: Option<String>,
+}
+
+impl ser::SerializeMap for SerializeObject {
+    type Ok = JsonValue;
+    type Error = ToonError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        self.next_key = Some(key.serialize(MapKeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let key = self
+            .next_key
+            .take()
+            .ok_or_else(|| ToonError::message("serialize_value called before serialize_key"))?;
+        self.entries.push((key, value.serialize(Serializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<JsonValue> {
+        Ok(JsonValue::Object(self.entries))
+    }
+}
+
+impl ser::SerializeStruct for SerializeObject {
+    type Ok = JsonValue;
+    type Error = ToonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.entries
+            .push((key.to_string(), value.serialize(Serializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<JsonValue> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+/// Wraps a tuple or struct variant as `{variant: ...}`.
+#[derive(Debug)]
+pub struct SerializeVariant<S> {
+    variant: &'static str,
+    inner: S,
+}
+
+impl<S> SerializeVariant<S> {
+    fn wrap(variant: &'static str, value: JsonValue) -> JsonValue {
+        JsonValue::Object(vec![(variant.to_string(), value)])
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeVariant<SerializeArray> {
+    type Ok = JsonValue;
+    type Error = ToonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        ser::SerializeSeq::serialize_element(&mut self.inner, value)
+    }
+
+    fn end(self) -> Result<JsonValue> {
+        Ok(Self::wrap(
+            self.variant,
+            ser::SerializeSeq::end(self.inner)?,
+        ))
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeVariant<SerializeObject> {
+    type Ok = JsonValue;
+    type Error = ToonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn end(self) -> Result<JsonValue> {
+        Ok(Self::wrap(
+            self.variant,
+            ser::SerializeMap::end(self.inner)?,
+        ))
+    }
+}
+
+/// Accepts the key types `serde_json` accepts: strings, chars, integers,
+/// booleans and unit variants, all written as strings.
+struct MapKeySerializer;
+
+fn key_must_be_string() -> ToonError {
+    ToonError::message("map keys must be strings, numbers, booleans or unit variants")
+}
+
+impl ser::Serializer for MapKeySerializer {
+    type Ok = String;
+    type Error = ToonError;
+
+    type SerializeSeq = ser::Impossible<String, ToonError>;
+    type SerializeTuple = ser::Impossible<String, ToonError>;
+    type SerializeTupleStruct = ser::Impossible<String, ToonError>;
+    type SerializeTupleVariant = ser::Impossible<String, ToonError>;
+    type SerializeMap = ser::Impossible<String, ToonError>;
+    type SerializeStruct = ser::Impossible<String, ToonError>;
+    type SerializeStructVariant = ser::Impossible<String, ToonError>;
+
+    fn serialize_bool(self, value: bool) -> Result<String> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<String> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<String> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<String> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<String> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<String> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<String> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<String> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<String> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_f32(self, _value: f32) -> Result<String> {
+        Err(key_must_be_string())
+    }
+
+    fn serialize_f64(self, _value: f64) -> Result<String> {
+        Err(key_must_be_string())
+    }
+
+    fn serialize_char(self, value: char) -> Result<String> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_str(self, value: &str) -> Result<String> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_bytes(self, _value: &[u8]) -> Result<String> {
+        Err(key_must_be_string())
+    }
+
+    fn serialize_none(self) -> Result<String> {
+        Err(key_must_be_string())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<String> {
+        Err(key_must_be_string())
+    }
+
+    fn serialize_unit(self) -> Result<String> {
+        Err(key_must_be_string())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String> {
+        Err(key_must_be_string())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<String> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String> {
+        Err(key_must_be_string())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(key_must_be_string())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(key_must_be_string())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(key_must_be_string())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(key_must_be_string())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(key_must_be_string())
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(key_must_be_string())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(key_must_be_string())
+    }
+}
@@ -5,6 +5,7 @@ pub const COMMA: char = ',';
 pub const COLON: char = ':';
 pub const SPACE: char = ' ';
 pub const PIPE: char = '|';
+pub const SEMICOLON: char = ';';
 pub const DOT: char = '.';
 
 pub const OPEN_BRACKET: char = '[';
@@ -141,5 +141,6 @@ pub fn is_numeric_literal(value: &str) -> bool {
         return false;
     }
 
-    trimmed.parse::<f64>().is_ok_and(f64::is_finite)
+    // With `bignum` every well-formed literal has an exact value.
+    cfg!(feature = "bignum") || trimmed.parse::<f64>().is_ok_and(f64::is_finite)
 }
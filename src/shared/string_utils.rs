@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use crate::shared::constants::{BACKSLASH, CARRIAGE_RETURN, DOUBLE_QUOTE, NEWLINE, TAB};
 
 #[must_use]
@@ -64,6 +66,20 @@ pub fn unescape_string(value: &str) -> Result<String, String> {
     Ok(out)
 }
 
+/// Like [`unescape_string`], but borrows `value` when it holds no escapes.
+///
+/// # Errors
+///
+/// Returns an error when the input contains invalid escape sequences or ends
+/// with a trailing backslash.
+pub fn unescape_str(value: &str) -> Result<Cow<'_, str>, String> {
+    if value.contains(BACKSLASH) {
+        unescape_string(value).map(Cow::Owned)
+    } else {
+        Ok(Cow::Borrowed(value))
+    }
+}
+
 #[must_use]
 pub fn find_closing_quote(content: &str, start: usize) -> Option<usize> {
     let bytes = content.as_bytes();
@@ -1,5 +1,10 @@
-use crate::shared::constants::{DEFAULT_DELIMITER, LIST_ITEM_MARKER};
+use crate::options::PathSegment;
+use crate::shared::constants::{
+    CLOSE_BRACKET, COMMA, DEFAULT_DELIMITER, DOT, DOUBLE_QUOTE, LIST_ITEM_MARKER, OPEN_BRACKET,
+    PIPE, SEMICOLON, TAB,
+};
 use crate::shared::literal_utils::{is_boolean_or_null_literal, is_numeric_like};
+use crate::shared::string_utils::{find_closing_quote, unescape_string};
 
 #[must_use]
 pub fn is_valid_unquoted_key(key: &str) -> bool {
@@ -43,45 +48,104 @@ pub fn is_identifier_segment(segment: &str) -> bool {
     true
 }
 
+/// Split a folded key, such as `"a.b".c` or `a[0].b`, into its segments.
+///
+/// `None` unless the whole key is dot-joined keys, each an identifier or a
+/// quoted string and optionally followed by `[N]` indexes, with at least
+/// two segments and a key last.
+#[must_use]
+pub fn split_folded_key(key: &str) -> Option<Vec<PathSegment>> {
+    let mut segments = Vec::new();
+    let mut rest = key;
+    loop {
+        let (name, mut tail) = if rest.starts_with(DOUBLE_QUOTE) {
+            let closing = find_closing_quote(rest, 0)?;
+            (
+                unescape_string(&rest[1..closing]).ok()?,
+                &rest[closing + 1..],
+            )
+        } else {
+            let end = rest.find([DOT, OPEN_BRACKET]).unwrap_or(rest.len());
+            if !is_identifier_segment(&rest[..end]) {
+                return None;
+            }
+            (rest[..end].to_string(), &rest[end..])
+        };
+        segments.push(PathSegment::Key(name));
+        while let Some(index) = tail.strip_prefix(OPEN_BRACKET) {
+            let close = index.find(CLOSE_BRACKET)?;
+            let digits = &index[..close];
+            if digits.is_empty()
+                || !digits.bytes().all(|b| b.is_ascii_digit())
+                || (digits.len() > 1 && digits.starts_with('0'))
+            {
+                return None;
+            }
+            segments.push(PathSegment::Index(digits.parse().ok()?));
+            tail = &index[close + 1..];
+        }
+        if tail.is_empty() {
+            break;
+        }
+        rest = tail.strip_prefix(DOT)?;
+    }
+    let ends_with_key = matches!(segments.last(), Some(PathSegment::Key(_)));
+    (ends_with_key && segments.len() > 1).then_some(segments)
+}
+
 #[must_use]
 pub fn is_safe_unquoted(value: &str, delimiter: char) -> bool {
+    unquoted_rejection(value, delimiter).is_none()
+}
+
+/// Why `value` must be quoted when written with `delimiter`, or `None` if it
+/// can be written bare.
+#[must_use]
+pub fn unquoted_rejection(value: &str, delimiter: char) -> Option<&'static str> {
     if value.is_empty() {
-        return false;
+        return Some("empty string");
     }
 
     if value.trim() != value {
-        return false;
+        return Some("leading or trailing whitespace");
     }
 
     if is_boolean_or_null_literal(value) || is_numeric_like(value) {
-        return false;
+        return Some("would read as a number, boolean or null");
     }
 
     if value.contains(':') {
-        return false;
+        return Some("contains `:`");
     }
 
     if value.contains('"') || value.contains('\\') {
-        return false;
+        return Some("contains a quote or backslash");
     }
 
     if value.contains('[') || value.contains(']') || value.contains('{') || value.contains('}') {
-        return false;
+        return Some("contains a bracket or brace");
     }
 
     if value.contains('\n') || value.contains('\r') || value.contains('\t') {
-        return false;
+        return Some("contains a control character");
     }
 
     if value.contains(delimiter) {
-        return false;
+        return Some("contains the delimiter");
     }
 
     if value.starts_with(LIST_ITEM_MARKER) {
-        return false;
+        return Some("starts with the list marker `-`");
     }
 
-    true
+    None
+}
+
+/// Whether `delimiter` is one of the array delimiters TOON supports:
+/// comma, tab, pipe, or semicolon.
+#[must_use]
+pub const fn is_supported_delimiter(delimiter: char) -> bool {
+    matches!(delimiter, COMMA | TAB | PIPE | SEMICOLON)
 }
 
 #[must_use]
@@ -0,0 +1,715 @@
+//! Typed access to tabular arrays, appending rows to them
+//! ([`TableAppender`]) and streaming them out ([`TabularWriter`]).
+//!
+//! A [`TableSpec`] lists the columns a table must have. Decoding through it
+//! checks the header before any row is converted, so a renamed or dropped
+//! column fails with the header's line number instead of a confusing
+//! per-row serde error:
+//!
+//! ```
+//! #[derive(Debug, serde::Deserialize)]
+//! struct User {
+//!     id: u32,
+//!     email: String,
+//! }
+//!
+//! let users: Vec<User> =
+//!     toon::decode_table("users[2]{id,email}:\n  1,a@x.io\n  2,b@x.io", "users", None).unwrap();
+//! assert_eq!(users[1].email, "b@x.io");
+//!
+//! let err = toon::decode_table::<User>("users[1]{id}:\n  1", "users", None).unwrap_err();
+//! assert_eq!(err.to_string(), "Validation error at line 1: column `email` missing");
+//! ```
+
+use std::fmt;
+use std::io::{self, Seek, SeekFrom, Write};
+
+use serde::de::{self, DeserializeOwned, Visitor};
+
+use crate::decode::parser::parse_array_header_line;
+use crate::decode::scanner::{create_scan_state, parse_lines_sync};
+use crate::encode::encoders::encode_root_table_row;
+use crate::encode::prepare_value_at;
+use crate::encode::primitives::{encode_key, encode_primitive, format_header};
+use crate::error::{Result, ToonError};
+use crate::options::{
+    DecodeOptions, EncodeOptions, PathSegment, QuotePolicy, ResolvedEncodeOptions,
+    resolve_decode_options, resolve_encode_options,
+};
+use crate::shared::constants::DEFAULT_DELIMITER;
+use crate::{JsonValue, try_decode};
+
+/// The columns a table is expected to have.
+///
+/// By default the header must list exactly these columns in this order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableSpec {
+    columns: Vec<String>,
+    allow_reordered: bool,
+    allow_extra: bool,
+}
+
+impl TableSpec {
+    #[must_use]
+    pub fn new<I, S>(columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            columns: columns.into_iter().map(Into::into).collect(),
+            allow_reordered: false,
+            allow_extra: false,
+        }
+    }
+
+    /// The spec for a struct's serde field names, in declaration order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `T` does not deserialize as a plain struct (for
+    /// example maps, enums or structs using `#[serde(flatten)]`).
+    pub fn of<T: DeserializeOwned>() -> Result<Self> {
+        let mut fields = None;
+        // The probe always errors; it only exists to observe the field list.
+        let _ = T::deserialize(FieldProbe(&mut fields));
+        fields
+            .map(|fields| Self::new(fields.iter().copied()))
+            .ok_or_else(|| {
+                ToonError::message(format!(
+                    "TableSpec::of: `{}` does not deserialize as a struct",
+                    std::any::type_name::<T>()
+                ))
+            })
+    }
+
+    /// Accept the columns in any order.
+    #[must_use]
+    pub const fn allow_reordered(mut self) -> Self {
+        self.allow_reordered = true;
+        self
+    }
+
+    /// Accept header columns the spec does not list.
+    #[must_use]
+    pub const fn allow_extra(mut self) -> Self {
+        self.allow_extra = true;
+        self
+    }
+
+    #[must_use]
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Check a table header against the spec; `line` is reported in errors.
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error naming the first missing, unexpected or
+    /// misplaced column.
+    pub fn verify<S: AsRef<str>>(&self, header: &[S], line: usize) -> Result<()> {
+        let header: Vec<&str> = header.iter().map(AsRef::as_ref).collect();
+        if let Some(missing) = self
+            .columns
+            .iter()
+            .find(|column| !header.contains(&column.as_str()))
+        {
+            return Err(ToonError::validation(
+                line,
+                format!("column `{missing}` missing"),
+            ));
+        }
+        if !self.allow_extra {
+            if let Some(extra) = header
+                .iter()
+                .find(|name| !self.columns.iter().any(|column| column == *name))
+            {
+                return Err(ToonError::validation(
+                    line,
+                    format!("unexpected column `{extra}`"),
+                ));
+            }
+        }
+        if !self.allow_reordered {
+            let listed = header
+                .iter()
+                .filter(|name| self.columns.iter().any(|column| column == *name));
+            for (position, (found, expected)) in listed.zip(&self.columns).enumerate() {
+                if found != expected {
+                    return Err(ToonError::validation(
+                        line,
+                        format!(
+                            "column `{found}` at position {}, expected `{expected}`",
+                            position + 1
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Decode the table under top-level key `key` (`""` for a root table),
+    /// verifying its header first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if decoding fails, the table is missing or is not
+    /// tabular, the header does not match, or a row does not convert to `T`
+    /// (reported with the row's line number).
+    pub fn decode<T: DeserializeOwned>(
+        &self,
+        input: &str,
+        key: &str,
+        options: Option<DecodeOptions>,
+    ) -> Result<Vec<T>> {
+        let resolved = resolve_decode_options(options.clone());
+        let lines = parse_lines_sync(
+            input.split('\n').map(ToString::to_string),
+            resolved.indent,
+            resolved.strictness.tabs,
+            resolved.strictness.indentation,
+            resolved.max_line_length,
+            &mut create_scan_state(),
+        )?;
+
+        let mut header = None;
+        for (index, line) in lines.iter().enumerate() {
+            if line.depth != 0 {
+                continue;
+            }
+            let Some(parsed) = parse_array_header_line(&line.content, DEFAULT_DELIMITER)? else {
+                continue;
+            };
+            if parsed.header.key.as_deref().unwrap_or("") == key {
+                header = Some((index, line.line_number, parsed.header.fields));
+                break;
+            }
+        }
+        let Some((header_index, header_line, fields)) = header else {
+            return Err(ToonError::message(format!("table `{key}` not found")));
+        };
+        let Some(fields) = fields else {
+            return Err(ToonError::validation(
+                header_line,
+                format!("`{key}` is not a table"),
+            ));
+        };
+        let names: Vec<&str> = fields.iter().map(|field| field.name.as_ref()).collect();
+        self.verify(&names, header_line)?;
+
+        let rows = match try_decode(input, options)? {
+            JsonValue::Array(rows) if key.is_empty() => rows,
+            JsonValue::Object(entries) => entries
+                .into_iter()
+                .find(|(name, _)| name == key)
+                .and_then(|(_, value)| match value {
+                    JsonValue::Array(rows) => Some(rows),
+                    _ => None,
+                })
+                .ok_or_else(|| ToonError::message(format!("table `{key}` not found")))?,
+            _ => return Err(ToonError::message(format!("table `{key}` not found"))),
+        };
+
+        rows.into_iter()
+            .enumerate()
+            .map(|(row, value)| {
+                serde_json::from_value(crate::exact::to_exact_json(value)).map_err(|err| {
+                    let line = lines
+                        .get(header_index + 1 + row)
+                        .map_or(header_line, |line| line.line_number);
+                    ToonError::parse(line, format!("row {}: {err}", row + 1))
+                })
+            })
+            .collect()
+    }
+}
+
+/// Decode the table under top-level key `key` (`""` for a root table) into
+/// `T` rows, requiring its header to list `T`'s fields in declaration order.
+///
+/// Use [`TableSpec`] directly to relax the order or allow extra columns.
+///
+/// # Errors
+///
+/// See [`TableSpec::of`] and [`TableSpec::decode`].
+pub fn decode_table<T: DeserializeOwned>(
+    input: &str,
+    key: &str,
+    options: Option<DecodeOptions>,
+) -> Result<Vec<T>> {
+    TableSpec::of::<T>()?.decode(input, key, options)
+}
+
+/// Appends rows to a top-level table of an existing document, keeping its
+/// length marker in step.
+///
+/// Suited to processes that log rows over time: keep the appender, push rows
+/// as they arrive and write [`to_string`](ToString::to_string) out whenever
+/// the document should be persisted.
+///
+/// ```
+/// use toon::JsonValue;
+/// use toon::table::TableAppender;
+///
+/// let mut log = TableAppender::open("events[1]{at,kind}:\n  1,start", "events").unwrap();
+/// log.append(&JsonValue::Object(vec![
+///     ("kind".to_string(), JsonValue::from("stop")),
+///     ("at".to_string(), JsonValue::from(2.0)),
+/// ]))
+/// .unwrap();
+/// assert_eq!(log.to_string(), "events[2]{at,kind}:\n  1,start\n  2,stop");
+/// ```
+#[derive(Debug, Clone)]
+pub struct TableAppender {
+    lines: Vec<String>,
+    header_index: usize,
+    row_indent: String,
+    key: String,
+    fields: Option<Vec<String>>,
+    delimiter: char,
+    length: usize,
+}
+
+impl TableAppender {
+    /// Open the table under top-level key `key` (`""` for a root table).
+    ///
+    /// An empty array (`key[0]:`) is accepted too; its columns are taken
+    /// from the first appended row. New rows reuse the indentation of the
+    /// existing ones, or two spaces past the header when there are none.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the table is missing, is a non-empty array that
+    /// is not tabular, or its header is malformed.
+    pub fn open(document: &str, key: &str) -> Result<Self> {
+        let lines: Vec<String> = document.split('\n').map(ToString::to_string).collect();
+        for (index, line) in lines.iter().enumerate() {
+            let content = line.trim_start_matches(' ');
+            if content.len() != line.len() || content.trim().is_empty() {
+                continue;
+            }
+            let Some(parsed) = parse_array_header_line(content, DEFAULT_DELIMITER)? else {
+                continue;
+            };
+            if parsed.header.key.as_deref().unwrap_or("") != key {
+                continue;
+            }
+            let header = parsed.header;
+            let fields = header.fields.map(|fields| {
+                fields
+                    .into_iter()
+                    .map(|field| field.name.into_owned())
+                    .collect()
+            });
+            if fields.is_none() && (header.length > 0 || parsed.inline_values.is_some()) {
+                return Err(ToonError::validation(
+                    index + 1,
+                    format!("`{key}` is not a table"),
+                ));
+            }
+            let row_indent = lines
+                .get(index + 1)
+                .filter(|_| header.length > 0)
+                .map_or_else(
+                    || "  ".to_string(),
+                    |row| " ".repeat(row.len() - row.trim_start_matches(' ').len()),
+                );
+            return Ok(Self {
+                header_index: index,
+                row_indent,
+                key: key.to_string(),
+                fields,
+                delimiter: header.delimiter,
+                length: header.length,
+                lines,
+            });
+        }
+        Err(ToonError::message(format!("table `{key}` not found")))
+    }
+
+    /// Append one row. Its keys must match the table's columns (in any
+    /// order) and its values must be primitives.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ToonError::InvalidValue`] naming the row when it is not an
+    /// object, misses or adds a column, or holds a nested value.
+    pub fn append(&mut self, row: &JsonValue) -> Result<()> {
+        let path = if self.key.is_empty() {
+            format!("[{}]", self.length)
+        } else {
+            format!("{}[{}]", self.key, self.length)
+        };
+        let JsonValue::Object(entries) = row else {
+            return Err(ToonError::invalid_value(path, "table rows must be objects"));
+        };
+        let fields = self
+            .fields
+            .get_or_insert_with(|| entries.iter().map(|(key, _)| key.clone()).collect());
+        if let Some((extra, _)) = entries.iter().find(|(key, _)| !fields.contains(key)) {
+            return Err(ToonError::invalid_value(
+                path,
+                format!("unexpected column `{extra}`"),
+            ));
+        }
+
+        let mut cells = Vec::with_capacity(fields.len());
+        for field in fields.iter() {
+            match entries.iter().find(|(key, _)| key == field) {
+                Some((_, JsonValue::Primitive(value))) => {
+                    cells.push(encode_primitive(
+                        value,
+                        self.delimiter,
+                        QuotePolicy::Minimal,
+                    ));
+                }
+                Some(_) => {
+                    return Err(ToonError::invalid_value(
+                        format!("{path}.{field}"),
+                        "table cells must be primitives",
+                    ));
+                }
+                None => {
+                    return Err(ToonError::invalid_value(
+                        path,
+                        format!("column `{field}` missing"),
+                    ));
+                }
+            }
+        }
+
+        let row_line = format!(
+            "{}{}",
+            self.row_indent,
+            cells.join(&self.delimiter.to_string())
+        );
+        self.lines
+            .insert(self.header_index + 1 + self.length, row_line);
+        self.length += 1;
+        let key = (!self.key.is_empty()).then_some(self.key.as_str());
+        self.lines[self.header_index] = format_header(
+            Some(self.length),
+            key,
+            self.fields.as_deref(),
+            self.delimiter,
+            QuotePolicy::Minimal,
+        );
+        Ok(())
+    }
+
+    /// Number of rows in the table, including appended ones.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.length
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+}
+
+impl fmt::Display for TableAppender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.lines.join("\n"))
+    }
+}
+
+/// Append `rows` to the table under top-level key `key` of `document`,
+/// returning the updated document. See [`TableAppender`].
+///
+/// # Errors
+///
+/// See [`TableAppender::open`] and [`TableAppender::append`].
+pub fn append_rows(document: &str, key: &str, rows: &[JsonValue]) -> Result<String> {
+    let mut appender = TableAppender::open(document, key)?;
+    for row in rows {
+        appender.append(row)?;
+    }
+    Ok(appender.to_string())
+}
+
+/// Digits reserved for the row count by [`TabularWriter::with_length_fixup`].
+const FIXUP_DIGITS: usize = 10;
+
+type LengthPatch<W> = fn(&mut W, u64, usize) -> io::Result<()>;
+
+/// Streams a table to an [`io::Write`] one row at a time, for row counts
+/// too large to hold as a [`JsonValue`] first.
+///
+/// The header is written up front, so the length is either left out
+/// (`rows[]{id,name}:`, see [`new`](Self::new)) or reserved and filled in
+/// by [`finish`](Self::finish) on a seekable writer (see
+/// [`with_length_fixup`](Self::with_length_fixup)).
+///
+/// ```
+/// use std::io::Cursor;
+/// use toon::JsonValue;
+/// use toon::table::TabularWriter;
+///
+/// let mut table =
+///     TabularWriter::with_length_fixup(Cursor::new(Vec::new()), "rows", ["id", "name"], None)
+///         .unwrap();
+/// for (id, name) in [(1.0, "Ada"), (2.0, "Lin")] {
+///     table.row([JsonValue::from(id), JsonValue::from(name)]).unwrap();
+/// }
+/// let out = table.finish().unwrap().into_inner();
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     "rows[0000000002]{id,name}:\n  1,Ada\n  2,Lin"
+/// );
+/// ```
+pub struct TabularWriter<W: Write> {
+    writer: W,
+    options: ResolvedEncodeOptions,
+    key: String,
+    fields: Vec<String>,
+    length: usize,
+    /// Rows passed to [`row`](Self::row), including any the replacer removed.
+    offered: usize,
+    fixup: Option<(u64, LengthPatch<W>)>,
+}
+
+impl<W: Write> TabularWriter<W> {
+    /// Write the header for a table under `key` (`""` for a root table)
+    /// without a length, as `key[]{fields}:`.
+    ///
+    /// Like the `key[]:` headers of [`LengthMarkers::Never`], this decodes
+    /// with [`Strictness::length_markers`] off; use
+    /// [`with_length_fixup`](Self::with_length_fixup) when the writer can
+    /// seek.
+    ///
+    /// [`LengthMarkers::Never`]: crate::options::LengthMarkers::Never
+    /// [`Strictness::length_markers`]: crate::options::Strictness::length_markers
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `fields` is empty or repeats a column, or the
+    /// writer fails.
+    pub fn new<I, S>(
+        writer: W,
+        key: &str,
+        fields: I,
+        options: Option<EncodeOptions>,
+    ) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut table = Self::open(writer, key, fields, options)?;
+        let header = table.header(None);
+        table.write(header.as_bytes())?;
+        Ok(table)
+    }
+
+    /// Append a row of cells, in column order.
+    ///
+    /// An [`EncodeOptions::replacer`] sees the row at `[key, index]` and its
+    /// cells at `[key, index, field]`, as when encoding the whole table (no
+    /// key for a root table), and skips the row if it removes it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cell count differs from the column count, a
+    /// cell is an array or object, or the writer fails.
+    pub fn row<I>(&mut self, cells: I) -> Result<()>
+    where
+        I: IntoIterator<Item = JsonValue>,
+    {
+        let cells: Vec<JsonValue> = cells.into_iter().collect();
+        if cells.len() != self.fields.len() {
+            return Err(ToonError::message(format!(
+                "row {} of table `{}` has {} cells, expected {}",
+                self.length + 1,
+                self.key,
+                cells.len(),
+                self.fields.len()
+            )));
+        }
+        if self.fixup.is_some() && (self.length + 1).to_string().len() > FIXUP_DIGITS {
+            return Err(ToonError::message(format!(
+                "table `{}` exceeds the {FIXUP_DIGITS} digits reserved for its length",
+                self.key
+            )));
+        }
+        let mut path = Vec::with_capacity(2);
+        if !self.key.is_empty() {
+            path.push(PathSegment::Key(self.key.clone()));
+        }
+        path.push(PathSegment::Index(self.offered));
+        self.offered += 1;
+        let row = JsonValue::Object(self.fields.iter().cloned().zip(cells).collect());
+        let Some(row) = prepare_value_at(row, &path, &self.options)? else {
+            return Ok(());
+        };
+        let line = encode_root_table_row(&row, &self.fields, &self.options).ok_or_else(|| {
+            ToonError::message(format!(
+                "row {} of table `{}`: cells must be primitives",
+                self.length + 1,
+                self.key
+            ))
+        })?;
+        let newline = self.options.line_ending.as_str();
+        self.write(newline.as_bytes())?;
+        self.write(line.as_bytes())?;
+        self.length += 1;
+        Ok(())
+    }
+
+    /// Number of rows written so far.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.length
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Fill in the reserved length, if any, flush and return the writer.
+    /// Nothing follows the last row, not even a newline.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the writer fails.
+    pub fn finish(mut self) -> Result<W> {
+        if let Some((offset, patch)) = self.fixup {
+            patch(&mut self.writer, offset, self.length)
+                .map_err(|err| ToonError::io("Failed to write table length", None, err))?;
+        }
+        self.writer
+            .flush()
+            .map_err(|err| ToonError::io("Failed to write table", None, err))?;
+        Ok(self.writer)
+    }
+
+    fn open<I, S>(writer: W, key: &str, fields: I, options: Option<EncodeOptions>) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let fields: Vec<String> = fields.into_iter().map(Into::into).collect();
+        if fields.is_empty() {
+            return Err(ToonError::message(format!("table `{key}` has no columns")));
+        }
+        if let Some(repeated) = fields
+            .iter()
+            .enumerate()
+            .find_map(|(index, field)| fields[..index].contains(field).then_some(field))
+        {
+            return Err(ToonError::message(format!(
+                "table `{key}` repeats column `{repeated}`"
+            )));
+        }
+        Ok(Self {
+            writer,
+            options: resolve_encode_options(options),
+            key: key.to_string(),
+            fields,
+            length: 0,
+            offered: 0,
+            fixup: None,
+        })
+    }
+
+    fn header(&self, length: Option<usize>) -> String {
+        let key = (!self.key.is_empty()).then_some(self.key.as_str());
+        format_header(
+            length,
+            key,
+            Some(&self.fields),
+            self.options.delimiter,
+            self.options.quote_strings,
+        )
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.writer
+            .write_all(bytes)
+            .map_err(|err| ToonError::io("Failed to write table", None, err))
+    }
+}
+
+impl<W: Write + Seek> TabularWriter<W> {
+    /// Like [`new`](Self::new), but reserves a zero-padded length
+    /// (`key[0000000000]{fields}:`) that [`finish`](Self::finish) seeks
+    /// back to and fills in, so the result decodes strictly. The padding
+    /// allows up to 9,999,999,999 rows.
+    ///
+    /// The filled-in header keeps its leading zeros (`rows[0000000002]`),
+    /// so the output is not canonical TOON: this crate decodes it, but a
+    /// decoder that insists on canonical numbers may reject it, and it
+    /// differs byte for byte from [`encode`](crate::encode) of the same
+    /// table. Decode and re-encode the result where canonical output
+    /// matters.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `fields` is empty or repeats a column, or the
+    /// writer fails.
+    pub fn with_length_fixup<I, S>(
+        writer: W,
+        key: &str,
+        fields: I,
+        options: Option<EncodeOptions>,
+    ) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut table = Self::open(writer, key, fields, options)?;
+        let start = table
+            .writer
+            .stream_position()
+            .map_err(|err| ToonError::io("Failed to write table", None, err))?;
+        let mut header = table.header(Some(0));
+        // `format_header` writes the key, then `[`, then the length.
+        let digits = encode_key(&table.key, table.options.quote_strings).len() + 1;
+        let digits = if table.key.is_empty() { 1 } else { digits };
+        header.replace_range(digits..=digits, &"0".repeat(FIXUP_DIGITS));
+        table.write(header.as_bytes())?;
+        table.fixup = Some((start + digits as u64, patch_length::<W>));
+        Ok(table)
+    }
+}
+
+fn patch_length<W: Write + Seek>(writer: &mut W, offset: u64, length: usize) -> io::Result<()> {
+    let end = writer.stream_position()?;
+    writer.seek(SeekFrom::Start(offset))?;
+    write!(writer, "{length:0FIXUP_DIGITS$}")?;
+    writer.seek(SeekFrom::Start(end))?;
+    Ok(())
+}
+
+/// A deserializer that records the field list a struct asks for.
+struct FieldProbe<'a>(&'a mut Option<&'static [&'static str]>);
+
+impl<'de> de::Deserializer<'de> for FieldProbe<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> std::result::Result<V::Value, Self::Error> {
+        Err(de::Error::custom("not a struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> std::result::Result<V::Value, Self::Error> {
+        *self.0 = Some(fields);
+        Err(de::Error::custom("fields recorded"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
@@ -0,0 +1,171 @@
+//! Path-pattern rules that rewrite matching values.
+//!
+//! A [`Transform`] holds rules pairing a [`PathPattern`] such as
+//! `users[*].email` with a closure. Apply it to a decoded value with
+//! [`Transform::apply`], or during encoding through
+//! [`Transform::into_replacer`]:
+//!
+//! ```
+//! use toon::transform::Transform;
+//! use toon::{EncodeOptions, JsonValue, encode};
+//!
+//! let redact = Transform::new()
+//!     .rule("users[*].email", |_| Some(JsonValue::from("***")))
+//!     .unwrap();
+//! let value = serde_json::json!({"users": [{"name": "Ada", "email": "ada@example.com"}]});
+//! let options = EncodeOptions::builder()
+//!     .replacer(redact.into_replacer())
+//!     .build();
+//! assert_eq!(encode(value, Some(options)), "users[1]{name,email}:\n  Ada,***");
+//! ```
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::JsonValue;
+use crate::error::{Result, ToonError};
+use crate::options::{EncodeReplacer, PathSegment};
+use crate::walk::WalkAction;
+
+/// Maps a matched value to its replacement; `None` removes it.
+pub type TransformFn = Arc<dyn Fn(&JsonValue) -> Option<JsonValue> + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PatternSegment {
+    Key(String),
+    AnyKey,
+    Index(usize),
+    AnyIndex,
+}
+
+/// A path with wildcards: dotted keys, `*` for any key, `[n]` for an index
+/// and `[*]` for any index (`users[*].email`, `config.*.enabled`).
+///
+/// Keys containing `.`, `[` or `*` cannot be matched literally.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathPattern {
+    segments: Vec<PatternSegment>,
+}
+
+impl PathPattern {
+    /// Whether `path` matches this pattern exactly (not as a prefix).
+    #[must_use]
+    pub fn matches(&self, path: &[PathSegment]) -> bool {
+        self.segments.len() == path.len()
+            && self
+                .segments
+                .iter()
+                .zip(path)
+                .all(|(pattern, segment)| match (pattern, segment) {
+                    (PatternSegment::Key(expected), PathSegment::Key(key)) => expected == key,
+                    (PatternSegment::AnyKey, PathSegment::Key(_))
+                    | (PatternSegment::AnyIndex, PathSegment::Index(_)) => true,
+                    (PatternSegment::Index(expected), PathSegment::Index(index)) => {
+                        expected == index
+                    }
+                    _ => false,
+                })
+    }
+}
+
+impl FromStr for PathPattern {
+    type Err = ToonError;
+
+    fn from_str(pattern: &str) -> Result<Self> {
+        let invalid = |reason: &str| {
+            ToonError::message(format!("Invalid path pattern `{pattern}`: {reason}"))
+        };
+
+        let mut segments = Vec::new();
+        for part in pattern.split('.') {
+            let (key, mut indices) = part.split_at(part.find('[').unwrap_or(part.len()));
+            if key.is_empty() && indices.is_empty() {
+                return Err(invalid("empty segment"));
+            }
+            match key {
+                "" => {}
+                "*" => segments.push(PatternSegment::AnyKey),
+                key if key.contains(['*', ']']) => return Err(invalid("unexpected `*` or `]`")),
+                key => segments.push(PatternSegment::Key(key.to_string())),
+            }
+            while !indices.is_empty() {
+                let (index, rest) = indices
+                    .strip_prefix('[')
+                    .and_then(|inner| inner.split_once(']'))
+                    .ok_or_else(|| invalid("expected `[index]` or `[*]`"))?;
+                segments.push(if index == "*" {
+                    PatternSegment::AnyIndex
+                } else {
+                    PatternSegment::Index(
+                        index
+                            .parse()
+                            .map_err(|_| invalid("index must be a number or `*`"))?,
+                    )
+                });
+                indices = rest;
+            }
+        }
+        Ok(Self { segments })
+    }
+}
+
+/// An ordered set of pattern rules. Every matching rule runs, in
+/// registration order, each seeing the previous rule's output.
+#[derive(Clone, Default)]
+pub struct Transform {
+    rules: Vec<(PathPattern, TransformFn)>,
+}
+
+impl Transform {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule applying `map` to values whose path matches `pattern`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `pattern` is malformed.
+    pub fn rule<F>(mut self, pattern: &str, map: F) -> Result<Self>
+    where
+        F: Fn(&JsonValue) -> Option<JsonValue> + Send + Sync + 'static,
+    {
+        self.rules.push((pattern.parse()?, Arc::new(map)));
+        Ok(self)
+    }
+
+    /// Run the rules for `path` on `value`; `None` means remove.
+    #[must_use]
+    pub fn apply_at(&self, path: &[PathSegment], value: &JsonValue) -> Option<JsonValue> {
+        let mut current: Option<JsonValue> = None;
+        for (pattern, map) in &self.rules {
+            if pattern.matches(path) {
+                current = Some(map(current.as_ref().unwrap_or(value))?);
+            }
+        }
+        Some(current.unwrap_or_else(|| value.clone()))
+    }
+
+    /// Apply the rules to a decoded value in place.
+    ///
+    /// Children of a rewritten node are matched against the rewritten value.
+    pub fn apply(&self, value: &mut JsonValue) {
+        value.walk_mut(|path, node| {
+            if !self.rules.iter().any(|(pattern, _)| pattern.matches(path)) {
+                return WalkAction::Continue;
+            }
+            self.apply_at(path, node)
+                .map_or(WalkAction::Remove, |replacement| {
+                    *node = replacement;
+                    WalkAction::Continue
+                })
+        });
+    }
+
+    /// Convert into an [`EncodeReplacer`] that applies the rules while encoding.
+    #[must_use]
+    pub fn into_replacer(self) -> EncodeReplacer {
+        Arc::new(move |_key, value, path| self.apply_at(path, value))
+    }
+}
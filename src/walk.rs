@@ -0,0 +1,67 @@
+//! In-place traversal of decoded values.
+
+use crate::options::PathSegment;
+use crate::{JsonValue, StringOrNumberOrBoolOrNull};
+
+/// What [`JsonValue::walk_mut`] does after visiting a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalkAction {
+    /// Descend into the node's children, as rewritten by the callback.
+    Continue,
+    /// Keep the node but do not visit its children.
+    SkipChildren,
+    /// Drop the node from its parent array or object.
+    Remove,
+}
+
+impl JsonValue {
+    /// Visit every node depth-first, parents before children, letting
+    /// `visit` rewrite a node in place or remove it.
+    ///
+    /// The path passed to `visit` is the one used by
+    /// [`EncodeReplacer`](crate::EncodeReplacer); array indices are positions
+    /// before any removals. Removing the root replaces it with `null`.
+    pub fn walk_mut<F>(&mut self, mut visit: F)
+    where
+        F: FnMut(&[PathSegment], &mut Self) -> WalkAction,
+    {
+        if !walk_node(self, &mut Vec::new(), &mut visit) {
+            *self = Self::Primitive(StringOrNumberOrBoolOrNull::Null);
+        }
+    }
+}
+
+/// Returns `false` when the node should be removed.
+fn walk_node<F>(value: &mut JsonValue, path: &mut Vec<PathSegment>, visit: &mut F) -> bool
+where
+    F: FnMut(&[PathSegment], &mut JsonValue) -> WalkAction,
+{
+    match visit(path, value) {
+        WalkAction::Remove => return false,
+        WalkAction::SkipChildren => return true,
+        WalkAction::Continue => {}
+    }
+
+    match value {
+        JsonValue::Primitive(_) => {}
+        JsonValue::Array(items) => {
+            let mut index = 0;
+            items.retain_mut(|item| {
+                path.push(PathSegment::Index(index));
+                index += 1;
+                let keep = walk_node(item, path, visit);
+                path.pop();
+                keep
+            });
+        }
+        JsonValue::Object(entries) => {
+            entries.retain_mut(|(key, item)| {
+                path.push(PathSegment::Key(key.clone()));
+                let keep = walk_node(item, path, visit);
+                path.pop();
+                keep
+            });
+        }
+    }
+    true
+}
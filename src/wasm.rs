@@ -23,11 +23,23 @@
 //! console.log(json);
 //! // {"name":"Alice","age":30}
 //!
+//! // JS values in and out; integers beyond 2^53 are BigInts both ways
+//! const value = decode_value('id: 9007199254740993', undefined);
+//! console.log(value.id);
+//! // 9007199254740993n
+//! console.log(encode_value({ id: 2n ** 60n }, undefined));
+//! // id: 1152921504606846976
+//!
 //! // With options
 //! const options = { keyFolding: 'safe', indent: 4 };
 //! const toonWithOptions = encode_with_options('{"a":{"b":{"c":1}}}', options);
 //! console.log(toonWithOptions);
 //! // a.b.c: 1
+//!
+//! // Streaming: NDJSON rows in, TOON chunks out
+//! for await (const chunk of encode_stream(response.body, rowCount, { fields: ['id', 'name'] })) {
+//!   sink.write(chunk);
+//! }
 //! ```
 
 use wasm_bindgen::prelude::*;
@@ -69,9 +81,20 @@ pub fn encode(json: &str) -> Result<String, JsError> {
 /// * `json` - A valid JSON string to encode
 /// * `options` - Encoding options as a JavaScript object:
 ///   - `indent`: Number of spaces per indent level (default: 2)
-///   - `delimiter`: Array delimiter character (default: ',')
-///   - `keyFolding`: 'off' or 'safe' (default: 'off')
+///   - `delimiter`: Array delimiter: ',', '\t', '|', or ';' (default: ',')
+///   - `keyFolding`: 'off', 'safe', or 'aggressive' (default: 'off')
 ///   - `flattenDepth`: Maximum depth for key folding (default: unlimited)
+///   - `delimiterOverrides`: Object mapping dotted array paths to delimiters
+///   - `lengthMarkers`: 'allArrays', 'tabularOnly', or 'never' (default: 'allArrays')
+///   - `sortKeys`: Write object keys in sorted order (default: false)
+///   - `quoteStrings`: 'minimal', 'alwaysStrings', or 'alwaysKeysAndStrings' (default: 'minimal')
+///   - `tabularMinRows`: Fewest rows for the table layout (default: 1)
+///   - `tabularMaxColumns`: Most columns for the table layout (default: unlimited)
+///   - `tabular`: Write arrays of uniform objects as tables (default: true)
+///   - `maxLineWidth`: Wrap inline arrays onto continuation lines past this width (default: no wrapping)
+///   - `indentStyle`: 'spaces' or 'tabs' (default: 'spaces')
+///   - `lineEnding`: 'lf' or 'crlf' (default: 'lf')
+///   - `foldSingleItemArrays`: Fold keys through arrays holding one single-key object (default: false)
 ///
 /// # Returns
 ///
@@ -85,6 +108,36 @@ pub fn encode_with_options(json: &str, options: JsValue) -> Result<String, JsErr
     Ok(crate::encode::encode(value, encode_options))
 }
 
+/// Encode a JavaScript value to TOON format.
+///
+/// # Arguments
+///
+/// * `value` - Any JSON-serializable value; `BigInt`s in it are written
+///   exactly when they fit in 64 bits (or are safe integers, on engines
+///   without `JSON.rawJSON`)
+/// * `options` - Optional `encode_with_options` options
+///
+/// # Returns
+///
+/// A TOON-formatted string, or throws an error if the value does not
+/// serialize.
+///
+/// # Example
+///
+/// ```javascript
+/// const toon = encode_value({ id: 2n ** 60n }, undefined);
+/// // Returns: "id: 1152921504606846976"
+/// ```
+#[wasm_bindgen]
+#[allow(clippy::needless_pass_by_value)]
+pub fn encode_value(value: JsValue, options: JsValue) -> Result<String, JsError> {
+    let json = value_json(&value)?;
+    let value: serde_json::Value =
+        serde_json::from_str(&json).map_err(|e| JsError::new(&format!("Invalid JSON: {e}")))?;
+    let encode_options = parse_encode_options(options)?;
+    Ok(crate::encode::encode(value, encode_options))
+}
+
 /// Decode a TOON string to JSON format.
 ///
 /// # Arguments
@@ -93,7 +146,9 @@ pub fn encode_with_options(json: &str, options: JsValue) -> Result<String, JsErr
 ///
 /// # Returns
 ///
-/// A compact JSON string, or throws an error if the TOON is invalid.
+/// A compact JSON string, or throws an error if the TOON is invalid. Its
+/// integers are exact, but `JSON.parse` rounds those beyond 2^53; use
+/// [`decode_value`] to get them as `BigInt`s.
 ///
 /// # Example
 ///
@@ -115,13 +170,23 @@ pub fn decode(toon: &str) -> Result<String, JsError> {
 ///
 /// * `toon` - A TOON-formatted string to decode
 /// * `options` - Decoding options as a JavaScript object:
-///   - `strict`: Enable strict validation (default: true)
-///   - `expandPaths`: 'off' or 'safe' (default: 'off')
+///   - `strict`: Enable the default strictness checks, or none of them (default: true)
+///   - `exactIndent`: Require indents to be multiples of `indent` (default: `strict`)
+///   - `expandPaths`: 'off', 'safe', 'indexed', or 'aggressive' (default: 'off')
+///   - `onConflict`: 'error', 'keepFirst', 'overwrite', 'merge', 'preferFolded', or 'preferLiteral'
+///     (default: 'error' when strict, 'merge' otherwise)
+///   - `keyOrder`: 'preserve' or 'sorted' (default: 'preserve')
 ///   - `indent`: Expected indent size (default: 2)
+///   - `duplicateKeys`: 'error', 'firstWins', 'lastWins', or 'collectIntoArray' for a key
+///     repeated within one object (default: every entry is kept)
+///   - `requireLengthMarkers`: Fail on array headers without a valid `[N]` (default: false)
+///   - `maxLineLength`: Reject lines longer than this many bytes (default: no limit)
+///   - `preserveNumberLiterals`: Keep numbers as their source text (default: false)
 ///
 /// # Returns
 ///
-/// A compact JSON string, or throws an error if the TOON is invalid.
+/// A compact JSON string, or throws an error if the TOON is invalid. As with
+/// [`decode`], use [`decode_value`] for integers beyond 2^53.
 #[wasm_bindgen]
 pub fn decode_with_options(toon: &str, options: JsValue) -> Result<String, JsError> {
     let decode_options = parse_decode_options(options)?;
@@ -131,22 +196,346 @@ pub fn decode_with_options(toon: &str, options: JsValue) -> Result<String, JsErr
     Ok(serde_json::to_string(&serde_value).unwrap_or_default())
 }
 
+/// Decode a TOON string to a JavaScript value.
+///
+/// # Arguments
+///
+/// * `toon` - A TOON-formatted string to decode
+/// * `options` - Optional `decode_with_options` options
+///
+/// # Returns
+///
+/// The decoded value, with integers beyond `Number.MAX_SAFE_INTEGER` as
+/// `BigInt`s and other numbers as numbers, or throws an error if the TOON is
+/// invalid.
+///
+/// # Example
+///
+/// ```javascript
+/// const value = decode_value('id: 9007199254740993\nscore: 1.5', undefined);
+/// // Returns: { id: 9007199254740993n, score: 1.5 }
+/// ```
+#[wasm_bindgen]
+pub fn decode_value(toon: &str, options: JsValue) -> Result<JsValue, JsError> {
+    let decode_options = parse_decode_options(options)?;
+    let value = crate::decode::try_decode(toon, decode_options)
+        .map_err(|e| JsError::new(&format!("Decode error: {e}")))?;
+    Ok(to_js(&value))
+}
+
+fn to_js(value: &crate::JsonValue) -> JsValue {
+    use crate::{JsonValue, StringOrNumberOrBoolOrNull};
+
+    match value {
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Null) => JsValue::NULL,
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Bool(value)) => JsValue::from_bool(*value),
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::String(text)) => JsValue::from_str(text),
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(number)) => {
+            unsafe_integer_digits(number)
+                .and_then(|digits| js_sys::BigInt::new(&JsValue::from_str(&digits)).ok())
+                .map_or_else(|| JsValue::from_f64(number.as_f64()), JsValue::from)
+        }
+        JsonValue::Array(items) => items.iter().map(to_js).collect::<js_sys::Array>().into(),
+        JsonValue::Object(entries) => {
+            let object = js_sys::Object::new();
+            for (key, item) in entries {
+                // Setting a property on a plain object cannot fail.
+                let _ = js_sys::Reflect::set(&object, &JsValue::from_str(key), &to_js(item));
+            }
+            object.into()
+        }
+    }
+}
+
+/// The digits of an integer a JS number cannot hold exactly, for a `BigInt`.
+fn unsafe_integer_digits(number: &crate::Number) -> Option<String> {
+    const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+    if let Some(value) = number.as_i64() {
+        return (value.unsigned_abs() > MAX_SAFE_INTEGER).then(|| value.to_string());
+    }
+    if let Some(value) = number.as_u64() {
+        return Some(value.to_string());
+    }
+    // Only integers beyond 64 bits are left, with `bignum`.
+    number.is_integer().then(|| match number {
+        crate::Number::Raw(text) => crate::Number::parse_literal(text).to_string(),
+        number => number.to_string(),
+    })
+}
+
 /// Decode a TOON string to a pretty-printed JSON format.
 ///
 /// # Arguments
 ///
 /// * `toon` - A TOON-formatted string to decode
+/// * `options` - Optional JavaScript object with the `decode_with_options`
+///   fields, plus:
+///   - `jsonIndent`: Spaces per level in the emitted JSON, 0 for compact (default: 2)
+///   - `ascii`: Escape non-ASCII characters as `\uXXXX` (default: false)
+///
+///   Use `keyOrder: 'sorted'` for sorted keys.
 ///
 /// # Returns
 ///
-/// A pretty-printed JSON string with 2-space indentation.
+/// A JSON string, indented with 2 spaces unless `jsonIndent` says otherwise.
 #[wasm_bindgen]
-pub fn decode_pretty(toon: &str) -> Result<String, JsError> {
-    let value = crate::decode::try_decode(toon, None)
+pub fn decode_pretty(toon: &str, options: JsValue) -> Result<String, JsError> {
+    let format = parse_json_format(&options)?;
+    let decode_options = parse_decode_options(options)?;
+    let value = crate::decode::try_decode(toon, decode_options)
         .map_err(|e| JsError::new(&format!("Decode error: {e}")))?;
-    let serde_value: serde_json::Value = value.into();
-    serde_json::to_string_pretty(&serde_value)
-        .map_err(|e| JsError::new(&format!("JSON stringify error: {e}")))
+    Ok(crate::cli::json_stringify::json_stringify_formatted(&value, &format).concat())
+}
+
+/// Encode items arriving from an async iterable or `ReadableStream` as one
+/// TOON root array, without collecting them first.
+///
+/// # Arguments
+///
+/// * `source` - Async iterable or `ReadableStream` of JS values (one item
+///   each), NDJSON text, or NDJSON bytes (`Uint8Array`)
+/// * `length` - Number of items; TOON headers declare it up front
+/// * `options` - `encode_with_options` options, plus `fields`: column names
+///   that make the output a table instead of a list
+///
+/// # Returns
+///
+/// An async iterable of TOON text chunks whose concatenation is the encoded
+/// document. Iteration throws if an item does not fit or the item count
+/// differs from `length`.
+///
+/// # Example
+///
+/// ```javascript
+/// const response = await fetch('/rows.ndjson');
+/// for await (const chunk of encode_stream(response.body, 1000, { fields: ['id', 'name'] })) {
+///   output.write(chunk);
+/// }
+/// ```
+#[wasm_bindgen]
+pub fn encode_stream(source: JsValue, length: usize, options: JsValue) -> Result<JsValue, JsError> {
+    Ok(pump(ToonStreamEncoder::new(length, options)?, source))
+}
+
+#[wasm_bindgen(inline_js = r"
+export async function* pump(encoder, source) {
+  if (typeof source[Symbol.asyncIterator] !== 'function' && typeof source.getReader === 'function') {
+    const reader = source.getReader();
+    source = {
+      async *[Symbol.asyncIterator]() {
+        for (;;) {
+          const { done, value } = await reader.read();
+          if (done) return;
+          yield value;
+        }
+      },
+    };
+  }
+  const bytes = new TextDecoder();
+  try {
+    for await (const chunk of source) {
+      const text = encoder.push(chunk instanceof Uint8Array ? bytes.decode(chunk, { stream: true }) : chunk);
+      if (text) yield text;
+    }
+    const tail = encoder.push(bytes.decode()) + encoder.finish();
+    if (tail) yield tail;
+  } finally {
+    encoder.free();
+  }
+}
+
+// TOON integers are exact to 64 bits. BigInts in that range are written as
+// raw JSON digits where the engine has JSON.rawJSON, and otherwise only when
+// they are safe integers.
+export function itemJson(item) {
+  return JSON.stringify(item, (_key, value) => {
+    if (typeof value !== 'bigint') return value;
+    if (value > 18446744073709551615n || value < -9223372036854775808n) {
+      throw new RangeError(`BigInt ${value} does not fit in 64 bits`);
+    }
+    if (typeof JSON.rawJSON === 'function') return JSON.rawJSON(value.toString());
+    if (value > BigInt(Number.MAX_SAFE_INTEGER) || value < BigInt(Number.MIN_SAFE_INTEGER)) {
+      throw new RangeError(`BigInt ${value} is outside the safe integer range`);
+    }
+    return Number(value);
+  });
+}
+")]
+extern "C" {
+    fn pump(encoder: ToonStreamEncoder, source: JsValue) -> JsValue;
+    #[wasm_bindgen(catch, js_name = itemJson)]
+    fn item_json(item: &JsValue) -> Result<JsValue, JsValue>;
+}
+
+/// Incremental encoder behind [`encode_stream`], for callers that drive the
+/// input loop themselves.
+///
+/// Items are written as list entries (`- ...`), or as table rows when
+/// `fields` is given. The `[N]` header comes with the first item.
+#[wasm_bindgen]
+pub struct ToonStreamEncoder {
+    options: crate::options::ResolvedEncodeOptions,
+    fields: Option<Vec<String>>,
+    length: usize,
+    written: usize,
+    pending: String,
+}
+
+#[wasm_bindgen]
+impl ToonStreamEncoder {
+    /// Create an encoder for `length` items; `options` are those of
+    /// [`encode_stream`].
+    #[wasm_bindgen(constructor)]
+    pub fn new(length: usize, options: JsValue) -> Result<Self, JsError> {
+        let fields = parse_fields(&options);
+        let options = crate::options::resolve_encode_options(parse_encode_options(options)?);
+        Ok(Self::with_options(length, options, fields))
+    }
+
+    /// Encode one chunk. A string is NDJSON text that may end mid-line; each
+    /// completed line is an item. Any other value is one item; `BigInt`s in
+    /// it must fit in 64 bits (or be safe integers, without `JSON.rawJSON`).
+    ///
+    /// Returns the TOON text for the items completed by this chunk, or an
+    /// empty string.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn push(&mut self, chunk: JsValue) -> Result<String, JsError> {
+        let result = if let Some(text) = chunk.as_string() {
+            self.push_text(&text)
+        } else {
+            self.push_json(&value_json(&chunk)?)
+        };
+        result.map_err(|e| JsError::new(&format!("Encode error: {e}")))
+    }
+
+    /// Encode a final NDJSON line without a trailing newline and check that
+    /// `length` items were received.
+    pub fn finish(&mut self) -> Result<String, JsError> {
+        self.finish_items()
+            .map_err(|e| JsError::new(&format!("Encode error: {e}")))
+    }
+}
+
+impl ToonStreamEncoder {
+    const fn with_options(
+        length: usize,
+        options: crate::options::ResolvedEncodeOptions,
+        fields: Option<Vec<String>>,
+    ) -> Self {
+        Self {
+            options,
+            fields,
+            length,
+            written: 0,
+            pending: String::new(),
+        }
+    }
+
+    fn push_text(&mut self, text: &str) -> crate::error::Result<String> {
+        self.pending.push_str(text);
+        let Some(end) = self.pending.rfind('\n') else {
+            return Ok(String::new());
+        };
+        let complete: String = self.pending.drain(..=end).collect();
+        let mut out = String::new();
+        for line in complete.lines().filter(|line| !line.trim().is_empty()) {
+            out.push_str(&self.push_json(line)?);
+        }
+        Ok(out)
+    }
+
+    fn push_json(&mut self, json: &str) -> crate::error::Result<String> {
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(|err| crate::error::ToonError::json_parse(&err))?;
+        self.push_value(crate::JsonValue::from(value))
+    }
+
+    fn push_value(&mut self, value: crate::JsonValue) -> crate::error::Result<String> {
+        use crate::encode::encoders::{encode_root_list_item, encode_root_table_row};
+        use crate::error::ToonError;
+
+        if self.written == self.length {
+            return Err(ToonError::message(format!(
+                "received more than the declared {} items",
+                self.length
+            )));
+        }
+        let value = crate::encode::prepare_value(value, &self.options)?;
+        let lines = match &self.fields {
+            Some(fields) => vec![
+                encode_root_table_row(&value, fields, &self.options).ok_or_else(|| {
+                    ToonError::invalid_value(
+                        format!("[{}]", self.written),
+                        "item does not have exactly the table fields as primitives",
+                    )
+                })?,
+            ],
+            None => encode_root_list_item(&value, &self.options),
+        };
+        let mut out = if self.written == 0 {
+            self.header()
+        } else {
+            String::new()
+        };
+        for line in lines {
+            out.push_str(self.options.line_ending.as_str());
+            out.push_str(&line);
+        }
+        self.written += 1;
+        Ok(out)
+    }
+
+    fn finish_items(&mut self) -> crate::error::Result<String> {
+        let tail = std::mem::take(&mut self.pending);
+        let out = if tail.trim().is_empty() {
+            String::new()
+        } else {
+            self.push_json(&tail)?
+        };
+        if self.written < self.length {
+            return Err(crate::error::ToonError::message(format!(
+                "expected {} items, received {}",
+                self.length, self.written
+            )));
+        }
+        if self.length == 0 {
+            return Ok(crate::encode::primitives::format_header(
+                self.options.length_marker(0, false),
+                None,
+                None,
+                self.options.delimiter,
+                self.options.quote_strings,
+            ));
+        }
+        Ok(out)
+    }
+
+    fn header(&self) -> String {
+        crate::encode::primitives::format_header(
+            self.options
+                .length_marker(self.length, self.fields.is_some()),
+            None,
+            self.fields.as_deref(),
+            self.options.delimiter,
+            self.options.quote_strings,
+        )
+    }
+}
+
+/// JSON text for a JS value, with `BigInt`s written as digits.
+fn value_json(value: &JsValue) -> Result<String, JsError> {
+    item_json(value)
+        .map_err(|err| {
+            JsError::new(&err.as_string().unwrap_or_else(|| {
+                js_sys::Error::from(err)
+                    .message()
+                    .as_string()
+                    .unwrap_or_default()
+            }))
+        })?
+        .as_string()
+        .ok_or_else(|| JsError::new("Value is not JSON-serializable"))
 }
 
 /// Get the library version.
@@ -158,15 +547,34 @@ pub fn version() -> String {
 
 // Helper functions for parsing JavaScript options
 
-#[allow(
-    clippy::cast_possible_truncation,
-    clippy::cast_sign_loss,
-    clippy::needless_pass_by_value
-)]
+/// A non-negative number option, truncated to `usize`.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn usize_option(obj: &js_sys::Object, name: &str) -> Option<usize> {
+    js_sys::Reflect::get(obj, &name.into())
+        .ok()
+        .and_then(|v| v.as_f64())
+        .map(|v| v as usize)
+}
+
+/// A string option mapped through `parse`.
+fn string_option<T>(
+    obj: &js_sys::Object,
+    name: &str,
+    parse: impl FnOnce(&str) -> Option<T>,
+) -> Option<T> {
+    js_sys::Reflect::get(obj, &name.into())
+        .ok()
+        .and_then(|v| v.as_string())
+        .and_then(|s| parse(&s))
+}
+
+#[allow(clippy::needless_pass_by_value)]
 fn parse_encode_options(
     options: JsValue,
 ) -> Result<Option<crate::options::EncodeOptions>, JsError> {
-    use crate::options::{EncodeOptions, KeyFoldingMode};
+    use crate::options::{
+        EncodeOptions, IndentStyle, KeyFoldingMode, LengthMarkers, LineEnding, QuotePolicy,
+    };
 
     if options.is_undefined() || options.is_null() {
         return Ok(None);
@@ -175,29 +583,80 @@ fn parse_encode_options(
     let obj = js_sys::Object::try_from(&options)
         .ok_or_else(|| JsError::new("Options must be an object"))?;
 
-    let indent = js_sys::Reflect::get(obj, &"indent".into())
-        .ok()
-        .and_then(|v| v.as_f64())
-        .map(|v| v as usize);
+    let indent = usize_option(obj, "indent");
 
     let delimiter = js_sys::Reflect::get(obj, &"delimiter".into())
         .ok()
         .and_then(|v| v.as_string())
         .and_then(|s| s.chars().next());
 
-    let key_folding = js_sys::Reflect::get(obj, &"keyFolding".into())
+    let key_folding = string_option(obj, "keyFolding", |s| match s {
+        "off" => Some(KeyFoldingMode::Off),
+        "safe" => Some(KeyFoldingMode::Safe),
+        "aggressive" => Some(KeyFoldingMode::Aggressive),
+        _ => None,
+    });
+
+    let flatten_depth = usize_option(obj, "flattenDepth");
+
+    let length_markers = string_option(obj, "lengthMarkers", |s| match s {
+        "tabularOnly" => Some(LengthMarkers::TabularOnly),
+        "allArrays" => Some(LengthMarkers::AllArrays),
+        "never" => Some(LengthMarkers::Never),
+        _ => None,
+    });
+
+    let sort_keys = js_sys::Reflect::get(obj, &"sortKeys".into())
         .ok()
-        .and_then(|v| v.as_string())
-        .and_then(|s| match s.as_str() {
-            "off" => Some(KeyFoldingMode::Off),
-            "safe" => Some(KeyFoldingMode::Safe),
-            _ => None,
-        });
+        .and_then(|v| v.as_bool());
+
+    let quote_strings = string_option(obj, "quoteStrings", |s| match s {
+        "minimal" => Some(QuotePolicy::Minimal),
+        "alwaysStrings" => Some(QuotePolicy::AlwaysStrings),
+        "alwaysKeysAndStrings" => Some(QuotePolicy::AlwaysKeysAndStrings),
+        _ => None,
+    });
+
+    let tabular_min_rows = usize_option(obj, "tabularMinRows");
 
-    let flatten_depth = js_sys::Reflect::get(obj, &"flattenDepth".into())
+    let tabular_max_columns = usize_option(obj, "tabularMaxColumns");
+
+    let tabular = js_sys::Reflect::get(obj, &"tabular".into())
         .ok()
-        .and_then(|v| v.as_f64())
-        .map(|v| v as usize);
+        .and_then(|v| v.as_bool());
+
+    let max_line_width = usize_option(obj, "maxLineWidth");
+
+    let indent_style = string_option(obj, "indentStyle", |s| match s {
+        "spaces" => Some(IndentStyle::Spaces),
+        "tabs" => Some(IndentStyle::Tabs),
+        _ => None,
+    });
+
+    let line_ending = string_option(obj, "lineEnding", |s| match s {
+        "lf" => Some(LineEnding::Lf),
+        "crlf" => Some(LineEnding::CrLf),
+        _ => None,
+    });
+
+    let fold_single_item_arrays = js_sys::Reflect::get(obj, &"foldSingleItemArrays".into())
+        .ok()
+        .and_then(|v| v.as_bool());
+
+    let delimiter_overrides = js_sys::Reflect::get(obj, &"delimiterOverrides".into())
+        .ok()
+        .and_then(|v| js_sys::Object::try_from(&v).cloned())
+        .map(|overrides| {
+            js_sys::Object::entries(&overrides)
+                .iter()
+                .filter_map(|entry| {
+                    let entry = js_sys::Array::from(&entry);
+                    let path = entry.get(0).as_string()?;
+                    let delimiter = entry.get(1).as_string()?.chars().next()?;
+                    Some((path, delimiter))
+                })
+                .collect()
+        });
 
     Ok(Some(EncodeOptions {
         indent,
@@ -205,6 +664,21 @@ fn parse_encode_options(
         key_folding,
         flatten_depth,
         replacer: None,
+        delimiter_overrides,
+        max_depth: None,
+        on_non_finite: None,
+        length_markers,
+        metrics: None,
+        sort_keys,
+        key_comparator: None,
+        quote_strings,
+        tabular_min_rows,
+        tabular_max_columns,
+        tabular,
+        max_line_width,
+        indent_style,
+        line_ending,
+        fold_single_item_arrays,
     }))
 }
 
@@ -216,7 +690,10 @@ fn parse_encode_options(
 fn parse_decode_options(
     options: JsValue,
 ) -> Result<Option<crate::options::DecodeOptions>, JsError> {
-    use crate::options::{DecodeOptions, ExpandPathsMode};
+    use crate::options::{
+        DecodeOptions, DuplicateKeyPolicy, ExpandConflictPolicy, ExpandPathsMode, KeyOrder,
+        Strictness,
+    };
 
     if options.is_undefined() || options.is_null() {
         return Ok(None);
@@ -240,16 +717,127 @@ fn parse_decode_options(
         .and_then(|s| match s.as_str() {
             "off" => Some(ExpandPathsMode::Off),
             "safe" => Some(ExpandPathsMode::Safe),
+            "indexed" => Some(ExpandPathsMode::Indexed),
+            "aggressive" => Some(ExpandPathsMode::Aggressive),
+            _ => None,
+        });
+
+    let on_conflict = js_sys::Reflect::get(obj, &"onConflict".into())
+        .ok()
+        .and_then(|v| v.as_string())
+        .and_then(|s| match s.as_str() {
+            "error" => Some(ExpandConflictPolicy::Error),
+            "keepFirst" => Some(ExpandConflictPolicy::KeepFirst),
+            "overwrite" => Some(ExpandConflictPolicy::Overwrite),
+            "merge" => Some(ExpandConflictPolicy::Merge),
+            "preferFolded" => Some(ExpandConflictPolicy::PreferFolded),
+            "preferLiteral" => Some(ExpandConflictPolicy::PreferLiteral),
+            _ => None,
+        });
+
+    let key_order = js_sys::Reflect::get(obj, &"keyOrder".into())
+        .ok()
+        .and_then(|v| v.as_string())
+        .and_then(|s| match s.as_str() {
+            "preserve" => Some(KeyOrder::Preserve),
+            "sorted" => Some(KeyOrder::Sorted),
+            _ => None,
+        });
+
+    let exact_indent = js_sys::Reflect::get(obj, &"exactIndent".into())
+        .ok()
+        .and_then(|v| v.as_bool());
+
+    let duplicate_keys = js_sys::Reflect::get(obj, &"duplicateKeys".into())
+        .ok()
+        .and_then(|v| v.as_string())
+        .and_then(|s| match s.as_str() {
+            "error" => Some(DuplicateKeyPolicy::Error),
+            "firstWins" => Some(DuplicateKeyPolicy::FirstWins),
+            "lastWins" => Some(DuplicateKeyPolicy::LastWins),
+            "collectIntoArray" => Some(DuplicateKeyPolicy::CollectIntoArray),
             _ => None,
         });
 
+    let require_length_markers = js_sys::Reflect::get(obj, &"requireLengthMarkers".into())
+        .ok()
+        .and_then(|v| v.as_bool());
+
+    let max_line_length = js_sys::Reflect::get(obj, &"maxLineLength".into())
+        .ok()
+        .and_then(|v| v.as_f64())
+        .map(|v| v as usize);
+
+    let preserve_number_literals = js_sys::Reflect::get(obj, &"preserveNumberLiterals".into())
+        .ok()
+        .and_then(|v| v.as_bool());
+
+    let lenient = strict == Some(false);
+    let preset = if lenient {
+        Strictness::lenient()
+    } else {
+        Strictness::default()
+    };
+    let strictness = Strictness {
+        indentation: exact_indent.unwrap_or(preset.indentation),
+        ..preset
+    };
+
     Ok(Some(DecodeOptions {
         indent,
-        strict,
+        strictness: Some(strictness),
         expand_paths,
+        on_conflict: on_conflict.or_else(|| lenient.then_some(ExpandConflictPolicy::Merge)),
+        key_order,
+        duplicate_keys,
+        require_length_markers,
+        max_line_length,
+        limits: None,
+        preserve_number_literals,
+        metrics: None,
+        collect_warnings: None,
     }))
 }
 
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn parse_json_format(options: &JsValue) -> Result<crate::cli::json_stringify::JsonFormat, JsError> {
+    use crate::cli::json_stringify::JsonFormat;
+
+    if options.is_undefined() || options.is_null() {
+        return Ok(JsonFormat::default());
+    }
+
+    let obj = js_sys::Object::try_from(options)
+        .ok_or_else(|| JsError::new("Options must be an object"))?;
+
+    let indent = js_sys::Reflect::get(obj, &"jsonIndent".into())
+        .ok()
+        .and_then(|v| v.as_f64())
+        .map_or(2, |v| v as usize);
+
+    let ascii = js_sys::Reflect::get(obj, &"ascii".into())
+        .ok()
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    Ok(JsonFormat { indent, ascii })
+}
+
+fn parse_fields(options: &JsValue) -> Option<Vec<String>> {
+    if options.is_undefined() || options.is_null() {
+        return None;
+    }
+    js_sys::Reflect::get(options, &"fields".into())
+        .ok()
+        .filter(js_sys::Array::is_array)
+        .map(|fields| {
+            js_sys::Array::from(&fields)
+                .iter()
+                .filter_map(|field| field.as_string())
+                .collect()
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -266,10 +854,86 @@ mod tests {
         assert_eq!(result, r#"{"name":"Alice"}"#);
     }
 
+    #[test]
+    fn test_stream_encoder_matches_encode() {
+        let rows = r#"[{"id":1,"tags":["a"]},{"id":2,"tags":[]},3]"#;
+        let options = crate::options::resolve_encode_options(None);
+        let mut encoder = ToonStreamEncoder::with_options(3, options, None);
+
+        let mut out = encoder
+            .push_text("{\"id\":1,\"tags\":[\"a\"]}\n{\"id\":2,")
+            .unwrap();
+        assert!(out.starts_with("[3]:\n  - id: 1"));
+        out.push_str(&encoder.push_text("\"tags\":[]}\n\n3").unwrap());
+        out.push_str(&encoder.finish_items().unwrap());
+        assert_eq!(out, encode(rows).unwrap());
+    }
+
+    #[test]
+    fn test_stream_encoder_writes_table_rows() {
+        let options = crate::options::resolve_encode_options(None);
+        let fields = Some(vec!["id".to_string(), "name".to_string()]);
+        let mut encoder = ToonStreamEncoder::with_options(2, options, fields);
+
+        let mut out = encoder.push_json(r#"{"name":"Ada","id":1}"#).unwrap();
+        out.push_str(&encoder.push_json(r#"{"id":2,"name":"Bob"}"#).unwrap());
+        out.push_str(&encoder.finish_items().unwrap());
+        assert_eq!(out, "[2]{id,name}:\n  1,Ada\n  2,Bob");
+
+        assert!(encoder.push_json(r#"{"id":3,"name":"Cy"}"#).is_err());
+    }
+
+    #[test]
+    fn test_stream_encoder_checks_row_shape_and_count() {
+        let options = crate::options::resolve_encode_options(None);
+        let mut encoder =
+            ToonStreamEncoder::with_options(2, options.clone(), Some(vec!["id".to_string()]));
+        let err = encoder.push_json(r#"{"id":1,"extra":2}"#).unwrap_err();
+        assert!(err.to_string().contains("[0]"), "{err}");
+
+        let mut encoder = ToonStreamEncoder::with_options(2, options.clone(), None);
+        encoder.push_json("1").unwrap();
+        let err = encoder.finish_items().unwrap_err();
+        assert!(
+            err.to_string().contains("expected 2 items, received 1"),
+            "{err}"
+        );
+
+        let mut empty = ToonStreamEncoder::with_options(0, options, None);
+        assert_eq!(empty.finish_items().unwrap(), "[0]:");
+    }
+
+    #[test]
+    fn test_unsafe_integers_become_bigint_digits() {
+        use crate::Number;
+
+        assert_eq!(
+            unsafe_integer_digits(&Number::I64(9_007_199_254_740_991)),
+            None
+        );
+        assert_eq!(
+            unsafe_integer_digits(&Number::I64(-9_007_199_254_740_991)),
+            None
+        );
+        assert_eq!(
+            unsafe_integer_digits(&Number::I64(-9_007_199_254_740_993)).as_deref(),
+            Some("-9007199254740993")
+        );
+        assert_eq!(
+            unsafe_integer_digits(&Number::U64(u64::MAX)).as_deref(),
+            Some("18446744073709551615")
+        );
+        assert_eq!(
+            unsafe_integer_digits(&Number::Raw("9007199254740993".to_string())).as_deref(),
+            Some("9007199254740993")
+        );
+        assert_eq!(unsafe_integer_digits(&Number::F64(1e300)), None);
+    }
+
     #[test]
     fn test_roundtrip() {
-        // Use floats in the original JSON since TOON uses f64 internally
-        let json = r#"{"users":[{"id":1.0,"name":"Alice"},{"id":2.0,"name":"Bob"}]}"#;
+        let json =
+            r#"{"users":[{"id":1,"name":"Alice"},{"id":18446744073709551615,"name":"Bob"}]}"#;
         let toon = encode(json).unwrap();
         let decoded = decode(&toon).unwrap();
         // Parse both to compare values
@@ -0,0 +1,94 @@
+//! Arbitrary-precision number tests (requires the `bignum` feature).
+
+#![cfg(feature = "bignum")]
+
+use serde::Deserialize;
+use toon::{BigDecimal, BigInt, JsonValue, Number, StringOrNumberOrBoolOrNull, decode, encode};
+
+fn number(value: &JsonValue) -> &Number {
+    match value {
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(number)) => number,
+        other => panic!("expected number, got {other:?}"),
+    }
+}
+
+#[test]
+fn big_literals_decode_exactly_and_encode_unchanged() {
+    let input = "big: 123456789012345678901234567890\nneg: -98765432109876543210\nprice: 0.1000000000000000000000001\nsmall: 0.5";
+    let value = decode(input, None);
+    let JsonValue::Object(entries) = &value else {
+        panic!("expected object");
+    };
+    assert!(matches!(number(&entries[0].1), Number::BigInt(_)));
+    assert!(matches!(number(&entries[1].1), Number::BigInt(_)));
+    assert!(matches!(number(&entries[2].1), Number::BigDecimal(_)));
+    assert!(matches!(number(&entries[3].1), Number::F64(_)));
+    assert_eq!(encode(value, None), input);
+}
+
+#[test]
+fn out_of_range_exponents_stay_exact() {
+    let value = decode("huge: 1e400\ntiny: 1.5e-400", None);
+    let JsonValue::Object(entries) = &value else {
+        panic!("expected object");
+    };
+    assert!(matches!(number(&entries[0].1), Number::BigDecimal(_)));
+    assert_eq!(
+        decode(&encode(value.clone(), None), None),
+        value,
+        "plain-notation output must decode to the same values"
+    );
+    assert_eq!(encode(decode("x: 1e99999", None), None), "x: null");
+}
+
+#[test]
+fn json_round_trips_big_numbers() {
+    let json =
+        r#"{"id":340282366920938463463374607431768211456,"amount":12345678901234567890.123456789}"#;
+    let toon = toon::json_to_toon(json).unwrap();
+    assert_eq!(
+        toon,
+        "id: 340282366920938463463374607431768211456\namount: 12345678901234567890.123456789"
+    );
+    assert_eq!(toon::toon_to_json(&toon).unwrap(), json);
+    let value = JsonValue::from_json(r#"{"k": "-1\"2", "n": [-18446744073709551617, 0.5]}"#);
+    assert_eq!(
+        value.unwrap(),
+        decode("k: \"-1\\\"2\"\nn[2]: -18446744073709551617,0.5", None)
+    );
+}
+
+#[test]
+fn big_numbers_compare_by_value() {
+    let max = BigInt::from(u64::MAX);
+    assert!(matches!(Number::from(max), Number::U64(u64::MAX)));
+    let half: BigDecimal = "0.50".parse().unwrap();
+    assert_eq!(Number::from(half), Number::F64(0.5));
+    let big = Number::parse_literal("123456789012345678901234567890");
+    assert_eq!(
+        big,
+        Number::parse_literal("123456789012345678901234567890.0")
+    );
+    assert_ne!(big, Number::F64(big.as_f64()));
+}
+
+#[test]
+fn from_str_reads_wide_integers() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Ledger {
+        total: u128,
+        delta: i128,
+    }
+
+    let ledger: Ledger = toon::from_str(
+        "total: 170141183460469231731687303715884105727\ndelta: -18446744073709551617",
+    )
+    .unwrap();
+    assert_eq!(
+        ledger,
+        Ledger {
+            total: i128::MAX as u128,
+            delta: -18_446_744_073_709_551_617,
+        }
+    );
+}
@@ -0,0 +1,76 @@
+//! Borrowed decoding tests: `decode_borrowed` against the owned decoder.
+
+use toon::try_decode;
+
+#[test]
+fn borrowed_decoding_matches_owned_decoding() {
+    let inputs = [
+        "",
+        "42",
+        "\"quoted \\\"root\\\"\"",
+        "name: Ada\nage: 36\nactive: true\nnothing: null",
+        "user:\n  name: Ada\n  address:\n    city: London\n  empty:",
+        "tags[3]: a,b,c",
+        "tags[0]:",
+        "pipes[2|]: a b|c",
+        "rows[2]{id,name}:\n  1,Ada\n  2,\"Grace \\\"G\\\" Hopper\"",
+        "items[4]:\n  - 1\n  - a: 1\n    b: 2\n  - [2]: x,y\n  -",
+        "items[2]:\n  - rows[1]{x,y}:\n      1,2\n    after: done\n  - key:\n      nested: true",
+        "[2]:\n  - \"a\\tb\"\n  - c",
+        "\"weird key\": 1\n\"esc\\nkey\": \"v\\\\\"",
+    ];
+    for input in inputs {
+        let borrowed = toon::decode_borrowed(input).unwrap().into_owned();
+        assert_eq!(
+            borrowed,
+            try_decode(input, None).unwrap(),
+            "input: {input:?}"
+        );
+    }
+}
+
+#[test]
+fn borrowed_decoding_copies_only_escaped_strings() {
+    use std::borrow::Cow;
+    use toon::{BorrowedPrimitive, BorrowedValue};
+
+    let value =
+        toon::decode_borrowed("\"plain\": \"x\"\n\"a\\tb\": y\nrows[1]{k}:\n  \"c\\nd\"").unwrap();
+    let BorrowedValue::Object(entries) = value else {
+        panic!("expected object");
+    };
+    assert!(matches!(entries[0].0, Cow::Borrowed("plain")));
+    assert!(matches!(
+        entries[0].1,
+        BorrowedValue::Primitive(BorrowedPrimitive::String(Cow::Borrowed("x")))
+    ));
+    assert!(matches!(&entries[1].0, Cow::Owned(key) if key == "a\tb"));
+    let BorrowedValue::Array(rows) = &entries[2].1 else {
+        panic!("expected array");
+    };
+    let BorrowedValue::Object(row) = &rows[0] else {
+        panic!("expected row");
+    };
+    assert!(matches!(row[0].0, Cow::Borrowed("k")));
+    assert!(matches!(
+        &row[0].1,
+        BorrowedValue::Primitive(BorrowedPrimitive::String(Cow::Owned(text))) if text == "c\nd"
+    ));
+}
+
+#[test]
+fn borrowed_decoding_reports_strict_errors() {
+    for input in [
+        "tags[3]: a,b",
+        "rows[2]{a,b}:\n  1,2",
+        "rows[1]{a,b}:\n  1",
+        "items[1]:\n  - a\n  - b",
+        "items[2]:\n  - a\n\n  - b",
+        "a:\n   b: 1",
+        "a: \"unterminated",
+    ] {
+        let borrowed = toon::decode_borrowed(input).unwrap_err().to_string();
+        let owned = try_decode(input, None).unwrap_err().to_string();
+        assert_eq!(borrowed, owned, "input: {input:?}");
+    }
+}
@@ -0,0 +1,81 @@
+//! Document splitting and concatenation tests.
+
+use toon::chunk::{ChunkLimit, concat_documents, estimate_tokens, split_document};
+use toon::{JsonValue, decode};
+
+fn sample() -> JsonValue {
+    decode(
+        "title: Inventory\nitems[6]{sku,qty}:\n  A1,1\n  B2,2\n  C3,3\n  D4,4\n  E5,5\n  F6,6\nowner: ops",
+        None,
+    )
+}
+
+#[test]
+fn every_chunk_fits_and_round_trips() {
+    let value = sample();
+    for limit in [30, 40, 60, 200] {
+        let chunks = split_document(&value, ChunkLimit::Bytes(limit), None).unwrap();
+        for chunk in &chunks {
+            assert!(chunk.len() <= limit, "{chunk:?} exceeds {limit}");
+        }
+        assert_eq!(concat_documents(&chunks, None).unwrap(), value);
+    }
+}
+
+#[test]
+fn keeps_document_whole_when_it_fits() {
+    let value = sample();
+    let chunks = split_document(&value, ChunkLimit::Tokens(1000), None).unwrap();
+    assert_eq!(chunks, [toon::encode(value, None)]);
+}
+
+#[test]
+fn splits_table_rows_repeating_the_key() {
+    let chunks = split_document(&sample(), ChunkLimit::Bytes(40), None).unwrap();
+    assert_eq!(
+        chunks,
+        [
+            "title: Inventory",
+            "items[3]{sku,qty}:\n  A1,1\n  B2,2\n  C3,3",
+            "items[3]{sku,qty}:\n  D4,4\n  E5,5\n  F6,6",
+            "owner: ops",
+        ]
+    );
+}
+
+#[test]
+fn splits_root_arrays_between_items() {
+    let value = decode("[5]: 1,2,3,4,5", None);
+    let chunks = split_document(&value, ChunkLimit::Bytes(10), None).unwrap();
+    assert!(chunks.len() > 1);
+    assert!(chunks.iter().all(|chunk| chunk.starts_with('[')));
+    assert_eq!(concat_documents(&chunks, None).unwrap(), value);
+}
+
+#[test]
+fn oversized_entries_get_their_own_chunk() {
+    let value = decode("note: a fairly long sentence\nn: 1", None);
+    let chunks = split_document(&value, ChunkLimit::Bytes(8), None).unwrap();
+    assert_eq!(chunks, ["note: a fairly long sentence", "n: 1"]);
+}
+
+#[test]
+fn concat_rejects_conflicting_chunks() {
+    let err = concat_documents(&["a: 1", "a: 2"], None).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "chunk 2: key `a` already appeared in an earlier chunk"
+    );
+    assert!(concat_documents(&["a: 1", "[1]: x"], None).is_err());
+    assert_eq!(
+        concat_documents::<&str>(&[], None).unwrap(),
+        JsonValue::Object(Vec::new())
+    );
+}
+
+#[test]
+fn estimates_tokens() {
+    assert_eq!(estimate_tokens(""), 1);
+    assert_eq!(estimate_tokens("a b c"), 3);
+    assert_eq!(estimate_tokens("abcdefghijklmnop"), 4);
+}
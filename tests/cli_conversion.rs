@@ -1,5 +1,10 @@
-use toon::cli::conversion::{decode_to_json_chunks, encode_to_toon_lines};
-use toon::options::{DecodeOptions, EncodeOptions, ExpandPathsMode, KeyFoldingMode};
+use toon::cli::conversion::{
+    decode_to_json_chunks, decode_to_json_chunks_with_format, encode_to_toon_lines,
+};
+use toon::cli::json_stringify::JsonFormat;
+use toon::options::{
+    DecodeOptions, EncodeOptions, ExpandPathsMode, KeyFoldingMode, KeyOrder, Strictness,
+};
 
 #[test]
 fn encode_to_toon_lines_matches_encode() {
@@ -22,11 +27,11 @@ fn encode_to_toon_lines_rejects_invalid_json() {
 #[test]
 fn decode_to_json_chunks_streams_without_expand_paths() {
     let input = "items[2]: a,b";
-    let options = DecodeOptions {
-        indent: Some(2),
-        strict: Some(true),
-        expand_paths: Some(ExpandPathsMode::Off),
-    };
+    let options = DecodeOptions::builder()
+        .indent(2)
+        .strictness(Strictness::default())
+        .expand_paths(ExpandPathsMode::Off)
+        .build();
 
     let output = decode_to_json_chunks(input, Some(options))
         .unwrap()
@@ -38,11 +43,11 @@ fn decode_to_json_chunks_streams_without_expand_paths() {
 #[test]
 fn decode_to_json_chunks_expands_paths_when_enabled() {
     let input = "a.b: 1";
-    let options = DecodeOptions {
-        indent: Some(0),
-        strict: Some(true),
-        expand_paths: Some(ExpandPathsMode::Safe),
-    };
+    let options = DecodeOptions::builder()
+        .indent(0)
+        .strictness(Strictness::default())
+        .expand_paths(ExpandPathsMode::Safe)
+        .build();
 
     let output = decode_to_json_chunks(input, Some(options))
         .unwrap()
@@ -52,16 +57,51 @@ fn decode_to_json_chunks_expands_paths_when_enabled() {
     assert_json_eq(&actual, &expected);
 }
 
+#[test]
+fn decode_to_json_chunks_with_format_sets_indent_independently() {
+    let input = "items[2]: a,b";
+    let options = DecodeOptions::builder().indent(2).build();
+    let format = JsonFormat {
+        indent: 4,
+        ..JsonFormat::default()
+    };
+
+    let output = decode_to_json_chunks_with_format(input, Some(options), &format)
+        .unwrap()
+        .concat();
+    assert_eq!(
+        output,
+        "{\n    \"items\": [\n        \"a\",\n        \"b\"\n    ]\n}"
+    );
+}
+
+#[test]
+fn decode_to_json_chunks_with_format_escapes_non_ascii() {
+    let format = JsonFormat {
+        indent: 0,
+        ascii: true,
+    };
+    let streamed = decode_to_json_chunks_with_format("b: Zoë\na: \"😀\"", None, &format)
+        .unwrap()
+        .concat();
+    assert_eq!(streamed, r#"{"b":"Zo\u00eb","a":"\ud83d\ude00"}"#);
+
+    let sorted = DecodeOptions::builder().key_order(KeyOrder::Sorted).build();
+    let output = decode_to_json_chunks_with_format("b: Zoë\na: \"😀\"", Some(sorted), &format)
+        .unwrap()
+        .concat();
+    assert_eq!(output, r#"{"a":"\ud83d\ude00","b":"Zo\u00eb"}"#);
+}
+
 #[test]
 fn encode_to_toon_lines_respects_options() {
     let input = r#"{"data":{"meta":{"items":["x","y"]}}}"#;
-    let options = EncodeOptions {
-        indent: Some(2),
-        delimiter: Some(','),
-        key_folding: Some(KeyFoldingMode::Safe),
-        flatten_depth: Some(usize::MAX),
-        replacer: None,
-    };
+    let options = EncodeOptions::builder()
+        .indent(2)
+        .delimiter(',')
+        .key_folding(KeyFoldingMode::Safe)
+        .flatten_depth(usize::MAX)
+        .build();
 
     let output = encode_to_toon_lines(input, Some(options))
         .unwrap()
@@ -86,6 +86,16 @@ fn encode_array_inline() {
         .stdout(predicate::str::contains("items[3]: a,b,c"));
 }
 
+#[test]
+fn encode_with_sorted_keys() {
+    toon()
+        .args(["--encode", "--key-order", "sorted"])
+        .write_stdin(r#"{"b":{"y":1,"x":2},"a":1}"#)
+        .assert()
+        .success()
+        .stdout("a: 1\nb:\n  x: 2\n  y: 1\n");
+}
+
 #[test]
 fn encode_with_custom_indent() {
     let json = r#"{"outer":{"inner":"value"}}"#;
@@ -115,6 +125,58 @@ fn encode_with_pipe_delimiter() {
         .stdout(predicate::str::contains("items[3|]: x|y|z"));
 }
 
+#[test]
+fn encode_with_delimiter_override() {
+    let json = r#"{"items":["x","y"],"notes":["a,b","c"]}"#;
+
+    toon()
+        .arg("--encode")
+        .arg("--delimiter-at")
+        .arg("notes=|")
+        .write_stdin(json)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("items[2]: x,y"))
+        .stdout(predicate::str::contains("notes[2|]: a,b|c"));
+}
+
+#[test]
+fn aggressive_key_folding_round_trips() {
+    toon()
+        .args(["--encode", "--key-folding", "aggressive"])
+        .write_stdin(r#"{"a.b":{"c":1}}"#)
+        .assert()
+        .success()
+        .stdout("\"a.b\".c: 1\n");
+    toon()
+        .args(["--decode", "--expand-paths", "aggressive"])
+        .write_stdin("\"a.b\".c: 1\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"a.b\": {"));
+}
+
+#[test]
+fn key_folding_through_single_item_arrays() {
+    toon()
+        .args([
+            "--encode",
+            "--key-folding",
+            "safe",
+            "--fold-single-item-arrays",
+        ])
+        .write_stdin(r#"{"a":[{"b":{"c":1}}]}"#)
+        .assert()
+        .success()
+        .stdout("a[0].b.c: 1\n");
+    toon()
+        .args(["--decode", "--expand-paths", "indexed"])
+        .write_stdin("a[0].b.c: 1\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"a\": [\n"));
+}
+
 #[test]
 fn encode_with_key_folding_safe() {
     let json = r#"{"data":{"meta":{"items":["x","y"]}}}"#;
@@ -129,6 +191,78 @@ fn encode_with_key_folding_safe() {
         .stdout(predicate::str::contains("data.meta.items[2]: x,y"));
 }
 
+#[test]
+fn encode_with_quote_strings_policy() {
+    toon()
+        .args(["--encode", "--quote-strings", "always-strings"])
+        .write_stdin(r#"{"name":"Ada","tags":["a","b"],"n":1}"#)
+        .assert()
+        .success()
+        .stdout("name: \"Ada\"\ntags[2]: \"a\",\"b\"\nn: 1\n");
+}
+
+#[test]
+fn encode_with_tabular_min_rows() {
+    toon()
+        .args(["--encode", "--tabular-min-rows", "3"])
+        .write_stdin(r#"{"rows":[{"id":1},{"id":2}]}"#)
+        .assert()
+        .success()
+        .stdout("rows[2]:\n  - id: 1\n  - id: 2\n");
+}
+
+#[test]
+fn encode_with_no_tabular() {
+    toon()
+        .args(["--encode", "--no-tabular"])
+        .write_stdin(r#"{"rows":[{"id":1},{"id":2}]}"#)
+        .assert()
+        .success()
+        .stdout("rows[2]:\n  - id: 1\n  - id: 2\n");
+}
+
+#[test]
+fn encode_with_max_line_width() {
+    toon()
+        .args(["--encode", "--max-line-width", "12"])
+        .write_stdin(r#"{"ids":[1,2,3,4,5,6,7]}"#)
+        .assert()
+        .success()
+        .stdout("ids[7]: 1,2\n  3,4,5,6,7\n");
+}
+
+#[test]
+fn tab_indented_output_decodes_with_no_strict() {
+    toon()
+        .args(["--encode", "--indent-style", "tabs"])
+        .write_stdin(r#"{"a":{"b":1}}"#)
+        .assert()
+        .success()
+        .stdout("a:\n\tb: 1\n");
+    toon()
+        .args(["--decode", "--no-strict"])
+        .write_stdin("a:\n\tb: 1\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"b\": 1"));
+}
+
+#[test]
+fn encode_with_crlf_line_endings() {
+    toon()
+        .args(["--encode", "--line-ending", "crlf"])
+        .write_stdin(r#"{"a":{"b":1}}"#)
+        .assert()
+        .success()
+        .stdout("a:\r\n  b: 1\r\n");
+    toon()
+        .args(["--decode"])
+        .write_stdin("a:\r\n  b: 1\r\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"b\": 1"));
+}
+
 #[test]
 fn encode_with_stats_flag() {
     let json = r#"{"name":"Alice","description":"This is a longer description text"}"#;
@@ -252,6 +386,133 @@ fn decode_with_expand_paths_safe() {
         .stdout(predicate::str::contains(r#""c": 42"#));
 }
 
+#[test]
+fn decode_with_on_conflict_keep_first() {
+    let toon_input = "a: 2\na.b: 1";
+
+    toon()
+        .arg("--decode")
+        .arg("--expand-paths")
+        .arg("safe")
+        .write_stdin(toon_input)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Path expansion conflict"));
+
+    toon()
+        .arg("--decode")
+        .arg("--expand-paths")
+        .arg("safe")
+        .arg("--on-conflict")
+        .arg("keep-first")
+        .write_stdin(toon_input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""a": 2"#));
+}
+
+#[test]
+fn decode_with_on_conflict_prefer_literal() {
+    toon()
+        .args([
+            "--decode",
+            "--expand-paths",
+            "safe",
+            "--on-conflict",
+            "prefer-literal",
+        ])
+        .write_stdin("a.b: 1\na: 2")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""a": 2"#));
+}
+
+#[test]
+fn decode_with_sorted_key_order() {
+    let output = toon()
+        .arg("--decode")
+        .arg("--key-order")
+        .arg("sorted")
+        .write_stdin("zeta: 1\nalpha: 2")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let alpha = stdout.find("alpha").unwrap();
+    let zeta = stdout.find("zeta").unwrap();
+    assert!(alpha < zeta, "keys not sorted: {stdout}");
+}
+
+#[test]
+fn decode_with_duplicate_keys_policy() {
+    toon()
+        .arg("--decode")
+        .arg("--duplicate-keys")
+        .arg("collect-into-array")
+        .write_stdin("a: 1\nb: 2\na: 3")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""a": ["#));
+
+    toon()
+        .arg("--decode")
+        .arg("--duplicate-keys")
+        .arg("error")
+        .write_stdin("a: 1\nb: 2\na: 3")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("first defined at line 1"));
+}
+
+#[test]
+fn decode_with_warn_ambiguous_reports_to_stderr() {
+    toon()
+        .arg("--decode")
+        .arg("--warn-ambiguous")
+        .write_stdin("zip: 007\nname: Ada")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""zip": "007""#))
+        .stderr(predicate::str::contains("warning: line 1:"));
+}
+
+#[test]
+fn decode_with_json_indent_and_ascii() {
+    toon()
+        .arg("--decode")
+        .arg("--json-indent")
+        .arg("0")
+        .arg("--ascii")
+        .write_stdin("city: Zürich")
+        .assert()
+        .success()
+        .stdout("{\"city\":\"Z\\u00fcrich\"}\n");
+}
+
+#[test]
+fn decode_with_query_outputs_matching_values() {
+    toon()
+        .arg("--decode")
+        .arg("--json-indent")
+        .arg("0")
+        .arg("--query")
+        .arg("$.users[?@.age > 20].name")
+        .write_stdin("users[3]{name,age}:\n  Ada,36\n  Bob,17\n  Cy,52")
+        .assert()
+        .success()
+        .stdout("[\"Ada\",\"Cy\"]\n");
+
+    toon()
+        .arg("--decode")
+        .arg("--query")
+        .arg("users[0]")
+        .write_stdin("users[1]: a")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid query"));
+}
+
 #[test]
 fn decode_with_no_strict_allows_invalid_indentation() {
     // Non-multiple of indent size (3 spaces with default indent 2)
@@ -575,6 +836,557 @@ fn stdin_dash_argument() {
         .stdout(predicate::str::contains("stdin: dash"));
 }
 
+// ============================================================================
+// Stats Subcommand
+// ============================================================================
+
+#[test]
+fn stats_reports_each_top_level_section() {
+    let tmp = TempDir::new().unwrap();
+    let input_path = tmp.path().join("data.toon");
+    fs::write(
+        &input_path,
+        "title: Inventory\nitems[3]{sku,qty}:\n  A1,1\n  B2,2\n  C3,3\nmeta:\n  owner: ops\n",
+    )
+    .unwrap();
+
+    toon()
+        .arg("stats")
+        .arg(&input_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "SECTION  LINES  BYTES  ROWS  TOKENS   SHARE\n",
+        ))
+        .stdout(predicate::str::contains("\ntitle        1     17     -"))
+        .stdout(predicate::str::contains("\nitems        4     40     3"))
+        .stdout(predicate::str::contains("\nmeta         2     19     -"))
+        .stdout(predicate::str::contains("\nTOTAL        7     76     -"));
+}
+
+#[test]
+fn stats_encodes_json_input_first() {
+    let tmp = TempDir::new().unwrap();
+    let input_path = tmp.path().join("data.json");
+    fs::write(&input_path, r#"{"rows":[{"a":1},{"a":2}],"n":1}"#).unwrap();
+
+    toon()
+        .arg("stats")
+        .arg(&input_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\nrows         3     20     2"));
+}
+
+#[test]
+fn stats_rejects_invalid_toon() {
+    toon()
+        .arg("stats")
+        .write_stdin("items[3]: a,b")
+        .assert()
+        .failure();
+}
+
+// ============================================================================
+// Explain Subcommand
+// ============================================================================
+
+#[test]
+fn explain_annotates_tables_folding_and_quoting() {
+    let tmp = TempDir::new().unwrap();
+    let input_path = tmp.path().join("data.json");
+    fs::write(
+        &input_path,
+        r#"{"config":{"db":{"host":"localhost"}},"code":"007","users":[{"id":1,"note":"a,b"}]}"#,
+    )
+    .unwrap();
+
+    toon()
+        .arg("explain")
+        .arg(&input_path)
+        .arg("--key-folding")
+        .arg("safe")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "   1 d0  config.db.host: localhost  # folded key chain `config.db.host`; key-value",
+        ))
+        .stdout(predicate::str::contains(
+            "# key-value; quoted \"007\": would read as a number, boolean or null",
+        ))
+        .stdout(predicate::str::contains("# table: 1 rows x 2 columns"))
+        .stdout(predicate::str::contains(
+            "   4 d1    1,\"a,b\"                  # row 1 of 1; quoted \"a,b\": contains the delimiter",
+        ));
+}
+
+#[test]
+fn explain_decodes_toon_input_first() {
+    let tmp = TempDir::new().unwrap();
+    let input_path = tmp.path().join("data.toon");
+    fs::write(&input_path, "items[2]:\n  - 1\n  - a: 1\n").unwrap();
+
+    toon()
+        .arg("explain")
+        .arg(&input_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("list array of 2 items"))
+        .stdout(predicate::str::contains("# list item; key-value"));
+}
+
+// ============================================================================
+// Fmt Subcommand
+// ============================================================================
+
+#[test]
+fn fmt_reindents_and_sorts_keeping_comments() {
+    toon()
+        .arg("fmt")
+        .arg("--sort-keys")
+        .arg("--indent")
+        .arg("4")
+        .write_stdin("# server\nserver:\n name:   api  \n tags[2|]: a|b\n\n\nid: 7\n")
+        .assert()
+        .success()
+        .stdout("id: 7\n# server\nserver:\n    name: api\n    tags[2]: a,b\n");
+}
+
+// ============================================================================
+// Grep Subcommand
+// ============================================================================
+
+#[test]
+fn grep_matches_key_and_value_across_directory() {
+    let tmp = TempDir::new().unwrap();
+    fs::create_dir(tmp.path().join("nested")).unwrap();
+    fs::write(
+        tmp.path().join("users.toon"),
+        "users[2]{id,email}:\n  1,ada@example.com\n  2,bob@test.org\nowner:\n  email: ops@example.com\n",
+    )
+    .unwrap();
+    fs::write(
+        tmp.path().join("nested").join("more.toon"),
+        "items[1]:\n  - email: eve@example.com\n",
+    )
+    .unwrap();
+    fs::write(tmp.path().join("notes.txt"), "email: x@example.com\n").unwrap();
+
+    let output = toon()
+        .arg("grep")
+        .arg("--key")
+        .arg("email")
+        .arg("--value")
+        .arg("@example.com")
+        .arg(tmp.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].ends_with("more.toon:2: items[0].email = eve@example.com"));
+    assert!(lines[1].ends_with("users.toon:2: users[0].email = ada@example.com"));
+    assert!(lines[2].ends_with("users.toon:5: owner.email = ops@example.com"));
+}
+
+#[test]
+fn grep_key_only_reports_containers_from_stdin() {
+    toon()
+        .arg("grep")
+        .arg("--key")
+        .arg("OWN")
+        .arg("-i")
+        .write_stdin("owner:\n  name: ops\nowners[2]: a,b\n")
+        .assert()
+        .success()
+        .stdout("1: owner = {...}\n3: owners = [...]\n");
+}
+
+#[test]
+fn grep_value_only_matches_array_items_and_numbers() {
+    toon()
+        .arg("grep")
+        .arg("--value")
+        .arg("4")
+        .write_stdin("tags[2]: a4,b\n\"port no\": 8443\n")
+        .assert()
+        .success()
+        .stdout("1: tags[0] = a4\n2: \"port no\" = 8443\n");
+}
+
+#[test]
+fn grep_reports_invalid_file() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("bad.toon");
+    fs::write(&path, "items[3]: a,b\n").unwrap();
+
+    toon()
+        .arg("grep")
+        .arg("--value")
+        .arg("a")
+        .arg(&path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("bad.toon: "));
+}
+
+#[test]
+fn grep_reader_reports_matches_before_the_input_ends() {
+    use std::io::Read;
+    use toon::cli::grep::{GrepQuery, grep_reader};
+
+    /// Hands out one byte per read and counts how many were taken.
+    struct Trickle<'a> {
+        bytes: &'a [u8],
+        taken: std::rc::Rc<std::cell::Cell<usize>>,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let Some((first, rest)) = self.bytes.split_first() else {
+                return Ok(0);
+            };
+            buf[0] = *first;
+            self.bytes = rest;
+            self.taken.set(self.taken.get() + 1);
+            Ok(1)
+        }
+    }
+
+    let input = "a: hit\nb: miss\nc: hit\nd: miss\n";
+    let taken = std::rc::Rc::new(std::cell::Cell::new(0));
+    let reader = Trickle {
+        bytes: input.as_bytes(),
+        taken: taken.clone(),
+    };
+    let query = GrepQuery {
+        value: Some("hit".to_string()),
+        ..GrepQuery::default()
+    };
+    let mut seen = Vec::new();
+    grep_reader(reader, &query, None, |found| {
+        seen.push((found.path, found.line, taken.get()));
+        Ok(())
+    })
+    .unwrap();
+
+    assert_eq!(seen.len(), 2);
+    assert_eq!((seen[0].0.as_str(), seen[0].1), ("a", 1));
+    assert_eq!((seen[1].0.as_str(), seen[1].1), ("c", 3));
+    assert!(
+        seen[0].2 < input.len(),
+        "first match waited for the whole input"
+    );
+}
+
+#[test]
+fn grep_requires_key_or_value() {
+    toon()
+        .arg("grep")
+        .write_stdin("a: 1\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--key <KEY>"));
+}
+
+// ============================================================================
+// Flatten / Unflatten Subcommands
+// ============================================================================
+
+#[test]
+fn flatten_lists_leaf_paths() {
+    toon()
+        .arg("flatten")
+        .write_stdin(r#"{"users":[{"id":1,"full name":"Ada"}],"tags":[]}"#)
+        .assert()
+        .success()
+        .stdout("users.0.id: 1\n\"users.0.\\\"full name\\\"\": Ada\ntags[0]:\n");
+}
+
+#[test]
+fn unflatten_restores_flattened_toon() {
+    let tmp = TempDir::new().unwrap();
+    let listing = tmp.path().join("flat.toon");
+    fs::write(&listing, "rows.0.id: 1\nrows.1.id: 2\nowner.name: ops\n").unwrap();
+
+    toon()
+        .arg("unflatten")
+        .arg(&listing)
+        .assert()
+        .success()
+        .stdout("rows[2]{id}:\n  1\n  2\nowner:\n  name: ops\n");
+}
+
+#[test]
+fn unflatten_reports_conflicting_paths() {
+    toon()
+        .arg("unflatten")
+        .write_stdin("a: 1\na.b: 2\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("a.b"))
+        .stderr(predicate::str::contains("conflicts with an earlier entry"));
+}
+
+// ============================================================================
+// Sample Subcommand
+// ============================================================================
+
+#[test]
+fn sample_is_reproducible_with_seed() {
+    let tmp = TempDir::new().unwrap();
+    let schema_path = tmp.path().join("schema.json");
+    fs::write(
+        &schema_path,
+        r#"{"type":"object","properties":{"id":{"type":"integer"},"email":{"type":"string"}}}"#,
+    )
+    .unwrap();
+
+    let run = || {
+        toon()
+            .arg("sample")
+            .arg("--schema")
+            .arg(&schema_path)
+            .arg("--rows")
+            .arg("3")
+            .arg("--seed")
+            .arg("7")
+            .output()
+            .unwrap()
+    };
+    let first = run();
+    assert!(first.status.success());
+    let stdout = String::from_utf8(first.stdout.clone()).unwrap();
+    assert!(stdout.starts_with("[3]{id,email}:\n  1,"), "{stdout}");
+    assert_eq!(stdout.lines().count(), 4);
+    assert_eq!(run().stdout, first.stdout);
+}
+
+#[test]
+fn sample_accepts_toon_schema() {
+    let tmp = TempDir::new().unwrap();
+    let schema_path = tmp.path().join("schema.toon");
+    fs::write(&schema_path, "sku: string\nqty: integer\n").unwrap();
+
+    toon()
+        .arg("sample")
+        .arg("--schema")
+        .arg(&schema_path)
+        .arg("--rows")
+        .arg("2")
+        .arg("--seed")
+        .arg("1")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("[2]{sku,qty}:\n"));
+}
+
+// ============================================================================
+// REPL Subcommand
+// ============================================================================
+
+#[test]
+fn repl_encodes_json_once_it_parses() {
+    toon()
+        .arg("repl")
+        .write_stdin("{\"users\":[{\"id\":1,\"name\":\"Ada\"},\n{\"id\":2,\"name\":\"Bob\"}]}\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "toon> ...> users[2]{id,name}:\n  1,Ada\n  2,Bob\n",
+        ))
+        .stdout(predicate::str::contains("tokens (JSON) vs ~"));
+}
+
+#[test]
+fn repl_decodes_toon_at_blank_line() {
+    toon()
+        .arg("repl")
+        .write_stdin("name: Ada\ntags[2]: a,b\n\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "\"tags\": [\n    \"a\",\n    \"b\"\n  ]",
+        ));
+}
+
+#[test]
+fn repl_remembers_session_options() {
+    toon()
+        .arg("repl")
+        .arg("--indent")
+        .arg("4")
+        .write_stdin(
+            ":set delimiter pipe\n{\"a\":{\"b\":[1,2]}}\n:show\n:quit\n{\"ignored\":true}\n",
+        )
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("delimiter = pipe"))
+        .stdout(predicate::str::contains("a:\n    b[2|]: 1|2"))
+        .stdout(predicate::str::contains("indent        = 4"))
+        .stdout(predicate::str::contains("ignored").not());
+}
+
+#[test]
+fn repl_reports_errors_and_continues() {
+    toon()
+        .arg("repl")
+        .write_stdin(":set indent x\n:bogus\nitems[3]: a,b\n\n[1]\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("error: invalid indent `x`"))
+        .stdout(predicate::str::contains("error: unknown command `:bogus`"))
+        .stdout(predicate::str::contains(
+            "error: Line 1, column 1: Expected 3 inline array items, but got 2",
+        ))
+        .stdout(predicate::str::contains("[1]: 1"));
+}
+
+// ============================================================================
+// Schema Validation
+// ============================================================================
+
+#[cfg(feature = "schema")]
+fn write_user_schema(tmp: &TempDir) -> std::path::PathBuf {
+    let schema_path = tmp.path().join("schema.json");
+    fs::write(
+        &schema_path,
+        r#"{"type":"object","required":["id","name"],"properties":{"id":{"type":"integer"},"name":{"type":"string"}}}"#,
+    )
+    .unwrap();
+    schema_path
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn encode_with_schema_accepts_conforming_data() {
+    let tmp = TempDir::new().unwrap();
+    let schema_path = write_user_schema(&tmp);
+
+    toon()
+        .arg("--encode")
+        .arg("--schema")
+        .arg(&schema_path)
+        .write_stdin(r#"{"id":1,"name":"Ada"}"#)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("name: Ada"));
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn encode_with_schema_reports_violations_by_path() {
+    let tmp = TempDir::new().unwrap();
+    let schema_path = write_user_schema(&tmp);
+
+    toon()
+        .arg("--encode")
+        .arg("--schema")
+        .arg(&schema_path)
+        .write_stdin(r#"{"id":"one"}"#)
+        .assert()
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains("(2 violations)"))
+        .stderr(predicate::str::contains("/id:"))
+        .stderr(predicate::str::contains("(root):"));
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn decode_with_schema_validates_decoded_data() {
+    let tmp = TempDir::new().unwrap();
+    let schema_path = write_user_schema(&tmp);
+
+    toon()
+        .arg("--decode")
+        .arg("--schema")
+        .arg(&schema_path)
+        .write_stdin("id: 1\nname: 7")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("/name:"));
+}
+
+#[cfg(not(feature = "schema"))]
+#[test]
+fn schema_flag_requires_schema_feature() {
+    toon()
+        .arg("--encode")
+        .arg("--schema")
+        .arg("schema.json")
+        .write_stdin("{}")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "requires toon to be built with the `schema` feature",
+        ));
+}
+
+// ============================================================================
+// CSV
+// ============================================================================
+
+#[test]
+fn csv_input_encodes_to_root_table_with_dialect() {
+    let tmp = TempDir::new().unwrap();
+    let input = tmp.path().join("users.csv");
+    fs::write(&input, "id;name;note\r\n1;Ada;\"a;b\"\r\n2;NULL;\"42\"\r\n").unwrap();
+
+    toon()
+        .arg(&input)
+        .arg("--csv-delimiter")
+        .arg(";")
+        .arg("--csv-null")
+        .arg("NULL")
+        .assert()
+        .success()
+        .stdout("[2]{id,name,note}:\n  1,Ada,a;b\n  2,null,\"42\"\n");
+}
+
+#[test]
+fn decode_to_csv_output_writes_single_table() {
+    let tmp = TempDir::new().unwrap();
+    let input = tmp.path().join("data.toon");
+    let output = tmp.path().join("users.csv");
+    fs::write(
+        &input,
+        "users[2]{id,name}:\n  1,\"Lovelace, Ada\"\n  2,null",
+    )
+    .unwrap();
+
+    toon()
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .arg("--csv-no-header")
+        .arg("--csv-quote")
+        .arg("'")
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(&output).unwrap(),
+        "1,'Lovelace, Ada'\n2,\n"
+    );
+}
+
+#[test]
+fn decode_to_csv_rejects_non_tabular_documents() {
+    let tmp = TempDir::new().unwrap();
+    let output = tmp.path().join("out.csv");
+
+    toon()
+        .arg("--decode")
+        .arg("-o")
+        .arg(&output)
+        .write_stdin("a: 1\nb: 2")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("CSV output needs a root array"));
+}
+
 // ============================================================================
 // Help and Version
 // ============================================================================
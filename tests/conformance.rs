@@ -12,7 +12,7 @@ use serde::Deserialize;
 use toon::cli::json_stringify::json_stringify_lines;
 use toon::decode::decode;
 use toon::encode::encode;
-use toon::options::{DecodeOptions, EncodeOptions, ExpandPathsMode, KeyFoldingMode};
+use toon::options::{DecodeOptions, EncodeOptions, ExpandPathsMode, KeyFoldingMode, Strictness};
 
 #[derive(Debug, Deserialize)]
 struct FixtureFile {
@@ -167,19 +167,27 @@ fn parse_encode_options(options: Option<&serde_json::Value>) -> Option<EncodeOpt
         .and_then(serde_json::Value::as_u64)
         .and_then(|value| usize::try_from(value).ok());
 
-    Some(EncodeOptions {
-        indent,
-        delimiter,
-        key_folding,
-        flatten_depth,
-        replacer: None,
-    })
+    let mut encode_options = EncodeOptions::default();
+    encode_options.indent = indent;
+    encode_options.delimiter = delimiter;
+    encode_options.key_folding = key_folding;
+    encode_options.flatten_depth = flatten_depth;
+    Some(encode_options)
 }
 
 fn parse_decode_options(options: Option<&serde_json::Value>) -> Option<DecodeOptions> {
     let options = options?;
 
-    let strict = options.get("strict").and_then(serde_json::Value::as_bool);
+    let strictness = options
+        .get("strict")
+        .and_then(serde_json::Value::as_bool)
+        .map(|strict| {
+            if strict {
+                Strictness::default()
+            } else {
+                Strictness::lenient()
+            }
+        });
 
     let expand_paths = options
         .get("expandPaths")
@@ -190,11 +198,10 @@ fn parse_decode_options(options: Option<&serde_json::Value>) -> Option<DecodeOpt
             _ => None,
         });
 
-    Some(DecodeOptions {
-        indent: None,
-        strict,
-        expand_paths,
-    })
+    let mut decode_options = DecodeOptions::default();
+    decode_options.strictness = strictness;
+    decode_options.expand_paths = expand_paths;
+    Some(decode_options)
 }
 
 fn fixture_root() -> PathBuf {
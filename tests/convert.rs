@@ -0,0 +1,68 @@
+use std::collections::{BTreeMap, HashMap};
+
+use toon::{JsonValue, Number, StringOrNumberOrBoolOrNull, toon_value};
+
+#[test]
+fn primitives_convert_to_matching_variants() {
+    assert_eq!(JsonValue::from(7_u8), toon_value!(7));
+    assert_eq!(JsonValue::from(-7_i16), toon_value!(-7));
+    assert_eq!(JsonValue::from(3_usize), toon_value!(3));
+    assert_eq!(
+        JsonValue::from(u64::MAX),
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(Number::U64(u64::MAX)))
+    );
+    assert_eq!(JsonValue::from(1.5_f32), toon_value!(1.5));
+    assert!(matches!(
+        JsonValue::from(f32::NAN),
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(Number::F64(value))) if value.is_nan()
+    ));
+    assert_eq!(JsonValue::from('x'), toon_value!("x"));
+    assert_eq!(JsonValue::from(()), toon_value!(null));
+    assert_eq!(JsonValue::from(None::<i32>), toon_value!(null));
+    assert_eq!(JsonValue::from(Some("a")), toon_value!("a"));
+}
+
+#[test]
+fn collections_convert_elementwise() {
+    assert_eq!(JsonValue::from(vec![1, 2]), toon_value!([1, 2]));
+    assert_eq!(JsonValue::from(&["a", "b"][..]), toon_value!(["a", "b"]));
+    assert_eq!(
+        JsonValue::from([Some(true), None]),
+        toon_value!([true, null])
+    );
+    assert_eq!(
+        JsonValue::from(vec![("k".to_string(), JsonValue::from(1))]),
+        toon_value!({"k": 1})
+    );
+
+    let map = BTreeMap::from([("b", vec![1]), ("a", vec![])]);
+    assert_eq!(JsonValue::from(map), toon_value!({"a": [], "b": [1]}));
+    let map = HashMap::from([("only".to_string(), 1.5)]);
+    assert_eq!(JsonValue::from(map), toon_value!({"only": 1.5}));
+}
+
+#[test]
+fn collect_builds_arrays_and_objects() {
+    let array: JsonValue = (1..=3).collect();
+    assert_eq!(array, toon_value!([1, 2, 3]));
+    let object: JsonValue = ["x", "y"].into_iter().zip([true, false]).collect();
+    assert_eq!(toon::encode(object, None), "x: true\ny: false");
+}
+
+#[test]
+fn parse_and_display_use_toon() {
+    let text = "users[2]{id,name}:\n  1,Ada\n  2,Bob\nactive: true";
+    let value: JsonValue = text.parse().unwrap();
+    assert_eq!(value, toon::decode(text, None));
+    assert_eq!(value.to_string(), text);
+    assert_eq!(format!("{}", toon_value!("a b")), "a b");
+    assert_eq!(format!("{}", toon_value!("")), "\"\"");
+
+    let err = "items[3]: 1,2".parse::<JsonValue>().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        toon::try_decode("items[3]: 1,2", None)
+            .unwrap_err()
+            .to_string()
+    );
+}
@@ -0,0 +1,120 @@
+use toon::cst::{Key, LineKind, parse};
+use toon::{JsonPrimitive, try_decode};
+
+const DOCUMENTS: &[&str] = &[
+    "",
+    "42",
+    "name: api\nport: 8080\n",
+    "# settings\nname:   api  \n\n# nested\nserver:\n  host: \"local host\"\n  port: 8080",
+    "a: 1\r\nb:\r\n  c: \"x\"\r\n",
+    "users[2|]{id|name}:\n  1|Alice\n  2|\"Bob, Jr\"\n",
+    "tags[3\t]: a\tb\tc\n",
+    "items[2]:\n  - id: 1\n    rows[1]{x,y}:\n      1,2\n    note: ok\n  - plain\n",
+    "\"quoted key\": 1\n\"a:b\"[2]: x,y\nempty[0]:\nlist[1]:\n  -\n",
+    "# unmarked\nrows[]{a}:\n  1\n",
+    "   \n\t\n# only comments\n",
+];
+
+#[test]
+fn printing_reproduces_input() {
+    for text in DOCUMENTS {
+        let doc = parse(text).unwrap();
+        assert_eq!(doc.to_string(), *text);
+    }
+}
+
+#[test]
+fn to_value_matches_decode() {
+    for text in DOCUMENTS.iter().filter(|text| !text.contains('#')) {
+        let doc = parse(text).unwrap();
+        assert_eq!(doc.to_value(None).unwrap(), try_decode(text, None).unwrap());
+    }
+    let doc = parse("# header\nserver:\n  # the host\n  host: a\n").unwrap();
+    assert_eq!(
+        doc.to_value(None).unwrap(),
+        try_decode("server:\n  host: a", None).unwrap()
+    );
+}
+
+#[test]
+fn to_value_reports_lines_of_the_document() {
+    let doc = parse("# one\n# two\na: 1\nb 2\n").unwrap();
+    let err = doc.to_value(None).unwrap_err();
+    assert_eq!(err.decode_error().unwrap().line, 4);
+}
+
+#[test]
+fn lines_nest_under_their_parent() {
+    let doc = parse("# c\nserver:\n  host: a\n\n  port: 1\nname: x\n").unwrap();
+    let keys: Vec<_> = doc.nodes().iter().map(|node| node.key()).collect();
+    assert_eq!(keys, [None, Some("server"), Some("name")]);
+
+    let server = doc.get(&["server"]).unwrap();
+    assert_eq!(server.children().len(), 3);
+    assert_eq!(server.children()[1].line().kind(), &LineKind::Blank);
+    assert_eq!(doc.get(&["server", "port"]).unwrap().line().number(), 5);
+}
+
+#[test]
+fn rows_and_entries_under_tabular_headers() {
+    let doc = parse("- rows[2]{a,b}:\n    1,2\n    \"x:y\",3\n  note: ok\n").unwrap();
+    let item = &doc.nodes()[0];
+    let kinds: Vec<_> = item
+        .children()
+        .iter()
+        .map(|node| matches!(node.line().kind(), LineKind::Row(_)))
+        .collect();
+    assert_eq!(kinds, [true, true, false]);
+    let LineKind::Row(row) = item.children()[1].line().kind() else {
+        panic!("expected a row");
+    };
+    assert_eq!(row.values[0].raw(), "\"x:y\"");
+    assert_eq!(row.values[0].value(), &JsonPrimitive::String("x:y".into()));
+}
+
+#[test]
+fn editing_keeps_other_lines_as_written() {
+    let text = "# keep\nname:   \"api\"   \nport: 80\r\nserver:\n    host: a\n    tls: true\n";
+    let mut doc = parse(text).unwrap();
+
+    let name = doc.get_mut(&["name"]).unwrap();
+    name.set_value(&JsonPrimitive::String("web".into()))
+        .unwrap();
+    let server = doc.get_mut(&["server"]).unwrap();
+    server.set_value(&JsonPrimitive::Null).unwrap();
+    let port = doc.get_mut(&["port"]).unwrap();
+    port.set_value(&JsonPrimitive::Number(81.into())).unwrap();
+
+    assert_eq!(
+        doc.to_string(),
+        "# keep\nname: \"web\"\nport: 81\r\nserver: null\n"
+    );
+}
+
+#[test]
+fn set_value_rejects_array_headers() {
+    let mut doc = parse("tags[2]: a,b\n").unwrap();
+    let tags = doc.get_mut(&["tags"]).unwrap();
+    assert!(tags.set_value(&JsonPrimitive::Bool(true)).is_err());
+    assert_eq!(doc.to_string(), "tags[2]: a,b\n");
+}
+
+#[test]
+fn rendering_edited_entries() {
+    let mut doc = parse("  tags[2|]: a|b\n").unwrap();
+    let line = doc.nodes_mut()[0].line_mut();
+    let mut entry = line.entry().unwrap().clone();
+    entry.key = Some(Key::new("my tags"));
+    line.set_kind(LineKind::Entry(entry));
+    assert_eq!(line.text(), "  \"my tags\"[2|]: a|b");
+
+    line.set_indent("\t");
+    assert_eq!(doc.to_string(), "\t\"my tags\"[2|]: a|b\n");
+}
+
+#[test]
+fn parse_errors_point_at_the_line() {
+    let err = parse("a: 1\nb: \"open\n").unwrap_err();
+    assert_eq!(err.decode_error().unwrap().line, 2);
+    assert_eq!(err.decode_error().unwrap().source_line, "b: \"open");
+}
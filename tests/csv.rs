@@ -0,0 +1,156 @@
+use serde_json::json;
+use toon::JsonValue;
+use toon::csv::{CsvDialect, read_table, write_table};
+
+fn rows(json: serde_json::Value) -> Vec<JsonValue> {
+    match JsonValue::from(json) {
+        JsonValue::Array(rows) => rows,
+        other => panic!("expected array, got {other:?}"),
+    }
+}
+
+#[test]
+fn read_types_unquoted_fields_and_keeps_quoted_strings() {
+    let parsed = read_table(
+        "id,name,active,code\n1,Ada,true,\"007\"\n2.5,,false,007\n",
+        &CsvDialect::default(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        parsed,
+        rows(json!([
+            {"id": 1, "name": "Ada", "active": true, "code": "007"},
+            {"id": 2.5, "name": null, "active": false, "code": "007"},
+        ]))
+    );
+}
+
+#[test]
+fn read_handles_quotes_crlf_and_embedded_newlines() {
+    let parsed = read_table(
+        "note,n\r\n\"say \"\"hi\"\"\",1\r\n\"two\nlines\",2\r\n",
+        &CsvDialect::default(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        parsed,
+        rows(json!([
+            {"note": "say \"hi\"", "n": 1},
+            {"note": "two\nlines", "n": 2},
+        ]))
+    );
+}
+
+#[test]
+fn read_without_header_names_columns_by_position() {
+    let dialect = CsvDialect {
+        delimiter: '\t',
+        has_header: false,
+        null: "NA".to_string(),
+        ..CsvDialect::default()
+    };
+    let parsed = read_table("a\tNA\n\nb\t\n", &dialect).unwrap();
+
+    assert_eq!(
+        parsed,
+        rows(json!([
+            {"col1": "a", "col2": null},
+            {"col1": "b", "col2": ""},
+        ]))
+    );
+}
+
+#[test]
+fn read_pads_short_records_and_rejects_long_ones() {
+    let dialect = CsvDialect::default();
+    assert_eq!(
+        read_table("a,b\n1\n", &dialect).unwrap(),
+        rows(json!([{"a": 1, "b": null}]))
+    );
+
+    let err = read_table("a,b\n1,2,3\n", &dialect).unwrap_err();
+    assert!(err.to_string().contains("3 fields, header has 2"), "{err}");
+}
+
+#[test]
+fn read_rejects_empty_and_repeated_header_names() {
+    let dialect = CsvDialect::default();
+    let err = read_table("\na,a\n1,2\n", &dialect).unwrap_err();
+    assert!(
+        matches!(err, toon::error::ToonError::Parse { line: 2, .. }),
+        "{err}"
+    );
+    assert!(err.to_string().contains("repeats column `a`"), "{err}");
+    let err = read_table("a,,b\n1,2,3\n", &dialect).unwrap_err();
+    assert!(err.to_string().contains("column 2 has no name"), "{err}");
+}
+
+#[test]
+fn read_keeps_integers_beyond_f64_exact() {
+    let parsed = read_table("id\n9007199254740993\n", &CsvDialect::default()).unwrap();
+    assert_eq!(
+        JsonValue::Array(parsed.clone()),
+        toon::decode("[1]{id}:\n  9007199254740993", None)
+    );
+    assert_eq!(
+        toon::encode(JsonValue::Array(parsed), None),
+        "[1]{id}:\n  9007199254740993"
+    );
+}
+
+#[test]
+fn read_rejects_unterminated_quotes_and_clashing_dialects() {
+    assert!(read_table("a\n\"open\n", &CsvDialect::default()).is_err());
+
+    let dialect = CsvDialect {
+        quote: ',',
+        ..CsvDialect::default()
+    };
+    assert!(read_table("a\n1\n", &dialect).is_err());
+}
+
+#[test]
+fn write_quotes_only_ambiguous_or_structural_strings() {
+    let table = rows(json!([
+        {"id": 1, "name": "Ada", "note": "a,b"},
+        {"id": 2, "name": "42", "note": " padded "},
+        {"id": 3, "name": null, "note": "true"},
+    ]));
+
+    assert_eq!(
+        write_table(&table, &CsvDialect::default()).unwrap(),
+        "id,name,note\n1,Ada,\"a,b\"\n2,\"42\",\" padded \"\n3,,\"true\"\n"
+    );
+}
+
+#[test]
+fn write_uses_dialect_and_round_trips() {
+    let dialect = CsvDialect {
+        delimiter: '|',
+        quote: '\'',
+        has_header: true,
+        null: "\\N".to_string(),
+    };
+    let table = rows(json!([
+        {"id": 1, "name": "O'Brien", "tag": ""},
+        {"id": 2, "tag": "\\N"},
+    ]));
+
+    let csv = write_table(&table, &dialect).unwrap();
+    assert_eq!(csv, "id|name|tag\n1|'O''Brien'|\n2|\\N|'\\N'\n");
+    assert_eq!(
+        read_table(&csv, &dialect).unwrap(),
+        rows(json!([
+            {"id": 1, "name": "O'Brien", "tag": ""},
+            {"id": 2, "name": null, "tag": "\\N"},
+        ]))
+    );
+}
+
+#[test]
+fn write_rejects_nested_values() {
+    let err = write_table(&rows(json!([{"a": [1]}])), &CsvDialect::default()).unwrap_err();
+    assert!(err.to_string().contains("[0].a"), "{err}");
+}
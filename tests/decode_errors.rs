@@ -0,0 +1,205 @@
+//! Decode error spans and error-recovery decoding.
+
+use toon::options::{DecodeLimits, DecodeOptions, Strictness};
+use toon::{decode, try_decode};
+
+fn reject_duplicates() -> DecodeOptions {
+    DecodeOptions::strict()
+}
+
+// ============================================================================
+// DECODE ERROR SPANS
+// ============================================================================
+
+fn decode_error(input: &str, options: Option<DecodeOptions>) -> toon::error::DecodeError {
+    let err = try_decode(input, options).unwrap_err();
+    err.decode_error()
+        .cloned()
+        .unwrap_or_else(|| panic!("not a decode error: {err}"))
+}
+
+#[test]
+fn decode_errors_point_at_the_offending_text() {
+    use toon::error::DecodeErrorKind;
+
+    let cases = [
+        (
+            "a: 1\nname: \"unterminated",
+            DecodeErrorKind::UnterminatedString,
+            2,
+            6..19,
+        ),
+        ("a: \"bad \\q\"", DecodeErrorKind::InvalidEscape, 1, 8..10),
+        ("a:\n  b: 1\n  c 2", DecodeErrorKind::MissingColon, 3, 2..5),
+        ("outer:\n   inner: 1", DecodeErrorKind::Indentation, 2, 0..3),
+        (
+            "x: 1\nitems[3]: a,b",
+            DecodeErrorKind::CountMismatch,
+            2,
+            0..13,
+        ),
+        (
+            "rows[2]{a}:\n  1\n  2\n  3",
+            DecodeErrorKind::CountMismatch,
+            4,
+            2..3,
+        ),
+        (
+            "list[2]:\n  - a\n\n  - b",
+            DecodeErrorKind::BlankLine,
+            3,
+            0..0,
+        ),
+    ];
+    for (input, kind, line, columns) in cases {
+        let err = decode_error(input, None);
+        assert_eq!(err.kind, kind, "{input:?}");
+        assert_eq!(err.line, line, "{input:?}");
+        assert_eq!(err.columns, columns, "{input:?}");
+        let source = input.split('\n').nth(line - 1).unwrap();
+        if kind != DecodeErrorKind::BlankLine {
+            assert_eq!(err.source_line, source, "{input:?}");
+        }
+    }
+
+    let options = DecodeOptions::builder()
+        .require_length_markers(true)
+        .build();
+    let err = decode_error("a: 1\nb[n]: 1", Some(options));
+    assert_eq!(err.kind, DecodeErrorKind::InvalidLength);
+    assert_eq!((err.line, err.columns), (2, 1..4));
+
+    let err = decode_error("a: 1\nb: 2\na: 3", Some(reject_duplicates()));
+    assert_eq!(err.kind, DecodeErrorKind::DuplicateKey);
+    assert_eq!((err.line, err.source_line.as_str()), (3, "a: 3"));
+}
+
+#[test]
+fn decode_errors_display_line_and_column() {
+    let err = try_decode("a:\n  b: 1\n  c 2", None).unwrap_err();
+    assert_eq!(err.to_string(), "Line 3, column 3: Missing colon after key");
+
+    let err = toon::decode_from_reader(&b"a: \xffb\n"[..], None).unwrap_err();
+    let details = err.decode_error().unwrap();
+    assert_eq!(details.source_line, "a: \u{fffd}b");
+    assert_eq!(details.columns, 3..6);
+
+    assert!(
+        toon::json_to_toon("{")
+            .unwrap_err()
+            .decode_error()
+            .is_none()
+    );
+}
+
+// ============================================================================
+// ERROR RECOVERY
+// ============================================================================
+
+#[test]
+fn decode_all_errors_reports_every_error_with_a_best_effort_value() {
+    use toon::error::DecodeErrorKind;
+
+    let input = "name: \"Ada\nage:\n\tbad: 1\nteam\n  x: 1\ntags[3]: a,b\nrows[1]{id,tag}:\n  1,x,extra\n  2,y\nlast: true";
+    let (value, errors) = toon::decode_all_errors(input, None);
+    let found: Vec<_> = errors
+        .iter()
+        .map(|err| {
+            let err = err.decode_error().unwrap();
+            (err.line, err.kind)
+        })
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (1, DecodeErrorKind::UnterminatedString),
+            (3, DecodeErrorKind::Indentation),
+            (4, DecodeErrorKind::MissingColon),
+            (6, DecodeErrorKind::CountMismatch),
+            (8, DecodeErrorKind::CountMismatch),
+            (9, DecodeErrorKind::CountMismatch),
+        ]
+    );
+    assert_eq!(
+        value,
+        decode(
+            "name: \"\\\"Ada\"\nage:\n  bad: 1\ntags[2]: a,b\nrows[1]{id,tag}:\n  1,x\nlast: true",
+            None
+        )
+    );
+}
+
+#[test]
+fn decode_all_errors_agrees_with_try_decode() {
+    let inputs = [
+        "",
+        "a: 1\nb:\n  c[2]: x,y\nd[1]{k}:\n  v",
+        "[2]:\n  - a: 1\n    b: 2\n  - 3",
+        "hello\nworld",
+        "a:\n   b: 1",
+        "[3]{id}:\n  1\n  2",
+        "[1]:\n  - a\n  - b\nafter: 1",
+        "[2]{id}:\n  1\n\n  2",
+        "a: \"x\\q\"",
+        "a[x]: 1",
+        "[x]: 1\nb: 2",
+        "- [x]: 1",
+        "[1]:\n  - [x]: 1",
+    ];
+    for input in inputs {
+        let (value, errors) = toon::decode_all_errors(input, None);
+        match try_decode(input, None) {
+            Ok(expected) => {
+                assert!(errors.is_empty(), "input: {input:?}: {errors:?}");
+                assert_eq!(value, expected, "input: {input:?}");
+            }
+            Err(err) => {
+                let messages: Vec<_> = errors.iter().map(ToString::to_string).collect();
+                assert!(
+                    messages.contains(&err.to_string()),
+                    "input: {input:?}: {err} not in {messages:?}"
+                );
+            }
+        }
+    }
+
+    // A duplicate key is reported and kept, as without the check.
+    let input = "a: 1\nb: 2\na: 3";
+    let (value, errors) = toon::decode_all_errors(input, Some(reject_duplicates()));
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].to_string(),
+        "Line 3, column 1: duplicate key `a` (first defined at line 1)"
+    );
+    assert_eq!(value, decode(input, None));
+}
+
+#[test]
+fn validate_reports_what_decode_all_errors_reports() {
+    let inputs = [
+        "",
+        "a: 1\nb:\n  c[2]: x,y\nd[1]{k}:\n  v",
+        "rows[]{id}:\n  1\n  2",
+        "name: \"Ada\nage: 36\n\tbad: 1\nteam\n  x: 1\ntags[3]: a,b\nrows[1]{id,tag}:\n  1,x,extra\n  2,y",
+        "a: 1\nb: 2\na: 3",
+        "a: \"bad\\q\"\nrows[1]{x,x}:\n  1,\"y\n  - k: \"x\\z\"\ntags[2]: \"open,b",
+    ];
+    // Limits make the decoder build the events it otherwise skips.
+    let limited = DecodeOptions::builder()
+        .strictness(Strictness::strict())
+        .limits(DecodeLimits::default())
+        .build();
+    for options in [reject_duplicates(), limited] {
+        for input in inputs {
+            let expected: Vec<_> = toon::decode_all_errors(input, Some(options.clone()))
+                .1
+                .iter()
+                .map(|err| err.decode_error().unwrap().clone())
+                .collect();
+            match toon::validate(input, Some(options.clone())) {
+                Ok(()) => assert!(expected.is_empty(), "input: {input:?}"),
+                Err(errors) => assert_eq!(errors, expected, "input: {input:?}"),
+            }
+        }
+    }
+}
@@ -4,8 +4,8 @@ use std::path::{Path, PathBuf};
 use serde::Deserialize;
 
 use toon::JsonValue;
-use toon::decode::decode;
-use toon::options::{DecodeOptions, ExpandPathsMode};
+use toon::decode::{decode, decode_to_serde};
+use toon::options::{DecodeOptions, ExpandPathsMode, KeyOrder, Strictness};
 
 #[derive(Debug, Deserialize)]
 struct FixtureFile {
@@ -43,11 +43,12 @@ fn run_case(case: &FixtureCase) {
     let options = parse_decode_options(case.options.as_ref());
     let should_error = case.should_error.unwrap_or(false);
 
+    let direct = decode_to_serde(&case.input, options.clone());
     let result = std::panic::catch_unwind(|| decode(&case.input, options));
 
     if should_error {
         assert!(
-            result.is_err(),
+            result.is_err() && direct.is_err(),
             "expected error for fixture '{}' but decode succeeded",
             case.name
         );
@@ -57,6 +58,12 @@ fn run_case(case: &FixtureCase) {
     let Ok(value) = result else {
         panic!("unexpected panic for fixture '{}'", case.name)
     };
+    assert_eq!(
+        direct.ok(),
+        Some(serde_json::Value::from(value.clone())),
+        "decode_to_serde differs for fixture '{}'",
+        case.name
+    );
 
     let expected = case
         .expected_json
@@ -75,7 +82,16 @@ fn parse_decode_options(options: Option<&serde_json::Value>) -> Option<DecodeOpt
         .get("indent")
         .and_then(serde_json::Value::as_u64)
         .and_then(|value| usize::try_from(value).ok());
-    let strict = options.get("strict").and_then(serde_json::Value::as_bool);
+    let strictness = options
+        .get("strict")
+        .and_then(serde_json::Value::as_bool)
+        .map(|strict| {
+            if strict {
+                Strictness::default()
+            } else {
+                Strictness::lenient()
+            }
+        });
     let expand_paths = options
         .get("expandPaths")
         .and_then(|value| value.as_str())
@@ -85,11 +101,11 @@ fn parse_decode_options(options: Option<&serde_json::Value>) -> Option<DecodeOpt
             _ => None,
         });
 
-    Some(DecodeOptions {
-        indent,
-        strict,
-        expand_paths,
-    })
+    let mut decode_options = DecodeOptions::default();
+    decode_options.indent = indent;
+    decode_options.strictness = strictness;
+    decode_options.expand_paths = expand_paths;
+    Some(decode_options)
 }
 
 fn json_value_to_serde(value: JsonValue) -> serde_json::Value {
@@ -97,7 +113,8 @@ fn json_value_to_serde(value: JsonValue) -> serde_json::Value {
         JsonValue::Primitive(primitive) => match primitive {
             toon::StringOrNumberOrBoolOrNull::Null => serde_json::Value::Null,
             toon::StringOrNumberOrBoolOrNull::Bool(value) => serde_json::Value::Bool(value),
-            toon::StringOrNumberOrBoolOrNull::Number(value) => serde_json::Number::from_f64(value)
+            toon::StringOrNumberOrBoolOrNull::Number(value) => value
+                .to_json_number()
                 .map_or(serde_json::Value::Null, serde_json::Value::Number),
             toon::StringOrNumberOrBoolOrNull::String(value) => serde_json::Value::String(value),
         },
@@ -153,11 +170,12 @@ fn json_eq(left: &serde_json::Value, right: &serde_json::Value) -> bool {
 #[test]
 fn expand_paths_respects_quoted_dotted_array_key() {
     let input = "\"a.b\"[1]: 1";
-    let options = Some(DecodeOptions {
-        indent: None,
-        strict: Some(true),
-        expand_paths: Some(ExpandPathsMode::Safe),
-    });
+    let options = Some(
+        DecodeOptions::builder()
+            .strictness(Strictness::default())
+            .expand_paths(ExpandPathsMode::Safe)
+            .build(),
+    );
 
     let value = decode(input, options);
     let actual = json_value_to_serde(value);
@@ -169,11 +187,12 @@ fn expand_paths_respects_quoted_dotted_array_key() {
 #[test]
 fn expand_paths_respects_quoted_dotted_tabular_field() {
     let input = "items[1]{\"a.b\"}:\n  1";
-    let options = Some(DecodeOptions {
-        indent: None,
-        strict: Some(true),
-        expand_paths: Some(ExpandPathsMode::Safe),
-    });
+    let options = Some(
+        DecodeOptions::builder()
+            .strictness(Strictness::default())
+            .expand_paths(ExpandPathsMode::Safe)
+            .build(),
+    );
 
     let value = decode(input, options);
     let actual = json_value_to_serde(value);
@@ -182,6 +201,34 @@ fn expand_paths_respects_quoted_dotted_tabular_field() {
     assert_json_eq(&actual, &expected, "quoted dotted tabular field");
 }
 
+#[test]
+fn decode_to_serde_matches_converted_value_when_expanding_and_sorting() {
+    let input = "b.y: 1\na.x: 2\nb.z[2]: q,r";
+    let options = DecodeOptions::builder()
+        .expand_paths(ExpandPathsMode::Safe)
+        .key_order(KeyOrder::Sorted)
+        .build();
+
+    let direct = decode_to_serde(input, Some(options.clone())).unwrap();
+    assert_eq!(
+        direct,
+        serde_json::Value::from(decode(input, Some(options)))
+    );
+    assert_eq!(
+        serde_json::to_string(&direct).unwrap(),
+        r#"{"a":{"x":2},"b":{"y":1,"z":["q","r"]}}"#
+    );
+}
+
+#[test]
+fn decode_to_serde_keeps_first_position_of_repeated_keys() {
+    let options = DecodeOptions::builder()
+        .strictness(Strictness::lenient())
+        .build();
+    let direct = decode_to_serde("a: 1\nb: 2\na: 3", Some(options)).unwrap();
+    assert_eq!(serde_json::to_string(&direct).unwrap(), r#"{"a":3,"b":2}"#);
+}
+
 fn fixture_root() -> PathBuf {
     if let Ok(path) = std::env::var("TOON_SPEC_FIXTURES") {
         return PathBuf::from(path);
@@ -0,0 +1,89 @@
+//! Decoding from `io::Read` sources.
+
+use toon::decode;
+use toon::options::DecodeOptions;
+
+/// Hands out at most one byte per read, to split lines across buffer fills.
+struct Trickle<'a>(&'a [u8]);
+
+impl std::io::Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let Some((&first, rest)) = self.0.split_first() else {
+            return Ok(0);
+        };
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        buf[0] = first;
+        self.0 = rest;
+        Ok(1)
+    }
+}
+
+#[test]
+fn decode_from_reader_matches_decode_for_lf_and_crlf() {
+    let input = "user:\n  name: Ada\n  tags[2]: a,b\nrows[2]{id,note}:\n  1,\"x\\ty\"\n  2,z\n";
+    let expected = decode(input, None);
+    let crlf = input.replace('\n', "\r\n");
+    for text in [input, input.trim_end(), crlf.as_str()] {
+        assert_eq!(
+            toon::decode_from_reader(text.as_bytes(), None).unwrap(),
+            expected
+        );
+        assert_eq!(
+            toon::decode_from_reader(Trickle(text.as_bytes()), None).unwrap(),
+            expected
+        );
+    }
+    assert_eq!(
+        toon::decode_from_reader(&b""[..], None).unwrap(),
+        decode("", None)
+    );
+
+    let events = toon::decode_stream_from_reader(crlf.as_bytes(), None).unwrap();
+    let lines = input.lines().map(String::from).collect::<Vec<_>>();
+    assert_eq!(events, toon::decode_stream_sync(lines, None));
+}
+
+#[test]
+fn decode_from_reader_enforces_max_line_length() {
+    let options = |limit| DecodeOptions::builder().max_line_length(limit).build();
+    let input = "a: 1\r\nname: abcdef\r\n";
+    assert!(toon::decode_from_reader(Trickle(input.as_bytes()), Some(options(12))).is_ok());
+    let err = toon::decode_from_reader(Trickle(input.as_bytes()), Some(options(11))).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Line 2, column 12: Line is 12 bytes long, over the limit of 11"
+    );
+
+    let long = format!("a: 1\nb: {}\n", "x".repeat(10_000));
+    let err = toon::decode_from_reader(long.as_bytes(), Some(options(64))).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Line 2, column 65: Line is 10003 bytes long, over the limit of 64"
+    );
+}
+
+#[test]
+fn decode_from_reader_reports_read_errors_first() {
+    struct Broken<'a>(&'a [u8]);
+
+    impl std::io::Read for Broken<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.0.is_empty() {
+                return Err(std::io::Error::other("connection reset"));
+            }
+            let n = buf.len().min(self.0.len());
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    // The truncated input would fail the length check, but the read error wins.
+    let err = toon::decode_from_reader(Broken(b"tags[3]:\n  - a\n"), None).unwrap_err();
+    assert_eq!(err.to_string(), "Failed to read input: connection reset");
+
+    let err = toon::decode_from_reader(&b"a: 1\nb: \xff\n"[..], None).unwrap_err();
+    assert_eq!(err.to_string(), "Line 2, column 4: Line is not valid UTF-8");
+}
@@ -0,0 +1,67 @@
+//! `miette` diagnostics for decode errors.
+
+#![cfg(feature = "diagnostics")]
+
+use miette::{Diagnostic, NarratableReportHandler};
+use toon::try_decode;
+
+fn render(diagnostic: &dyn Diagnostic) -> String {
+    let mut out = String::new();
+    NarratableReportHandler::new()
+        .render_report(&mut out, diagnostic)
+        .unwrap();
+    out
+}
+
+#[test]
+fn decode_errors_label_the_offending_text() {
+    let err = try_decode("a:\n  b: 1\n  c 2", None).unwrap_err();
+    assert_eq!(
+        err.code().unwrap().to_string(),
+        "toon::decode::missing_colon"
+    );
+    assert_eq!(
+        err.help().unwrap().to_string(),
+        "separate the key from its value with `: `"
+    );
+    let labels: Vec<_> = err.labels().unwrap().collect();
+    assert_eq!(labels.len(), 1);
+    assert_eq!((labels[0].offset(), labels[0].len()), (2, 3));
+    assert_eq!(labels[0].label(), Some("expected `key: value`"));
+
+    let span = err
+        .source_code()
+        .unwrap()
+        .read_span(labels[0].inner(), 1, 1)
+        .unwrap();
+    assert_eq!(span.data(), b"  c 2");
+    // Zero-based, as miette counts lines.
+    assert_eq!(span.line(), 2);
+}
+
+#[test]
+fn decode_errors_render_with_their_line_number() {
+    let err = try_decode("items[2]: a,b\nname: \"Ada", None).unwrap_err();
+    let report = render(&err);
+    assert!(
+        report.contains("Line 2, column 7: Unterminated string"),
+        "{report}"
+    );
+    assert!(report.contains("snippet line 2: name: \"Ada"), "{report}");
+    assert!(
+        report.contains("label at line 2, columns 7 to 10: string opened here"),
+        "{report}"
+    );
+    assert!(
+        report.contains("diagnostic help: close the string with `\"`"),
+        "{report}"
+    );
+}
+
+#[test]
+fn errors_without_a_location_have_no_snippet() {
+    let err = toon::json_to_toon("{").unwrap_err();
+    assert!(err.code().is_none());
+    assert!(err.labels().is_none());
+    assert!(err.source_code().is_none());
+}
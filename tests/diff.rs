@@ -0,0 +1,79 @@
+use serde_json::json;
+use toon::JsonValue;
+use toon::diff::{DiffEntry, DiffFormat, render, to_patch};
+use toon::patch::apply;
+
+fn value(json: serde_json::Value) -> JsonValue {
+    JsonValue::from(json)
+}
+
+#[test]
+fn diff_replays_as_patch() {
+    let pairs = [
+        (json!({"a": 1}), json!({"a": 1})),
+        (json!({"a": 1, "b": 2}), json!({"b": 2, "a": 1})),
+        (json!(1), json!("one")),
+        (json!([1, 2, 3, 4]), json!([1, 5])),
+        (json!([1]), json!([1, [2], {"x": 3}])),
+        (
+            json!({"server": {"host": "a", "ports": [80, 443]}, "old": true}),
+            json!({"server": {"host": "b", "ports": [8080]}, "new/key~": null}),
+        ),
+        (
+            json!({"list": [{"id": 1}, {"id": 2}]}),
+            json!({"list": {"id": 1}}),
+        ),
+    ];
+    for (old, new) in pairs {
+        let (old, new) = (value(old), value(new));
+        let entries = toon::diff(&old, &new);
+        assert_eq!(
+            entries.is_empty(),
+            old.semantic_eq(&new, toon::Tolerance::EXACT)
+        );
+        let mut patched = old.clone();
+        apply(&mut patched, &to_patch(&entries)).unwrap();
+        assert!(
+            patched.semantic_eq(&new, toon::Tolerance::EXACT),
+            "{entries:?}"
+        );
+    }
+}
+
+#[test]
+fn diff_paths_are_escaped_pointers() {
+    let entries = toon::diff(
+        &value(json!({"a/b": {"c~d": 1}})),
+        &value(json!({"a/b": {"c~d": 2.0}})),
+    );
+    assert_eq!(
+        entries,
+        [DiffEntry::Changed {
+            path: "/a~1b/c~0d".to_string(),
+            old: 1.into(),
+            new: 2.0.into(),
+        }]
+    );
+    assert!(toon::diff(&value(json!(1)), &value(json!(1.0))).is_empty());
+}
+
+#[test]
+fn render_as_toon_or_json_patch() {
+    let entries = toon::diff(
+        &value(json!({"name": "api", "port": 80})),
+        &value(json!({"port": 8080})),
+    );
+    assert_eq!(
+        render(&entries, DiffFormat::Toon, None).unwrap(),
+        "[2]:\n  - op: remove\n    path: /name\n  - op: replace\n    path: /port\n    value: 8080"
+    );
+    let json: serde_json::Value =
+        serde_json::from_str(&render(&entries, DiffFormat::JsonPatch, None).unwrap()).unwrap();
+    assert_eq!(
+        json,
+        json!([
+            {"op": "remove", "path": "/name"},
+            {"op": "replace", "path": "/port", "value": 8080}
+        ])
+    );
+}
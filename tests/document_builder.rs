@@ -0,0 +1,112 @@
+//! Incremental document construction tests.
+
+use toon::{DocumentBuilder, JsonValue, decode};
+
+#[test]
+fn builds_nested_scopes_and_tables() {
+    let mut doc = DocumentBuilder::new();
+    doc.field("service", "api").unwrap();
+    doc.object("limits").unwrap().field("rps", 50).unwrap();
+    assert_eq!(doc.depth(), 1);
+    doc.end().unwrap();
+    doc.array("tags")
+        .unwrap()
+        .item("a")
+        .unwrap()
+        .item("b")
+        .unwrap();
+    doc.end().unwrap();
+    doc.table("hosts", ["name", "up"]).unwrap();
+    doc.row([JsonValue::from("h1"), JsonValue::from(true)])
+        .unwrap();
+    doc.row([JsonValue::from("h2"), JsonValue::from(false)])
+        .unwrap();
+    doc.end().unwrap();
+    doc.array("events").unwrap();
+    doc.object_item()
+        .unwrap()
+        .field("id", 1)
+        .unwrap()
+        .end()
+        .unwrap();
+    doc.array_item().unwrap().item(2).unwrap().end().unwrap();
+    doc.end().unwrap();
+
+    let toon = doc.build(None).unwrap();
+    assert_eq!(
+        toon,
+        "service: api\nlimits:\n  rps: 50\ntags[2]: a,b\nhosts[2]{name,up}:\n  h1,true\n  h2,false\nevents[2]:\n  - id: 1\n  - [1]: 2"
+    );
+    assert_eq!(
+        serde_json::Value::from(decode(&toon, None)),
+        serde_json::json!({
+            "service": "api",
+            "limits": {"rps": 50},
+            "tags": ["a", "b"],
+            "hosts": [{"name": "h1", "up": true}, {"name": "h2", "up": false}],
+            "events": [{"id": 1}, [2]]
+        })
+    );
+}
+
+#[test]
+fn writes_to_writer() {
+    let mut doc = DocumentBuilder::new();
+    doc.field("a", 1).unwrap();
+    let mut out = Vec::new();
+    doc.write_to(&mut out, None).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "a: 1");
+}
+
+#[test]
+fn rejects_misplaced_calls() {
+    let mut doc = DocumentBuilder::new();
+    assert!(doc.item(1).is_err());
+    assert!(doc.row([JsonValue::from(1)]).is_err());
+    assert!(doc.end().is_err());
+
+    doc.field("a", 1).unwrap();
+    let err = doc.field("a", 2).unwrap_err();
+    assert_eq!(err.to_string(), "duplicate key `a`");
+
+    doc.array("list").unwrap();
+    let err = doc.field("x", 1).unwrap_err();
+    assert_eq!(err.to_string(), "key `x` given outside an object");
+}
+
+#[test]
+fn validates_table_rows() {
+    let mut doc = DocumentBuilder::new();
+    assert!(doc.table("t", Vec::<String>::new()).is_err());
+    assert!(doc.table("t", ["a", "a"]).is_err());
+
+    doc.object("data")
+        .unwrap()
+        .table("rows", ["a", "b"])
+        .unwrap();
+    let err = doc.row([JsonValue::from(1)]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "row 1 of table `data.rows` has 1 cells, expected 2"
+    );
+    let err = doc
+        .row([JsonValue::from(1), JsonValue::Array(Vec::new())])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "row 1 of table `data.rows`: cell `b` must be a primitive"
+    );
+}
+
+#[test]
+fn finish_requires_closed_scopes() {
+    let mut doc = DocumentBuilder::new();
+    doc.object("outer").unwrap().array("inner").unwrap();
+    let err = doc.finish().unwrap_err();
+    assert_eq!(err.to_string(), "scope `outer.inner` was never closed");
+
+    assert_eq!(
+        DocumentBuilder::default().finish().unwrap(),
+        JsonValue::Object(Vec::new())
+    );
+}
@@ -10,9 +10,19 @@
 //! - Delimiter edge cases
 //! - Key folding conflict scenarios
 
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use proptest::prelude::*;
-use toon::options::{DecodeOptions, EncodeOptions, ExpandPathsMode, KeyFoldingMode};
-use toon::{JsonValue, decode, encode, try_decode};
+use toon::options::{
+    DecodeOptions, EncodeOptions, ExpandPathsMode, KeyFoldingMode, KeyOrder, NonFinitePolicy,
+    Profile, QuotePolicy, Strictness,
+};
+use toon::{
+    DecodeWarningKind, EncodeDecisionKind, IndentStyle, JsonStreamEvent, JsonValue, LengthMarkers,
+    LineEnding, Metrics, Number, StringOrNumberOrBoolOrNull, Tolerance, WarningSink, decode,
+    encode, encode_with_report, try_decode, try_decode_with_warnings, try_encode,
+};
 
 // ============================================================================
 // UNICODE EDGE CASES
@@ -122,7 +132,7 @@ fn deeply_nested_arrays_100_levels() {
 
 #[test]
 fn deeply_nested_mixed_100_levels() {
-    let mut value: serde_json::Value = serde_json::json!(42.0);
+    let mut value: serde_json::Value = serde_json::json!(42);
     for i in 0..50 {
         if i % 2 == 0 {
             value = serde_json::json!({ "obj": value });
@@ -239,7 +249,9 @@ fn numeric_nan_becomes_null() {
         ),
         (
             "valid".to_string(),
-            JsonValue::Primitive(toon::StringOrNumberOrBoolOrNull::Number(42.0)),
+            JsonValue::Primitive(toon::StringOrNumberOrBoolOrNull::Number(toon::Number::I64(
+                42,
+            ))),
         ),
     ]);
     let toon = toon::encode::encode(value, None);
@@ -388,13 +400,7 @@ fn array_with_pipe_delimiter() {
     let json: serde_json::Value = serde_json::json!({
         "items": ["a", "b", "c"]
     });
-    let options = Some(EncodeOptions {
-        indent: None,
-        delimiter: Some('|'),
-        key_folding: None,
-        flatten_depth: None,
-        replacer: None,
-    });
+    let options = Some(EncodeOptions::builder().delimiter('|').build());
     let toon = encode(json.clone(), options);
     assert!(toon.contains('|'));
     let decoded = decode(&toon, None);
@@ -402,6 +408,117 @@ fn array_with_pipe_delimiter() {
     assert_eq!(json, decoded_json);
 }
 
+fn delimiter_options(delimiter: char) -> EncodeOptions {
+    EncodeOptions::builder().delimiter(delimiter).build()
+}
+
+#[test]
+fn tabular_array_with_semicolon_delimiter() {
+    let json: serde_json::Value = serde_json::json!({
+        "rows": [
+            {"note": "a, b", "tag": "x;y"},
+            {"note": "plain", "tag": "z"}
+        ]
+    });
+    let toon = encode(json.clone(), Some(delimiter_options(';')));
+    assert_eq!(toon, "rows[2;]{note;tag}:\n  a, b;\"x;y\"\n  plain;z");
+    let decoded_json: serde_json::Value = decode(&toon, None).into();
+    assert_eq!(json, decoded_json);
+}
+
+#[test]
+fn inline_array_with_tab_delimiter_quotes_embedded_tabs() {
+    let json: serde_json::Value = serde_json::json!({
+        "items": ["a|b", "c\td", "e,f"]
+    });
+    let toon = encode(json.clone(), Some(delimiter_options('\t')));
+    assert_eq!(toon, "items[3\t]: a|b\t\"c\\td\"\te,f");
+    let decoded_json: serde_json::Value = decode(&toon, None).into();
+    assert_eq!(json, decoded_json);
+}
+
+#[test]
+fn unsupported_delimiter_falls_back_to_comma() {
+    let json: serde_json::Value = serde_json::json!({"items": ["a", "b"]});
+    let toon = encode(json.clone(), Some(delimiter_options('#')));
+    assert_eq!(toon, "items[2]: a,b");
+
+    let err = try_encode(json.clone(), Some(delimiter_options(':'))).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Unsupported delimiter ':': expected ',', '\\t', '|' or ';'"
+    );
+    let options = EncodeOptions::builder()
+        .delimiter_override("items", '#')
+        .build();
+    assert!(
+        try_encode(json, Some(options))
+            .unwrap_err()
+            .to_string()
+            .contains("for `items`")
+    );
+}
+
+#[test]
+fn delimiter_override_applies_to_matching_path_only() {
+    let json: serde_json::Value = serde_json::json!({
+        "notes": [{"text": "a, b", "id": 1}, {"text": "c, d", "id": 2}],
+        "data": {"tags": ["x", "y"]},
+        "other": ["p", "q"]
+    });
+    let options = EncodeOptions::builder()
+        .delimiter_override("notes", '|')
+        .delimiter_override("data.tags", ';')
+        .build();
+    let toon = encode(json.clone(), Some(options));
+    assert_eq!(
+        toon,
+        "notes[2|]{text|id}:\n  a, b|1\n  c, d|2\ndata:\n  tags[2;]: x;y\nother[2]: p,q"
+    );
+    let decoded_json: serde_json::Value = decode(&toon, None).into();
+    assert_eq!(json, decoded_json);
+}
+
+#[test]
+fn delimiter_override_matches_through_array_elements() {
+    let json: serde_json::Value = serde_json::json!({
+        "items": [
+            {"tags": ["a", "b"], "id": 1},
+            {"id": 2, "tags": ["c"]}
+        ]
+    });
+    let options = EncodeOptions::builder()
+        .delimiter_override("items.tags", '|')
+        .build();
+    let toon = encode(json.clone(), Some(options));
+    assert_eq!(
+        toon,
+        "items[2]:\n  - tags[2|]: a|b\n    id: 1\n  - id: 2\n    tags[1|]: c"
+    );
+    let decoded_json: serde_json::Value = decode(&toon, None).into();
+    assert_eq!(json, decoded_json);
+}
+
+#[test]
+fn delimiter_override_does_not_reach_nested_arrays() {
+    let json: serde_json::Value = serde_json::json!({
+        "grid": [[1, 2], [3]],
+        "groups": [{"name": "a", "members": ["x", "y"]}, ["p", "q"]]
+    });
+    let options = EncodeOptions::builder()
+        .delimiter(';')
+        .delimiter_override("grid", '|')
+        .delimiter_override("groups", '|')
+        .build();
+    let toon = encode(json.clone(), Some(options));
+    assert_eq!(
+        toon,
+        "grid[2|]:\n  - [2;]: 1;2\n  - [1;]: 3\ngroups[2|]:\n  - name: a\n    members[2;]: x;y\n  - [2;]: p;q"
+    );
+    let decoded_json: serde_json::Value = decode(&toon, None).into();
+    assert_eq!(json, decoded_json);
+}
+
 #[test]
 fn tabular_array_with_special_chars() {
     let json: serde_json::Value = serde_json::json!([
@@ -419,82 +536,130 @@ fn tabular_array_with_special_chars() {
 // ============================================================================
 
 #[test]
-fn key_folding_simple() {
-    let json: serde_json::Value = serde_json::json!({
-        "a": {"b": {"c": "value"}}
+fn aggressive_key_folding_quotes_segments() {
+    let json = serde_json::json!({
+        "a.b": {"c": 1},
+        "x": {"y-z": {"w": [1, 2]}},
+        "k": {"has \"quote\"": {"n": null}},
     });
-    let options = Some(EncodeOptions {
-        indent: None,
-        delimiter: None,
-        key_folding: Some(KeyFoldingMode::Safe),
-        flatten_depth: None,
-        replacer: None,
-    });
-    let toon = encode(json.clone(), options);
-    assert!(toon.contains("a.b.c"));
+    let options = EncodeOptions::builder()
+        .key_folding(KeyFoldingMode::Aggressive)
+        .build();
+    let toon = encode(json.clone(), Some(options));
+    assert_eq!(
+        toon,
+        "\"a.b\".c: 1\nx.\"y-z\".w[2]: 1,2\nk.\"has \\\"quote\\\"\".n: null"
+    );
 
-    let decode_options = Some(DecodeOptions {
-        indent: None,
-        strict: None,
-        expand_paths: Some(ExpandPathsMode::Safe),
-    });
-    let decoded = decode(&toon, decode_options);
-    let decoded_json: serde_json::Value = decoded.into();
-    assert_eq!(json, decoded_json);
+    let aggressive = DecodeOptions::builder()
+        .expand_paths(ExpandPathsMode::Aggressive)
+        .build();
+    assert_eq!(
+        serde_json::Value::from(decode(&toon, Some(aggressive.clone()))),
+        json
+    );
+    assert_eq!(
+        serde_json::Value::from(toon::decode_borrowed(&toon).unwrap().into_owned())["\"a.b\".c"],
+        1
+    );
+
+    // Other modes keep the key as written.
+    let safe = DecodeOptions::builder()
+        .expand_paths(ExpandPathsMode::Safe)
+        .build();
+    let decoded = serde_json::Value::from(decode(&toon, Some(safe)));
+    assert_eq!(decoded["\"a.b\".c"], 1);
+    assert_eq!(decoded["x.\"y-z\".w"], serde_json::json!([1, 2]));
+
+    // Always-quoted keys quote every segment so the path still expands.
+    let quoted = EncodeOptions::builder()
+        .key_folding(KeyFoldingMode::Aggressive)
+        .quote_strings(QuotePolicy::AlwaysKeysAndStrings)
+        .build();
+    let toon = encode(serde_json::json!({"a": {"b": 1}}), Some(quoted));
+    assert_eq!(toon, "\"a\".\"b\": 1");
+    assert_eq!(
+        serde_json::Value::from(decode(&toon, Some(aggressive))),
+        serde_json::json!({"a": {"b": 1}})
+    );
 }
 
 #[test]
-fn key_folding_with_sibling() {
-    // When there's a sibling, folding still happens for the single-key chain
-    // but we need expand_paths to reconstruct the nested structure
-    let json: serde_json::Value = serde_json::json!({
-        "a": {
-            "b": {"c": "deep"},
-            "sibling": "value"
-        }
-    });
-    let options = Some(EncodeOptions {
-        indent: None,
-        delimiter: None,
-        key_folding: Some(KeyFoldingMode::Safe),
-        flatten_depth: None,
-        replacer: None,
-    });
-    let toon = encode(json.clone(), options);
-
-    // Need to expand paths to reconstruct the nested structure
-    let decode_options = Some(DecodeOptions {
-        indent: None,
-        strict: None,
-        expand_paths: Some(ExpandPathsMode::Safe),
-    });
-    let decoded = decode(&toon, decode_options);
-    let decoded_json: serde_json::Value = decoded.into();
-    assert_eq!(json, decoded_json);
+fn aggressive_key_folding_quotes_literal_dotted_keys() {
+    let options = EncodeOptions::builder()
+        .key_folding(KeyFoldingMode::Aggressive)
+        .build();
+    let aggressive = DecodeOptions::builder()
+        .expand_paths(ExpandPathsMode::Aggressive)
+        .build();
+    for (json, expected) in [
+        (serde_json::json!({"a.b": 1}), "\"a.b\": 1"),
+        (
+            serde_json::json!({"x": {"a.b": 1, "c": 2}}),
+            "x:\n  \"a.b\": 1\n  c: 2",
+        ),
+        (
+            serde_json::json!({"a": {"b": 1}, "a.b": 2}),
+            "a:\n  b: 1\n\"a.b\": 2",
+        ),
+        (serde_json::json!({"a.b": {"c": 1}}), "\"a.b\".c: 1"),
+        (
+            serde_json::json!({"items": [{"a.b": 1, "c": [2]}]}),
+            "items[1]:\n  - \"a.b\": 1\n    c[1]: 2",
+        ),
+    ] {
+        let toon = encode(json.clone(), Some(options.clone()));
+        assert_eq!(toon, expected);
+        let decoded = try_decode(&toon, Some(aggressive.clone())).unwrap();
+        assert_eq!(serde_json::Value::from(decoded), json, "{toon}");
+    }
 }
 
 #[test]
-fn key_folding_depth_limit() {
-    let json: serde_json::Value = serde_json::json!({
-        "a": {"b": {"c": {"d": {"e": "deep"}}}}
+fn key_folding_through_single_item_arrays() {
+    let json = serde_json::json!({
+        "a": [{"b": 1}],
+        "c": {"d": [{"e": {"f": [1, 2]}}]},
+        "g": [{"h": 1, "i": 2}],
+        "j": [{"k": 1}, {"k": 2}],
     });
-    let options = Some(EncodeOptions {
-        indent: None,
-        delimiter: None,
-        key_folding: Some(KeyFoldingMode::Safe),
-        flatten_depth: Some(2), // Only fold 2 levels
-        replacer: None,
-    });
-    let toon = encode(json.clone(), options);
+    let fold = |mode| {
+        EncodeOptions::builder()
+            .key_folding(mode)
+            .fold_single_item_arrays(true)
+            .build()
+    };
+    let toon = encode(json.clone(), Some(fold(KeyFoldingMode::Safe)));
+    assert_eq!(
+        toon,
+        "a[0].b: 1\nc.d[0].e.f[2]: 1,2\ng[1]{h,i}:\n  1,2\nj[2]{k}:\n  1\n  2"
+    );
+    for mode in [ExpandPathsMode::Indexed, ExpandPathsMode::Aggressive] {
+        let options = DecodeOptions::builder().expand_paths(mode).build();
+        assert_eq!(serde_json::Value::from(decode(&toon, Some(options))), json);
+    }
 
-    let decode_options = Some(DecodeOptions {
-        indent: None,
-        strict: None,
-        expand_paths: Some(ExpandPathsMode::Safe),
-    });
-    let decoded = decode(&toon, decode_options);
-    let decoded_json: serde_json::Value = decoded.into();
-    assert_eq!(json, decoded_json);
+    // Without the option arrays end the chain.
+    let toon = encode(
+        json,
+        Some(
+            EncodeOptions::builder()
+                .key_folding(KeyFoldingMode::Safe)
+                .build(),
+        ),
+    );
+    assert!(toon.starts_with("a[1]{b}:\n  1\nc.d[1]:"));
+
+    let json = serde_json::json!({"x-y": [{"z": {"w": true}}]});
+    let toon = encode(json.clone(), Some(fold(KeyFoldingMode::Aggressive)));
+    assert_eq!(toon, "\"x-y\"[0].z.w: true");
+    let aggressive = DecodeOptions::builder()
+        .expand_paths(ExpandPathsMode::Aggressive)
+        .build();
+    assert_eq!(
+        serde_json::Value::from(decode(&toon, Some(aggressive))),
+        json
+    );
 }
 
 #[test]
@@ -573,11 +738,11 @@ fn strict_mode_rejects_tabs() {
     let toon_with_tabs = "\tname: value";
     let result = try_decode(
         toon_with_tabs,
-        Some(DecodeOptions {
-            indent: None,
-            strict: Some(true),
-            expand_paths: None,
-        }),
+        Some(
+            DecodeOptions::builder()
+                .strictness(Strictness::default())
+                .build(),
+        ),
     );
     assert!(result.is_err());
 }
@@ -587,17 +752,119 @@ fn non_strict_mode_accepts_tabs() {
     let toon_with_tabs = "\tname: value";
     let result = try_decode(
         toon_with_tabs,
-        Some(DecodeOptions {
-            indent: None,
-            strict: Some(false),
-            expand_paths: None,
-        }),
+        Some(
+            DecodeOptions::builder()
+                .strictness(Strictness::lenient())
+                .build(),
+        ),
     );
     // Non-strict mode should at least not panic - we accept any result
     // (The exact outcome depends on implementation details)
     let _ = result;
 }
 
+#[test]
+fn strict_mode_rejects_indent_off_by_one() {
+    let err = try_decode("outer:\n   inner: 1", None).unwrap_err();
+    assert!(err.to_string().contains("Line 2"), "{err}");
+}
+
+#[test]
+fn exact_indent_applies_without_strict_mode() {
+    let toon = "outer:\n   inner: 1";
+    let lenient = DecodeOptions::builder()
+        .strictness(Strictness::lenient())
+        .build();
+    assert!(try_decode(toon, Some(lenient)).is_ok());
+
+    let mut strictness = Strictness::lenient();
+    strictness.indentation = true;
+    let exact = DecodeOptions::builder().strictness(strictness).build();
+    assert!(try_decode(toon, Some(exact)).is_err());
+}
+
+#[test]
+fn exact_indent_can_be_relaxed_in_strict_mode() {
+    let mut strictness = Strictness::default();
+    strictness.indentation = false;
+    let options = DecodeOptions::builder().strictness(strictness).build();
+    let decoded = try_decode("outer:\n   inner: 1", Some(options.clone())).unwrap();
+    let json: serde_json::Value = decoded.into();
+    assert_eq!(json["outer"]["inner"].as_f64(), Some(1.0));
+    assert!(try_decode("\tname: value", Some(options)).is_err());
+}
+
+#[test]
+fn require_length_markers_rejects_unmarked_headers() {
+    let options = DecodeOptions::builder()
+        .require_length_markers(true)
+        .build();
+    for toon in ["items[]: a,b", "rows[]{id,name}:\n  1,x", "a: 1\nb[n]: 1"] {
+        let err = try_decode(toon, Some(options.clone())).unwrap_err();
+        assert!(err.to_string().contains("no valid length marker"), "{err}");
+    }
+    let err = try_decode("a: 1\nb[n]: 1", Some(options.clone())).unwrap_err();
+    assert!(err.to_string().contains("Line 2"), "{err}");
+
+    assert!(try_decode("items[2]: a,b\nnote: see x[]: y", Some(options)).is_ok());
+}
+
+#[test]
+fn unmarked_headers_are_plain_keys_by_default() {
+    let decoded = try_decode("items[]: a,b", None).unwrap();
+    assert_eq!(
+        decoded,
+        JsonValue::Object(vec![("items[]".to_string(), JsonValue::from("a,b"))])
+    );
+}
+
+#[test]
+fn max_line_length_rejects_long_lines() {
+    let options = DecodeOptions::builder().max_line_length(16).build();
+    let toon = format!("a: 1\nb: {}", "x".repeat(64));
+    let err = try_decode(&toon, Some(options.clone())).unwrap_err();
+    assert!(err.to_string().contains("Line 2"), "{err}");
+    assert!(err.to_string().contains("67 bytes"), "{err}");
+
+    assert!(try_decode("a: 1\nb: 0123456789abc", Some(options)).is_ok());
+    assert!(try_decode(&toon, None).is_ok());
+}
+
+fn reject_duplicates() -> DecodeOptions {
+    DecodeOptions::strict()
+}
+
+#[test]
+fn reject_duplicate_keys_names_both_lines() {
+    let err = try_decode("a: 1\nb: 2\na: 3", Some(reject_duplicates())).unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains("Line 3"), "{message}");
+    assert!(
+        message.contains("duplicate key `a` (first defined at line 1)"),
+        "{message}"
+    );
+}
+
+#[test]
+fn reject_duplicate_keys_in_nested_objects_and_tables() {
+    let nested = "outer:\n  x: 1\n  x: 2";
+    assert!(try_decode(nested, Some(reject_duplicates())).is_err());
+
+    let table = "rows[1]{id,id}:\n  1,2";
+    assert!(try_decode(table, Some(reject_duplicates())).is_err());
+}
+
+#[test]
+fn reject_duplicate_keys_allows_same_key_in_siblings() {
+    let toon = "a:\n  id: 1\nb:\n  id: 2\nitems[2]:\n  - id: 1\n  - id: 2";
+    assert!(try_decode(toon, Some(reject_duplicates())).is_ok());
+}
+
+#[test]
+fn duplicate_keys_accepted_by_default() {
+    assert!(try_decode("a: 1\na: 2", None).is_ok());
+}
+
 // ============================================================================
 // SPECIAL CHARACTERS IN KEYS
 // ============================================================================
@@ -638,6 +905,18 @@ fn key_with_quotes() {
     assert_eq!(json, decoded_json);
 }
 
+#[test]
+fn tabular_fields_with_braces_and_colons() {
+    let json: serde_json::Value = serde_json::json!([
+        {"}": 1, "a:b": "x", "{c}": true},
+        {"}": 2, "a:b": "y", "{c}": false}
+    ]);
+    let toon = encode(json.clone(), None);
+    assert!(toon.starts_with("[2]{"));
+    let decoded_json: serde_json::Value = decode(&toon, None).into();
+    assert_eq!(json, decoded_json);
+}
+
 #[test]
 fn numeric_keys() {
     let json: serde_json::Value = serde_json::json!({
@@ -663,3 +942,1401 @@ fn boolean_like_keys() {
     let decoded_json: serde_json::Value = decoded.into();
     assert_eq!(json, decoded_json);
 }
+
+// ============================================================================
+// KEY ORDER
+// ============================================================================
+
+fn decode_keys(input: &str, key_order: KeyOrder) -> Vec<String> {
+    let options = DecodeOptions::builder().key_order(key_order).build();
+    match try_decode(input, Some(options)).unwrap() {
+        JsonValue::Object(entries) => entries.into_iter().map(|(key, _)| key).collect(),
+        other => panic!("expected object, got {other:?}"),
+    }
+}
+
+#[test]
+fn key_order_preserve_keeps_source_order() {
+    let keys = decode_keys("zeta: 1\nalpha: 2\nmid: 3", KeyOrder::Preserve);
+    assert_eq!(keys, ["zeta", "alpha", "mid"]);
+}
+
+#[test]
+fn key_order_sorted_sorts_every_level() {
+    let input = "zeta: 1\nalpha:\n  y: 1\n  b: 2\nmid[1]:\n  - d: 1\n    c: 2";
+    assert_eq!(
+        decode_keys(input, KeyOrder::Sorted),
+        ["alpha", "mid", "zeta"]
+    );
+
+    let options = DecodeOptions::builder().key_order(KeyOrder::Sorted).build();
+    let decoded = try_decode(input, Some(options)).unwrap();
+    assert_eq!(
+        serde_json::to_string(&serde_json::Value::from(decoded)).unwrap(),
+        r#"{"alpha":{"b":2,"y":1},"mid":[{"c":2,"d":1}],"zeta":1}"#
+    );
+}
+
+#[test]
+fn key_order_sorted_applies_after_path_expansion() {
+    let options = DecodeOptions::builder()
+        .expand_paths(ExpandPathsMode::Safe)
+        .key_order(KeyOrder::Sorted)
+        .build();
+    let decoded = try_decode("b.z: 1\na: 2\nb.c: 3", Some(options)).unwrap();
+    assert_eq!(
+        serde_json::to_string(&serde_json::Value::from(decoded)).unwrap(),
+        r#"{"a":2,"b":{"c":3,"z":1}}"#
+    );
+}
+
+#[test]
+fn sort_keys_encodes_every_level_in_sorted_order() {
+    let json = serde_json::json!({
+        "zeta": 1,
+        "alpha": {"y": 1, "b": 2},
+        "mid": [{"d": 1, "c": 2}, {"c": 3, "d": 4}],
+    });
+    let options = EncodeOptions::builder().sort_keys(true).build();
+    assert_eq!(
+        encode(json, Some(options)),
+        "alpha:\n  b: 2\n  y: 1\nmid[2]{c,d}:\n  2,1\n  3,4\nzeta: 1"
+    );
+
+    let options = EncodeOptions::builder()
+        .sort_keys(true)
+        .key_comparator(Arc::new(|a: &str, b: &str| b.cmp(a)))
+        .build();
+    assert_eq!(
+        encode(serde_json::json!({"a": 1, "c": 2, "b": 3}), Some(options)),
+        "c: 2\nb: 3\na: 1"
+    );
+}
+
+// ============================================================================
+// AMBIGUITY WARNINGS
+// ============================================================================
+
+fn ambiguity_lines(input: &str) -> Vec<usize> {
+    let (_, warnings) = try_decode_with_warnings(input, None).unwrap();
+    assert!(
+        warnings
+            .iter()
+            .all(|warning| warning.kind == DecodeWarningKind::AmbiguousValue)
+    );
+    warnings.iter().map(|warning| warning.line).collect()
+}
+
+#[test]
+fn ambiguity_warnings_flag_lookalike_values() {
+    let input = "zip: 007\nscale: 1e5\nenabled: yes\nflag: True\nid: 123456789012345678901234";
+    // With `bignum` the long integer is kept exactly, so nothing is lost.
+    let expected: &[usize] = if cfg!(feature = "bignum") {
+        &[1, 2, 3, 4]
+    } else {
+        &[1, 2, 3, 4, 5]
+    };
+    assert_eq!(ambiguity_lines(input), expected);
+}
+
+#[test]
+fn ambiguity_warnings_cover_inline_and_tabular_values() {
+    let input = "codes[2]: 01,2\nrows[2]{a,b}:\n  x,NULL\n  y,z";
+    assert_eq!(ambiguity_lines(input), [1, 3]);
+}
+
+#[test]
+fn ambiguity_warnings_skip_quoted_and_plain_values() {
+    let input = "zip: \"007\"\ncount: 42\nname: Ada\nok: true\nratio: 0.5";
+    assert!(ambiguity_lines(input).is_empty());
+}
+
+#[test]
+fn ambiguity_warnings_do_not_change_decoded_value() {
+    let input = "zip: 007\nscale: 1e5";
+    let (value, _) = try_decode_with_warnings(input, None).unwrap();
+    assert_eq!(value, decode(input, None));
+}
+
+fn lenient() -> DecodeOptions {
+    DecodeOptions::builder()
+        .strictness(Strictness::lenient())
+        .build()
+}
+
+#[test]
+fn collect_warnings_sink_gathers_warnings_alongside_the_value() {
+    let sink = WarningSink::new();
+    let options = lenient()
+        .into_builder()
+        .collect_warnings(sink.clone())
+        .build();
+    let input = "a: \n\tb: 2\ntags[3]: x,y\nrows[1]{id,name}:\n  1\nzip: 007";
+    let value = try_decode(input, Some(options.clone())).unwrap();
+    assert_eq!(value, decode(input, Some(lenient())));
+
+    let found: Vec<_> = sink
+        .take()
+        .into_iter()
+        .map(|warning| (warning.line, warning.kind))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (1, DecodeWarningKind::TrailingWhitespace),
+            (2, DecodeWarningKind::TabIndentation),
+            (3, DecodeWarningKind::LengthMismatch),
+            (5, DecodeWarningKind::LengthMismatch),
+            (6, DecodeWarningKind::AmbiguousValue),
+        ]
+    );
+    assert!(sink.take().is_empty());
+
+    // try_decode_with_warnings returns the same warnings and still feeds the sink.
+    let (value, warnings) = try_decode_with_warnings(input, Some(options.clone())).unwrap();
+    assert_eq!(value, decode(input, Some(lenient())));
+    assert_eq!(warnings.len(), found.len());
+    assert_eq!(sink.take(), warnings);
+
+    // The same sink keeps gathering across decodes.
+    toon::decode_from_reader(&b"a: 1 \nb: 2 "[..], Some(options.clone())).unwrap();
+    toon::decode_to_serde("[2]: x", Some(options)).unwrap();
+    let messages: Vec<_> = sink.take().iter().map(ToString::to_string).collect();
+    assert_eq!(
+        messages,
+        [
+            "line 1: line ends with whitespace",
+            "line 2: line ends with whitespace",
+            "line 1: Expected 2 inline array items, but got 1",
+        ]
+    );
+}
+
+#[test]
+fn collect_warnings_is_silent_on_strict_errors_and_clean_input() {
+    let sink = WarningSink::new();
+    let options = DecodeOptions::builder()
+        .collect_warnings(sink.clone())
+        .build();
+    assert!(try_decode("tags[3]: x,y", Some(options.clone())).is_err());
+    assert!(sink.take().is_empty());
+    try_decode("a: 1\nrows[1]{id}:\n  1", Some(options)).unwrap();
+    assert!(sink.take().is_empty());
+}
+
+#[test]
+fn non_strict_decode_infers_missing_length_markers() {
+    let input =
+        "tags[]: a,b\nrows[n]{id,name}:\n  1,x\n  2,y\nitems[|]:\n  - [|]: 1|2\n  - k: v\nempty[]:";
+    let (value, warnings) = try_decode_with_warnings(input, Some(lenient())).unwrap();
+    let json: serde_json::Value = value.into();
+    assert_eq!(json["tags"], serde_json::json!(["a", "b"]));
+    assert_eq!(json["rows"].as_array().map(Vec::len), Some(2));
+    assert_eq!(json["rows"][1]["name"], "y");
+    assert_eq!(json["items"][1], serde_json::json!({"k": "v"}));
+    assert_eq!(json["empty"], serde_json::json!([]));
+
+    let lines: Vec<usize> = warnings
+        .iter()
+        .filter(|warning| warning.kind == DecodeWarningKind::MissingLengthMarker)
+        .map(|warning| warning.line)
+        .collect();
+    assert_eq!(lines, [1, 2, 6, 5, 8]);
+    assert!(
+        warnings[1].message.contains("inferred length 2"),
+        "{}",
+        warnings[1]
+    );
+}
+
+#[test]
+fn length_markers_never_round_trips_in_non_strict_mode() {
+    let value: serde_json::Value = serde_json::json!({
+        "rows": [{"id": 1, "name": "x"}, {"id": 2, "name": "y"}],
+        "nested": [[1, 2], {"tags": ["a"]}, 3]
+    });
+    let toon = encode(
+        value.clone(),
+        Some(
+            EncodeOptions::builder()
+                .length_markers(LengthMarkers::Never)
+                .build(),
+        ),
+    );
+    let decoded = try_decode(&toon, Some(lenient())).unwrap();
+    assert!(decoded.semantic_eq(&JsonValue::from(value), Tolerance::EXACT));
+    assert!(try_decode(&toon, None).is_err());
+}
+
+// ============================================================================
+// SEMANTIC EQUALITY
+// ============================================================================
+
+const fn number(value: f64) -> JsonValue {
+    JsonValue::Primitive(toon::StringOrNumberOrBoolOrNull::Number(toon::Number::F64(
+        value,
+    )))
+}
+
+#[test]
+fn semantic_eq_allows_relative_float_error() {
+    let a = number(0.1 + 0.2);
+    assert!(a.semantic_eq(&number(0.3), Tolerance::default()));
+    assert!(!a.semantic_eq(&number(0.3), Tolerance::EXACT));
+    assert!(!number(1.0).semantic_eq(&number(1.001), Tolerance::default()));
+}
+
+#[test]
+fn semantic_eq_absolute_tolerance_near_zero() {
+    let tiny = number(1e-20);
+    assert!(!tiny.semantic_eq(&number(0.0), Tolerance::default()));
+    assert!(tiny.semantic_eq(&number(0.0), Tolerance::absolute(1e-15)));
+}
+
+#[test]
+fn semantic_eq_signed_zero_and_nan_flags() {
+    assert!(number(-0.0).semantic_eq(&number(0.0), Tolerance::default()));
+    assert!(!number(-0.0).semantic_eq(&number(0.0), Tolerance::EXACT));
+
+    let nan = number(f64::NAN);
+    assert!(!nan.semantic_eq(&nan, Tolerance::default()));
+    let nan_equal = Tolerance {
+        nan_equal: true,
+        ..Tolerance::default()
+    };
+    assert!(nan.semantic_eq(&nan, nan_equal));
+    assert!(!nan.semantic_eq(&number(0.0), nan_equal));
+}
+
+#[test]
+fn semantic_eq_int_float_flag() {
+    let one = JsonValue::from(1);
+    assert!(one.semantic_eq(&number(1.0), Tolerance::default()));
+    assert!(!one.semantic_eq(&number(1.0), Tolerance::EXACT));
+    assert!(!number(1.0).semantic_eq(&one, Tolerance::EXACT));
+    assert!(one.semantic_eq(&JsonValue::from(1), Tolerance::EXACT));
+
+    let int_float_equal = Tolerance {
+        int_float_equal: true,
+        ..Tolerance::EXACT
+    };
+    assert!(one.semantic_eq(&number(1.0), int_float_equal));
+    assert!(!one.semantic_eq(&number(1.5), int_float_equal));
+}
+
+#[test]
+fn semantic_eq_ignores_key_order_but_not_array_order() {
+    let a = decode("x: 1\ny: 2\nitems[2]: 1,2", None);
+    let b = decode("items[2]: 1,2\ny: 2\nx: 1", None);
+    assert!(a.semantic_eq(&b, Tolerance::default()));
+
+    let c = decode("x: 1\ny: 2\nitems[2]: 2,1", None);
+    assert!(!a.semantic_eq(&c, Tolerance::default()));
+
+    let d = decode("x: 1\nitems[2]: 1,2", None);
+    assert!(!a.semantic_eq(&d, Tolerance::default()));
+}
+
+#[test]
+fn semantic_eq_compares_repeated_keys_as_a_multiset() {
+    let repeated = decode("a: 1\na: 1", None);
+    let distinct = decode("a: 1\nb: 2", None);
+    assert!(!repeated.semantic_eq(&distinct, Tolerance::default()));
+    assert!(!distinct.semantic_eq(&repeated, Tolerance::default()));
+
+    let reordered = decode("a: 2\nb: 3\na: 1", None);
+    let original = decode("a: 1\na: 2\nb: 3", None);
+    assert!(original.semantic_eq(&reordered, Tolerance::default()));
+    assert!(reordered.semantic_eq(&original, Tolerance::default()));
+    assert!(!original.semantic_eq(&decode("a: 1\na: 1\nb: 3", None), Tolerance::default()));
+}
+
+#[test]
+fn structural_hash_ignores_key_order_and_number_spelling() {
+    let a = decode("x: 1\ny:\n  b: true\n  a: null\nitems[2]: 1,2", None);
+    let b = decode("items[2]: 1.0,2\ny:\n  a: null\n  b: true\nx: 1", None);
+    assert_eq!(a.structural_hash(), b.structural_hash());
+
+    let different = [
+        "x: 1\ny:\n  b: true\n  a: null\nitems[2]: 2,1",
+        "x: 1\ny:\n  b: true\nitems[2]: 1,2",
+        "x: \"1\"\ny:\n  b: true\n  a: null\nitems[2]: 1,2",
+        "x: 1\ny:\n  a: true\n  b: null\nitems[2]: 1,2",
+    ];
+    for text in different {
+        assert_ne!(
+            decode(text, None).structural_hash(),
+            a.structural_hash(),
+            "{text}"
+        );
+    }
+
+    assert_eq!(
+        number(-0.0).structural_hash(),
+        JsonValue::from(0).structural_hash()
+    );
+    assert_eq!(
+        JsonValue::from(Number::U64(1 << 63)).structural_hash(),
+        number(9_223_372_036_854_775_808.0).structural_hash(),
+    );
+}
+
+#[test]
+fn values_are_eq_and_hash_and_dedup_keeps_first() {
+    let nan = number(f64::NAN);
+    assert_eq!(nan, nan.clone());
+    assert_eq!(number(-0.0), JsonValue::from(0));
+    assert_ne!(decode("a: 1\nb: 2", None), decode("b: 2\na: 1", None));
+
+    let set: std::collections::HashSet<JsonValue> = [
+        decode("a: 1", None),
+        decode("a: 1.0", None),
+        nan.clone(),
+        nan.clone(),
+        JsonValue::from(1),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(set.len(), 3);
+
+    let mut values = vec![
+        decode("[2]: x,1", None),
+        nan.clone(),
+        JsonValue::from(1),
+        decode("[2]: x,1.0", None),
+        nan,
+        number(1.0),
+        decode("[2]: 1,x", None),
+    ];
+    toon::dedup_values(&mut values);
+    assert_eq!(
+        values,
+        [
+            decode("[2]: x,1", None),
+            number(f64::NAN),
+            JsonValue::from(1),
+            decode("[2]: 1,x", None),
+        ]
+    );
+}
+
+proptest! {
+    #[test]
+    fn semantic_eq_after_number_roundtrip(n in proptest::num::f64::NORMAL) {
+        let original = JsonValue::from(serde_json::json!({ "value": n }));
+        let decoded = decode(&encode(original.clone(), None), None);
+        prop_assert!(original.semantic_eq(&decoded, Tolerance::default()));
+    }
+}
+
+#[cfg(feature = "proptest")]
+proptest! {
+    #[test]
+    fn roundtrip_arbitrary_documents(value in any::<JsonValue>()) {
+        let decoded = decode(&encode(value.clone(), None), None);
+        prop_assert!(value.semantic_eq(&decoded, Tolerance::default()));
+    }
+
+    #[test]
+    fn roundtrip_arbitrary_tables(
+        value in any_with::<JsonValue>(toon::arbitrary::JsonValueParams {
+            max_depth: 2,
+            table_weight: 8,
+            ..Default::default()
+        })
+    ) {
+        let decoded = decode(&encode(value.clone(), None), None);
+        prop_assert!(value.semantic_eq(&decoded, Tolerance::default()));
+    }
+}
+
+// ============================================================================
+// STRICT ENCODE
+// ============================================================================
+
+#[test]
+fn try_encode_rejects_non_finite_numbers_with_path() {
+    let value = JsonValue::Object(vec![(
+        "items".to_string(),
+        JsonValue::Array(vec![number(1.0), number(f64::NAN)]),
+    )]);
+    let err = try_encode(value.clone(), None).unwrap_err();
+    assert!(err.to_string().contains("items[1]"), "{err}");
+    assert_eq!(encode(value, None), "items[2]: 1,null");
+
+    assert!(try_encode(number(f64::NEG_INFINITY), None).is_err());
+}
+
+#[test]
+fn try_encode_enforces_max_depth() {
+    let value = decode("a:\n  b:\n    c: 1", None);
+    let options = |max_depth| Some(EncodeOptions::builder().max_depth(max_depth).build());
+    assert_eq!(
+        try_encode(value.clone(), options(3)).unwrap(),
+        encode(value.clone(), None)
+    );
+    let err = try_encode(value, options(2)).unwrap_err();
+    assert!(err.to_string().contains("'a.b'"), "{err}");
+}
+
+fn non_finite_options(policy: NonFinitePolicy) -> EncodeOptions {
+    EncodeOptions::builder().on_non_finite(policy).build()
+}
+
+#[test]
+fn non_finite_policy_string_literal() {
+    let value = JsonValue::Array(vec![
+        number(f64::NAN),
+        number(f64::INFINITY),
+        number(f64::NEG_INFINITY),
+        number(1.5),
+    ]);
+    let options = Some(non_finite_options(NonFinitePolicy::StringLiteral));
+    assert_eq!(
+        encode(value.clone(), options.clone()),
+        "[4]: NaN,Infinity,\"-Infinity\",1.5"
+    );
+    assert_eq!(
+        try_encode(value, options).unwrap(),
+        "[4]: NaN,Infinity,\"-Infinity\",1.5"
+    );
+}
+
+#[test]
+fn non_finite_policy_null_opts_try_encode_out_of_errors() {
+    let value = JsonValue::Array(vec![number(f64::NAN)]);
+    assert_eq!(
+        try_encode(value, Some(non_finite_options(NonFinitePolicy::Null))).unwrap(),
+        "[1]: null"
+    );
+}
+
+#[test]
+#[should_panic(expected = "not a finite number")]
+fn non_finite_policy_error_panics_in_encode() {
+    let value = JsonValue::Array(vec![number(f64::INFINITY)]);
+    let _ = encode(value, Some(non_finite_options(NonFinitePolicy::Error)));
+}
+
+#[test]
+fn non_finite_policy_covers_replacer_results() {
+    let value = serde_json::json!({"ratio": 0.5, "name": "x"});
+    let replacer = |policy| {
+        EncodeOptions::builder()
+            .on_non_finite(policy)
+            .replacer(Arc::new(|key: &str, value: &JsonValue, _: &[_]| {
+                Some(if key == "ratio" {
+                    number(f64::NAN)
+                } else {
+                    value.clone()
+                })
+            }))
+            .build()
+    };
+    let err = try_encode(value.clone(), Some(replacer(NonFinitePolicy::Error))).unwrap_err();
+    assert!(err.to_string().contains("ratio"), "{err}");
+    assert_eq!(
+        try_encode(value, Some(replacer(NonFinitePolicy::StringLiteral))).unwrap(),
+        "ratio: NaN\nname: x"
+    );
+}
+
+#[test]
+fn non_finite_policy_sees_converted_floats() {
+    assert!(try_encode(f64::NAN, None).is_err());
+    assert!(try_encode(JsonValue::from(f32::INFINITY), None).is_err());
+    assert!(try_encode(JsonValue::from(Number::F64(f64::NAN)), None).is_err());
+
+    let options = Some(non_finite_options(NonFinitePolicy::StringLiteral));
+    assert_eq!(
+        encode(JsonValue::from(vec![1.0, f64::INFINITY]), options.clone()),
+        "[2]: 1,Infinity"
+    );
+    assert_eq!(
+        encode(toon::toon_value!({"a": (f64::NAN)}), options),
+        "a: NaN"
+    );
+    assert_eq!(
+        encode(JsonValue::from(vec![1.0, f64::NEG_INFINITY]), None),
+        "[2]: 1,null"
+    );
+}
+
+#[test]
+fn quote_policy_quotes_strings_and_keys() {
+    let value = serde_json::json!({
+        "name": "Ada",
+        "a": {"b": "x"},
+        "tags": ["t", 1, null],
+        "rows": [{"id": 1, "label": "p"}],
+    });
+    let encode_with = |policy| {
+        encode(
+            value.clone(),
+            Some(
+                EncodeOptions::builder()
+                    .key_folding(KeyFoldingMode::Safe)
+                    .quote_strings(policy)
+                    .build(),
+            ),
+        )
+    };
+
+    assert_eq!(
+        encode_with(QuotePolicy::Minimal),
+        "name: Ada\na.b: x\ntags[3]: t,1,null\nrows[1]{id,label}:\n  1,p"
+    );
+    assert_eq!(
+        encode_with(QuotePolicy::AlwaysStrings),
+        "name: \"Ada\"\na.b: \"x\"\ntags[3]: \"t\",1,null\nrows[1]{id,label}:\n  1,\"p\""
+    );
+    // Quoted keys cannot fold: `"a.b"` would be a literal key.
+    let quoted = encode_with(QuotePolicy::AlwaysKeysAndStrings);
+    assert_eq!(
+        quoted,
+        "\"name\": \"Ada\"\n\"a\":\n  \"b\": \"x\"\n\"tags\"[3]: \"t\",1,null\n\"rows\"[1]{\"id\",\"label\"}:\n  1,\"p\""
+    );
+    let expand = DecodeOptions::builder()
+        .expand_paths(ExpandPathsMode::Safe)
+        .build();
+    for policy in [
+        QuotePolicy::AlwaysStrings,
+        QuotePolicy::AlwaysKeysAndStrings,
+    ] {
+        assert_eq!(
+            serde_json::Value::from(
+                try_decode(&encode_with(policy), Some(expand.clone())).unwrap()
+            ),
+            value
+        );
+    }
+
+    let (_, decisions) = encode_with_report(
+        serde_json::json!({"k": "v"}),
+        Some(
+            EncodeOptions::builder()
+                .quote_strings(QuotePolicy::AlwaysKeysAndStrings)
+                .build(),
+        ),
+    )
+    .unwrap();
+    let messages: Vec<_> = decisions.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        [
+            "key quoted: the quote policy quotes every key",
+            "string quoted: the quote policy quotes every string"
+        ]
+    );
+}
+
+#[test]
+fn tabular_thresholds_fall_back_to_list_items() {
+    let value = serde_json::json!({
+        "pair": [{"id": 1, "ok": true}, {"id": 2, "ok": false}],
+        "wide": [{"a": 1, "b": 2, "c": 3}, {"a": 4, "b": 5, "c": 6}, {"a": 7, "b": 8, "c": 9}],
+        "rows": [{"id": 1}, {"id": 2}, {"id": 3}],
+    });
+    let options = EncodeOptions::builder()
+        .tabular_min_rows(3)
+        .tabular_max_columns(2)
+        .build();
+    let encoded = encode(value.clone(), Some(options.clone()));
+    assert_eq!(
+        encoded,
+        "pair[2]:\n  - id: 1\n    ok: true\n  - id: 2\n    ok: false\n\
+         wide[3]:\n  - a: 1\n    b: 2\n    c: 3\n  - a: 4\n    b: 5\n    c: 6\n  - a: 7\n    b: 8\n    c: 9\n\
+         rows[3]{id}:\n  1\n  2\n  3"
+    );
+    assert_eq!(serde_json::Value::from(decode(&encoded, None)), value);
+
+    // Tables as the first field of a list item follow the same thresholds.
+    let nested = serde_json::json!([{"pair": [{"id": 1}, {"id": 2}], "n": 1}]);
+    assert_eq!(
+        encode(nested, Some(options.clone())),
+        "[1]:\n  - pair[2]:\n      - id: 1\n      - id: 2\n    n: 1"
+    );
+
+    let (_, decisions) = encode_with_report(value, Some(options)).unwrap();
+    let messages: Vec<_> = decisions.iter().map(|d| d.message.as_str()).collect();
+    assert!(messages.contains(&"not a table: 2 rows, fewer than tabular_min_rows (3)"));
+    assert!(messages.contains(&"not a table: 3 columns, more than tabular_max_columns (2)"));
+}
+
+#[test]
+fn tabular_false_writes_every_array_of_objects_as_list_items() {
+    let value = serde_json::json!({"rows": [{"id": 1, "ok": true}, {"id": 2, "ok": false}]});
+    let options = EncodeOptions::builder().tabular(false).build();
+    let encoded = encode(value.clone(), Some(options.clone()));
+    assert_eq!(
+        encoded,
+        "rows[2]:\n  - id: 1\n    ok: true\n  - id: 2\n    ok: false"
+    );
+    assert_eq!(serde_json::Value::from(decode(&encoded, None)), value);
+    assert_eq!(
+        encode(serde_json::json!([{"id": 1}]), Some(options.clone())),
+        "[1]:\n  - id: 1"
+    );
+
+    let (_, decisions) = encode_with_report(value, Some(options)).unwrap();
+    assert!(
+        decisions
+            .iter()
+            .any(|d| d.message == "not a table: tables are disabled")
+    );
+}
+
+#[test]
+fn max_line_width_wraps_inline_arrays() {
+    let value = serde_json::json!({
+        "tags": ["alpha", "beta", "gamma", "delta", "epsilon"],
+        "short": [1, 2],
+        "items": [{"xs": [1000, 2000, 3000, 4000], "k": 1}, [111, 222, 333, 444, 555]],
+    });
+    let encode_with = |markers| {
+        encode(
+            value.clone(),
+            Some(
+                EncodeOptions::builder()
+                    .max_line_width(18)
+                    .length_markers(markers)
+                    .build(),
+            ),
+        )
+    };
+
+    let encoded = encode_with(LengthMarkers::AllArrays);
+    assert_eq!(
+        encoded,
+        "tags[5]: alpha\n  beta,gamma,delta\n  epsilon\nshort[2]: 1,2\nitems[2]:\n\
+         \x20 - xs[4]: 1000\n      2000,3000\n      4000\n    k: 1\n\
+         \x20 - [5]: 111,222\n    333,444,555"
+    );
+    assert!(
+        encoded
+            .lines()
+            .all(|line| line.len() <= 18 || !line.contains(','))
+    );
+    assert_eq!(serde_json::Value::from(decode(&encoded, None)), value);
+    assert_eq!(
+        serde_json::Value::from(toon::decode_borrowed(&encoded).unwrap().into_owned()),
+        value
+    );
+
+    // Wrapped arrays keep `[N]` so the decoder knows when they end.
+    let unmarked = encode_with(LengthMarkers::TabularOnly);
+    assert!(unmarked.starts_with("tags[5]: alpha\n"));
+    assert!(unmarked.contains("\nshort[]: 1,2\n"));
+}
+
+#[test]
+fn tab_indentation_round_trips_in_lenient_mode() {
+    let value = serde_json::json!({
+        "a": {"b": [1, 2], "rows": [{"x": 1}]},
+        "l": [{"k": {"z": 1}}, [1]],
+    });
+    let encoded = encode(
+        value.clone(),
+        Some(
+            EncodeOptions::builder()
+                .indent_style(IndentStyle::Tabs)
+                .build(),
+        ),
+    );
+    assert_eq!(
+        encoded,
+        "a:\n\tb[2]: 1,2\n\trows[1]{x}:\n\t\t1\nl[2]:\n\t- k:\n\t\t\tz: 1\n\t- [1]: 1"
+    );
+
+    assert!(try_decode(&encoded, None).is_err());
+    // A tab is one level whatever the indent size.
+    for indent in [2, 4] {
+        let options = DecodeOptions::lenient()
+            .into_builder()
+            .indent(indent)
+            .build();
+        assert_eq!(
+            serde_json::Value::from(try_decode(&encoded, Some(options)).unwrap()),
+            value
+        );
+    }
+}
+
+#[test]
+fn crlf_line_endings_round_trip() {
+    let value = serde_json::json!({
+        "a": {"b": [1, 2]},
+        "rows": [{"x": 1}, {"x": 2}],
+        "l": [{"k": "v"}, [1]],
+    });
+    let options = EncodeOptions::builder()
+        .line_ending(LineEnding::CrLf)
+        .build();
+    let encoded = encode(value.clone(), Some(options.clone()));
+    assert_eq!(encoded, encode(value.clone(), None).replace('\n', "\r\n"));
+
+    let mut bytes = Vec::new();
+    toon::encode_to_writer(value.clone(), &mut bytes, Some(options)).unwrap();
+    assert_eq!(String::from_utf8(bytes).unwrap(), encoded);
+
+    assert_eq!(serde_json::Value::from(decode(&encoded, None)), value);
+    assert_eq!(
+        serde_json::Value::from(toon::decode_borrowed(&encoded).unwrap().into_owned()),
+        value
+    );
+}
+
+#[test]
+fn length_markers_option_controls_headers() {
+    let value: serde_json::Value = serde_json::json!({
+        "tags": ["a", "b"],
+        "rows": [{"id": 1}, {"id": 2}],
+        "items": [[1], {"x": 1}],
+        "empty": []
+    });
+    let encode_with = |markers| {
+        encode(
+            value.clone(),
+            Some(
+                EncodeOptions::builder()
+                    .length_markers(markers)
+                    .delimiter('|')
+                    .build(),
+            ),
+        )
+    };
+    assert_eq!(
+        encode_with(LengthMarkers::TabularOnly),
+        "tags[|]: a|b\nrows[2|]{id}:\n  1\n  2\nitems[|]:\n  - [|]: 1\n  - x: 1\nempty[|]:"
+    );
+    assert_eq!(
+        encode_with(LengthMarkers::Never),
+        "tags[|]: a|b\nrows[|]{id}:\n  1\n  2\nitems[|]:\n  - [|]: 1\n  - x: 1\nempty[|]:"
+    );
+    assert_eq!(
+        encode_with(LengthMarkers::AllArrays),
+        encode(
+            value.clone(),
+            Some(EncodeOptions::builder().delimiter('|').build())
+        )
+    );
+}
+
+#[test]
+fn encode_profiles_bundle_options() {
+    let value = serde_json::json!({
+        "server": {"http": {"port": 80}},
+        "tags": ["a", "b"],
+        "rows": [{"id": 1, "name": "x"}, {"id": 2, "name": "y"}],
+    });
+    let encode_with = |profile| encode(value.clone(), Some(EncodeOptions::profile(profile)));
+
+    assert_eq!(
+        encode_with(Profile::Compact),
+        "server.http.port: 80\ntags[2]: a,b\nrows[2]{id,name}:\n  1,x\n  2,y"
+    );
+    assert_eq!(encode_with(Profile::Readable), encode(value.clone(), None));
+    assert_eq!(
+        encode_with(Profile::Canonical),
+        "rows[2]{id,name}:\n  1,x\n  2,y\nserver:\n  http:\n    port: 80\ntags[2]: a,b"
+    );
+    assert_eq!(
+        encode_with(Profile::LlmOptimized),
+        "server.http.port: 80\ntags[2\t]: a\tb\nrows[2\t]{id\tname}:\n  1\tx\n  2\ty"
+    );
+
+    let readable_indent_4 = EncodeOptions::profile(Profile::Readable)
+        .into_builder()
+        .indent(4)
+        .build();
+    assert!(encode(value.clone(), Some(readable_indent_4)).contains("\n    http:"));
+    assert_eq!(
+        encode(value.clone(), Some(EncodeOptions::compact())),
+        encode_with(Profile::Compact)
+    );
+    assert_eq!(
+        encode(value.clone(), Some(EncodeOptions::llm_optimized())),
+        encode_with(Profile::LlmOptimized)
+    );
+
+    let expand = DecodeOptions::builder()
+        .expand_paths(ExpandPathsMode::Safe)
+        .build();
+    let decoded = try_decode(&encode_with(Profile::Compact), Some(expand)).unwrap();
+    assert_eq!(serde_json::Value::from(decoded), value);
+}
+
+#[test]
+fn compact_profile_roundtrips_through_the_default_decoder() {
+    let value = serde_json::json!({
+        "tags": ["a", "b"],
+        "nested": {"list": [1, 2], "empty": [], "mixed": [1, {"k": "v"}]},
+        "grid": [[1, 2], [3]],
+        "rows": [{"id": 1, "name": "x"}],
+    });
+    let toon = encode(value.clone(), Some(EncodeOptions::compact()));
+    assert_eq!(
+        serde_json::Value::from(try_decode(&toon, None).unwrap()),
+        value
+    );
+}
+
+// ============================================================================
+// MAP DECODE
+// ============================================================================
+
+#[derive(Debug, PartialEq, serde::Deserialize)]
+struct Service {
+    port: u16,
+    tags: Vec<String>,
+}
+
+#[test]
+fn decode_map_into_btree_map_of_structs() {
+    let input = "api:\n  port: 8080\n  tags[2]: public,v2\ndb:\n  port: 5432\n  tags[0]:";
+    let services: std::collections::BTreeMap<String, Service> =
+        toon::decode_map(input, None).unwrap();
+    assert_eq!(services.len(), 2);
+    assert_eq!(
+        services["api"],
+        Service {
+            port: 8080,
+            tags: vec!["public".to_string(), "v2".to_string()],
+        }
+    );
+    assert!(services["db"].tags.is_empty());
+}
+
+#[test]
+fn decode_map_into_hash_map_with_parsed_keys() {
+    let scores: std::collections::HashMap<u32, i64> =
+        toon::decode_map("\"1\": -5\n\"2\": 3", None).unwrap();
+    assert_eq!(scores[&1], -5);
+    assert_eq!(scores[&2], 3);
+}
+
+#[test]
+fn decode_map_reads_integers_beyond_the_f64_safe_range() {
+    let ids: std::collections::BTreeMap<String, u64> =
+        toon::decode_map("max: 18446744073709551615\nodd: 9007199254740993", None).unwrap();
+    assert_eq!(ids["max"], u64::MAX);
+    assert_eq!(ids["odd"], 9_007_199_254_740_993);
+}
+
+#[test]
+fn decode_map_errors_name_the_key() {
+    let err =
+        toon::decode_map::<std::collections::BTreeMap<String, u16>, _, _>("ok: 1\nbad: text", None)
+            .unwrap_err();
+    assert!(err.to_string().contains("'bad'"), "{err}");
+
+    assert!(
+        toon::decode_map::<std::collections::BTreeMap<String, u16>, _, _>("[2]: 1,2", None)
+            .is_err()
+    );
+}
+
+// ============================================================================
+// NORMALIZATION
+// ============================================================================
+
+fn object(entries: Vec<(&str, JsonValue)>) -> JsonValue {
+    JsonValue::Object(
+        entries
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect(),
+    )
+}
+
+#[test]
+fn normalize_sorts_dedups_and_nfc_folds_keys() {
+    let mut value = object(vec![
+        ("b", number(1.0)),
+        ("e\u{301}", number(2.0)),
+        ("a", object(vec![("z", number(-0.0)), ("y", number(3.0))])),
+        ("\u{e9}", number(4.0)),
+        ("b", number(5.0)),
+    ]);
+    value.normalize(toon::NormalizeOptions::default());
+
+    let expected = object(vec![
+        ("a", object(vec![("y", number(3.0)), ("z", number(0.0))])),
+        ("b", number(5.0)),
+        ("\u{e9}", number(4.0)),
+    ]);
+    assert_eq!(value, expected);
+    assert!(value.semantic_eq(&expected, Tolerance::EXACT));
+}
+
+#[test]
+fn normalize_steps_can_be_disabled() {
+    let mut value = object(vec![
+        ("b", number(-0.0)),
+        ("a", number(1.0)),
+        ("a", number(2.0)),
+    ]);
+    let original = value.clone();
+    value.normalize(toon::NormalizeOptions {
+        sort_keys: false,
+        dedup_keys: false,
+        nfc_keys: false,
+        fold_negative_zero: false,
+    });
+    assert_eq!(value, original);
+    let JsonValue::Object(entries) = &value else {
+        panic!("expected object");
+    };
+    assert!(entries[0].1.semantic_eq(&number(-0.0), Tolerance::EXACT));
+
+    value.normalize(toon::NormalizeOptions {
+        sort_keys: false,
+        ..Default::default()
+    });
+    assert_eq!(value, object(vec![("b", number(0.0)), ("a", number(2.0))]));
+}
+
+#[test]
+fn encode_canonical_agrees_for_semantically_equal_values() {
+    let options = DecodeOptions::builder()
+        .preserve_number_literals(true)
+        .build();
+    let written = [
+        "b[2]: 1.50,2\na:\n  y: -0\n  x: s\n  \"e\u{301}\": 1",
+        "a:\n  \"\u{e9}\": 1\n  x: s\n  y: 0\nb[2]: 1.5,2.0",
+        "a:\n    x: s\n    \"\u{e9}\": 1\n    y: 0.0\nb[2|]: 15e-1|2",
+    ];
+    let encoded: Vec<_> = written
+        .iter()
+        .map(|text| {
+            let options = options
+                .clone()
+                .into_builder()
+                .indent(if text.contains("    x") { 4 } else { 2 })
+                .build();
+            toon::encode_canonical(try_decode(text, Some(options)).unwrap())
+        })
+        .collect();
+    assert_eq!(
+        encoded[0],
+        "a:\n  x: s\n  y: 0\n  \"\u{e9}\": 1\nb[2]: 1.5,2"
+    );
+    assert!(encoded.iter().all(|text| *text == encoded[0]));
+}
+
+// ============================================================================
+// WALK
+// ============================================================================
+
+#[test]
+fn walk_mut_rewrites_and_removes_nodes() {
+    let mut value = decode(
+        "users[2]{name,email,note}:\n  Ada,ada@example.com,null\n  Bob,bob@example.com,hi",
+        None,
+    );
+    let mut visited = Vec::new();
+    value.walk_mut(|path, node| {
+        visited.push(path.to_vec());
+        if matches!(path.last(), Some(toon::options::PathSegment::Key(key)) if key == "email") {
+            *node = JsonValue::from("redacted");
+        }
+        if *node == JsonValue::Primitive(toon::StringOrNumberOrBoolOrNull::Null) {
+            return toon::WalkAction::Remove;
+        }
+        toon::WalkAction::Continue
+    });
+
+    assert_eq!(
+        encode(value, None),
+        "users[2]:\n  - name: Ada\n    email: redacted\n  - name: Bob\n    email: redacted\n    note: hi"
+    );
+    assert_eq!(visited.len(), 10);
+    assert_eq!(
+        visited[2],
+        vec![
+            toon::options::PathSegment::Key("users".to_string()),
+            toon::options::PathSegment::Index(0),
+        ]
+    );
+}
+
+#[test]
+fn walk_mut_skip_children_and_root_removal() {
+    let mut value = decode("keep:\n  a: 1\nvisit:\n  b: 2", None);
+    let mut leaves = 0;
+    value.walk_mut(|path, node| {
+        if matches!(path, [toon::options::PathSegment::Key(key)] if key == "keep") {
+            return toon::WalkAction::SkipChildren;
+        }
+        if matches!(node, JsonValue::Primitive(_)) {
+            leaves += 1;
+        }
+        toon::WalkAction::Continue
+    });
+    assert_eq!(leaves, 1);
+
+    value.walk_mut(|_, _| toon::WalkAction::Remove);
+    assert_eq!(
+        value,
+        JsonValue::Primitive(toon::StringOrNumberOrBoolOrNull::Null)
+    );
+}
+
+// ============================================================================
+// TRANSFORM
+// ============================================================================
+
+#[test]
+fn transform_patterns_match_wildcards_and_indices() {
+    use toon::options::PathSegment;
+    use toon::transform::PathPattern;
+
+    let key = |k: &str| PathSegment::Key(k.to_string());
+    let pattern: PathPattern = "users[*].email".parse().unwrap();
+    assert!(pattern.matches(&[key("users"), PathSegment::Index(3), key("email")]));
+    assert!(!pattern.matches(&[key("users"), PathSegment::Index(3)]));
+    assert!(!pattern.matches(&[key("users"), key("x"), key("email")]));
+
+    let pattern: PathPattern = "config.*.ports[1]".parse().unwrap();
+    assert!(pattern.matches(&[
+        key("config"),
+        key("web"),
+        key("ports"),
+        PathSegment::Index(1)
+    ]));
+    assert!(!pattern.matches(&[
+        key("config"),
+        key("web"),
+        key("ports"),
+        PathSegment::Index(0)
+    ]));
+
+    for invalid in ["", "a..b", "a[x]", "a[1", "a]b"] {
+        assert!(invalid.parse::<PathPattern>().is_err(), "{invalid}");
+    }
+}
+
+#[test]
+fn transform_applies_to_decoded_values() {
+    let transform = toon::transform::Transform::new()
+        .rule("users[*].email", |_| Some(JsonValue::from("***")))
+        .unwrap()
+        .rule("users[*].bio", |value| match value {
+            JsonValue::Primitive(toon::StringOrNumberOrBoolOrNull::String(text)) => {
+                Some(JsonValue::from(text.chars().take(5).collect::<String>()))
+            }
+            _ => Some(value.clone()),
+        })
+        .unwrap()
+        .rule("users[*].token", |_| None)
+        .unwrap();
+
+    let mut value = decode(
+        "users[2]{name,email,bio,token}:\n  Ada,ada@x.io,Mathematician,t1\n  Bob,bob@x.io,Hi,t2",
+        None,
+    );
+    transform.apply(&mut value);
+    assert_eq!(
+        encode(value.clone(), None),
+        "users[2]{name,email,bio}:\n  Ada,***,Mathe\n  Bob,***,Hi"
+    );
+
+    let original = decode(
+        "users[2]{name,email,bio,token}:\n  Ada,ada@x.io,Mathematician,t1\n  Bob,bob@x.io,Hi,t2",
+        None,
+    );
+    let options = EncodeOptions::builder()
+        .replacer(transform.into_replacer())
+        .build();
+    assert_eq!(encode(original, Some(options)), encode(value, None));
+}
+
+// ============================================================================
+// METRICS HOOKS
+// ============================================================================
+
+#[derive(Default)]
+struct Counters {
+    documents: AtomicUsize,
+    rows: AtomicUsize,
+    quoted: AtomicUsize,
+    warnings: AtomicUsize,
+}
+
+impl Metrics for Counters {
+    fn document_decoded(&self) {
+        self.documents.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn rows_parsed(&self, count: usize) {
+        self.rows.fetch_add(count, Ordering::Relaxed);
+    }
+
+    fn strings_quoted(&self, count: usize) {
+        self.quoted.fetch_add(count, Ordering::Relaxed);
+    }
+
+    fn warnings_emitted(&self, count: usize) {
+        self.warnings.fetch_add(count, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn metrics_count_decoded_documents_rows_and_warnings() {
+    let counters = Arc::new(Counters::default());
+    let options = DecodeOptions::builder().metrics(counters.clone()).build();
+
+    let input = "a[2]{x,y}:\n  1,2\n  3,4\nb[1]{z}:\n  yes";
+    try_decode(input, Some(options.clone())).unwrap();
+    try_decode_with_warnings(input, Some(options.clone())).unwrap();
+    assert!(try_decode("a:\n   b: 1", Some(options)).is_err());
+
+    assert_eq!(counters.documents.load(Ordering::Relaxed), 2);
+    assert_eq!(counters.rows.load(Ordering::Relaxed), 6);
+    assert_eq!(counters.warnings.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn metrics_count_quoted_strings() {
+    let counters = Arc::new(Counters::default());
+    let options = EncodeOptions::builder().metrics(counters.clone()).build();
+
+    let value = serde_json::json!({
+        "plain": "word",
+        "spaced": " padded",
+        "tags": ["a,b", "c", "true"],
+        "rows": [{"id": 1, "note": ""}, {"id": 2, "note": "ok"}],
+    });
+    encode(value, Some(options));
+
+    assert_eq!(counters.quoted.load(Ordering::Relaxed), 4);
+}
+
+// ============================================================================
+// ENCODE REPORT
+// ============================================================================
+
+#[test]
+fn encode_with_report_explains_each_decision() {
+    let value = serde_json::json!({
+        "config": {"server": 8080},
+        "config.server": "literal",
+        "first name": "Ada",
+        "db": {"primary": {"host": "h"}},
+        "users": [{"id": 1, "zip": "007"}, {"id": 2, "zip": "123"}],
+        "events": [{"id": 1}, {"id": 2, "tags": ["a"]}],
+        "mixed": [1, {"a": 1}],
+    });
+    let options = EncodeOptions::builder()
+        .key_folding(KeyFoldingMode::Safe)
+        .build();
+
+    let (toon, decisions) = encode_with_report(value.clone(), Some(options.clone())).unwrap();
+    assert_eq!(toon, encode(value, Some(options)));
+
+    let lines: Vec<(EncodeDecisionKind, String)> = decisions
+        .iter()
+        .map(|decision| (decision.kind, decision.to_string()))
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            (
+                EncodeDecisionKind::FoldSkipped,
+                "config: not folded: sibling key `config.server` already exists".to_string()
+            ),
+            (
+                EncodeDecisionKind::Quoted,
+                "first name: key quoted: not a valid bare key".to_string()
+            ),
+            (
+                EncodeDecisionKind::Folded,
+                "db: 3 single-key objects folded into `db.primary.host`".to_string()
+            ),
+            (
+                EncodeDecisionKind::Table,
+                "users: table of 2 rows x 2 columns: every row has the same primitive fields"
+                    .to_string()
+            ),
+            (
+                EncodeDecisionKind::Quoted,
+                "users[0].zip: string quoted: has a leading zero, so it looks like a number"
+                    .to_string()
+            ),
+            (
+                EncodeDecisionKind::Quoted,
+                "users[1].zip: string quoted: would read as a number, boolean or null".to_string()
+            ),
+            (
+                EncodeDecisionKind::ListArray,
+                "events: not a table: row [1] has field `tags`, unlike row [0]".to_string()
+            ),
+            (
+                EncodeDecisionKind::InlineArray,
+                "events[1].tags: inline array of 1 primitives".to_string()
+            ),
+            (
+                EncodeDecisionKind::ListArray,
+                "mixed: not a table or inline array: items mix primitives and objects".to_string()
+            ),
+        ]
+    );
+}
+
+// ============================================================================
+// INTEGER PRECISION
+// ============================================================================
+
+#[test]
+fn integers_decode_exactly_and_stay_integers_in_json() {
+    let toon = "id: 1\nbig: 18446744073709551615\nmin: -9223372036854775808\nratio: 1.5";
+    assert_eq!(
+        toon::toon_to_json(toon).unwrap(),
+        r#"{"id":1,"big":18446744073709551615,"min":-9223372036854775808,"ratio":1.5}"#
+    );
+    assert_eq!(
+        toon::json_to_toon(r#"{"big":9007199254740993}"#).unwrap(),
+        "big: 9007199254740993"
+    );
+    assert_eq!(
+        toon::decode_to_serde("n: 9007199254740993", None).unwrap()["n"],
+        serde_json::json!(9_007_199_254_740_993_u64)
+    );
+}
+
+#[test]
+fn stream_events_carry_exact_integers() {
+    let events = toon::decode_stream_sync(vec!["[2]: 7,-0.5".to_string()], None);
+    let values: Vec<_> = events
+        .into_iter()
+        .filter_map(|event| match event {
+            JsonStreamEvent::Primitive {
+                value: StringOrNumberOrBoolOrNull::Number(number),
+            } => Some(number),
+            _ => None,
+        })
+        .collect();
+    assert!(matches!(values[..], [Number::I64(7), Number::F64(_)]));
+}
+
+#[test]
+fn numbers_compare_by_value_across_variants() {
+    assert_eq!(Number::I64(1), Number::F64(1.0));
+    assert_eq!(Number::I64(5), Number::U64(5));
+    assert_ne!(
+        Number::U64(u64::MAX),
+        Number::F64(18_446_744_073_709_551_616.0)
+    );
+    assert_eq!(Number::F64(f64::NAN), Number::F64(f64::NAN));
+    assert_eq!(decode("n: 3", None), decode("n: 3.0", None));
+    assert_eq!(Number::from(u64::MAX).as_i64(), None);
+    assert_eq!(Number::F64(2.0).as_u64(), None);
+}
+
+// ============================================================================
+// NUMBER LITERAL PRESERVATION
+// ============================================================================
+
+fn preserve_literals() -> DecodeOptions {
+    DecodeOptions::builder()
+        .preserve_number_literals(true)
+        .build()
+}
+
+#[test]
+fn preserved_number_literals_round_trip_byte_for_byte() {
+    let input = "price: 1.50\nscale: 1e3\nratio: 0.1\nzero: -0\nlist[2]: 1.0,2.50\nrows[2]{x,y}:\n  1.10,2E-2\n  3,4.000\nitems[2]:\n  - 7.0\n  - a: 1.0";
+    let value = try_decode(input, Some(preserve_literals())).unwrap();
+    assert_eq!(encode(value, None), input);
+    assert_eq!(
+        encode(decode(input, None), None),
+        "price: 1.5\nscale: 1000\nratio: 0.1\nzero: 0\nlist[2]: 1,2.5\nrows[2]{x,y}:\n  1.1,0.02\n  3,4\nitems[2]:\n  - 7\n  - a: 1"
+    );
+}
+
+#[test]
+fn preserved_number_literals_behave_as_their_values() {
+    let value = try_decode("a: 1.50\nb: 12", Some(preserve_literals())).unwrap();
+    let JsonValue::Object(entries) = &value else {
+        panic!("expected object");
+    };
+    let JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(number)) = &entries[0].1 else {
+        panic!("expected number");
+    };
+    assert!(matches!(number, Number::Raw(text) if text == "1.50"));
+    assert_eq!(*number, Number::F64(1.5));
+    assert!(!number.is_integer());
+    assert_eq!(value, decode("a: 1.5\nb: 12", None));
+    assert_eq!(
+        serde_json::Value::from(value),
+        serde_json::json!({"a": 1.5, "b": 12})
+    );
+}
+
+#[test]
+fn preserved_number_literals_leave_strings_alone() {
+    let value = try_decode("zip: 007\nid: \"1.50\"", Some(preserve_literals())).unwrap();
+    assert_eq!(value, decode("zip: 007\nid: \"1.50\"", None));
+    let events = toon::decode_stream_sync(
+        vec!["n: 2.50".to_string()],
+        Some(toon::options::DecodeStreamOptions {
+            preserve_number_literals: Some(true),
+            ..Default::default()
+        }),
+    );
+    assert!(events.iter().any(|event| matches!(
+        event,
+        JsonStreamEvent::Primitive {
+            value: StringOrNumberOrBoolOrNull::Number(Number::Raw(text)),
+        } if text == "2.50"
+    )));
+}
+
+// ============================================================================
+// ENCODING TO WRITERS
+// ============================================================================
+
+#[test]
+fn encode_to_writer_matches_encode() {
+    let values = [
+        serde_json::json!({}),
+        serde_json::json!("plain"),
+        serde_json::json!({"users": [{"id": 1, "name": "Ada"}, {"id": 2, "name": "Bob"}], "tags": ["a", "b"]}),
+        serde_json::json!([1, {"a": [{"b": 1}]}, [2, 3]]),
+    ];
+    let options = EncodeOptions::builder().delimiter('|').build();
+    for value in values {
+        let expected = encode(value.clone(), Some(options.clone()));
+        let mut bytes = Vec::new();
+        toon::encode_to_writer(value.clone(), &mut bytes, Some(options.clone())).unwrap();
+        assert_eq!(String::from_utf8(bytes).unwrap(), expected);
+        let mut text = String::new();
+        toon::encode_to_fmt(value, &mut text, Some(options.clone())).unwrap();
+        assert_eq!(text, expected);
+    }
+}
+
+#[test]
+fn encode_to_writer_reports_write_errors() {
+    struct Full(usize);
+
+    impl std::io::Write for Full {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if self.0 == 0 {
+                return Err(std::io::Error::new(std::io::ErrorKind::WriteZero, "full"));
+            }
+            let written = buf.len().min(self.0);
+            self.0 -= written;
+            Ok(written)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let value = serde_json::json!({"a": 1, "b": 2, "c": 3});
+    let err = toon::encode_to_writer(value.clone(), Full(5), None).unwrap_err();
+    assert_eq!(err.to_string(), "Failed to write encoded output: full");
+    assert!(toon::encode_to_writer(value, Full(14), None).is_ok());
+}
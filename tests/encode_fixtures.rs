@@ -72,13 +72,12 @@ fn parse_encode_options(options: Option<&serde_json::Value>) -> Option<EncodeOpt
         .and_then(serde_json::Value::as_u64)
         .and_then(|value| usize::try_from(value).ok());
 
-    Some(EncodeOptions {
-        indent,
-        delimiter,
-        key_folding,
-        flatten_depth,
-        replacer: None,
-    })
+    let mut encode_options = EncodeOptions::default();
+    encode_options.indent = indent;
+    encode_options.delimiter = delimiter;
+    encode_options.key_folding = key_folding;
+    encode_options.flatten_depth = flatten_depth;
+    Some(encode_options)
 }
 
 fn fixture_root() -> PathBuf {
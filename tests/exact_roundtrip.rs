@@ -0,0 +1,122 @@
+//! Exact round-trip mode: `decode_exact(encode_exact(x)) == x` for any JSON value.
+
+use proptest::prelude::*;
+use serde_json::{Value, json};
+use toon::options::EncodeOptions;
+use toon::{decode, decode_exact, encode_exact};
+
+fn roundtrip(value: &Value) -> Value {
+    let toon = encode_exact(value, None).unwrap();
+    decode_exact(&toon, None).unwrap()
+}
+
+fn assert_exact(value: &Value) {
+    let back = roundtrip(value);
+    assert_eq!(&back, value);
+    assert_eq!(
+        serde_json::to_string(&back).unwrap(),
+        serde_json::to_string(value).unwrap()
+    );
+}
+
+#[test]
+fn plain_documents_need_no_annotations() {
+    let value = json!({"name": "Ada", "count": 3, "ratio": 0.5, "tags": ["a", "b"]});
+    let toon = encode_exact(&value, None).unwrap();
+    assert!(!toon.contains("$exact"));
+    assert_exact(&value);
+}
+
+#[test]
+fn integers_and_floats_keep_their_kind() {
+    assert_exact(&json!({"int": 1, "float": 1.0, "neg_zero": -0.0, "list": [2, 2.0]}));
+}
+
+#[test]
+fn large_integers_keep_full_precision() {
+    assert_exact(&json!({"u": u64::MAX, "i": i64::MIN, "edge": 9_007_199_254_740_993_u64}));
+}
+
+#[test]
+fn tabular_rows_keep_their_key_order() {
+    assert_exact(&json!([{"a": 1, "b": 2}, {"b": 3, "a": 4}]));
+}
+
+#[test]
+fn annotated_output_is_plain_toon() {
+    let value = json!({"ratio": 1.0});
+    let toon = encode_exact(&value, None).unwrap();
+    let plain: Value = decode(&toon, None).into();
+    assert_eq!(plain["$value"]["ratio"], json!(1));
+}
+
+#[test]
+fn values_shaped_like_the_envelope_are_wrapped() {
+    assert_exact(&json!({"$exact": [], "$value": 1}));
+}
+
+#[test]
+fn custom_indent_roundtrips() {
+    let value = json!({"outer": {"inner": 1.0}});
+    let options = EncodeOptions::builder().indent(4).build();
+    let toon = encode_exact(&value, Some(options)).unwrap();
+    let decode_options = toon::DecodeOptions::builder().indent(4).build();
+    assert_eq!(decode_exact(&toon, Some(decode_options)).unwrap(), value);
+}
+
+#[test]
+fn non_default_layouts_still_roundtrip() {
+    let value = json!({"outer": {"ratio": 1.0, "list": [1, 2.0]}, "rows": [{"b": 1, "a": 2.5}]});
+    for options in [
+        EncodeOptions::builder()
+            .indent_style(toon::IndentStyle::Tabs)
+            .build(),
+        EncodeOptions::compact(),
+        EncodeOptions::builder()
+            .length_markers(toon::LengthMarkers::Never)
+            .build(),
+    ] {
+        let toon = encode_exact(&value, Some(options)).unwrap();
+        assert!(toon.contains("$exact"), "{toon}");
+        assert_eq!(decode_exact(&toon, None).unwrap(), value, "{toon}");
+    }
+}
+
+#[test]
+fn rejected_options_are_an_error() {
+    let options = EncodeOptions::builder().max_depth(1).build();
+    assert!(encode_exact(&json!({"a": {"b": 1}}), Some(options)).is_err());
+}
+
+fn arbitrary_json() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(Value::from),
+        any::<u64>().prop_map(Value::from),
+        any::<f64>()
+            .prop_filter("finite", |f| f.is_finite())
+            .prop_map(Value::from),
+        ".*".prop_map(Value::String),
+    ];
+    leaf.prop_recursive(4, 32, 6, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..6).prop_map(Value::Array),
+            prop::collection::vec((".*", inner), 0..6)
+                .prop_map(|entries| Value::Object(entries.into_iter().collect())),
+        ]
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(200))]
+
+    #[test]
+    fn exact_roundtrip_arbitrary_values(value in arbitrary_json()) {
+        let back = roundtrip(&value);
+        prop_assert_eq!(
+            serde_json::to_string(&back).unwrap(),
+            serde_json::to_string(&value).unwrap()
+        );
+    }
+}
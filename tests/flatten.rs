@@ -0,0 +1,96 @@
+use serde_json::json;
+use toon::error::ToonError;
+use toon::options::ExpandPathsMode;
+use toon::{DecodeOptions, JsonValue, try_decode};
+
+fn value(json: serde_json::Value) -> JsonValue {
+    JsonValue::from(json)
+}
+
+#[test]
+fn flatten_lists_every_leaf_by_path() {
+    let flat = value(json!({
+        "users": [{"id": 1, "tags": ["a", "b"]}],
+        "meta": {"empty": {}, "none": []},
+    }))
+    .flatten();
+
+    assert_eq!(
+        flat,
+        value(json!({
+            "users.0.id": 1,
+            "users.0.tags.0": "a",
+            "users.0.tags.1": "b",
+            "meta.empty": {},
+            "meta.none": [],
+        }))
+    );
+}
+
+#[test]
+fn flatten_quotes_keys_that_are_not_identifiers() {
+    let original = value(json!({"a b": {"x.y": 1, "0": 2, "q\"": 3}}));
+    let flat = original.flatten();
+
+    let JsonValue::Object(entries) = &flat else {
+        panic!("expected object");
+    };
+    let keys: Vec<&str> = entries.iter().map(|(key, _)| key.as_str()).collect();
+    assert_eq!(
+        keys,
+        ["\"a b\".\"x.y\"", "\"a b\".\"0\"", "\"a b\".\"q\\\"\""]
+    );
+    assert_eq!(flat.unflatten().unwrap(), original);
+}
+
+#[test]
+fn root_arrays_and_leaves_round_trip() {
+    for original in [
+        value(json!([{"a": 1}, [2, 3], "x"])),
+        value(json!("plain")),
+        value(json!([])),
+        value(json!({})),
+    ] {
+        assert_eq!(original.flatten().unflatten().unwrap(), original);
+    }
+    assert_eq!(value(json!(7)).flatten(), value(json!(7)));
+}
+
+#[test]
+fn identifier_listing_expands_with_indexed_paths() {
+    let original = value(json!({"rows": [{"id": 1}, {"id": 2}], "owner": {"name": "ops"}}));
+    let listing = toon::encode(original.flatten(), None);
+
+    let expanded = try_decode(
+        &listing,
+        Some(
+            DecodeOptions::builder()
+                .expand_paths(ExpandPathsMode::Indexed)
+                .build(),
+        ),
+    )
+    .unwrap();
+    assert_eq!(expanded, original);
+}
+
+#[test]
+fn unflatten_rejects_gaps_and_collisions() {
+    let err = value(json!({"items.1": "x"})).unflatten().unwrap_err();
+    assert!(
+        matches!(&err, ToonError::InvalidValue { path, message }
+            if path == "items.1" && message.contains("skips positions")),
+        "{err}"
+    );
+
+    let err = value(json!({"a": 1, "a.b": 2})).unflatten().unwrap_err();
+    assert!(
+        matches!(&err, ToonError::InvalidValue { path, .. } if path == "a.b"),
+        "{err}"
+    );
+
+    let err = value(json!({"a..b": 1})).unflatten().unwrap_err();
+    assert!(err.to_string().contains("empty segment"), "{err}");
+
+    let err = value(json!({"\"a\"b": 1})).unflatten().unwrap_err();
+    assert!(err.to_string().contains("expected `.`"), "{err}");
+}
@@ -0,0 +1,89 @@
+use toon::{FormatOptions, format, try_decode};
+
+fn sorted() -> FormatOptions {
+    FormatOptions {
+        sort_keys: true,
+        ..FormatOptions::default()
+    }
+}
+
+#[test]
+fn reindents_and_trims_whitespace() {
+    let text = "a:\r\n    b:   1   \r\n    c:\r\n        d: \"x\"\r\n";
+    assert_eq!(
+        format(text, FormatOptions::default()).unwrap(),
+        "a:\n  b: 1\n  c:\n    d: \"x\"\n"
+    );
+}
+
+#[test]
+fn keeps_comments_quoting_and_number_spelling() {
+    let text = "# totals\nprice: 1.50\ncode: \"007\"\n\n\n\n# tail\n";
+    assert_eq!(
+        format(text, FormatOptions::default()).unwrap(),
+        "# totals\nprice: 1.50\ncode: \"007\"\n\n# tail\n"
+    );
+}
+
+#[test]
+fn list_items_nest_their_fields() {
+    let text = "items[2]:\n    - id: 1\n        tags[2]:   a,b\n        meta:\n            x: 1\n    - - y\n";
+    let formatted = format(text, FormatOptions::default()).unwrap();
+    assert_eq!(
+        formatted,
+        "items[2]:\n  - id: 1\n    tags[2]: a,b\n    meta:\n      x: 1\n  - - y\n"
+    );
+    assert_eq!(
+        try_decode(&formatted, None).unwrap(),
+        try_decode(text, Some(toon::DecodeOptions::builder().indent(4).build())).unwrap()
+    );
+
+    let text = "- rows[1]{a,b}:\n    1,2\n  note: ok\n";
+    assert_eq!(format(text, FormatOptions::default()).unwrap(), text);
+}
+
+#[test]
+fn normalizes_delimiters_quoting_where_needed() {
+    let text = "rows[2|]{id|\"a,b\"}:\n  1|x,y\n  2|\"z\"\ntags[2\t]: p\tq,r\n";
+    let options = FormatOptions {
+        delimiter: Some(','),
+        ..FormatOptions::default()
+    };
+    let formatted = format(text, options).unwrap();
+    assert_eq!(
+        formatted,
+        "rows[2]{id,\"a,b\"}:\n  1,\"x,y\"\n  2,\"z\"\ntags[2]: p,\"q,r\"\n"
+    );
+    assert_eq!(
+        try_decode(&formatted, None).unwrap(),
+        try_decode(text, None).unwrap()
+    );
+}
+
+#[test]
+fn sorts_keys_with_their_comments() {
+    let text =
+        "b: 1\n# about a\na:\n  z: 1\n  y: 2\nitems[2]:\n  - k: 1\n    c: 3\n    b: 2\n  - 4\n";
+    assert_eq!(
+        format(text, sorted()).unwrap(),
+        "# about a\na:\n  y: 2\n  z: 1\nb: 1\nitems[2]:\n  - k: 1\n    b: 2\n    c: 3\n  - 4\n"
+    );
+}
+
+#[test]
+fn formatting_is_idempotent() {
+    for text in [
+        "a:   1\n\n\nb:\n      c: 2",
+        "\n\nx[2]{a,b}:\n   1,2\n   3,4\n\n",
+        "- a: 1\n  b:\n    c: 2\n- 3\n",
+    ] {
+        let once = format(text, sorted()).unwrap();
+        assert_eq!(format(&once, sorted()).unwrap(), once);
+    }
+}
+
+#[test]
+fn reports_lines_that_do_not_parse() {
+    let err = format("a: 1\nb: \"open\n", FormatOptions::default()).unwrap_err();
+    assert_eq!(err.decode_error().unwrap().line, 2);
+}
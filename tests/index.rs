@@ -0,0 +1,60 @@
+use toon::{JsonValue, toon_value};
+
+#[test]
+fn index_reads_with_null_for_missing() {
+    let value = toon::decode(
+        "users[2]{name,age}:\n  Ada,36\n  Bob,17\nname: x\nname: y",
+        None,
+    );
+    assert_eq!(value["users"][1]["name"], "Bob".into());
+    assert_eq!(value["name"], "y".into());
+    let key = String::from("users");
+    assert_eq!(value[&key][0]["age"], 36.into());
+    for missing in [
+        &value["nope"],
+        &value["users"][9],
+        &value["users"]["name"],
+        &value["name"][0],
+        &value[0],
+    ] {
+        assert_eq!(*missing, toon_value!(null));
+    }
+}
+
+#[test]
+fn get_and_get_mut() {
+    let mut value = toon_value!({"list": [1, 2], "obj": {"k": true}});
+    assert_eq!(
+        value.get("list").and_then(|list| list.get(1)),
+        Some(&2.into())
+    );
+    assert_eq!(value.get("obj").and_then(|obj| obj.get(0)), None);
+    assert_eq!(value.get("missing"), None);
+    *value.get_mut("obj").unwrap().get_mut("k").unwrap() = false.into();
+    assert!(value.get_mut("list").unwrap().get_mut(2).is_none());
+    assert_eq!(value["obj"]["k"], false.into());
+}
+
+#[test]
+fn index_mut_inserts_keys_and_replaces_items() {
+    let mut value = JsonValue::Object(Vec::new());
+    value["a"]["b"] = 1.into();
+    value["list"] = toon_value!([0, 0]);
+    value["list"][1] = "x".into();
+    value["a"]["b"] = 2.into();
+    assert_eq!(value, toon_value!({"a": {"b": 2}, "list": [0, "x"]}));
+}
+
+#[test]
+#[should_panic(expected = "cannot access index 2 of JSON array of length 2")]
+fn index_mut_out_of_range_panics() {
+    let mut value = toon_value!([1, 2]);
+    value[2] = 3.into();
+}
+
+#[test]
+#[should_panic(expected = "cannot access key \"k\" in JSON number")]
+fn index_mut_into_primitive_panics() {
+    let mut value = toon_value!(1);
+    value["k"] = 3.into();
+}
@@ -1,7 +1,8 @@
 use toon::cli::json_stream::json_stream_from_events;
 use toon::cli::json_stringify::json_stringify_lines;
+use toon::decode::decoders::decode_stream_sync_with_lines;
 use toon::{
-    JsonStreamEvent, JsonValue, StringOrNumberOrBoolOrNull, decode_stream_sync, encode,
+    JsonStreamEvent, JsonValue, Number, StringOrNumberOrBoolOrNull, decode_stream_sync, encode,
     encode_stream_events,
 };
 
@@ -10,7 +11,7 @@ fn json_stringify_lines_matches_serde_for_compact() {
     let value = JsonValue::Object(vec![
         (
             "a".to_string(),
-            JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(1.0)),
+            JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(Number::I64(1))),
         ),
         (
             "b".to_string(),
@@ -32,7 +33,7 @@ fn json_stringify_lines_matches_serde_for_compact() {
 fn json_stringify_lines_matches_serde_for_pretty() {
     let value = JsonValue::Array(vec![
         JsonValue::Primitive(StringOrNumberOrBoolOrNull::Null),
-        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(3.5)),
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(Number::F64(3.5))),
         JsonValue::Object(vec![(
             "key".to_string(),
             JsonValue::Primitive(StringOrNumberOrBoolOrNull::String("value".to_string())),
@@ -55,7 +56,7 @@ fn json_stream_from_events_matches_stringify() {
             was_quoted: false,
         },
         JsonStreamEvent::Primitive {
-            value: StringOrNumberOrBoolOrNull::Number(1.0),
+            value: StringOrNumberOrBoolOrNull::Number(Number::I64(1)),
         },
         JsonStreamEvent::Key {
             key: "b".to_string(),
@@ -77,7 +78,7 @@ fn json_stream_from_events_matches_stringify() {
     let value = JsonValue::Object(vec![
         (
             "a".to_string(),
-            JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(1.0)),
+            JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(Number::I64(1))),
         ),
         (
             "b".to_string(),
@@ -104,7 +105,8 @@ fn serde_value(value: &JsonValue) -> serde_json::Value {
         JsonValue::Primitive(primitive) => match primitive {
             StringOrNumberOrBoolOrNull::Null => serde_json::Value::Null,
             StringOrNumberOrBoolOrNull::Bool(value) => serde_json::Value::Bool(*value),
-            StringOrNumberOrBoolOrNull::Number(value) => serde_json::Number::from_f64(*value)
+            StringOrNumberOrBoolOrNull::Number(value) => value
+                .to_json_number()
                 .map_or(serde_json::Value::Null, serde_json::Value::Number),
             StringOrNumberOrBoolOrNull::String(value) => serde_json::Value::String(value.clone()),
         },
@@ -123,13 +125,13 @@ fn serde_value(value: &JsonValue) -> serde_json::Value {
 
 #[test]
 fn encode_stream_events_primitive() {
-    let value = JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(42.0));
+    let value = JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(Number::I64(42)));
     let events = encode_stream_events(value, None);
     assert_eq!(events.len(), 1);
     assert_eq!(
         events[0],
         JsonStreamEvent::Primitive {
-            value: StringOrNumberOrBoolOrNull::Number(42.0)
+            value: StringOrNumberOrBoolOrNull::Number(Number::I64(42))
         }
     );
 }
@@ -143,7 +145,7 @@ fn encode_stream_events_simple_object() {
         ),
         (
             "age".to_string(),
-            JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(30.0)),
+            JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(Number::I64(30))),
         ),
     ]);
 
@@ -174,7 +176,7 @@ fn encode_stream_events_simple_object() {
     assert_eq!(
         events[4],
         JsonStreamEvent::Primitive {
-            value: StringOrNumberOrBoolOrNull::Number(30.0)
+            value: StringOrNumberOrBoolOrNull::Number(Number::I64(30))
         }
     );
     assert_eq!(events[5], JsonStreamEvent::EndObject);
@@ -183,9 +185,9 @@ fn encode_stream_events_simple_object() {
 #[test]
 fn encode_stream_events_array() {
     let value = JsonValue::Array(vec![
-        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(1.0)),
-        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(2.0)),
-        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(3.0)),
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(Number::I64(1))),
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(Number::I64(2))),
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(Number::I64(3))),
     ]);
 
     let events = encode_stream_events(value, None);
@@ -195,19 +197,19 @@ fn encode_stream_events_array() {
     assert_eq!(
         events[1],
         JsonStreamEvent::Primitive {
-            value: StringOrNumberOrBoolOrNull::Number(1.0)
+            value: StringOrNumberOrBoolOrNull::Number(Number::I64(1))
         }
     );
     assert_eq!(
         events[2],
         JsonStreamEvent::Primitive {
-            value: StringOrNumberOrBoolOrNull::Number(2.0)
+            value: StringOrNumberOrBoolOrNull::Number(Number::I64(2))
         }
     );
     assert_eq!(
         events[3],
         JsonStreamEvent::Primitive {
-            value: StringOrNumberOrBoolOrNull::Number(3.0)
+            value: StringOrNumberOrBoolOrNull::Number(Number::I64(3))
         }
     );
     assert_eq!(events[4], JsonStreamEvent::EndArray);
@@ -218,7 +220,7 @@ fn encode_stream_events_quoted_key() {
     // Keys with special characters should have was_quoted=true
     let value = JsonValue::Object(vec![(
         "my-key".to_string(),
-        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(1.0)),
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(Number::I64(1))),
     )]);
 
     let events = encode_stream_events(value, None);
@@ -245,7 +247,7 @@ fn encode_stream_events_roundtrip_with_decode() {
                 JsonValue::Object(vec![
                     (
                         "id".to_string(),
-                        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(1.0)),
+                        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(Number::I64(1))),
                     ),
                     (
                         "name".to_string(),
@@ -257,7 +259,7 @@ fn encode_stream_events_roundtrip_with_decode() {
                 JsonValue::Object(vec![
                     (
                         "id".to_string(),
-                        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(2.0)),
+                        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(Number::I64(2))),
                     ),
                     (
                         "name".to_string(),
@@ -268,7 +270,7 @@ fn encode_stream_events_roundtrip_with_decode() {
         ),
         (
             "count".to_string(),
-            JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(2.0)),
+            JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(Number::I64(2))),
         ),
     ]);
 
@@ -291,3 +293,21 @@ fn encode_stream_events_roundtrip_with_decode() {
 
     assert_eq!(decode_json, encode_json);
 }
+
+#[test]
+fn decode_stream_with_lines_tags_each_event() {
+    let toon = "rows[2]{id}:\n  1\n  2\nowner:\n  name: ops";
+    let located = decode_stream_sync_with_lines(toon.lines().map(str::to_string), None).unwrap();
+
+    let lines: Vec<usize> = located.iter().map(|(line, _)| *line).collect();
+    assert_eq!(
+        lines,
+        [1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 4, 4, 5, 5, 5, 5]
+    );
+
+    let events: Vec<JsonStreamEvent> = located.into_iter().map(|(_, event)| event).collect();
+    assert_eq!(
+        events,
+        decode_stream_sync(toon.lines().map(str::to_string), None)
+    );
+}
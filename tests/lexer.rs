@@ -0,0 +1,126 @@
+use toon::lexer::{TokenKind, tokenize};
+
+const DOCUMENTS: &[&str] = &[
+    "",
+    "42",
+    "name: api\nport: 8080\n",
+    "# settings\nname:   api  \n\n# nested\nserver:\n  host: \"local host\"\n  port: 8080",
+    "a: 1\r\nb:\r\n  c: \"x\"\r\n",
+    "users[2|]{id|name}:\n  1|Alice\n  2|\"Bob, Jr\"\n",
+    "tags[3\t]: a\tb\tc\n",
+    "items[2]:\n  - id: 1\n    rows[1]{x,y}:\n      1,2\n    note: ok\n  - plain\n",
+    "\"quoted key\": 1\n\"a:b\"[2]: x,y\nempty[0]:\nlist[1]:\n  -\n",
+    "bad: \"open\nnext: ünï\n",
+];
+
+fn lex(text: &str) -> Vec<(TokenKind, &str)> {
+    tokenize(text)
+        .map(|token| (token.kind, token.text(text)))
+        .collect()
+}
+
+#[test]
+fn spans_cover_input() {
+    for text in DOCUMENTS {
+        let mut end = 0;
+        for token in tokenize(text) {
+            assert_eq!(token.span.start, end, "{text:?}");
+            assert!(token.span.end > token.span.start);
+            end = token.span.end;
+        }
+        assert_eq!(end, text.len());
+    }
+}
+
+#[test]
+fn key_values_and_literals() {
+    use TokenKind::{Bool, Colon, Indent, Key, Newline, Null, Number, String, Text, Whitespace};
+    assert_eq!(
+        lex("a: 1\n  \"b c\": \"x\"\nd: true\ne: null\nf: hi there\r\n"),
+        [
+            (Key, "a"),
+            (Colon, ":"),
+            (Whitespace, " "),
+            (Number, "1"),
+            (Newline, "\n"),
+            (Indent, "  "),
+            (Key, "\"b c\""),
+            (Colon, ":"),
+            (Whitespace, " "),
+            (String, "\"x\""),
+            (Newline, "\n"),
+            (Key, "d"),
+            (Colon, ":"),
+            (Whitespace, " "),
+            (Bool, "true"),
+            (Newline, "\n"),
+            (Key, "e"),
+            (Colon, ":"),
+            (Whitespace, " "),
+            (Null, "null"),
+            (Newline, "\n"),
+            (Key, "f"),
+            (Colon, ":"),
+            (Whitespace, " "),
+            (Text, "hi there"),
+            (Newline, "\r\n"),
+        ]
+    );
+}
+
+#[test]
+fn tabular_headers_and_rows() {
+    use TokenKind::{
+        CloseBrace, CloseBracket, Colon, Comment, Delimiter, Indent, Key, Length, ListMarker,
+        Newline, Number, OpenBrace, OpenBracket, String, Text, Whitespace,
+    };
+    assert_eq!(
+        lex("rows[2]{id,\"a b\"}:\n  1,x\n  # 2,y\nnext[1]:\n  - z\n"),
+        [
+            (Key, "rows"),
+            (OpenBracket, "["),
+            (Length, "2"),
+            (CloseBracket, "]"),
+            (OpenBrace, "{"),
+            (Key, "id"),
+            (Delimiter, ","),
+            (Key, "\"a b\""),
+            (CloseBrace, "}"),
+            (Colon, ":"),
+            (Newline, "\n"),
+            (Indent, "  "),
+            (Number, "1"),
+            (Delimiter, ","),
+            (Text, "x"),
+            (Newline, "\n"),
+            (Indent, "  "),
+            (Text, "# 2"),
+            (Delimiter, ","),
+            (Text, "y"),
+            (Newline, "\n"),
+            (Key, "next"),
+            (OpenBracket, "["),
+            (Length, "1"),
+            (CloseBracket, "]"),
+            (Colon, ":"),
+            (Newline, "\n"),
+            (Indent, "  "),
+            (ListMarker, "-"),
+            (Whitespace, " "),
+            (Text, "z"),
+            (Newline, "\n"),
+        ]
+    );
+    assert_eq!(lex("# note")[0], (Comment, "# note"));
+    assert_eq!(
+        lex("x[1]: \"a,b\"")[4..],
+        [(Colon, ":"), (Whitespace, " "), (String, "\"a,b\"")]
+    );
+}
+
+#[test]
+fn malformed_text_is_an_error_token() {
+    let tokens = lex("bad: \"open\nok: 1\n");
+    assert_eq!(tokens[3], (TokenKind::Error, "\"open"));
+    assert_eq!(tokens[5], (TokenKind::Key, "ok"));
+}
@@ -0,0 +1,111 @@
+//! Resource limits for untrusted input.
+
+use toon::options::{DecodeLimits, DecodeOptions};
+use toon::try_decode;
+
+fn with_limits(limits: DecodeLimits) -> DecodeOptions {
+    DecodeOptions::builder().limits(limits).build()
+}
+
+fn limit_error(input: &str, limits: DecodeLimits) -> toon::error::DecodeError {
+    let err = try_decode(input, Some(with_limits(limits))).unwrap_err();
+    let details = err.decode_error().expect("decode error").clone();
+    assert_eq!(
+        details.kind,
+        toon::error::DecodeErrorKind::LimitExceeded,
+        "{err}"
+    );
+    details
+}
+
+#[test]
+fn limits_reject_documents_past_each_bound() {
+    let depth = DecodeLimits {
+        max_depth: Some(2),
+        ..Default::default()
+    };
+    assert!(try_decode("a:\n  b: 1\nc[1]: x", Some(with_limits(depth))).is_ok());
+    let err = limit_error("a:\n  b:\n    c: 1", depth);
+    assert_eq!(err.line, 2);
+    assert_eq!(err.message, "Nesting depth exceeds the limit of 2");
+
+    let nodes = DecodeLimits {
+        max_nodes: Some(5),
+        ..Default::default()
+    };
+    assert!(try_decode("a: 1\nb[2]: x,y", Some(with_limits(nodes))).is_ok());
+    let err = limit_error("a: 1\nb[2]: x,y\nc: 2", nodes);
+    assert_eq!(err.line, 3);
+    assert_eq!(err.message, "Document exceeds the limit of 5 values");
+
+    let strings = DecodeLimits {
+        max_string_bytes: Some(5),
+        ..Default::default()
+    };
+    assert!(try_decode("name: hello\nn: 123456789", Some(with_limits(strings))).is_ok());
+    let err = limit_error("a: 1\nname: \"hello!\"", strings);
+    assert_eq!(err.line, 2);
+    assert_eq!(err.message, "String of 6 bytes exceeds the limit of 5");
+    let err = limit_error("rows[1]{id,longname}:\n  1,x", strings);
+    assert_eq!(err.message, "Key of 8 bytes exceeds the limit of 5");
+}
+
+#[test]
+fn limits_reject_declared_lengths_before_reading_items() {
+    let limits = DecodeLimits {
+        max_array_length: Some(2),
+        ..Default::default()
+    };
+    assert!(try_decode("items[2]: x,y", Some(with_limits(limits))).is_ok());
+    let err = limit_error("a: 1\nitems[999999999]:\n  - x", limits);
+    assert_eq!(err.line, 2);
+    assert_eq!(
+        err.message,
+        "Declared array length 999999999 exceeds the limit of 2"
+    );
+    // Lengths counted from the items are not declared.
+    let lenient = DecodeOptions::lenient()
+        .into_builder()
+        .limits(DecodeLimits {
+            max_array_length: Some(1),
+            ..Default::default()
+        })
+        .build();
+    assert!(try_decode("items[]: a,b,c", Some(lenient)).is_ok());
+}
+
+#[test]
+fn limits_stop_deep_nesting_early() {
+    let input: String = (0..10_000)
+        .map(|depth| "  ".repeat(depth) + "k:\n")
+        .collect();
+    let limits = DecodeLimits {
+        max_depth: Some(64),
+        ..Default::default()
+    };
+    assert_eq!(limit_error(&input, limits).line, 64);
+}
+
+#[test]
+fn limits_apply_to_push_parsing_and_recovery() {
+    let limits = DecodeLimits {
+        max_nodes: Some(3),
+        ..Default::default()
+    };
+    let input = "a: 1\nb: 2\nc: 3";
+    let expected = try_decode(input, Some(with_limits(limits))).unwrap_err();
+
+    let mut parser = toon::ToonParser::new(Some(toon::DecodeStreamOptions {
+        limits: Some(limits),
+        ..Default::default()
+    }));
+    let err = parser
+        .feed(input.as_bytes())
+        .and_then(|()| parser.finish())
+        .unwrap_err();
+    assert_eq!(err.to_string(), expected.to_string());
+
+    let (_, errors) = toon::decode_all_errors(input, Some(with_limits(limits)));
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].to_string(), expected.to_string());
+}
@@ -0,0 +1,117 @@
+use toon::lint::{Finding, LintOptions, Rule, Severity, lint};
+
+fn rules(findings: &[Finding]) -> Vec<(usize, &'static str)> {
+    findings
+        .iter()
+        .map(|finding| (finding.line, finding.rule.id()))
+        .collect()
+}
+
+#[test]
+fn clean_document_has_no_findings() {
+    let text =
+        "# users\nusers[2]{id,name}:\n  1,Ada\n  2,Bob\nowner:\n  name: ops\n  teams[2]: a,b\n";
+    assert!(lint(text, &LintOptions::default()).is_empty());
+}
+
+#[test]
+fn inconsistent_indentation() {
+    let text = "a:\n  b: 1\n   c: 2\nd:\n      e: 1\n  f: 1\n";
+    let findings = lint(text, &LintOptions::default());
+    let indents: Vec<_> = findings
+        .iter()
+        .filter(|finding| finding.rule == Rule::InconsistentIndent)
+        .map(|finding| (finding.line, finding.columns.clone()))
+        .collect();
+    assert_eq!(indents, [(3, 0..3), (5, 0..6)]);
+    assert!(findings.iter().any(|finding| finding.rule == Rule::Invalid));
+
+    let text = "items[1]:\n  - a:\n      b: 1\n    c: 2\n";
+    assert!(lint(text, &LintOptions::default()).is_empty());
+}
+
+#[test]
+fn mixed_delimiters() {
+    let text = "a[2]: 1,2\nb[2|]: 1|2\nc[1]:\n  - x\nd[2|]{x|y}:\n  1|2\n  3|4\n";
+    let findings = lint(text, &LintOptions::default());
+    assert_eq!(
+        rules(&findings),
+        [(2, "mixed-delimiters"), (5, "mixed-delimiters")]
+    );
+    assert_eq!(
+        findings[0].message,
+        "Array uses `|` while the array on line 1 uses `,`"
+    );
+}
+
+#[test]
+fn foldable_keys_report_the_whole_chain_once() {
+    let text = "server:\n  http:\n    port: 80\nmeta:\n  a: 1\n  b: 2\n";
+    let findings = lint(text, &LintOptions::default());
+    assert_eq!(rules(&findings), [(1, "foldable-key")]);
+    assert_eq!(
+        findings[0].message,
+        "`server` holds a single key; with key folding it reads `server.http.port`"
+    );
+    assert_eq!(findings[0].severity, Severity::Info);
+}
+
+#[test]
+fn lists_of_uniform_objects_could_be_tabular() {
+    let text = "users[2]:\n  - id: 1\n    name: Ada\n  - name: Bob\n    id: 2\nmixed[2]:\n  - id: 1\n  - id: 2\n    tags[1]: x\n";
+    let findings = lint(text, &LintOptions::default());
+    assert_eq!(rules(&findings), [(1, "could-be-tabular")]);
+    assert_eq!(
+        findings[0].message,
+        "2 objects with the same primitive fields fit a table: `users[2]{id,name}:`"
+    );
+}
+
+#[test]
+fn suspicious_and_redundant_quotes() {
+    let text =
+        "\"name\": \"api\"\nport: \"8080\"\nflags[3]: \"true\",\"a,b\",\"x\"\nurl: \"http://x\"\n";
+    let findings = lint(text, &LintOptions::default());
+    assert_eq!(
+        rules(&findings),
+        [
+            (1, "redundant-quotes"),
+            (1, "redundant-quotes"),
+            (2, "quoted-literal"),
+            (3, "quoted-literal"),
+            (3, "redundant-quotes"),
+        ]
+    );
+    assert_eq!(findings[1].columns, 8..13);
+    assert_eq!(findings[3].columns, 10..16);
+    assert_eq!(findings[4].columns, 23..26);
+}
+
+#[test]
+fn invalid_documents() {
+    let findings = lint("a: 1\nb 2\n", &LintOptions::default());
+    assert_eq!(rules(&findings), [(2, "invalid")]);
+    assert_eq!(findings[0].severity, Severity::Error);
+    assert_eq!(
+        findings[0].to_string(),
+        "2:1: error[invalid]: Missing colon after key"
+    );
+
+    let findings = lint("a: \"open\n", &LintOptions::default());
+    assert_eq!(rules(&findings), [(1, "invalid")]);
+}
+
+#[test]
+fn rules_can_be_reconfigured() {
+    let text = "a: \"x\"\nb:\n  c: 1\n";
+    let options = LintOptions::default()
+        .with("redundant-quotes".parse().unwrap(), Some(Severity::Error))
+        .with(Rule::FoldableKey, None);
+    let findings = lint(text, &options);
+    assert_eq!(rules(&findings), [(1, "redundant-quotes")]);
+    assert_eq!(findings[0].severity, Severity::Error);
+    assert!("no-such-rule".parse::<Rule>().is_err());
+    for rule in Rule::ALL {
+        assert_eq!(rule.id().parse::<Rule>().unwrap(), rule);
+    }
+}
@@ -0,0 +1,68 @@
+use toon::{JsonValue, Number, StringOrNumberOrBoolOrNull, toon_value};
+
+fn object(entries: Vec<(&str, JsonValue)>) -> JsonValue {
+    JsonValue::Object(
+        entries
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect(),
+    )
+}
+
+#[test]
+fn scalars() {
+    assert_eq!(
+        toon_value!(null),
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Null)
+    );
+    assert_eq!(toon_value!(true), JsonValue::from(true));
+    assert_eq!(toon_value!(-7), JsonValue::from(-7_i64));
+    assert_eq!(toon_value!(1.5), JsonValue::from(1.5));
+    assert_eq!(toon_value!("hi"), JsonValue::from("hi"));
+    assert_eq!(
+        toon_value!(Number::U64(u64::MAX)),
+        Number::U64(u64::MAX).into()
+    );
+}
+
+#[test]
+fn nested_containers_keep_key_order() {
+    let label = String::from("b");
+    let value = toon_value!({
+        "z": [],
+        "a": [null, [1, {}], {"k": null}, 2 + 3],
+        (label): {"inner": [true, false,]},
+    });
+    assert_eq!(
+        value,
+        object(vec![
+            ("z", JsonValue::Array(Vec::new())),
+            (
+                "a",
+                JsonValue::Array(vec![
+                    StringOrNumberOrBoolOrNull::Null.into(),
+                    JsonValue::Array(vec![1.into(), JsonValue::Object(Vec::new())]),
+                    object(vec![("k", StringOrNumberOrBoolOrNull::Null.into())]),
+                    5.into(),
+                ]),
+            ),
+            (
+                "b",
+                object(vec![(
+                    "inner",
+                    JsonValue::Array(vec![true.into(), false.into()])
+                )]),
+            ),
+        ])
+    );
+}
+
+#[test]
+fn values_may_be_arbitrary_expressions() {
+    let tags = vec![JsonValue::from("x")];
+    let value =
+        toon_value!({"tags": tags, "count": i64::from(2_i32) * 2, "nested": toon_value!([1])});
+    assert_eq!(value.pointer("/tags/0"), Some(&"x".into()));
+    assert_eq!(value.pointer("/count"), Some(&4.into()));
+    assert_eq!(value.pointer("/nested/0"), Some(&1.into()));
+}
@@ -0,0 +1,93 @@
+use std::sync::{Arc, Mutex};
+
+use serde_json::json;
+use toon::JsonValue;
+use toon::merge::{ArrayMerge, MergeStrategy};
+
+fn value(json: serde_json::Value) -> JsonValue {
+    JsonValue::from(json)
+}
+
+fn merged(base: serde_json::Value, overlay: serde_json::Value, arrays: ArrayMerge) -> JsonValue {
+    let mut base = value(base);
+    base.deep_merge(
+        value(overlay),
+        &MergeStrategy {
+            arrays,
+            ..MergeStrategy::default()
+        },
+    );
+    base
+}
+
+#[test]
+fn layers_union_objects_and_overlay_wins() {
+    let defaults = json!({"server": {"host": "localhost", "port": 80}, "debug": false});
+    let environment = json!({"server": {"port": 8080}});
+    let overrides = json!({"debug": true, "server": {"tls": {"enabled": true}}});
+    let mut config = value(defaults);
+    for layer in [environment, overrides] {
+        config.deep_merge(value(layer), &MergeStrategy::default());
+    }
+    assert!(config.semantic_eq(
+        &value(json!({
+            "server": {"host": "localhost", "port": 8080, "tls": {"enabled": true}},
+            "debug": true
+        })),
+        toon::Tolerance::EXACT
+    ));
+}
+
+#[test]
+fn array_strategies() {
+    let base = json!({"items": [{"id": 1, "tag": "a"}, {"id": 2}, "x"]});
+    let overlay = json!({"items": [{"id": 2, "tag": "b"}, {"id": 3}, "x", "y"]});
+    assert_eq!(
+        merged(base.clone(), overlay.clone(), ArrayMerge::Replace),
+        value(overlay.clone())
+    );
+    assert_eq!(
+        merged(base.clone(), overlay.clone(), ArrayMerge::Concat),
+        value(json!({"items": [
+            {"id": 1, "tag": "a"}, {"id": 2}, "x",
+            {"id": 2, "tag": "b"}, {"id": 3}, "x", "y"
+        ]}))
+    );
+    assert_eq!(
+        merged(base, overlay, ArrayMerge::UnionByKey("id".to_string())),
+        value(json!({"items": [
+            {"id": 1, "tag": "a"}, {"id": 2, "tag": "b"}, "x", {"id": 3}, "y"
+        ]}))
+    );
+}
+
+#[test]
+fn conflict_callback_sees_path_and_both_values() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&seen);
+    let strategy = MergeStrategy {
+        arrays: ArrayMerge::UnionByKey("name".to_string()),
+        on_conflict: Some(Arc::new(move |path, base, overlay| {
+            log.lock()
+                .unwrap()
+                .push((path.to_string(), base.clone(), overlay.clone()));
+            base.clone()
+        })),
+    };
+    let mut base = value(json!({"a/b": 1, "same": 2, "list": [{"name": "x", "v": 1}]}));
+    base.deep_merge(
+        value(json!({"a/b": 5, "same": 2, "list": [{"name": "x", "v": 2}]})),
+        &strategy,
+    );
+    assert_eq!(
+        base,
+        value(json!({"a/b": 1, "same": 2, "list": [{"name": "x", "v": 1}]}))
+    );
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [
+            ("/a~1b".to_string(), 1.into(), 5.into()),
+            ("/list/0/v".to_string(), 1.into(), 2.into()),
+        ]
+    );
+}
@@ -0,0 +1,64 @@
+use toon::object::Entry;
+use toon::{JsonValue, toon_value};
+
+#[test]
+fn insert_remove_and_retain_keep_order() {
+    let mut value = toon::decode("a: 1\nb: 2\na: 3\nc: 4", None);
+    assert_eq!(value.insert("b", 20.into()), Some(2.into()));
+    assert_eq!(value.insert("d", 5.into()), None);
+    assert_eq!(value.keys().collect::<Vec<_>>(), ["a", "b", "a", "c", "d"]);
+
+    assert_eq!(value.remove("a"), Some(3.into()));
+    assert_eq!(value.remove("a"), None);
+    value.retain(|key, value| key != "c" && *value != toon_value!(5));
+    assert_eq!(value, toon_value!({"b": 20}));
+
+    for item in value.values_mut() {
+        *item = "x".into();
+    }
+    assert_eq!(value.values().collect::<Vec<_>>(), [&"x".into()]);
+}
+
+#[test]
+fn entry_api() {
+    let mut value = toon_value!(null);
+    *value.entry("n").or_insert(1.into()) = 2.into();
+    value
+        .entry("n")
+        .and_modify(|n| *n = 3.into())
+        .or_insert(0.into());
+    value
+        .entry("m")
+        .and_modify(|_| unreachable!())
+        .or_insert_with(|| 7.into());
+    assert_eq!(value, toon_value!({"n": 3, "m": 7}));
+
+    match value.entry("n") {
+        Entry::Occupied(mut entry) => {
+            assert_eq!(entry.key(), "n");
+            assert_eq!(entry.insert(4.into()), 3.into());
+            assert_eq!(entry.remove(), 4.into());
+        }
+        Entry::Vacant(_) => unreachable!(),
+    }
+    match value.entry(String::from("z")) {
+        Entry::Vacant(entry) => assert_eq!(entry.key(), "z"),
+        Entry::Occupied(_) => unreachable!(),
+    }
+    assert_eq!(value, toon_value!({"m": 7}));
+}
+
+#[test]
+fn non_objects_are_left_alone() {
+    let mut list = toon_value!([1, 2]);
+    assert_eq!(list.remove("a"), None);
+    list.retain(|_, _| false);
+    assert_eq!(list.keys().count() + list.values().count(), 0);
+    assert_eq!(list, toon_value!([1, 2]));
+}
+
+#[test]
+#[should_panic(expected = "not an object")]
+fn insert_into_array_panics() {
+    JsonValue::Array(Vec::new()).insert("a", 1.into());
+}
@@ -0,0 +1,32 @@
+//! `DecodeOptions` builder and presets.
+
+use toon::options::{DecodeOptions, ExpandPathsMode, Strictness};
+use toon::{decode, try_decode};
+
+#[test]
+fn decode_option_presets_toggle_every_check() {
+    let input = "a: 1\na: 2\nitems[3]: x,y";
+    assert!(try_decode(input, Some(DecodeOptions::strict())).is_err());
+    assert!(try_decode("a: 1\na: 2", Some(DecodeOptions::strict())).is_err());
+    assert!(try_decode("a: 1\na: 2", None).is_ok());
+    assert_eq!(
+        try_decode(input, Some(DecodeOptions::lenient())).unwrap(),
+        decode(
+            input,
+            Some(
+                DecodeOptions::builder()
+                    .strictness(Strictness::lenient())
+                    .build()
+            )
+        )
+    );
+
+    let options = DecodeOptions::lenient()
+        .into_builder()
+        .expand_paths(ExpandPathsMode::Safe)
+        .build();
+    assert_eq!(
+        serde_json::Value::from(try_decode("a.b: 1\na: 2", Some(options)).unwrap()),
+        serde_json::json!({"a": 2})
+    );
+}
@@ -0,0 +1,116 @@
+//! Parquet export/import round-trip tests (requires the `parquet` feature).
+
+#![cfg(feature = "parquet")]
+
+use tempfile::TempDir;
+use toon::parquet::{export_tables, read_table_from_path, tabular_sections, write_table};
+use toon::{JsonValue, Number, decode};
+
+#[test]
+fn tabular_section_round_trips_through_parquet() {
+    let value = decode(
+        "users[3]{id,name,active}:\n  1,Ada,true\n  2,Bob,false\n  3,null,true",
+        None,
+    );
+    let JsonValue::Object(entries) = &value else {
+        panic!("expected object");
+    };
+    let JsonValue::Array(rows) = &entries[0].1 else {
+        panic!("expected array");
+    };
+
+    let mut buf = Vec::new();
+    write_table(rows, &mut buf).unwrap();
+
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("users.parquet");
+    std::fs::write(&path, &buf).unwrap();
+
+    let restored = read_table_from_path(&path).unwrap();
+    assert_eq!(&restored, rows);
+}
+
+#[test]
+fn integer_columns_stay_exact() {
+    let value = decode(
+        "rows[2]{id,big,ratio}:\n  9007199254740993,18446744073709551615,1.5\n  -1,1,2",
+        None,
+    );
+    let JsonValue::Object(entries) = &value else {
+        panic!("expected object");
+    };
+    let JsonValue::Array(rows) = &entries[0].1 else {
+        panic!("expected array");
+    };
+
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("rows.parquet");
+    write_table(rows, std::fs::File::create(&path).unwrap()).unwrap();
+    let restored = read_table_from_path(&path).unwrap();
+
+    let cell = |row: usize, column: usize| match &restored[row] {
+        JsonValue::Object(entries) => entries[column].1.clone(),
+        other => panic!("expected object, got {other:?}"),
+    };
+    assert_eq!(
+        cell(0, 0),
+        JsonValue::from(Number::I64(9_007_199_254_740_993))
+    );
+    assert_eq!(cell(0, 1), JsonValue::from(Number::U64(u64::MAX)));
+    assert_eq!(cell(1, 1), JsonValue::from(Number::U64(1)));
+    assert_eq!(cell(1, 2), JsonValue::from(Number::F64(2.0)));
+    assert_eq!(
+        toon::encode(JsonValue::Array(restored), None),
+        toon::encode(JsonValue::Array(rows.clone()), None)
+    );
+}
+
+#[test]
+fn export_tables_writes_one_file_per_section() {
+    let value = decode(
+        "meta:\n  version: 1\na[2]{x}:\n  1\n  2\nb[1]{y}:\n  hi\nc[2]: 1,2",
+        None,
+    );
+    assert_eq!(tabular_sections(&value).len(), 2);
+
+    let tmp = TempDir::new().unwrap();
+    let written = export_tables(&value, tmp.path()).unwrap();
+    assert_eq!(written.len(), 2);
+    assert!(written[0].ends_with("a.parquet"));
+    assert!(written[1].ends_with("b.parquet"));
+}
+
+#[test]
+fn export_tables_rejects_keys_that_leave_the_directory() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().join("out");
+    std::fs::create_dir(&dir).unwrap();
+
+    for key in ["../escape", "/tmp/abs", "a/b", "a\\b", "..", "."] {
+        let document = format!(
+            "ok[1]{{x}}:\n  1\n\"{}\"[1]{{x}}:\n  1",
+            key.replace('\\', "\\\\")
+        );
+        let value = decode(&document, None);
+        let err = export_tables(&value, &dir).unwrap_err();
+        assert!(
+            err.to_string().contains("not a plain file name"),
+            "{key}: {err}"
+        );
+    }
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+    assert_eq!(std::fs::read_dir(tmp.path()).unwrap().count(), 1);
+}
+
+#[test]
+fn mixed_column_types_are_rejected() {
+    let value = decode("rows[2]{v}:\n  1\n  text", None);
+    let JsonValue::Object(entries) = &value else {
+        panic!("expected object");
+    };
+    let JsonValue::Array(rows) = &entries[0].1 else {
+        panic!("expected array");
+    };
+    let err = write_table(rows, Vec::new()).unwrap_err();
+    assert!(err.to_string().contains("mixes value types"));
+}
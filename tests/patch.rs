@@ -0,0 +1,239 @@
+use serde_json::json;
+use toon::JsonValue;
+use toon::error::ToonError;
+use toon::patch::{Patch, PatchOperation, apply};
+
+fn value(json: serde_json::Value) -> JsonValue {
+    JsonValue::from(json)
+}
+
+fn patched(doc: serde_json::Value, patch: serde_json::Value) -> toon::error::Result<JsonValue> {
+    let mut doc = value(doc);
+    apply(&mut doc, &Patch::from_value(&value(patch))?)?;
+    Ok(doc)
+}
+
+#[test]
+fn rfc6902_examples() {
+    let cases = [
+        (
+            json!({"foo": "bar"}),
+            json!([{"op": "add", "path": "/baz", "value": "qux"}]),
+            json!({"foo": "bar", "baz": "qux"}),
+        ),
+        (
+            json!({"foo": ["bar", "baz"]}),
+            json!([{"op": "add", "path": "/foo/1", "value": "qux"}]),
+            json!({"foo": ["bar", "qux", "baz"]}),
+        ),
+        (
+            json!({"baz": "qux", "foo": "bar"}),
+            json!([{"op": "remove", "path": "/baz"}]),
+            json!({"foo": "bar"}),
+        ),
+        (
+            json!({"foo": ["bar", "qux", "baz"]}),
+            json!([{"op": "remove", "path": "/foo/1"}]),
+            json!({"foo": ["bar", "baz"]}),
+        ),
+        (
+            json!({"baz": "qux", "foo": "bar"}),
+            json!([{"op": "replace", "path": "/baz", "value": "boo"}]),
+            json!({"baz": "boo", "foo": "bar"}),
+        ),
+        (
+            json!({"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}),
+            json!([{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]),
+            json!({"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}}),
+        ),
+        (
+            json!({"foo": ["all", "grass", "cows", "eat"]}),
+            json!([{"op": "move", "from": "/foo/1", "path": "/foo/3"}]),
+            json!({"foo": ["all", "cows", "eat", "grass"]}),
+        ),
+        (
+            json!({"baz": "qux", "foo": ["a", 2, "c"]}),
+            json!([
+                {"op": "test", "path": "/baz", "value": "qux"},
+                {"op": "test", "path": "/foo/1", "value": 2}
+            ]),
+            json!({"baz": "qux", "foo": ["a", 2, "c"]}),
+        ),
+        (
+            json!({"foo": "bar"}),
+            json!([{"op": "add", "path": "/child", "value": {"grandchild": {}}}]),
+            json!({"foo": "bar", "child": {"grandchild": {}}}),
+        ),
+        (
+            json!({"foo": "bar"}),
+            json!([{"op": "add", "path": "/baz", "value": "qux", "xyz": 123}]),
+            json!({"foo": "bar", "baz": "qux"}),
+        ),
+        (
+            json!({"/": 9, "~1": 10}),
+            json!([{"op": "test", "path": "/~01", "value": 10}]),
+            json!({"/": 9, "~1": 10}),
+        ),
+        (
+            json!({"foo": ["bar"]}),
+            json!([{"op": "add", "path": "/foo/-", "value": ["abc", "def"]}]),
+            json!({"foo": ["bar", ["abc", "def"]]}),
+        ),
+        (
+            json!({"foo": 1}),
+            json!([{"op": "copy", "from": "/foo", "path": "/bar"}]),
+            json!({"foo": 1, "bar": 1}),
+        ),
+        (
+            json!({"foo": 1}),
+            json!([{"op": "add", "path": "", "value": [1]}]),
+            json!([1]),
+        ),
+    ];
+    for (doc, patch, expected) in cases {
+        let result = patched(doc, patch.clone()).unwrap();
+        assert!(
+            result.semantic_eq(&value(expected), toon::Tolerance::EXACT),
+            "{patch}"
+        );
+    }
+}
+
+#[test]
+fn failed_operations_leave_document_unchanged() {
+    let failures = [
+        (
+            json!({"baz": "qux"}),
+            json!([{"op": "test", "path": "/baz", "value": "bar"}]),
+            0,
+        ),
+        (
+            json!({"foo": "bar"}),
+            json!([{"op": "add", "path": "/baz/bat", "value": "qux"}]),
+            0,
+        ),
+        (
+            json!({"foo": [1]}),
+            json!([
+                {"op": "add", "path": "/foo/-", "value": 2},
+                {"op": "add", "path": "/foo/5", "value": 3}
+            ]),
+            1,
+        ),
+        (
+            json!({"foo": {"bar": 1}}),
+            json!([{"op": "move", "from": "/foo", "path": "/foo/bar/baz"}]),
+            0,
+        ),
+        (
+            json!({"foo": 1}),
+            json!([{"op": "remove", "path": "/bar"}]),
+            0,
+        ),
+    ];
+    for (doc, patch, failing) in failures {
+        let mut target = value(doc.clone());
+        let patch = Patch::from_value(&value(patch)).unwrap();
+        match apply(&mut target, &patch) {
+            Err(ToonError::Patch { index, .. }) => assert_eq!(index, failing),
+            other => panic!("expected patch error, got {other:?}"),
+        }
+        assert_eq!(target, value(doc));
+    }
+}
+
+#[test]
+fn malformed_patches_are_rejected() {
+    for patch in [
+        json!({"op": "add"}),
+        json!([{"op": "add", "path": "/a"}]),
+        json!([{"op": "move", "path": "/a"}]),
+        json!([{"op": "frobnicate", "path": "/a"}]),
+        json!([{"path": "/a"}]),
+    ] {
+        assert!(Patch::from_value(&value(patch.clone())).is_err(), "{patch}");
+    }
+    let patch = Patch::from_value(&value(json!([{"op": "remove", "path": "/a"}]))).unwrap();
+    assert_eq!(
+        patch.0,
+        [PatchOperation::Remove {
+            path: "/a".to_string()
+        }]
+    );
+}
+
+#[test]
+fn merge_patch_rfc7386_examples() {
+    let cases = [
+        (json!({"a": "b"}), json!({"a": "c"}), json!({"a": "c"})),
+        (
+            json!({"a": "b"}),
+            json!({"b": "c"}),
+            json!({"a": "b", "b": "c"}),
+        ),
+        (json!({"a": "b"}), json!({"a": null}), json!({})),
+        (
+            json!({"a": "b", "b": "c"}),
+            json!({"a": null}),
+            json!({"b": "c"}),
+        ),
+        (json!({"a": ["b"]}), json!({"a": "c"}), json!({"a": "c"})),
+        (json!({"a": "c"}), json!({"a": ["b"]}), json!({"a": ["b"]})),
+        (
+            json!({"a": {"b": "c"}}),
+            json!({"a": {"b": "d", "c": null}}),
+            json!({"a": {"b": "d"}}),
+        ),
+        (
+            json!({"a": [{"b": "c"}]}),
+            json!({"a": [1]}),
+            json!({"a": [1]}),
+        ),
+        (json!(["a", "b"]), json!(["c", "d"]), json!(["c", "d"])),
+        (json!({"a": "b"}), json!(["c"]), json!(["c"])),
+        (json!({"a": "foo"}), json!(null), json!(null)),
+        (json!({"a": "foo"}), json!("bar"), json!("bar")),
+        (
+            json!({"e": null}),
+            json!({"a": 1}),
+            json!({"e": null, "a": 1}),
+        ),
+        (
+            json!([1, 2]),
+            json!({"a": "b", "c": null}),
+            json!({"a": "b"}),
+        ),
+        (
+            json!({}),
+            json!({"a": {"bb": {"ccc": null}}}),
+            json!({"a": {"bb": {}}}),
+        ),
+    ];
+    for (target, patch, expected) in cases {
+        let mut doc = value(target);
+        doc.merge_patch(&value(patch.clone()));
+        assert_eq!(doc, value(expected), "{patch}");
+    }
+}
+
+#[test]
+fn merge_patch_deletes_every_duplicate_entry() {
+    let mut doc = toon::decode("a: 1\nb: 2\na: 3", None);
+    doc.merge_patch(&value(json!({"a": null})));
+    assert_eq!(doc, value(json!({"b": 2})));
+}
+
+#[test]
+fn test_operation_compares_repeated_keys_both_ways() {
+    let test = |doc: &str, expected: &str| {
+        let mut doc = toon::decode(doc, None);
+        let patch = Patch(vec![PatchOperation::Test {
+            path: String::new(),
+            value: toon::decode(expected, None),
+        }]);
+        apply(&mut doc, &patch).is_ok()
+    };
+    assert!(!test("a: 1\na: 1", "a: 1\nb: 2"));
+    assert!(!test("a: 1\nb: 2", "a: 1\na: 1"));
+    assert!(test("a: 1\na: 2", "a: 2\na: 1"));
+}
@@ -0,0 +1,68 @@
+use serde_json::json;
+use toon::options::KeyFoldingMode;
+use toon::{EncodeOptions, JsonValue};
+
+fn value(json: serde_json::Value) -> JsonValue {
+    JsonValue::from(json)
+}
+
+#[test]
+fn folded_keys_address_the_same_values() {
+    let doc = value(json!({"server": {"tls": {"cert": "a.pem"}}, "ports": [80, 443]}));
+    let folded = toon::encode(
+        doc.clone(),
+        Some(
+            EncodeOptions::builder()
+                .key_folding(KeyFoldingMode::Safe)
+                .build(),
+        ),
+    );
+    assert!(folded.contains("server.tls.cert: a.pem"), "{folded}");
+    assert_eq!(doc.get_path("server.tls.cert"), Some(&"a.pem".into()));
+    assert_eq!(doc.get_path("ports[1]"), Some(&443.into()));
+    assert_eq!(doc.get_path("ports.1"), Some(&443.into()));
+    assert_eq!(doc.get_path(""), Some(&doc));
+}
+
+#[test]
+fn get_path_syntax() {
+    let doc = value(json!({
+        "a": {"b": [[1, 2], {"c": true}]},
+        "first name": {"x": 1},
+        "0": "zero",
+        "rows": [{"id": 7}]
+    }));
+    assert_eq!(doc.get_path("a.b[0][1]"), Some(&2.into()));
+    assert_eq!(doc.get_path("a.b[1].c"), Some(&true.into()));
+    assert_eq!(doc.get_path("\"first name\".x"), Some(&1.into()));
+    assert_eq!(doc.get_path("0"), Some(&"zero".into()));
+    assert_eq!(doc.get_path("rows[0].id"), Some(&7.into()));
+    for missing in [
+        "a.b[2]", "a.b[01]", "a..b", "a.", "a[", "a[x]", "a]b", "\"a", "rows.x",
+    ] {
+        assert_eq!(doc.get_path(missing), None, "{missing}");
+    }
+    let root = value(json!([{"id": 1}]));
+    assert_eq!(root.get_path("[0].id"), Some(&1.into()));
+}
+
+#[test]
+fn set_path_creates_and_replaces() {
+    let mut doc = value(json!({"a": 1}));
+    doc.set_path("a", 2.into()).unwrap();
+    doc.set_path("b.c[0].d", "x".into()).unwrap();
+    doc.set_path("b.c[1]", "y".into()).unwrap();
+    doc.set_path("b.list.0", true.into()).unwrap();
+    *doc.get_path_mut("b.c[0].d").unwrap() = "z".into();
+    assert_eq!(
+        doc,
+        value(json!({"a": 2, "b": {"c": [{"d": "z"}, "y"], "list": [true]}}))
+    );
+
+    assert!(doc.set_path("b.c[5]", 1.into()).is_err());
+    assert!(doc.set_path("a.x", 1.into()).is_err());
+    assert!(doc.set_path("a[", 1.into()).is_err());
+
+    doc.set_path("", 3.into()).unwrap();
+    assert_eq!(doc, 3.into());
+}
@@ -0,0 +1,265 @@
+//! Path expansion tests: dotted keys, array indices, conflict handling, and
+//! round trips through key folding.
+
+use toon::options::{
+    DecodeLimits, DecodeOptions, EncodeOptions, ExpandConflictPolicy, ExpandPathsMode,
+    KeyFoldingMode, Strictness,
+};
+use toon::{decode, encode, try_decode};
+
+fn decode_json(
+    input: &str,
+    mode: ExpandPathsMode,
+    strict: bool,
+) -> toon::error::Result<serde_json::Value> {
+    let options = DecodeOptions::builder()
+        .strictness(if strict {
+            Strictness::default()
+        } else {
+            Strictness::lenient()
+        })
+        .expand_paths(mode)
+        .build();
+    try_decode(input, Some(options)).map(serde_json::Value::from)
+}
+
+#[test]
+fn indexed_mode_rebuilds_arrays_from_numeric_segments() {
+    let input = "items.0.name: Ada\nitems.0.id: 1\nitems.1.name: Bob\nitems.1.id: 2";
+    let value = decode_json(input, ExpandPathsMode::Indexed, true).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({"items": [{"name": "Ada", "id": 1}, {"name": "Bob", "id": 2}]})
+    );
+}
+
+#[test]
+fn indexed_mode_accepts_bracket_indices() {
+    let input = "items[0].name: Ada\nitems[1].name: Bob\nmatrix[0][0]: y\nmatrix[0][1]: x";
+    let value = decode_json(input, ExpandPathsMode::Indexed, false).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({
+            "items": [{"name": "Ada"}, {"name": "Bob"}],
+            "matrix": [["y", "x"]]
+        })
+    );
+}
+
+#[test]
+fn indexed_mode_rejects_index_gaps_under_every_policy() {
+    let input = "items.0: a\nitems.2: c";
+    for strict in [true, false] {
+        let err = decode_json(input, ExpandPathsMode::Indexed, strict).unwrap_err();
+        assert!(err.to_string().contains("index 2"), "{err}");
+    }
+
+    // A gap is never padded, so a huge index cannot allocate.
+    let err = decode_json("a.100000000000: 1", ExpandPathsMode::Indexed, false).unwrap_err();
+    assert!(err.to_string().contains("array has only 0 items"), "{err}");
+}
+
+#[test]
+fn indexed_mode_applies_max_array_length() {
+    let limits = DecodeLimits {
+        max_array_length: Some(2),
+        ..DecodeLimits::default()
+    };
+    let options = DecodeOptions::builder()
+        .strictness(Strictness::lenient())
+        .expand_paths(ExpandPathsMode::Indexed)
+        .limits(limits);
+    let value = try_decode("items.0: a\nitems.1: b", Some(options.clone().build())).unwrap();
+    assert_eq!(
+        serde_json::Value::from(value),
+        serde_json::json!({"items": ["a", "b"]})
+    );
+
+    let err = try_decode("items.0: a\nitems.1: b\nitems.2: c", Some(options.build())).unwrap_err();
+    assert_eq!(
+        err.decode_error().map(|details| details.kind),
+        Some(toon::error::DecodeErrorKind::LimitExceeded),
+        "{err}"
+    );
+}
+
+#[test]
+fn safe_mode_keeps_numeric_segments_literal() {
+    let input = "items.0.name: Ada";
+    let value = decode_json(input, ExpandPathsMode::Safe, true).unwrap();
+    assert_eq!(value, serde_json::json!({"items.0.name": "Ada"}));
+}
+
+#[test]
+fn quoted_keys_are_never_expanded() {
+    let input = "\"items.0\": a";
+    let value = decode_json(input, ExpandPathsMode::Indexed, true).unwrap();
+    assert_eq!(value, serde_json::json!({"items.0": "a"}));
+}
+
+fn decode_with_policy(
+    input: &str,
+    policy: ExpandConflictPolicy,
+) -> toon::error::Result<serde_json::Value> {
+    let options = DecodeOptions::builder()
+        .expand_paths(ExpandPathsMode::Safe)
+        .on_conflict(policy)
+        .build();
+    try_decode(input, Some(options)).map(serde_json::Value::from)
+}
+
+#[test]
+fn conflict_policy_error_rejects_scalar_collision() {
+    let err = decode_with_policy("a: 2\na.b: 1", ExpandConflictPolicy::Error).unwrap_err();
+    assert!(err.to_string().contains("Path expansion conflict"));
+}
+
+#[test]
+fn conflict_policy_keep_first_retains_earlier_value() {
+    let value = decode_with_policy("a: 2\na.b: 1", ExpandConflictPolicy::KeepFirst).unwrap();
+    assert_eq!(value, serde_json::json!({"a": 2}));
+
+    let value = decode_with_policy("a.b: 1\na: 2", ExpandConflictPolicy::KeepFirst).unwrap();
+    assert_eq!(value, serde_json::json!({"a": {"b": 1}}));
+}
+
+#[test]
+fn conflict_policy_overwrite_replaces_objects_wholesale() {
+    let value = decode_with_policy("a.b: 1\na:\n  c: 2", ExpandConflictPolicy::Overwrite).unwrap();
+    assert_eq!(value, serde_json::json!({"a": {"c": 2}}));
+}
+
+#[test]
+fn conflict_policy_merge_combines_objects_and_lets_last_scalar_win() {
+    let value =
+        decode_with_policy("a.b: 1\na:\n  c: 2\na.b: 3", ExpandConflictPolicy::Merge).unwrap();
+    assert_eq!(value, serde_json::json!({"a": {"b": 3, "c": 2}}));
+}
+
+#[test]
+fn conflict_policy_prefer_settles_by_origin_not_order() {
+    for input in ["a: 2\na.b: 1", "a.b: 1\na: 2"] {
+        let folded = decode_with_policy(input, ExpandConflictPolicy::PreferFolded).unwrap();
+        assert_eq!(folded, serde_json::json!({"a": {"b": 1}}));
+        let literal = decode_with_policy(input, ExpandConflictPolicy::PreferLiteral).unwrap();
+        assert_eq!(literal, serde_json::json!({"a": 2}));
+    }
+
+    // Values merged in from a literal object stay literal.
+    let input = "a.b: 1\na:\n  c: 2\na.c.d: 3";
+    let literal = decode_with_policy(input, ExpandConflictPolicy::PreferLiteral).unwrap();
+    assert_eq!(literal, serde_json::json!({"a": {"b": 1, "c": 2}}));
+    let folded = decode_with_policy(input, ExpandConflictPolicy::PreferFolded).unwrap();
+    assert_eq!(folded, serde_json::json!({"a": {"b": 1, "c": {"d": 3}}}));
+
+    // Two dotted keys: the later wins.
+    let value =
+        decode_with_policy("a.b: 1\na.b.c: 2", ExpandConflictPolicy::PreferLiteral).unwrap();
+    assert_eq!(value, serde_json::json!({"a": {"b": {"c": 2}}}));
+}
+
+#[test]
+fn quoted_literal_key_coexists_with_the_same_dotted_key() {
+    for policy in [
+        ExpandConflictPolicy::Error,
+        ExpandConflictPolicy::PreferFolded,
+        ExpandConflictPolicy::PreferLiteral,
+    ] {
+        for input in ["a.b: 1\n\"a.b\": 2", "\"a.b\": 2\na.b: 1"] {
+            let value = decode_with_policy(input, policy).unwrap();
+            assert_eq!(
+                value["a"],
+                serde_json::json!({"b": 1}),
+                "{policy:?}: {input}"
+            );
+            assert_eq!(value["a.b"], serde_json::json!(2), "{policy:?}: {input}");
+        }
+    }
+}
+
+#[test]
+fn conflict_policy_defaults_follow_strictness() {
+    let lenient = DecodeOptions::builder()
+        .strictness(Strictness::lenient())
+        .expand_paths(ExpandPathsMode::Safe)
+        .build();
+    let value = try_decode("a: 2\na.b: 1", Some(lenient)).unwrap();
+    assert_eq!(
+        serde_json::Value::from(value),
+        serde_json::json!({"a": {"b": 1}})
+    );
+}
+
+#[test]
+fn key_folding_simple() {
+    let json: serde_json::Value = serde_json::json!({
+        "a": {"b": {"c": "value"}}
+    });
+    let options = Some(
+        EncodeOptions::builder()
+            .key_folding(KeyFoldingMode::Safe)
+            .build(),
+    );
+    let toon = encode(json.clone(), options);
+    assert!(toon.contains("a.b.c"));
+
+    let decode_options = Some(
+        DecodeOptions::builder()
+            .expand_paths(ExpandPathsMode::Safe)
+            .build(),
+    );
+    let decoded = decode(&toon, decode_options);
+    let decoded_json: serde_json::Value = decoded.into();
+    assert_eq!(json, decoded_json);
+}
+
+#[test]
+fn key_folding_with_sibling() {
+    // When there's a sibling, folding still happens for the single-key chain
+    // but we need expand_paths to reconstruct the nested structure
+    let json: serde_json::Value = serde_json::json!({
+        "a": {
+            "b": {"c": "deep"},
+            "sibling": "value"
+        }
+    });
+    let options = Some(
+        EncodeOptions::builder()
+            .key_folding(KeyFoldingMode::Safe)
+            .build(),
+    );
+    let toon = encode(json.clone(), options);
+
+    // Need to expand paths to reconstruct the nested structure
+    let decode_options = Some(
+        DecodeOptions::builder()
+            .expand_paths(ExpandPathsMode::Safe)
+            .build(),
+    );
+    let decoded = decode(&toon, decode_options);
+    let decoded_json: serde_json::Value = decoded.into();
+    assert_eq!(json, decoded_json);
+}
+
+#[test]
+fn key_folding_depth_limit() {
+    let json: serde_json::Value = serde_json::json!({
+        "a": {"b": {"c": {"d": {"e": "deep"}}}}
+    });
+    let options = Some(
+        EncodeOptions::builder()
+            .key_folding(KeyFoldingMode::Safe)
+            .flatten_depth(2) // Only fold 2 levels
+            .build(),
+    );
+    let toon = encode(json.clone(), options);
+
+    let decode_options = Some(
+        DecodeOptions::builder()
+            .expand_paths(ExpandPathsMode::Safe)
+            .build(),
+    );
+    let decoded = decode(&toon, decode_options);
+    let decoded_json: serde_json::Value = decoded.into();
+    assert_eq!(json, decoded_json);
+}
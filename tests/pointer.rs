@@ -0,0 +1,72 @@
+use serde_json::json;
+use toon::JsonValue;
+
+fn value(json: serde_json::Value) -> JsonValue {
+    JsonValue::from(json)
+}
+
+#[test]
+fn pointer_matches_serde_json() {
+    let json = json!({
+        "foo": ["bar", "baz"],
+        "": 0,
+        "a/b": 1,
+        "c%d": 2,
+        "e^f": 3,
+        "g|h": 4,
+        "i\\j": 5,
+        "k\"l": 6,
+        " ": 7,
+        "m~n": 8,
+        "nested": {"list": [{"id": 10}]},
+    });
+    let doc = value(json.clone());
+    let pointers = [
+        "",
+        "/foo",
+        "/foo/0",
+        "/foo/1",
+        "/",
+        "/a~1b",
+        "/c%d",
+        "/e^f",
+        "/g|h",
+        "/i\\j",
+        "/k\"l",
+        "/ ",
+        "/m~0n",
+        "/nested/list/0/id",
+        "/foo/2",
+        "/foo/01",
+        "/foo/+1",
+        "/foo/-",
+        "/nope",
+        "/foo/0/x",
+        "foo",
+    ];
+    for pointer in pointers {
+        assert_eq!(
+            doc.pointer(pointer).cloned(),
+            json.pointer(pointer).cloned().map(value),
+            "{pointer}"
+        );
+    }
+}
+
+#[test]
+fn pointer_finds_the_last_of_repeated_keys() {
+    let mut doc = JsonValue::Object(vec![
+        ("a".to_string(), 1.into()),
+        ("a".to_string(), 2.into()),
+    ]);
+    assert_eq!(doc.pointer("/a"), Some(&2.into()));
+    *doc.pointer_mut("/a").unwrap() = 3.into();
+    assert_eq!(
+        doc,
+        JsonValue::Object(vec![
+            ("a".to_string(), 1.into()),
+            ("a".to_string(), 3.into()),
+        ])
+    );
+    assert!(doc.pointer_mut("/b").is_none());
+}
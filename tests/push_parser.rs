@@ -0,0 +1,174 @@
+//! Push parser tests: chunked input and resumable checkpoints.
+
+use toon::JsonStreamEvent;
+use toon::options::Strictness;
+
+// ============================================================================
+// PUSH PARSING
+// ============================================================================
+
+fn push_all(
+    chunks: &[&[u8]],
+    options: Option<toon::DecodeStreamOptions>,
+) -> toon::error::Result<Vec<JsonStreamEvent>> {
+    let mut parser = toon::ToonParser::new(options);
+    let mut events = Vec::new();
+    for chunk in chunks {
+        parser.feed(chunk)?;
+        events.extend(std::iter::from_fn(|| parser.next_event()));
+    }
+    parser.finish()?;
+    events.extend(std::iter::from_fn(|| parser.next_event()));
+    Ok(events)
+}
+
+#[test]
+fn toon_parser_matches_sync_decoder_for_any_split() {
+    let inputs = [
+        "",
+        "42",
+        "a: 1\nb:\n  c: [2]\nd: héllo ✓",
+        "users[2]{id,name}:\n  1,Ada\n  2,Zoë\nnext: true",
+        "[3]:\n  - 1\n  - a: 1\n    b[2]:\n      - x\n      - y: 2\n    c: 3\n  -",
+        "[2]: a,b",
+    ];
+    for input in inputs {
+        let expected =
+            toon::try_decode_stream_sync(input.split('\n').map(String::from), None).unwrap();
+        for text in [input.to_string(), input.replace('\n', "\r\n") + "\r\n"] {
+            let bytes = text.as_bytes();
+            let single: Vec<&[u8]> = bytes.chunks(1).collect();
+            assert_eq!(push_all(&single, None).unwrap(), expected, "{text:?}");
+            for split in 0..=bytes.len() {
+                let halves = [&bytes[..split], &bytes[split..]];
+                assert_eq!(push_all(&halves, None).unwrap(), expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn toon_parser_emits_events_as_items_complete() {
+    let mut parser = toon::ToonParser::new(None);
+    parser.feed(b"a: 1\nb:\n  c").unwrap();
+    assert_eq!(parser.next_event(), Some(JsonStreamEvent::StartObject));
+    assert!(matches!(
+        parser.next_event(),
+        Some(JsonStreamEvent::Key { .. })
+    ));
+    assert!(matches!(
+        parser.next_event(),
+        Some(JsonStreamEvent::Primitive { .. })
+    ));
+    assert_eq!(parser.next_event(), None);
+
+    // `b` is complete only once the next top-level line starts.
+    parser.feed(b": 2\n").unwrap();
+    assert_eq!(parser.next_event(), None);
+    parser.feed(b"d").unwrap();
+    assert_eq!(parser.next_event(), None);
+    parser.feed(b": 3\n").unwrap();
+    assert!(matches!(
+        parser.next_event(),
+        Some(JsonStreamEvent::Key { .. })
+    ));
+}
+
+#[test]
+fn toon_parser_reports_errors_and_stops() {
+    let mut parser = toon::ToonParser::new(None);
+    parser.feed(b"a: 1\nb: \xe2\x9c").unwrap();
+    let err = parser.feed(b"\x93\xff\n").unwrap_err();
+    assert_eq!(err.to_string(), "Line 2, column 7: Line is not valid UTF-8");
+    assert_eq!(parser.next_event(), None);
+    let err = parser.feed(b"c: 3\n").unwrap_err();
+    assert_eq!(err.to_string(), "Parser has already finished");
+
+    let expected = toon::try_decode_stream_sync(
+        "[2]:\n  - a\n  - b\n  - c".split('\n').map(String::from),
+        None,
+    )
+    .unwrap_err();
+    let err = push_all(&[b"[2]:\n  - a\n  - b\n  - c"], None).unwrap_err();
+    assert_eq!(err.to_string(), expected.to_string());
+
+    // An unfinished line is rejected once it outgrows the limit.
+    let options = toon::DecodeStreamOptions {
+        max_line_length: Some(8),
+        ..toon::DecodeStreamOptions::default()
+    };
+    let mut parser = toon::ToonParser::new(Some(options));
+    parser.feed(b"a: 1\r\nb: 1234").unwrap();
+    let err = parser.feed(b"5678").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Line 2, column 9: Line is 11 bytes long, over the limit of 8"
+    );
+
+    let mut parser = toon::ToonParser::new(None);
+    parser.finish().unwrap();
+    assert!(parser.finish().is_err());
+}
+
+// ============================================================================
+// PARSER CHECKPOINTS
+// ============================================================================
+
+#[test]
+fn toon_parser_resumes_from_any_checkpoint() {
+    let inputs = [
+        "a: 1\nb:\n  c: [2]\n  d: x\ne: héllo",
+        "[3]:\n  - 1\n  - a: 1\n    b[2]: x,y\n\n  - c\r\nafter: ignored?",
+        "rows[3]{id,name}:\n  1,Ada\n  2,Zoë\n  3,Lin",
+    ];
+    for input in inputs {
+        let expected = push_all(&[input.as_bytes()], None);
+        let bytes = input.as_bytes();
+        for split in 0..=bytes.len() {
+            let mut parser = toon::ToonParser::new(None);
+            let mut events = Vec::new();
+            if let Err(err) = parser.feed(&bytes[..split]) {
+                assert_eq!(err.to_string(), expected.as_ref().unwrap_err().to_string());
+                continue;
+            }
+            events.extend(std::iter::from_fn(|| parser.next_event()));
+
+            let saved = serde_json::to_string(&parser.checkpoint().unwrap()).unwrap();
+            drop(parser);
+            let checkpoint: toon::ParserCheckpoint = serde_json::from_str(&saved).unwrap();
+            assert_eq!(checkpoint.offset(), split as u64);
+            let mut parser = toon::ToonParser::resume(checkpoint, None);
+            let result = parser.feed(&bytes[split..]).and_then(|()| parser.finish());
+            events.extend(std::iter::from_fn(|| parser.next_event()));
+            match &expected {
+                Ok(expected) => {
+                    result.unwrap();
+                    assert_eq!(&events, expected, "split at {split}");
+                }
+                Err(expected) => assert_eq!(result.unwrap_err().to_string(), expected.to_string()),
+            }
+        }
+    }
+}
+
+#[test]
+fn toon_parser_checkpoint_keeps_duplicate_key_tracking() {
+    let options = || toon::DecodeStreamOptions {
+        strictness: Some(Strictness::strict()),
+        ..toon::DecodeStreamOptions::default()
+    };
+    let mut parser = toon::ToonParser::new(Some(options()));
+    parser.feed(b"a: 1\nb: 2\n").unwrap();
+    assert!(parser.checkpoint().is_err(), "events are still pending");
+    while parser.next_event().is_some() {}
+    let checkpoint = parser.checkpoint().unwrap();
+    assert_eq!(checkpoint.line_number(), 2);
+
+    let mut parser = toon::ToonParser::resume(checkpoint, Some(options()));
+    parser.feed(b"a: 3\n").unwrap();
+    let err = parser.finish().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Line 3, column 1: duplicate key `a` (first defined at line 1)"
+    );
+}
@@ -0,0 +1,122 @@
+use serde_json::json;
+use toon::JsonValue;
+use toon::error::ToonError;
+use toon::query::{Query, query};
+
+fn store() -> JsonValue {
+    JsonValue::from(json!({
+        "store": {
+            "book": [
+                {"category": "reference", "author": "Nigel Rees", "title": "Sayings of the Century", "price": 8.95},
+                {"category": "fiction", "author": "Evelyn Waugh", "title": "Sword of Honour", "price": 12.99},
+                {"category": "fiction", "author": "Herman Melville", "title": "Moby Dick", "isbn": "0-553-21311-3", "price": 8.99},
+                {"category": "fiction", "author": "J. R. R. Tolkien", "title": "The Lord of the Rings", "isbn": "0-395-19395-8", "price": 22.99}
+            ],
+            "bicycle": {"color": "red", "price": 399}
+        }
+    }))
+}
+
+fn values(doc: &JsonValue, expression: &str) -> Vec<serde_json::Value> {
+    query(doc, expression)
+        .unwrap()
+        .into_iter()
+        .map(|m| serde_json::Value::from(m.value.clone()))
+        .collect()
+}
+
+fn paths(doc: &JsonValue, expression: &str) -> Vec<String> {
+    query(doc, expression)
+        .unwrap()
+        .into_iter()
+        .map(|m| m.path)
+        .collect()
+}
+
+#[test]
+fn bookstore_examples() {
+    let doc = store();
+    assert_eq!(
+        values(&doc, "$.store.book[*].author"),
+        [
+            "Nigel Rees",
+            "Evelyn Waugh",
+            "Herman Melville",
+            "J. R. R. Tolkien"
+        ]
+    );
+    assert_eq!(values(&doc, "$..author").len(), 4);
+    assert_eq!(values(&doc, "$.store.*").len(), 2);
+    assert_eq!(values(&doc, "$.store..price").len(), 5);
+    assert_eq!(values(&doc, "$..book[2].title"), ["Moby Dick"]);
+    assert_eq!(values(&doc, "$..book[-1].title"), ["The Lord of the Rings"]);
+    assert_eq!(
+        values(&doc, "$..book[0,1].price"),
+        [json!(8.95), json!(12.99)]
+    );
+    assert_eq!(
+        values(&doc, "$..book[:2].price"),
+        [json!(8.95), json!(12.99)]
+    );
+    assert_eq!(
+        values(&doc, "$..book[?@.isbn].title"),
+        ["Moby Dick", "The Lord of the Rings"]
+    );
+    assert_eq!(
+        values(&doc, "$..book[?(@.price < 10)].title"),
+        ["Sayings of the Century", "Moby Dick"]
+    );
+    assert_eq!(
+        values(
+            &doc,
+            "$.store.book[?@.category == 'fiction' && !(@.price > 20)]['title']"
+        ),
+        ["Sword of Honour", "Moby Dick"]
+    );
+    assert_eq!(
+        values(&doc, "$.store.book[?@.price > $.store.bicycle.price]"),
+        Vec::<serde_json::Value>::new()
+    );
+    assert_eq!(values(&doc, "$..*").len(), 27);
+}
+
+#[test]
+fn matches_carry_get_path_paths() {
+    let doc = JsonValue::from(json!({"a b": [{"c": 1}, {"c": 2}], "x": {"c": 3}}));
+    let found = paths(&doc, "$..c");
+    assert_eq!(found, ["\"a b\"[0].c", "\"a b\"[1].c", "x.c"]);
+    for path in &found {
+        assert!(doc.get_path(path).is_some(), "{path}");
+    }
+    assert_eq!(paths(&doc, "$"), [""]);
+}
+
+#[test]
+fn slices_and_negative_steps() {
+    let doc = JsonValue::from(json!([0, 1, 2, 3, 4, 5]));
+    assert_eq!(values(&doc, "$[1:3]"), [json!(1), json!(2)]);
+    assert_eq!(values(&doc, "$[::2]"), [json!(0), json!(2), json!(4)]);
+    assert_eq!(values(&doc, "$[-2:]"), [json!(4), json!(5)]);
+    assert_eq!(values(&doc, "$[::-2]"), [json!(5), json!(3), json!(1)]);
+    assert_eq!(values(&doc, "$[5:1:-2]"), [json!(5), json!(3)]);
+    assert!(values(&doc, "$[1:3:0]").is_empty());
+    assert!(values(&doc, "$[9]").is_empty());
+}
+
+#[test]
+fn malformed_queries_report_offsets() {
+    for (expression, offset) in [
+        ("store", 0),
+        ("$.", 2),
+        ("$[", 2),
+        ("$['a'", 5),
+        ("$[?@.a ==]", 9),
+        ("$.a b", 4),
+        ("$[?1]", 4),
+    ] {
+        match expression.parse::<Query>() {
+            Err(ToonError::Query { offset: at, .. }) => assert_eq!(at, offset, "{expression}"),
+            other => panic!("{expression}: expected query error, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! JSON Schema inference tests.
+
+use toon::schema::infer_schema;
+use toon::{JsonValue, decode};
+
+#[test]
+fn infers_table_columns_with_required_and_enum() {
+    let value = decode(
+        "users[4]{id,name,role,score}:\n  1,Ada,admin,9.5\n  2,Bob,user,7\n  3,Cy,user,null\n  4,Di,admin,8",
+        None,
+    );
+    let JsonValue::Object(entries) = value else {
+        panic!("expected object");
+    };
+    let JsonValue::Array(rows) = &entries[0].1 else {
+        panic!("expected array");
+    };
+
+    let schema = serde_json::Value::from(infer_schema(rows));
+    assert_eq!(
+        schema,
+        serde_json::json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer" },
+                "name": { "type": "string" },
+                "role": { "type": "string", "enum": ["admin", "user"] },
+                "score": { "type": ["number", "null"] }
+            },
+            "required": ["id", "name", "role", "score"]
+        })
+    );
+}
+
+#[test]
+fn infers_optional_fields_and_nested_arrays() {
+    let samples = [
+        decode("name: a\ntags[2]: x,y", None),
+        decode("name: b\nextra:\n  on: true", None),
+    ];
+    let schema = serde_json::Value::from(infer_schema(&samples));
+    assert_eq!(schema["required"], serde_json::json!(["name"]));
+    assert_eq!(
+        schema["properties"]["tags"],
+        serde_json::json!({ "type": "array", "items": { "type": "string" } })
+    );
+    assert_eq!(
+        schema["properties"]["extra"]["properties"]["on"],
+        serde_json::json!({ "type": "boolean" })
+    );
+}
+
+#[test]
+fn empty_samples_accept_anything() {
+    let schema = serde_json::Value::from(infer_schema(&[]));
+    assert_eq!(
+        schema,
+        serde_json::json!({ "$schema": "https://json-schema.org/draft/2020-12/schema" })
+    );
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn inferred_schema_validates_its_samples() {
+    let samples = [
+        decode("id: 1\nkind: a\nvalues[2]: 1.5,2", None),
+        decode("id: 2\nkind: a\nvalues[0]:", None),
+        decode("id: 3\nkind: b\nvalues[1]: 3", None),
+        decode("id: 4\nkind: b\nvalues[1]: 3", None),
+    ];
+    let schema = infer_schema(&samples);
+    for sample in &samples {
+        assert!(toon::schema::validate(sample, &schema).is_empty());
+    }
+    let outlier = decode("id: 5\nkind: c\nvalues[1]: x", None);
+    assert_eq!(toon::schema::validate(&outlier, &schema).len(), 2);
+}
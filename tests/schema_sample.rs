@@ -0,0 +1,129 @@
+//! Synthetic data generation from schemas.
+
+use serde_json::json;
+use toon::JsonValue;
+use toon::schema::{ToonSchema, sample};
+
+fn schema(json: serde_json::Value) -> JsonValue {
+    JsonValue::from(json)
+}
+
+fn user_schema() -> JsonValue {
+    schema(json!({
+        "type": "object",
+        "properties": {
+            "id": {"type": "integer"},
+            "name": {"type": "string"},
+            "email": {"type": "string", "format": "email"},
+            "age": {"type": "integer", "minimum": 30, "maximum": 40},
+            "role": {"enum": ["admin", "user"]},
+            "score": {"type": "number", "minimum": 0, "maximum": 1},
+            "code": {"type": "string", "minLength": 12, "maxLength": 12},
+        },
+    }))
+}
+
+#[test]
+fn same_seed_gives_same_rows() {
+    let first = sample(&user_schema(), 5, 7).unwrap();
+    assert_eq!(first, sample(&user_schema(), 5, 7).unwrap());
+    assert_ne!(first, sample(&user_schema(), 5, 8).unwrap());
+}
+
+#[test]
+fn rows_respect_keywords_and_encode_as_table() {
+    let value = sample(&user_schema(), 20, 1).unwrap();
+    let JsonValue::Array(rows) = &value else {
+        panic!("expected array");
+    };
+    assert_eq!(rows.len(), 20);
+
+    let rows: Vec<serde_json::Value> = rows.iter().cloned().map(serde_json::Value::from).collect();
+    for (id, row) in (1..).zip(&rows) {
+        assert_eq!(row["id"].as_f64(), Some(f64::from(id)));
+        let age = row["age"].as_f64().unwrap();
+        assert!((30.0..=40.0).contains(&age) && age.fract() == 0.0, "{age}");
+        assert!(["admin", "user"].contains(&row["role"].as_str().unwrap()));
+        assert!((0.0..=1.0).contains(&row["score"].as_f64().unwrap()));
+        assert_eq!(row["code"].as_str().unwrap().chars().count(), 12);
+
+        let name = row["name"].as_str().unwrap().to_lowercase();
+        let email = row["email"].as_str().unwrap();
+        let first = name.split(' ').next().unwrap();
+        assert!(email.starts_with(&format!("{first}.")), "{name} / {email}");
+        assert!(email.ends_with("@example.com"));
+    }
+
+    let encoded = toon::encode(value, None);
+    assert!(encoded.starts_with("[20]{id,name,email,age,role,score,code}:"));
+}
+
+#[test]
+fn samples_from_toon_schema_validate() {
+    let toon_schema: ToonSchema = "\
+id: integer
+nickname: string?
+role: enum(admin|user)
+tags: string[]
+address:
+  city: string
+orders[2]{field,type}:
+  sku,string
+  qty,integer"
+        .parse()
+        .unwrap();
+
+    let JsonValue::Array(values) = sample(&toon_schema.to_json_schema(), 30, 3).unwrap() else {
+        panic!("expected array");
+    };
+    for value in &values {
+        assert_eq!(toon_schema.validate(value), Vec::new(), "{value:?}");
+    }
+}
+
+#[test]
+fn root_array_schema_sets_item_count_and_follows_refs() {
+    let value = sample(
+        &schema(json!({
+            "type": "array",
+            "items": {"$ref": "#/$defs/point"},
+            "$defs": {
+                "point": {
+                    "type": "object",
+                    "properties": {"x": {"const": 0}, "tags": {"type": "array", "minItems": 2, "maxItems": 2, "items": {"type": "boolean"}}},
+                },
+            },
+        })),
+        3,
+        0,
+    )
+    .unwrap();
+
+    let JsonValue::Array(points) = value else {
+        panic!("expected array");
+    };
+    assert_eq!(points.len(), 3);
+    for point in points {
+        let point = serde_json::Value::from(point);
+        assert_eq!(point["x"], json!(0));
+        assert_eq!(point["tags"].as_array().unwrap().len(), 2);
+    }
+}
+
+#[test]
+fn unresolved_ref_is_an_error() {
+    let err = sample(&schema(json!({"$ref": "#/$defs/missing"})), 1, 0).unwrap_err();
+    assert!(err.to_string().contains("#/$defs/missing"), "{err}");
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn samples_validate_against_json_schema() {
+    let user = user_schema();
+    let JsonValue::Array(values) = sample(&user, 50, 11).unwrap() else {
+        panic!("expected array");
+    };
+    for value in &values {
+        assert_eq!(toon::schema::validate(value, &user), Vec::new());
+    }
+}
@@ -0,0 +1,127 @@
+//! JSON Schema validation tests (requires the `schema` feature).
+
+#![cfg(feature = "schema")]
+
+use toon::schema::validate;
+use toon::{JsonValue, decode};
+
+fn json_schema(json: serde_json::Value) -> JsonValue {
+    JsonValue::from(json)
+}
+
+fn user_schema() -> JsonValue {
+    json_schema(serde_json::json!({
+        "type": "object",
+        "required": ["users"],
+        "properties": {
+            "users": {
+                "type": "array",
+                "minItems": 1,
+                "items": { "$ref": "#/$defs/user" }
+            }
+        },
+        "additionalProperties": false,
+        "$defs": {
+            "user": {
+                "type": "object",
+                "required": ["id", "email"],
+                "properties": {
+                    "id": { "type": "integer", "minimum": 1 },
+                    "email": { "type": "string", "pattern": "^[^@]+@[^@]+$" },
+                    "role": { "enum": ["admin", "user"] }
+                }
+            }
+        }
+    }))
+}
+
+#[test]
+fn conforming_document_has_no_violations() {
+    let value = decode(
+        "users[2]{id,email,role}:\n  1,ada@example.com,admin\n  2,bob@example.com,user",
+        None,
+    );
+    assert!(validate(&value, &user_schema()).is_empty());
+}
+
+#[test]
+fn violations_are_path_addressed() {
+    let value = decode(
+        "users[2]{id,email,role}:\n  0,ada@example.com,owner\n  2.5,bob,user\nextra: true",
+        None,
+    );
+    let violations = validate(&value, &user_schema());
+    let found: Vec<(String, String)> = violations
+        .iter()
+        .map(|violation| (violation.path.clone(), violation.keyword.clone()))
+        .collect();
+    let expected = [
+        ("/users/0/id", "minimum"),
+        ("/users/0/role", "enum"),
+        ("/users/1/id", "type"),
+        ("/users/1/email", "pattern"),
+        ("/extra", "additionalProperties"),
+    ];
+    assert_eq!(
+        found,
+        expected
+            .iter()
+            .map(|(path, keyword)| ((*path).to_string(), (*keyword).to_string()))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        violations[2].to_string(),
+        "/users/1/id: expected integer, found number"
+    );
+}
+
+#[test]
+fn required_and_root_violations() {
+    let violations = validate(&decode("other: 1", None), &user_schema());
+    assert!(
+        violations
+            .iter()
+            .any(|violation| violation.keyword == "required" && violation.path.is_empty())
+    );
+    assert!(violations[0].to_string().starts_with("(root): "));
+}
+
+#[test]
+fn combinators_and_array_keywords() {
+    let schema = json_schema(serde_json::json!({
+        "type": "array",
+        "prefixItems": [{ "type": "string" }],
+        "items": { "oneOf": [{ "type": "integer" }, { "minimum": 10 }] },
+        "uniqueItems": true,
+        "maxItems": 4
+    }));
+
+    assert!(validate(&decode("[3]: a,1,12.5", None), &schema).is_empty());
+
+    let violations = validate(&decode("[5]: 1,12,1,3.5,x", None), &schema);
+    let keywords: Vec<&str> = violations
+        .iter()
+        .map(|violation| violation.keyword.as_str())
+        .collect();
+    assert_eq!(
+        keywords,
+        ["maxItems", "uniqueItems", "type", "oneOf", "oneOf"]
+    );
+    assert_eq!(violations[3].path, "/1");
+
+    let not_null = json_schema(serde_json::json!({ "not": { "type": "null" } }));
+    assert_eq!(validate(&decode("null", None), &not_null).len(), 1);
+    assert!(validate(&decode("x", None), &JsonValue::from(true)).is_empty());
+    assert_eq!(
+        validate(&decode("x", None), &JsonValue::from(false)).len(),
+        1
+    );
+}
+
+#[test]
+fn cyclic_reference_is_reported() {
+    let schema = json_schema(serde_json::json!({ "$ref": "#" }));
+    let violations = validate(&decode("a: 1", None), &schema);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].keyword, "$ref");
+}
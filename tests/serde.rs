@@ -0,0 +1,185 @@
+//! Encoding Rust types through `toon::to_string` and `toon::to_value`, and
+//! decoding them back through `toon::from_str` and `toon::from_value`.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use toon::options::EncodeOptions;
+use toon::{JsonValue, Number, StringOrNumberOrBoolOrNull, encode};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Order {
+    id: u64,
+    customer: Option<String>,
+    status: Status,
+    lines: Vec<Line>,
+    notes: Vec<Note>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Line {
+    sku: String,
+    qty: u32,
+    price: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Shipped,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Note {
+    Text(String),
+    Flag { urgent: bool },
+    Pair(i32, i32),
+}
+
+fn order() -> Order {
+    Order {
+        id: 42,
+        customer: None,
+        status: Status::Shipped,
+        lines: vec![
+            Line {
+                sku: "A1".to_string(),
+                qty: 2,
+                price: 9.5,
+            },
+            Line {
+                sku: "B2".to_string(),
+                qty: 1,
+                price: 20.0,
+            },
+        ],
+        notes: vec![
+            Note::Text("fragile".to_string()),
+            Note::Flag { urgent: true },
+            Note::Pair(1, -2),
+        ],
+    }
+}
+
+#[test]
+fn to_string_matches_the_serde_json_route() {
+    let via_json = encode(serde_json::to_value(order()).unwrap(), None);
+    assert_eq!(toon::to_string(&order(), None).unwrap(), via_json);
+    assert_eq!(
+        via_json,
+        "id: 42\ncustomer: null\nstatus: shipped\nlines[2]{sku,qty,price}:\n  A1,2,9.5\n  B2,1,20\nnotes[3]:\n  - Text: fragile\n  - Flag:\n      urgent: true\n  - Pair[2]: 1,-2"
+    );
+}
+
+#[test]
+fn to_string_applies_encode_options() {
+    let options = EncodeOptions::builder().delimiter('|').build();
+    let toon = toon::to_string(&vec![("a", 1), ("b", 2)], Some(options)).unwrap();
+    assert_eq!(toon, "[2|]:\n  - [2|]: a|1\n  - [2|]: b|2");
+}
+
+#[test]
+fn map_keys_become_strings() {
+    let map = BTreeMap::from([(1, "one"), (2, "two")]);
+    assert_eq!(
+        toon::to_string(&map, None).unwrap(),
+        "\"1\": one\n\"2\": two"
+    );
+
+    let bad = BTreeMap::from([((1, 2), "pair")]);
+    assert!(toon::to_value(&bad).is_err());
+}
+
+#[test]
+fn to_value_keeps_non_finite_numbers_for_the_encoder() {
+    assert!(matches!(
+        toon::to_value(&f64::NAN).unwrap(),
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(Number::F64(n))) if n.is_nan()
+    ));
+    assert!(toon::to_string(&f64::INFINITY, None).is_err());
+    assert_eq!(
+        toon::to_value(&[1u8, 2]).unwrap(),
+        JsonValue::from(vec![JsonValue::from(1.0), JsonValue::from(2.0)])
+    );
+}
+
+#[test]
+fn from_str_round_trips_through_to_string() {
+    let toon = toon::to_string(&order(), None).unwrap();
+    assert_eq!(toon::from_str::<Order>(&toon).unwrap(), order());
+}
+
+#[test]
+fn from_str_parses_map_keys_and_enum_shapes() {
+    let map: BTreeMap<u32, bool> = toon::from_str("\"1\": true\n\"2\": false").unwrap();
+    assert_eq!(map, BTreeMap::from([(1, true), (2, false)]));
+
+    let notes: Vec<Note> = toon::from_str("[2]:\n  - Pair[2]: 3,4\n  - Text: hi").unwrap();
+    assert_eq!(notes, vec![Note::Pair(3, 4), Note::Text("hi".to_string())]);
+}
+
+#[test]
+fn from_str_reports_type_mismatches() {
+    let err = toon::from_str::<Line>("sku: A1\nqty: -1\nprice: 2").unwrap_err();
+    assert!(err.to_string().contains("invalid value"), "{err}");
+
+    assert!(toon::from_str::<u8>("1.5").is_err());
+    assert!(toon::from_str::<Status>("pending").is_err());
+    assert!(toon::from_str::<(u8, u8)>("[3]: 1,2,3").is_err());
+    assert!(toon::from_str::<Order>("id: [").is_err());
+}
+
+#[test]
+fn from_value_keeps_large_numbers_as_floats() {
+    let value = toon::to_value(&1e300).unwrap();
+    assert_eq!(
+        toon::from_value::<f64>(value).unwrap().to_bits(),
+        1e300f64.to_bits()
+    );
+    assert!(toon::from_value::<u64>(toon::to_value(&1e300).unwrap()).is_err());
+    assert_eq!(toon::from_str::<Option<i64>>("null").unwrap(), None);
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Envelope {
+    kind: String,
+    payload: JsonValue,
+}
+
+#[test]
+fn json_value_embeds_in_serde_types() {
+    let payload = toon::decode("b: 1\na[2]: x,null\nb: 2.5", None);
+    let envelope = Envelope {
+        kind: "event".to_string(),
+        payload: payload.clone(),
+    };
+
+    assert_eq!(toon::to_value(&payload).unwrap(), payload);
+    assert_eq!(
+        toon::from_value::<JsonValue>(payload.clone()).unwrap(),
+        payload
+    );
+
+    let toon = toon::to_string(&envelope, None).unwrap();
+    assert_eq!(
+        toon,
+        "kind: event\npayload:\n  b: 1\n  a[2]: x,null\n  b: 2.5"
+    );
+    assert_eq!(toon::from_str::<Envelope>(&toon).unwrap(), envelope);
+
+    let json = serde_json::to_string(&envelope.payload).unwrap();
+    assert_eq!(json, r#"{"b":1,"a":["x",null],"b":2.5}"#);
+    let back: JsonValue = serde_json::from_str(r#"{"b":1,"a":["x",null]}"#).unwrap();
+    assert_eq!(back, toon::decode("b: 1\na[2]: x,null", None));
+}
+
+#[test]
+fn json_value_keeps_wide_integers() {
+    let value = toon::to_value(&u128::from(u64::MAX)).unwrap();
+    assert_eq!(
+        value,
+        JsonValue::Primitive(StringOrNumberOrBoolOrNull::Number(Number::U64(u64::MAX)))
+    );
+    let value: JsonValue = serde_json::from_str("-9223372036854775808").unwrap();
+    assert_eq!(value, JsonValue::from(i64::MIN));
+}
@@ -0,0 +1,163 @@
+//! Granular strictness checks and the duplicate-key policy.
+
+use toon::options::{DecodeOptions, DuplicateKeyPolicy, Strictness};
+use toon::{JsonValue, try_decode};
+
+// ============================================================================
+// STRICTNESS
+// ============================================================================
+
+fn with_strictness(strictness: Strictness) -> DecodeOptions {
+    DecodeOptions::builder().strictness(strictness).build()
+}
+
+#[test]
+fn strictness_keeps_exact_indentation_with_lenient_lengths() {
+    let mut strictness = Strictness::strict();
+    strictness.length_markers = false;
+    let decoded = try_decode(
+        "items[3]: a,b\nrows[3]{id}:\n  1\n  2",
+        Some(with_strictness(strictness)),
+    )
+    .unwrap();
+    let json: serde_json::Value = decoded.into();
+    assert_eq!(
+        json,
+        serde_json::json!({"items": ["a", "b"], "rows": [{"id": 1}, {"id": 2}]})
+    );
+
+    let err = try_decode("outer:\n   inner: 1", Some(with_strictness(strictness))).unwrap_err();
+    assert!(err.to_string().contains("Line 2"), "{err}");
+}
+
+#[test]
+fn strictness_checks_toggle_independently() {
+    type Relax = fn(&mut Strictness);
+    let cases: [(&str, Relax); 6] = [
+        ("outer:\n   inner: 1", |s| s.indentation = false),
+        ("outer:\n\tinner: 1", |s| s.tabs = false),
+        ("items[3]: a,b", |s| s.length_markers = false),
+        ("a: 1\nb: 2\na: 3", |s| s.duplicate_keys = false),
+        ("[1]:\n  - a\n  - b", |s| s.trailing_content = false),
+        ("[2]:\n  - a\n\n  - b", |s| s.blank_lines = false),
+    ];
+    for (input, relax) in cases {
+        assert!(
+            try_decode(input, Some(with_strictness(Strictness::strict()))).is_err(),
+            "input: {input:?}"
+        );
+        let mut strictness = Strictness::strict();
+        relax(&mut strictness);
+        assert!(
+            try_decode(input, Some(with_strictness(strictness))).is_ok(),
+            "input: {input:?}"
+        );
+        assert!(
+            try_decode(input, Some(with_strictness(Strictness::lenient()))).is_ok(),
+            "input: {input:?}"
+        );
+    }
+}
+
+#[test]
+fn strictness_default_is_strict_but_allows_duplicate_keys() {
+    let mut expected = Strictness::strict();
+    expected.duplicate_keys = false;
+    assert_eq!(Strictness::default(), expected);
+    assert!(try_decode("a: 1\na: 2", None).is_ok());
+    assert!(try_decode("items[3]: a,b", None).is_err());
+}
+
+// ============================================================================
+// DUPLICATE KEY POLICY
+// ============================================================================
+
+fn decode_duplicates(input: &str, policy: Option<DuplicateKeyPolicy>) -> serde_json::Value {
+    let mut options = DecodeOptions::default();
+    options.duplicate_keys = policy;
+    try_decode(input, Some(options)).unwrap().into()
+}
+
+#[test]
+fn duplicate_key_policies_resolve_repeated_keys() {
+    let input = "a: 1\nb:\n  x: 1\n  x: 2\na: 3\nrows[2]{id}:\n  1\n  2\na: 5";
+    assert_eq!(
+        decode_duplicates(input, Some(DuplicateKeyPolicy::FirstWins)),
+        serde_json::json!({"a": 1, "b": {"x": 1}, "rows": [{"id": 1}, {"id": 2}]})
+    );
+    let last_wins = toon::decode(
+        input,
+        Some(
+            DecodeOptions::builder()
+                .duplicate_keys(DuplicateKeyPolicy::LastWins)
+                .build(),
+        ),
+    );
+    let JsonValue::Object(entries) = &last_wins else {
+        panic!("not an object: {last_wins:?}");
+    };
+    let keys: Vec<_> = entries.iter().map(|(key, _)| key.as_str()).collect();
+    assert_eq!(keys, ["a", "b", "rows"]);
+    assert_eq!(
+        serde_json::Value::from(last_wins),
+        serde_json::json!({"a": 5, "b": {"x": 2}, "rows": [{"id": 1}, {"id": 2}]})
+    );
+    assert_eq!(
+        decode_duplicates(input, Some(DuplicateKeyPolicy::CollectIntoArray)),
+        serde_json::json!({"a": [1, 3, 5], "b": {"x": [1, 2]}, "rows": [{"id": 1}, {"id": 2}]})
+    );
+    // Items of a collected array are resolved too.
+    assert_eq!(
+        decode_duplicates(
+            "a:\n  k: 1\n  k: 2\na:\n  k: 3",
+            Some(DuplicateKeyPolicy::CollectIntoArray)
+        ),
+        serde_json::json!({"a": [{"k": [1, 2]}, {"k": 3}]})
+    );
+    // A key that is not repeated keeps its value, even an array.
+    assert_eq!(
+        decode_duplicates("a[2]: 1,2", Some(DuplicateKeyPolicy::CollectIntoArray)),
+        serde_json::json!({"a": [1, 2]})
+    );
+}
+
+#[test]
+fn duplicate_key_policy_overrides_strictness() {
+    let err = try_decode(
+        "a: 1\nb: 2\na: 3",
+        Some(
+            DecodeOptions::builder()
+                .strictness(Strictness::lenient())
+                .duplicate_keys(DuplicateKeyPolicy::Error)
+                .build(),
+        ),
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("duplicate key `a` (first defined at line 1)"),
+        "{err}"
+    );
+
+    let options = DecodeOptions::builder()
+        .strictness(Strictness::strict())
+        .duplicate_keys(DuplicateKeyPolicy::FirstWins)
+        .build();
+    let value = toon::decode_to_serde("a: 1\na: 2", Some(options)).unwrap();
+    assert_eq!(value, serde_json::json!({"a": 1}));
+
+    // Strictness only picks the default policy.
+    assert!(try_decode("a: 1\na: 2", Some(DecodeOptions::strict())).is_err());
+    let options = DecodeOptions::builder()
+        .strictness(Strictness::strict())
+        .duplicate_keys(DuplicateKeyPolicy::LastWins)
+        .build();
+    assert_eq!(
+        toon::decode("a: 1\na: 2", Some(options)),
+        toon::toon_value!({"a": 2})
+    );
+
+    // Without a policy, every entry is kept.
+    let value = toon::decode("a: 1\na: 2", None);
+    assert!(matches!(value, JsonValue::Object(entries) if entries.len() == 2));
+}
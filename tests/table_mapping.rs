@@ -0,0 +1,354 @@
+//! Typed table mapping, header verification and row appending tests.
+
+use serde::Deserialize;
+use std::io::Cursor;
+use toon::error::ToonError;
+
+use toon::options::{DecodeOptions, EncodeOptions, Strictness};
+use toon::{
+    JsonValue, TableAppender, TableSpec, TabularWriter, append_rows, decode, decode_table, encode,
+    try_decode,
+};
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct User {
+    id: u64,
+    name: String,
+    email: String,
+}
+
+const USERS: &str = "\
+title: Staff
+users[2]{id,name,email}:
+  1,Ada,ada@example.com
+  2,Bob,bob@example.com";
+
+#[test]
+fn decodes_rows_into_structs() {
+    let users: Vec<User> = decode_table(USERS, "users", None).unwrap();
+    assert_eq!(
+        users,
+        vec![
+            User {
+                id: 1,
+                name: "Ada".to_string(),
+                email: "ada@example.com".to_string(),
+            },
+            User {
+                id: 2,
+                name: "Bob".to_string(),
+                email: "bob@example.com".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn decodes_root_table() {
+    let users: Vec<User> =
+        decode_table("[1]{id,name,email}:\n  7,Cy,cy@example.com", "", None).unwrap();
+    assert_eq!(users[0].id, 7);
+}
+
+#[test]
+fn reports_missing_column_with_header_line() {
+    let input = "title: Staff\n\nusers[1]{id,name}:\n  1,Ada";
+    let err = decode_table::<User>(input, "users", None).unwrap_err();
+    assert!(
+        matches!(err, ToonError::Validation { line: 3, .. }),
+        "{err:?}"
+    );
+    assert_eq!(
+        err.to_string(),
+        "Validation error at line 3: column `email` missing"
+    );
+}
+
+#[test]
+fn reports_reordered_and_extra_columns() {
+    let reordered = "users[1]{id,email,name}:\n  1,ada@example.com,Ada";
+    let err = decode_table::<User>(reordered, "users", None).unwrap_err();
+    assert!(
+        err.to_string()
+            .ends_with("column `email` at position 2, expected `name`"),
+        "{err}"
+    );
+
+    let extra = "users[1]{id,name,email,age}:\n  1,Ada,ada@example.com,36";
+    let err = decode_table::<User>(extra, "users", None).unwrap_err();
+    assert!(
+        err.to_string().ends_with("unexpected column `age`"),
+        "{err}"
+    );
+}
+
+#[test]
+fn relaxed_spec_accepts_reordered_and_extra_columns() {
+    let input = "users[1]{email,age,id,name}:\n  ada@example.com,36,1,Ada";
+    let spec = TableSpec::of::<User>()
+        .unwrap()
+        .allow_reordered()
+        .allow_extra();
+    let users: Vec<User> = spec.decode(input, "users", None).unwrap();
+    assert_eq!(users[0].name, "Ada");
+}
+
+#[test]
+fn reports_row_conversion_errors_with_row_line() {
+    let input = "users[2]{id,name,email}:\n  1,Ada,ada@example.com\n  two,Bob,bob@example.com";
+    let err = decode_table::<User>(input, "users", None).unwrap_err();
+    assert!(matches!(err, ToonError::Parse { line: 3, .. }), "{err:?}");
+    assert!(err.to_string().starts_with("Line 3: row 2:"), "{err}");
+}
+
+#[test]
+fn explicit_spec_verifies_headers() {
+    let spec = TableSpec::new(["sku", "qty"]);
+    assert_eq!(spec.columns(), ["sku", "qty"]);
+    assert!(spec.verify(&["sku", "qty"], 1).is_ok());
+    assert!(spec.verify(&["sku"], 1).is_err());
+    assert!(spec.verify(&["qty", "sku"], 1).is_err());
+}
+
+#[test]
+fn rejects_missing_and_non_tabular_tables() {
+    let err = decode_table::<User>(USERS, "admins", None).unwrap_err();
+    assert_eq!(err.to_string(), "table `admins` not found");
+
+    let err = decode_table::<User>("users[2]: a,b", "users", None).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Validation error at line 1: `users` is not a table"
+    );
+
+    assert!(TableSpec::of::<Vec<User>>().is_err());
+}
+
+fn row(cells: Vec<(&str, JsonValue)>) -> JsonValue {
+    JsonValue::Object(
+        cells
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect(),
+    )
+}
+
+#[test]
+fn appends_rows_and_updates_length_marker() {
+    let document = "title: Log\nevents[1]{at,kind}:\n  1,start\nowner: ops\n";
+    let updated = append_rows(
+        document,
+        "events",
+        &[
+            row(vec![
+                ("at", JsonValue::from(2.0)),
+                ("kind", JsonValue::from("tick")),
+            ]),
+            row(vec![
+                ("kind", JsonValue::from("a,b")),
+                ("at", JsonValue::from(3.0)),
+            ]),
+        ],
+    )
+    .unwrap();
+    assert_eq!(
+        updated,
+        "title: Log\nevents[3]{at,kind}:\n  1,start\n  2,tick\n  3,\"a,b\"\nowner: ops\n"
+    );
+    assert_eq!(
+        serde_json::Value::from(decode(&updated, None)),
+        serde_json::json!({
+            "title": "Log",
+            "events": [
+                {"at": 1, "kind": "start"},
+                {"at": 2, "kind": "tick"},
+                {"at": 3, "kind": "a,b"}
+            ],
+            "owner": "ops"
+        })
+    );
+}
+
+#[test]
+fn appends_to_empty_array_and_alternate_delimiter() {
+    let document = encode(serde_json::json!({"rows": []}), None);
+    let mut appender = TableAppender::open(&document, "rows").unwrap();
+    assert!(appender.is_empty());
+    appender
+        .append(&row(vec![
+            ("id", JsonValue::from(1.0)),
+            ("ok", JsonValue::from(true)),
+        ]))
+        .unwrap();
+    assert_eq!(appender.len(), 1);
+    assert_eq!(appender.to_string(), "rows[1]{id,ok}:\n  1,true");
+
+    let piped = append_rows(
+        "[1|]{a|b}:\n    x|y",
+        "",
+        &[row(vec![
+            ("a", JsonValue::from("p|q")),
+            ("b", JsonValue::from("z")),
+        ])],
+    )
+    .unwrap();
+    assert_eq!(piped, "[2|]{a|b}:\n    x|y\n    \"p|q\"|z");
+}
+
+#[test]
+fn rejects_rows_that_do_not_fit_the_table() {
+    let document = "events[1]{at,kind}:\n  1,start";
+    let mut appender = TableAppender::open(document, "events").unwrap();
+
+    let err = appender
+        .append(&row(vec![("at", JsonValue::from(2.0))]))
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid value at 'events[1]': column `kind` missing"
+    );
+
+    let err = appender
+        .append(&row(vec![
+            ("at", JsonValue::from(2.0)),
+            ("kind", JsonValue::Array(Vec::new())),
+        ]))
+        .unwrap_err();
+    assert!(err.to_string().contains("'events[1].kind'"), "{err}");
+
+    assert!(appender.append(&JsonValue::from("row")).is_err());
+    assert_eq!(appender.to_string(), document);
+
+    assert!(TableAppender::open("events[2]: a,b", "events").is_err());
+    assert!(TableAppender::open(document, "missing").is_err());
+}
+
+fn cells(id: f64, name: &str) -> [JsonValue; 2] {
+    [JsonValue::from(id), JsonValue::from(name)]
+}
+
+#[test]
+fn tabular_writer_fills_in_the_length_on_finish() {
+    let options = EncodeOptions::builder().delimiter('|').build();
+    let mut out = Cursor::new(b"meta: 1\n".to_vec());
+    out.set_position(8);
+    let mut table =
+        TabularWriter::with_length_fixup(out, "rows", ["id", "name"], Some(options)).unwrap();
+    table.row(cells(1.0, "a|b")).unwrap();
+    table.row(cells(2.0, "c")).unwrap();
+    assert_eq!(table.len(), 2);
+
+    let mut out = table.finish().unwrap();
+    std::io::Write::write_all(&mut out, b"\ndone: true").unwrap();
+    let text = String::from_utf8(out.into_inner()).unwrap();
+    assert_eq!(
+        text,
+        "meta: 1\nrows[0000000002|]{id|name}:\n  1|\"a|b\"\n  2|c\ndone: true"
+    );
+    let value = decode(&text, None);
+    assert_eq!(
+        value,
+        decode(
+            "meta: 1\nrows[2|]{id|name}:\n  1|\"a|b\"\n  2|c\ndone: true",
+            None
+        )
+    );
+}
+
+#[test]
+fn tabular_writer_without_length_decodes_leniently() {
+    let mut table = TabularWriter::new(Vec::new(), "", ["id", "name"], None).unwrap();
+    for id in 1..=3 {
+        table.row(cells(f64::from(id), "x")).unwrap();
+    }
+    let text = String::from_utf8(table.finish().unwrap()).unwrap();
+    assert_eq!(text, "[]{id,name}:\n  1,x\n  2,x\n  3,x");
+
+    assert!(try_decode(&text, None).is_err());
+    let mut strictness = Strictness::default();
+    strictness.length_markers = false;
+    let lenient = DecodeOptions::builder().strictness(strictness).build();
+    let value = try_decode(&text, Some(lenient)).unwrap();
+    let JsonValue::Array(rows) = value else {
+        panic!("expected an array, got {value:?}");
+    };
+    assert_eq!(rows.len(), 3);
+
+    let empty = TabularWriter::with_length_fixup(Cursor::new(Vec::new()), "rows", ["id"], None)
+        .unwrap()
+        .finish()
+        .unwrap();
+    let text = String::from_utf8(empty.into_inner()).unwrap();
+    assert_eq!(decode(&text, None), decode("rows[0]:", None));
+}
+
+#[test]
+fn tabular_writer_rejects_bad_rows() {
+    assert!(TabularWriter::new(Vec::new(), "rows", Vec::<String>::new(), None).is_err());
+    assert!(TabularWriter::new(Vec::new(), "rows", ["id", "id"], None).is_err());
+
+    let mut table = TabularWriter::new(Vec::new(), "rows", ["id", "name"], None).unwrap();
+    let err = table.row([JsonValue::from(1.0)]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "row 1 of table `rows` has 1 cells, expected 2"
+    );
+    let err = table
+        .row([JsonValue::from(1.0), JsonValue::Array(Vec::new())])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "row 1 of table `rows`: cells must be primitives"
+    );
+    assert!(table.is_empty());
+    assert_eq!(table.finish().unwrap(), b"rows[]{id,name}:");
+}
+
+#[test]
+fn tabular_writer_replacer_sees_the_full_path() {
+    use std::sync::{Arc, Mutex};
+    use toon::options::PathSegment;
+
+    let paths = Arc::new(Mutex::new(Vec::new()));
+    let seen = paths.clone();
+    let options = EncodeOptions::builder()
+        .replacer(Arc::new(
+            move |_key: &str, value: &JsonValue, path: &[PathSegment]| {
+                seen.lock().unwrap().push(path.to_vec());
+                match path {
+                    [PathSegment::Key(key), PathSegment::Index(1)] if key == "rows" => None,
+                    [_, _, PathSegment::Key(field)] if field == "name" => {
+                        Some(JsonValue::from("***"))
+                    }
+                    _ => Some(value.clone()),
+                }
+            },
+        ))
+        .build();
+    let mut table = TabularWriter::new(Vec::new(), "rows", ["id", "name"], Some(options)).unwrap();
+    for id in 1..=3 {
+        table.row(cells(f64::from(id), "x")).unwrap();
+    }
+    assert_eq!(table.len(), 2);
+    let text = String::from_utf8(table.finish().unwrap()).unwrap();
+    assert_eq!(text, "rows[]{id,name}:\n  1,***\n  3,***");
+
+    let row = |index| vec![PathSegment::Key("rows".into()), PathSegment::Index(index)];
+    let cell = |index, field: &str| {
+        let mut path = row(index);
+        path.push(PathSegment::Key(field.into()));
+        path
+    };
+    assert_eq!(
+        *paths.lock().unwrap(),
+        [
+            row(0),
+            cell(0, "id"),
+            cell(0, "name"),
+            row(1),
+            row(2),
+            cell(2, "id"),
+            cell(2, "name"),
+        ]
+    );
+}
@@ -0,0 +1,129 @@
+//! Native TOON schema parsing and validation tests.
+
+use toon::schema::{SchemaType, ToonSchema};
+use toon::{JsonValue, decode};
+
+const USER_SCHEMA: &str = "\
+id: integer
+email: string
+nickname: string?
+role: enum(admin|user)
+tags: string[]
+address:
+  city: string
+  \"zip?\": string
+orders[2]{field,type}:
+  sku,string
+  qty,integer";
+
+fn user_schema() -> ToonSchema {
+    USER_SCHEMA.parse().expect("schema parses")
+}
+
+fn violations(schema: &ToonSchema, data: &str) -> Vec<(String, String)> {
+    schema
+        .validate(&decode(data, None))
+        .into_iter()
+        .map(|violation| (violation.path, violation.keyword))
+        .collect()
+}
+
+#[test]
+fn parses_types_arrays_and_shapes() {
+    let ToonSchema::Object(fields) = user_schema() else {
+        panic!("expected object schema");
+    };
+    let names: Vec<(&str, bool)> = fields
+        .iter()
+        .map(|(name, _, optional)| (name.as_str(), *optional))
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            ("id", false),
+            ("email", false),
+            ("nickname", true),
+            ("role", false),
+            ("tags", false),
+            ("address", false),
+            ("orders", false),
+        ]
+    );
+    assert_eq!(fields[0].1, ToonSchema::Type(SchemaType::Integer));
+    assert_eq!(
+        fields[3].1,
+        ToonSchema::Enum(vec!["admin".to_string(), "user".to_string()])
+    );
+    assert_eq!(
+        fields[4].1,
+        ToonSchema::Array(Box::new(ToonSchema::Type(SchemaType::String)))
+    );
+    assert_eq!(
+        fields[6].1,
+        ToonSchema::Array(Box::new(ToonSchema::Object(vec![
+            (
+                "sku".to_string(),
+                ToonSchema::Type(SchemaType::String),
+                false
+            ),
+            (
+                "qty".to_string(),
+                ToonSchema::Type(SchemaType::Integer),
+                false
+            ),
+        ])))
+    );
+}
+
+#[test]
+fn accepts_conforming_document() {
+    let data = "id: 7\nemail: ada@example.com\nrole: admin\ntags[2]: a,b\naddress:\n  city: London\norders[2]{sku,qty}:\n  A1,2\n  B2,1";
+    assert!(violations(&user_schema(), data).is_empty());
+}
+
+#[test]
+fn reports_each_violation_with_pointer_path() {
+    let data = "id: 7.5\nnickname: null\nrole: guest\ntags[1]: 3\naddress:\n  city: Paris\n  zip: 75\norders[1]{sku,qty,note}:\n  A1,two,x";
+    assert_eq!(
+        violations(&user_schema(), data),
+        vec![
+            (String::from("/id"), String::from("type")),
+            (String::new(), String::from("required")),
+            (String::from("/role"), String::from("enum")),
+            (String::from("/tags/0"), String::from("type")),
+            (String::from("/address/zip"), String::from("type")),
+            (String::from("/orders/0/qty"), String::from("type")),
+            (
+                String::from("/orders/0/note"),
+                String::from("additionalProperties")
+            ),
+        ]
+    );
+}
+
+#[test]
+fn rejects_unknown_types_naming_the_field() {
+    let err = "address:\n  city: text".parse::<ToonSchema>().unwrap_err();
+    assert!(err.to_string().contains("`address.city`"), "{err}");
+    assert!("count: 3".parse::<ToonSchema>().is_err());
+    assert!("[2]: a,b".parse::<ToonSchema>().is_err());
+}
+
+#[test]
+fn converts_to_json_schema() {
+    let schema: ToonSchema = "id: integer\nnote: string?".parse().unwrap();
+    assert_eq!(
+        serde_json::Value::from(schema.to_json_schema()),
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer" },
+                "note": { "anyOf": [{ "type": "string" }, { "type": "null" }] }
+            },
+            "required": ["id"],
+            "additionalProperties": false
+        })
+    );
+    let any = ToonSchema::Type(SchemaType::Any);
+    assert_eq!(any.to_json_schema(), JsonValue::Object(Vec::new()));
+}